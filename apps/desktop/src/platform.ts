@@ -49,7 +49,9 @@ export const platform = {
 		constructServerUrl(
 			`/thumbnail/${encodeURIComponent(
 				thumbKey.base_directory_str
-			)}/${encodeURIComponent(thumbKey.shard_hex)}/${encodeURIComponent(thumbKey.cas_id)}.webp`
+			)}/${encodeURIComponent(thumbKey.shard_hex)}/${encodeURIComponent(thumbKey.cas_id)}.webp${
+				thumbKey.size && thumbKey.size !== 'Medium' ? `?size=${thumbKey.size.toLowerCase()}` : ''
+			}`
 		),
 	getFileUrl: (libraryId, locationLocalId, filePathId) =>
 		constructServerUrl(`/file/${libraryId}/${locationLocalId}/${filePathId}`),
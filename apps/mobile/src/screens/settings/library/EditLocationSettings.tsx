@@ -54,6 +54,8 @@ const EditLocationSettingsScreen = ({
 			sync_preview_media: data.syncPreviewMedia,
 			generate_preview_media: data.generatePreviewMedia,
 			hidden: data.hidden,
+			symlink_policy: null,
+			indexed_xattrs: null,
 			indexer_rules_ids: []
 		})
 	);
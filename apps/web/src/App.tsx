@@ -45,7 +45,9 @@ const platform: Platform = {
 	getThumbnailUrlByThumbKey: (thumbKey) =>
 		`${spacedriveURL}/thumbnail/${encodeURIComponent(
 			thumbKey.base_directory_str
-		)}/${encodeURIComponent(thumbKey.shard_hex)}/${encodeURIComponent(thumbKey.cas_id)}.webp`,
+		)}/${encodeURIComponent(thumbKey.shard_hex)}/${encodeURIComponent(thumbKey.cas_id)}.webp${
+			thumbKey.size && thumbKey.size !== 'Medium' ? `?size=${thumbKey.size.toLowerCase()}` : ''
+		}`,
 	getFileUrl: (libraryId, locationLocalId, filePathId) =>
 		`${spacedriveURL}/file/${encodeURIComponent(libraryId)}/${encodeURIComponent(
 			locationLocalId
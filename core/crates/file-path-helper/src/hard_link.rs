@@ -0,0 +1,310 @@
+use sd_prisma::prisma::{file_path, location, PrismaClient};
+use sd_utils::db::{inode_from_db, size_in_bytes_from_db};
+
+use std::{
+	collections::{HashMap, HashSet},
+	fs::Metadata,
+};
+
+use prisma_client_rust::QueryError;
+use serde::{Deserialize, Serialize};
+
+/// Identifies an inode across devices, as every hard link to a file shares both
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct InodeKey {
+	pub device: u64,
+	pub inode: u64,
+}
+
+impl InodeKey {
+	/// The key of a file with other hard links to it, `None` if `nlink` says it has no other links
+	#[must_use]
+	pub const fn for_hard_link(device: u64, inode: u64, nlink: u64) -> Option<Self> {
+		if nlink > 1 {
+			Some(Self { device, inode })
+		} else {
+			None
+		}
+	}
+
+	/// Same as [`InodeKey::for_hard_link`] but with data fetched from database
+	#[must_use]
+	pub fn from_db(
+		device: Option<&[u8]>,
+		inode: Option<&[u8]>,
+		nlink: Option<i32>,
+	) -> Option<Self> {
+		match (device, inode, nlink) {
+			(Some(device), Some(inode), Some(nlink)) if device.len() >= 8 && inode.len() >= 8 => {
+				Self::for_hard_link(
+					inode_from_db(&device[0..8]),
+					inode_from_db(&inode[0..8]),
+					u64::try_from(nlink).unwrap_or_default(),
+				)
+			}
+			_ => None,
+		}
+	}
+}
+
+/// Device id and number of hard links of a file path.
+///
+/// Directories always have a single link here, as their link count only reflects how many
+/// subdirectories they have.
+#[must_use]
+pub fn device_and_nlink(metadata: &Metadata) -> (u64, u64) {
+	#[cfg(target_family = "unix")]
+	{
+		use std::os::unix::fs::MetadataExt;
+
+		let nlink = if metadata.is_dir() {
+			1
+		} else {
+			metadata.nlink()
+		};
+
+		(metadata.dev(), nlink)
+	}
+
+	#[cfg(not(target_family = "unix"))]
+	{
+		// TODO: Use `MetadataExt::volume_serial_number` and `MetadataExt::number_of_links`
+		// when they get stabilized
+		let _ = metadata; // just to avoid warnings on Windows
+		(0, 1)
+	}
+}
+
+/// Constructs a database representation of the number of hard links, saturating as we only have
+/// signed integers in the database and nobody has that many links to a single file anyway
+#[must_use]
+pub fn nlink_to_db(nlink: u64) -> i32 {
+	i32::try_from(nlink).unwrap_or(i32::MAX)
+}
+
+/// Sum of sizes where every file with hard links is only counted once, no matter how many of its
+/// links are added
+#[derive(Debug, Default)]
+pub struct UniqueInodesSize {
+	total: u64,
+	seen_hard_links: HashSet<InodeKey>,
+}
+
+impl UniqueInodesSize {
+	/// Adds `size` to the total, unless `hard_link` was already counted
+	pub fn add(&mut self, hard_link: Option<InodeKey>, size: u64) {
+		let is_first_link = match hard_link {
+			Some(key) => self.seen_hard_links.insert(key),
+			None => true,
+		};
+
+		if is_first_link {
+			self.total += size;
+		}
+	}
+
+	#[must_use]
+	pub const fn total(&self) -> u64 {
+		self.total
+	}
+}
+
+impl FromIterator<(Option<InodeKey>, u64)> for UniqueInodesSize {
+	fn from_iter<T: IntoIterator<Item = (Option<InodeKey>, u64)>>(iter: T) -> Self {
+		let mut sum = Self::default();
+
+		for (hard_link, size) in iter {
+			sum.add(hard_link, size);
+		}
+
+		sum
+	}
+}
+
+/// A link to a file with other hard links to it, as indexed in a location
+#[derive(Debug, Clone)]
+pub struct HardLink {
+	pub key: InodeKey,
+	pub size: u64,
+	/// Materialized path of the directory holding this link
+	pub materialized_path: String,
+}
+
+/// Fetches every link to a file with other hard links in the location, which are rare enough to
+/// be kept in memory while computing directory sizes
+pub async fn fetch_hard_links(
+	location_id: location::id::Type,
+	db: &PrismaClient,
+) -> Result<Vec<HardLink>, QueryError> {
+	Ok(db
+		.file_path()
+		.find_many(vec![
+			file_path::location_id::equals(Some(location_id)),
+			file_path::is_dir::equals(Some(false)),
+			file_path::nlink::gt(1),
+		])
+		.select(file_path::select!({ materialized_path inode device nlink size_in_bytes_bytes }))
+		.exec()
+		.await?
+		.into_iter()
+		.filter_map(|file_path| {
+			Some(HardLink {
+				key: InodeKey::from_db(
+					file_path.device.as_deref(),
+					file_path.inode.as_deref(),
+					file_path.nlink,
+				)?,
+				size: size_in_bytes_from_db(&file_path.size_in_bytes_bytes?),
+				materialized_path: file_path.materialized_path?,
+			})
+		})
+		.collect())
+}
+
+/// Bytes of hard linked files counted more than once in the size of the directory whose children
+/// have `materialized_path`, when that size adds up parts of its subtree which each count an inode
+/// only once. `part_of` maps the materialized path of a link, relative to the directory, to its part
+fn counted_more_than_once<'link>(
+	hard_links: &'link [HardLink],
+	materialized_path: &str,
+	part_of: impl Fn(&'link str) -> &'link str,
+) -> u64 {
+	let mut parts_by_inode = HashMap::<_, (u64, HashSet<_>)>::new();
+
+	for hard_link in hard_links {
+		if let Some(relative_path) = hard_link.materialized_path.strip_prefix(materialized_path) {
+			parts_by_inode
+				.entry(hard_link.key)
+				.or_insert_with(|| (hard_link.size, HashSet::new()))
+				.1
+				.insert(part_of(relative_path));
+		}
+	}
+
+	parts_by_inode
+		.into_values()
+		.map(|(size, parts)| size * (parts.len() as u64 - 1))
+		.sum()
+}
+
+/// Bytes to subtract from a directory size which adds up the sizes of its direct children, as each
+/// subdirectory counts its inodes once but the same inode can be linked from many of them
+#[must_use]
+pub fn counted_more_than_once_by_subdirectories(
+	hard_links: &[HardLink],
+	materialized_path: &str,
+) -> u64 {
+	counted_more_than_once(hard_links, materialized_path, |relative_path| {
+		// Links directly within the directory have an empty relative path, so they're a part too
+		relative_path.split('/').next().unwrap_or_default()
+	})
+}
+
+/// Bytes to subtract from a directory size which adds up the files directly within each
+/// directory of its subtree, like the sizes computed while walking it
+#[must_use]
+pub fn counted_more_than_once_by_directories(
+	hard_links: &[HardLink],
+	materialized_path: &str,
+) -> u64 {
+	counted_more_than_once(hard_links, materialized_path, |relative_path| relative_path)
+}
+
+#[cfg(test)]
+mod tests {
+	use sd_utils::db::inode_to_db;
+
+	use super::*;
+
+	#[test]
+	fn hard_links_are_counted_once() {
+		let shared = InodeKey::for_hard_link(42, 7, 3);
+		let other_device = InodeKey::for_hard_link(43, 7, 2);
+
+		let sum = [
+			(shared, 100),
+			(None, 10),
+			(shared, 100),
+			(None, 10),
+			(other_device, 1000),
+			(shared, 100),
+		]
+		.into_iter()
+		.collect::<UniqueInodesSize>();
+
+		assert_eq!(sum.total(), 1120);
+	}
+
+	#[test]
+	fn single_link_has_no_key() {
+		assert!(InodeKey::for_hard_link(42, 7, 1).is_none());
+		assert!(InodeKey::for_hard_link(42, 7, 0).is_none());
+	}
+
+	#[test]
+	fn key_from_db() {
+		let (device, inode) = (inode_to_db(42), inode_to_db(7));
+
+		assert_eq!(
+			InodeKey::from_db(Some(&device), Some(&inode), Some(2)),
+			Some(InodeKey {
+				device: 42,
+				inode: 7
+			})
+		);
+		assert!(InodeKey::from_db(Some(&device), Some(&inode), Some(1)).is_none());
+		assert!(InodeKey::from_db(None, Some(&inode), Some(2)).is_none());
+		assert_eq!(nlink_to_db(u64::MAX), i32::MAX);
+	}
+
+	#[test]
+	fn hard_links_in_sibling_subdirectories_are_counted_once() {
+		let link = |materialized_path: &str, inode, size| HardLink {
+			key: InodeKey { device: 42, inode },
+			size,
+			materialized_path: materialized_path.to_string(),
+		};
+
+		// An rsnapshot like tree, with a file linked from both snapshots and another one linked
+		// twice within the same one
+		let hard_links = [
+			link("/backups/daily.0/", 7, 100),
+			link("/backups/daily.1/", 7, 100),
+			link("/backups/daily.1/docs/", 8, 10),
+			link("/backups/daily.1/docs/", 8, 10),
+			link("/elsewhere/", 9, 1000),
+		];
+
+		// Each snapshot counts its inodes once, so only the file in both of them is extra
+		assert_eq!(
+			counted_more_than_once_by_subdirectories(&hard_links, "/backups/"),
+			100
+		);
+		assert_eq!(
+			counted_more_than_once_by_subdirectories(&hard_links, "/backups/daily.1/"),
+			0
+		);
+		assert_eq!(
+			counted_more_than_once_by_subdirectories(&hard_links, "/"),
+			100
+		);
+
+		// Walked directories only dedup links directly within them, so the ones of both
+		// snapshots are extra too, but not the ones within the same directory
+		assert_eq!(
+			counted_more_than_once_by_directories(&hard_links, "/backups/"),
+			100
+		);
+		assert_eq!(
+			counted_more_than_once_by_directories(
+				&[
+					link("/backups/daily.0/", 7, 100),
+					link("/backups/daily.0/old/", 7, 100),
+					link("/backups/daily.1/", 7, 100),
+				],
+				"/backups/"
+			),
+			200
+		);
+	}
+}
@@ -31,6 +31,7 @@ use sd_prisma::prisma::{file_path, location, PrismaClient};
 use sd_utils::error::{FileIOError, NonUtf8PathError};
 
 use std::{
+	collections::BTreeMap,
 	fs::Metadata,
 	path::{Path, PathBuf, MAIN_SEPARATOR_STR},
 	time::SystemTime,
@@ -43,20 +44,38 @@ use thiserror::Error;
 use tokio::{fs, io};
 use tracing::error;
 
+pub mod hard_link;
 pub mod isolated_file_path_data;
+pub mod posix;
 
+pub use hard_link::{
+	counted_more_than_once_by_directories, counted_more_than_once_by_subdirectories,
+	fetch_hard_links, HardLink, InodeKey, UniqueInodesSize,
+};
 pub use isolated_file_path_data::{
 	join_location_relative_path, push_location_relative_path, IsolatedFilePathData,
 	IsolatedFilePathDataParts,
 };
+pub use posix::PosixMetadata;
 
-#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct FilePathMetadata {
 	pub inode: u64,
+	/// Device holding the inode, to tell hard links apart from unrelated files with the same inode
+	#[serde(default)]
+	pub device: u64,
+	/// Number of hard links to this file, always 1 for directories
+	#[serde(default)]
+	pub nlink: u64,
 	pub size_in_bytes: u64,
 	pub created_at: DateTime<Utc>,
 	pub modified_at: DateTime<Utc>,
 	pub hidden: bool,
+	#[serde(default)]
+	pub posix: Option<PosixMetadata>,
+	/// Extended attributes selected in the location settings, by name
+	#[serde(default)]
+	pub xattrs: BTreeMap<String, String>,
 }
 
 pub fn path_is_hidden(path: impl AsRef<Path>, metadata: &Metadata) -> bool {
@@ -155,14 +174,39 @@ impl FilePathMetadata {
 			}
 		};
 
+		let (device, nlink) = hard_link::device_and_nlink(metadata);
+
 		Ok(Self {
 			inode,
+			device,
+			nlink,
 			hidden: path_is_hidden(path, metadata),
 			size_in_bytes: metadata.len(),
 			created_at: metadata.created_or_now().into(),
 			modified_at: metadata.modified_or_now().into(),
+			posix: PosixMetadata::from_metadata(metadata),
+			xattrs: BTreeMap::new(),
 		})
 	}
+
+	/// The key to count this file only once among its hard links, `None` if it has no other links
+	#[must_use]
+	pub const fn hard_link_key(&self) -> Option<InodeKey> {
+		InodeKey::for_hard_link(self.device, self.inode, self.nlink)
+	}
+
+	/// Also collect the extended attributes in `xattrs_names` that `path` has
+	#[must_use]
+	pub fn with_xattrs(self, path: impl AsRef<Path>, xattrs_names: &[String]) -> Self {
+		if xattrs_names.is_empty() {
+			return self;
+		}
+
+		Self {
+			xattrs: posix::read_xattrs(path, xattrs_names),
+			..self
+		}
+	}
 }
 
 #[derive(Error, Debug)]
@@ -0,0 +1,254 @@
+use std::{collections::BTreeMap, fs::Metadata, path::Path};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// Permission bits and ownership of a file path, only available on unix systems
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PosixMetadata {
+	/// Permission bits without the file type ones, `st_mode & 0o7777`
+	pub mode: u32,
+	pub uid: u32,
+	pub gid: u32,
+	/// Name of the owner user, `None` if it couldn't be resolved
+	pub owner_name: Option<String>,
+	/// Name of the owner group, `None` if it couldn't be resolved
+	pub group_name: Option<String>,
+}
+
+impl PosixMetadata {
+	#[must_use]
+	pub fn from_metadata(metadata: &Metadata) -> Option<Self> {
+		#[cfg(target_family = "unix")]
+		{
+			use std::os::unix::fs::MetadataExt;
+
+			let (uid, gid) = (metadata.uid(), metadata.gid());
+
+			Some(Self {
+				mode: metadata.mode() & 0o7777,
+				uid,
+				gid,
+				owner_name: names::user_name(uid),
+				group_name: names::group_name(gid),
+			})
+		}
+
+		#[cfg(not(target_family = "unix"))]
+		{
+			let _ = metadata; // just to avoid warnings on Windows
+			None
+		}
+	}
+}
+
+/// Resolving names goes through NSS, which can be quite slow, so we cache them for the whole
+/// lifetime of the process, as a single location usually has just a handful of owners
+#[cfg(target_family = "unix")]
+mod names {
+	use std::{
+		collections::HashMap,
+		sync::{LazyLock, PoisonError, RwLock},
+	};
+
+	type NamesCache = LazyLock<RwLock<HashMap<u32, Option<String>>>>;
+
+	static USER_NAMES: NamesCache = LazyLock::new(RwLock::default);
+	static GROUP_NAMES: NamesCache = LazyLock::new(RwLock::default);
+
+	fn cached(
+		cache: &NamesCache,
+		id: u32,
+		resolve: impl FnOnce(u32) -> Option<String>,
+	) -> Option<String> {
+		if let Some(name) = cache
+			.read()
+			.unwrap_or_else(PoisonError::into_inner)
+			.get(&id)
+		{
+			return name.clone();
+		}
+
+		let name = resolve(id);
+
+		cache
+			.write()
+			.unwrap_or_else(PoisonError::into_inner)
+			.insert(id, name.clone());
+
+		name
+	}
+
+	pub(super) fn user_name(uid: u32) -> Option<String> {
+		cached(&USER_NAMES, uid, |uid| {
+			uzers::get_user_by_uid(uid).map(|user| user.name().to_string_lossy().into_owned())
+		})
+	}
+
+	pub(super) fn group_name(gid: u32) -> Option<String> {
+		cached(&GROUP_NAMES, gid, |gid| {
+			uzers::get_group_by_gid(gid).map(|group| group.name().to_string_lossy().into_owned())
+		})
+	}
+}
+
+/// Read the extended attributes in `names` from `path`, skipping the ones it doesn't have.
+///
+/// Values are stored as text, as the attributes worth auditing are mostly textual, binary values are
+/// converted lossily.
+#[must_use]
+pub fn read_xattrs(path: impl AsRef<Path>, names: &[String]) -> BTreeMap<String, String> {
+	#[cfg(target_family = "unix")]
+	{
+		let path = path.as_ref();
+
+		names
+			.iter()
+			.filter_map(|name| match xattr::get(path, name) {
+				Ok(maybe_value) => maybe_value
+					.map(|value| (name.clone(), String::from_utf8_lossy(&value).into_owned())),
+				Err(e) => {
+					warn!(path = %path.display(), %name, ?e, "Failed to read extended attribute;");
+					None
+				}
+			})
+			.collect()
+	}
+
+	#[cfg(not(target_family = "unix"))]
+	{
+		let _ = (path, names); // just to avoid warnings on Windows
+		BTreeMap::new()
+	}
+}
+
+/// Constructs a database representation of a POSIX id or mode, which are unsigned 32 bit integers
+/// but we only have signed ones in SQLite, so the bits are kept as they are
+#[must_use]
+#[allow(clippy::cast_possible_wrap)]
+pub const fn posix_id_to_db(id: u32) -> i32 {
+	id as i32
+}
+
+/// Construct back a POSIX id or mode after storing it in database
+#[must_use]
+#[allow(clippy::cast_sign_loss)]
+pub const fn posix_id_from_db(db_id: i32) -> u32 {
+	db_id as u32
+}
+
+/// Database representation of [`PosixMetadata`], with every field as `None` when there is none
+#[derive(Debug, Default)]
+pub struct DBPosixMetadata {
+	pub permissions: Option<i32>,
+	pub uid: Option<i32>,
+	pub gid: Option<i32>,
+	pub owner_name: Option<String>,
+	pub group_name: Option<String>,
+}
+
+#[must_use]
+pub fn posix_metadata_to_db(posix: Option<PosixMetadata>) -> DBPosixMetadata {
+	posix.map_or_else(
+		DBPosixMetadata::default,
+		|PosixMetadata {
+		     mode,
+		     uid,
+		     gid,
+		     owner_name,
+		     group_name,
+		 }| DBPosixMetadata {
+			permissions: Some(posix_id_to_db(mode)),
+			uid: Some(posix_id_to_db(uid)),
+			gid: Some(posix_id_to_db(gid)),
+			owner_name,
+			group_name,
+		},
+	)
+}
+
+/// Constructs a database representation of the extended attributes, a JSON object from name to
+/// value, or `None` if there are none
+#[must_use]
+pub fn xattrs_to_db(xattrs: &BTreeMap<String, String>) -> Option<String> {
+	(!xattrs.is_empty()).then(|| {
+		serde_json::to_string(xattrs).expect("a map from strings to strings is always valid JSON")
+	})
+}
+
+/// Pattern to look for in the database representation of the extended attributes, to find the
+/// file paths having the `name` attribute.
+///
+/// JSON escapes quotes inside strings, so the pattern can only match a key, never a value.
+#[must_use]
+pub fn xattr_name_db_pattern(name: &str) -> String {
+	format!("{}:", serde_json::Value::from(name))
+}
+
+/// Constructs a database representation of the names of the extended attributes to be indexed in
+/// a location, or `None` if there are none
+#[must_use]
+pub fn indexed_xattrs_to_db(names: &[String]) -> Option<String> {
+	(!names.is_empty())
+		.then(|| serde_json::to_string(names).expect("a list of strings is always valid JSON"))
+}
+
+/// Construct back the names of the extended attributes to be indexed in a location
+#[must_use]
+pub fn indexed_xattrs_from_db(db_names: Option<&str>) -> Vec<String> {
+	db_names
+		.map(|db_names| {
+			serde_json::from_str(db_names).unwrap_or_else(|e| {
+				warn!(
+					?e,
+					"Invalid indexed extended attributes in database, ignoring them;"
+				);
+				Vec::new()
+			})
+		})
+		.unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn xattr_name_pattern_only_matches_keys() {
+		let xattrs = BTreeMap::from([
+			("user.origin".to_string(), "\"user.comment\":".to_string()),
+			("user.comment".to_string(), "audited".to_string()),
+		]);
+		let db_xattrs = xattrs_to_db(&xattrs).expect("not empty");
+
+		assert!(db_xattrs.contains(&xattr_name_db_pattern("user.comment")));
+		assert!(db_xattrs.contains(&xattr_name_db_pattern("user.origin")));
+
+		let only_origin = xattrs_to_db(&BTreeMap::from([(
+			"user.origin".to_string(),
+			"\"user.comment\":".to_string(),
+		)]))
+		.expect("not empty");
+
+		assert!(!only_origin.contains(&xattr_name_db_pattern("user.comment")));
+	}
+
+	#[test]
+	fn posix_ids_roundtrip() {
+		for id in [0, 1000, 65534, u32::MAX - 1] {
+			assert_eq!(posix_id_from_db(posix_id_to_db(id)), id);
+		}
+	}
+
+	#[test]
+	fn indexed_xattrs_roundtrip() {
+		let names = vec!["user.owner".to_string(), "security.selinux".to_string()];
+
+		assert_eq!(
+			indexed_xattrs_from_db(indexed_xattrs_to_db(&names).as_deref()),
+			names
+		);
+		assert!(indexed_xattrs_to_db(&[]).is_none());
+		assert!(indexed_xattrs_from_db(Some("not json")).is_empty());
+	}
+}
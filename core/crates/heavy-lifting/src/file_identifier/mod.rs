@@ -149,6 +149,26 @@ impl FileMetadata {
 			fs_metadata,
 		})
 	}
+
+	/// Fetch metadata for a symlink indexed as an alias, without touching its target.
+	pub async fn new_alias(
+		location_path: impl AsRef<Path> + Send,
+		iso_file_path: &IsolatedFilePathData<'_>,
+	) -> Result<Self, FileIOError> {
+		let path = location_path.as_ref().join(iso_file_path);
+
+		let fs_metadata = fs::symlink_metadata(&path)
+			.await
+			.map_err(|e| FileIOError::from((&path, e)))?;
+
+		trace!(path = %path.display(), "Analyzed alias;");
+
+		Ok(Self {
+			cas_id: None,
+			kind: ObjectKind::Alias,
+			fs_metadata,
+		})
+	}
 }
 
 fn orphan_path_filters_shallow(
@@ -155,13 +155,20 @@ impl Task<Error> for Identifier {
 						Arc::clone(location_path),
 						&mut output.errors,
 					)
+					.map(|extracted| (extracted, file_path.symlink_target.is_some()))
 				})
-				.map(|(file_path_id, iso_file_path, location_path)| async move {
-					StreamMessage::Processed(
-						file_path_id,
-						FileMetadata::new(&*location_path, &iso_file_path).await,
-					)
-				})
+				.map(
+					|((file_path_id, iso_file_path, location_path), is_alias)| async move {
+						StreamMessage::Processed(
+							file_path_id,
+							if is_alias {
+								FileMetadata::new_alias(&*location_path, &iso_file_path).await
+							} else {
+								FileMetadata::new(&*location_path, &iso_file_path).await
+							},
+						)
+					},
+				)
 				.collect::<FuturesUnordered<_>>();
 
 			let mut msg_stream = pin!((
@@ -12,14 +12,14 @@ use crate::{
 	Error, LocationScanState, NonCriticalError, OuterContext,
 };
 
-use sd_core_file_path_helper::IsolatedFilePathData;
+use sd_core_file_path_helper::{posix::indexed_xattrs_from_db, IsolatedFilePathData};
 use sd_core_indexer_rules::{IndexerRule, IndexerRuler};
 use sd_core_prisma_helpers::location_with_indexer_rules;
 
 use sd_prisma::prisma::location;
 use sd_task_system::{
 	AnyTaskOutput, IntoTask, SerializableTask, Task, TaskDispatcher, TaskHandle, TaskId,
-	TaskOutput, TaskStatus,
+	TaskOutput, TaskStatus, Throttle,
 };
 use sd_utils::{db::maybe_missing, u64_to_frontend};
 
@@ -27,7 +27,7 @@ use std::{
 	collections::{HashMap, HashSet, VecDeque},
 	hash::{Hash, Hasher},
 	mem,
-	path::PathBuf,
+	path::{Path, PathBuf},
 	sync::Arc,
 	time::Duration,
 };
@@ -42,11 +42,13 @@ use tracing::{debug, instrument, trace, warn, Level};
 
 use super::{
 	remove_non_existing_file_paths, reverse_update_directories_sizes,
+	snapshot::DirectorySnapshot,
 	tasks::{
 		self, saver, updater,
 		walker::{self, WalkedEntry},
 	},
-	update_directory_sizes, update_location_size, IsoFilePathFactory, WalkerDBProxy, BATCH_SIZE,
+	update_directory_sizes, update_location_size, DirectorySnapshots, IsoFilePathFactory,
+	NonCriticalIndexerError, SymlinkPolicy, WalkerDBProxy, BATCH_SIZE,
 };
 
 #[derive(Debug)]
@@ -54,6 +56,7 @@ pub struct Indexer {
 	// Received arguments
 	location: location_with_indexer_rules::Data,
 	sub_path: Option<PathBuf>,
+	is_incremental: bool,
 
 	// Derived from received arguments
 	iso_file_path_factory: IsoFilePathFactory,
@@ -64,6 +67,8 @@ pub struct Indexer {
 	ancestors_needing_indexing: HashSet<WalkedEntry>,
 	ancestors_already_indexed: HashSet<IsolatedFilePathData<'static>>,
 	iso_paths_and_sizes: HashMap<IsolatedFilePathData<'static>, u64>,
+	snapshots: Option<Arc<DirectorySnapshots>>,
+	new_snapshots: HashMap<PathBuf, DirectorySnapshot>,
 
 	// Optimizations
 	processing_first_directory: bool,
@@ -73,6 +78,7 @@ pub struct Indexer {
 	// Run data
 	metadata: Metadata,
 	errors: Vec<NonCriticalError>,
+	throttles: Vec<Throttle>,
 
 	// On shutdown data
 	pending_tasks_on_resume: Vec<TaskHandle<Error>>,
@@ -89,6 +95,7 @@ impl Job for Indexer {
 		SerializedTasks(serialized_tasks): SerializedTasks,
 	) -> Result<(), Error> {
 		let location_id = self.location.id;
+		let snapshots = self.walker_snapshots(ctx).await;
 
 		if let Ok(tasks) = dispatcher
 			.dispatch_many_boxed(
@@ -98,6 +105,7 @@ impl Job for Indexer {
 					.map(|(task_kind, task_bytes)| {
 						let indexer_ruler = self.indexer_ruler.clone();
 						let iso_file_path_factory = self.iso_file_path_factory.clone();
+						let snapshots = snapshots.clone();
 						async move {
 							match task_kind {
 								TaskKind::Walk => tasks::Walker::deserialize(
@@ -109,6 +117,7 @@ impl Job for Indexer {
 											db: Arc::clone(ctx.db()),
 										},
 										iso_file_path_factory.clone(),
+										snapshots,
 									),
 								)
 								.await
@@ -161,6 +170,8 @@ impl Job for Indexer {
 	) -> Result<ReturnStatus, Error> {
 		let mut pending_running_tasks = FuturesUnordered::new();
 
+		self.throttles = ctx.indexer_throttles(&self.location.pub_id);
+
 		match self
 			.init_or_resume(&mut pending_running_tasks, &ctx, &dispatcher)
 			.await
@@ -226,21 +237,26 @@ impl Job for Indexer {
 
 		let Self {
 			location,
+			sub_path,
+			is_incremental,
 			mut metadata,
 			iso_file_path_factory,
 			walker_root_path,
 			iso_paths_and_sizes,
+			snapshots,
+			new_snapshots,
 			mut errors,
 			tasks_for_shutdown,
 			..
 		} = self;
 
+		let root_path = walker_root_path.expect("must be set");
+
 		if metadata.indexed_count > 0 || metadata.removed_count > 0 || metadata.updated_count > 0 {
 			let start_size_update_time = Instant::now();
 
-			update_directory_sizes(iso_paths_and_sizes, ctx.db(), ctx.sync()).await?;
+			update_directory_sizes(location.id, iso_paths_and_sizes, ctx.db(), ctx.sync()).await?;
 
-			let root_path = walker_root_path.expect("must be set");
 			if root_path != iso_file_path_factory.location_path {
 				reverse_update_directories_sizes(
 					&*root_path,
@@ -271,6 +287,20 @@ impl Job for Indexer {
 			"all tasks must be completed here"
 		);
 
+		// Scans of sub paths from the location watcher are too frequent to rewrite all snapshots
+		// each time, and the snapshots they would replace are refreshed by the next scan anyway
+		if is_incremental || sub_path.is_none() {
+			save_snapshots(
+				&location,
+				&root_path,
+				snapshots,
+				new_snapshots,
+				&ctx,
+				&mut errors,
+			)
+			.await;
+		}
+
 		ctx.db()
 			.location()
 			.update(
@@ -294,6 +324,24 @@ impl Indexer {
 	pub fn new(
 		location: location_with_indexer_rules::Data,
 		sub_path: Option<PathBuf>,
+	) -> Result<Self, indexer::Error> {
+		Self::new_inner(location, sub_path, false)
+	}
+
+	/// Skips the entries of directories that didn't change since the last scan, according to their
+	/// snapshots. Files edited in place while the location wasn't being watched are only updated by
+	/// a full scan.
+	pub fn new_incremental(
+		location: location_with_indexer_rules::Data,
+		sub_path: Option<PathBuf>,
+	) -> Result<Self, indexer::Error> {
+		Self::new_inner(location, sub_path, true)
+	}
+
+	fn new_inner(
+		location: location_with_indexer_rules::Data,
+		sub_path: Option<PathBuf>,
+		is_incremental: bool,
 	) -> Result<Self, indexer::Error> {
 		Ok(Self {
 			indexer_ruler: location
@@ -312,8 +360,11 @@ impl Indexer {
 			ancestors_needing_indexing: HashSet::new(),
 			ancestors_already_indexed: HashSet::new(),
 			iso_paths_and_sizes: HashMap::new(),
+			snapshots: None,
+			new_snapshots: HashMap::new(),
 			location,
 			sub_path,
+			is_incremental,
 			metadata: Metadata::default(),
 
 			processing_first_directory: true,
@@ -322,6 +373,7 @@ impl Indexer {
 			to_update_buffer: VecDeque::new(),
 
 			errors: Vec::new(),
+			throttles: Vec::new(),
 
 			pending_tasks_on_resume: Vec::new(),
 			tasks_for_shutdown: Vec::new(),
@@ -398,6 +450,9 @@ impl Indexer {
 			errors,
 			directory_iso_file_path,
 			total_size,
+			entries_count,
+			is_unchanged,
+			snapshot,
 			keep_walking_tasks,
 			scan_time,
 			..
@@ -406,6 +461,19 @@ impl Indexer {
 		dispatcher: &JobTaskDispatcher,
 	) -> Result<Vec<TaskHandle<Error>>, JobErrorOrDispatcherError<indexer::Error>> {
 		self.metadata.mean_scan_read_time += scan_time;
+
+		for throttle in &self.throttles {
+			throttle.consume(entries_count);
+		}
+
+		if is_unchanged {
+			self.metadata.unchanged_directories_count += 1;
+		}
+
+		if let Some((path, snapshot)) = snapshot {
+			self.new_snapshots.insert(path, snapshot);
+		}
+
 		#[allow(clippy::cast_possible_truncation)]
 		// SAFETY: we know that `keep_walking_tasks.len()` is a valid u32 as we wouldn't dispatch more than `u32::MAX` tasks
 		{
@@ -488,16 +556,42 @@ impl Indexer {
 			self.metadata.total_tasks
 		);
 
-		dispatcher
+		let mut handles = match dispatcher
 			.dispatch_many_boxed(
-				keep_walking_tasks
+				save_tasks
 					.into_iter()
 					.map(IntoTask::into_task)
-					.chain(save_tasks.into_iter().map(IntoTask::into_task))
 					.chain(update_tasks.into_iter().map(IntoTask::into_task)),
 			)
 			.await
-			.map_err(Into::into)
+		{
+			Ok(handles) => handles,
+			Err(DispatcherError::Shutdown(mut tasks)) => {
+				tasks.extend(keep_walking_tasks.into_iter().map(IntoTask::into_task));
+				return Err(DispatcherError::Shutdown(tasks).into());
+			}
+			Err(e) => return Err(e.into()),
+		};
+
+		// Walkers are the ones hammering the disk, so they are dispatched one by one, respecting
+		// the throttles configured for this location and node
+		let mut keep_walking_tasks = keep_walking_tasks.into_iter();
+		while let Some(walker) = keep_walking_tasks.next() {
+			match dispatcher.dispatch_throttled(&self.throttles, walker).await {
+				Ok(handle) => handles.push(handle),
+				Err(DispatcherError::Shutdown(tasks)) => {
+					// Returning the handles already dispatched, as their tasks must be collected
+					// on shutdown too
+					self.tasks_for_shutdown.extend(tasks);
+					self.tasks_for_shutdown
+						.extend(keep_walking_tasks.map(IntoTask::into_task));
+					break;
+				}
+				Err(e) => return Err(e.into()),
+			}
+		}
+
+		Ok(handles)
 	}
 
 	#[instrument(skip(self, ctx))]
@@ -622,18 +716,26 @@ impl Indexer {
 				.await?,
 			);
 
+			let snapshots = self.walker_snapshots(ctx).await;
+
 			pending_running_tasks.push(
 				dispatcher
-					.dispatch(tasks::Walker::new_deep(
-						walker_root_path.as_ref(),
-						Arc::clone(&walker_root_path),
-						self.indexer_ruler.clone(),
-						self.iso_file_path_factory.clone(),
-						WalkerDBProxy {
-							location_id: self.location.id,
-							db: Arc::clone(ctx.db()),
-						},
-					)?)
+					.dispatch_throttled(
+						&self.throttles,
+						tasks::Walker::new_deep(
+							walker_root_path.as_ref(),
+							Arc::clone(&walker_root_path),
+							self.indexer_ruler.clone(),
+							SymlinkPolicy::from(self.location.symlink_policy),
+							indexed_xattrs_from_db(self.location.indexed_xattrs.as_deref()).into(),
+							self.iso_file_path_factory.clone(),
+							WalkerDBProxy {
+								location_id: self.location.id,
+								db: Arc::clone(ctx.db()),
+							},
+						)?
+						.with_snapshots(snapshots),
+					)
 					.await?,
 			);
 
@@ -891,6 +993,32 @@ impl Indexer {
 		}
 	}
 
+	/// Snapshots are only loaded for incremental scans, as full scans walk through every entry
+	async fn walker_snapshots<OuterCtx: OuterContext>(
+		&mut self,
+		ctx: &impl JobContext<OuterCtx>,
+	) -> Option<Arc<DirectorySnapshots>> {
+		if !self.is_incremental {
+			return None;
+		}
+
+		if self.snapshots.is_none() {
+			self.snapshots = Some(Arc::new(
+				DirectorySnapshots::load(
+					DirectorySnapshots::path(
+						ctx.get_data_directory(),
+						ctx.id(),
+						&self.location.pub_id,
+					),
+					DirectorySnapshots::fingerprint(&self.location),
+				)
+				.await,
+			));
+		}
+
+		self.snapshots.clone()
+	}
+
 	async fn cancel_job(
 		&mut self,
 		pending_running_tasks: &mut FuturesUnordered<TaskHandle<Error>>,
@@ -920,6 +1048,8 @@ pub struct Metadata {
 	indexed_count: u64,
 	updated_count: u64,
 	removed_count: u64,
+	#[serde(default)]
+	unchanged_directories_count: u64,
 }
 
 impl From<Metadata> for Vec<ReportOutputMetadata> {
@@ -937,6 +1067,7 @@ impl From<Metadata> for Vec<ReportOutputMetadata> {
 			indexed_count,
 			updated_count,
 			removed_count,
+			unchanged_directories_count,
 		}: Metadata,
 	) -> Self {
 		mean_scan_read_time /= u32::max(total_walk_tasks, 1); // To avoid division by zero
@@ -959,11 +1090,39 @@ impl From<Metadata> for Vec<ReportOutputMetadata> {
 				("indexed_count".into(), json!(indexed_count)),
 				("updated_count".into(), json!(updated_count)),
 				("removed_count".into(), json!(removed_count)),
+				(
+					"unchanged_directories_count".into(),
+					json!(unchanged_directories_count),
+				),
 			])),
 		]
 	}
 }
 
+async fn save_snapshots<OuterCtx: OuterContext>(
+	location: &location_with_indexer_rules::Data,
+	root_path: &Path,
+	snapshots: Option<Arc<DirectorySnapshots>>,
+	new_snapshots: HashMap<PathBuf, DirectorySnapshot>,
+	ctx: &impl JobContext<OuterCtx>,
+	errors: &mut Vec<NonCriticalError>,
+) {
+	let path = DirectorySnapshots::path(ctx.get_data_directory(), ctx.id(), &location.pub_id);
+
+	let mut snapshots = if let Some(snapshots) = snapshots {
+		Arc::unwrap_or_clone(snapshots)
+	} else {
+		DirectorySnapshots::load(&path, DirectorySnapshots::fingerprint(location)).await
+	};
+
+	snapshots.merge(root_path, new_snapshots);
+
+	if let Err(e) = snapshots.save(&path).await {
+		warn!(?e, "Failed to save directory snapshots;");
+		errors.push(NonCriticalIndexerError::DirectorySnapshots(e.to_string()).into());
+	}
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 enum TaskKind {
 	Walk,
@@ -975,6 +1134,8 @@ enum TaskKind {
 struct SaveState {
 	location: location_with_indexer_rules::Data,
 	sub_path: Option<PathBuf>,
+	#[serde(default)]
+	is_incremental: bool,
 
 	iso_file_path_factory: IsoFilePathFactory,
 	indexer_ruler: IndexerRuler,
@@ -983,6 +1144,8 @@ struct SaveState {
 	ancestors_needing_indexing: HashSet<WalkedEntry>,
 	ancestors_already_indexed: HashSet<IsolatedFilePathData<'static>>,
 	iso_paths_and_sizes: HashMap<IsolatedFilePathData<'static>, u64>,
+	#[serde(default)]
+	new_snapshots: HashMap<PathBuf, DirectorySnapshot>,
 
 	processing_first_directory: bool,
 	to_create_buffer: VecDeque<WalkedEntry>,
@@ -999,6 +1162,7 @@ impl<OuterCtx: OuterContext> SerializableJob<OuterCtx> for Indexer {
 		let Self {
 			location,
 			sub_path,
+			is_incremental,
 			metadata,
 			iso_file_path_factory,
 			indexer_ruler,
@@ -1006,6 +1170,7 @@ impl<OuterCtx: OuterContext> SerializableJob<OuterCtx> for Indexer {
 			ancestors_needing_indexing,
 			ancestors_already_indexed,
 			iso_paths_and_sizes,
+			new_snapshots,
 			processing_first_directory,
 			to_create_buffer,
 			to_update_buffer,
@@ -1052,12 +1217,14 @@ impl<OuterCtx: OuterContext> SerializableJob<OuterCtx> for Indexer {
 		rmp_serde::to_vec_named(&SaveState {
 			location,
 			sub_path,
+			is_incremental,
 			iso_file_path_factory,
 			indexer_ruler,
 			walker_root_path,
 			ancestors_needing_indexing,
 			ancestors_already_indexed,
 			iso_paths_and_sizes,
+			new_snapshots,
 			processing_first_directory,
 			to_create_buffer,
 			to_update_buffer,
@@ -1075,12 +1242,14 @@ impl<OuterCtx: OuterContext> SerializableJob<OuterCtx> for Indexer {
 		let SaveState {
 			location,
 			sub_path,
+			is_incremental,
 			iso_file_path_factory,
 			indexer_ruler,
 			walker_root_path,
 			ancestors_needing_indexing,
 			ancestors_already_indexed,
 			iso_paths_and_sizes,
+			new_snapshots,
 			processing_first_directory,
 			to_create_buffer,
 			to_update_buffer,
@@ -1093,6 +1262,7 @@ impl<OuterCtx: OuterContext> SerializableJob<OuterCtx> for Indexer {
 			Self {
 				location,
 				sub_path,
+				is_incremental,
 				metadata,
 				iso_file_path_factory,
 				indexer_ruler,
@@ -1100,10 +1270,13 @@ impl<OuterCtx: OuterContext> SerializableJob<OuterCtx> for Indexer {
 				ancestors_needing_indexing,
 				ancestors_already_indexed,
 				iso_paths_and_sizes,
+				snapshots: None,
+				new_snapshots,
 				processing_first_directory,
 				to_create_buffer,
 				to_update_buffer,
 				errors,
+				throttles: Vec::new(),
 				pending_tasks_on_resume: Vec::new(),
 				tasks_for_shutdown: Vec::new(),
 			},
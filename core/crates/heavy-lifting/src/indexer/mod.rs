@@ -1,6 +1,9 @@
 use crate::{utils::sub_path, OuterContext};
 
-use sd_core_file_path_helper::{FilePathError, IsolatedFilePathData};
+use sd_core_file_path_helper::{
+	counted_more_than_once_by_directories, counted_more_than_once_by_subdirectories,
+	fetch_hard_links, FilePathError, InodeKey, IsolatedFilePathData, UniqueInodesSize,
+};
 use sd_core_prisma_helpers::{
 	file_path_pub_and_cas_ids, file_path_to_isolate_with_pub_id, file_path_walker,
 };
@@ -34,9 +37,14 @@ use tracing::{instrument, warn};
 
 pub mod job;
 mod shallow;
+mod snapshot;
+mod symlink;
 mod tasks;
 
 pub use shallow::shallow;
+pub use snapshot::{DirectorySnapshots, DIRECTORY_SNAPSHOTS_DIR_NAME};
+pub use symlink::{resolve_symlink, SymlinkPolicy, SymlinkResolution};
+pub use tasks::walker::{preview_indexer_rules, IndexerRulesPreview, PreviewedEntry};
 
 use tasks::walker;
 
@@ -109,6 +117,8 @@ pub enum NonCriticalIndexerError {
 	DispatchKeepWalking(String),
 	#[error("missing file_path data on database: {0}")]
 	MissingFilePathData(String),
+	#[error("failed to save directory snapshots: {0}")]
+	DirectorySnapshots(String),
 }
 
 fn chunk_db_queries<'db, 'iso>(
@@ -132,10 +142,13 @@ fn chunk_db_queries<'db, 'iso>(
 
 #[allow(clippy::missing_panics_doc)] // Can't actually panic as we use the hashmap to fetch entries from db
 async fn update_directory_sizes(
-	iso_paths_and_sizes: HashMap<IsolatedFilePathData<'_>, u64, impl BuildHasher + Send>,
+	location_id: location::id::Type,
+	mut iso_paths_and_sizes: HashMap<IsolatedFilePathData<'_>, u64, impl BuildHasher + Send>,
 	db: &PrismaClient,
 	sync: &SyncManager,
 ) -> Result<(), Error> {
+	dedup_hard_links_in_walked_sizes(location_id, &mut iso_paths_and_sizes, db).await?;
+
 	let to_sync_and_update = db
 		._batch(chunk_db_queries(iso_paths_and_sizes.keys(), db))
 		.await?
@@ -172,6 +185,40 @@ async fn update_directory_sizes(
 	Ok(())
 }
 
+/// Walked sizes add up the files directly within each directory of the subtree, which only dedup
+/// hard links within that directory, so we take out the ones linked from many walked directories
+async fn dedup_hard_links_in_walked_sizes(
+	location_id: location::id::Type,
+	iso_paths_and_sizes: &mut HashMap<IsolatedFilePathData<'_>, u64, impl BuildHasher + Send>,
+	db: &PrismaClient,
+) -> Result<(), QueryError> {
+	let walked_materialized_paths = iso_paths_and_sizes
+		.keys()
+		.filter_map(IsolatedFilePathData::materialized_path_for_children)
+		.collect::<HashSet<_>>();
+
+	let hard_links = fetch_hard_links(location_id, db)
+		.await?
+		.into_iter()
+		.filter(|hard_link| walked_materialized_paths.contains(&hard_link.materialized_path))
+		.collect::<Vec<_>>();
+
+	if hard_links.is_empty() {
+		return Ok(());
+	}
+
+	for (iso_file_path, size) in iso_paths_and_sizes.iter_mut() {
+		if let Some(materialized_path) = iso_file_path.materialized_path_for_children() {
+			*size = size.saturating_sub(counted_more_than_once_by_directories(
+				&hard_links,
+				&materialized_path,
+			));
+		}
+	}
+
+	Ok(())
+}
+
 async fn update_location_size(
 	location_id: location::id::Type,
 	db: &PrismaClient,
@@ -183,16 +230,28 @@ async fn update_location_size(
 			file_path::location_id::equals(Some(location_id)),
 			file_path::materialized_path::equals(Some("/".to_string())),
 		])
-		.select(file_path::select!({ size_in_bytes_bytes }))
+		.select(file_path::select!({ size_in_bytes_bytes inode device nlink }))
 		.exec()
 		.await?
 		.into_iter()
 		.filter_map(|file_path| {
-			file_path
-				.size_in_bytes_bytes
-				.map(|size_in_bytes_bytes| size_in_bytes_from_db(&size_in_bytes_bytes))
+			file_path.size_in_bytes_bytes.map(|size_in_bytes_bytes| {
+				(
+					InodeKey::from_db(
+						file_path.device.as_deref(),
+						file_path.inode.as_deref(),
+						file_path.nlink,
+					),
+					size_in_bytes_from_db(&size_in_bytes_bytes),
+				)
+			})
 		})
-		.sum::<u64>();
+		.collect::<UniqueInodesSize>()
+		.total()
+		.saturating_sub(counted_more_than_once_by_subdirectories(
+			&fetch_hard_links(location_id, db).await?,
+			"/",
+		));
 
 	db.location()
 		.update(
@@ -358,27 +417,44 @@ async fn compute_sizes(
 	db: &PrismaClient,
 	errors: &mut Vec<crate::NonCriticalError>,
 ) -> Result<(), QueryError> {
+	let mut sizes_by_materialized_path = HashMap::<_, UniqueInodesSize>::new();
+
 	for file_path in db
 		.file_path()
 		.find_many(vec![
 			file_path::location_id::equals(Some(location_id)),
 			file_path::materialized_path::in_vec(materialized_paths),
 		])
-		.select(file_path::select!({ pub_id materialized_path size_in_bytes_bytes }))
+		.select(file_path::select!({
+			pub_id
+			materialized_path
+			size_in_bytes_bytes
+			inode
+			device
+			nlink
+		}))
 		.exec()
 		.await?
 	{
 		if let Some(materialized_path) = file_path.materialized_path {
-			if let Some((_, size)) =
-				pub_id_by_ancestor_materialized_path.get_mut(&materialized_path)
-			{
-				*size += file_path.size_in_bytes_bytes.map_or_else(
-					|| {
-						warn!("Got a directory missing its size in bytes");
-						0
-					},
-					|size_in_bytes_bytes| size_in_bytes_from_db(&size_in_bytes_bytes),
-				);
+			if pub_id_by_ancestor_materialized_path.contains_key(&materialized_path) {
+				sizes_by_materialized_path
+					.entry(materialized_path)
+					.or_default()
+					.add(
+						InodeKey::from_db(
+							file_path.device.as_deref(),
+							file_path.inode.as_deref(),
+							file_path.nlink,
+						),
+						file_path.size_in_bytes_bytes.map_or_else(
+							|| {
+								warn!("Got a directory missing its size in bytes");
+								0
+							},
+							|size_in_bytes_bytes| size_in_bytes_from_db(&size_in_bytes_bytes),
+						),
+					);
 			}
 		} else {
 			errors.push(
@@ -391,6 +467,23 @@ async fn compute_sizes(
 		}
 	}
 
+	// Subdirectories only dedup hard links within themselves, so we take out the ones linked
+	// from more than one branch of each directory
+	let hard_links = fetch_hard_links(location_id, db).await?;
+
+	for (materialized_path, size) in sizes_by_materialized_path {
+		if let Some((_, total_size)) =
+			pub_id_by_ancestor_materialized_path.get_mut(&materialized_path)
+		{
+			*total_size += size
+				.total()
+				.saturating_sub(counted_more_than_once_by_subdirectories(
+					&hard_links,
+					&materialized_path,
+				));
+		}
+	}
+
 	Ok(())
 }
 
@@ -408,6 +501,10 @@ impl walker::IsoFilePathFactory for IsoFilePathFactory {
 	) -> Result<IsolatedFilePathData<'static>, FilePathError> {
 		IsolatedFilePathData::new(self.location_id, self.location_path.as_ref(), path, is_dir)
 	}
+
+	fn location_path(&self) -> &Path {
+		self.location_path.as_ref()
+	}
 }
 
 #[derive(Debug, Clone)]
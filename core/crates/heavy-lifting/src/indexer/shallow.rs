@@ -2,6 +2,7 @@ use crate::{
 	indexer, utils::sub_path::get_full_path_from_sub_path, Error, NonCriticalError, OuterContext,
 };
 
+use sd_core_file_path_helper::posix::indexed_xattrs_from_db;
 use sd_core_indexer_rules::{IndexerRule, IndexerRuler};
 use sd_core_prisma_helpers::location_with_indexer_rules;
 use sd_core_sync::Manager as SyncManager;
@@ -26,7 +27,8 @@ use super::{
 		self, saver, updater,
 		walker::{self, ToWalkEntry, WalkedEntry},
 	},
-	update_directory_sizes, update_location_size, IsoFilePathFactory, WalkerDBProxy, BATCH_SIZE,
+	update_directory_sizes, update_location_size, IsoFilePathFactory, SymlinkPolicy, WalkerDBProxy,
+	BATCH_SIZE,
 };
 
 #[instrument(
@@ -106,6 +108,7 @@ pub async fn shallow(
 
 	if indexed_count > 0 || removed_count > 0 || updated_count > 0 {
 		update_directory_sizes(
+			location.id,
 			HashMap::from([(directory_iso_file_path, total_size)]),
 			db,
 			sync,
@@ -156,6 +159,8 @@ async fn walk(
 				.collect::<Result<Vec<_>, _>>()
 				.map(IndexerRuler::new)
 				.map_err(indexer::Error::from)?,
+			SymlinkPolicy::from(location.symlink_policy),
+			indexed_xattrs_from_db(location.indexed_xattrs.as_deref()).into(),
 			IsoFilePathFactory {
 				location_id: location.id,
 				location_path,
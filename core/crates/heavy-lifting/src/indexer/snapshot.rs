@@ -0,0 +1,326 @@
+//! Directories snapshots let incremental scans skip the per-entry work on directories that didn't
+//! change since the last time they were walked.
+//!
+//! Creating, removing or renaming an entry updates the modification time of its parent directory,
+//! so a directory with the same modification time and the same number of entries as in its snapshot
+//! still has the same entries, and we only need to keep walking into its inner directories.
+//! Editing a file in place doesn't touch its parent directory though, so full scans still look at
+//! every entry, while incremental scans rely on the location watcher to catch these edits, also
+//! updating on startup the files modified since it was last running.
+
+use sd_core_prisma_helpers::location_with_indexer_rules;
+use sd_utils::{error::FileIOError, from_bytes_to_uuid};
+
+use std::{
+	collections::HashMap,
+	io::ErrorKind,
+	path::{Path, PathBuf},
+	time::{Duration, SystemTime},
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+use tracing::{trace, warn};
+use uuid::Uuid;
+
+use super::tasks::walker::ToWalkEntry;
+
+pub const DIRECTORY_SNAPSHOTS_DIR_NAME: &str = "directory_snapshots";
+
+const SNAPSHOTS_VERSION: u8 = 1;
+
+/// File systems store modification times with a coarse granularity, so a directory changed right
+/// after we read its modification time can keep the same one. We don't take snapshots of directories
+/// modified this close to the walk, as we can't be sure that we saw all of their entries.
+const RACY_MODIFICATION_WINDOW: Duration = Duration::from_secs(2);
+
+/// State of a directory at the last time it was walked by the indexer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectorySnapshot {
+	modified_at: SystemTime,
+	entries_count: usize,
+	/// Total size computed by the walker, to keep directories sizes right when skipping it
+	pub(super) total_size: u64,
+	/// Inner directories accepted by the indexer rules, that we have to keep walking into
+	pub(super) to_walk: Vec<ToWalkEntry>,
+}
+
+impl DirectorySnapshot {
+	/// Returns `None` if the directory was modified too close to the walk to be trusted
+	pub(super) fn new(
+		modified_at: SystemTime,
+		walked_at: SystemTime,
+		entries_count: usize,
+		total_size: u64,
+		to_walk: Vec<ToWalkEntry>,
+	) -> Option<Self> {
+		walked_at
+			.duration_since(modified_at)
+			.is_ok_and(|elapsed| elapsed > RACY_MODIFICATION_WINDOW)
+			.then_some(Self {
+				modified_at,
+				entries_count,
+				total_size,
+				to_walk,
+			})
+	}
+
+	pub(super) fn is_unchanged(&self, modified_at: SystemTime, entries_count: usize) -> bool {
+		self.modified_at == modified_at && self.entries_count == entries_count
+	}
+}
+
+/// Snapshots of every directory walked in a location, stored in the node data directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectorySnapshots {
+	version: u8,
+	/// Hash of the location settings that decide which entries are indexed, snapshots taken with
+	/// other indexer rules can't be used
+	fingerprint: [u8; 32],
+	directories: HashMap<PathBuf, DirectorySnapshot>,
+}
+
+impl DirectorySnapshots {
+	pub(super) fn new(fingerprint: [u8; 32]) -> Self {
+		Self {
+			version: SNAPSHOTS_VERSION,
+			fingerprint,
+			directories: HashMap::new(),
+		}
+	}
+
+	pub fn path(
+		data_directory: impl AsRef<Path>,
+		library_id: Uuid,
+		location_pub_id: &[u8],
+	) -> PathBuf {
+		data_directory
+			.as_ref()
+			.join(DIRECTORY_SNAPSHOTS_DIR_NAME)
+			.join(library_id.to_string())
+			.join(from_bytes_to_uuid(location_pub_id).to_string())
+	}
+
+	pub(super) fn fingerprint(location: &location_with_indexer_rules::Data) -> [u8; 32] {
+		let mut hasher = blake3::Hasher::new();
+
+		for rule in &location.indexer_rules {
+			hasher.update(&rule.indexer_rule.id.to_le_bytes());
+			if let Some(rules_per_kind) = &rule.indexer_rule.rules_per_kind {
+				hasher.update(rules_per_kind);
+			}
+		}
+
+		hasher.update(&location.symlink_policy.unwrap_or_default().to_le_bytes());
+		if let Some(indexed_xattrs) = &location.indexed_xattrs {
+			hasher.update(indexed_xattrs.as_bytes());
+		}
+
+		*hasher.finalize().as_bytes()
+	}
+
+	/// Loads the stored snapshots, starting from scratch if they are missing, unreadable or
+	/// were taken with other indexer settings
+	pub(super) async fn load(path: impl AsRef<Path> + Send, fingerprint: [u8; 32]) -> Self {
+		let path = path.as_ref();
+
+		let bytes = match fs::read(path).await {
+			Ok(bytes) => bytes,
+			Err(e) if e.kind() == ErrorKind::NotFound => {
+				trace!("No directory snapshots for this location yet");
+				return Self::new(fingerprint);
+			}
+			Err(e) => {
+				warn!(?e, path = %path.display(), "Failed to read directory snapshots;");
+				return Self::new(fingerprint);
+			}
+		};
+
+		match rmp_serde::from_slice::<Self>(&bytes) {
+			Ok(snapshots)
+				if snapshots.version == SNAPSHOTS_VERSION
+					&& snapshots.fingerprint == fingerprint =>
+			{
+				snapshots
+			}
+			Ok(_) => {
+				trace!("Discarding directory snapshots taken with other indexer settings");
+				Self::new(fingerprint)
+			}
+			Err(e) => {
+				warn!(?e, path = %path.display(), "Failed to decode directory snapshots;");
+				Self::new(fingerprint)
+			}
+		}
+	}
+
+	pub(super) fn get(&self, path: impl AsRef<Path>) -> Option<&DirectorySnapshot> {
+		self.directories.get(path.as_ref())
+	}
+
+	/// Replaces every snapshot inside `root` by the ones taken in the last walk, as directories
+	/// missing from it were removed or aren't accepted by the indexer rules anymore
+	pub(super) fn merge(
+		&mut self,
+		root: impl AsRef<Path>,
+		new_snapshots: HashMap<PathBuf, DirectorySnapshot>,
+	) {
+		let root = root.as_ref();
+
+		self.directories.retain(|path, _| !path.starts_with(root));
+		self.directories.extend(new_snapshots);
+	}
+
+	pub(super) async fn save(&self, path: impl AsRef<Path> + Send) -> Result<(), FileIOError> {
+		let path = path.as_ref();
+
+		if let Some(parent) = path.parent() {
+			fs::create_dir_all(parent).await.map_err(|e| {
+				FileIOError::from((parent, e, "Failed to create directory snapshots directory"))
+			})?;
+		}
+
+		let bytes = rmp_serde::to_vec_named(self).map_err(|e| {
+			FileIOError::from((
+				path,
+				std::io::Error::new(ErrorKind::InvalidData, e),
+				"Failed to encode directory snapshots",
+			))
+		})?;
+
+		// Writing to a temporary file first, so a crash while saving doesn't leave a broken file
+		let tmp_path = path.with_extension("tmp");
+		fs::write(&tmp_path, bytes).await.map_err(|e| {
+			FileIOError::from((&tmp_path, e, "Failed to write directory snapshots"))
+		})?;
+		fs::rename(&tmp_path, path)
+			.await
+			.map_err(|e| FileIOError::from((path, e, "Failed to replace directory snapshots")))
+	}
+
+	/// Checks if a location has snapshots, which means that an incremental scan won't walk through
+	/// every entry of the location
+	pub async fn exist(
+		data_directory: impl AsRef<Path> + Send,
+		library_id: Uuid,
+		location_pub_id: &[u8],
+	) -> bool {
+		fs::try_exists(Self::path(data_directory, library_id, location_pub_id))
+			.await
+			.unwrap_or(false)
+	}
+
+	pub async fn remove(
+		data_directory: impl AsRef<Path> + Send,
+		library_id: Uuid,
+		location_pub_id: &[u8],
+	) -> Result<(), FileIOError> {
+		let path = Self::path(data_directory, library_id, location_pub_id);
+
+		match fs::remove_file(&path).await {
+			Ok(()) => Ok(()),
+			Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+			Err(e) => Err(FileIOError::from((
+				&path,
+				e,
+				"Failed to remove directory snapshots",
+			))),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use tempfile::tempdir;
+
+	fn snapshot(modified_at: SystemTime, entries_count: usize) -> DirectorySnapshot {
+		DirectorySnapshot::new(
+			modified_at,
+			modified_at + Duration::from_secs(10),
+			entries_count,
+			0,
+			vec![],
+		)
+		.expect("modified long before the walk")
+	}
+
+	#[test]
+	fn racy_directories_are_not_snapshotted() {
+		let now = SystemTime::now();
+
+		assert!(DirectorySnapshot::new(now, now, 1, 0, vec![]).is_none());
+		assert!(DirectorySnapshot::new(now, now + Duration::from_secs(1), 1, 0, vec![]).is_none());
+		assert!(DirectorySnapshot::new(now, now + Duration::from_secs(3), 1, 0, vec![]).is_some());
+	}
+
+	#[test]
+	fn unchanged_directory() {
+		let modified_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+		let snapshot = snapshot(modified_at, 3);
+
+		assert!(snapshot.is_unchanged(modified_at, 3));
+		assert!(!snapshot.is_unchanged(modified_at, 4));
+		assert!(!snapshot.is_unchanged(modified_at + Duration::from_nanos(1), 3));
+	}
+
+	#[test]
+	fn merge_replaces_snapshots_inside_root() {
+		let modified_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+		let mut snapshots = DirectorySnapshots::new([0; 32]);
+		snapshots.merge(
+			"/location",
+			HashMap::from([
+				(PathBuf::from("/location"), snapshot(modified_at, 2)),
+				(PathBuf::from("/location/a"), snapshot(modified_at, 1)),
+				(
+					PathBuf::from("/location/a/removed"),
+					snapshot(modified_at, 0),
+				),
+				(PathBuf::from("/location/b"), snapshot(modified_at, 0)),
+			]),
+		);
+
+		snapshots.merge(
+			"/location/a",
+			HashMap::from([(PathBuf::from("/location/a"), snapshot(modified_at, 0))]),
+		);
+
+		assert_eq!(snapshots.directories.len(), 3);
+		assert!(snapshots.get("/location/a/removed").is_none());
+		assert!(snapshots
+			.get("/location/a")
+			.is_some_and(|snapshot| snapshot.is_unchanged(modified_at, 0)));
+		assert!(snapshots.get("/location/b").is_some());
+	}
+
+	#[tokio::test]
+	async fn load_discards_other_settings() {
+		let dir = tempdir().unwrap();
+		let path = dir.path().join("snapshots");
+		let modified_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+
+		let mut snapshots = DirectorySnapshots::new([1; 32]);
+		snapshots.merge(
+			"/location",
+			HashMap::from([(PathBuf::from("/location"), snapshot(modified_at, 2))]),
+		);
+		snapshots.save(&path).await.unwrap();
+
+		assert!(DirectorySnapshots::load(&path, [1; 32])
+			.await
+			.get("/location")
+			.is_some());
+		assert!(DirectorySnapshots::load(&path, [2; 32])
+			.await
+			.get("/location")
+			.is_none());
+		assert!(
+			DirectorySnapshots::load(dir.path().join("missing"), [1; 32])
+				.await
+				.directories
+				.is_empty()
+		);
+	}
+}
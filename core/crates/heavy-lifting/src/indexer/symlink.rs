@@ -0,0 +1,116 @@
+use sd_utils::error::FileIOError;
+
+use std::{
+	fs::Metadata,
+	path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tokio::fs;
+use tracing::trace;
+
+/// How symbolic links found inside a location are handled by the indexer and the watcher
+#[repr(i32)]
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, Type, Eq, PartialEq)]
+pub enum SymlinkPolicy {
+	/// Symbolic links are not indexed at all
+	#[default]
+	Ignore = 0,
+	/// Symbolic links are indexed as `ObjectKind::Alias` entries which record their target
+	Alias = 1,
+	/// Symbolic links are indexed as the entry they point to, directories are walked into
+	Follow = 2,
+}
+
+impl From<Option<i32>> for SymlinkPolicy {
+	fn from(value: Option<i32>) -> Self {
+		match value {
+			Some(1) => Self::Alias,
+			Some(2) => Self::Follow,
+			_ => Self::Ignore,
+		}
+	}
+}
+
+#[derive(Debug)]
+pub enum SymlinkResolution {
+	/// The link must not be indexed
+	Ignore,
+	/// The link must be indexed as an alias to `target`
+	Alias { target: PathBuf },
+	/// The link must be indexed as the entry it points to, with the target's metadata
+	Follow {
+		canonical_target: PathBuf,
+		metadata: Metadata,
+	},
+}
+
+/// Decide how the symbolic link at `path` must be indexed according to `policy`.
+///
+/// Following a link falls back to indexing it as an alias when the link is broken, when it points
+/// inside the location (its target is already indexed) or when its target overlaps one of the
+/// `followed_targets` we went through to reach it, which would be a cycle.
+pub async fn resolve_symlink(
+	policy: SymlinkPolicy,
+	path: impl AsRef<Path> + Send,
+	location_path: impl AsRef<Path> + Send,
+	followed_targets: &[PathBuf],
+) -> Result<SymlinkResolution, FileIOError> {
+	let path = path.as_ref();
+
+	if policy == SymlinkPolicy::Ignore {
+		trace!(path = %path.display(), "Ignoring symlink;");
+		return Ok(SymlinkResolution::Ignore);
+	}
+
+	let target = fs::read_link(path)
+		.await
+		.map_err(|e| FileIOError::from((path, e, "Failed to read symlink target")))?;
+
+	if policy == SymlinkPolicy::Alias {
+		return Ok(SymlinkResolution::Alias { target });
+	}
+
+	let (Ok(canonical_target), Ok(metadata)) =
+		(fs::canonicalize(path).await, fs::metadata(path).await)
+	else {
+		trace!(path = %path.display(), "Broken symlink, indexing it as an alias;");
+		return Ok(SymlinkResolution::Alias { target });
+	};
+
+	let location_path = location_path.as_ref();
+	let canonical_location_path = fs::canonicalize(location_path)
+		.await
+		.map_err(|e| FileIOError::from((location_path, e)))?;
+
+	if overlaps(&canonical_target, &canonical_location_path) {
+		trace!(
+			path = %path.display(),
+			target = %canonical_target.display(),
+			"Symlink points inside the location, indexing it as an alias;",
+		);
+		return Ok(SymlinkResolution::Alias { target });
+	}
+
+	if followed_targets
+		.iter()
+		.any(|followed| overlaps(&canonical_target, followed))
+	{
+		trace!(
+			path = %path.display(),
+			target = %canonical_target.display(),
+			"Symlink cycle detected, indexing it as an alias;",
+		);
+		return Ok(SymlinkResolution::Alias { target });
+	}
+
+	Ok(SymlinkResolution::Follow {
+		canonical_target,
+		metadata,
+	})
+}
+
+fn overlaps(a: &Path, b: &Path) -> bool {
+	a.starts_with(b) || b.starts_with(a)
+}
@@ -1,6 +1,10 @@
 use crate::{indexer, Error};
 
-use sd_core_file_path_helper::{FilePathMetadata, IsolatedFilePathDataParts};
+use sd_core_file_path_helper::{
+	hard_link::nlink_to_db,
+	posix::{posix_metadata_to_db, xattrs_to_db, DBPosixMetadata},
+	FilePathMetadata, IsolatedFilePathDataParts,
+};
 use sd_core_sync::Manager as SyncManager;
 
 use sd_prisma::{
@@ -75,8 +79,9 @@ impl Task<Error> for Updater {
 	#[allow(clippy::blocks_in_conditions)] // Due to `err` on `instrument` macro above
 	async fn run(&mut self, interrupter: &Interrupter) -> Result<ExecStatus, Error> {
 		use file_path::{
-			cas_id, date_created, date_modified, hidden, inode, is_dir, object, object_id,
-			size_in_bytes_bytes,
+			cas_id, date_created, date_modified, device, gid, group_name, hidden, inode, is_dir,
+			nlink, object, object_id, owner_name, permissions, size_in_bytes_bytes, symlink_target,
+			uid, xattrs,
 		};
 
 		let start_time = Instant::now();
@@ -103,14 +108,28 @@ impl Task<Error> for Updater {
 				     metadata:
 				         FilePathMetadata {
 				             inode,
+				             device: file_device,
+				             nlink: file_nlink,
 				             size_in_bytes,
 				             created_at,
 				             modified_at,
 				             hidden,
+				             posix,
+				             xattrs: file_xattrs,
 				         },
+				     symlink_target: maybe_symlink_target,
 				 }| {
 					let IsolatedFilePathDataParts { is_dir, .. } = &iso_file_path.to_parts();
 
+					let DBPosixMetadata {
+						permissions: maybe_permissions,
+						uid: maybe_uid,
+						gid: maybe_gid,
+						owner_name: maybe_owner_name,
+						group_name: maybe_group_name,
+					} = posix_metadata_to_db(posix);
+					let maybe_xattrs = xattrs_to_db(&file_xattrs);
+
 					let should_unlink_object = maybe_object_id.map_or(false, |object_id| {
 						object_ids_that_should_be_unlinked.contains(&object_id)
 					});
@@ -121,9 +140,33 @@ impl Task<Error> for Updater {
 							sync_db_entry!(*is_dir, is_dir),
 							sync_db_entry!(size_in_bytes_to_db(size_in_bytes), size_in_bytes_bytes),
 							sync_db_entry!(inode_to_db(inode), inode),
+							sync_db_entry!(inode_to_db(file_device), device),
+							sync_db_entry!(nlink_to_db(file_nlink), nlink),
 							sync_db_entry!(created_at, date_created),
 							sync_db_entry!(modified_at, date_modified),
 							sync_db_entry!(hidden, hidden),
+							(
+								(symlink_target::NAME, msgpack!(maybe_symlink_target)),
+								symlink_target::set(maybe_symlink_target),
+							),
+							(
+								(permissions::NAME, msgpack!(maybe_permissions)),
+								permissions::set(maybe_permissions),
+							),
+							((uid::NAME, msgpack!(maybe_uid)), uid::set(maybe_uid)),
+							((gid::NAME, msgpack!(maybe_gid)), gid::set(maybe_gid)),
+							(
+								(owner_name::NAME, msgpack!(maybe_owner_name)),
+								owner_name::set(maybe_owner_name),
+							),
+							(
+								(group_name::NAME, msgpack!(maybe_group_name)),
+								group_name::set(maybe_group_name),
+							),
+							(
+								(xattrs::NAME, msgpack!(maybe_xattrs)),
+								xattrs::set(maybe_xattrs),
+							),
 						],
 						[
 							// As this file was updated while Spacedrive was offline, we mark the object_id and cas_id as null
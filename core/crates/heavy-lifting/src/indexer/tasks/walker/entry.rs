@@ -17,6 +17,8 @@ pub struct WalkedEntry {
 	pub maybe_object_id: file_path::object_id::Type,
 	pub iso_file_path: IsolatedFilePathData<'static>,
 	pub metadata: FilePathMetadata,
+	/// Target of the symlink when this entry is indexed as an alias
+	pub symlink_target: Option<String>,
 }
 
 impl PartialEq for WalkedEntry {
@@ -37,6 +39,7 @@ impl Hash for WalkedEntry {
 pub(super) struct WalkingEntry {
 	pub(super) iso_file_path: IsolatedFilePathData<'static>,
 	pub(super) metadata: FilePathMetadata,
+	pub(super) symlink_target: Option<String>,
 }
 
 impl From<WalkingEntry> for WalkedEntry {
@@ -44,6 +47,7 @@ impl From<WalkingEntry> for WalkedEntry {
 		WalkingEntry {
 			iso_file_path,
 			metadata,
+			symlink_target,
 		}: WalkingEntry,
 	) -> Self {
 		Self {
@@ -51,6 +55,7 @@ impl From<WalkingEntry> for WalkedEntry {
 			maybe_object_id: None,
 			iso_file_path,
 			metadata,
+			symlink_target,
 		}
 	}
 }
@@ -65,6 +70,7 @@ impl<PubId: Into<FilePathPubId>> From<(PubId, file_path::object_id::Type, Walkin
 			WalkingEntry {
 				iso_file_path,
 				metadata,
+				symlink_target,
 			},
 		): (PubId, file_path::object_id::Type, WalkingEntry),
 	) -> Self {
@@ -73,14 +79,18 @@ impl<PubId: Into<FilePathPubId>> From<(PubId, file_path::object_id::Type, Walkin
 			maybe_object_id,
 			iso_file_path,
 			metadata,
+			symlink_target,
 		}
 	}
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToWalkEntry {
 	pub(super) path: PathBuf,
 	pub(super) parent_dir_accepted_by_its_children: Option<bool>,
+	/// Canonical targets of the symlinks followed to reach this directory
+	#[serde(default)]
+	pub(super) followed_symlinks: Vec<PathBuf>,
 }
 
 impl<P: AsRef<Path>> From<P> for ToWalkEntry {
@@ -88,6 +98,7 @@ impl<P: AsRef<Path>> From<P> for ToWalkEntry {
 		Self {
 			path: path.as_ref().into(),
 			parent_dir_accepted_by_its_children: None,
+			followed_symlinks: Vec::new(),
 		}
 	}
 }
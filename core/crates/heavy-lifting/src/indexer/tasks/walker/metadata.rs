@@ -1,9 +1,13 @@
 use crate::indexer;
 
-use sd_core_file_path_helper::FilePathMetadata;
+use sd_core_file_path_helper::{FilePathMetadata, PosixMetadata};
 use sd_core_indexer_rules::MetadataForIndexerRules;
 
-use std::{fs::Metadata, path::Path};
+use std::{
+	collections::BTreeMap,
+	fs::Metadata,
+	path::{Path, PathBuf},
+};
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -11,36 +15,84 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Serialize, Deserialize)]
 pub(super) struct InnerMetadata {
 	pub is_dir: bool,
-	pub is_symlink: bool,
 	pub inode: u64,
+	#[serde(default)]
+	pub device: u64,
+	#[serde(default)]
+	pub nlink: u64,
 	pub size_in_bytes: u64,
 	pub hidden: bool,
 	pub created_at: DateTime<Utc>,
 	pub modified_at: DateTime<Utc>,
+	#[serde(default)]
+	pub posix: Option<PosixMetadata>,
+	#[serde(default)]
+	pub xattrs: BTreeMap<String, String>,
+	/// Target of a symlink indexed as an alias
+	#[serde(default)]
+	pub symlink_target: Option<String>,
+	/// Canonical target of a followed symlink, used to detect cycles while walking into it
+	#[serde(default)]
+	pub followed_target: Option<PathBuf>,
 }
 
 impl InnerMetadata {
 	pub fn new(
 		path: impl AsRef<Path> + Copy,
 		metadata: &Metadata,
+		indexed_xattrs: &[String],
 	) -> Result<Self, indexer::NonCriticalIndexerError> {
 		let FilePathMetadata {
 			inode,
+			device,
+			nlink,
 			size_in_bytes,
 			created_at,
 			modified_at,
 			hidden,
+			posix,
+			xattrs,
 		} = FilePathMetadata::from_path(path, metadata)
-			.map_err(|e| indexer::NonCriticalIndexerError::FilePathMetadata(e.to_string()))?;
+			.map_err(|e| indexer::NonCriticalIndexerError::FilePathMetadata(e.to_string()))?
+			.with_xattrs(path, indexed_xattrs);
 
 		Ok(Self {
 			is_dir: metadata.is_dir(),
-			is_symlink: metadata.is_symlink(),
 			inode,
+			device,
+			nlink,
 			size_in_bytes,
 			hidden,
 			created_at,
 			modified_at,
+			posix,
+			xattrs,
+			symlink_target: None,
+			followed_target: None,
+		})
+	}
+
+	pub fn new_alias(
+		path: impl AsRef<Path> + Copy,
+		symlink_metadata: &Metadata,
+		indexed_xattrs: &[String],
+		target: impl AsRef<Path>,
+	) -> Result<Self, indexer::NonCriticalIndexerError> {
+		Self::new(path, symlink_metadata, indexed_xattrs).map(|metadata| Self {
+			symlink_target: Some(target.as_ref().to_string_lossy().into_owned()),
+			..metadata
+		})
+	}
+
+	pub fn new_followed(
+		path: impl AsRef<Path> + Copy,
+		target_metadata: &Metadata,
+		indexed_xattrs: &[String],
+		canonical_target: PathBuf,
+	) -> Result<Self, indexer::NonCriticalIndexerError> {
+		Self::new(path, target_metadata, indexed_xattrs).map(|metadata| Self {
+			followed_target: Some(canonical_target),
+			..metadata
 		})
 	}
 }
@@ -49,16 +101,28 @@ impl MetadataForIndexerRules for InnerMetadata {
 	fn is_dir(&self) -> bool {
 		self.is_dir
 	}
+
+	fn size_in_bytes(&self) -> u64 {
+		self.size_in_bytes
+	}
+
+	fn modified_at(&self) -> Option<DateTime<Utc>> {
+		Some(self.modified_at)
+	}
 }
 
 impl From<InnerMetadata> for FilePathMetadata {
 	fn from(metadata: InnerMetadata) -> Self {
 		Self {
 			inode: metadata.inode,
+			device: metadata.device,
+			nlink: metadata.nlink,
 			size_in_bytes: metadata.size_in_bytes,
 			hidden: metadata.hidden,
 			created_at: metadata.created_at,
 			modified_at: metadata.modified_at,
+			posix: metadata.posix,
+			xattrs: metadata.xattrs,
 		}
 	}
 }
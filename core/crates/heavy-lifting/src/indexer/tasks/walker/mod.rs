@@ -1,14 +1,20 @@
 use crate::{
 	indexer::{
-		self,
+		self, resolve_symlink,
+		snapshot::DirectorySnapshot,
 		tasks::walker::rules::{apply_indexer_rules, process_rules_results},
+		DirectorySnapshots, SymlinkPolicy, SymlinkResolution,
 	},
 	Error, NonCriticalError,
 };
 
-use sd_core_file_path_helper::{FilePathError, FilePathMetadata, IsolatedFilePathData};
+use sd_core_file_path_helper::{
+	hard_link::nlink_to_db,
+	posix::{posix_id_to_db, xattrs_to_db},
+	FilePathError, FilePathMetadata, IsolatedFilePathData, UniqueInodesSize,
+};
 use sd_core_indexer_rules::{
-	seed::{GitIgnoreRules, GITIGNORE},
+	seed::{GitIgnoreRules, IgnoreFileRules, GITIGNORE, IGNORE_FILES},
 	IndexerRuler, MetadataForIndexerRules, RuleKind,
 };
 use sd_core_prisma_helpers::{file_path_pub_and_cas_ids, file_path_walker};
@@ -29,7 +35,7 @@ use std::{
 	mem,
 	path::{Path, PathBuf},
 	sync::Arc,
-	time::Duration,
+	time::{Duration, SystemTime},
 };
 
 use chrono::{DateTime, Duration as ChronoDuration, FixedOffset};
@@ -40,10 +46,12 @@ use tracing::{instrument, trace, Level};
 
 mod entry;
 mod metadata;
+mod preview;
 mod rules;
 mod save_state;
 
 pub use entry::{ToWalkEntry, WalkedEntry};
+pub use preview::{preview_indexer_rules, IndexerRulesPreview, PreviewedEntry};
 
 use entry::WalkingEntry;
 use metadata::InnerMetadata;
@@ -54,6 +62,9 @@ pub trait IsoFilePathFactory: Clone + Send + Sync + fmt::Debug + 'static {
 		path: impl AsRef<Path>,
 		is_dir: bool,
 	) -> Result<IsolatedFilePathData<'static>, FilePathError>;
+
+	/// Root path of the location, symlinks are only followed to targets outside of it
+	fn location_path(&self) -> &Path;
 }
 
 pub trait WalkerDBProxy: Clone + Send + Sync + fmt::Debug + 'static {
@@ -87,9 +98,16 @@ where
 	root: Arc<PathBuf>,
 	entry_iso_file_path: IsolatedFilePathData<'static>,
 	indexer_ruler: IndexerRuler,
+	symlink_policy: SymlinkPolicy,
+	indexed_xattrs: Arc<[String]>,
+	/// Snapshots taken on the last walk, only used on incremental scans
+	snapshots: Option<Arc<DirectorySnapshots>>,
 
 	// Inner state
 	stage: WalkerStage,
+	/// Modification time of the walked directory and when we read it
+	directory_modified_at: Option<(SystemTime, SystemTime)>,
+	found_entries_count: usize,
 
 	// Dependencies
 	iso_file_path_factory: IsoPathFactory,
@@ -123,8 +141,14 @@ where
 	pub errors: Vec<NonCriticalError>,
 	/// Directory that was indexed
 	pub directory_iso_file_path: IsolatedFilePathData<'static>,
-	/// Total size of the directory that was indexed
+	/// Total size of the directory that was indexed, counting files with hard links only once
 	pub total_size: u64,
+	/// How many entries of the directory were accepted by the indexer rules, changed or not
+	pub entries_count: usize,
+	/// The directory didn't change since its last snapshot, so its entries were skipped
+	pub is_unchanged: bool,
+	/// New snapshot of the walked directory, to be used by the next incremental scan
+	pub snapshot: Option<(PathBuf, DirectorySnapshot)>,
 	/// Task handles that were dispatched to run `WalkDir` tasks for inner directories
 	pub keep_walking_tasks: Vec<Walker<DBProxy, IsoPathFactory>>,
 	/// Time spent walking through the received directory
@@ -161,15 +185,22 @@ where
 		let is_shallow = self.is_shallow;
 		let Self {
 			root,
-			entry: ToWalkEntry {
-				path,
-				parent_dir_accepted_by_its_children,
-			},
+			entry:
+				ToWalkEntry {
+					path,
+					parent_dir_accepted_by_its_children,
+					followed_symlinks,
+				},
 			entry_iso_file_path,
 			iso_file_path_factory,
 			indexer_ruler,
+			symlink_policy,
+			indexed_xattrs,
+			snapshots,
 			db_proxy,
 			stage,
+			directory_modified_at,
+			found_entries_count,
 			errors,
 			scan_time,
 			..
@@ -184,20 +215,30 @@ where
 			non_indexed_paths,
 			accepted_ancestors,
 			total_size,
+			entries_count,
 			keep_walking_tasks,
+			snapshot,
 		) = loop {
 			match stage {
 				WalkerStage::Start => {
-					trace!("Preparing git indexer rules for walking root");
-					if indexer_ruler.has_system(&GITIGNORE) {
-						if let Some(rules) =
-							GitIgnoreRules::get_rules_if_in_git_repo(root.as_ref(), path).await
-						{
-							trace!("Found gitignore rules to follow");
-							indexer_ruler.extend(rules.map(Into::into));
-						}
+					if !is_shallow {
+						// Read before the entries, so changes made while walking invalidate the snapshot
+						*directory_modified_at = fs::metadata(&path)
+							.await
+							.and_then(|metadata| metadata.modified())
+							.ok()
+							.map(|modified_at| (modified_at, SystemTime::now()));
 					}
 
+					trace!("Preparing git and ignore files indexer rules for walking root");
+					extend_with_ignore_rules(
+						indexer_ruler,
+						root.as_ref(),
+						iso_file_path_factory.location_path(),
+						path,
+					)
+					.await;
+
 					*stage = WalkerStage::Walking {
 						read_dir_stream: ReadDirStream::new(fs::read_dir(&path).await.map_err(
 							|e| {
@@ -241,17 +282,66 @@ where
 
 					trace!(total_paths = found_paths.len(), "Finished walking!;");
 
-					*stage = WalkerStage::CollectingMetadata {
-						found_paths: mem::take(found_paths),
+					*found_entries_count = found_paths.len();
+
+					*stage = if let Some(snapshot) = unchanged_snapshot(
+						snapshots.as_deref(),
+						path,
+						*directory_modified_at,
+						*found_entries_count,
+						errors,
+					) {
+						trace!("Directory didn't change since its last snapshot, skipping entries");
+						WalkerStage::Unchanged {
+							snapshot: snapshot.clone(),
+						}
+					} else {
+						WalkerStage::CollectingMetadata {
+							found_paths: mem::take(found_paths),
+						}
 					};
 
 					check_interruption!(interrupter, start_time, scan_time);
 				}
 
+				WalkerStage::Unchanged { snapshot } => {
+					let keep_walking_tasks = keep_walking(
+						root,
+						indexer_ruler,
+						*symlink_policy,
+						indexed_xattrs,
+						snapshots.as_ref(),
+						iso_file_path_factory,
+						db_proxy,
+						Some(&mut snapshot.to_walk.clone()),
+						errors,
+					);
+
+					break (
+						vec![],
+						vec![],
+						vec![],
+						vec![],
+						HashSet::new(),
+						snapshot.total_size,
+						0,
+						keep_walking_tasks,
+						Some((path.clone(), snapshot.clone())),
+					);
+				}
+
 				WalkerStage::CollectingMetadata { found_paths } => {
 					trace!("Collecting metadata for found paths");
 					*stage = WalkerStage::CheckingIndexerRules {
-						paths_and_metadatas: collect_metadata(found_paths, errors).await,
+						paths_and_metadatas: collect_metadata(
+							found_paths,
+							iso_file_path_factory.location_path(),
+							*symlink_policy,
+							indexed_xattrs,
+							followed_symlinks,
+							errors,
+						)
+						.await,
 					};
 					trace!("Finished collecting metadata!");
 
@@ -285,6 +375,8 @@ where
 							root,
 							iso_file_path_factory,
 							*parent_dir_accepted_by_its_children,
+							followed_symlinks,
+							indexed_xattrs,
 							paths_metadatas_and_acceptance,
 							&mut maybe_to_keep_walking,
 							is_shallow,
@@ -347,6 +439,7 @@ where
 					non_indexed_paths,
 				} => {
 					trace!("Segregating creates and updates");
+					let entries_count = walking_entries.len();
 					let (to_create, to_update, total_size) =
 						segregate_creates_and_updates(walking_entries, db_proxy).await?;
 					trace!(
@@ -358,9 +451,23 @@ where
 						"Finished segregating creates and updates!;"
 					);
 
+					let snapshot = take_snapshot(
+						*directory_modified_at,
+						*found_entries_count,
+						total_size,
+						maybe_to_keep_walking.as_ref(),
+						followed_symlinks,
+						accepted_ancestors,
+						errors,
+					)
+					.map(|snapshot| (path.clone(), snapshot));
+
 					let keep_walking_tasks = keep_walking(
 						root,
 						indexer_ruler,
+						*symlink_policy,
+						indexed_xattrs,
+						snapshots.as_ref(),
 						iso_file_path_factory,
 						db_proxy,
 						maybe_to_keep_walking.as_mut(),
@@ -374,7 +481,9 @@ where
 						mem::take(non_indexed_paths),
 						mem::take(accepted_ancestors),
 						total_size,
+						entries_count,
 						keep_walking_tasks,
+						snapshot,
 					);
 				}
 			}
@@ -382,6 +491,8 @@ where
 
 		*scan_time += start_time.elapsed();
 
+		let is_unchanged = matches!(stage, WalkerStage::Unchanged { .. });
+
 		// Taking out some data as the task is finally complete
 		Ok(ExecStatus::Done(
 			Output {
@@ -393,6 +504,9 @@ where
 				errors: mem::take(errors),
 				directory_iso_file_path: mem::take(entry_iso_file_path),
 				total_size,
+				entries_count,
+				is_unchanged,
+				snapshot,
 				keep_walking_tasks,
 				scan_time: *scan_time,
 			}
@@ -408,6 +522,9 @@ enum WalkerStage {
 		read_dir_stream: ReadDirStream,
 		found_paths: Vec<PathBuf>,
 	},
+	Unchanged {
+		snapshot: DirectorySnapshot,
+	},
 	CollectingMetadata {
 		found_paths: Vec<PathBuf>,
 	},
@@ -442,6 +559,8 @@ where
 		entry: impl Into<ToWalkEntry> + Send,
 		root: Arc<PathBuf>,
 		indexer_ruler: IndexerRuler,
+		symlink_policy: SymlinkPolicy,
+		indexed_xattrs: Arc<[String]>,
 		iso_file_path_factory: IsoPathFactory,
 		db_proxy: DBProxy,
 	) -> Result<Self, indexer::Error> {
@@ -450,16 +569,28 @@ where
 			id: TaskId::new_v4(),
 			root,
 			indexer_ruler,
+			symlink_policy,
+			indexed_xattrs,
+			snapshots: None,
 			entry_iso_file_path: iso_file_path_factory.build(&entry.path, true)?,
 			iso_file_path_factory,
 			db_proxy,
 			stage: WalkerStage::Start,
+			directory_modified_at: None,
+			found_entries_count: 0,
 			entry,
 			is_shallow: false,
 			errors: Vec::new(),
 			scan_time: Duration::ZERO,
 		})
 	}
+
+	/// Skips the entries of directories that didn't change since these snapshots were taken
+	#[must_use]
+	pub fn with_snapshots(mut self, snapshots: Option<Arc<DirectorySnapshots>>) -> Self {
+		self.snapshots = snapshots;
+		self
+	}
 }
 
 impl<DBProxy, IsoPathFactory> Walker<DBProxy, IsoPathFactory>
@@ -471,6 +602,8 @@ where
 		entry: impl Into<ToWalkEntry> + Send,
 		root: Arc<PathBuf>,
 		indexer_ruler: IndexerRuler,
+		symlink_policy: SymlinkPolicy,
+		indexed_xattrs: Arc<[String]>,
 		iso_file_path_factory: IsoPathFactory,
 		db_proxy: DBProxy,
 	) -> Result<Self, indexer::Error> {
@@ -479,10 +612,15 @@ where
 			id: TaskId::new_v4(),
 			root,
 			indexer_ruler,
+			symlink_policy,
+			indexed_xattrs,
+			snapshots: None,
 			entry_iso_file_path: iso_file_path_factory.build(&entry.path, true)?,
 			iso_file_path_factory,
 			db_proxy,
 			stage: WalkerStage::Start,
+			directory_modified_at: None,
+			found_entries_count: 0,
 			entry,
 			is_shallow: true,
 			errors: Vec::new(),
@@ -518,15 +656,16 @@ async fn segregate_creates_and_updates(
 			})
 			.collect::<HashMap<_, _>>();
 
-		Ok(walking_entries.drain(..).fold(
-			(Vec::new(), Vec::new(), 0),
+		let (to_create, to_update, total_size) = walking_entries.drain(..).fold(
+			(Vec::new(), Vec::new(), UniqueInodesSize::default()),
 			|(mut to_create, mut to_update, mut total_size), entry| {
 				let WalkingEntry {
 					iso_file_path,
 					metadata,
+					symlink_target,
 				} = &entry;
 
-				total_size += metadata.size_in_bytes;
+				total_size.add(metadata.hard_link_key(), metadata.size_in_bytes);
 
 				if let Some(file_path) = iso_paths_already_in_db.get(iso_file_path) {
 					if let (Some(inode), Some(date_modified)) =
@@ -543,6 +682,9 @@ async fn segregate_creates_and_updates(
 										)
 									|| file_path.hidden.is_none()
 									|| metadata.hidden != file_path.hidden.unwrap_or_default()
+									|| *symlink_target != file_path.symlink_target
+									|| posix_metadata_changed(metadata, file_path)
+									|| hard_links_changed(metadata, file_path)
 								)
 								// We ignore the size of directories because it is not reliable, we need to
 								// calculate it ourselves later
@@ -579,13 +721,35 @@ async fn segregate_creates_and_updates(
 
 				(to_create, to_update, total_size)
 			},
-		))
+		);
+
+		Ok((to_create, to_update, total_size.total()))
 	}
 }
 
+/// Check if the permissions, ownership or extended attributes changed since the last time we indexed
+fn posix_metadata_changed(metadata: &FilePathMetadata, file_path: &file_path_walker::Data) -> bool {
+	let posix = metadata.posix.as_ref();
+
+	posix.map(|posix| posix_id_to_db(posix.mode)) != file_path.permissions
+		|| posix.map(|posix| posix_id_to_db(posix.uid)) != file_path.uid
+		|| posix.map(|posix| posix_id_to_db(posix.gid)) != file_path.gid
+		|| xattrs_to_db(&metadata.xattrs) != file_path.xattrs
+}
+
+/// Check if the number of hard links or the device changed since the last time we indexed
+fn hard_links_changed(metadata: &FilePathMetadata, file_path: &file_path_walker::Data) -> bool {
+	Some(nlink_to_db(metadata.nlink)) != file_path.nlink
+		|| Some(inode_to_db(metadata.device)) != file_path.device
+}
+
+#[allow(clippy::too_many_arguments)]
 fn keep_walking<DBProxy, IsoPathFactory>(
 	root: &Arc<PathBuf>,
 	indexer_ruler: &IndexerRuler,
+	symlink_policy: SymlinkPolicy,
+	indexed_xattrs: &Arc<[String]>,
+	snapshots: Option<&Arc<DirectorySnapshots>>,
 	iso_file_path_factory: &IsoPathFactory,
 	db_proxy: &DBProxy,
 	maybe_to_keep_walking: Option<&mut Vec<ToWalkEntry>>,
@@ -604,9 +768,12 @@ where
 						entry,
 						Arc::clone(root),
 						indexer_ruler.clone(),
+						symlink_policy,
+						Arc::clone(indexed_xattrs),
 						iso_file_path_factory.clone(),
 						db_proxy.clone(),
 					)
+					.map(|walker| walker.with_snapshots(snapshots.cloned()))
 					.map_err(|e| {
 						indexer::NonCriticalIndexerError::DispatchKeepWalking(e.to_string())
 					})
@@ -617,30 +784,130 @@ where
 		.unwrap_or_default()
 }
 
+fn unchanged_snapshot<'snapshots>(
+	snapshots: Option<&'snapshots DirectorySnapshots>,
+	path: &Path,
+	directory_modified_at: Option<(SystemTime, SystemTime)>,
+	entries_count: usize,
+	errors: &[NonCriticalError],
+) -> Option<&'snapshots DirectorySnapshot> {
+	let (modified_at, _) = directory_modified_at?;
+
+	snapshots?
+		.get(path)
+		.filter(|snapshot| errors.is_empty() && snapshot.is_unchanged(modified_at, entries_count))
+}
+
+/// Directories with errors or accepted ancestors must always be walked again, as skipping them
+/// would lose entries or ancestors that we have to save. The same goes for directories with followed
+/// symlinks, as their targets can be removed without changing the directory.
+fn take_snapshot(
+	directory_modified_at: Option<(SystemTime, SystemTime)>,
+	entries_count: usize,
+	total_size: u64,
+	maybe_to_keep_walking: Option<&Vec<ToWalkEntry>>,
+	followed_symlinks: &[PathBuf],
+	accepted_ancestors: &HashSet<WalkedEntry>,
+	errors: &[NonCriticalError],
+) -> Option<DirectorySnapshot> {
+	let (modified_at, walked_at) = directory_modified_at?;
+
+	if !errors.is_empty() || !accepted_ancestors.is_empty() {
+		return None;
+	}
+
+	// Shallow walks don't keep walking, so we don't have inner directories to snapshot
+	maybe_to_keep_walking
+		.filter(|to_keep_walking| {
+			to_keep_walking
+				.iter()
+				.all(|entry| entry.followed_symlinks.len() == followed_symlinks.len())
+		})
+		.and_then(|to_keep_walking| {
+			DirectorySnapshot::new(
+				modified_at,
+				walked_at,
+				entries_count,
+				total_size,
+				to_keep_walking.clone(),
+			)
+		})
+}
+
+async fn extend_with_ignore_rules(
+	indexer_ruler: &mut IndexerRuler,
+	root: &Path,
+	location_path: &Path,
+	path: &Path,
+) {
+	if indexer_ruler.has_system(&GITIGNORE) {
+		if let Some(rules) = GitIgnoreRules::get_rules_if_in_git_repo(root, path).await {
+			trace!("Found gitignore rules to follow");
+			indexer_ruler.extend(rules.map(Into::into));
+		}
+	}
+
+	if indexer_ruler.has_system(&IGNORE_FILES) {
+		if let Some(rules) = IgnoreFileRules::get_rules(location_path, path).await {
+			trace!("Found ignore files rules to follow");
+			indexer_ruler.extend(rules.map(Into::into));
+		}
+	}
+}
+
 async fn collect_metadata(
 	found_paths: &mut Vec<PathBuf>,
+	location_path: &Path,
+	symlink_policy: SymlinkPolicy,
+	indexed_xattrs: &[String],
+	followed_symlinks: &[PathBuf],
 	errors: &mut Vec<NonCriticalError>,
 ) -> HashMap<PathBuf, InnerMetadata> {
 	found_paths
 		.drain(..)
 		.map(|current_path| async move {
-			fs::metadata(&current_path)
-				.await
-				.map_err(|e| {
-					indexer::NonCriticalIndexerError::Metadata(
-						FileIOError::from((&current_path, e)).to_string(),
-					)
-				})
-				.and_then(|metadata| {
-					InnerMetadata::new(&current_path, &metadata)
-						.map(|metadata| (current_path, metadata))
-				})
+			let metadata = fs::symlink_metadata(&current_path).await.map_err(|e| {
+				indexer::NonCriticalIndexerError::Metadata(
+					FileIOError::from((&current_path, e)).to_string(),
+				)
+			})?;
+
+			if !metadata.is_symlink() {
+				return InnerMetadata::new(&current_path, &metadata, indexed_xattrs)
+					.map(|metadata| Some((current_path, metadata)));
+			}
+
+			match resolve_symlink(
+				symlink_policy,
+				&current_path,
+				location_path,
+				followed_symlinks,
+			)
+			.await
+			.map_err(|e| indexer::NonCriticalIndexerError::Metadata(e.to_string()))?
+			{
+				SymlinkResolution::Ignore => Ok(None),
+				SymlinkResolution::Alias { target } => {
+					InnerMetadata::new_alias(&current_path, &metadata, indexed_xattrs, target)
+						.map(|metadata| Some((current_path, metadata)))
+				}
+				SymlinkResolution::Follow {
+					canonical_target,
+					metadata,
+				} => InnerMetadata::new_followed(
+					&current_path,
+					&metadata,
+					indexed_xattrs,
+					canonical_target,
+				)
+				.map(|metadata| Some((current_path, metadata))),
+			}
 		})
 		.collect::<Vec<_>>()
 		.join()
 		.await
 		.into_iter()
-		.filter_map(|res| res.map_err(|e| errors.push(e.into())).ok())
+		.filter_map(|res| res.map_err(|e| errors.push(e.into())).ok().flatten())
 		.collect()
 }
 
@@ -660,12 +927,17 @@ async fn gather_file_paths_to_remove(
 				.build(&path, metadata.is_dir())
 				.map(|iso_file_path| {
 					let params = file_path::WhereParam::from(&iso_file_path);
-					existing_inodes.insert(inode_to_db(metadata.inode));
+					// Other links to the same inode are different files for us, not moved ones
+					if metadata.nlink <= 1 {
+						existing_inodes.insert(inode_to_db(metadata.inode));
+					}
+					let symlink_target = metadata.symlink_target.clone();
 
 					(
 						WalkingEntry {
 							iso_file_path,
 							metadata: FilePathMetadata::from(metadata),
+							symlink_target,
 						},
 						params,
 					)
@@ -698,6 +970,8 @@ mod tests {
 	use sd_core_prisma_helpers::FilePathPubId;
 	use sd_task_system::{TaskOutput, TaskStatus, TaskSystem};
 
+	use std::collections::BTreeMap;
+
 	use chrono::Utc;
 	use futures::stream::FuturesUnordered;
 	use globset::{Glob, GlobSetBuilder};
@@ -720,6 +994,10 @@ mod tests {
 		) -> Result<IsolatedFilePathData<'static>, FilePathError> {
 			IsolatedFilePathData::new(0, self.root_path.as_ref(), path, is_dir).map_err(Into::into)
 		}
+
+		fn location_path(&self) -> &Path {
+			self.root_path.as_ref()
+		}
 	}
 
 	#[derive(Debug, Clone)]
@@ -874,8 +1152,9 @@ mod tests {
 	async fn run_test(
 		root_path: &Path,
 		indexer_ruler: IndexerRuler,
+		symlink_policy: SymlinkPolicy,
 		expected: HashSet<WalkedEntry>,
-	) {
+	) -> HashSet<WalkedEntry> {
 		let system = TaskSystem::new();
 
 		let handle = system
@@ -884,6 +1163,8 @@ mod tests {
 					root_path.to_path_buf(),
 					Arc::new(root_path.to_path_buf()),
 					indexer_ruler,
+					symlink_policy,
+					Vec::new().into(),
 					DummyIsoPathFactory {
 						root_path: Arc::new(root_path.to_path_buf()),
 					},
@@ -943,6 +1224,8 @@ mod tests {
 			expected.difference(&actual_set),
 			actual_set.difference(&expected)
 		);
+
+		actual_set
 	}
 
 	#[tokio::test]
@@ -953,10 +1236,14 @@ mod tests {
 
 		let metadata = FilePathMetadata {
 			inode: 0,
+			device: 0,
+			nlink: 1,
 			size_in_bytes: 0,
 			created_at: Utc::now(),
 			modified_at: Utc::now(),
 			hidden: false,
+			posix: None,
+			xattrs: BTreeMap::new(),
 		};
 
 		let f = |path, is_dir| IsolatedFilePathData::new(0, root_path, path, is_dir).unwrap();
@@ -965,34 +1252,40 @@ mod tests {
 
 		#[rustfmt::skip]
 		let expected = [
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/.git"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/.gitignore"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/Cargo.toml"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial/readme"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/src"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/src/main.rs"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.git"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.gitignore"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/package.json"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src/App.tsx"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules/react"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules/react/package.json"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("photos"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("photos/photo1.png"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("photos/photo2.jpg"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("photos/photo3.jpeg"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos/text.txt"), false), metadata },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project"), true), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/.git"), true), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/.gitignore"), false), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/Cargo.toml"), false), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial"), true), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial/readme"), false), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/src"), true), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/src/main.rs"), false), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner"), true), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project"), true), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.git"), true), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.gitignore"), false), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/package.json"), false), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src"), true), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src/App.tsx"), false), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules"), true), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules/react"), true), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules/react/package.json"), false), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("photos"), true), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("photos/photo1.png"), false), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("photos/photo2.jpg"), false), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("photos/photo3.jpeg"), false), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos/text.txt"), false), metadata, symlink_target: None },
 		]
 		.into_iter()
 		.collect::<HashSet<_>>();
 
-		run_test(root_path, IndexerRuler::default(), expected).await;
+		run_test(
+			root_path,
+			IndexerRuler::default(),
+			SymlinkPolicy::Ignore,
+			expected,
+		)
+		.await;
 	}
 
 	#[tokio::test]
@@ -1003,10 +1296,14 @@ mod tests {
 
 		let metadata = FilePathMetadata {
 			inode: 0,
+			device: 0,
+			nlink: 1,
 			size_in_bytes: 0,
 			created_at: Utc::now(),
 			modified_at: Utc::now(),
 			hidden: false,
+			posix: None,
+			xattrs: BTreeMap::new(),
 		};
 
 		let f = |path, is_dir| IsolatedFilePathData::new(0, root_path, path, is_dir).unwrap();
@@ -1015,10 +1312,10 @@ mod tests {
 
 		#[rustfmt::skip]
 		let expected = [
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("photos"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("photos/photo1.png"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("photos/photo2.jpg"), false), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos/photo3.jpeg"), false), metadata },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("photos"), true), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("photos/photo1.png"), false), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("photos/photo2.jpg"), false), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("photos/photo3.jpeg"), false), metadata, symlink_target: None },
 		]
 		.into_iter()
 		.collect::<HashSet<_>>();
@@ -1036,6 +1333,7 @@ mod tests {
 						.unwrap(),
 				)],
 			)]),
+			SymlinkPolicy::Ignore,
 			expected,
 		)
 		.await;
@@ -1049,10 +1347,14 @@ mod tests {
 
 		let metadata = FilePathMetadata {
 			inode: 0,
+			device: 0,
+			nlink: 1,
 			size_in_bytes: 0,
 			created_at: Utc::now(),
 			modified_at: Utc::now(),
 			hidden: false,
+			posix: None,
+			xattrs: BTreeMap::new(),
 		};
 
 		let f = |path, is_dir| IsolatedFilePathData::new(0, root_path, path, is_dir).unwrap();
@@ -1061,24 +1363,24 @@ mod tests {
 
 		#[rustfmt::skip]
 		let expected = [
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/.git"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/.gitignore"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/Cargo.toml"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/src"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/src/main.rs"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial/readme"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.git"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/package.json"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.gitignore"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src/App.tsx"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules/react"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules/react/package.json"), false), metadata },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project"), true), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/.git"), true), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/.gitignore"), false), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/Cargo.toml"), false), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/src"), true), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/src/main.rs"), false), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial"), true), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial/readme"), false), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner"), true), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project"), true), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.git"), true), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/package.json"), false), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.gitignore"), false), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src"), true), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src/App.tsx"), false), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules"), true), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules/react"), true), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/node_modules/react/package.json"), false), metadata, symlink_target: None },
 		]
 		.into_iter()
 		.collect::<HashSet<_>>();
@@ -1092,6 +1394,7 @@ mod tests {
 					HashSet::from([".git".to_string()]),
 				)],
 			)]),
+			SymlinkPolicy::Ignore,
 			expected,
 		)
 		.await;
@@ -1105,10 +1408,14 @@ mod tests {
 
 		let metadata = FilePathMetadata {
 			inode: 0,
+			device: 0,
+			nlink: 1,
 			size_in_bytes: 0,
 			created_at: Utc::now(),
 			modified_at: Utc::now(),
 			hidden: false,
+			posix: None,
+			xattrs: BTreeMap::new(),
 		};
 
 		let f = |path, is_dir| IsolatedFilePathData::new(0, root_path, path, is_dir).unwrap();
@@ -1117,21 +1424,21 @@ mod tests {
 
 		#[rustfmt::skip]
 		let expected = [
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/.git"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/.gitignore"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/Cargo.toml"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial/readme"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/src"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/src/main.rs"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.git"), true), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.gitignore"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/package.json"), false), metadata },
-			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src"), true), metadata },
-			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src/App.tsx"), false), metadata },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project"), true), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/.git"), true), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/.gitignore"), false), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/Cargo.toml"), false), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial"), true), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/partial/readme"), false), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/src"), true), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("rust_project/src/main.rs"), false), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner"), true), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project"), true), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.git"), true), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/.gitignore"), false), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/package.json"), false), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src"), true), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("inner/node_project/src/App.tsx"), false), metadata, symlink_target: None },
 		]
 		.into_iter()
 		.collect::<HashSet<_>>();
@@ -1169,8 +1476,325 @@ mod tests {
 					)],
 				),
 			]),
+			SymlinkPolicy::Ignore,
 			expected,
 		)
 		.await;
 	}
+
+	#[cfg(unix)]
+	#[tokio::test]
+	#[traced_test]
+	async fn test_symlink_policies() {
+		// root
+		// |__ docs
+		// |   |__ readme.md
+		// |   |__ readme_link.md -> root/docs/readme.md
+		// |__ external -> outside
+		// |__ broken -> root/missing
+		//
+		// outside
+		// |__ shared.txt
+		// |__ loop -> outside
+
+		let root = tempdir().unwrap();
+		let root_path = root.path();
+		let outside = tempdir().unwrap();
+		let outside_path = outside.path();
+
+		let docs = root_path.join("docs");
+		fs::create_dir(&docs).await.unwrap();
+		fs::File::create(docs.join("readme.md")).await.unwrap();
+		fs::symlink(docs.join("readme.md"), docs.join("readme_link.md"))
+			.await
+			.unwrap();
+		fs::symlink(outside_path, root_path.join("external"))
+			.await
+			.unwrap();
+		fs::symlink(root_path.join("missing"), root_path.join("broken"))
+			.await
+			.unwrap();
+
+		fs::File::create(outside_path.join("shared.txt"))
+			.await
+			.unwrap();
+		fs::symlink(outside_path, outside_path.join("loop"))
+			.await
+			.unwrap();
+
+		let metadata = FilePathMetadata {
+			inode: 0,
+			device: 0,
+			nlink: 1,
+			size_in_bytes: 0,
+			created_at: Utc::now(),
+			modified_at: Utc::now(),
+			hidden: false,
+			posix: None,
+			xattrs: BTreeMap::new(),
+		};
+
+		let f = |path, is_dir| IsolatedFilePathData::new(0, root_path, path, is_dir).unwrap();
+		let pub_id = FilePathPubId::new();
+		let maybe_object_id = None;
+
+		#[rustfmt::skip]
+		let ignored = [
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("docs"), true), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("docs/readme.md"), false), metadata: metadata.clone(), symlink_target: None },
+		]
+		.into_iter()
+		.collect::<HashSet<_>>();
+
+		run_test(
+			root_path,
+			IndexerRuler::default(),
+			SymlinkPolicy::Ignore,
+			ignored,
+		)
+		.await;
+
+		#[rustfmt::skip]
+		let aliased = [
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("docs"), true), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("docs/readme.md"), false), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("docs/readme_link.md"), false), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("external"), false), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("broken"), false), metadata: metadata.clone(), symlink_target: None },
+		]
+		.into_iter()
+		.collect::<HashSet<_>>();
+
+		let actual = run_test(
+			root_path,
+			IndexerRuler::default(),
+			SymlinkPolicy::Alias,
+			aliased,
+		)
+		.await;
+
+		for entry in actual {
+			let name = entry.iso_file_path.to_parts().name;
+			assert_eq!(
+				entry.symlink_target.is_some(),
+				matches!(name, "readme_link" | "external" | "broken"),
+				"unexpected symlink target for {name}: {:?}",
+				entry.symlink_target
+			);
+		}
+
+		#[rustfmt::skip]
+		let followed = [
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("docs"), true), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("docs/readme.md"), false), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("docs/readme_link.md"), false), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("external"), true), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("external/shared.txt"), false), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id: pub_id.clone(), maybe_object_id, iso_file_path: f(root_path.join("external/loop"), false), metadata: metadata.clone(), symlink_target: None },
+			WalkedEntry { pub_id, maybe_object_id, iso_file_path: f(root_path.join("broken"), false), metadata, symlink_target: None },
+		]
+		.into_iter()
+		.collect::<HashSet<_>>();
+
+		let actual = run_test(
+			root_path,
+			IndexerRuler::default(),
+			SymlinkPolicy::Follow,
+			followed,
+		)
+		.await;
+
+		// Links inside the location, cycles and broken links fall back to aliases
+		for entry in actual {
+			let name = entry.iso_file_path.to_parts().name;
+			assert_eq!(
+				entry.symlink_target.is_some(),
+				matches!(name, "readme_link" | "loop" | "broken"),
+				"unexpected symlink target for {name}: {:?}",
+				entry.symlink_target
+			);
+		}
+	}
+
+	#[tokio::test]
+	#[traced_test]
+	async fn test_preview_only_photos() {
+		let root = prepare_location().await;
+		let root_path = root.path();
+
+		let indexer_ruler = IndexerRuler::new(vec![new_indexer_rule(
+			"only photos",
+			false,
+			vec![RulePerKind::AcceptFilesByGlob(
+				vec![],
+				GlobSetBuilder::new()
+					.add(Glob::new("{*.png,*.jpg,*.jpeg}").unwrap())
+					.build()
+					.unwrap(),
+			)],
+		)]);
+
+		let preview =
+			preview_indexer_rules(root_path, &indexer_ruler, SymlinkPolicy::Ignore, 10, 1000)
+				.await
+				.unwrap();
+
+		assert!(preview.errors.is_empty(), "errors: {:#?}", preview.errors);
+		assert!(!preview.truncated);
+
+		let accepted = preview
+			.accepted
+			.into_iter()
+			.map(|entry| (entry.path, entry.decided_by))
+			.collect::<HashSet<_>>();
+
+		let accepted_photo = Some(RuleKind::AcceptFilesByGlob);
+
+		assert_eq!(
+			accepted,
+			[
+				(root_path.join("photos"), None),
+				(root_path.join("photos/photo1.png"), accepted_photo),
+				(root_path.join("photos/photo2.jpg"), accepted_photo),
+				(root_path.join("photos/photo3.jpeg"), accepted_photo),
+			]
+			.into_iter()
+			.collect()
+		);
+
+		assert!(preview
+			.rejected
+			.iter()
+			.any(|entry| entry.path == root_path.join("photos/text.txt")
+				&& entry.decided_by == Some(RuleKind::AcceptFilesByGlob)));
+
+		let truncated =
+			preview_indexer_rules(root_path, &indexer_ruler, SymlinkPolicy::Ignore, 10, 2)
+				.await
+				.unwrap();
+
+		assert!(truncated.truncated);
+		assert_eq!(truncated.accepted.len() + truncated.rejected.len(), 2);
+	}
+
+	#[cfg(unix)]
+	async fn walk_collecting_outputs(
+		root_path: &Path,
+		snapshots: Option<Arc<DirectorySnapshots>>,
+	) -> Vec<Output<DummyDBProxy, DummyIsoPathFactory>> {
+		let system = TaskSystem::new();
+
+		let handle = system
+			.dispatch(
+				Walker::new_deep(
+					root_path.to_path_buf(),
+					Arc::new(root_path.to_path_buf()),
+					IndexerRuler::default(),
+					SymlinkPolicy::Ignore,
+					Vec::new().into(),
+					DummyIsoPathFactory {
+						root_path: Arc::new(root_path.to_path_buf()),
+					},
+					DummyDBProxy,
+				)
+				.unwrap()
+				.with_snapshots(snapshots),
+			)
+			.await
+			.unwrap();
+
+		let group = FuturesUnordered::new();
+
+		group.push(handle);
+
+		let mut group = group.lend_mut();
+
+		let mut outputs = Vec::new();
+
+		while let Some((group, task_result)) = group.next().await {
+			let TaskStatus::Done((_task_id, TaskOutput::Out(output))) = task_result.unwrap() else {
+				panic!("unexpected task output")
+			};
+
+			let mut output = *output
+				.downcast::<Output<DummyDBProxy, DummyIsoPathFactory>>()
+				.unwrap();
+
+			assert!(output.errors.is_empty(), "errors: {:#?}", output.errors);
+
+			group.extend(
+				system
+					.dispatch_many(mem::take(&mut output.keep_walking_tasks))
+					.await
+					.unwrap(),
+			);
+
+			outputs.push(output);
+		}
+
+		outputs
+	}
+
+	/// Directories modified right before the walk aren't snapshotted, so we move them to the past
+	#[cfg(unix)]
+	fn age_directories(path: &Path) {
+		for entry in std::fs::read_dir(path).unwrap() {
+			let entry = entry.unwrap();
+			if entry.file_type().unwrap().is_dir() {
+				age_directories(&entry.path());
+			}
+		}
+
+		std::fs::File::open(path)
+			.unwrap()
+			.set_modified(SystemTime::now() - Duration::from_secs(60 * 60))
+			.unwrap();
+	}
+
+	#[cfg(unix)]
+	#[tokio::test]
+	#[traced_test]
+	async fn test_walk_skips_unchanged_directories() {
+		let root = prepare_location().await;
+		let root_path = root.path();
+
+		age_directories(root_path);
+
+		let first_walk = walk_collecting_outputs(root_path, None).await;
+		assert!(first_walk.iter().all(|output| !output.is_unchanged));
+
+		let mut snapshots = DirectorySnapshots::new([0; 32]);
+		let walked_directories_count = first_walk.len();
+		snapshots.merge(
+			root_path,
+			first_walk
+				.into_iter()
+				.filter_map(|output| output.snapshot)
+				.collect(),
+		);
+		let snapshots = Arc::new(snapshots);
+
+		let second_walk = walk_collecting_outputs(root_path, Some(Arc::clone(&snapshots))).await;
+		assert_eq!(second_walk.len(), walked_directories_count);
+		assert!(second_walk
+			.iter()
+			.all(|output| output.is_unchanged && output.to_create.is_empty()));
+
+		fs::File::create(root_path.join("photos/photo4.png"))
+			.await
+			.unwrap();
+
+		let (changed, unchanged) = walk_collecting_outputs(root_path, Some(snapshots))
+			.await
+			.into_iter()
+			.partition::<Vec<_>, _>(|output| !output.is_unchanged);
+
+		assert_eq!(unchanged.len(), walked_directories_count - 1);
+		assert_eq!(changed.len(), 1);
+		assert_eq!(
+			changed[0].directory_iso_file_path,
+			IsolatedFilePathData::new(0, root_path, root_path.join("photos"), true).unwrap()
+		);
+		assert_eq!(changed[0].to_create.len(), 5);
+	}
 }
@@ -0,0 +1,195 @@
+use crate::{
+	indexer::{self, SymlinkPolicy},
+	NonCriticalError,
+};
+
+use sd_core_indexer_rules::{IndexerRuler, RuleKind};
+use sd_utils::error::FileIOError;
+
+use std::{
+	collections::{HashSet, VecDeque},
+	mem,
+	path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+use specta::Type;
+use tokio::fs;
+use tokio_stream::{wrappers::ReadDirStream, StreamExt};
+use tracing::{instrument, trace};
+
+use super::{
+	collect_metadata, extend_with_ignore_rules,
+	rules::{apply_indexer_rules, decide_path, PathDecision},
+	ToWalkEntry,
+};
+
+#[derive(Debug, Serialize, Type)]
+pub struct PreviewedEntry {
+	pub path: PathBuf,
+	pub is_dir: bool,
+	/// Kind of the rule that decided this entry, `None` if no rule applied to it or if it was only
+	/// accepted for being an ancestor of an accepted entry
+	pub decided_by: Option<RuleKind>,
+}
+
+#[derive(Debug, Default, Serialize, Type)]
+pub struct IndexerRulesPreview {
+	pub accepted: Vec<PreviewedEntry>,
+	pub rejected: Vec<PreviewedEntry>,
+	/// The walk stopped early because it reached the maximum number of entries
+	pub truncated: bool,
+	pub errors: Vec<NonCriticalError>,
+}
+
+/// Walks `root` applying the indexer rules exactly like the [`super::Walker`] task would, but
+/// without touching the database, going at most `max_depth` directories down and deciding at most
+/// `max_entries` entries.
+#[instrument(skip(root, indexer_ruler), fields(root = %root.as_ref().display()), err)]
+pub async fn preview_indexer_rules(
+	root: impl AsRef<Path> + Send,
+	indexer_ruler: &IndexerRuler,
+	symlink_policy: SymlinkPolicy,
+	max_depth: usize,
+	max_entries: usize,
+) -> Result<IndexerRulesPreview, indexer::Error> {
+	let root = root.as_ref();
+
+	let mut preview = IndexerRulesPreview::default();
+	let mut to_walk = VecDeque::from([(ToWalkEntry::from(root), 0)]);
+
+	while let Some((
+		ToWalkEntry {
+			path,
+			parent_dir_accepted_by_its_children,
+			followed_symlinks,
+		},
+		depth,
+	)) = to_walk.pop_front()
+	{
+		// Each directory has its own git and ignore files rules, as the walker task does
+		let mut indexer_ruler = indexer_ruler.clone();
+		extend_with_ignore_rules(&mut indexer_ruler, root, root, &path).await;
+
+		let mut read_dir_stream = match fs::read_dir(&path).await {
+			Ok(read_dir) => ReadDirStream::new(read_dir),
+			Err(e) if depth == 0 => {
+				return Err(indexer::Error::FileIO(
+					(&path, e, "Failed to open directory to read its entries").into(),
+				));
+			}
+			Err(e) => {
+				preview.errors.push(
+					indexer::NonCriticalIndexerError::FailedDirectoryEntry(
+						FileIOError::from((&path, e)).to_string(),
+					)
+					.into(),
+				);
+				continue;
+			}
+		};
+
+		let mut found_paths = Vec::new();
+		while let Some(res) = read_dir_stream.next().await {
+			match res {
+				Ok(dir_entry) => found_paths.push(dir_entry.path()),
+				Err(e) => preview.errors.push(
+					indexer::NonCriticalIndexerError::FailedDirectoryEntry(
+						FileIOError::from((&path, e)).to_string(),
+					)
+					.into(),
+				),
+			}
+		}
+
+		let mut paths_and_metadatas = collect_metadata(
+			&mut found_paths,
+			root,
+			symlink_policy,
+			&[],
+			&followed_symlinks,
+			&mut preview.errors,
+		)
+		.await;
+
+		let mut paths_metadatas_and_acceptance = apply_indexer_rules(
+			&mut paths_and_metadatas,
+			&indexer_ruler,
+			&mut preview.errors,
+		)
+		.await
+		.into_iter()
+		.collect::<Vec<_>>();
+		paths_metadatas_and_acceptance.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+		let mut maybe_to_keep_walking = (depth < max_depth).then(Vec::new);
+
+		for (current_path, (metadata, acceptance_per_rule_kind)) in paths_metadatas_and_acceptance {
+			if preview.accepted.len() + preview.rejected.len() >= max_entries {
+				trace!("Reached the maximum number of entries to preview");
+				preview.truncated = true;
+				break;
+			}
+
+			match decide_path(
+				&current_path,
+				&metadata,
+				&acceptance_per_rule_kind,
+				parent_dir_accepted_by_its_children,
+				&followed_symlinks,
+				&mut maybe_to_keep_walking,
+			) {
+				PathDecision::Accepted(decided_by) => preview.accepted.push(PreviewedEntry {
+					path: current_path,
+					is_dir: metadata.is_dir,
+					decided_by,
+				}),
+				PathDecision::Rejected(decided_by) => preview.rejected.push(PreviewedEntry {
+					path: current_path,
+					is_dir: metadata.is_dir,
+					decided_by: Some(decided_by),
+				}),
+			}
+		}
+
+		if preview.truncated {
+			break;
+		}
+
+		to_walk.extend(
+			maybe_to_keep_walking
+				.into_iter()
+				.flatten()
+				.map(|to_walk_entry| (to_walk_entry, depth + 1)),
+		);
+	}
+
+	// Just like the walker does, rejected directories are indexed anyway if any of their
+	// descendants were accepted
+	let accepted_ancestors = preview
+		.accepted
+		.iter()
+		.flat_map(|entry| {
+			entry
+				.path
+				.ancestors()
+				.skip(1)
+				.take_while(|&ancestor| ancestor != root)
+		})
+		.map(Path::to_path_buf)
+		.collect::<HashSet<_>>();
+
+	let (ancestors, rejected) = mem::take(&mut preview.rejected)
+		.into_iter()
+		.partition::<Vec<_>, _>(|entry| accepted_ancestors.contains(&entry.path));
+
+	preview.rejected = rejected;
+	preview
+		.accepted
+		.extend(ancestors.into_iter().map(|entry| PreviewedEntry {
+			decided_by: None,
+			..entry
+		}));
+
+	Ok(preview)
+}
@@ -27,8 +27,6 @@ pub(super) async fn apply_indexer_rules(
 ) -> HashMap<PathBuf, (InnerMetadata, HashMap<RuleKind, Vec<bool>>)> {
 	paths_and_metadatas
 		.drain()
-		// TODO: Hard ignoring symlinks for now, but this should be configurable
-		.filter(|(_, metadata)| !metadata.is_symlink)
 		.map(|(current_path, metadata)| async {
 			indexer_ruler
 				.apply_all(&current_path, &metadata)
@@ -46,10 +44,13 @@ pub(super) async fn apply_indexer_rules(
 		.collect()
 }
 
+#[allow(clippy::too_many_arguments)]
 pub(super) async fn process_rules_results(
 	root: &Arc<PathBuf>,
 	iso_file_path_factory: &impl IsoFilePathFactory,
 	parent_dir_accepted_by_its_children: Option<bool>,
+	followed_symlinks: &[PathBuf],
+	indexed_xattrs: &[String],
 	paths_metadatas_and_acceptance: &mut HashMap<
 		PathBuf,
 		(InnerMetadata, HashMap<RuleKind, Vec<bool>>),
@@ -67,6 +68,7 @@ pub(super) async fn process_rules_results(
 		iso_file_path_factory,
 		paths_metadatas_and_acceptance.drain(),
 		parent_dir_accepted_by_its_children,
+		followed_symlinks,
 		maybe_to_keep_walking,
 		collect_rejected_paths,
 		errors,
@@ -89,7 +91,8 @@ pub(super) async fn process_rules_results(
 							.map(|metadata| {
 								WalkingEntry {
 									iso_file_path: ancestor_iso_file_path,
-									metadata,
+									metadata: metadata.with_xattrs(&ancestor_path, indexed_xattrs),
+									symlink_target: None,
 								}
 								.into()
 							})
@@ -108,6 +111,7 @@ pub(super) async fn process_rules_results(
 	)
 }
 
+#[allow(clippy::too_many_arguments)]
 fn segregate_paths(
 	root: &Arc<PathBuf>,
 	iso_file_path_factory: &impl IsoFilePathFactory,
@@ -115,6 +119,7 @@ fn segregate_paths(
 		Item = (PathBuf, (InnerMetadata, HashMap<RuleKind, Vec<bool>>)),
 	>,
 	parent_dir_accepted_by_its_children: Option<bool>,
+	followed_symlinks: &[PathBuf],
 	maybe_to_keep_walking: &mut Option<Vec<ToWalkEntry>>,
 	collect_rejected_paths: bool,
 	errors: &mut Vec<NonCriticalError>,
@@ -130,21 +135,14 @@ fn segregate_paths(
 	let mut rejected = Vec::new();
 
 	for (current_path, (metadata, acceptance_per_rule_kind)) in paths_metadatas_and_acceptance {
-		// Accept by children has three states,
-		// None if we don't now yet or if this check doesn't apply
-		// Some(true) if this check applies and it passes
-		// Some(false) if this check applies and it was rejected
-		// and we pass the current parent state to its children
-		let mut accept_by_children_dir = parent_dir_accepted_by_its_children;
-
-		if !reject_path(
+		if let PathDecision::Accepted(_) = decide_path(
 			&current_path,
 			&metadata,
 			&acceptance_per_rule_kind,
-			&mut accept_by_children_dir,
+			parent_dir_accepted_by_its_children,
+			followed_symlinks,
 			maybe_to_keep_walking,
-		) && accept_by_children_dir.unwrap_or(true)
-		{
+		) {
 			accept_path_and_ancestors(
 				current_path,
 				metadata,
@@ -166,29 +164,103 @@ fn segregate_paths(
 	(accepted, accepted_ancestors, rejected)
 }
 
+/// Outcome of the indexer rules for a path, along with the kind of the rule that decided it, if any
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum PathDecision {
+	Accepted(Option<RuleKind>),
+	Rejected(RuleKind),
+}
+
+pub(super) fn decide_path(
+	current_path: &Path,
+	metadata: &InnerMetadata,
+	acceptance_per_rule_kind: &HashMap<RuleKind, Vec<bool>>,
+	parent_dir_accepted_by_its_children: Option<bool>,
+	followed_symlinks: &[PathBuf],
+	maybe_to_keep_walking: &mut Option<Vec<ToWalkEntry>>,
+) -> PathDecision {
+	// Accept by children has three states,
+	// None if we don't now yet or if this check doesn't apply
+	// Some(true) if this check applies and it passes
+	// Some(false) if this check applies and it was rejected
+	// and we pass the current parent state to its children
+	let mut accept_by_children_dir = parent_dir_accepted_by_its_children;
+
+	if let Some(kind) = reject_path(
+		current_path,
+		metadata,
+		acceptance_per_rule_kind,
+		&mut accept_by_children_dir,
+		followed_symlinks,
+		maybe_to_keep_walking,
+	) {
+		return PathDecision::Rejected(kind);
+	}
+
+	match accept_by_children_dir {
+		Some(false) => PathDecision::Rejected(RuleKind::AcceptIfChildrenDirectoriesArePresent),
+		Some(true) => PathDecision::Accepted(Some(RuleKind::AcceptIfChildrenDirectoriesArePresent)),
+		None => PathDecision::Accepted(
+			[RuleKind::AcceptFilesByGlob, RuleKind::AcceptFilesByKind]
+				.into_iter()
+				.find(|kind| acceptance_per_rule_kind.contains_key(kind)),
+		),
+	}
+}
+
 #[instrument(skip_all, fields(current_path = %current_path.display()))]
 fn reject_path(
 	current_path: &Path,
 	metadata: &InnerMetadata,
 	acceptance_per_rule_kind: &HashMap<RuleKind, Vec<bool>>,
 	accept_by_children_dir: &mut Option<bool>,
+	followed_symlinks: &[PathBuf],
 	maybe_to_keep_walking: &mut Option<Vec<ToWalkEntry>>,
-) -> bool {
-	IndexerRuler::rejected_by_reject_glob(acceptance_per_rule_kind)
-		|| IndexerRuler::rejected_by_git_ignore(acceptance_per_rule_kind)
-		|| (metadata.is_dir()
-			&& process_and_maybe_reject_by_directory_rules(
-				current_path,
-				acceptance_per_rule_kind,
-				accept_by_children_dir,
-				maybe_to_keep_walking,
-			)) || IndexerRuler::rejected_by_accept_glob(acceptance_per_rule_kind)
+) -> Option<RuleKind> {
+	if IndexerRuler::rejected_by_reject_glob(acceptance_per_rule_kind) {
+		return Some(RuleKind::RejectFilesByGlob);
+	}
+
+	if IndexerRuler::rejected_by_git_ignore(acceptance_per_rule_kind) {
+		return Some(RuleKind::IgnoredByGit);
+	}
+
+	if IndexerRuler::rejected_by_ignore_files(acceptance_per_rule_kind) {
+		return Some(RuleKind::IgnoredByIgnoreFiles);
+	}
+
+	if let Some(kind) = IndexerRuler::rejected_by_file_filters(acceptance_per_rule_kind) {
+		return Some(kind);
+	}
+
+	if metadata.is_dir()
+		&& process_and_maybe_reject_by_directory_rules(
+			current_path,
+			acceptance_per_rule_kind,
+			accept_by_children_dir,
+			followed_symlinks
+				.iter()
+				.chain(&metadata.followed_target)
+				.cloned()
+				.collect(),
+			maybe_to_keep_walking,
+		) {
+		return Some(RuleKind::RejectIfChildrenDirectoriesArePresent);
+	}
+
+	if IndexerRuler::rejected_by_accept_glob(acceptance_per_rule_kind) {
+		return Some(RuleKind::AcceptFilesByGlob);
+	}
+
+	IndexerRuler::rejected_by_accept_kind(acceptance_per_rule_kind)
+		.then_some(RuleKind::AcceptFilesByKind)
 }
 
 fn process_and_maybe_reject_by_directory_rules(
 	current_path: &Path,
 	acceptance_per_rule_kind: &HashMap<RuleKind, Vec<bool>>,
 	accept_by_children_dir: &mut Option<bool>,
+	followed_symlinks: Vec<PathBuf>,
 	maybe_to_keep_walking: &mut Option<Vec<ToWalkEntry>>,
 ) -> bool {
 	// If it is a directory, first we check if we must reject it and its children entirely
@@ -219,6 +291,7 @@ fn process_and_maybe_reject_by_directory_rules(
 		to_keep_walking.push(ToWalkEntry {
 			path: current_path.to_path_buf(),
 			parent_dir_accepted_by_its_children: *accept_by_children_dir,
+			followed_symlinks,
 		});
 	}
 
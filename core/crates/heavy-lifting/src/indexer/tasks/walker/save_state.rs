@@ -1,4 +1,7 @@
-use crate::{Error, NonCriticalError};
+use crate::{
+	indexer::{DirectorySnapshots, SymlinkPolicy},
+	Error, NonCriticalError,
+};
 
 use sd_core_file_path_helper::IsolatedFilePathData;
 use sd_core_indexer_rules::{IndexerRuler, RuleKind};
@@ -8,7 +11,7 @@ use std::{
 	collections::{HashMap, HashSet},
 	path::PathBuf,
 	sync::Arc,
-	time::Duration,
+	time::{Duration, SystemTime},
 };
 
 use sd_task_system::{SerializableTask, TaskId};
@@ -28,8 +31,16 @@ pub(super) struct WalkDirSaveState {
 	entry: ToWalkEntry,
 	root: Arc<PathBuf>,
 	entry_iso_file_path: IsolatedFilePathData<'static>,
+	#[serde(default)]
+	symlink_policy: SymlinkPolicy,
+	#[serde(default)]
+	indexed_xattrs: Arc<[String]>,
 
 	stage: WalkerStageSaveState,
+	#[serde(default)]
+	directory_modified_at: Option<(SystemTime, SystemTime)>,
+	#[serde(default)]
+	found_entries_count: usize,
 
 	errors: Vec<NonCriticalError>,
 	scan_time: Duration,
@@ -67,7 +78,9 @@ impl From<WalkerStage> for WalkerStageSaveState {
 	fn from(stage: WalkerStage) -> Self {
 		match stage {
 			// We can't store the current state of `ReadDirStream` so we start again from the beginning
-			WalkerStage::Start | WalkerStage::Walking { .. } => Self::Start,
+			WalkerStage::Start | WalkerStage::Walking { .. } | WalkerStage::Unchanged { .. } => {
+				Self::Start
+			}
 			WalkerStage::CollectingMetadata { found_paths } => {
 				Self::CollectingMetadata { found_paths }
 			}
@@ -161,7 +174,12 @@ where
 {
 	type SerializeError = rmp_serde::encode::Error;
 	type DeserializeError = rmp_serde::decode::Error;
-	type DeserializeCtx = (IndexerRuler, DBProxy, IsoPathFactory);
+	type DeserializeCtx = (
+		IndexerRuler,
+		DBProxy,
+		IsoPathFactory,
+		Option<Arc<DirectorySnapshots>>,
+	);
 
 	async fn serialize(self) -> Result<Vec<u8>, Self::SerializeError> {
 		let Self {
@@ -169,7 +187,11 @@ where
 			entry,
 			root,
 			entry_iso_file_path,
+			symlink_policy,
+			indexed_xattrs,
 			stage,
+			directory_modified_at,
+			found_entries_count,
 			errors,
 			scan_time,
 			is_shallow,
@@ -181,7 +203,11 @@ where
 			entry,
 			root,
 			entry_iso_file_path,
+			symlink_policy,
+			indexed_xattrs,
 			stage: stage.into(),
+			directory_modified_at,
+			found_entries_count,
 			errors,
 			scan_time,
 		})
@@ -189,7 +215,7 @@ where
 
 	async fn deserialize(
 		data: &[u8],
-		(indexer_ruler, db_proxy, iso_file_path_factory): Self::DeserializeCtx,
+		(indexer_ruler, db_proxy, iso_file_path_factory, snapshots): Self::DeserializeCtx,
 	) -> Result<Self, Self::DeserializeError> {
 		rmp_serde::from_slice(data).map(
 			|WalkDirSaveState {
@@ -197,7 +223,11 @@ where
 			     entry,
 			     root,
 			     entry_iso_file_path,
+			     symlink_policy,
+			     indexed_xattrs,
 			     stage,
+			     directory_modified_at,
+			     found_entries_count,
 			     errors,
 			     scan_time,
 			     is_shallow,
@@ -207,9 +237,14 @@ where
 				root,
 				entry_iso_file_path,
 				indexer_ruler,
+				symlink_policy,
+				indexed_xattrs,
+				snapshots,
 				iso_file_path_factory,
 				db_proxy,
 				stage: stage.into(),
+				directory_modified_at,
+				found_entries_count,
 				errors,
 				scan_time,
 				is_shallow,
@@ -1,10 +1,12 @@
-use crate::{Error, NonCriticalError, UpdateEvent};
+use crate::{media_processor::VideoPreviewsOptions, Error, NonCriticalError, UpdateEvent};
 
 use sd_core_sync::Manager as SyncManager;
 
+use sd_media_metadata::exif::ReverseGeocoder;
 use sd_prisma::prisma::PrismaClient;
 use sd_task_system::{
-	BaseTaskDispatcher, Task, TaskDispatcher, TaskHandle, TaskRemoteController, TaskSystemError,
+	BaseTaskDispatcher, IntoTask, Task, TaskDispatcher, TaskHandle, TaskRemoteController,
+	TaskSystemError, Throttle,
 };
 
 use std::{
@@ -24,7 +26,7 @@ use async_channel as chan;
 use chrono::{DateTime, Utc};
 use futures::{stream, Future, FutureExt, StreamExt};
 use futures_concurrency::{
-	future::{Join, TryJoin},
+	future::{Join, Race, TryJoin},
 	stream::Merge,
 };
 use serde::{Deserialize, Serialize};
@@ -103,6 +105,16 @@ pub trait OuterContext: Send + Sync + Clone + 'static {
 	fn query_invalidator(&self) -> impl Fn(&'static str) + Send + Sync;
 	fn report_update(&self, update: UpdateEvent);
 	fn get_data_directory(&self) -> &Path;
+	/// Throttles limiting how fast the indexer walks the location with this `pub_id`, in the
+	/// order they must be acquired
+	fn indexer_throttles(&self, location_pub_id: &[u8]) -> Vec<Throttle>;
+	/// Which extra previews the media processor generates for videos
+	fn video_previews_options(&self) -> VideoPreviewsOptions;
+	/// The loaded reverse geocoder, if any, to name the places where photos were taken
+	fn reverse_geocoder(&self) -> Option<Arc<ReverseGeocoder>>;
+	/// The loaded image labeler model, if any, as the media processor doesn't label images without it
+	#[cfg(feature = "ai")]
+	fn image_labeler(&self) -> Option<Arc<sd_ai::image_labeler::ImageLabeler>>;
 }
 
 pub trait JobContext<OuterCtx: OuterContext>: OuterContext {
@@ -1128,6 +1140,69 @@ impl TaskDispatcher<Error> for JobTaskDispatcher {
 	}
 }
 
+impl JobTaskDispatcher {
+	/// Same as [`TaskDispatcher::dispatch`], but the task is only dispatched after every throttle
+	/// allows it to run, keeping their permits until the task is finalized
+	pub async fn dispatch_throttled(
+		&self,
+		throttles: &[Throttle],
+		into_task: impl IntoTask<Error>,
+	) -> Result<TaskHandle<Error>, DispatcherError> {
+		let boxed_task = into_task.into_task();
+
+		if throttles.is_empty() {
+			return self.dispatch_boxed(boxed_task).await;
+		}
+
+		let permits = loop {
+			match self.wait_for_dispatch_approval().await {
+				DispatchApproval::Canceled => {
+					return Err(DispatcherError::JobCanceled(self.job_id))
+				}
+				DispatchApproval::Shutdown => {
+					return Err(DispatcherError::Shutdown(vec![boxed_task]))
+				}
+				DispatchApproval::Approved => { /* Now we wait for the throttles */ }
+			}
+
+			// Cloning the receiver, so a state change is still seen when we wait for approval again
+			let mut running_state_rx = self.running_state.lock().await.clone();
+
+			let acquire_permits = async {
+				let mut permits = Vec::with_capacity(throttles.len());
+				for throttle in throttles {
+					permits.push(throttle.acquire().await);
+				}
+				Some(permits)
+			};
+
+			let running_state_changed = async {
+				running_state_rx
+					.changed()
+					.await
+					.expect("job running state watch channel unexpectedly closed");
+				None
+			};
+
+			if let Some(permits) = (acquire_permits, running_state_changed).race().await {
+				break permits;
+			}
+
+			trace!("Job running state changed while waiting for throttles");
+		};
+
+		let handle = self.dispatch_boxed(boxed_task).await?;
+
+		let remote_controller = handle.remote_controller();
+		spawn(async move {
+			remote_controller.wait_finalized().await;
+			drop(permits);
+		});
+
+		Ok(handle)
+	}
+}
+
 enum DispatchApproval {
 	Approved,
 	Canceled,
@@ -4,12 +4,15 @@ use sd_core_prisma_helpers::ObjectPubId;
 use sd_core_sync::Manager as SyncManager;
 
 use sd_file_ext::extensions::{Extension, ImageExtension, ALL_IMAGE_EXTENSIONS};
-use sd_media_metadata::ExifMetadata;
+use sd_media_metadata::{
+	exif::{MediaLocation, PlaceName, ReverseGeocoder},
+	ExifMetadata,
+};
 use sd_prisma::{
-	prisma::{exif_data, object, PrismaClient},
+	prisma::{exif_data, object, PrismaClient, SortOrder},
 	prisma_sync,
 };
-use sd_sync::{option_sync_db_entry, OperationFactory};
+use sd_sync::{option_sync_db_entry, sync_db_entry, OperationFactory};
 use sd_utils::chain_optional_iter;
 
 use std::{path::Path, sync::LazyLock};
@@ -18,6 +21,9 @@ use futures_concurrency::future::TryJoin;
 
 use super::from_slice_option_to_option;
 
+/// How many media are named at a time, so we never hold a whole library in memory
+const RESOLVE_PLACES_BATCH_SIZE: i64 = 1000;
+
 pub static AVAILABLE_EXTENSIONS: LazyLock<Vec<Extension>> = LazyLock::new(|| {
 	ALL_IMAGE_EXTENSIONS
 		.iter()
@@ -44,6 +50,7 @@ fn to_query(
 		resolution,
 		date_taken,
 		location,
+		place,
 		camera_data,
 		artist,
 		description,
@@ -52,8 +59,18 @@ fn to_query(
 	}: ExifMetadata,
 	object_id: exif_data::object_id::Type,
 ) -> (Vec<(&'static str, rmpv::Value)>, exif_data::Create) {
+	let (latitude, longitude) = location.as_ref().map(MediaLocation::coordinates).unzip();
+	let (city, region, country) = place.map_or((None, None, None), |place| {
+		(Some(place.city), place.region, Some(place.country))
+	});
+	let megapixels = (resolution.width > 0 && resolution.height > 0)
+		.then(|| f64::from(resolution.width) * f64::from(resolution.height) / 1_000_000.0);
+
 	let (sync_params, db_params) = chain_optional_iter(
-		[],
+		[sync_db_entry!(
+			camera_data.orientation.as_str().to_string(),
+			exif_data::orientation
+		)],
 		[
 			option_sync_db_entry!(
 				serde_json::to_vec(&camera_data).ok(),
@@ -73,6 +90,17 @@ fn to_query(
 				date_taken.map(|x| x.unix_timestamp()),
 				exif_data::epoch_time
 			),
+			option_sync_db_entry!(latitude, exif_data::latitude),
+			option_sync_db_entry!(longitude, exif_data::longitude),
+			option_sync_db_entry!(city, exif_data::city),
+			option_sync_db_entry!(region, exif_data::region),
+			option_sync_db_entry!(country, exif_data::country),
+			option_sync_db_entry!(camera_data.device_make, exif_data::device_make),
+			option_sync_db_entry!(camera_data.device_model, exif_data::device_model),
+			option_sync_db_entry!(camera_data.lens_model, exif_data::lens_model),
+			option_sync_db_entry!(camera_data.focal_length, exif_data::focal_length),
+			option_sync_db_entry!(camera_data.iso, exif_data::iso),
+			option_sync_db_entry!(megapixels, exif_data::megapixels),
 		],
 	)
 	.into_iter()
@@ -87,18 +115,26 @@ fn to_query(
 	)
 }
 
+/// Extracts the EXIF data of an image, naming the place where it was taken when we have a
+/// reverse geocoder
 pub async fn extract(
 	path: impl AsRef<Path> + Send,
+	reverse_geocoder: Option<&ReverseGeocoder>,
 ) -> Result<Option<ExifMetadata>, media_processor::NonCriticalMediaProcessorError> {
 	let path = path.as_ref();
 
-	ExifMetadata::from_path(&path).await.map_err(|e| {
+	let mut exif_data = ExifMetadata::from_path(&path).await.map_err(|e| {
 		media_data_extractor::NonCriticalMediaDataExtractorError::FailedToExtractImageMediaData(
 			path.to_path_buf(),
 			e.to_string(),
 		)
-		.into()
-	})
+	})?;
+
+	if let (Some(exif_data), Some(reverse_geocoder)) = (&mut exif_data, reverse_geocoder) {
+		exif_data.resolve_place(reverse_geocoder);
+	}
+
+	Ok(exif_data)
 }
 
 pub async fn save(
@@ -136,6 +172,79 @@ pub async fn save(
 		.map(|created_vec| created_vec.len() as u64)
 }
 
+/// Names the places of media with a location but no place yet, like media extracted before the
+/// reverse geocoder was available, returning how many were named.
+///
+/// Media far from every place in the dataset stays unnamed, so it's looked up again each time.
+pub async fn resolve_missing_places(
+	reverse_geocoder: &ReverseGeocoder,
+	db: &PrismaClient,
+	sync: &SyncManager,
+) -> Result<u64, sd_core_sync::Error> {
+	let mut resolved = 0;
+	let mut cursor = exif_data::id::Type::MIN;
+
+	loop {
+		let exif_datas = db
+			.exif_data()
+			.find_many(vec![
+				exif_data::id::gt(cursor),
+				exif_data::latitude::not(None),
+				exif_data::longitude::not(None),
+				exif_data::city::equals(None),
+			])
+			.order_by(exif_data::id::order(SortOrder::Asc))
+			.take(RESOLVE_PLACES_BATCH_SIZE)
+			.select(exif_data::select!({ id latitude longitude object: select { pub_id } }))
+			.exec()
+			.await?;
+
+		let Some(last) = exif_datas.last() else {
+			return Ok(resolved);
+		};
+		cursor = last.id;
+
+		let (sync_params, db_params) = exif_datas
+			.into_iter()
+			.filter_map(|data| {
+				let place = reverse_geocoder.lookup((data.latitude?, data.longitude?))?;
+				let sync_id = || prisma_sync::exif_data::SyncId {
+					object: prisma_sync::object::SyncId {
+						pub_id: data.object.pub_id.clone(),
+					},
+				};
+
+				let (sync_params, db_params) = chain_optional_iter(
+					[
+						sync_db_entry!(place.city, exif_data::city),
+						sync_db_entry!(place.country, exif_data::country),
+					],
+					[option_sync_db_entry!(place.region, exif_data::region)],
+				)
+				.into_iter()
+				.unzip::<_, _, Vec<_>, Vec<_>>();
+
+				Some((
+					sync_params
+						.into_iter()
+						.map(|(field, value)| sync.shared_update(sync_id(), field, value))
+						.collect::<Vec<_>>(),
+					db.exif_data()
+						.update(exif_data::id::equals(data.id), db_params)
+						.select(exif_data::select!({ id })),
+				))
+			})
+			.unzip::<_, _, Vec<_>, Vec<_>>();
+
+		if !db_params.is_empty() {
+			resolved += sync
+				.write_ops(db, (sync_params.into_iter().flatten().collect(), db_params))
+				.await?
+				.len() as u64;
+		}
+	}
+}
+
 #[must_use]
 pub fn from_prisma_data(
 	exif_data::Data {
@@ -147,6 +256,9 @@ pub fn from_prisma_data(
 		description,
 		copyright,
 		exif_version,
+		city,
+		region,
+		country,
 		..
 	}: exif_data::Data,
 ) -> ExifMetadata {
@@ -155,6 +267,11 @@ pub fn from_prisma_data(
 		date_taken: from_slice_option_to_option(media_date).unwrap_or_default(),
 		resolution: from_slice_option_to_option(resolution).unwrap_or_default(),
 		location: from_slice_option_to_option(media_location),
+		place: city.zip(country).map(|(city, country)| PlaceName {
+			city,
+			region,
+			country,
+		}),
 		artist,
 		description,
 		copyright,
@@ -14,6 +14,7 @@ use sd_media_metadata::{
 		program::Program,
 		stream::Stream,
 		video_props::VideoProps,
+		waveform::Waveform,
 	},
 	FFmpegMetadata,
 };
@@ -30,7 +31,7 @@ use std::{collections::HashMap, path::Path, sync::LazyLock};
 
 use futures_concurrency::future::TryJoin;
 use prisma_client_rust::QueryError;
-use tracing::error;
+use tracing::{error, warn};
 
 use super::from_slice_option_to_option;
 
@@ -98,18 +99,32 @@ pub const fn can_extract_for_video(video_extension: VideoExtension) -> bool {
 	)
 }
 
+/// Extracts the media data of an audio or video file, along with the waveform of audio objects
 pub async fn extract(
 	path: impl AsRef<Path> + Send,
+	is_audio: bool,
 ) -> Result<FFmpegMetadata, media_processor::NonCriticalMediaProcessorError> {
 	let path = path.as_ref();
 
-	FFmpegMetadata::from_path(&path).await.map_err(|e| {
+	let mut ffmpeg_metadata = FFmpegMetadata::from_path(&path).await.map_err(|e| {
 		media_data_extractor::NonCriticalMediaDataExtractorError::FailedToExtractImageMediaData(
 			path.to_path_buf(),
 			e.to_string(),
 		)
-		.into()
-	})
+	})?;
+
+	// Videos already have thumbnails, the waveform is the preview of audio files. When it fails
+	// we keep an empty one, so the file isn't extracted again on every scan to get a waveform
+	if is_audio {
+		ffmpeg_metadata.waveform = Some(
+			Waveform::from_path(&path)
+				.await
+				.map_err(|e| warn!(?e, path = %path.display(), "Failed to extract audio waveform;"))
+				.unwrap_or_default(),
+		);
+	}
+
+	Ok(ffmpeg_metadata)
 }
 
 pub async fn save(
@@ -118,46 +133,60 @@ pub async fn save(
 ) -> Result<u64, sd_core_sync::Error> {
 	ffmpeg_datas
 		.into_iter()
-		.map(
-			move |(
-				FFmpegMetadata {
-					formats,
-					duration,
-					start_time,
-					bit_rate,
-					chapters,
-					programs,
-					metadata,
-				},
-				object_id,
-			)| {
-				db._transaction()
-					.with_timeout(30 * 1000)
-					.run(move |db| async move {
-						let data_id = create_ffmpeg_data(
-							formats, bit_rate, duration, start_time, metadata, object_id, &db,
-						)
+		.map(move |(ffmpeg_metadata, object_id)| {
+			let search_params = search_params(&ffmpeg_metadata);
+
+			let FFmpegMetadata {
+				formats,
+				duration,
+				start_time,
+				bit_rate,
+				chapters,
+				programs,
+				metadata,
+				waveform,
+			} = ffmpeg_metadata;
+
+			db._transaction()
+				.with_timeout(30 * 1000)
+				.run(move |db| async move {
+					// Media extracted again, like audio without a waveform yet, replaces its old data
+					db.ffmpeg_data()
+						.delete_many(vec![ffmpeg_data::object_id::equals(object_id)])
+						.exec()
 						.await?;
 
-						create_ffmpeg_chapters(data_id, chapters, &db).await?;
+					let data_id = create_ffmpeg_data(
+						formats,
+						bit_rate,
+						duration,
+						start_time,
+						metadata,
+						waveform,
+						search_params,
+						object_id,
+						&db,
+					)
+					.await?;
 
-						let streams = create_ffmpeg_programs(data_id, programs, &db).await?;
+					create_ffmpeg_chapters(data_id, chapters, &db).await?;
 
-						let codecs = create_ffmpeg_streams(data_id, streams, &db).await?;
+					let streams = create_ffmpeg_programs(data_id, programs, &db).await?;
 
-						let (audio_props, video_props) =
-							create_ffmpeg_codecs(data_id, codecs, &db).await?;
+					let codecs = create_ffmpeg_streams(data_id, streams, &db).await?;
 
-						(
-							create_ffmpeg_audio_props(audio_props, &db),
-							create_ffmpeg_video_props(video_props, &db),
-						)
-							.try_join()
-							.await
-							.map(|_| ())
-					})
-			},
-		)
+					let (audio_props, video_props) =
+						create_ffmpeg_codecs(data_id, codecs, &db).await?;
+
+					(
+						create_ffmpeg_audio_props(audio_props, &db),
+						create_ffmpeg_video_props(video_props, &db),
+					)
+						.try_join()
+						.await
+						.map(|_| ())
+				})
+		})
 		.collect::<Vec<_>>()
 		.try_join()
 		.await
@@ -165,12 +194,80 @@ pub async fn save(
 		.map_err(Into::into)
 }
 
+/// `FFmpeg` durations are in microseconds
+const AV_TIME_BASE: f64 = 1_000_000.0;
+
+/// The duration, bit rate and properties of the main streams of the media, copied to their own
+/// `ffmpeg_data` columns so we can filter and order by them
+#[must_use]
+pub fn search_params(
+	FFmpegMetadata {
+		duration,
+		bit_rate: (bit_rate_high, bit_rate_low),
+		programs,
+		..
+	}: &FFmpegMetadata,
+) -> Vec<ffmpeg_data::SetParam> {
+	let streams = programs
+		.iter()
+		.flat_map(|program| &program.streams)
+		.filter_map(|stream| stream.codec.as_ref().map(|codec| (stream, codec)));
+
+	// Media can have a few video streams, like cover art or previews, the main one is the largest
+	let main_video = streams
+		.clone()
+		.filter_map(|(stream, codec)| match &codec.props {
+			Some(Props::Video(props)) => Some((stream, props)),
+			_ => None,
+		})
+		.max_by_key(|(_, props)| i64::from(props.width) * i64::from(props.height));
+
+	let main_audio = streams
+		.clone()
+		.filter_map(|(_, codec)| match &codec.props {
+			Some(Props::Audio(props)) => Some(props),
+			_ => None,
+		})
+		.max_by_key(|props| props.channel_count());
+
+	let has_subtitles = streams
+		.clone()
+		.any(|(_, codec)| codec.kind.as_deref() == Some("subtitle"));
+
+	#[allow(clippy::cast_precision_loss)]
+	// SAFETY: durations are in microseconds, so they'd have to be longer than a century to lose
+	// precision on this cast
+	let duration_seconds = duration.map(|(duration_high, duration_low)| {
+		(i64::from(duration_high) << 32 | i64::from(duration_low)) as f64 / AV_TIME_BASE
+	});
+
+	vec![
+		ffmpeg_data::duration_seconds::set(duration_seconds),
+		ffmpeg_data::bits_per_second::set(Some(
+			i64::from(*bit_rate_high) << 32 | i64::from(*bit_rate_low),
+		)),
+		ffmpeg_data::width::set(main_video.map(|(_, props)| props.width)),
+		ffmpeg_data::height::set(main_video.map(|(_, props)| props.height)),
+		ffmpeg_data::frame_rate::set(main_video.and_then(|(stream, _)| {
+			(stream.frames_per_second_num > 0 && stream.frames_per_second_den > 0).then(|| {
+				f64::from(stream.frames_per_second_num) / f64::from(stream.frames_per_second_den)
+			})
+		})),
+		ffmpeg_data::audio_channels::set(main_audio.and_then(AudioProps::channel_count)),
+		ffmpeg_data::sample_rate::set(main_audio.and_then(|props| props.sample_rate)),
+		ffmpeg_data::has_subtitles::set(Some(has_subtitles)),
+	]
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn create_ffmpeg_data(
 	formats: Vec<String>,
 	(bit_rate_high, bit_rate_low): (i32, u32),
 	maybe_duration: Option<(i32, u32)>,
 	maybe_start_time: Option<(i32, u32)>,
 	metadata: Metadata,
+	waveform: Option<Waveform>,
+	search_params: Vec<ffmpeg_data::SetParam>,
 	object_id: i32,
 	db: &PrismaClient,
 ) -> Result<ffmpeg_data::id::Type, QueryError> {
@@ -200,7 +297,11 @@ async fn create_ffmpeg_data(
 						})
 						.ok(),
 				),
-			],
+				ffmpeg_data::waveform::set(waveform.as_ref().map(Waveform::to_bytes)),
+			]
+			.into_iter()
+			.chain(search_params)
+			.collect(),
 		)
 		.select(ffmpeg_data::select!({ id }))
 		.exec()
@@ -508,6 +609,7 @@ async fn create_ffmpeg_audio_props(
 							sample_format,
 							bit_per_sample,
 							channel_layout,
+							channels,
 						},
 					)| ffmpeg_media_audio_props::CreateUnchecked {
 						delay,
@@ -518,6 +620,7 @@ async fn create_ffmpeg_audio_props(
 							ffmpeg_media_audio_props::sample_format::set(sample_format),
 							ffmpeg_media_audio_props::bit_per_sample::set(bit_per_sample),
 							ffmpeg_media_audio_props::channel_layout::set(channel_layout),
+							ffmpeg_media_audio_props::channels::set(channels),
 						],
 					},
 				)
@@ -591,6 +694,7 @@ pub fn from_prisma_data(
 		start_time,
 		bit_rate,
 		metadata,
+		waveform,
 		chapters,
 		programs,
 		..
@@ -605,6 +709,10 @@ pub fn from_prisma_data(
 		chapters: chapters_from_prisma_data(chapters),
 		programs: programs_from_prisma_data(programs),
 		metadata: from_slice_option_to_option(metadata).unwrap_or_default(),
+		waveform: waveform
+			.filter(|waveform| !waveform.is_empty())
+			.as_deref()
+			.map(Waveform::from_bytes),
 	}
 }
 
@@ -731,6 +839,7 @@ fn codec_from_prisma_data(
 						sample_format,
 						bit_per_sample,
 						channel_layout,
+						channels,
 						..
 					}),
 					None,
@@ -741,6 +850,7 @@ fn codec_from_prisma_data(
 					sample_format,
 					bit_per_sample,
 					channel_layout,
+					channels,
 				})),
 				(
 					None,
@@ -0,0 +1,277 @@
+use crate::media_processor::{self, labels_generator::NonCriticalLabelsGeneratorError};
+
+use sd_core_prisma_helpers::ObjectPubId;
+use sd_core_sync::Manager as SyncManager;
+
+use sd_ai::image_labeler::{ImageLabeler, Labels};
+use sd_file_ext::extensions::{Extension, ALL_IMAGE_EXTENSIONS};
+use sd_prisma::{
+	prisma::{label, label_on_object, object, object_labeling, PrismaClient},
+	prisma_sync,
+};
+use sd_sync::OperationFactory;
+use sd_utils::msgpack;
+
+use std::{
+	collections::{HashMap, HashSet},
+	panic::{self, AssertUnwindSafe},
+	path::PathBuf,
+	sync::{Arc, LazyLock},
+};
+
+use chrono::{DateTime, FixedOffset, Utc};
+use tokio::task::spawn_blocking;
+
+use super::thumbnailer::{can_generate_thumbnail_for_image, load_scaled_image};
+
+/// The image labeler model shrinks images to 640x640 anyway, so we don't decode them any larger
+const TARGET_PX: f32 = 409_600.0;
+
+pub static AVAILABLE_EXTENSIONS: LazyLock<Vec<Extension>> = LazyLock::new(|| {
+	ALL_IMAGE_EXTENSIONS
+		.iter()
+		.copied()
+		.filter(|&ext| can_generate_thumbnail_for_image(ext))
+		.map(Extension::Image)
+		.collect()
+});
+
+/// Decodes and labels the image at `path` on a blocking thread, as both are CPU heavy
+pub async fn generate(
+	image_labeler: Arc<ImageLabeler>,
+	path: PathBuf,
+) -> Result<Labels, NonCriticalLabelsGeneratorError> {
+	spawn_blocking({
+		let path = path.clone();
+
+		move || {
+			panic::catch_unwind(AssertUnwindSafe(|| {
+				let image = load_scaled_image(&path, TARGET_PX).map_err(|e| {
+					NonCriticalLabelsGeneratorError::FailedToLoadImage(path.clone(), e.to_string())
+				})?;
+
+				image_labeler.labels(&image).map_err(|e| {
+					NonCriticalLabelsGeneratorError::FailedToGenerateLabels(
+						path.clone(),
+						e.to_string(),
+					)
+				})
+			}))
+			.unwrap_or_else(|_| {
+				Err(NonCriticalLabelsGeneratorError::FailedToGenerateLabels(
+					path.clone(),
+					"Internal panic on third party crate".to_string(),
+				))
+			})
+		}
+	})
+	.await
+	.unwrap_or_else(|e| {
+		Err(NonCriticalLabelsGeneratorError::FailedToGenerateLabels(
+			path,
+			e.to_string(),
+		))
+	})
+}
+
+/// Objects among `object_ids` which were already labeled by the `model_version` model
+pub async fn fetch_objects_labeled_with(
+	model_version: &str,
+	object_ids: Vec<object::id::Type>,
+	db: &PrismaClient,
+) -> Result<Vec<object::id::Type>, media_processor::Error> {
+	db.object_labeling()
+		.find_many(vec![
+			object_labeling::object_id::in_vec(object_ids),
+			object_labeling::model_version::equals(model_version.to_string()),
+		])
+		.select(object_labeling::select!({ object_id }))
+		.exec()
+		.await
+		.map(|labelings| {
+			labelings
+				.into_iter()
+				.map(|labeling| labeling.object_id)
+				.collect()
+		})
+		.map_err(Into::into)
+}
+
+/// Replaces the labels of objects with the ones generated by the `model_version` model, returning
+/// how many labels were assigned.
+///
+/// Labels are only ever assigned by the image labeler, so the previous ones of these objects are
+/// removed as a whole, which also cleans up labels a previous model version got wrong.
+pub async fn save(
+	labels_by_object: impl IntoIterator<Item = (object::id::Type, ObjectPubId, Labels)> + Send,
+	model_version: &str,
+	db: &PrismaClient,
+	sync: &SyncManager,
+) -> Result<u64, media_processor::Error> {
+	let labels_by_object = labels_by_object.into_iter().collect::<Vec<_>>();
+
+	if labels_by_object.is_empty() {
+		return Ok(0);
+	}
+
+	let object_ids = labels_by_object
+		.iter()
+		.map(|(object_id, _, _)| *object_id)
+		.collect::<Vec<_>>();
+
+	let date_created: DateTime<FixedOffset> = Utc::now().into();
+
+	let label_ids = get_or_create_labels(
+		labels_by_object
+			.iter()
+			.flat_map(|(_, _, labels)| labels.keys().cloned())
+			.collect(),
+		date_created,
+		db,
+		sync,
+	)
+	.await?;
+
+	let previous_labels = db
+		.label_on_object()
+		.find_many(vec![label_on_object::object_id::in_vec(object_ids.clone())])
+		.select(label_on_object::select!({ label: select { name } object: select { pub_id } }))
+		.exec()
+		.await?;
+
+	if !previous_labels.is_empty() {
+		sync.write_ops(
+			db,
+			(
+				previous_labels
+					.into_iter()
+					.map(|label_on_object| {
+						sync.relation_delete(prisma_sync::label_on_object::SyncId {
+							label: prisma_sync::label::SyncId {
+								name: label_on_object.label.name,
+							},
+							object: prisma_sync::object::SyncId {
+								pub_id: label_on_object.object.pub_id,
+							},
+						})
+					})
+					.collect(),
+				db.label_on_object()
+					.delete_many(vec![label_on_object::object_id::in_vec(object_ids.clone())]),
+			),
+		)
+		.await?;
+	}
+
+	let mut sync_params = Vec::new();
+	let mut db_params = Vec::new();
+
+	for (object_id, object_pub_id, labels) in labels_by_object {
+		for (name, confidence) in labels {
+			let confidence = f64::from(confidence);
+			let label_id = *label_ids
+				.get(&name)
+				.expect("labels were fetched or created above");
+
+			sync_params.extend(sync.relation_create(
+				prisma_sync::label_on_object::SyncId {
+					label: prisma_sync::label::SyncId { name },
+					object: prisma_sync::object::SyncId {
+						pub_id: object_pub_id.to_db(),
+					},
+				},
+				[(label_on_object::confidence::NAME, msgpack!(confidence))],
+			));
+
+			db_params.push(label_on_object::create_unchecked(
+				label_id,
+				object_id,
+				vec![
+					label_on_object::date_created::set(date_created),
+					label_on_object::confidence::set(Some(confidence)),
+				],
+			));
+		}
+	}
+
+	let assigned = if db_params.is_empty() {
+		0
+	} else {
+		sync.write_ops(
+			db,
+			(
+				sync_params,
+				db.label_on_object()
+					.create_many(db_params)
+					.skip_duplicates(),
+			),
+		)
+		.await?
+	};
+
+	db._batch((
+		db.object_labeling()
+			.delete_many(vec![object_labeling::object_id::in_vec(object_ids.clone())]),
+		db.object_labeling().create_many(
+			object_ids
+				.into_iter()
+				.map(|object_id| {
+					object_labeling::create_unchecked(model_version.to_string(), object_id, vec![])
+				})
+				.collect(),
+		),
+	))
+	.await?;
+
+	#[allow(clippy::cast_sign_loss)]
+	{
+		// SAFETY: `create_many` returns the amount of created rows, which is never negative
+		Ok(assigned as u64)
+	}
+}
+
+async fn get_or_create_labels(
+	mut names: HashSet<String>,
+	date_created: DateTime<FixedOffset>,
+	db: &PrismaClient,
+	sync: &SyncManager,
+) -> Result<HashMap<String, label::id::Type>, media_processor::Error> {
+	let mut label_ids = db
+		.label()
+		.find_many(vec![label::name::in_vec(names.iter().cloned().collect())])
+		.select(label::select!({ id name }))
+		.exec()
+		.await?
+		.into_iter()
+		.map(|label| {
+			names.remove(&label.name);
+			(label.name, label.id)
+		})
+		.collect::<HashMap<_, _>>();
+
+	if !names.is_empty() {
+		let (sync_params, db_params) = names
+			.into_iter()
+			.map(|name| {
+				(
+					sync.shared_create(
+						prisma_sync::label::SyncId { name: name.clone() },
+						[(label::date_created::NAME, msgpack!(&date_created))],
+					),
+					db.label()
+						.create(name, vec![label::date_created::set(Some(date_created))])
+						.select(label::select!({ id name })),
+				)
+			})
+			.unzip::<_, _, Vec<_>, Vec<_>>();
+
+		label_ids.extend(
+			sync.write_ops(db, (sync_params.into_iter().flatten().collect(), db_params))
+				.await?
+				.into_iter()
+				.map(|label| (label.name, label.id)),
+		);
+	}
+
+	Ok(label_ids)
+}
@@ -1,5 +1,8 @@
 pub mod exif_media_data;
 pub mod ffmpeg_media_data;
+#[cfg(feature = "ai")]
+pub mod image_labels;
+pub mod perceptual_hash;
 pub mod thumbnailer;
 
 #[must_use]
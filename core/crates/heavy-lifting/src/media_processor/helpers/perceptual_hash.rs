@@ -0,0 +1,177 @@
+use crate::media_processor::{
+	thumbnailer::{self, ThumbnailId},
+	ThumbnailKind,
+};
+
+use sd_core_prisma_helpers::CasId;
+
+use sd_file_ext::kind::ObjectKind;
+use sd_images::PerceptualHash;
+use sd_prisma::prisma::{file_path, object, perceptual_hash, PrismaClient, SortOrder};
+
+use std::{collections::HashMap, path::Path};
+
+use image::ImageFormat;
+use prisma_client_rust::not;
+use tokio::{fs, task::spawn_blocking};
+use tracing::{trace, warn};
+use uuid::Uuid;
+
+/// How many file paths are hashed before their hashes are saved, so we never hold the hashes of a
+/// whole library in memory
+const BACKFILL_BATCH_SIZE: i64 = 1000;
+
+/// Kinds of objects whose thumbnails get a perceptual hash, to find similar images and videos
+const HASHED_KINDS: [ObjectKind; 2] = [ObjectKind::Image, ObjectKind::Video];
+
+/// Saves the perceptual hashes of indexed thumbnails on the objects of their file paths, replacing
+/// the previous ones
+pub async fn save(
+	hashes: impl IntoIterator<Item = (ThumbnailId, PerceptualHash)> + Send,
+	db: &PrismaClient,
+) -> Result<u64, thumbnailer::NonCriticalThumbnailerError> {
+	let hashes = hashes
+		.into_iter()
+		.map(|(id, hash)| {
+			#[allow(clippy::cast_possible_wrap)]
+			{
+				// SAFETY: indexed thumbnails ids are file path ids, which came from the db
+				(id as file_path::id::Type, hash)
+			}
+		})
+		.collect::<HashMap<_, _>>();
+
+	if hashes.is_empty() {
+		return Ok(0);
+	}
+
+	// Many file paths can share the same object, so we also deduplicate them here
+	let objects_hashes = db
+		.file_path()
+		.find_many(vec![file_path::id::in_vec(
+			hashes.keys().copied().collect(),
+		)])
+		.select(file_path::select!({ id object_id }))
+		.exec()
+		.await
+		.map_err(|e| thumbnailer::NonCriticalThumbnailerError::SavePerceptualHashes(e.to_string()))?
+		.into_iter()
+		.filter_map(|file_path| {
+			file_path
+				.object_id
+				.and_then(|object_id| hashes.get(&file_path.id).map(|hash| (object_id, *hash)))
+		})
+		.collect::<HashMap<_, _>>();
+
+	db._batch((
+		db.perceptual_hash()
+			.delete_many(vec![perceptual_hash::object_id::in_vec(
+				objects_hashes.keys().copied().collect(),
+			)]),
+		db.perceptual_hash().create_many(
+			objects_hashes
+				.into_iter()
+				.map(|(object_id, hash)| {
+					perceptual_hash::create_unchecked(hash.into(), object_id, vec![])
+				})
+				.collect(),
+		),
+	))
+	.await
+	.map(|(_, created)| {
+		#[allow(clippy::cast_sign_loss)]
+		{
+			// SAFETY: `create_many` returns the amount of created rows, which is never negative
+			created as u64
+		}
+	})
+	.map_err(|e| thumbnailer::NonCriticalThumbnailerError::SavePerceptualHashes(e.to_string()))
+}
+
+/// Hashes the existing thumbnails of objects without a perceptual hash, like the thumbnails
+/// generated before hashes were computed, returning how many objects were hashed.
+///
+/// Only images and videos are hashed, and the ones without a thumbnail stay without a hash, so
+/// they're checked again each time.
+pub async fn hash_missing(
+	data_directory: &Path,
+	library_id: Uuid,
+	db: &PrismaClient,
+) -> Result<u64, thumbnailer::NonCriticalThumbnailerError> {
+	let kind = ThumbnailKind::Indexed(library_id);
+	let mut hashed = 0;
+	let mut cursor = file_path::id::Type::MIN;
+
+	loop {
+		let file_paths = db
+			.file_path()
+			.find_many(vec![
+				file_path::id::gt(cursor),
+				file_path::cas_id::not(None),
+				file_path::object::is(vec![
+					object::kind::in_vec(HASHED_KINDS.iter().map(|kind| *kind as i32).collect()),
+					not![object::perceptual_hash::is(vec![])],
+				]),
+			])
+			.order_by(file_path::id::order(SortOrder::Asc))
+			.take(BACKFILL_BATCH_SIZE)
+			.select(file_path::select!({ id cas_id }))
+			.exec()
+			.await
+			.map_err(|e| {
+				thumbnailer::NonCriticalThumbnailerError::SavePerceptualHashes(e.to_string())
+			})?;
+
+		let Some(last) = file_paths.last() else {
+			return Ok(hashed);
+		};
+		cursor = last.id;
+
+		// Many file paths can share the same thumbnail, so each one is only hashed once
+		let mut hashes_by_cas_id = HashMap::new();
+		let mut hashes = Vec::with_capacity(file_paths.len());
+
+		for file_path in file_paths {
+			let Some(cas_id) = file_path.cas_id.map(CasId::from) else {
+				continue;
+			};
+
+			let hash = if let Some(hash) = hashes_by_cas_id.get(cas_id.as_str()) {
+				*hash
+			} else {
+				let hash = hash_thumbnail(&kind.compute_path(data_directory, &cas_id)).await;
+				hashes_by_cas_id.insert(cas_id.as_str().to_string(), hash);
+				hash
+			};
+
+			if let Some(hash) = hash {
+				#[allow(clippy::cast_sign_loss)]
+				{
+					// SAFETY: db doesn't have negative indexes
+					hashes.push((file_path.id as ThumbnailId, hash));
+				}
+			}
+		}
+
+		hashed += save(hashes, db).await?;
+	}
+}
+
+async fn hash_thumbnail(path: &Path) -> Option<PerceptualHash> {
+	let webp = match fs::read(path).await {
+		Ok(webp) => webp,
+		Err(e) => {
+			// Not every object has a thumbnail
+			trace!(path = %path.display(), ?e, "No thumbnail to hash;");
+			return None;
+		}
+	};
+
+	spawn_blocking(move || image::load_from_memory_with_format(&webp, ImageFormat::WebP))
+		.await
+		.map_err(|e| warn!(path = %path.display(), ?e, "Failed to join thumbnail decoding task;"))
+		.ok()?
+		.map(|image| PerceptualHash::from_image(&image))
+		.map_err(|e| warn!(path = %path.display(), ?e, "Failed to decode thumbnail;"))
+		.ok()
+}
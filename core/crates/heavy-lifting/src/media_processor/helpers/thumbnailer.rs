@@ -3,9 +3,14 @@ use crate::media_processor::thumbnailer;
 use sd_core_prisma_helpers::CasId;
 
 use sd_file_ext::extensions::{
-	DocumentExtension, Extension, ImageExtension, ALL_DOCUMENT_EXTENSIONS, ALL_IMAGE_EXTENSIONS,
+	CodeExtension, ConfigExtension, DocumentExtension, Extension, ImageExtension, TextExtension,
+	ALL_CODE_EXTENSIONS, ALL_CONFIG_EXTENSIONS, ALL_DOCUMENT_EXTENSIONS, ALL_IMAGE_EXTENSIONS,
+	ALL_TEXT_EXTENSIONS,
+};
+use sd_images::{
+	format_image, format_text, render_pdf_page, scale_dimensions, ConvertibleExtension,
+	PerceptualHash, PDF_MAX_PAGE_RENDER_WIDTH,
 };
-use sd_images::{format_image, scale_dimensions, ConvertibleExtension};
 use sd_media_metadata::exif::Orientation;
 use sd_utils::error::FileIOError;
 
@@ -21,7 +26,7 @@ use std::{
 	time::Duration,
 };
 
-use image::{imageops, DynamicImage, GenericImageView};
+use image::{codecs::jpeg::JpegEncoder, imageops, DynamicImage, GenericImageView};
 use serde::{Deserialize, Serialize};
 use specta::Type;
 use tokio::{
@@ -51,6 +56,26 @@ pub const TARGET_QUALITY: f32 = 60.0;
 /// How much time we allow for the thumbnailer task to complete before we give up.
 pub const THUMBNAILER_TASK_TIMEOUT: Duration = Duration::from_secs(60 * 5);
 
+/// How many frames we take from a video to generate its thumbstrip.
+#[cfg(feature = "ffmpeg")]
+const THUMBSTRIP_FRAMES_COUNT: u32 = 10;
+
+/// Size of each frame in a thumbstrip, small as the frontend shows them while scrubbing.
+#[cfg(feature = "ffmpeg")]
+const THUMBSTRIP_FRAME_SIZE: u32 = 256;
+
+/// Size of video preview clips, they are only shown on hover so they can be small.
+#[cfg(feature = "ffmpeg")]
+const PREVIEW_CLIP_SIZE: u32 = 320;
+
+/// We render preview clips with a lower quality than thumbnails, to keep them lightweight.
+#[cfg(feature = "ffmpeg")]
+const PREVIEW_CLIP_QUALITY: f32 = 40.0;
+
+/// Document pages are rendered at widths rounded up to a multiple of this, so we don't store a
+/// page for every width that was ever requested
+const DOCUMENT_PAGE_WIDTH_STEP: u32 = 256;
+
 pub fn get_thumbnails_directory(data_directory: impl AsRef<Path>) -> PathBuf {
 	data_directory.as_ref().join(THUMBNAIL_CACHE_DIR_NAME)
 }
@@ -78,6 +103,21 @@ pub static THUMBNAILABLE_EXTENSIONS: LazyLock<Vec<Extension>> = LazyLock::new(||
 				.filter(|&ext| can_generate_thumbnail_for_document(ext))
 				.map(Extension::Document),
 		)
+		.chain(
+			ALL_TEXT_EXTENSIONS
+				.iter()
+				.copied()
+				.filter(|&ext| can_generate_thumbnail_for_text(ext))
+				.map(Extension::Text),
+		)
+		.chain(
+			ALL_CODE_EXTENSIONS
+				.iter()
+				.copied()
+				.filter(|&ext| can_generate_thumbnail_for_code(ext))
+				.map(Extension::Code),
+		)
+		.chain(ALL_CONFIG_EXTENSIONS.iter().copied().map(Extension::Config))
 		.collect()
 });
 
@@ -114,6 +154,8 @@ pub struct ThumbKey {
 	pub shard_hex: String,
 	pub cas_id: CasId<'static>,
 	pub base_directory_str: String,
+	#[serde(default)]
+	pub size: ThumbSize,
 }
 
 impl ThumbKey {
@@ -126,6 +168,7 @@ impl ThumbKey {
 				ThumbnailKind::Ephemeral => String::from(EPHEMERAL_DIR),
 				ThumbnailKind::Indexed(library_id) => library_id.to_string(),
 			},
+			size: ThumbSize::default(),
 		}
 	}
 
@@ -135,6 +178,7 @@ impl ThumbKey {
 			shard_hex: get_shard_hex(&cas_id).to_string(),
 			cas_id,
 			base_directory_str: library_id.to_string(),
+			size: ThumbSize::default(),
 		}
 	}
 
@@ -144,6 +188,7 @@ impl ThumbKey {
 			shard_hex: get_shard_hex(&cas_id).to_string(),
 			cas_id,
 			base_directory_str: String::from(EPHEMERAL_DIR),
+			size: ThumbSize::default(),
 		}
 	}
 }
@@ -169,6 +214,206 @@ impl ThumbnailKind {
 
 		thumb_path
 	}
+
+	/// Thumbnails in other sizes or formats than the default ones are stored next to the default
+	/// thumbnail, as `<cas_id>.<px>.<extension>`
+	pub fn compute_variant_path(
+		&self,
+		data_directory: impl AsRef<Path>,
+		cas_id: &CasId<'_>,
+		size: ThumbSize,
+		format: ThumbFormat,
+	) -> PathBuf {
+		let mut thumb_path = self.compute_path(data_directory, cas_id);
+
+		if size != ThumbSize::default() || format != ThumbFormat::default() {
+			thumb_path.set_file_name(format!(
+				"{}.{}.{}",
+				cas_id.as_str(),
+				size.px(),
+				format.extension()
+			));
+		}
+
+		thumb_path
+	}
+}
+
+/// Sizes we serve thumbnails in, [`ThumbSize::Medium`] is the one generated by the media
+/// processor while the others are only generated on their first request.
+#[derive(Debug, Serialize, Deserialize, Type, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ThumbSize {
+	/// For grid views with small items
+	Small,
+	#[default]
+	Medium,
+	/// For full screen previews
+	Large,
+}
+
+impl ThumbSize {
+	pub const ALL: [Self; 3] = [Self::Small, Self::Medium, Self::Large];
+
+	/// Side of a square with the same area of the thumbnail, as thumbnails keep the aspect ratio
+	/// of their original files
+	#[must_use]
+	pub const fn px(self) -> u32 {
+		match self {
+			Self::Small => 256,
+			Self::Medium => 1024,
+			Self::Large => 2048,
+		}
+	}
+
+	#[must_use]
+	pub const fn as_str(self) -> &'static str {
+		match self {
+			Self::Small => "small",
+			Self::Medium => "medium",
+			Self::Large => "large",
+		}
+	}
+
+	#[must_use]
+	pub fn from_name(name: &str) -> Option<Self> {
+		Self::ALL.into_iter().find(|size| size.as_str() == name)
+	}
+
+	#[allow(clippy::cast_precision_loss)] // Our sizes are way smaller than `f32` precision
+	fn target_px(self) -> f32 {
+		(self.px() * self.px()) as f32
+	}
+}
+
+/// Formats we serve thumbnails in, picked from what each client accepts. Thumbnails generated by
+/// the media processor are always `WebP`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ThumbFormat {
+	#[default]
+	Webp,
+	Jpeg,
+	#[cfg(feature = "avif")]
+	Avif,
+}
+
+impl ThumbFormat {
+	#[must_use]
+	pub const fn extension(self) -> &'static str {
+		match self {
+			Self::Webp => WEBP_EXTENSION,
+			Self::Jpeg => "jpg",
+			#[cfg(feature = "avif")]
+			Self::Avif => "avif",
+		}
+	}
+
+	#[must_use]
+	pub const fn mime_type(self) -> &'static str {
+		match self {
+			Self::Webp => "image/webp",
+			Self::Jpeg => "image/jpeg",
+			#[cfg(feature = "avif")]
+			Self::Avif => "image/avif",
+		}
+	}
+
+	/// Picks a format from the `Accept` header of a request. `AVIF` is preferred as it's the
+	/// smallest, then `WebP`, which we also use when the client accepts any image, and `JPEG` is
+	/// only used for clients that explicitly ask for it and not for `WebP`.
+	#[must_use]
+	pub fn negotiate(accept: Option<&str>) -> Self {
+		let Some(accept) = accept else {
+			return Self::default();
+		};
+
+		let accepted = accept
+			.split(',')
+			.filter_map(|media_range| {
+				let mut parts = media_range.split(';').map(str::trim);
+				let mime_type = parts.next()?;
+
+				// A `q=0` means that the client explicitly refuses this type
+				let refused = parts.any(|param| {
+					param
+						.strip_prefix("q=")
+						.and_then(|quality| quality.parse::<f32>().ok())
+						.is_some_and(|quality| quality <= 0.0)
+				});
+
+				(!refused).then_some(mime_type)
+			})
+			.collect::<Vec<_>>();
+
+		#[cfg(feature = "avif")]
+		if accepted.contains(&"image/avif") {
+			return Self::Avif;
+		}
+
+		if accepted.contains(&"image/jpeg") && !accepted.contains(&"image/webp") {
+			Self::Jpeg
+		} else {
+			Self::Webp
+		}
+	}
+}
+
+/// Extra previews generated for indexed videos, besides their thumbnail.
+#[derive(Debug, Serialize, Deserialize, Type, Clone, Copy, Default, PartialEq, Eq)]
+pub struct VideoPreviewsOptions {
+	/// A sprite sheet with evenly spaced frames, to scrub through the video
+	pub thumbstrip: bool,
+	/// A short animated clip without audio, to be played on hover
+	pub preview_clip: bool,
+}
+
+impl VideoPreviewsOptions {
+	#[must_use]
+	pub const fn is_enabled(self) -> bool {
+		self.thumbstrip || self.preview_clip
+	}
+
+	pub fn kinds(self) -> impl Iterator<Item = VideoPreviewKind> {
+		[
+			(self.thumbstrip, VideoPreviewKind::Thumbstrip),
+			(self.preview_clip, VideoPreviewKind::PreviewClip),
+		]
+		.into_iter()
+		.filter_map(|(enabled, kind)| enabled.then_some(kind))
+	}
+}
+
+#[derive(Debug, Serialize, Deserialize, Type, Clone, Copy, PartialEq, Eq)]
+pub enum VideoPreviewKind {
+	Thumbstrip,
+	PreviewClip,
+}
+
+impl VideoPreviewKind {
+	const fn file_suffix(self) -> &'static str {
+		match self {
+			Self::Thumbstrip => "thumbstrip",
+			Self::PreviewClip => "preview",
+		}
+	}
+
+	/// Video previews are stored next to the video thumbnail, as `<cas_id>.<suffix>.webp`, so we
+	/// can use the same [`ThumbKey`] to request them
+	pub fn compute_path(
+		self,
+		thumbnails_directory: impl AsRef<Path>,
+		library_id: Uuid,
+		cas_id: &CasId<'_>,
+	) -> PathBuf {
+		let mut preview_path = thumbnails_directory.as_ref().join(library_id.to_string());
+		preview_path.push(get_shard_hex(cas_id));
+		preview_path.push(format!(
+			"{}.{}.{WEBP_EXTENSION}",
+			cas_id.as_str(),
+			self.file_suffix()
+		));
+
+		preview_path
+	}
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -232,9 +477,31 @@ pub const fn can_generate_thumbnail_for_document(document_extension: DocumentExt
 	matches!(document_extension, Pdf)
 }
 
+#[must_use]
+pub const fn can_generate_thumbnail_for_text(text_extension: TextExtension) -> bool {
+	// Rich text would be shown as its raw markup
+	!matches!(text_extension, TextExtension::Rtf)
+}
+
+#[must_use]
+pub const fn can_generate_thumbnail_for_code(code_extension: CodeExtension) -> bool {
+	use CodeExtension::{Scpt, Scptd};
+
+	// Compiled AppleScripts aren't text
+	!matches!(code_extension, Scpt | Scptd)
+}
+
+/// Text, code and config files get their first page rendered as thumbnail
+fn has_text_thumbnail(extension: &str) -> bool {
+	TextExtension::from_str(extension).is_ok_and(can_generate_thumbnail_for_text)
+		|| CodeExtension::from_str(extension).is_ok_and(can_generate_thumbnail_for_code)
+		|| ConfigExtension::from_str(extension).is_ok()
+}
+
 #[derive(Debug)]
 pub enum GenerationStatus {
-	Generated,
+	/// Along with the perceptual hash of the thumbnail, computed from the image we just encoded
+	Generated(Option<PerceptualHash>),
 	Skipped,
 }
 
@@ -283,22 +550,33 @@ pub async fn generate_thumbnail(
 		);
 	}
 
+	let mut perceptual_hash = None;
+
 	if let Ok(extension) = ImageExtension::from_str(extension) {
 		if can_generate_thumbnail_for_image(extension) {
 			trace!("Generating image thumbnail");
-			if let Err(e) = generate_image_thumbnail(&path, &output_path).await {
-				return (start.elapsed(), Err(e));
+			match generate_image_thumbnail(&path, &output_path).await {
+				Ok(hash) => perceptual_hash = Some(hash),
+				Err(e) => return (start.elapsed(), Err(e)),
 			}
 			trace!("Generated image thumbnail");
 		}
 	} else if let Ok(extension) = DocumentExtension::from_str(extension) {
 		if can_generate_thumbnail_for_document(extension) {
 			trace!("Generating document thumbnail");
+			// We only look for similar images and videos, so we don't hash rendered pages
 			if let Err(e) = generate_image_thumbnail(&path, &output_path).await {
 				return (start.elapsed(), Err(e));
 			}
 			trace!("Generating document thumbnail");
 		}
+	} else if has_text_thumbnail(extension) {
+		trace!("Generating text thumbnail");
+		// Rendered text all looks alike to a perceptual hash, so we don't keep it
+		if let Err(e) = generate_image_thumbnail(&path, &output_path).await {
+			return (start.elapsed(), Err(e));
+		}
+		trace!("Generated text thumbnail");
 	}
 
 	#[cfg(feature = "ffmpeg")]
@@ -309,45 +587,105 @@ pub async fn generate_thumbnail(
 		if let Ok(extension) = VideoExtension::from_str(extension) {
 			if can_generate_thumbnail_for_video(extension) {
 				trace!("Generating video thumbnail");
-				if let Err(e) = generate_video_thumbnail(&path, &output_path).await {
-					return (start.elapsed(), Err(e));
+				match generate_video_thumbnail(&path, &output_path).await {
+					Ok(hash) => perceptual_hash = Some(hash),
+					Err(e) => return (start.elapsed(), Err(e)),
 				}
 				trace!("Generated video thumbnail");
 			}
 		}
 	}
 
+	// Variants were rendered from the previous thumbnail, so they have to be generated again
+	if should_regenerate {
+		remove_thumbnail_variants(&output_path, cas_id).await;
+	}
+
 	trace!("Generated thumbnail");
 
 	(
 		start.elapsed(),
 		Ok((
 			ThumbKey::new(cas_id.to_owned(), kind),
-			GenerationStatus::Generated,
+			GenerationStatus::Generated(perceptual_hash),
 		)),
 	)
 }
 
+/// Removes every variant generated next to the default thumbnail at `default_path`
+async fn remove_thumbnail_variants(default_path: &Path, cas_id: &CasId<'_>) {
+	let Some(shard_dir) = default_path.parent() else {
+		return;
+	};
+
+	let mut entries = match fs::read_dir(shard_dir).await {
+		Ok(entries) => entries,
+		Err(e) if e.kind() == io::ErrorKind::NotFound => return,
+		Err(e) => {
+			error!(
+				e = ?FileIOError::from((shard_dir, e)),
+				"Failed to read thumbnails shard directory to remove variants;",
+			);
+			return;
+		}
+	};
+
+	let prefix = format!("{}.", cas_id.as_str());
+
+	while let Ok(Some(entry)) = entries.next_entry().await {
+		let path = entry.path();
+
+		if path != default_path
+			&& entry
+				.file_name()
+				.to_str()
+				.is_some_and(|file_name| file_name.starts_with(&prefix))
+		{
+			if let Err(e) = fs::remove_file(&path).await {
+				error!(
+					e = ?FileIOError::from((&path, e)),
+					"Failed to remove outdated thumbnail variant;",
+				);
+			}
+		}
+	}
+}
+
 fn inner_generate_image_thumbnail(
 	file_path: &PathBuf,
-) -> Result<Vec<u8>, thumbnailer::NonCriticalThumbnailerError> {
-	let mut img = format_image(file_path).map_err(|e| {
+) -> Result<(Vec<u8>, PerceptualHash), thumbnailer::NonCriticalThumbnailerError> {
+	let img = load_scaled_image(file_path, TARGET_PX)?;
+
+	// Hashing the already decoded and downscaled image is way cheaper than the original one
+	let perceptual_hash = PerceptualHash::from_image(&img);
+
+	encode_thumbnail(file_path, &img, ThumbFormat::Webp).map(|webp| (webp, perceptual_hash))
+}
+
+/// Loads an image or document, downscaled to the target pixel count and with its orientation
+/// corrected
+pub(super) fn load_scaled_image(
+	file_path: &PathBuf,
+	target_px: f32,
+) -> Result<DynamicImage, thumbnailer::NonCriticalThumbnailerError> {
+	let is_text = file_path
+		.extension()
+		.and_then(|extension| extension.to_str())
+		.is_some_and(has_text_thumbnail);
+
+	let img = if is_text {
+		format_text(file_path)
+	} else {
+		format_image(file_path)
+	}
+	.map_err(|e| {
 		thumbnailer::NonCriticalThumbnailerError::FormatImage(file_path.clone(), e.to_string())
 	})?;
 
-	let (w, h) = img.dimensions();
+	let mut img = downscale_image(img, target_px);
 
-	#[allow(clippy::cast_precision_loss)]
-	let (w_scaled, h_scaled) = scale_dimensions(w as f32, h as f32, TARGET_PX);
-
-	// Optionally, resize the existing photo and convert back into DynamicImage
-	if w != w_scaled && h != h_scaled {
-		img = DynamicImage::ImageRgba8(imageops::resize(
-			&img,
-			w_scaled,
-			h_scaled,
-			imageops::FilterType::Triangle,
-		));
+	if is_text {
+		return Ok(img);
 	}
 
 	// this corrects the rotation/flip of the image based on the *available* exif data
@@ -361,25 +699,96 @@ fn inner_generate_image_thumbnail(
 		}
 	}
 
-	// Create the WebP encoder for the above image
-	let encoder = Encoder::from_image(&img).map_err(|reason| {
-		thumbnailer::NonCriticalThumbnailerError::WebPEncoding(
-			file_path.clone(),
-			reason.to_string(),
-		)
-	})?;
+	Ok(img)
+}
 
-	let thumb = encoder.encode_advanced(&WEBP_CONFIG).map_err(|reason| {
-		thumbnailer::NonCriticalThumbnailerError::WebPEncoding(
-			file_path.clone(),
-			format!("{reason:?}"),
-		)
-	})?;
+fn downscale_image(img: DynamicImage, target_px: f32) -> DynamicImage {
+	let (w, h) = img.dimensions();
+
+	#[allow(clippy::cast_precision_loss)]
+	let (w_scaled, h_scaled) = scale_dimensions(w as f32, h as f32, target_px);
 
-	// Type `WebPMemory` is !Send, which makes the `Future` in this function `!Send`,
-	// this make us `deref` to have a `&[u8]` and then `to_owned` to make a `Vec<u8>`
-	// which implies on a unwanted clone...
-	Ok(thumb.deref().to_owned())
+	// Optionally, resize the existing photo and convert back into DynamicImage
+	if w != w_scaled && h != h_scaled {
+		DynamicImage::ImageRgba8(imageops::resize(
+			&img,
+			w_scaled,
+			h_scaled,
+			imageops::FilterType::Triangle,
+		))
+	} else {
+		img
+	}
+}
+
+fn encode_thumbnail(
+	file_path: &Path,
+	img: &DynamicImage,
+	format: ThumbFormat,
+) -> Result<Vec<u8>, thumbnailer::NonCriticalThumbnailerError> {
+	#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+	// The quality is a percentage, so it fits in an `u8`
+	let quality = TARGET_QUALITY as u8;
+
+	match format {
+		ThumbFormat::Webp => {
+			// Create the WebP encoder for the above image
+			let encoder = Encoder::from_image(img).map_err(|reason| {
+				thumbnailer::NonCriticalThumbnailerError::WebPEncoding(
+					file_path.to_path_buf(),
+					reason.to_string(),
+				)
+			})?;
+
+			let thumb = encoder.encode_advanced(&WEBP_CONFIG).map_err(|reason| {
+				thumbnailer::NonCriticalThumbnailerError::WebPEncoding(
+					file_path.to_path_buf(),
+					format!("{reason:?}"),
+				)
+			})?;
+
+			// Type `WebPMemory` is !Send, which makes the `Future` in this function `!Send`,
+			// this make us `deref` to have a `&[u8]` and then `to_owned` to make a `Vec<u8>`
+			// which implies on a unwanted clone...
+			Ok(thumb.deref().to_owned())
+		}
+
+		ThumbFormat::Jpeg => {
+			let mut bytes = Vec::new();
+
+			// JPEG doesn't support transparency
+			JpegEncoder::new_with_quality(&mut bytes, quality)
+				.encode_image(&img.to_rgb8())
+				.map_err(|e| {
+					thumbnailer::NonCriticalThumbnailerError::ImageEncoding(
+						file_path.to_path_buf(),
+						e.to_string(),
+					)
+				})?;
+
+			Ok(bytes)
+		}
+
+		#[cfg(feature = "avif")]
+		ThumbFormat::Avif => {
+			use image::{codecs::avif::AvifEncoder, ColorType, ImageEncoder};
+
+			let mut bytes = Vec::new();
+			let img = img.to_rgba8();
+
+			// A fast speed, as these thumbnails are generated while the client waits for them
+			AvifEncoder::new_with_speed_quality(&mut bytes, 8, quality)
+				.write_image(img.as_raw(), img.width(), img.height(), ColorType::Rgba8)
+				.map_err(|e| {
+					thumbnailer::NonCriticalThumbnailerError::ImageEncoding(
+						file_path.to_path_buf(),
+						e.to_string(),
+					)
+				})?;
+
+			Ok(bytes)
+		}
+	}
 }
 
 #[instrument(
@@ -392,7 +801,7 @@ fn inner_generate_image_thumbnail(
 async fn generate_image_thumbnail(
 	file_path: impl AsRef<Path> + Send,
 	output_path: impl AsRef<Path> + Send,
-) -> Result<(), thumbnailer::NonCriticalThumbnailerError> {
+) -> Result<PerceptualHash, thumbnailer::NonCriticalThumbnailerError> {
 	let file_path = file_path.as_ref().to_path_buf();
 
 	let (tx, rx) = oneshot::channel();
@@ -420,7 +829,7 @@ async fn generate_image_thumbnail(
 		}
 	});
 
-	let webp = if let Ok(res) = rx.await {
+	let (webp, perceptual_hash) = if let Ok(res) = rx.await {
 		res?
 	} else {
 		error!("Failed to generate thumbnail");
@@ -437,8 +846,17 @@ async fn generate_image_thumbnail(
 
 	trace!("Generated thumbnail bytes");
 
-	let output_path = output_path.as_ref();
+	write_thumbnail(&file_path, output_path.as_ref(), &webp).await?;
 
+	trace!("Wrote thumbnail to disk");
+	return Ok(perceptual_hash);
+}
+
+async fn write_thumbnail(
+	file_path: &Path,
+	output_path: &Path,
+	bytes: &[u8],
+) -> Result<(), thumbnailer::NonCriticalThumbnailerError> {
 	if let Some(shard_dir) = output_path.parent() {
 		fs::create_dir_all(shard_dir).await.map_err(|e| {
 			thumbnailer::NonCriticalThumbnailerError::CreateShardDirectory(
@@ -453,27 +871,24 @@ async fn generate_image_thumbnail(
 
 	let mut file = File::create(output_path).await.map_err(|e| {
 		thumbnailer::NonCriticalThumbnailerError::SaveThumbnail(
-			file_path.clone(),
+			file_path.to_path_buf(),
 			FileIOError::from((output_path, e)).to_string(),
 		)
 	})?;
 
-	file.write_all(&webp).await.map_err(|e| {
+	file.write_all(bytes).await.map_err(|e| {
 		thumbnailer::NonCriticalThumbnailerError::SaveThumbnail(
-			file_path.clone(),
+			file_path.to_path_buf(),
 			FileIOError::from((output_path, e)).to_string(),
 		)
 	})?;
 
 	file.sync_all().await.map_err(|e| {
 		thumbnailer::NonCriticalThumbnailerError::SaveThumbnail(
-			file_path,
+			file_path.to_path_buf(),
 			FileIOError::from((output_path, e)).to_string(),
 		)
-	})?;
-
-	trace!("Wrote thumbnail to disk");
-	return Ok(());
+	})
 }
 
 #[instrument(
@@ -487,7 +902,7 @@ async fn generate_image_thumbnail(
 async fn generate_video_thumbnail(
 	file_path: impl AsRef<Path> + Send,
 	output_path: impl AsRef<Path> + Send,
-) -> Result<(), thumbnailer::NonCriticalThumbnailerError> {
+) -> Result<PerceptualHash, thumbnailer::NonCriticalThumbnailerError> {
 	use sd_ffmpeg::{to_thumbnail, ThumbnailSize};
 
 	let file_path = file_path.as_ref();
@@ -499,6 +914,7 @@ async fn generate_video_thumbnail(
 		TARGET_QUALITY,
 	)
 	.await
+	.map(|frame| PerceptualHash::from_image(&frame))
 	.map_err(|e| {
 		thumbnailer::NonCriticalThumbnailerError::VideoThumbnailGenerationFailed(
 			file_path.to_path_buf(),
@@ -507,6 +923,321 @@ async fn generate_video_thumbnail(
 	})
 }
 
+/// Generates the enabled previews for a video, only done for indexed ones as ephemeral thumbnails
+/// are just for browsing
+#[allow(unused_variables, clippy::needless_return)] // Due to the `ffmpeg` feature
+pub async fn generate_video_previews(
+	thumbnails_directory: &Path,
+	GenerateThumbnailArgs {
+		extension,
+		cas_id,
+		path,
+	}: &GenerateThumbnailArgs<'_>,
+	kind: &ThumbnailKind,
+	options: VideoPreviewsOptions,
+	should_regenerate: bool,
+) -> Vec<thumbnailer::NonCriticalThumbnailerError> {
+	#[cfg(feature = "ffmpeg")]
+	{
+		use sd_ffmpeg::{to_preview_clip, to_thumbstrip, ThumbnailSize};
+
+		let ThumbnailKind::Indexed(library_id) = *kind else {
+			return Vec::new();
+		};
+
+		if !VideoExtension::from_str(extension).is_ok_and(can_generate_thumbnail_for_video) {
+			return Vec::new();
+		}
+
+		let mut errors = Vec::new();
+
+		for preview_kind in options.kinds() {
+			let output_path = preview_kind.compute_path(thumbnails_directory, library_id, cas_id);
+
+			if !should_regenerate && fs::try_exists(&output_path).await.unwrap_or(false) {
+				trace!(
+					?preview_kind,
+					"Skipping video preview generation because it already exists"
+				);
+				continue;
+			}
+
+			trace!(?preview_kind, "Generating video preview");
+
+			let res = match preview_kind {
+				VideoPreviewKind::Thumbstrip => {
+					to_thumbstrip(
+						path,
+						&output_path,
+						THUMBSTRIP_FRAMES_COUNT,
+						ThumbnailSize::Scale(THUMBSTRIP_FRAME_SIZE),
+						TARGET_QUALITY,
+					)
+					.await
+				}
+				VideoPreviewKind::PreviewClip => {
+					to_preview_clip(
+						path,
+						&output_path,
+						ThumbnailSize::Scale(PREVIEW_CLIP_SIZE),
+						PREVIEW_CLIP_QUALITY,
+					)
+					.await
+				}
+			};
+
+			if let Err(e) = res {
+				errors.push(
+					thumbnailer::NonCriticalThumbnailerError::VideoPreviewGenerationFailed(
+						path.clone(),
+						preview_kind,
+						e.to_string(),
+					),
+				);
+			}
+		}
+
+		return errors;
+	}
+
+	#[cfg(not(feature = "ffmpeg"))]
+	Vec::new()
+}
+
+/// Generates a thumbnail in another size or format than the default ones, meant to be called on
+/// its first request, returning the path where it was saved.
+///
+/// Thumbnails larger than the default one are rendered from the `original` file, with its
+/// extension, and when we don't have it we settle for the default size. Smaller ones are just
+/// downscaled from the default thumbnail.
+pub async fn generate_thumbnail_variant(
+	data_directory: impl AsRef<Path> + Send,
+	kind: ThumbnailKind,
+	cas_id: CasId<'static>,
+	size: ThumbSize,
+	format: ThumbFormat,
+	original: Option<(PathBuf, String)>,
+) -> Result<PathBuf, thumbnailer::NonCriticalThumbnailerError> {
+	let data_directory = data_directory.as_ref();
+	let default_path = kind.compute_path(data_directory, &cas_id);
+
+	// We never upscale thumbnails, so without the original file the default size is our best
+	let size = if original.is_none() && size.px() > ThumbSize::Medium.px() {
+		ThumbSize::Medium
+	} else {
+		size
+	};
+
+	let output_path = kind.compute_variant_path(data_directory, &cas_id, size, format);
+	if output_path == default_path || fs::try_exists(&output_path).await.unwrap_or(false) {
+		return Ok(output_path);
+	}
+
+	let (source, temp_frame_path) = match original {
+		Some((path, extension)) if size.px() > ThumbSize::Medium.px() => {
+			variant_source_from_original(path, &extension, &output_path, size).await?
+		}
+		_ => (VariantSource::Rendered(default_path), None),
+	};
+
+	let res = render_variant(source, size, format).await;
+
+	if let Some(temp_frame_path) = temp_frame_path {
+		if let Err(e) = fs::remove_file(&temp_frame_path).await {
+			error!(
+				e = ?FileIOError::from((&temp_frame_path, e)),
+				"Failed to remove temporary video frame;",
+			);
+		}
+	}
+
+	let (source_path, bytes) = res?;
+
+	write_thumbnail(&source_path, &output_path, &bytes).await?;
+
+	Ok(output_path)
+}
+
+/// What a thumbnail variant is rendered from
+enum VariantSource {
+	/// An original image or document, which must be decoded and oriented
+	Original(PathBuf),
+	/// An already rendered thumbnail, which we just resize and convert
+	Rendered(PathBuf),
+}
+
+/// Videos are first rendered to a temporary frame by `FFmpeg`, returned along with the source
+/// so it can be removed afterwards
+#[allow(unused_variables)] // Due to the `ffmpeg` feature
+async fn variant_source_from_original(
+	path: PathBuf,
+	extension: &str,
+	output_path: &Path,
+	size: ThumbSize,
+) -> Result<(VariantSource, Option<PathBuf>), thumbnailer::NonCriticalThumbnailerError> {
+	#[cfg(feature = "ffmpeg")]
+	if VideoExtension::from_str(extension).is_ok_and(can_generate_thumbnail_for_video) {
+		use sd_ffmpeg::{to_thumbnail, ThumbnailSize};
+
+		let frame_path = output_path.with_extension(format!("frame.{WEBP_EXTENSION}"));
+
+		to_thumbnail(&path, &frame_path, ThumbnailSize::Scale(size.px()), 100.0)
+			.await
+			.map_err(|e| {
+				thumbnailer::NonCriticalThumbnailerError::VideoThumbnailGenerationFailed(
+					path,
+					e.to_string(),
+				)
+			})?;
+
+		return Ok((
+			VariantSource::Rendered(frame_path.clone()),
+			Some(frame_path),
+		));
+	}
+
+	Ok((VariantSource::Original(path), None))
+}
+
+async fn render_variant(
+	source: VariantSource,
+	size: ThumbSize,
+	format: ThumbFormat,
+) -> Result<(PathBuf, Vec<u8>), thumbnailer::NonCriticalThumbnailerError> {
+	let source_path = match &source {
+		VariantSource::Original(path) | VariantSource::Rendered(path) => path.clone(),
+	};
+
+	let res = spawn_blocking({
+		let source_path = source_path.clone();
+		move || {
+			panic::catch_unwind(|| {
+				let img = match source {
+					VariantSource::Original(path) => load_scaled_image(&path, size.target_px())?,
+					VariantSource::Rendered(path) => image::open(&path)
+						.map(|img| downscale_image(img, size.target_px()))
+						.map_err(|e| {
+							thumbnailer::NonCriticalThumbnailerError::FormatImage(
+								path.clone(),
+								e.to_string(),
+							)
+						})?,
+				};
+
+				encode_thumbnail(&source_path, &img, format)
+			})
+		}
+	})
+	.await;
+
+	match res {
+		Ok(Ok(res)) => res.map(|bytes| (source_path, bytes)),
+		Ok(Err(_)) => Err(
+			thumbnailer::NonCriticalThumbnailerError::PanicWhileGeneratingThumbnail(
+				source_path,
+				"Internal panic on third party crate".to_string(),
+			),
+		),
+		Err(e) => Err(
+			thumbnailer::NonCriticalThumbnailerError::PanicWhileGeneratingThumbnail(
+				source_path,
+				e.to_string(),
+			),
+		),
+	}
+}
+
+/// Rendered pages of an indexed document are stored next to its thumbnail, as
+/// `<cas_id>.page.<page>.<width>.webp`
+pub fn compute_document_page_path(
+	thumbnails_directory: impl AsRef<Path>,
+	library_id: Uuid,
+	cas_id: &CasId<'_>,
+	page: u16,
+	width: u32,
+) -> PathBuf {
+	let mut page_path = thumbnails_directory.as_ref().join(library_id.to_string());
+	page_path.push(get_shard_hex(cas_id));
+	page_path.push(format!(
+		"{}.page.{page}.{width}.{WEBP_EXTENSION}",
+		cas_id.as_str()
+	));
+
+	page_path
+}
+
+/// Renders the page at the zero based `page` index of a PDF document at least `width` pixels
+/// wide, meant to be called on its first request, returning the path where it was saved.
+///
+/// Pages are cached by `cas_id`, so later requests for the same page of the same document reuse it.
+pub async fn generate_document_page(
+	thumbnails_directory: impl AsRef<Path> + Send,
+	library_id: Uuid,
+	cas_id: &CasId<'_>,
+	document_path: PathBuf,
+	page: u16,
+	width: u32,
+) -> Result<PathBuf, thumbnailer::NonCriticalThumbnailerError> {
+	let width = width
+		.clamp(1, PDF_MAX_PAGE_RENDER_WIDTH)
+		.div_ceil(DOCUMENT_PAGE_WIDTH_STEP)
+		.saturating_mul(DOCUMENT_PAGE_WIDTH_STEP)
+		.min(PDF_MAX_PAGE_RENDER_WIDTH);
+
+	let output_path =
+		compute_document_page_path(thumbnails_directory, library_id, cas_id, page, width);
+	if fs::try_exists(&output_path).await.unwrap_or(false) {
+		return Ok(output_path);
+	}
+
+	let res = spawn_blocking({
+		let document_path = document_path.clone();
+		move || {
+			panic::catch_unwind(|| {
+				let img = render_pdf_page(&document_path, page, width).map_err(|e| match e {
+					sd_images::Error::PageNotFound(page) => {
+						thumbnailer::NonCriticalThumbnailerError::DocumentPageNotFound(
+							document_path.clone(),
+							page,
+						)
+					}
+					e => thumbnailer::NonCriticalThumbnailerError::FormatImage(
+						document_path.clone(),
+						e.to_string(),
+					),
+				})?;
+
+				encode_thumbnail(&document_path, &img, ThumbFormat::Webp)
+			})
+		}
+	})
+	.await;
+
+	let bytes = match res {
+		Ok(Ok(res)) => res?,
+		Ok(Err(_)) => {
+			return Err(
+				thumbnailer::NonCriticalThumbnailerError::PanicWhileGeneratingThumbnail(
+					document_path,
+					"Internal panic on third party crate".to_string(),
+				),
+			)
+		}
+		Err(e) => {
+			return Err(
+				thumbnailer::NonCriticalThumbnailerError::PanicWhileGeneratingThumbnail(
+					document_path,
+					e.to_string(),
+				),
+			)
+		}
+	};
+
+	write_thumbnail(&document_path, &output_path, &bytes).await?;
+
+	Ok(output_path)
+}
+
 /// WARNING!!!! DON'T USE THIS FUNCTION IN A LOOP!!!!!!!!!!!!! It will be pretty slow on purpose!
 pub async fn generate_single_thumbnail(
 	thumbnails_directory: impl AsRef<Path> + Send,
@@ -538,10 +1269,63 @@ pub async fn generate_single_thumbnail(
 
 	let (_thumb_key, status) = res?;
 
-	if matches!(status, GenerationStatus::Generated) {
+	if matches!(status, GenerationStatus::Generated(_)) {
 		*last_single_thumb_generated_guard = Instant::now();
 		drop(last_single_thumb_generated_guard); // Clippy was weirdly complaining about not doing an "early" drop here
 	}
 
 	Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn thumbnail_format_negotiation() {
+		assert_eq!(ThumbFormat::negotiate(None), ThumbFormat::Webp);
+		assert_eq!(ThumbFormat::negotiate(Some("*/*")), ThumbFormat::Webp);
+		assert_eq!(
+			ThumbFormat::negotiate(Some("image/webp,image/jpeg;q=0.8,*/*;q=0.5")),
+			ThumbFormat::Webp
+		);
+		assert_eq!(
+			ThumbFormat::negotiate(Some("image/jpeg, image/png;q=0.9")),
+			ThumbFormat::Jpeg
+		);
+		// Refused formats don't count as accepted
+		assert_eq!(
+			ThumbFormat::negotiate(Some("image/jpeg, image/webp;q=0")),
+			ThumbFormat::Jpeg
+		);
+
+		#[cfg(feature = "avif")]
+		assert_eq!(
+			ThumbFormat::negotiate(Some("image/avif,image/webp,*/*")),
+			ThumbFormat::Avif
+		);
+	}
+
+	#[test]
+	fn variants_are_stored_next_to_the_default_thumbnail() {
+		let cas_id = CasId::from("0123456789abcdef");
+		let kind = ThumbnailKind::Ephemeral;
+
+		let default_path = kind.compute_path("/data", &cas_id);
+
+		assert_eq!(
+			kind.compute_variant_path("/data", &cas_id, ThumbSize::Medium, ThumbFormat::Webp),
+			default_path
+		);
+		assert_eq!(
+			kind.compute_variant_path("/data", &cas_id, ThumbSize::Small, ThumbFormat::Webp),
+			default_path.with_file_name("0123456789abcdef.256.webp")
+		);
+		assert_eq!(
+			kind.compute_variant_path("/data", &cas_id, ThumbSize::Medium, ThumbFormat::Jpeg),
+			default_path.with_file_name("0123456789abcdef.1024.jpg")
+		);
+		assert_eq!(ThumbSize::from_name("large"), Some(ThumbSize::Large));
+		assert_eq!(ThumbSize::from_name("huge"), None);
+	}
+}
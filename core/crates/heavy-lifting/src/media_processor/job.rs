@@ -48,17 +48,22 @@ use super::{
 	NewThumbnailsReporter, RawFilePathForMediaProcessor, BATCH_SIZE,
 };
 
+#[cfg(feature = "ai")]
+use super::tasks::labels_generator;
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 enum TaskKind {
 	MediaDataExtractor,
 	Thumbnailer,
+	#[cfg(feature = "ai")]
+	LabelsGenerator,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Copy)]
 enum Phase {
 	MediaDataExtraction,
 	ThumbnailGeneration,
-	// LabelsGeneration, // TODO: Implement labels generation
+	LabelsGeneration,
 }
 
 impl Default for Phase {
@@ -72,7 +77,7 @@ impl fmt::Display for Phase {
 		match self {
 			Self::MediaDataExtraction => write!(f, "media_data"),
 			Self::ThumbnailGeneration => write!(f, "thumbnails"),
-			// Self::LabelsGeneration => write!(f, "labels"), // TODO: Implement labels generation
+			Self::LabelsGeneration => write!(f, "labels"),
 		}
 	}
 }
@@ -84,12 +89,15 @@ pub struct MediaProcessor {
 	location_path: Arc<PathBuf>,
 	sub_path: Option<PathBuf>,
 	regenerate_thumbnails: bool,
+	regenerate_labels: bool,
 
 	// Job control
 	total_media_data_extraction_files: u64,
 	total_media_data_extraction_tasks: u64,
 	total_thumbnailer_tasks: u64,
 	total_thumbnailer_files: u64,
+	total_labels_generator_tasks: u64,
+	total_labels_generator_files: u64,
 	phase: Phase,
 
 	// Run data
@@ -113,19 +121,41 @@ impl Job for MediaProcessor {
 		let reporter: Arc<dyn NewThumbnailReporter> =
 			Arc::new(NewThumbnailsReporter { ctx: ctx.clone() });
 
+		let tasks_to_resume = rmp_serde::from_slice::<Vec<(TaskKind, Vec<u8>)>>(&serialized_tasks)
+			.map_err(media_processor::Error::from)?;
+
+		#[cfg(feature = "ai")]
+		let image_labeler = ctx.image_labeler();
+
+		#[cfg(feature = "ai")]
+		let tasks_to_resume = if image_labeler.is_some() {
+			tasks_to_resume
+		} else {
+			warn!("Image labeler isn't available anymore, so labels generation won't be resumed;");
+			tasks_to_resume
+				.into_iter()
+				.filter(|(task_kind, _)| !matches!(task_kind, TaskKind::LabelsGenerator))
+				.collect()
+		};
+
 		if let Ok(tasks) = dispatcher
 			.dispatch_many_boxed(
-				rmp_serde::from_slice::<Vec<(TaskKind, Vec<u8>)>>(&serialized_tasks)
-					.map_err(media_processor::Error::from)?
+				tasks_to_resume
 					.into_iter()
 					.map(|(task_kind, task_bytes)| {
 						let reporter = Arc::clone(&reporter);
+						#[cfg(feature = "ai")]
+						let image_labeler = image_labeler.clone();
 						async move {
 							match task_kind {
 								TaskKind::MediaDataExtractor => {
 									tasks::MediaDataExtractor::deserialize(
 										&task_bytes,
-										(Arc::clone(ctx.db()), Arc::clone(ctx.sync())),
+										(
+											Arc::clone(ctx.db()),
+											Arc::clone(ctx.sync()),
+											ctx.reverse_geocoder(),
+										),
 									)
 									.await
 									.map(IntoTask::into_task)
@@ -136,6 +166,19 @@ impl Job for MediaProcessor {
 										.await
 										.map(IntoTask::into_task)
 								}
+
+								#[cfg(feature = "ai")]
+								TaskKind::LabelsGenerator => tasks::LabelsGenerator::deserialize(
+									&task_bytes,
+									(
+										image_labeler
+											.expect("tasks were filtered above without a labeler"),
+										Arc::clone(ctx.db()),
+										Arc::clone(ctx.sync()),
+									),
+								)
+								.await
+								.map(IntoTask::into_task),
 							}
 						}
 					})
@@ -161,6 +204,7 @@ impl Job for MediaProcessor {
 			location_path = ?self.location.path,
 			sub_path = ?self.sub_path.as_ref().map(|path| path.display()),
 			regenerate_thumbnails = self.regenerate_thumbnails,
+			regenerate_labels = self.regenerate_labels,
 		),
 		ret(level = Level::TRACE),
 		err,
@@ -248,10 +292,13 @@ impl MediaProcessor {
 			location: Arc::new(location),
 			sub_path,
 			regenerate_thumbnails,
+			regenerate_labels: false,
 			total_media_data_extraction_files: 0,
 			total_media_data_extraction_tasks: 0,
 			total_thumbnailer_tasks: 0,
 			total_thumbnailer_files: 0,
+			total_labels_generator_tasks: 0,
+			total_labels_generator_files: 0,
 			phase: Phase::default(),
 			metadata: Metadata::default(),
 			errors: Vec::new(),
@@ -260,6 +307,13 @@ impl MediaProcessor {
 		})
 	}
 
+	/// Labels images again even if they were already labeled by the current model version
+	#[must_use]
+	pub const fn with_regenerate_labels(mut self, regenerate_labels: bool) -> Self {
+		self.regenerate_labels = regenerate_labels;
+		self
+	}
+
 	#[allow(clippy::too_many_lines)]
 	async fn init_or_resume<OuterCtx: OuterContext>(
 		&mut self,
@@ -370,6 +424,19 @@ impl MediaProcessor {
 					return Err(e.into());
 				}
 			}
+
+			// Labels generation is the slowest phase, so it's dispatched last
+			#[cfg(feature = "ai")]
+			match self
+				.dispatch_labels_generator_tasks(&iso_file_path, dispatcher, job_ctx)
+				.await
+			{
+				Ok(task_handles) => pending_running_tasks.extend(task_handles),
+				Err(JobErrorOrDispatcherError::Dispatcher(DispatcherError::Shutdown(tasks))) => {
+					self.tasks_for_shutdown.extend(tasks);
+				}
+				Err(e) => return Err(e),
+			}
 		} else {
 			let updates = match self.phase {
 				Phase::MediaDataExtraction => vec![
@@ -397,6 +464,18 @@ impl MediaProcessor {
 						self.total_thumbnailer_files, self.total_thumbnailer_tasks
 					)),
 				],
+				Phase::LabelsGeneration => vec![
+					ProgressUpdate::TaskCount(self.total_labels_generator_files),
+					ProgressUpdate::CompletedTaskCount(
+						self.metadata.labels_generator_metrics.labeled
+							+ self.metadata.labels_generator_metrics.skipped,
+					),
+					ProgressUpdate::Phase(self.phase.to_string()),
+					ProgressUpdate::Message(format!(
+						"Preparing to process {} files in {} chunks",
+						self.total_labels_generator_files, self.total_labels_generator_tasks
+					)),
+				],
 			};
 
 			job_ctx.progress(updates).await;
@@ -467,6 +546,16 @@ impl MediaProcessor {
 		any_task_output: Box<dyn AnyTaskOutput>,
 		job_ctx: &impl JobContext<OuterCtx>,
 	) {
+		#[cfg(feature = "ai")]
+		if any_task_output.is::<labels_generator::Output>() {
+			return self
+				.process_labels_generator_output(
+					*any_task_output.downcast().expect("just checked"),
+					job_ctx,
+				)
+				.await;
+		}
+
 		if any_task_output.is::<media_data_extractor::Output>() {
 			let media_data_extractor::Output {
 				extracted,
@@ -530,6 +619,7 @@ impl MediaProcessor {
 				total_time,
 				mean_time_acc,
 				std_dev_acc,
+				perceptual_hashes,
 			} = *any_task_output.downcast().expect("just checked");
 
 			self.metadata.thumbnailer_metrics_acc.generated += generated;
@@ -544,6 +634,12 @@ impl MediaProcessor {
 				self.errors.extend(errors);
 			}
 
+			if let Err(e) = helpers::perceptual_hash::save(perceptual_hashes, job_ctx.db()).await {
+				warn!(?e, "Failed to save perceptual hashes of thumbnails;");
+				self.errors
+					.push(media_processor::NonCriticalMediaProcessorError::from(e).into());
+			}
+
 			debug!(
 				"Processed ({}/{}) thumbnailer tasks, took: {total_time:?}",
 				self.metadata.thumbnailer_metrics_acc.total_successful_tasks,
@@ -559,27 +655,86 @@ impl MediaProcessor {
 					.await;
 			}
 
-		// if self.total_thumbnailer_tasks
-		// 	== self.metadata.thumbnailer_metrics_acc.total_successful_tasks
-		// {
-		// 	debug!("All thumbnailer tasks have been processed");
-
-		// 	self.phase = Phase::LabelsGeneration;
-
-		// 	ctx.progress(vec![
-		// 		ProgressUpdate::TaskCount(self.total_thumbnailer_files),
-		// 		ProgressUpdate::Phase(self.phase.to_string()),
-		// 		ProgressUpdate::Message(format!(
-		// 			"Waiting for processing of {} labels in {} tasks",
-		// 			self.total_labeller_files, self.total_labeller_tasks
-		// 		)),
-		// 	]).await;
-		// }
+			#[cfg(feature = "ai")]
+			if self.total_thumbnailer_tasks
+				== self.metadata.thumbnailer_metrics_acc.total_successful_tasks
+				&& self.total_labels_generator_tasks > 0
+			{
+				debug!("All thumbnailer tasks have been processed");
+
+				self.phase = Phase::LabelsGeneration;
+
+				job_ctx
+					.progress(vec![
+						ProgressUpdate::TaskCount(self.total_labels_generator_files),
+						// Labels tasks run along thumbnails ones, so some could be done already
+						ProgressUpdate::CompletedTaskCount(
+							self.metadata.labels_generator_metrics.labeled
+								+ self.metadata.labels_generator_metrics.skipped,
+						),
+						ProgressUpdate::Phase(self.phase.to_string()),
+						ProgressUpdate::Message(format!(
+							"Waiting for labeling of {} files in {} tasks",
+							self.total_labels_generator_files, self.total_labels_generator_tasks
+						)),
+					])
+					.await;
+			}
 		} else {
 			unreachable!("Unexpected task output type: <id='{task_id}'>");
 		}
 	}
 
+	#[cfg(feature = "ai")]
+	async fn process_labels_generator_output<OuterCtx: OuterContext>(
+		&mut self,
+		labels_generator::Output {
+			labeled,
+			skipped,
+			labels_assigned,
+			db_read_time,
+			filtering_time,
+			labeling_time,
+			db_write_time,
+			errors,
+		}: labels_generator::Output,
+		job_ctx: &impl JobContext<OuterCtx>,
+	) {
+		self.metadata.labels_generator_metrics.labeled += labeled;
+		self.metadata.labels_generator_metrics.skipped += skipped;
+		self.metadata.labels_generator_metrics.labels_assigned += labels_assigned;
+		self.metadata.labels_generator_metrics.mean_db_read_time += db_read_time;
+		self.metadata.labels_generator_metrics.mean_filtering_time += filtering_time;
+		self.metadata.labels_generator_metrics.mean_labeling_time += labeling_time;
+		self.metadata.labels_generator_metrics.mean_db_write_time += db_write_time;
+		self.metadata
+			.labels_generator_metrics
+			.total_successful_tasks += 1;
+
+		if !errors.is_empty() {
+			warn!(?errors, "Non critical errors while generating labels;");
+			self.errors.extend(errors);
+		}
+
+		debug!(
+			"Processed ({}/{}) labels generator tasks, took: {:?};",
+			self.metadata
+				.labels_generator_metrics
+				.total_successful_tasks,
+			self.total_labels_generator_tasks,
+			db_read_time + filtering_time + labeling_time + db_write_time,
+		);
+
+		if matches!(self.phase, Phase::LabelsGeneration) {
+			job_ctx
+				.progress(vec![ProgressUpdate::CompletedTaskCount(
+					self.metadata.labels_generator_metrics.labeled
+						+ self.metadata.labels_generator_metrics.skipped,
+				)])
+				.await;
+		}
+	}
+
 	async fn cancel_job(
 		&mut self,
 		pending_running_tasks: &mut FuturesUnordered<TaskHandle<Error>>,
@@ -603,6 +758,7 @@ impl MediaProcessor {
 	) -> Result<Vec<TaskHandle<Error>>, JobErrorOrDispatcherError<media_processor::Error>> {
 		let db = job_ctx.db();
 		let sync = job_ctx.sync();
+		let reverse_geocoder = job_ctx.reverse_geocoder();
 
 		let (extract_exif_file_paths, extract_ffmpeg_file_paths) = (
 			get_all_children_files_by_extensions(
@@ -633,6 +789,7 @@ impl MediaProcessor {
 					Arc::clone(&self.location_path),
 					Arc::clone(db),
 					Arc::clone(sync),
+					reverse_geocoder.clone(),
 				)
 			})
 			.map(IntoTask::into_task)
@@ -695,6 +852,7 @@ impl MediaProcessor {
 		let db = ctx.db();
 		let reporter: Arc<dyn NewThumbnailReporter> =
 			Arc::new(NewThumbnailsReporter { ctx: ctx.clone() });
+		let video_previews = ctx.video_previews_options();
 
 		let priority_file_paths = get_direct_children_files_by_extensions(
 			parent_iso_file_path,
@@ -737,6 +895,7 @@ impl MediaProcessor {
 					true,
 					Arc::clone(&reporter),
 				)
+				.with_video_previews(video_previews)
 			})
 			.map(IntoTask::into_task)
 			.collect::<Vec<_>>();
@@ -755,6 +914,7 @@ impl MediaProcessor {
 					false,
 					Arc::clone(&reporter),
 				)
+				.with_video_previews(video_previews)
 			})
 			.map(IntoTask::into_task)
 			.collect::<Vec<_>>();
@@ -774,12 +934,73 @@ impl MediaProcessor {
 			.await
 			.map_err(Into::into)
 	}
+
+	#[cfg(feature = "ai")]
+	async fn dispatch_labels_generator_tasks(
+		&mut self,
+		parent_iso_file_path: &IsolatedFilePathData<'_>,
+		dispatcher: &JobTaskDispatcher,
+		ctx: &impl OuterContext,
+	) -> Result<Vec<TaskHandle<Error>>, JobErrorOrDispatcherError<media_processor::Error>> {
+		let Some(image_labeler) = ctx.image_labeler() else {
+			debug!("No image labeler model is loaded, skipping labels generation;");
+			return Ok(Vec::new());
+		};
+
+		let db = ctx.db();
+		let sync = ctx.sync();
+
+		let file_paths = get_all_children_files_by_extensions(
+			parent_iso_file_path,
+			&helpers::image_labels::AVAILABLE_EXTENSIONS,
+			db,
+		)
+		.await?;
+
+		let files_count = file_paths.len() as u64;
+
+		let tasks = file_paths
+			.into_iter()
+			.chunks(BATCH_SIZE)
+			.into_iter()
+			.map(Iterator::collect::<Vec<_>>)
+			.map(|chunked_file_paths| {
+				tasks::LabelsGenerator::new(
+					&chunked_file_paths,
+					parent_iso_file_path.location_id(),
+					Arc::clone(&self.location_path),
+					self.regenerate_labels,
+					Arc::clone(&image_labeler),
+					Arc::clone(db),
+					Arc::clone(sync),
+				)
+			})
+			.map(IntoTask::into_task)
+			.collect::<Vec<_>>();
+
+		debug!(
+			%files_count,
+			tasks_count = tasks.len(),
+			model_version = image_labeler.version(),
+			"Dispatching labels generator tasks;",
+		);
+
+		self.total_labels_generator_files = files_count;
+		self.total_labels_generator_tasks = tasks.len() as u64;
+
+		dispatcher
+			.dispatch_many_boxed(tasks)
+			.await
+			.map_err(Into::into)
+	}
 }
 
 #[derive(Debug, Serialize, Deserialize, Default)]
 struct Metadata {
 	media_data_metrics: MediaExtractorMetrics,
 	thumbnailer_metrics_acc: ThumbnailerMetricsAccumulator,
+	#[serde(default)]
+	labels_generator_metrics: LabelsGeneratorMetrics,
 }
 
 impl From<Metadata> for Vec<ReportOutputMetadata> {
@@ -787,6 +1008,7 @@ impl From<Metadata> for Vec<ReportOutputMetadata> {
 		Metadata {
 			media_data_metrics,
 			thumbnailer_metrics_acc: thumbnailer_metrics_accumulator,
+			labels_generator_metrics,
 		}: Metadata,
 	) -> Self {
 		let thumbnailer_metrics = ThumbnailerMetrics::from(thumbnailer_metrics_accumulator);
@@ -810,6 +1032,13 @@ impl From<Metadata> for Vec<ReportOutputMetadata> {
 				// Thumbnailer
 				//
 				("thumbnailer_metrics".into(), json!(thumbnailer_metrics)),
+				//
+				// Labels generator
+				//
+				(
+					"labels_generator_metrics".into(),
+					json!(labels_generator_metrics),
+				),
 			])),
 		]
 	}
@@ -826,6 +1055,18 @@ struct MediaExtractorMetrics {
 	total_successful_tasks: u64,
 }
 
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct LabelsGeneratorMetrics {
+	labeled: u64,
+	skipped: u64,
+	labels_assigned: u64,
+	mean_db_read_time: Duration,
+	mean_filtering_time: Duration,
+	mean_labeling_time: Duration,
+	mean_db_write_time: Duration,
+	total_successful_tasks: u64,
+}
+
 #[derive(Debug, Serialize, Deserialize, Default)]
 struct ThumbnailerMetricsAccumulator {
 	generated: u64,
@@ -915,7 +1156,8 @@ async fn get_all_children_files_by_extensions(
 				file_path.extension,
 				file_path.cas_id,
 				object.id as 'object_id',
-				object.pub_id as 'object_pub_id'
+				object.pub_id as 'object_pub_id',
+				object.kind as 'object_kind'
 			FROM file_path
 			INNER JOIN object ON object.id = file_path.object_id
 			WHERE
@@ -955,11 +1197,17 @@ struct SaveState {
 	location_path: Arc<PathBuf>,
 	sub_path: Option<PathBuf>,
 	regenerate_thumbnails: bool,
+	#[serde(default)]
+	regenerate_labels: bool,
 
 	total_media_data_extraction_files: u64,
 	total_media_data_extraction_tasks: u64,
 	total_thumbnailer_tasks: u64,
 	total_thumbnailer_files: u64,
+	#[serde(default)]
+	total_labels_generator_tasks: u64,
+	#[serde(default)]
+	total_labels_generator_files: u64,
 
 	phase: Phase,
 
@@ -977,10 +1225,13 @@ impl<OuterCtx: OuterContext> SerializableJob<OuterCtx> for MediaProcessor {
 			location_path,
 			sub_path,
 			regenerate_thumbnails,
+			regenerate_labels,
 			total_media_data_extraction_files,
 			total_media_data_extraction_tasks,
 			total_thumbnailer_tasks,
 			total_thumbnailer_files,
+			total_labels_generator_tasks,
+			total_labels_generator_files,
 			phase,
 			metadata,
 			errors,
@@ -1004,6 +1255,16 @@ impl<OuterCtx: OuterContext> SerializableJob<OuterCtx> for MediaProcessor {
 						.await
 						.map(|bytes| (TaskKind::Thumbnailer, bytes))
 				} else {
+					#[cfg(feature = "ai")]
+					if task.is::<tasks::LabelsGenerator>() {
+						return task
+							.downcast::<tasks::LabelsGenerator>()
+							.expect("just checked")
+							.serialize()
+							.await
+							.map(|bytes| (TaskKind::LabelsGenerator, bytes));
+					}
+
 					unreachable!("Unexpected task type: <task='{task:#?}'>")
 				}
 			})
@@ -1022,10 +1283,13 @@ impl<OuterCtx: OuterContext> SerializableJob<OuterCtx> for MediaProcessor {
 			location_path,
 			sub_path,
 			regenerate_thumbnails,
+			regenerate_labels,
 			total_media_data_extraction_files,
 			total_media_data_extraction_tasks,
 			total_thumbnailer_tasks,
 			total_thumbnailer_files,
+			total_labels_generator_tasks,
+			total_labels_generator_files,
 			phase,
 			metadata,
 			errors,
@@ -1043,10 +1307,13 @@ impl<OuterCtx: OuterContext> SerializableJob<OuterCtx> for MediaProcessor {
 			location_path,
 			sub_path,
 			regenerate_thumbnails,
+			regenerate_labels,
 			total_media_data_extraction_files,
 			total_media_data_extraction_tasks,
 			total_thumbnailer_tasks,
 			total_thumbnailer_files,
+			total_labels_generator_tasks,
+			total_labels_generator_files,
 			phase,
 			metadata,
 			errors,
@@ -1059,10 +1326,13 @@ impl<OuterCtx: OuterContext> SerializableJob<OuterCtx> for MediaProcessor {
 				location_path,
 				sub_path,
 				regenerate_thumbnails,
+				regenerate_labels,
 				total_media_data_extraction_files,
 				total_media_data_extraction_tasks,
 				total_thumbnailer_tasks,
 				total_thumbnailer_files,
+				total_labels_generator_tasks,
+				total_labels_generator_files,
 				phase,
 				metadata,
 				errors,
@@ -24,22 +24,31 @@ pub use tasks::{
 };
 
 pub use helpers::{
-	exif_media_data, ffmpeg_media_data,
+	exif_media_data, ffmpeg_media_data, perceptual_hash,
 	thumbnailer::{
-		can_generate_thumbnail_for_document, can_generate_thumbnail_for_image,
-		generate_single_thumbnail, get_shard_hex, get_thumbnails_directory, GenerateThumbnailArgs,
-		ThumbKey, ThumbnailKind, WEBP_EXTENSION,
+		can_generate_thumbnail_for_code, can_generate_thumbnail_for_document,
+		can_generate_thumbnail_for_image, can_generate_thumbnail_for_text,
+		compute_document_page_path, generate_document_page, generate_single_thumbnail,
+		generate_thumbnail_variant, get_shard_hex, get_thumbnails_directory, GenerateThumbnailArgs,
+		ThumbFormat, ThumbKey, ThumbSize, ThumbnailKind, VideoPreviewKind, VideoPreviewsOptions,
+		EPHEMERAL_DIR, WEBP_EXTENSION,
 	},
 };
 
 #[cfg(feature = "ffmpeg")]
 pub use helpers::thumbnailer::can_generate_thumbnail_for_video;
 
+#[cfg(feature = "ai")]
+pub use tasks::labels_generator::{self, LabelsGenerator};
+
 pub use shallow::shallow;
 
 use media_data_extractor::NonCriticalMediaDataExtractorError;
 use thumbnailer::{NewThumbnailReporter, NonCriticalThumbnailerError};
 
+#[cfg(feature = "ai")]
+use labels_generator::NonCriticalLabelsGeneratorError;
+
 const BATCH_SIZE: usize = 10;
 
 #[derive(thiserror::Error, Debug)]
@@ -76,6 +85,9 @@ pub enum NonCriticalMediaProcessorError {
 	MediaDataExtractor(#[from] NonCriticalMediaDataExtractorError),
 	#[error(transparent)]
 	Thumbnailer(#[from] NonCriticalThumbnailerError),
+	#[cfg(feature = "ai")]
+	#[error(transparent)]
+	LabelsGenerator(#[from] NonCriticalLabelsGeneratorError),
 }
 
 #[derive(Clone)]
@@ -106,6 +118,7 @@ struct RawFilePathForMediaProcessor {
 	cas_id: file_path::cas_id::Type,
 	object_id: object::id::Type,
 	object_pub_id: object::pub_id::Type,
+	object_kind: object::kind::Type,
 }
 
 impl From<RawFilePathForMediaProcessor> for file_path_for_media_processor::Data {
@@ -119,6 +132,7 @@ impl From<RawFilePathForMediaProcessor> for file_path_for_media_processor::Data
 			cas_id,
 			object_id,
 			object_pub_id,
+			object_kind,
 		}: RawFilePathForMediaProcessor,
 	) -> Self {
 		Self {
@@ -131,6 +145,7 @@ impl From<RawFilePathForMediaProcessor> for file_path_for_media_processor::Data
 			object: Some(file_path_for_media_processor::object::Data {
 				id: object_id,
 				pub_id: object_pub_id,
+				kind: object_kind,
 			}),
 		}
 	}
@@ -154,7 +169,8 @@ async fn get_direct_children_files_by_extensions(
 				file_path.extension,
 				file_path.cas_id,
 				object.id as 'object_id',
-				object.pub_id as 'object_pub_id'
+				object.pub_id as 'object_pub_id',
+				object.kind as 'object_kind'
 			FROM file_path
 			INNER JOIN object ON object.id = file_path.object_id
 			WHERE
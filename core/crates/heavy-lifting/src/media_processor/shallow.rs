@@ -6,6 +6,7 @@ use crate::{
 use sd_core_file_path_helper::IsolatedFilePathData;
 use sd_core_sync::Manager as SyncManager;
 
+use sd_media_metadata::exif::ReverseGeocoder;
 use sd_prisma::prisma::{location, PrismaClient};
 use sd_task_system::{
 	BaseTaskDispatcher, CancelTaskOnDrop, IntoTask, TaskDispatcher, TaskHandle, TaskOutput,
@@ -25,7 +26,10 @@ use tracing::{debug, warn};
 
 use super::{
 	get_direct_children_files_by_extensions,
-	helpers::{self, exif_media_data, ffmpeg_media_data, thumbnailer::THUMBNAIL_CACHE_DIR_NAME},
+	helpers::{
+		self, exif_media_data, ffmpeg_media_data, perceptual_hash,
+		thumbnailer::THUMBNAIL_CACHE_DIR_NAME,
+	},
 	tasks::{
 		self, media_data_extractor,
 		thumbnailer::{self, NewThumbnailReporter},
@@ -69,6 +73,7 @@ pub async fn shallow(
 	let media_data_extraction_tasks = dispatch_media_data_extractor_tasks(
 		ctx.db(),
 		ctx.sync(),
+		ctx.reverse_geocoder(),
 		&sub_iso_file_path,
 		&location_path,
 		dispatcher,
@@ -120,11 +125,17 @@ pub async fn shallow(
 					let thumbnailer::Output {
 						total_time,
 						errors: new_errors,
+						perceptual_hashes,
 						..
 					} = *out.downcast::<thumbnailer::Output>().expect("just checked");
 
 					errors.extend(new_errors);
 
+					if let Err(e) = perceptual_hash::save(perceptual_hashes, ctx.db()).await {
+						errors
+							.push(media_processor::NonCriticalMediaProcessorError::from(e).into());
+					}
+
 					completed_thumbnailer_tasks += 1;
 
 					debug!(
@@ -155,6 +166,7 @@ pub async fn shallow(
 async fn dispatch_media_data_extractor_tasks(
 	db: &Arc<PrismaClient>,
 	sync: &Arc<SyncManager>,
+	reverse_geocoder: Option<Arc<ReverseGeocoder>>,
 	parent_iso_file_path: &IsolatedFilePathData<'_>,
 	location_path: &Arc<PathBuf>,
 	dispatcher: &BaseTaskDispatcher<Error>,
@@ -186,6 +198,7 @@ async fn dispatch_media_data_extractor_tasks(
 				Arc::clone(location_path),
 				Arc::clone(db),
 				Arc::clone(sync),
+				reverse_geocoder.clone(),
 			)
 		})
 		.map(IntoTask::into_task)
@@ -231,6 +244,7 @@ async fn dispatch_thumbnailer_tasks(
 	let db = ctx.db();
 	let reporter: Arc<dyn NewThumbnailReporter> =
 		Arc::new(NewThumbnailsReporter { ctx: ctx.clone() });
+	let video_previews = ctx.video_previews_options();
 
 	let file_paths = get_direct_children_files_by_extensions(
 		parent_iso_file_path,
@@ -255,6 +269,7 @@ async fn dispatch_thumbnailer_tasks(
 				true,
 				Arc::clone(&reporter),
 			)
+			.with_video_previews(video_previews)
 		})
 		.map(IntoTask::into_task)
 		.collect::<Vec<_>>();
@@ -0,0 +1,432 @@
+use crate::{
+	media_processor::{self, helpers::image_labels},
+	Error,
+};
+
+use sd_core_file_path_helper::IsolatedFilePathData;
+use sd_core_prisma_helpers::{file_path_for_media_processor, ObjectPubId};
+use sd_core_sync::Manager as SyncManager;
+
+use sd_ai::image_labeler::{ImageLabeler, Labels};
+use sd_prisma::prisma::{file_path, location, object, PrismaClient};
+use sd_task_system::{
+	check_interruption, ExecStatus, Interrupter, InterruptionKind, IntoAnyTaskOutput,
+	SerializableTask, Task, TaskId,
+};
+
+use std::{
+	collections::{HashMap, HashSet},
+	future::IntoFuture,
+	mem,
+	path::{Path, PathBuf},
+	sync::Arc,
+	time::Duration,
+};
+
+use futures::FutureExt;
+use futures_concurrency::future::Race;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tokio::time::Instant;
+use tracing::{debug, instrument, trace, Level};
+
+#[derive(thiserror::Error, Debug, Serialize, Deserialize, Type, Clone)]
+pub enum NonCriticalLabelsGeneratorError {
+	#[error("file path missing object id: <file_path_id='{0}'>")]
+	FilePathMissingObjectId(file_path::id::Type),
+	#[error("failed to construct isolated file path data: <file_path_id='{0}'>: {1}")]
+	FailedToConstructIsolatedFilePathData(file_path::id::Type, String),
+	#[error("failed to load image to label <file='{}'>: {1}", .0.display())]
+	FailedToLoadImage(PathBuf, String),
+	#[error("failed to generate labels for <file='{}'>: {1}", .0.display())]
+	FailedToGenerateLabels(PathBuf, String),
+}
+
+#[derive(Debug)]
+pub struct LabelsGenerator {
+	// Task control
+	id: TaskId,
+
+	// Received input args
+	file_paths: Vec<file_path_for_media_processor::Data>,
+	location_id: location::id::Type,
+	location_path: Arc<PathBuf>,
+	regenerate: bool,
+
+	// Inner state
+	stage: Stage,
+
+	// Out collector
+	output: Output,
+
+	// Dependencies
+	image_labeler: Arc<ImageLabeler>,
+	db: Arc<PrismaClient>,
+	sync: Arc<SyncManager>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+enum Stage {
+	Starting,
+	FetchedObjectsAlreadyLabeled(Vec<object::id::Type>),
+	GeneratingLabels {
+		paths_by_id: HashMap<file_path::id::Type, (PathBuf, object::id::Type, ObjectPubId)>,
+		labels_by_object: Vec<(object::id::Type, ObjectPubId, Labels)>,
+	},
+	SaveLabels {
+		labels_by_object: Vec<(object::id::Type, ObjectPubId, Labels)>,
+	},
+}
+
+/// [`LabelsGenerator`] task output
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct Output {
+	/// How many files were labeled
+	pub labeled: u64,
+	/// How many files were skipped, as they were already labeled by the current model version
+	pub skipped: u64,
+	/// How many labels were assigned to objects
+	pub labels_assigned: u64,
+	/// Time spent reading data from database
+	pub db_read_time: Duration,
+	/// Time spent filtering files to label and files to skip
+	pub filtering_time: Duration,
+	/// Time spent decoding and labeling images
+	pub labeling_time: Duration,
+	/// Time spent writing labels to database
+	pub db_write_time: Duration,
+	/// Errors encountered during the task
+	pub errors: Vec<crate::NonCriticalError>,
+}
+
+#[async_trait::async_trait]
+impl Task<Error> for LabelsGenerator {
+	fn id(&self) -> TaskId {
+		self.id
+	}
+
+	/// Labels are only used for search and the labels explorer, so they aren't latency sensitive,
+	/// and labeling is CPU heavy, so we let other tasks go first
+	fn with_priority(&self) -> bool {
+		false
+	}
+
+	#[instrument(
+		skip_all,
+		fields(
+			task_id = %self.id,
+			location_id = %self.location_id,
+			location_path = %self.location_path.display(),
+			file_paths_count = %self.file_paths.len(),
+			model_version = %self.image_labeler.version(),
+			regenerate = %self.regenerate,
+		),
+		ret(level = Level::TRACE),
+		err,
+	)]
+	#[allow(clippy::blocks_in_conditions)] // Due to `err` on `instrument` macro above
+	async fn run(&mut self, interrupter: &Interrupter) -> Result<ExecStatus, Error> {
+		loop {
+			match &mut self.stage {
+				Stage::Starting => {
+					let db_read_start = Instant::now();
+					let object_ids = if self.regenerate {
+						Vec::new()
+					} else {
+						image_labels::fetch_objects_labeled_with(
+							self.image_labeler.version(),
+							self.file_paths
+								.iter()
+								.filter_map(|file_path| {
+									file_path.object.as_ref().map(|object| object.id)
+								})
+								.collect(),
+							&self.db,
+						)
+						.await?
+					};
+					self.output.db_read_time = db_read_start.elapsed();
+					trace!(
+						object_ids_count = object_ids.len(),
+						"Fetched objects already labeled;",
+					);
+
+					self.stage = Stage::FetchedObjectsAlreadyLabeled(object_ids);
+				}
+
+				Stage::FetchedObjectsAlreadyLabeled(objects_already_labeled) => {
+					if self.file_paths.len() == objects_already_labeled.len() {
+						self.output.skipped = self.file_paths.len() as u64; // Files already labeled, skipping
+						debug!("Skipped all files as they are already labeled");
+						break;
+					}
+
+					let filtering_start = Instant::now();
+					let paths_by_id = filter_files_to_label(
+						mem::take(objects_already_labeled),
+						self.location_id,
+						&self.location_path,
+						&mut self.file_paths,
+						&mut self.output,
+					);
+					self.output.filtering_time = filtering_start.elapsed();
+
+					trace!(
+						paths_needing_labels_count = paths_by_id.len(),
+						"Filtered files to label;",
+					);
+
+					self.stage = Stage::GeneratingLabels {
+						labels_by_object: Vec::with_capacity(paths_by_id.len()),
+						paths_by_id,
+					};
+				}
+
+				Stage::GeneratingLabels {
+					paths_by_id,
+					labels_by_object,
+				} => {
+					let labeling_start = Instant::now();
+
+					// Labeling already uses all cores, so we label one image at a time
+					let file_path_ids = paths_by_id.keys().copied().collect::<Vec<_>>();
+					for file_path_id in file_path_ids {
+						let (path, object_id, object_pub_id) = paths_by_id
+							.get(&file_path_id)
+							.cloned()
+							.expect("we just got the ids from this map");
+
+						match (
+							image_labels::generate(Arc::clone(&self.image_labeler), path)
+								.map(InterruptRace::Processed),
+							interrupter.into_future().map(InterruptRace::Interrupted),
+						)
+							.race()
+							.await
+						{
+							InterruptRace::Processed(Ok(labels)) => {
+								labels_by_object.push((object_id, object_pub_id, labels));
+								self.output.labeled += 1;
+							}
+
+							InterruptRace::Processed(Err(e)) => {
+								self.output.errors.push(
+									media_processor::NonCriticalMediaProcessorError::from(e).into(),
+								);
+							}
+
+							InterruptRace::Interrupted(kind) => {
+								self.output.labeling_time += labeling_start.elapsed();
+								return Ok(match kind {
+									InterruptionKind::Pause => ExecStatus::Paused,
+									InterruptionKind::Cancel => ExecStatus::Canceled,
+								});
+							}
+						}
+
+						paths_by_id.remove(&file_path_id);
+					}
+
+					self.output.labeling_time += labeling_start.elapsed();
+
+					self.stage = Stage::SaveLabels {
+						labels_by_object: mem::take(labels_by_object),
+					};
+				}
+
+				Stage::SaveLabels { labels_by_object } => {
+					let db_write_start = Instant::now();
+					self.output.labels_assigned = image_labels::save(
+						mem::take(labels_by_object),
+						self.image_labeler.version(),
+						&self.db,
+						&self.sync,
+					)
+					.await?;
+					self.output.db_write_time = db_write_start.elapsed();
+
+					self.output.skipped += self.output.errors.len() as u64;
+
+					break;
+				}
+			}
+
+			check_interruption!(interrupter);
+		}
+
+		Ok(ExecStatus::Done(mem::take(&mut self.output).into_output()))
+	}
+}
+
+impl LabelsGenerator {
+	#[must_use]
+	pub fn new(
+		file_paths: &[file_path_for_media_processor::Data],
+		location_id: location::id::Type,
+		location_path: Arc<PathBuf>,
+		regenerate: bool,
+		image_labeler: Arc<ImageLabeler>,
+		db: Arc<PrismaClient>,
+		sync: Arc<SyncManager>,
+	) -> Self {
+		let mut output = Output::default();
+
+		Self {
+			id: TaskId::new_v4(),
+			file_paths: file_paths
+				.iter()
+				.filter(|file_path| {
+					if file_path.object.is_some() {
+						true
+					} else {
+						output.errors.push(
+							media_processor::NonCriticalMediaProcessorError::from(
+								NonCriticalLabelsGeneratorError::FilePathMissingObjectId(
+									file_path.id,
+								),
+							)
+							.into(),
+						);
+						false
+					}
+				})
+				.cloned()
+				.collect(),
+			location_id,
+			location_path,
+			regenerate,
+			stage: Stage::Starting,
+			output,
+			image_labeler,
+			db,
+			sync,
+		}
+	}
+}
+
+enum InterruptRace {
+	Interrupted(InterruptionKind),
+	Processed(Result<Labels, NonCriticalLabelsGeneratorError>),
+}
+
+#[inline]
+fn filter_files_to_label(
+	objects_already_labeled: Vec<object::id::Type>,
+	location_id: location::id::Type,
+	location_path: &Path,
+	file_paths: &mut Vec<file_path_for_media_processor::Data>,
+	Output {
+		skipped, errors, ..
+	}: &mut Output,
+) -> HashMap<file_path::id::Type, (PathBuf, object::id::Type, ObjectPubId)> {
+	let unique_objects_already_labeled =
+		objects_already_labeled.into_iter().collect::<HashSet<_>>();
+
+	*skipped = unique_objects_already_labeled.len() as u64;
+
+	file_paths.retain(|file_path| {
+		!unique_objects_already_labeled
+			.contains(&file_path.object.as_ref().expect("already checked").id)
+	});
+
+	file_paths
+		.iter()
+		.filter_map(|file_path| {
+			IsolatedFilePathData::try_from((location_id, file_path))
+				.map_err(|e| {
+					errors.push(
+						media_processor::NonCriticalMediaProcessorError::from(
+							NonCriticalLabelsGeneratorError::FailedToConstructIsolatedFilePathData(
+								file_path.id,
+								e.to_string(),
+							),
+						)
+						.into(),
+					);
+				})
+				.map(|iso_file_path| {
+					let object = file_path.object.as_ref().expect("already checked");
+
+					(
+						file_path.id,
+						(
+							location_path.join(iso_file_path),
+							object.id,
+							object.pub_id.as_slice().into(),
+						),
+					)
+				})
+				.ok()
+		})
+		.collect()
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SaveState {
+	id: TaskId,
+	file_paths: Vec<file_path_for_media_processor::Data>,
+	location_id: location::id::Type,
+	location_path: Arc<PathBuf>,
+	regenerate: bool,
+	stage: Stage,
+	output: Output,
+}
+
+impl SerializableTask<Error> for LabelsGenerator {
+	type SerializeError = rmp_serde::encode::Error;
+
+	type DeserializeError = rmp_serde::decode::Error;
+
+	type DeserializeCtx = (Arc<ImageLabeler>, Arc<PrismaClient>, Arc<SyncManager>);
+
+	async fn serialize(self) -> Result<Vec<u8>, Self::SerializeError> {
+		let Self {
+			id,
+			file_paths,
+			location_id,
+			location_path,
+			regenerate,
+			stage,
+			output,
+			..
+		} = self;
+
+		rmp_serde::to_vec_named(&SaveState {
+			id,
+			file_paths,
+			location_id,
+			location_path,
+			regenerate,
+			stage,
+			output,
+		})
+	}
+
+	async fn deserialize(
+		data: &[u8],
+		(image_labeler, db, sync): Self::DeserializeCtx,
+	) -> Result<Self, Self::DeserializeError> {
+		rmp_serde::from_slice(data).map(
+			|SaveState {
+			     id,
+			     file_paths,
+			     location_id,
+			     location_path,
+			     regenerate,
+			     stage,
+			     output,
+			 }| Self {
+				id,
+				file_paths,
+				location_id,
+				location_path,
+				regenerate,
+				stage,
+				output,
+				image_labeler,
+				db,
+				sync,
+			},
+		)
+	}
+}
@@ -10,7 +10,8 @@ use sd_core_file_path_helper::IsolatedFilePathData;
 use sd_core_prisma_helpers::{file_path_for_media_processor, ObjectPubId};
 use sd_core_sync::Manager as SyncManager;
 
-use sd_media_metadata::{ExifMetadata, FFmpegMetadata};
+use sd_file_ext::kind::ObjectKind;
+use sd_media_metadata::{exif::ReverseGeocoder, ExifMetadata, FFmpegMetadata};
 use sd_prisma::prisma::{exif_data, ffmpeg_data, file_path, location, object, PrismaClient};
 use sd_task_system::{
 	check_interruption, ExecStatus, Interrupter, InterruptionKind, IntoAnyTaskOutput,
@@ -29,6 +30,7 @@ use std::{
 
 use futures::{stream::FuturesUnordered, FutureExt, StreamExt};
 use futures_concurrency::future::Race;
+use prisma_client_rust::not;
 use serde::{Deserialize, Serialize};
 use specta::Type;
 use tokio::time::Instant;
@@ -70,6 +72,16 @@ pub struct MediaDataExtractor {
 	// Dependencies
 	db: Arc<PrismaClient>,
 	sync: Arc<SyncManager>,
+	reverse_geocoder: Option<Arc<ReverseGeocoder>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PathToExtract {
+	path: PathBuf,
+	object_id: object::id::Type,
+	object_pub_id: ObjectPubId,
+	/// Audio objects always get a waveform, even an empty one, so they aren't extracted again
+	is_audio: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -77,7 +89,7 @@ enum Stage {
 	Starting,
 	FetchedObjectsAlreadyWithMediaData(Vec<object::id::Type>),
 	ExtractingMediaData {
-		paths_by_id: HashMap<file_path::id::Type, (PathBuf, object::id::Type, ObjectPubId)>,
+		paths_by_id: HashMap<file_path::id::Type, PathToExtract>,
 		exif_media_datas: Vec<(ExifMetadata, object::id::Type, ObjectPubId)>,
 		ffmpeg_media_datas: Vec<(FFmpegMetadata, object::id::Type)>,
 		extract_ids_to_remove_from_map: Vec<file_path::id::Type>,
@@ -208,6 +220,7 @@ impl Task<Error> for MediaDataExtractor {
 						let mut futures = pin!(prepare_extraction_futures(
 							self.kind,
 							paths_by_id,
+							self.reverse_geocoder.as_deref(),
 							interrupter
 						));
 
@@ -276,6 +289,7 @@ impl MediaDataExtractor {
 		location_path: Arc<PathBuf>,
 		db: Arc<PrismaClient>,
 		sync: Arc<SyncManager>,
+		reverse_geocoder: Option<Arc<ReverseGeocoder>>,
 	) -> Self {
 		let mut output = Output::default();
 
@@ -306,10 +320,12 @@ impl MediaDataExtractor {
 			stage: Stage::Starting,
 			db,
 			sync,
+			reverse_geocoder,
 			output,
 		}
 	}
 
+	/// Without a `reverse_geocoder`, the EXIF data is saved without the place where media was taken
 	#[must_use]
 	pub fn new_exif(
 		file_paths: &[file_path_for_media_processor::Data],
@@ -317,8 +333,17 @@ impl MediaDataExtractor {
 		location_path: Arc<PathBuf>,
 		db: Arc<PrismaClient>,
 		sync: Arc<SyncManager>,
+		reverse_geocoder: Option<Arc<ReverseGeocoder>>,
 	) -> Self {
-		Self::new(Kind::Exif, file_paths, location_id, location_path, db, sync)
+		Self::new(
+			Kind::Exif,
+			file_paths,
+			location_id,
+			location_path,
+			db,
+			sync,
+			reverse_geocoder,
+		)
 	}
 
 	#[must_use]
@@ -336,6 +361,7 @@ impl MediaDataExtractor {
 			location_path,
 			db,
 			sync,
+			None,
 		)
 	}
 }
@@ -363,7 +389,16 @@ async fn fetch_objects_already_with_media_data(
 
 		Kind::FFmpeg => db
 			.ffmpeg_data()
-			.find_many(vec![ffmpeg_data::object_id::in_vec(object_ids)])
+			.find_many(vec![
+				ffmpeg_data::object_id::in_vec(object_ids),
+				// Audio extracted before waveforms existed goes through extraction again to get one
+				not![
+					ffmpeg_data::waveform::equals(None),
+					ffmpeg_data::object::is(vec![object::kind::equals(Some(
+						ObjectKind::Audio as i32
+					))]),
+				],
+			])
 			.select(ffmpeg_data::select!({ object_id }))
 			.exec()
 			.await
@@ -381,7 +416,7 @@ fn filter_files_to_extract_media_data(
 	Output {
 		skipped, errors, ..
 	}: &mut Output,
-) -> HashMap<file_path::id::Type, (PathBuf, object::id::Type, ObjectPubId)> {
+) -> HashMap<file_path::id::Type, PathToExtract> {
 	let unique_objects_already_with_media_data = objects_already_with_media_data
 		.into_iter()
 		.collect::<HashSet<_>>();
@@ -413,11 +448,12 @@ fn filter_files_to_extract_media_data(
 
 					(
 						file_path.id,
-						(
-							location_path.join(iso_file_path),
-							object.id,
-							object.pub_id.as_slice().into(),
-						),
+						PathToExtract {
+							path: location_path.join(iso_file_path),
+							object_id: object.id,
+							object_pub_id: object.pub_id.as_slice().into(),
+							is_audio: object.kind == Some(ObjectKind::Audio as i32),
+						},
 					)
 				})
 				.ok()
@@ -450,24 +486,33 @@ enum InterruptRace {
 #[inline]
 fn prepare_extraction_futures<'a>(
 	kind: Kind,
-	paths_by_id: &'a HashMap<file_path::id::Type, (PathBuf, object::id::Type, ObjectPubId)>,
+	paths_by_id: &'a HashMap<file_path::id::Type, PathToExtract>,
+	reverse_geocoder: Option<&'a ReverseGeocoder>,
 	interrupter: &'a Interrupter,
 ) -> FuturesUnordered<impl Future<Output = InterruptRace> + 'a> {
 	paths_by_id
 		.iter()
 		.map(
-			|(file_path_id, (path, object_id, object_pub_id))| async move {
+			|(
+				file_path_id,
+				PathToExtract {
+					path,
+					object_id,
+					object_pub_id,
+					is_audio,
+				},
+			)| async move {
 				InterruptRace::Processed(ExtractionOutput {
 					file_path_id: *file_path_id,
 					object_id: *object_id,
 					object_pub_id: object_pub_id.clone(),
 					kind: match kind {
-						Kind::Exif => {
-							ExtractionOutputKind::Exif(exif_media_data::extract(path).await)
-						}
-						Kind::FFmpeg => {
-							ExtractionOutputKind::FFmpeg(ffmpeg_media_data::extract(path).await)
-						}
+						Kind::Exif => ExtractionOutputKind::Exif(
+							exif_media_data::extract(path, reverse_geocoder).await,
+						),
+						Kind::FFmpeg => ExtractionOutputKind::FFmpeg(
+							ffmpeg_media_data::extract(path, *is_audio).await,
+						),
 					},
 				})
 			},
@@ -550,7 +595,11 @@ impl SerializableTask<Error> for MediaDataExtractor {
 
 	type DeserializeError = rmp_serde::decode::Error;
 
-	type DeserializeCtx = (Arc<PrismaClient>, Arc<SyncManager>);
+	type DeserializeCtx = (
+		Arc<PrismaClient>,
+		Arc<SyncManager>,
+		Option<Arc<ReverseGeocoder>>,
+	);
 
 	async fn serialize(self) -> Result<Vec<u8>, Self::SerializeError> {
 		let Self {
@@ -577,7 +626,7 @@ impl SerializableTask<Error> for MediaDataExtractor {
 
 	async fn deserialize(
 		data: &[u8],
-		(db, sync): Self::DeserializeCtx,
+		(db, sync, reverse_geocoder): Self::DeserializeCtx,
 	) -> Result<Self, Self::DeserializeError> {
 		rmp_serde::from_slice(data).map(
 			|SaveState {
@@ -598,6 +647,7 @@ impl SerializableTask<Error> for MediaDataExtractor {
 				output,
 				db,
 				sync,
+				reverse_geocoder,
 			},
 		)
 	}
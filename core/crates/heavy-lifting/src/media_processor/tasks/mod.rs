@@ -1,5 +1,9 @@
+#[cfg(feature = "ai")]
+pub mod labels_generator;
 pub mod media_data_extractor;
 pub mod thumbnailer;
 
+#[cfg(feature = "ai")]
+pub use labels_generator::LabelsGenerator;
 pub use media_data_extractor::MediaDataExtractor;
 pub use thumbnailer::Thumbnailer;
@@ -12,7 +12,8 @@ use crate::{
 	media_processor::{
 		self,
 		helpers::thumbnailer::{
-			generate_thumbnail, GenerateThumbnailArgs, GenerationStatus, THUMBNAILER_TASK_TIMEOUT,
+			generate_thumbnail, generate_video_previews, GenerateThumbnailArgs, GenerationStatus,
+			VideoPreviewKind, VideoPreviewsOptions, THUMBNAILER_TASK_TIMEOUT,
 		},
 		ThumbKey, ThumbnailKind,
 	},
@@ -22,6 +23,7 @@ use crate::{
 use sd_core_file_path_helper::IsolatedFilePathData;
 use sd_core_prisma_helpers::{file_path_for_media_processor, CasId};
 
+use sd_images::PerceptualHash;
 use sd_prisma::prisma::{file_path, location};
 use sd_task_system::{
 	ExecStatus, Interrupter, InterruptionKind, IntoAnyTaskOutput, SerializableTask, Task, TaskId,
@@ -63,6 +65,7 @@ pub struct Thumbnailer {
 	thumbnails_directory_path: Arc<PathBuf>,
 	thumbnails_to_generate: HashMap<ThumbnailId, GenerateThumbnailArgs<'static>>,
 	should_regenerate: bool,
+	video_previews: VideoPreviewsOptions,
 
 	// Inner state
 	already_processed_ids: Vec<ThumbnailId>,
@@ -94,6 +97,7 @@ impl Task<Error> for Thumbnailer {
 			task_id = %self.id,
 			thumbs_kind = ?self.thumbs_kind,
 			should_regenerate = self.should_regenerate,
+			video_previews = ?self.video_previews,
 			thumbnails_to_generate_count = self.thumbnails_to_generate.len(),
 			already_processed_ids_count = self.already_processed_ids.len(),
 			with_priority = self.with_priority,
@@ -114,12 +118,20 @@ impl Task<Error> for Thumbnailer {
 			thumbnails_to_generate,
 			already_processed_ids,
 			should_regenerate,
+			video_previews,
 			with_priority,
 			reporter,
 			output,
 			..
 		} = self;
 
+		let (thumbnails_directory_path, thumbs_kind, should_regenerate, video_previews) = (
+			thumbnails_directory_path.as_path(),
+			&*thumbs_kind,
+			*should_regenerate,
+			*video_previews,
+		);
+
 		// Removing already processed thumbnails from a possible previous run
 		already_processed_ids.drain(..).for_each(|id| {
 			thumbnails_to_generate.remove(&id);
@@ -134,9 +146,26 @@ impl Task<Error> for Thumbnailer {
 					thumbnails_directory_path,
 					generate_args,
 					thumbs_kind,
-					*should_regenerate,
+					should_regenerate,
 				)
-				.map(|res| InterruptRace::Processed((*id, res)))
+				.then(move |(elapsed_time, res)| async move {
+					// Only trying to generate video previews if we could read the video to generate
+					// its thumbnail
+					let video_previews_errors = if res.is_ok() && video_previews.is_enabled() {
+						generate_video_previews(
+							thumbnails_directory_path,
+							generate_args,
+							thumbs_kind,
+							video_previews,
+							should_regenerate,
+						)
+						.await
+					} else {
+						Vec::new()
+					};
+
+					InterruptRace::Processed((*id, (elapsed_time, res), video_previews_errors))
+				})
 			})
 			.map(|fut| {
 				(
@@ -203,6 +232,10 @@ pub struct Output {
 	pub total_time: Duration,
 	pub mean_time_acc: f64,
 	pub std_dev_acc: f64,
+	/// Perceptual hashes of the generated thumbnails, by their ids, which for indexed thumbnails
+	/// are the ids of their file paths
+	#[serde(default)]
+	pub perceptual_hashes: Vec<(ThumbnailId, PerceptualHash)>,
 }
 
 #[derive(thiserror::Error, Debug, Serialize, Deserialize, Type, Clone)]
@@ -213,10 +246,16 @@ pub enum NonCriticalThumbnailerError {
 	FailedToExtractIsolatedFilePathData(file_path::id::Type, String),
 	#[error("failed to generate video file thumbnail <path='{}'>: {1}", .0.display())]
 	VideoThumbnailGenerationFailed(PathBuf, String),
+	#[error("failed to generate video {1:?} <path='{}'>: {2}", .0.display())]
+	VideoPreviewGenerationFailed(PathBuf, VideoPreviewKind, String),
 	#[error("failed to format image <path='{}'>: {1}", .0.display())]
 	FormatImage(PathBuf, String),
+	#[error("document has no page at index {1} <path='{}'>", .0.display())]
+	DocumentPageNotFound(PathBuf, u16),
 	#[error("failed to encode webp image <path='{}'>: {1}", .0.display())]
 	WebPEncoding(PathBuf, String),
+	#[error("failed to encode image <path='{}'>: {1}", .0.display())]
+	ImageEncoding(PathBuf, String),
 	#[error("processing thread panicked while generating thumbnail from <path='{}'>: {1}", .0.display())]
 	PanicWhileGeneratingThumbnail(PathBuf, String),
 	#[error("failed to create shard directory for thumbnail: {0}")]
@@ -225,6 +264,8 @@ pub enum NonCriticalThumbnailerError {
 	SaveThumbnail(PathBuf, String),
 	#[error("task timed out: {0}")]
 	TaskTimeout(TaskId),
+	#[error("failed to save perceptual hashes: {0}")]
+	SavePerceptualHashes(String),
 }
 
 impl Thumbnailer {
@@ -244,6 +285,7 @@ impl Thumbnailer {
 			already_processed_ids: Vec::with_capacity(thumbnails_to_generate.len()),
 			thumbnails_to_generate,
 			should_regenerate,
+			video_previews: VideoPreviewsOptions::default(),
 			with_priority,
 			output: Output {
 				errors,
@@ -281,6 +323,13 @@ impl Thumbnailer {
 		)
 	}
 
+	/// Videos previews are only generated for indexed thumbnails
+	#[must_use]
+	pub const fn with_video_previews(mut self, video_previews: VideoPreviewsOptions) -> Self {
+		self.video_previews = video_previews;
+		self
+	}
+
 	#[must_use]
 	pub fn new_indexed(
 		thumbnails_directory_path: Arc<PathBuf>,
@@ -357,7 +406,7 @@ impl Thumbnailer {
 
 #[instrument(skip_all, fields(thumb_id = id, %generated, %skipped, ?elapsed_time, ?res))]
 fn process_thumbnail_generation_output(
-	(id, (elapsed_time, res)): ThumbnailGenerationOutput,
+	(id, (elapsed_time, res), video_previews_errors): ThumbnailGenerationOutput,
 	with_priority: bool,
 	reporter: &dyn NewThumbnailReporter,
 	already_processed_ids: &mut Vec<ThumbnailId>,
@@ -367,6 +416,7 @@ fn process_thumbnail_generation_output(
 		errors,
 		mean_time_acc: mean_generation_time_accumulator,
 		std_dev_acc: std_dev_accumulator,
+		perceptual_hashes,
 		..
 	}: &mut Output,
 ) {
@@ -377,8 +427,9 @@ fn process_thumbnail_generation_output(
 	match res {
 		Ok((thumb_key, status)) => {
 			match status {
-				GenerationStatus::Generated => {
+				GenerationStatus::Generated(perceptual_hash) => {
 					*generated += 1;
+					perceptual_hashes.extend(perceptual_hash.map(|hash| (id, hash)));
 					// This if is REALLY needed, due to the sheer performance of the thumbnailer,
 					// I restricted to only send events notifying for thumbnails in the current
 					// opened directory, sending events for the entire location turns into a
@@ -400,6 +451,12 @@ fn process_thumbnail_generation_output(
 		}
 	}
 
+	errors.extend(
+		video_previews_errors
+			.into_iter()
+			.map(|e| media_processor::NonCriticalMediaProcessorError::from(e).into()),
+	);
+
 	already_processed_ids.push(id);
 
 	trace!("Thumbnail processed");
@@ -412,6 +469,8 @@ struct SaveState {
 	thumbnails_directory_path: Arc<PathBuf>,
 	thumbnails_to_generate: HashMap<ThumbnailId, GenerateThumbnailArgs<'static>>,
 	should_regenerate: bool,
+	#[serde(default)]
+	video_previews: VideoPreviewsOptions,
 	with_priority: bool,
 	output: Output,
 }
@@ -431,6 +490,7 @@ impl SerializableTask<Error> for Thumbnailer {
 			mut thumbnails_to_generate,
 			already_processed_ids,
 			should_regenerate,
+			video_previews,
 			with_priority,
 			output,
 			..
@@ -446,6 +506,7 @@ impl SerializableTask<Error> for Thumbnailer {
 			thumbnails_directory_path,
 			thumbnails_to_generate,
 			should_regenerate,
+			video_previews,
 			with_priority,
 			output,
 		})
@@ -462,6 +523,7 @@ impl SerializableTask<Error> for Thumbnailer {
 			     thumbnails_to_generate,
 			     thumbnails_directory_path,
 			     should_regenerate,
+			     video_previews,
 			     with_priority,
 			     output,
 			 }| Self {
@@ -472,6 +534,7 @@ impl SerializableTask<Error> for Thumbnailer {
 				thumbnails_directory_path,
 				already_processed_ids: Vec::new(),
 				should_regenerate,
+				video_previews,
 				with_priority,
 				output,
 			},
@@ -485,4 +548,5 @@ type ThumbnailGenerationOutput = (
 		Duration,
 		Result<(ThumbKey, GenerationStatus), NonCriticalThumbnailerError>,
 	),
+	Vec<NonCriticalThumbnailerError>,
 );
@@ -27,6 +27,7 @@
 #![forbid(deprecated_in_future)]
 #![allow(clippy::missing_errors_doc)]
 
+use sd_file_ext::{extensions::Extension, kind::ObjectKind, magic::ExtensionPossibility};
 use sd_prisma::prisma::{indexer_rule, PrismaClient};
 use sd_utils::{
 	db::{maybe_missing, MissingFieldError},
@@ -50,6 +51,7 @@ use rmp_serde::{decode, encode};
 use rspc::ErrorCode;
 
 use specta::Type;
+use strum::IntoEnumIterator;
 use thiserror::Error;
 use tokio::fs;
 use tracing::{debug, instrument, trace};
@@ -63,6 +65,11 @@ pub enum Error {
 	// User errors
 	#[error("invalid indexer rule kind integer: {0}")]
 	InvalidRuleKindInt(i32),
+	#[error("invalid parameters for indexer rule kind {kind:?}: {parameters:?}")]
+	InvalidRuleParameters {
+		kind: RuleKind,
+		parameters: Vec<String>,
+	},
 	#[error("glob builder error: {0}")]
 	Glob(#[from] globset::Error),
 	#[error(transparent)]
@@ -86,9 +93,10 @@ pub enum Error {
 impl From<Error> for rspc::Error {
 	fn from(e: Error) -> Self {
 		match e {
-			Error::InvalidRuleKindInt(_) | Error::Glob(_) | Error::NonUtf8Path(_) => {
-				Self::with_cause(ErrorCode::BadRequest, e.to_string(), e)
-			}
+			Error::InvalidRuleKindInt(_)
+			| Error::InvalidRuleParameters { .. }
+			| Error::Glob(_)
+			| Error::NonUtf8Path(_) => Self::with_cause(ErrorCode::BadRequest, e.to_string(), e),
 
 			_ => Self::with_cause(ErrorCode::InternalServerError, e.to_string(), e),
 		}
@@ -103,6 +111,18 @@ impl From<Error> for rspc::Error {
 ///
 /// In case of `RuleKind::AcceptIfChildrenDirectoriesArePresent` or `RuleKind::RejectIfChildrenDirectoriesArePresent` the
 /// `parameters` field must be a vector of strings containing the names of the directories.
+///
+/// In case of `RuleKind::RejectFilesSmallerThan` or `RuleKind::RejectFilesLargerThan` the
+/// `parameters` field must contain a single integer, the size limit in bytes.
+///
+/// In case of `RuleKind::RejectFilesModifiedBefore` or `RuleKind::RejectFilesModifiedAfter` the
+/// `parameters` field must contain a single RFC 3339 date, like `2024-01-01T00:00:00Z`.
+///
+/// In case of `RuleKind::RejectFilesByXattr` the `parameters` field must be a vector of strings
+/// containing extended attribute names, like `com.dropbox.ignored` or `user.spacedrive.ignore`.
+///
+/// In case of `RuleKind::AcceptFilesByKind` or `RuleKind::RejectFilesByKind` the `parameters`
+/// field must be a vector of `ObjectKind` names, like `Image` or `Video`.
 #[derive(Type, Deserialize)]
 pub struct IndexerRuleCreateArgs {
 	pub name: String,
@@ -128,27 +148,7 @@ impl IndexerRuleCreateArgs {
 			&self
 				.rules
 				.into_iter()
-				.map(|(kind, parameters)| match kind {
-					RuleKind::AcceptFilesByGlob => {
-						RulePerKind::new_accept_files_by_globs_str(parameters)
-					}
-					RuleKind::RejectFilesByGlob => {
-						RulePerKind::new_reject_files_by_globs_str(parameters)
-					}
-					RuleKind::AcceptIfChildrenDirectoriesArePresent => {
-						Ok(RulePerKind::AcceptIfChildrenDirectoriesArePresent(
-							parameters.into_iter().collect(),
-						))
-					}
-					RuleKind::RejectIfChildrenDirectoriesArePresent => {
-						Ok(RulePerKind::RejectIfChildrenDirectoriesArePresent(
-							parameters.into_iter().collect(),
-						))
-					}
-					RuleKind::IgnoredByGit => {
-						Ok(RulePerKind::IgnoredByGit(PathBuf::new(), Search::default()))
-					}
-				})
+				.map(|(kind, parameters)| RulePerKind::new(kind, parameters))
 				.collect::<Result<Vec<_>, _>>()?,
 		)?;
 
@@ -184,14 +184,51 @@ pub enum RuleKind {
 	AcceptIfChildrenDirectoriesArePresent = 2,
 	RejectIfChildrenDirectoriesArePresent = 3,
 	IgnoredByGit = 4,
+	RejectFilesSmallerThan = 5,
+	RejectFilesLargerThan = 6,
+	RejectFilesModifiedBefore = 7,
+	RejectFilesModifiedAfter = 8,
+	RejectFilesByXattr = 9,
+	AcceptFilesByKind = 10,
+	RejectFilesByKind = 11,
+	IgnoredByIgnoreFiles = 12,
 }
 
 impl RuleKind {
 	#[must_use]
 	pub const fn variant_count() -> usize {
 		// TODO: Use https://doc.rust-lang.org/std/mem/fn.variant_count.html if it ever gets stabilized
-		5
+		13
+	}
+}
+
+fn parse_single_parameter<T>(
+	kind: RuleKind,
+	parameters: Vec<String>,
+	parse: impl FnOnce(&str) -> Option<T>,
+) -> Result<T, Error> {
+	match parameters.as_slice() {
+		[parameter] => parse(parameter.trim()),
+		_ => None,
 	}
+	.ok_or(Error::InvalidRuleParameters { kind, parameters })
+}
+
+fn parse_date(date: &str) -> Option<DateTime<Utc>> {
+	DateTime::parse_from_rfc3339(date)
+		.ok()
+		.map(|date| date.with_timezone(&Utc))
+}
+
+fn parse_object_kinds(kind: RuleKind, parameters: Vec<String>) -> Result<Vec<ObjectKind>, Error> {
+	parameters
+		.iter()
+		.map(|name| {
+			ObjectKind::iter()
+				.find(|object_kind| object_kind.to_string().eq_ignore_ascii_case(name.trim()))
+		})
+		.collect::<Option<Vec<_>>>()
+		.ok_or(Error::InvalidRuleParameters { kind, parameters })
 }
 
 /// `ParametersPerKind` is a mapping from `RuleKind` to the parameters required for each kind of rule.
@@ -202,19 +239,74 @@ impl RuleKind {
 /// In case of `ParametersPerKind::AcceptIfChildrenDirectoriesArePresent` or
 /// `ParametersPerKind::RejectIfChildrenDirectoriesArePresent`
 /// first we change the data structure to a vector, then we serialize it.
+///
+/// Size, modification date and kind rejections only apply to files, directories always pass them
+/// so we keep walking into them. Kinds are resolved from the file extension, and directories are
+/// only accepted by kind if `ObjectKind::Folder` is listed, like it happens with accept globs.
+/// Extended attributes are only checked on unix systems.
 #[derive(Debug, Clone)]
 pub enum RulePerKind {
-	// TODO: Add an indexer rule that filter files based on their extended attributes
-	// https://learn.microsoft.com/en-us/windows/win32/fileio/file-attribute-constants
-	// https://en.wikipedia.org/wiki/Extended_file_attributes
 	AcceptFilesByGlob(Vec<Glob>, GlobSet),
 	RejectFilesByGlob(Vec<Glob>, GlobSet),
 	AcceptIfChildrenDirectoriesArePresent(HashSet<String>),
 	RejectIfChildrenDirectoriesArePresent(HashSet<String>),
 	IgnoredByGit(PathBuf, Search),
+	RejectFilesSmallerThan(u64),
+	RejectFilesLargerThan(u64),
+	RejectFilesModifiedBefore(DateTime<Utc>),
+	RejectFilesModifiedAfter(DateTime<Utc>),
+	RejectFilesByXattr(HashSet<String>),
+	AcceptFilesByKind(Vec<ObjectKind>),
+	RejectFilesByKind(Vec<ObjectKind>),
+	IgnoredByIgnoreFiles(PathBuf, Search),
 }
 
 impl RulePerKind {
+	/// Builds a rule from its kind and the parameters received from the client, see
+	/// [`IndexerRuleCreateArgs`] for the expected parameters of each kind
+	pub fn new(kind: RuleKind, parameters: Vec<String>) -> Result<Self, Error> {
+		match kind {
+			RuleKind::AcceptFilesByGlob => Self::new_accept_files_by_globs_str(parameters),
+			RuleKind::RejectFilesByGlob => Self::new_reject_files_by_globs_str(parameters),
+			RuleKind::AcceptIfChildrenDirectoriesArePresent => Ok(
+				Self::AcceptIfChildrenDirectoriesArePresent(parameters.into_iter().collect()),
+			),
+			RuleKind::RejectIfChildrenDirectoriesArePresent => Ok(
+				Self::RejectIfChildrenDirectoriesArePresent(parameters.into_iter().collect()),
+			),
+			RuleKind::IgnoredByGit => Ok(Self::IgnoredByGit(PathBuf::new(), Search::default())),
+			RuleKind::IgnoredByIgnoreFiles => Ok(Self::IgnoredByIgnoreFiles(
+				PathBuf::new(),
+				Search::default(),
+			)),
+			RuleKind::RejectFilesSmallerThan => {
+				parse_single_parameter(kind, parameters, |size| size.parse().ok())
+					.map(Self::RejectFilesSmallerThan)
+			}
+			RuleKind::RejectFilesLargerThan => {
+				parse_single_parameter(kind, parameters, |size| size.parse().ok())
+					.map(Self::RejectFilesLargerThan)
+			}
+			RuleKind::RejectFilesModifiedBefore => {
+				parse_single_parameter(kind, parameters, parse_date)
+					.map(Self::RejectFilesModifiedBefore)
+			}
+			RuleKind::RejectFilesModifiedAfter => {
+				parse_single_parameter(kind, parameters, parse_date)
+					.map(Self::RejectFilesModifiedAfter)
+			}
+			RuleKind::RejectFilesByXattr => {
+				Ok(Self::RejectFilesByXattr(parameters.into_iter().collect()))
+			}
+			RuleKind::AcceptFilesByKind => {
+				parse_object_kinds(kind, parameters).map(Self::AcceptFilesByKind)
+			}
+			RuleKind::RejectFilesByKind => {
+				parse_object_kinds(kind, parameters).map(Self::RejectFilesByKind)
+			}
+		}
+	}
+
 	fn new_files_by_globs_str_and_kind(
 		globs_str: impl IntoIterator<Item = impl AsRef<str>>,
 		kind_fn: impl Fn(Vec<Glob>, GlobSet) -> Self,
@@ -252,12 +344,24 @@ impl RulePerKind {
 
 pub trait MetadataForIndexerRules: Send + Sync + 'static {
 	fn is_dir(&self) -> bool;
+
+	fn size_in_bytes(&self) -> u64;
+
+	fn modified_at(&self) -> Option<DateTime<Utc>>;
 }
 
 impl MetadataForIndexerRules for Metadata {
 	fn is_dir(&self) -> bool {
 		self.is_dir()
 	}
+
+	fn size_in_bytes(&self) -> u64 {
+		self.len()
+	}
+
+	fn modified_at(&self) -> Option<DateTime<Utc>> {
+		self.modified().ok().map(Into::into)
+	}
 }
 
 impl RulePerKind {
@@ -290,6 +394,46 @@ impl RulePerKind {
 				RuleKind::IgnoredByGit,
 				accept_by_git_pattern(source, base_dir, patterns),
 			)),
+			// Ignore files share the `.gitignore` syntax, including negations
+			Self::IgnoredByIgnoreFiles(base_dir, patterns) => Ok((
+				RuleKind::IgnoredByIgnoreFiles,
+				accept_by_git_pattern(source, base_dir, patterns),
+			)),
+
+			Self::RejectFilesSmallerThan(min_size) => Ok((
+				RuleKind::RejectFilesSmallerThan,
+				metadata.is_dir() || metadata.size_in_bytes() >= *min_size,
+			)),
+			Self::RejectFilesLargerThan(max_size) => Ok((
+				RuleKind::RejectFilesLargerThan,
+				metadata.is_dir() || metadata.size_in_bytes() <= *max_size,
+			)),
+			Self::RejectFilesModifiedBefore(date) => Ok((
+				RuleKind::RejectFilesModifiedBefore,
+				metadata.is_dir()
+					|| metadata
+						.modified_at()
+						.map_or(true, |modified_at| modified_at >= *date),
+			)),
+			Self::RejectFilesModifiedAfter(date) => Ok((
+				RuleKind::RejectFilesModifiedAfter,
+				metadata.is_dir()
+					|| metadata
+						.modified_at()
+						.map_or(true, |modified_at| modified_at <= *date),
+			)),
+			Self::RejectFilesByXattr(xattrs) => Ok((
+				RuleKind::RejectFilesByXattr,
+				reject_by_xattr(source, xattrs),
+			)),
+			Self::AcceptFilesByKind(kinds) => Ok((
+				RuleKind::AcceptFilesByKind,
+				accept_by_kind(source, metadata, kinds),
+			)),
+			Self::RejectFilesByKind(kinds) => Ok((
+				RuleKind::RejectFilesByKind,
+				metadata.is_dir() || !accept_by_kind(source, metadata, kinds),
+			)),
 		}
 	}
 }
@@ -302,7 +446,7 @@ fn accept_by_git_pattern(
 	fn inner(source: &Path, base_dir: &Path, search: &Search) -> bool {
 		let relative = source
 			.strip_prefix(base_dir)
-			.expect("`base_dir` should be our git repo, and `source` should be inside of it");
+			.expect("`source` should be inside of `base_dir`, our git repo or location");
 
 		let Some(src) = relative.to_str().map(|s| s.as_bytes().into()) else {
 			return false;
@@ -458,8 +602,57 @@ impl IndexerRuler {
 	) -> bool {
 		Self::rejected_by_reject_glob(acceptance_per_rule_kind)
 			|| Self::rejected_by_git_ignore(acceptance_per_rule_kind)
+			|| Self::rejected_by_ignore_files(acceptance_per_rule_kind)
+			|| Self::rejected_by_file_filters(acceptance_per_rule_kind).is_some()
 			|| (is_dir && Self::rejected_by_children_directories(acceptance_per_rule_kind))
 			|| Self::rejected_by_accept_glob(acceptance_per_rule_kind)
+			|| Self::rejected_by_accept_kind(acceptance_per_rule_kind)
+	}
+
+	pub fn rejected_by_accept_kind(
+		acceptance_per_rule_kind: &HashMap<RuleKind, Vec<bool>>,
+	) -> bool {
+		let res = acceptance_per_rule_kind
+			.get(&RuleKind::AcceptFilesByKind)
+			.map_or(false, |accept_rules| {
+				accept_rules.iter().all(|accept| !accept)
+			});
+
+		if res {
+			trace!("Reject because it didn't passed in any `RuleKind::AcceptFilesByKind` rules");
+		}
+
+		res
+	}
+
+	/// Size, modification date, extended attributes and kind rejections, returning the kind of the
+	/// first rule that rejected the path
+	#[must_use]
+	pub fn rejected_by_file_filters(
+		acceptance_per_rule_kind: &HashMap<RuleKind, Vec<bool>>,
+	) -> Option<RuleKind> {
+		[
+			RuleKind::RejectFilesSmallerThan,
+			RuleKind::RejectFilesLargerThan,
+			RuleKind::RejectFilesModifiedBefore,
+			RuleKind::RejectFilesModifiedAfter,
+			RuleKind::RejectFilesByXattr,
+			RuleKind::RejectFilesByKind,
+		]
+		.into_iter()
+		.find(|kind| {
+			let res = acceptance_per_rule_kind
+				.get(kind)
+				.map_or(false, |reject_results| {
+					reject_results.iter().any(|reject| !reject)
+				});
+
+			if res {
+				trace!("Rejected by `RuleKind::{kind:?}`");
+			}
+
+			res
+		})
 	}
 
 	pub fn rejected_by_accept_glob(
@@ -523,6 +716,22 @@ impl IndexerRuler {
 
 		res
 	}
+
+	pub fn rejected_by_ignore_files(
+		acceptance_per_rule_kind: &HashMap<RuleKind, Vec<bool>>,
+	) -> bool {
+		let res = acceptance_per_rule_kind
+			.get(&RuleKind::IgnoredByIgnoreFiles)
+			.map_or(false, |reject_results| {
+				reject_results.iter().any(|reject| !reject)
+			});
+
+		if res {
+			trace!("Rejected by `RuleKind::IgnoredByIgnoreFiles`");
+		}
+
+		res
+	}
 }
 
 impl TryFrom<&indexer_rule::Data> for IndexerRule {
@@ -559,6 +768,49 @@ fn reject_by_glob(source: impl AsRef<Path>, reject_glob_set: &GlobSet) -> bool {
 	!accept_by_glob(source.as_ref(), reject_glob_set)
 }
 
+#[cfg(unix)]
+fn reject_by_xattr(source: impl AsRef<Path>, xattrs: &HashSet<String>) -> bool {
+	let source = source.as_ref();
+
+	// Filesystems without extended attributes support just don't have any to reject
+	xattr::list(source).map_or_else(
+		|e| {
+			trace!(source = %source.display(), ?e, "Failed to list extended attributes;");
+			true
+		},
+		|mut names| !names.any(|name| name.to_str().map_or(false, |name| xattrs.contains(name))),
+	)
+}
+
+#[cfg(not(unix))]
+fn reject_by_xattr(_source: impl AsRef<Path>, _xattrs: &HashSet<String>) -> bool {
+	true
+}
+
+fn accept_by_kind(
+	source: impl AsRef<Path>,
+	metadata: &impl MetadataForIndexerRules,
+	kinds: &[ObjectKind],
+) -> bool {
+	if metadata.is_dir() {
+		return kinds.contains(&ObjectKind::Folder);
+	}
+
+	match source
+		.as_ref()
+		.extension()
+		.and_then(|ext| ext.to_str())
+		.and_then(Extension::from_str)
+	{
+		Some(ExtensionPossibility::Known(ext)) => kinds.contains(&ext.into()),
+		// Without reading the file we can't tell which one it is, so any of them will do
+		Some(ExtensionPossibility::Conflicts(exts)) => {
+			exts.into_iter().any(|ext| kinds.contains(&ext.into()))
+		}
+		None => kinds.contains(&ObjectKind::Unknown),
+	}
+}
+
 async fn accept_dir_for_its_children(
 	source: impl AsRef<Path> + Send,
 	metadata: &impl MetadataForIndexerRules,
@@ -689,6 +941,10 @@ mod tests {
 					RuleKind::IgnoredByGit,
 					accept_by_git_pattern(path, git_repo, patterns),
 				),
+				RulePerKind::IgnoredByIgnoreFiles(location_root, patterns) => (
+					RuleKind::IgnoredByIgnoreFiles,
+					accept_by_git_pattern(path, location_root, patterns),
+				),
 
 				_ => unimplemented!("can't use simple `apply` for this rule: {:?}", rule),
 			})
@@ -884,6 +1140,239 @@ mod tests {
 		);
 	}
 
+	#[tokio::test]
+	async fn test_nested_ignore_files() {
+		let root = tempdir().unwrap();
+		let root = root.path();
+
+		let sub = root.join("sub");
+		let build = root.join("build");
+
+		fs::create_dir(&sub).await.unwrap();
+		fs::create_dir(&build).await.unwrap();
+
+		fs::write(root.join(".ignore"), "*.log\nsecret.txt\n")
+			.await
+			.unwrap();
+		fs::write(root.join(".sdignore"), "/build\n!secret.txt\n")
+			.await
+			.unwrap();
+		fs::write(sub.join(".sdignore"), "!keep.log\n")
+			.await
+			.unwrap();
+
+		let rule = IndexerRule::from(
+			seed::IgnoreFileRules::get_rules(root, &sub)
+				.await
+				.unwrap()
+				.unwrap(),
+		);
+
+		assert!(!check_rule(&rule, root.join("debug.log")));
+		assert!(!check_rule(&rule, &build));
+		assert!(check_rule(&rule, root.join("notes.txt")));
+		// `.sdignore` takes precedence over `.ignore` in the same directory
+		assert!(check_rule(&rule, root.join("secret.txt")));
+		// Nested ignore files take precedence over the ones above them
+		assert!(check_rule(&rule, sub.join("keep.log")));
+		assert!(!check_rule(&rule, sub.join("other.log")));
+		// Anchored patterns are relative to the directory of their ignore file
+		assert!(check_rule(&rule, sub.join("build")));
+
+		assert!(seed::IgnoreFileRules::get_rules(&build, &build)
+			.await
+			.is_none());
+	}
+
+	struct FakeMetadata {
+		is_dir: bool,
+		size_in_bytes: u64,
+		modified_at: DateTime<Utc>,
+	}
+
+	impl FakeMetadata {
+		fn file(size_in_bytes: u64, modified_at: &str) -> Self {
+			Self {
+				is_dir: false,
+				size_in_bytes,
+				modified_at: parse_date(modified_at).unwrap(),
+			}
+		}
+
+		fn dir() -> Self {
+			Self {
+				is_dir: true,
+				size_in_bytes: 0,
+				modified_at: parse_date("2000-01-01T00:00:00Z").unwrap(),
+			}
+		}
+	}
+
+	impl MetadataForIndexerRules for FakeMetadata {
+		fn is_dir(&self) -> bool {
+			self.is_dir
+		}
+
+		fn size_in_bytes(&self) -> u64 {
+			self.size_in_bytes
+		}
+
+		fn modified_at(&self) -> Option<DateTime<Utc>> {
+			Some(self.modified_at)
+		}
+	}
+
+	#[tokio::test]
+	async fn test_reject_by_size_and_modified_date() {
+		let rule = IndexerRule::new(
+			"medium sized and recent".to_string(),
+			false,
+			vec![
+				RulePerKind::RejectFilesSmallerThan(1024),
+				RulePerKind::RejectFilesLargerThan(1024 * 1024),
+				RulePerKind::RejectFilesModifiedBefore(parse_date("2020-01-01T00:00:00Z").unwrap()),
+				RulePerKind::RejectFilesModifiedAfter(parse_date("2024-01-01T00:00:00Z").unwrap()),
+			],
+		);
+
+		let path = Path::new("/test/file.txt");
+
+		assert!(
+			check_rule_with_metadata(
+				&rule,
+				path,
+				&FakeMetadata::file(4096, "2022-06-01T00:00:00Z")
+			)
+			.await
+		);
+		assert!(
+			!check_rule_with_metadata(&rule, path, &FakeMetadata::file(10, "2022-06-01T00:00:00Z"))
+				.await
+		);
+		assert!(
+			!check_rule_with_metadata(
+				&rule,
+				path,
+				&FakeMetadata::file(10 * 1024 * 1024, "2022-06-01T00:00:00Z")
+			)
+			.await
+		);
+		assert!(
+			!check_rule_with_metadata(
+				&rule,
+				path,
+				&FakeMetadata::file(4096, "2019-06-01T00:00:00Z")
+			)
+			.await
+		);
+		assert!(
+			!check_rule_with_metadata(
+				&rule,
+				path,
+				&FakeMetadata::file(4096, "2025-06-01T00:00:00Z")
+			)
+			.await
+		);
+		assert!(
+			check_rule_with_metadata(&rule, Path::new("/test/dir"), &FakeMetadata::dir()).await
+		);
+	}
+
+	#[tokio::test]
+	async fn test_accept_and_reject_by_kind() {
+		let only_media = IndexerRule::new(
+			"only media".to_string(),
+			false,
+			vec![RulePerKind::AcceptFilesByKind(vec![
+				ObjectKind::Image,
+				ObjectKind::Video,
+			])],
+		);
+		let no_code = IndexerRule::new(
+			"no code".to_string(),
+			false,
+			vec![RulePerKind::RejectFilesByKind(vec![ObjectKind::Code])],
+		);
+
+		let file = FakeMetadata::file(4096, "2022-06-01T00:00:00Z");
+
+		assert!(check_rule_with_metadata(&only_media, "/test/photo.JPG", &file).await);
+		assert!(check_rule_with_metadata(&only_media, "/test/movie.mp4", &file).await);
+		assert!(!check_rule_with_metadata(&only_media, "/test/notes.txt", &file).await);
+		assert!(!check_rule_with_metadata(&only_media, "/test/no_extension", &file).await);
+		assert!(!check_rule_with_metadata(&only_media, "/test/dir", &FakeMetadata::dir()).await);
+
+		assert!(!check_rule_with_metadata(&no_code, "/test/main.rs", &file).await);
+		assert!(check_rule_with_metadata(&no_code, "/test/photo.png", &file).await);
+		assert!(check_rule_with_metadata(&no_code, "/test/dir", &FakeMetadata::dir()).await);
+	}
+
+	#[cfg(unix)]
+	#[tokio::test]
+	async fn test_reject_by_xattr() {
+		let root = tempdir().unwrap();
+
+		let ignored = root.path().join("ignored.txt");
+		let normal = root.path().join("normal.txt");
+
+		fs::write(&ignored, b"ignored").await.unwrap();
+		fs::write(&normal, b"normal").await.unwrap();
+
+		if xattr::set(&ignored, "user.spacedrive.ignore", b"1").is_err() {
+			// The filesystem holding our temporary directory doesn't support extended attributes
+			return;
+		}
+
+		let rule = IndexerRule::new(
+			"no ignored".to_string(),
+			false,
+			vec![RulePerKind::RejectFilesByXattr(HashSet::from([
+				"user.spacedrive.ignore".to_string(),
+				"com.dropbox.ignored".to_string(),
+			]))],
+		);
+
+		assert!(
+			!check_rule_with_metadata(&rule, &ignored, &fs::metadata(&ignored).await.unwrap())
+				.await
+		);
+		assert!(
+			check_rule_with_metadata(&rule, &normal, &fs::metadata(&normal).await.unwrap()).await
+		);
+	}
+
+	#[test]
+	fn test_parse_rule_parameters() {
+		assert_eq!(
+			parse_single_parameter(
+				RuleKind::RejectFilesLargerThan,
+				vec!["1024".to_string()],
+				|s| { s.parse::<u64>().ok() }
+			)
+			.unwrap(),
+			1024
+		);
+		assert!(matches!(
+			parse_single_parameter(
+				RuleKind::RejectFilesModifiedBefore,
+				vec!["yesterday".to_string()],
+				parse_date
+			),
+			Err(Error::InvalidRuleParameters { .. })
+		));
+		assert_eq!(
+			parse_object_kinds(
+				RuleKind::AcceptFilesByKind,
+				vec!["image".to_string(), "Video".to_string()]
+			)
+			.unwrap(),
+			vec![ObjectKind::Image, ObjectKind::Video]
+		);
+		assert!(
+			parse_object_kinds(RuleKind::AcceptFilesByKind, vec!["Photo".to_string()]).is_err()
+		);
+	}
+
 	impl PartialEq for RulePerKind {
 		fn eq(&self, other: &Self) -> bool {
 			match (self, other) {
@@ -905,6 +1394,33 @@ mod tests {
 					Self::RejectIfChildrenDirectoriesArePresent(other_childrens),
 				) => self_childrens == other_childrens,
 
+				(
+					Self::RejectFilesSmallerThan(self_size),
+					Self::RejectFilesSmallerThan(other_size),
+				)
+				| (
+					Self::RejectFilesLargerThan(self_size),
+					Self::RejectFilesLargerThan(other_size),
+				) => self_size == other_size,
+
+				(
+					Self::RejectFilesModifiedBefore(self_date),
+					Self::RejectFilesModifiedBefore(other_date),
+				)
+				| (
+					Self::RejectFilesModifiedAfter(self_date),
+					Self::RejectFilesModifiedAfter(other_date),
+				) => self_date == other_date,
+
+				(Self::RejectFilesByXattr(self_xattrs), Self::RejectFilesByXattr(other_xattrs)) => {
+					self_xattrs == other_xattrs
+				}
+
+				(Self::AcceptFilesByKind(self_kinds), Self::AcceptFilesByKind(other_kinds))
+				| (Self::RejectFilesByKind(self_kinds), Self::RejectFilesByKind(other_kinds)) => {
+					self_kinds == other_kinds
+				}
+
 				_ => false,
 			}
 		}
@@ -944,4 +1460,27 @@ mod tests {
 
 		assert_eq!(actual, expected);
 	}
+
+	#[test]
+	fn serde_file_filters_roundtrip() {
+		let actual = IndexerRule::new(
+			"File filters".to_string(),
+			false,
+			vec![
+				RulePerKind::RejectFilesSmallerThan(1024),
+				RulePerKind::RejectFilesLargerThan(1024 * 1024),
+				RulePerKind::RejectFilesModifiedBefore(parse_date("2020-01-01T00:00:00Z").unwrap()),
+				RulePerKind::RejectFilesModifiedAfter(parse_date("2024-01-01T00:00:00Z").unwrap()),
+				RulePerKind::RejectFilesByXattr(HashSet::from(["com.dropbox.ignored".to_string()])),
+				RulePerKind::AcceptFilesByKind(vec![ObjectKind::Image, ObjectKind::Video]),
+				RulePerKind::RejectFilesByKind(vec![ObjectKind::Code]),
+			],
+		);
+
+		let expected =
+			rmp_serde::from_slice::<IndexerRule>(&rmp_serde::to_vec_named(&actual).unwrap())
+				.unwrap();
+
+		assert_eq!(actual, expected);
+	}
 }
@@ -129,6 +129,72 @@ impl From<GitIgnoreRules> for IndexerRule {
 	}
 }
 
+/// Ignore files honoured in any directory, in increasing order of precedence
+pub const IGNORE_FILE_NAMES: [&str; 2] = [".ignore", ".sdignore"];
+
+#[derive(Debug)]
+pub struct IgnoreFileRules {
+	rules: RulePerKind,
+}
+
+impl IgnoreFileRules {
+	/// Collects the ignore files from `location_root` down to `current`, files in deeper directories
+	/// take precedence over the ones above them, just like nested `.gitignore` files
+	pub async fn get_rules(
+		location_root: &Path,
+		current: &Path,
+	) -> Option<Result<Self, SeederError>> {
+		let mut ancestors = current
+			.ancestors()
+			.take_while(|&path| path.starts_with(location_root))
+			.collect::<Vec<_>>();
+		ancestors.reverse();
+
+		let ignore_files = ancestors
+			.into_iter()
+			.flat_map(|dir| IGNORE_FILE_NAMES.iter().map(move |name| dir.join(name)))
+			.collect::<Vec<_>>();
+
+		let location_root = location_root.to_path_buf();
+
+		tokio::task::spawn_blocking(move || {
+			let mut buf = Vec::with_capacity(30);
+			let mut search = Search::default();
+
+			search.patterns.extend(
+				ignore_files
+					.into_iter()
+					// Missing files are `Ok(None)`, unreadable ones are skipped just like `.gitignore`s
+					.filter_map(|ignore_file| {
+						List::from_file(ignore_file, Some(&location_root), true, &mut buf)
+							.ok()
+							.flatten()
+					}),
+			);
+
+			(!search.patterns.is_empty()).then(|| Self {
+				rules: RulePerKind::IgnoredByIgnoreFiles(location_root, search),
+			})
+		})
+		.await
+		.map_err(|_| SeederError::InheritedExternalRules)
+		.transpose()
+	}
+}
+
+impl From<IgnoreFileRules> for IndexerRule {
+	fn from(ignore_files: IgnoreFileRules) -> Self {
+		Self {
+			id: None,
+			name: ".ignore'd".to_owned(),
+			default: true,
+			date_created: Utc::now(),
+			date_modified: Utc::now(),
+			rules: vec![ignore_files.rules],
+		}
+	}
+}
+
 #[derive(Debug)]
 pub struct SystemIndexerRule {
 	name: &'static str,
@@ -179,6 +245,7 @@ pub async fn new_or_existing_library(db: &PrismaClient) -> Result<(), SeederErro
 		&NO_GIT,
 		&GITIGNORE,
 		&ONLY_IMAGES,
+		&IGNORE_FILES,
 	]
 	.into_iter()
 	.enumerate()
@@ -344,3 +411,10 @@ pub static ONLY_IMAGES: LazyLock<SystemIndexerRule> = LazyLock::new(|| SystemInd
 	])
 	.expect("this is hardcoded and should always work")],
 });
+
+pub static IGNORE_FILES: LazyLock<SystemIndexerRule> = LazyLock::new(|| SystemIndexerRule {
+	name: "Ignore files",
+	default: true,
+	// Empty rules because this rule is only used to allow frontend to toggle IgnoreFileRules
+	rules: vec![],
+});
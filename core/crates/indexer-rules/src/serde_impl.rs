@@ -1,5 +1,8 @@
+use sd_file_ext::kind::ObjectKind;
+
 use std::{collections::HashSet, marker::PhantomData};
 
+use chrono::{DateTime, Utc};
 use globset::{Glob, GlobSetBuilder};
 use serde::{de, ser, Deserialize, Serialize};
 
@@ -31,8 +34,50 @@ impl Serialize for RulePerKind {
 					"RejectIfChildrenDirectoriesArePresent",
 					children,
 				),
-			Self::IgnoredByGit(_, _) => {
-				unreachable!("git ignore rules are dynamic and not serialized")
+			Self::RejectFilesSmallerThan(ref size) => serializer.serialize_newtype_variant(
+				"ParametersPerKind",
+				5,
+				"RejectFilesSmallerThan",
+				size,
+			),
+			Self::RejectFilesLargerThan(ref size) => serializer.serialize_newtype_variant(
+				"ParametersPerKind",
+				6,
+				"RejectFilesLargerThan",
+				size,
+			),
+			Self::RejectFilesModifiedBefore(ref date) => serializer.serialize_newtype_variant(
+				"ParametersPerKind",
+				7,
+				"RejectFilesModifiedBefore",
+				date,
+			),
+			Self::RejectFilesModifiedAfter(ref date) => serializer.serialize_newtype_variant(
+				"ParametersPerKind",
+				8,
+				"RejectFilesModifiedAfter",
+				date,
+			),
+			Self::RejectFilesByXattr(ref xattrs) => serializer.serialize_newtype_variant(
+				"ParametersPerKind",
+				9,
+				"RejectFilesByXattr",
+				xattrs,
+			),
+			Self::AcceptFilesByKind(ref kinds) => serializer.serialize_newtype_variant(
+				"ParametersPerKind",
+				10,
+				"AcceptFilesByKind",
+				kinds,
+			),
+			Self::RejectFilesByKind(ref kinds) => serializer.serialize_newtype_variant(
+				"ParametersPerKind",
+				11,
+				"RejectFilesByKind",
+				kinds,
+			),
+			Self::IgnoredByGit(_, _) | Self::IgnoredByIgnoreFiles(_, _) => {
+				unreachable!("git ignore and ignore files rules are dynamic and not serialized")
 			}
 		}
 	}
@@ -49,6 +94,13 @@ impl<'de> Deserialize<'de> for RulePerKind {
 			"RejectFilesByGlob",
 			"AcceptIfChildrenDirectoriesArePresent",
 			"RejectIfChildrenDirectoriesArePresent",
+			"RejectFilesSmallerThan",
+			"RejectFilesLargerThan",
+			"RejectFilesModifiedBefore",
+			"RejectFilesModifiedAfter",
+			"RejectFilesByXattr",
+			"AcceptFilesByKind",
+			"RejectFilesByKind",
 		];
 
 		enum Fields {
@@ -56,6 +108,13 @@ impl<'de> Deserialize<'de> for RulePerKind {
 			RejectFilesByGlob,
 			AcceptIfChildrenDirectoriesArePresent,
 			RejectIfChildrenDirectoriesArePresent,
+			RejectFilesSmallerThan,
+			RejectFilesLargerThan,
+			RejectFilesModifiedBefore,
+			RejectFilesModifiedAfter,
+			RejectFilesByXattr,
+			AcceptFilesByKind,
+			RejectFilesByKind,
 		}
 
 		struct FieldsVisitor;
@@ -68,7 +127,14 @@ impl<'de> Deserialize<'de> for RulePerKind {
 					"`AcceptFilesByGlob` \
 				or `RejectFilesByGlob` \
 				or `AcceptIfChildrenDirectoriesArePresent` \
-				or `RejectIfChildrenDirectoriesArePresent`",
+				or `RejectIfChildrenDirectoriesArePresent` \
+				or `RejectFilesSmallerThan` \
+				or `RejectFilesLargerThan` \
+				or `RejectFilesModifiedBefore` \
+				or `RejectFilesModifiedAfter` \
+				or `RejectFilesByXattr` \
+				or `AcceptFilesByKind` \
+				or `RejectFilesByKind`",
 				)
 			}
 
@@ -81,9 +147,16 @@ impl<'de> Deserialize<'de> for RulePerKind {
 					1 => Ok(Fields::RejectFilesByGlob),
 					2 => Ok(Fields::AcceptIfChildrenDirectoriesArePresent),
 					3 => Ok(Fields::RejectIfChildrenDirectoriesArePresent),
+					5 => Ok(Fields::RejectFilesSmallerThan),
+					6 => Ok(Fields::RejectFilesLargerThan),
+					7 => Ok(Fields::RejectFilesModifiedBefore),
+					8 => Ok(Fields::RejectFilesModifiedAfter),
+					9 => Ok(Fields::RejectFilesByXattr),
+					10 => Ok(Fields::AcceptFilesByKind),
+					11 => Ok(Fields::RejectFilesByKind),
 					_ => Err(de::Error::invalid_value(
 						de::Unexpected::Unsigned(value),
-						&"variant index 0 <= i < 3",
+						&"variant index 0 <= i < 4 or 5 <= i < 12",
 					)),
 				}
 			}
@@ -100,6 +173,13 @@ impl<'de> Deserialize<'de> for RulePerKind {
 					"RejectIfChildrenDirectoriesArePresent" => {
 						Ok(Fields::RejectIfChildrenDirectoriesArePresent)
 					}
+					"RejectFilesSmallerThan" => Ok(Fields::RejectFilesSmallerThan),
+					"RejectFilesLargerThan" => Ok(Fields::RejectFilesLargerThan),
+					"RejectFilesModifiedBefore" => Ok(Fields::RejectFilesModifiedBefore),
+					"RejectFilesModifiedAfter" => Ok(Fields::RejectFilesModifiedAfter),
+					"RejectFilesByXattr" => Ok(Fields::RejectFilesByXattr),
+					"AcceptFilesByKind" => Ok(Fields::AcceptFilesByKind),
+					"RejectFilesByKind" => Ok(Fields::RejectFilesByKind),
 					_ => Err(de::Error::unknown_variant(value, VARIANTS)),
 				}
 			}
@@ -116,6 +196,13 @@ impl<'de> Deserialize<'de> for RulePerKind {
 					b"RejectIfChildrenDirectoriesArePresent" => {
 						Ok(Fields::RejectIfChildrenDirectoriesArePresent)
 					}
+					b"RejectFilesSmallerThan" => Ok(Fields::RejectFilesSmallerThan),
+					b"RejectFilesLargerThan" => Ok(Fields::RejectFilesLargerThan),
+					b"RejectFilesModifiedBefore" => Ok(Fields::RejectFilesModifiedBefore),
+					b"RejectFilesModifiedAfter" => Ok(Fields::RejectFilesModifiedAfter),
+					b"RejectFilesByXattr" => Ok(Fields::RejectFilesByXattr),
+					b"AcceptFilesByKind" => Ok(Fields::AcceptFilesByKind),
+					b"RejectFilesByKind" => Ok(Fields::RejectFilesByKind),
 					_ => Err(de::Error::unknown_variant(
 						&String::from_utf8_lossy(bytes),
 						VARIANTS,
@@ -201,6 +288,38 @@ impl<'de> Deserialize<'de> for RulePerKind {
 						reject_if_children_directories_are_present,
 					)
 					.map(Self::Value::RejectIfChildrenDirectoriesArePresent),
+					(Fields::RejectFilesSmallerThan, reject_files_smaller_than) => {
+						de::VariantAccess::newtype_variant::<u64>(reject_files_smaller_than)
+							.map(Self::Value::RejectFilesSmallerThan)
+					}
+					(Fields::RejectFilesLargerThan, reject_files_larger_than) => {
+						de::VariantAccess::newtype_variant::<u64>(reject_files_larger_than)
+							.map(Self::Value::RejectFilesLargerThan)
+					}
+					(Fields::RejectFilesModifiedBefore, reject_files_modified_before) => {
+						de::VariantAccess::newtype_variant::<DateTime<Utc>>(
+							reject_files_modified_before,
+						)
+						.map(Self::Value::RejectFilesModifiedBefore)
+					}
+					(Fields::RejectFilesModifiedAfter, reject_files_modified_after) => {
+						de::VariantAccess::newtype_variant::<DateTime<Utc>>(
+							reject_files_modified_after,
+						)
+						.map(Self::Value::RejectFilesModifiedAfter)
+					}
+					(Fields::RejectFilesByXattr, reject_files_by_xattr) => {
+						de::VariantAccess::newtype_variant::<HashSet<String>>(reject_files_by_xattr)
+							.map(Self::Value::RejectFilesByXattr)
+					}
+					(Fields::AcceptFilesByKind, accept_files_by_kind) => {
+						de::VariantAccess::newtype_variant::<Vec<ObjectKind>>(accept_files_by_kind)
+							.map(Self::Value::AcceptFilesByKind)
+					}
+					(Fields::RejectFilesByKind, reject_files_by_kind) => {
+						de::VariantAccess::newtype_variant::<Vec<ObjectKind>>(reject_files_by_kind)
+							.map(Self::Value::RejectFilesByKind)
+					}
 				})
 			}
 		}
@@ -54,6 +54,7 @@ file_path::select!(file_path_for_file_identifier {
 	name
 	extension
 	object_id
+	symlink_target
 });
 file_path::select!(file_path_for_object_validator {
 	pub_id
@@ -73,6 +74,7 @@ file_path::select!(file_path_for_media_processor {
 	object: select {
 		id
 		pub_id
+		kind
 	}
 });
 file_path::select!(file_path_to_isolate {
@@ -110,6 +112,13 @@ file_path::select!(file_path_walker {
 	inode
 	size_in_bytes_bytes
 	hidden
+	symlink_target
+	permissions
+	uid
+	gid
+	xattrs
+	device
+	nlink
 });
 file_path::select!(file_path_to_handle_custom_uri {
 	pub_id
@@ -270,6 +279,8 @@ impl From<location_with_indexer_rules::Data> for location::Data {
 			hidden: data.hidden,
 			date_created: data.date_created,
 			scan_state: data.scan_state,
+			symlink_policy: data.symlink_policy,
+			indexed_xattrs: data.indexed_xattrs,
 			file_paths: None,
 			indexer_rules: None,
 			instance: None,
@@ -294,6 +305,8 @@ impl From<&location_with_indexer_rules::Data> for location::Data {
 			hidden: data.hidden,
 			date_created: data.date_created,
 			scan_state: data.scan_state,
+			symlink_policy: data.symlink_policy,
+			indexed_xattrs: data.indexed_xattrs.clone(),
 			file_paths: None,
 			indexer_rules: None,
 			instance: None,
@@ -0,0 +1,10 @@
+-- AlterTable
+ALTER TABLE "location" ADD COLUMN "indexed_xattrs" TEXT;
+
+-- AlterTable
+ALTER TABLE "file_path" ADD COLUMN "permissions" INTEGER;
+ALTER TABLE "file_path" ADD COLUMN "uid" INTEGER;
+ALTER TABLE "file_path" ADD COLUMN "gid" INTEGER;
+ALTER TABLE "file_path" ADD COLUMN "owner_name" TEXT;
+ALTER TABLE "file_path" ADD COLUMN "group_name" TEXT;
+ALTER TABLE "file_path" ADD COLUMN "xattrs" TEXT;
@@ -0,0 +1,12 @@
+-- DropIndex
+DROP INDEX "file_path_location_id_inode_key";
+
+-- AlterTable
+ALTER TABLE "file_path" ADD COLUMN "device" BLOB;
+ALTER TABLE "file_path" ADD COLUMN "nlink" INTEGER;
+
+-- CreateIndex
+CREATE INDEX "file_path_location_id_inode_idx" ON "file_path"("location_id", "inode");
+
+-- CreateIndex
+CREATE INDEX "file_path_device_inode_idx" ON "file_path"("device", "inode");
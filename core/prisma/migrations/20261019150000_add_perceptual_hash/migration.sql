@@ -0,0 +1,10 @@
+-- CreateTable
+CREATE TABLE "perceptual_hash" (
+    "id" INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    "hash" BIGINT NOT NULL,
+    "object_id" INTEGER NOT NULL,
+    CONSTRAINT "perceptual_hash_object_id_fkey" FOREIGN KEY ("object_id") REFERENCES "object" ("id") ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+-- CreateIndex
+CREATE UNIQUE INDEX "perceptual_hash_object_id_key" ON "perceptual_hash"("object_id");
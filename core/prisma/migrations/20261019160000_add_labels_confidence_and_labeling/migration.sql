@@ -0,0 +1,14 @@
+-- AlterTable
+ALTER TABLE "label_on_object" ADD COLUMN "confidence" REAL;
+
+-- CreateTable
+CREATE TABLE "object_labeling" (
+    "id" INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    "model_version" TEXT NOT NULL,
+    "date_labeled" DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "object_id" INTEGER NOT NULL,
+    CONSTRAINT "object_labeling_object_id_fkey" FOREIGN KEY ("object_id") REFERENCES "object" ("id") ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+-- CreateIndex
+CREATE UNIQUE INDEX "object_labeling_object_id_key" ON "object_labeling"("object_id");
@@ -0,0 +1,16 @@
+-- AlterTable
+ALTER TABLE "exif_data" ADD COLUMN "latitude" REAL;
+ALTER TABLE "exif_data" ADD COLUMN "longitude" REAL;
+ALTER TABLE "exif_data" ADD COLUMN "city" TEXT;
+ALTER TABLE "exif_data" ADD COLUMN "region" TEXT;
+ALTER TABLE "exif_data" ADD COLUMN "country" TEXT;
+
+-- Backfill coordinates from the JSON encoded media location
+UPDATE "exif_data"
+SET
+    "latitude" = json_extract(CAST("media_location" AS TEXT), '$.latitude'),
+    "longitude" = json_extract(CAST("media_location" AS TEXT), '$.longitude')
+WHERE "media_location" IS NOT NULL AND json_valid(CAST("media_location" AS TEXT));
+
+-- CreateIndex
+CREATE INDEX "exif_data_latitude_longitude_idx" ON "exif_data"("latitude", "longitude");
@@ -0,0 +1,27 @@
+-- AlterTable
+ALTER TABLE "exif_data" ADD COLUMN "device_make" TEXT;
+ALTER TABLE "exif_data" ADD COLUMN "device_model" TEXT;
+ALTER TABLE "exif_data" ADD COLUMN "lens_model" TEXT;
+ALTER TABLE "exif_data" ADD COLUMN "focal_length" REAL;
+ALTER TABLE "exif_data" ADD COLUMN "iso" INTEGER;
+ALTER TABLE "exif_data" ADD COLUMN "orientation" TEXT;
+ALTER TABLE "exif_data" ADD COLUMN "megapixels" REAL;
+
+-- Backfill camera fields from the JSON encoded camera data
+UPDATE "exif_data"
+SET
+    "device_make" = json_extract(CAST("camera_data" AS TEXT), '$.device_make'),
+    "device_model" = json_extract(CAST("camera_data" AS TEXT), '$.device_model'),
+    "lens_model" = json_extract(CAST("camera_data" AS TEXT), '$.lens_model'),
+    "focal_length" = json_extract(CAST("camera_data" AS TEXT), '$.focal_length'),
+    "iso" = json_extract(CAST("camera_data" AS TEXT), '$.iso'),
+    "orientation" = json_extract(CAST("camera_data" AS TEXT), '$.orientation')
+WHERE "camera_data" IS NOT NULL AND json_valid(CAST("camera_data" AS TEXT));
+
+-- Backfill megapixels from the JSON encoded resolution
+UPDATE "exif_data"
+SET "megapixels" = json_extract(CAST("resolution" AS TEXT), '$.width') * json_extract(CAST("resolution" AS TEXT), '$.height') / 1000000.0
+WHERE "resolution" IS NOT NULL AND json_valid(CAST("resolution" AS TEXT));
+
+-- CreateIndex
+CREATE INDEX "exif_data_device_make_device_model_idx" ON "exif_data"("device_make", "device_model");
@@ -0,0 +1,12 @@
+-- AlterTable
+ALTER TABLE "ffmpeg_data" ADD COLUMN "duration_seconds" REAL;
+ALTER TABLE "ffmpeg_data" ADD COLUMN "bits_per_second" BIGINT;
+ALTER TABLE "ffmpeg_data" ADD COLUMN "width" INTEGER;
+ALTER TABLE "ffmpeg_data" ADD COLUMN "height" INTEGER;
+ALTER TABLE "ffmpeg_data" ADD COLUMN "frame_rate" REAL;
+ALTER TABLE "ffmpeg_data" ADD COLUMN "audio_channels" INTEGER;
+ALTER TABLE "ffmpeg_data" ADD COLUMN "sample_rate" INTEGER;
+ALTER TABLE "ffmpeg_data" ADD COLUMN "has_subtitles" BOOLEAN;
+
+-- AlterTable
+ALTER TABLE "ffmpeg_media_audio_props" ADD COLUMN "channels" INTEGER;
@@ -0,0 +1,2 @@
+-- AlterTable
+ALTER TABLE "ffmpeg_data" ADD COLUMN "waveform" BLOB;
@@ -54,7 +54,7 @@ enum EphemeralFileCreateContextTypes {
 pub(crate) fn mount() -> AlphaRouter<Ctx> {
 	R.router()
 		.procedure("getMediaData", {
-			R.query(|_, full_path: PathBuf| async move {
+			R.query(|node, full_path: PathBuf| async move {
 				let kind: Option<ObjectKind> = Extension::resolve_conflicting(&full_path, false)
 					.await
 					.map(Into::into);
@@ -77,16 +77,19 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 							return Ok(None);
 						}
 
-						let exif_data = exif_media_data::extract(full_path)
-							.await
-							.map_err(|e| {
-								rspc::Error::with_cause(
-									ErrorCode::InternalServerError,
-									"Failed to extract media data".to_string(),
-									e,
-								)
-							})?
-							.map(MediaData::Exif);
+						let exif_data = exif_media_data::extract(
+							full_path,
+							node.reverse_geocoder.get().as_deref(),
+						)
+						.await
+						.map_err(|e| {
+							rspc::Error::with_cause(
+								ErrorCode::InternalServerError,
+								"Failed to extract media data".to_string(),
+								e,
+							)
+						})?
+						.map(MediaData::Exif);
 
 						Ok(exif_data)
 					}
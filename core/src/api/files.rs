@@ -14,11 +14,11 @@ use crate::{
 	old_job::OldJob,
 };
 
-use sd_core_file_path_helper::{FilePathError, IsolatedFilePathData};
+use sd_core_file_path_helper::{FilePathError, InodeKey, IsolatedFilePathData};
 use sd_core_heavy_lifting::media_processor::{exif_media_data, ffmpeg_media_data};
 use sd_core_prisma_helpers::{
-	file_path_to_isolate, file_path_to_isolate_with_id, object_with_file_paths,
-	object_with_media_data,
+	file_path_for_frontend, file_path_to_isolate, file_path_to_isolate_with_id,
+	object_with_file_paths, object_with_media_data,
 };
 
 use sd_file_ext::kind::ObjectKind;
@@ -171,6 +171,77 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 						.map(|str| str.to_string()))
 				})
 		})
+		.procedure("getDocumentPageCount", {
+			R.with2(library())
+				.query(|(_, library), id: file_path::id::Type| async move {
+					let isolated_path = IsolatedFilePathData::try_from(
+						library
+							.db
+							.file_path()
+							.find_unique(file_path::id::equals(id))
+							.select(file_path_to_isolate::select())
+							.exec()
+							.await?
+							.ok_or(LocationError::FilePath(FilePathError::IdNotFound(id)))?,
+					)
+					.map_err(LocationError::MissingField)?;
+
+					let path = Path::new(
+						&get_location_path_from_location_id(
+							&library.db,
+							isolated_path.location_id(),
+						)
+						.await?,
+					)
+					.join(&isolated_path);
+
+					Ok(spawn_blocking(move || sd_images::pdf_page_count(path))
+						.await
+						.map_err(|e| {
+							error!(?e, "Failed to count document pages;");
+							rspc::Error::with_cause(
+								ErrorCode::InternalServerError,
+								"Had an internal problem counting document pages".to_string(),
+								e,
+							)
+						})??)
+				})
+		})
+		.procedure("getHardLinks", {
+			R.with2(library())
+				.query(|(_, library), id: file_path::id::Type| async move {
+					let file_path = library
+						.db
+						.file_path()
+						.find_unique(file_path::id::equals(id))
+						.select(file_path::select!({ inode device nlink }))
+						.exec()
+						.await?
+						.ok_or(LocationError::FilePath(FilePathError::IdNotFound(id)))?;
+
+					if InodeKey::from_db(
+						file_path.device.as_deref(),
+						file_path.inode.as_deref(),
+						file_path.nlink,
+					)
+					.is_none()
+					{
+						return Ok(vec![]);
+					}
+
+					Ok(library
+						.db
+						.file_path()
+						.find_many(vec![
+							file_path::device::equals(file_path.device),
+							file_path::inode::equals(file_path.inode),
+							file_path::id::not(id),
+						])
+						.include(file_path_for_frontend::include())
+						.exec()
+						.await?)
+				})
+		})
 		.procedure("setNote", {
 			#[derive(Type, Deserialize)]
 			pub struct SetNoteArgs {
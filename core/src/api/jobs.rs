@@ -332,36 +332,60 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 				},
 			)
 		})
-		// .procedure("generateLabelsForLocation", {
-		// 	#[derive(Type, Deserialize)]
-		// 	pub struct GenerateLabelsForLocationArgs {
-		// 		pub id: location::id::Type,
-		// 		pub path: PathBuf,
-		// 		#[serde(default)]
-		// 		pub regenerate: bool,
-		// 	}
-		// 	R.with2(library()).mutation(
-		// 		|(node, library),
-		// 		 GenerateLabelsForLocationArgs {
-		// 		     id,
-		// 		     path,
-		// 		     regenerate,
-		// 		 }: GenerateLabelsForLocationArgs| async move {
-		// 			let Some(location) = find_location(&library, id).exec().await? else {
-		// 				return Err(LocationError::IdNotFound(id).into());
-		// 			};
-		// 			OldJob::new(OldMediaProcessorJobInit {
-		// 				location,
-		// 				sub_path: Some(path),
-		// 				regenerate_thumbnails: false,
-		// 				regenerate_labels: regenerate,
-		// 			})
-		// 			.spawn(&node, &library)
-		// 			.await
-		// 			.map_err(Into::into)
-		// 		},
-		// 	)
-		// })
+		.procedure("generateLabelsForLocation", {
+			#[derive(Type, Deserialize)]
+			pub struct GenerateLabelsForLocationArgs {
+				pub id: location::id::Type,
+				pub path: PathBuf,
+				#[serde(default)]
+				pub regenerate: bool,
+			}
+
+			R.with2(library()).mutation(
+				|(node, library),
+				 GenerateLabelsForLocationArgs {
+				     id,
+				     path,
+				     regenerate,
+				 }: GenerateLabelsForLocationArgs| async move {
+					#[cfg(not(feature = "ai"))]
+					{
+						let _ = (node, library, id, path, regenerate);
+						return Err::<JobId, _>(rspc::Error::new(
+							rspc::ErrorCode::MethodNotSupported,
+							"AI feature is not available".to_string(),
+						));
+					}
+
+					#[cfg(feature = "ai")]
+					{
+						if node.image_labeler.get().is_none() {
+							return Err(rspc::Error::new(
+								rspc::ErrorCode::PreconditionFailed,
+								"image labeler model is not loaded".to_string(),
+							));
+						}
+
+						let Some(location) = find_location(&library, id).exec().await? else {
+							return Err(LocationError::IdNotFound(id).into());
+						};
+
+						node.job_system
+							.dispatch(
+								MediaProcessor::new(location, Some(path), false)?
+									.with_regenerate_labels(regenerate),
+								id,
+								NodeContext {
+									node: Arc::clone(&node),
+									library,
+								},
+							)
+							.await
+							.map_err(Into::into)
+					}
+				},
+			)
+		})
 		.procedure("objectValidator", {
 			#[derive(Type, Deserialize)]
 			pub struct ObjectValidatorArgs {
@@ -411,6 +435,121 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 				},
 			)
 		})
+		.procedure("taskSystemStats", {
+			// Snapshots of the task system workers every second, to diagnose why jobs are stalling
+			#[derive(Debug, Serialize, Type)]
+			pub struct TaskSystemStats {
+				workers: Vec<TaskSystemWorkerStats>,
+			}
+
+			#[derive(Debug, Serialize, Type)]
+			pub struct TaskSystemWorkerStats {
+				worker_id: u32,
+				is_idle: bool,
+				is_retiring: bool,
+				queued_interactive_tasks: u32,
+				queued_normal_tasks: u32,
+				queued_background_tasks: u32,
+				paused_tasks_count: u32,
+				running_task: Option<RunningTaskStats>,
+				suspended_task: Option<SuspendedTaskStats>,
+				stolen_tasks_count: u32,
+				tasks_stolen_by_others_count: u32,
+			}
+
+			#[derive(Debug, Serialize, Type)]
+			pub struct RunningTaskStats {
+				task_id: Uuid,
+				priority: TaskPriority,
+				elapsed_ms: u32,
+			}
+
+			#[derive(Debug, Serialize, Type)]
+			pub struct SuspendedTaskStats {
+				task_id: Uuid,
+				priority: TaskPriority,
+				suspended_for_ms: u32,
+			}
+
+			#[derive(Debug, Serialize, Type)]
+			pub enum TaskPriority {
+				Background,
+				Normal,
+				Interactive,
+			}
+
+			impl From<sd_task_system::TaskPriority> for TaskPriority {
+				fn from(priority: sd_task_system::TaskPriority) -> Self {
+					match priority {
+						sd_task_system::TaskPriority::Background => Self::Background,
+						sd_task_system::TaskPriority::Normal => Self::Normal,
+						sd_task_system::TaskPriority::Interactive => Self::Interactive,
+					}
+				}
+			}
+
+			fn saturating_u32(value: impl TryInto<u32>) -> u32 {
+				value.try_into().unwrap_or(u32::MAX)
+			}
+
+			impl From<sd_task_system::TaskSystemStats> for TaskSystemStats {
+				fn from(stats: sd_task_system::TaskSystemStats) -> Self {
+					Self {
+						workers: stats
+							.workers
+							.into_iter()
+							.map(|worker| TaskSystemWorkerStats {
+								worker_id: saturating_u32(worker.worker_id),
+								is_idle: worker.is_idle,
+								is_retiring: worker.is_retiring,
+								queued_interactive_tasks: saturating_u32(
+									worker.queued_tasks.interactive,
+								),
+								queued_normal_tasks: saturating_u32(worker.queued_tasks.normal),
+								queued_background_tasks: saturating_u32(
+									worker.queued_tasks.background,
+								),
+								paused_tasks_count: saturating_u32(worker.paused_tasks_count),
+								running_task: worker.running_task.map(|running_task| {
+									RunningTaskStats {
+										task_id: running_task.task_id,
+										priority: running_task.priority.into(),
+										elapsed_ms: saturating_u32(
+											running_task.elapsed.as_millis(),
+										),
+									}
+								}),
+								suspended_task: worker.suspended_task.map(|suspended_task| {
+									SuspendedTaskStats {
+										task_id: suspended_task.task_id,
+										priority: suspended_task.priority.into(),
+										suspended_for_ms: saturating_u32(
+											suspended_task.suspended_for.as_millis(),
+										),
+									}
+								}),
+								stolen_tasks_count: saturating_u32(worker.stolen_tasks_count),
+								tasks_stolen_by_others_count: saturating_u32(
+									worker.tasks_stolen_by_others_count,
+								),
+							})
+							.collect(),
+					}
+				}
+			}
+
+			R.subscription(|node, _: ()| async move {
+				let mut stats_interval = tokio::time::interval(Duration::from_secs(1));
+
+				async_stream::stream! {
+					loop {
+						stats_interval.tick().await;
+
+						yield TaskSystemStats::from(node.task_system.stats().await);
+					}
+				}
+			})
+		})
 		.procedure("newThumbnail", {
 			R.with2(library())
 				.subscription(|(node, _), _: ()| async move {
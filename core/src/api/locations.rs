@@ -9,8 +9,14 @@ use crate::{
 	util::AbortOnDrop,
 };
 
-use sd_core_heavy_lifting::{media_processor::ThumbKey, JobName};
-use sd_core_indexer_rules::IndexerRuleCreateArgs;
+use sd_core_heavy_lifting::{
+	indexer::{preview_indexer_rules, SymlinkPolicy},
+	media_processor::ThumbKey,
+	JobName,
+};
+use sd_core_indexer_rules::{
+	IndexerRule, IndexerRuleCreateArgs, IndexerRuler, RuleKind, RulePerKind,
+};
 use sd_core_prisma_helpers::{
 	file_path_for_frontend, label_with_objects, location_with_indexer_rules, object_with_file_paths,
 };
@@ -467,6 +473,22 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 		.merge("indexer_rules.", mount_indexer_rule_routes())
 }
 
+/// Default bounds for `locations.indexer_rules.preview`, so previewing a huge directory stays cheap
+const PREVIEW_DEFAULT_MAX_DEPTH: u32 = 3;
+const PREVIEW_DEFAULT_MAX_ENTRIES: u32 = 1000;
+
+#[derive(Type, Deserialize)]
+pub struct IndexerRulesPreviewArgs {
+	pub path: PathBuf,
+	/// Already saved indexer rules to apply along with the candidate ones
+	pub indexer_rules_ids: Vec<i32>,
+	/// Candidate rules, in the same format of `IndexerRuleCreateArgs`
+	pub rules: Vec<(RuleKind, Vec<String>)>,
+	pub symlink_policy: Option<SymlinkPolicy>,
+	pub max_depth: Option<u32>,
+	pub max_entries: Option<u32>,
+}
+
 fn mount_indexer_rule_routes() -> AlphaRouter<Ctx> {
 	R.router()
 		.procedure("create", {
@@ -544,6 +566,44 @@ fn mount_indexer_rule_routes() -> AlphaRouter<Ctx> {
 				Ok(library.db.indexer_rule().find_many(vec![]).exec().await?)
 			})
 		})
+		.procedure("preview", {
+			R.with2(library())
+				.query(|(_, library), args: IndexerRulesPreviewArgs| async move {
+					let mut indexer_rules = library
+						.db
+						.indexer_rule()
+						.find_many(vec![indexer_rule::id::in_vec(args.indexer_rules_ids)])
+						.exec()
+						.await?
+						.iter()
+						.map(IndexerRule::try_from)
+						.collect::<Result<Vec<_>, _>>()?;
+
+					let now = Utc::now();
+
+					indexer_rules.push(IndexerRule {
+						id: None,
+						name: "Preview".to_string(),
+						default: false,
+						rules: args
+							.rules
+							.into_iter()
+							.map(|(kind, parameters)| RulePerKind::new(kind, parameters))
+							.collect::<Result<Vec<_>, _>>()?,
+						date_created: now,
+						date_modified: now,
+					});
+
+					Ok(preview_indexer_rules(
+						args.path,
+						&IndexerRuler::new(indexer_rules),
+						args.symlink_policy.unwrap_or_default(),
+						args.max_depth.unwrap_or(PREVIEW_DEFAULT_MAX_DEPTH) as usize,
+						args.max_entries.unwrap_or(PREVIEW_DEFAULT_MAX_ENTRIES) as usize,
+					)
+					.await?)
+				})
+		})
 		// list indexer rules for location, returning the indexer rule
 		.procedure("listForLocation", {
 			R.with2(library())
@@ -13,7 +13,10 @@ use sd_core_heavy_lifting::media_processor::ThumbKey;
 use sd_p2p::RemoteIdentity;
 use sd_prisma::prisma::file_path;
 
-use std::sync::{atomic::Ordering, Arc};
+use std::{
+	path::PathBuf,
+	sync::{atomic::Ordering, Arc},
+};
 
 use itertools::Itertools;
 use rspc::{alpha::Rspc, Config, ErrorCode};
@@ -96,6 +99,8 @@ pub struct SanitisedNodeConfig {
 	pub features: Vec<BackendFeature>,
 	pub preferences: NodePreferences,
 	pub image_labeler_version: Option<String>,
+	pub image_labeler_model_path: Option<PathBuf>,
+	pub reverse_geocoder_dataset_path: Option<PathBuf>,
 }
 
 impl From<NodeConfig> for SanitisedNodeConfig {
@@ -108,6 +113,8 @@ impl From<NodeConfig> for SanitisedNodeConfig {
 			features: value.features,
 			preferences: value.preferences,
 			image_labeler_version: value.image_labeler_version,
+			image_labeler_model_path: value.image_labeler_model_path,
+			reverse_geocoder_dataset_path: value.reverse_geocoder_dataset_path,
 		}
 	}
 }
@@ -1,8 +1,14 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, sync::Arc};
 
 use crate::{
 	invalidate_query,
-	node::config::{P2PDiscoveryState, Port},
+	node::{
+		config::{
+			BandwidthLimit, BandwidthWindow, IndexerPreferences, P2PDiscoveryState, Port,
+			ThumbnailerPreferences,
+		},
+		resolve_missing_places,
+	},
 };
 
 use sd_prisma::prisma::{instance, location};
@@ -13,7 +19,12 @@ use specta::Type;
 use tracing::error;
 use uuid::Uuid;
 
-use super::{locations::ExplorerItem, utils::library, Ctx, R};
+use super::{
+	locations::ExplorerItem,
+	notifications::{NotificationData, NotificationKind},
+	utils::library,
+	Ctx, R,
+};
 
 pub(crate) fn mount() -> AlphaRouter<Ctx> {
 	R.router()
@@ -28,8 +39,16 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 				pub p2p_discovery: Option<P2PDiscoveryState>,
 				pub p2p_remote_access: Option<bool>,
 				pub p2p_manual_peers: Option<HashSet<String>>,
+				pub p2p_trusted_peers_only: Option<bool>,
+				pub p2p_bandwidth_limit: Option<BandwidthLimit>,
+				pub p2p_bandwidth_schedule: Option<Vec<BandwidthWindow>>,
 				#[cfg(feature = "ai")]
 				pub image_labeler_version: Option<String>,
+				/// An empty path stops using a custom model file
+				#[cfg(feature = "ai")]
+				pub image_labeler_model_path: Option<std::path::PathBuf>,
+				/// An empty path goes back to the bundled dataset
+				pub reverse_geocoder_dataset_path: Option<std::path::PathBuf>,
 			}
 			R.mutation(|node, args: ChangeNodeNameArgs| async move {
 				if let Some(name) = &args.name {
@@ -41,8 +60,21 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 					}
 				}
 
+				if let Some(schedule) = &args.p2p_bandwidth_schedule {
+					if schedule
+						.iter()
+						.any(|window| window.start >= 24 * 60 || window.end > 24 * 60)
+					{
+						return Err(rspc::Error::new(
+							ErrorCode::BadRequest,
+							"invalid bandwidth schedule window".into(),
+						));
+					}
+				}
+
+				let mut reload_reverse_geocoder = false;
 				#[cfg(feature = "ai")]
-				let mut new_model = None;
+				let mut reload_model = false;
 
 				node.config
 					.write(|config| {
@@ -71,29 +103,52 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 						if let Some(manual_peers) = args.p2p_manual_peers {
 							config.p2p.manual_peers = manual_peers;
 						};
+						if let Some(trusted_peers_only) = args.p2p_trusted_peers_only {
+							config.p2p.trusted_peers_only = trusted_peers_only;
+						};
+						if let Some(bandwidth_limit) = args.p2p_bandwidth_limit {
+							config.p2p.bandwidth_limit = bandwidth_limit;
+						};
+						if let Some(bandwidth_schedule) = args.p2p_bandwidth_schedule {
+							config.p2p.bandwidth_schedule = bandwidth_schedule;
+						};
+
+						if let Some(path) = args.reverse_geocoder_dataset_path {
+							let path = (!path.as_os_str().is_empty()).then_some(path);
+							if config.reverse_geocoder_dataset_path != path {
+								config.reverse_geocoder_dataset_path = path;
+								reload_reverse_geocoder = true;
+							}
+						}
 
 						#[cfg(feature = "ai")]
 						if let Some(version) = args.image_labeler_version {
-							if config
-								.image_labeler_version
-								.as_ref()
-								.map(|node_version| version != *node_version)
-								.unwrap_or(true)
-							{
-								new_model = sd_ai::old_image_labeler::YoloV8::model(Some(&version))
-									.map_err(|e| {
+							if config.image_labeler_version.as_ref() != Some(&version) {
+								match sd_ai::image_labeler::YoloV8::model(Some(&version)) {
+									Ok(_) => {
+										config.image_labeler_version = Some(version);
+										reload_model = true;
+									}
+									Err(e) => {
 										error!(
 											%version,
 											?e,
 											"Failed to crate image_detection model;",
 										);
-									})
-									.ok();
-								if new_model.is_some() {
-									config.image_labeler_version = Some(version);
+									}
 								}
 							}
 						}
+
+						#[cfg(feature = "ai")]
+						if let Some(path) = args.image_labeler_model_path {
+							// An empty path goes back to the bundled or downloaded models
+							let path = (!path.as_os_str().is_empty()).then_some(path);
+							if config.image_labeler_model_path != path {
+								config.image_labeler_model_path = path;
+								reload_model = true;
+							}
+						}
 					})
 					.await
 					.map_err(|e| {
@@ -109,42 +164,59 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 
 				invalidate_query!(node; node, "nodeState");
 
-				#[cfg(feature = "ai")]
-				{
-					use super::notifications::{NotificationData, NotificationKind};
-
-					if let Some(model) = new_model {
-						let version = model.version().to_string();
-						tokio::spawn(async move {
-							let notification = if let Some(image_labeller) =
-								node.old_image_labeller.as_ref()
-							{
-								if let Err(e) = image_labeller.change_model(model).await {
+				if reload_reverse_geocoder {
+					let node = Arc::clone(&node);
+					tokio::spawn(async move {
+						let dataset_path = node.config.get().await.reverse_geocoder_dataset_path;
+						match node.reverse_geocoder.load(dataset_path.as_deref()).await {
+							Ok(reverse_geocoder) => {
+								resolve_missing_places(&reverse_geocoder, &node.libraries).await;
+							}
+							Err(e) => {
+								node.emit_notification(
 									NotificationData {
-										title: String::from(
-											"Failed to change image detection model",
-										),
+										title: String::from("Failed to load places dataset"),
 										content: format!("Error: {e}"),
 										kind: NotificationKind::Error,
-									}
-								} else {
-									NotificationData {
-										title: String::from("Model download completed"),
-										content: format!("Successfully loaded model: {version}"),
-										kind: NotificationKind::Success,
-									}
-								}
-							} else {
-								NotificationData {
-									title: String::from("Failed to change image detection model"),
-									content: "The AI system is disabled due to a previous error. Contact support for help.".to_string(),
-									kind: NotificationKind::Success,
-								}
-							};
+									},
+									None,
+								)
+								.await;
+							}
+						}
+					});
+				}
 
-							node.emit_notification(notification, None).await;
-						});
-					}
+				#[cfg(feature = "ai")]
+				if reload_model {
+					tokio::spawn(async move {
+						let config = node.config.get().await;
+						let notification = match node
+							.image_labeler
+							.load(
+								config.image_labeler_version.as_deref(),
+								config.image_labeler_model_path.as_deref(),
+								&node.data_dir,
+							)
+							.await
+						{
+							Ok(image_labeler) => NotificationData {
+								title: String::from("Model download completed"),
+								content: format!(
+									"Successfully loaded model: {}",
+									image_labeler.version()
+								),
+								kind: NotificationKind::Success,
+							},
+							Err(e) => NotificationData {
+								title: String::from("Failed to change image detection model"),
+								content: format!("Error: {e}"),
+								kind: NotificationKind::Error,
+							},
+						};
+
+						node.emit_notification(notification, None).await;
+					});
 				}
 
 				Ok(())
@@ -184,26 +256,43 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 				})
 		})
 		.procedure("updateThumbnailerPreferences", {
-			#[derive(Deserialize, Type)]
-			pub struct UpdateThumbnailerPreferences {
-				// pub background_processing_percentage: u8, // 0-100
-			}
-			R.mutation(
-				|node, UpdateThumbnailerPreferences { .. }: UpdateThumbnailerPreferences| async move {
-					node.config
-						.update_preferences(|_| {
-							// TODO(fogodev): introduce configurable workers count to task system
-						})
-						.await
-						.map_err(|e| {
-							error!(?e, "Failed to update thumbnailer preferences;");
-							rspc::Error::with_cause(
-								ErrorCode::InternalServerError,
-								"Failed to update thumbnailer preferences".to_string(),
-								e,
-							)
-						})
-				},
-			)
+			R.mutation(|node, preferences: ThumbnailerPreferences| async move {
+				node.config
+					.update_preferences(|node_preferences| {
+						node_preferences.thumbnailer = preferences
+					})
+					.await
+					.map_err(|e| {
+						error!(?e, "Failed to update thumbnailer preferences;");
+						rspc::Error::with_cause(
+							ErrorCode::InternalServerError,
+							"Failed to update thumbnailer preferences".to_string(),
+							e,
+						)
+					})?;
+
+				invalidate_query!(node; node, "nodeState");
+
+				Ok(())
+			})
+		})
+		.procedure("updateIndexerPreferences", {
+			R.mutation(|node, preferences: IndexerPreferences| async move {
+				node.config
+					.update_preferences(|node_preferences| node_preferences.indexer = preferences)
+					.await
+					.map_err(|e| {
+						error!(?e, "Failed to update indexer preferences;");
+						rspc::Error::with_cause(
+							ErrorCode::InternalServerError,
+							"Failed to update indexer preferences".to_string(),
+							e,
+						)
+					})?;
+
+				invalidate_query!(node; node, "nodeState");
+
+				Ok(())
+			})
 		})
 }
@@ -1,16 +1,30 @@
-use crate::p2p::{operations, ConnectionMethod, DiscoveryMethod, Header, P2PEvent, PeerMetadata};
+use crate::{
+	invalidate_query,
+	p2p::{
+		operations::{self, pairing::PairingCode},
+		DiscoveryMethod, Header, P2PEvent, PeerMetadata,
+	},
+};
 
 use sd_p2p::{PeerConnectionCandidate, RemoteIdentity};
 
+use futures::future::join_all;
 use rspc::{alpha::AlphaRouter, ErrorCode};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use specta::Type;
-use std::{path::PathBuf, sync::PoisonError};
-use tokio::io::AsyncWriteExt;
+use std::{path::PathBuf, sync::PoisonError, time::Duration};
+use tokio::{
+	io::AsyncWriteExt,
+	time::{sleep, timeout},
+};
+use tracing::debug;
 use uuid::Uuid;
 
 use super::{Ctx, R};
 
+/// How often `p2p.peerStats` pings peers and emits a new snapshot
+const PEER_STATS_INTERVAL: Duration = Duration::from_secs(5);
+
 pub(crate) fn mount() -> AlphaRouter<Ctx> {
 	R.router()
 		.procedure("events", {
@@ -26,15 +40,7 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 				}) {
 					queued.push(P2PEvent::PeerChange {
 						identity: peer.identity(),
-						connection: if peer.is_connected() {
-							if node.p2p.quic.is_relayed(peer.identity()) {
-								ConnectionMethod::Relay
-							} else {
-								ConnectionMethod::Local
-							}
-						} else {
-							ConnectionMethod::Disconnected
-						},
+						connection: node.p2p.connection_method(peer),
 						discovery: if peer
 							.connection_candidates()
 							.iter()
@@ -69,6 +75,42 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 		.procedure("state", {
 			R.query(|node, _: ()| async move { Ok(node.p2p.state().await) })
 		})
+		.procedure("peerStats", {
+			// Connection diagnostics for all known peers.
+			// Connected peers are pinged on each tick so the round trip times stay fresh.
+			R.subscription(|node, _: ()| async move {
+				Ok(async_stream::stream! {
+					loop {
+						let connected = node
+							.p2p
+							.p2p
+							.peers()
+							.values()
+							.filter(|peer| peer.is_connected())
+							.map(|peer| peer.identity())
+							.collect::<Vec<_>>();
+
+						join_all(connected.into_iter().map(|identity| {
+							let p2p = node.p2p.p2p.clone();
+							async move {
+								match timeout(PEER_STATS_INTERVAL, operations::ping(p2p, identity)).await {
+									Ok(Ok(_)) => {}
+									Ok(Err(e)) => {
+										debug!(%identity, %e, "Failed to ping peer for stats;");
+									}
+									Err(_) => debug!(%identity, "Timed out pinging peer for stats;"),
+								}
+							}
+						}))
+						.await;
+
+						yield node.p2p.peer_stats();
+
+						sleep(PEER_STATS_INTERVAL).await;
+					}
+				})
+			})
+		})
 		.procedure("listeners", {
 			R.query(|node, _: ()| async move {
 				Ok(node
@@ -131,6 +173,71 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 				})
 			})
 		})
+		.procedure("spacedropMany", {
+			#[derive(Type, Deserialize)]
+			pub struct SpacedropManyArgs {
+				identities: Vec<RemoteIdentity>,
+				// Files or directories, directories are sent recursively
+				paths: Vec<String>,
+			}
+
+			#[derive(Serialize, Type)]
+			#[serde(tag = "type")]
+			pub enum SpacedropStarted {
+				Started {
+					identity: RemoteIdentity,
+					id: Uuid,
+				},
+				Failed {
+					identity: RemoteIdentity,
+					error: String,
+				},
+			}
+
+			R.mutation(|node, args: SpacedropManyArgs| async move {
+				Ok(operations::spacedrop_many(
+					node.p2p.clone(),
+					args.identities,
+					args.paths
+						.into_iter()
+						.map(PathBuf::from)
+						.collect::<Vec<_>>(),
+				)
+				.await
+				.map_err(|spacedrop_err| {
+					rspc::Error::new(ErrorCode::InternalServerError, spacedrop_err.to_string())
+				})?
+				.into_iter()
+				.map(|(identity, result)| match result {
+					Ok(id) => SpacedropStarted::Started { identity, id },
+					Err(e) => SpacedropStarted::Failed {
+						identity,
+						error: e.to_string(),
+					},
+				})
+				.collect::<Vec<_>>())
+			})
+		})
+		.procedure("prefetchRemoteThumbnails", {
+			#[derive(Type, Deserialize)]
+			pub struct PrefetchRemoteThumbnailsArgs {
+				identity: RemoteIdentity,
+				library_id: Uuid,
+				cas_ids: Vec<String>,
+			}
+
+			// Called when a directory of a remote library is opened so it's thumbnails are cached before they are scrolled into view
+			R.mutation(|node, args: PrefetchRemoteThumbnailsArgs| async move {
+				tokio::spawn(operations::prefetch_thumbnails(
+					node.clone(),
+					args.identity,
+					args.library_id,
+					args.cas_ids,
+				));
+
+				Ok(())
+			})
+		})
 		.procedure("acceptSpacedrop", {
 			R.mutation(|node, (id, path): (Uuid, Option<String>)| async move {
 				match path {
@@ -145,6 +252,105 @@ pub(crate) fn mount() -> AlphaRouter<Ctx> {
 			R.mutation(|node, id: Uuid| async move {
 				node.p2p.cancel_spacedrop(id).await;
 
+				Ok(())
+			})
+		})
+		.procedure("generatePairingCode", {
+			#[derive(Serialize, Type)]
+			pub struct GeneratedPairingCode {
+				// The code to share with the other node, this is also the payload for a QR code
+				code: String,
+				expires_in_secs: u32,
+			}
+
+			R.mutation(|node, _: ()| async move {
+				Ok(GeneratedPairingCode {
+					code: node.p2p.generate_pairing_code().await.to_string(),
+					expires_in_secs: operations::pairing::PAIRING_CODE_TIMEOUT.as_secs() as u32,
+				})
+			})
+		})
+		.procedure("pair", {
+			R.mutation(|node, code: String| async move {
+				let code = code.parse::<PairingCode>().map_err(|e| {
+					rspc::Error::new(ErrorCode::BadRequest, format!("invalid pairing code: {e}"))
+				})?;
+
+				let peer =
+					operations::pair(node.p2p.clone(), code)
+						.await
+						.map_err(|pairing_err| {
+							rspc::Error::new(
+								ErrorCode::InternalServerError,
+								pairing_err.to_string(),
+							)
+						})?;
+
+				invalidate_query!(node; node, "p2p.trustedPeers.list");
+				invalidate_query!(node; node, "nodeState");
+
+				Ok(peer)
+			})
+		})
+		.merge("trustedPeers.", mount_trusted_peers_routes())
+}
+
+fn mount_trusted_peers_routes() -> AlphaRouter<Ctx> {
+	R.router()
+		.procedure("list", {
+			R.query(|node, _: ()| async move { Ok(node.config.get().await.p2p.trusted_peers) })
+		})
+		.procedure("rename", {
+			#[derive(Type, Deserialize)]
+			pub struct RenameTrustedPeerArgs {
+				identity: RemoteIdentity,
+				name: String,
+			}
+
+			R.mutation(|node, args: RenameTrustedPeerArgs| async move {
+				if args.name.is_empty() || args.name.len() > 250 {
+					return Err(rspc::Error::new(
+						ErrorCode::BadRequest,
+						"invalid peer name".into(),
+					));
+				}
+
+				let found = node
+					.p2p
+					.rename_trusted_peer(args.identity, args.name)
+					.await
+					.map_err(|e| rspc::Error::new(ErrorCode::InternalServerError, e.to_string()))?;
+
+				if !found {
+					return Err(rspc::Error::new(
+						ErrorCode::NotFound,
+						"trusted peer not found".into(),
+					));
+				}
+
+				invalidate_query!(node; node, "p2p.trustedPeers.list");
+				invalidate_query!(node; node, "nodeState");
+
+				Ok(())
+			})
+		})
+		.procedure("revoke", {
+			R.mutation(|node, identity: RemoteIdentity| async move {
+				let found =
+					node.p2p.revoke_trusted_peer(identity).await.map_err(|e| {
+						rspc::Error::new(ErrorCode::InternalServerError, e.to_string())
+					})?;
+
+				if !found {
+					return Err(rspc::Error::new(
+						ErrorCode::NotFound,
+						"trusted peer not found".into(),
+					));
+				}
+
+				invalidate_query!(node; node, "p2p.trustedPeers.list");
+				invalidate_query!(node; node, "nodeState");
+
 				Ok(())
 			})
 		})
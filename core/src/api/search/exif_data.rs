@@ -1,5 +1,7 @@
-use sd_prisma::prisma::{self, exif_data};
+use sd_media_metadata::exif::Orientation;
+use sd_prisma::prisma::{self, exif_data, object};
 
+use prisma_client_rust::not;
 use serde::{Deserialize, Serialize};
 use specta::Type;
 
@@ -9,12 +11,24 @@ use super::utils::*;
 #[serde(rename_all = "camelCase", tag = "field", content = "value")]
 pub enum ExifDataOrder {
 	EpochTime(SortOrder),
+	CameraMake(SortOrder),
+	CameraModel(SortOrder),
+	Lens(SortOrder),
+	FocalLength(SortOrder),
+	Iso(SortOrder),
+	Megapixels(SortOrder),
 }
 
 impl ExifDataOrder {
 	pub fn get_sort_order(&self) -> prisma::SortOrder {
 		(*match self {
 			Self::EpochTime(v) => v,
+			Self::CameraMake(v) => v,
+			Self::CameraModel(v) => v,
+			Self::Lens(v) => v,
+			Self::FocalLength(v) => v,
+			Self::Iso(v) => v,
+			Self::Megapixels(v) => v,
 		})
 		.into()
 	}
@@ -24,6 +38,71 @@ impl ExifDataOrder {
 		use exif_data::*;
 		match self {
 			Self::EpochTime(_) => epoch_time::order(dir),
+			Self::CameraMake(_) => device_make::order(dir),
+			Self::CameraModel(_) => device_model::order(dir),
+			Self::Lens(_) => lens_model::order(dir),
+			Self::FocalLength(_) => focal_length::order(dir),
+			Self::Iso(_) => iso::order(dir),
+			Self::Megapixels(_) => megapixels::order(dir),
 		}
 	}
 }
+
+#[derive(Serialize, Deserialize, Type, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub enum ExifDataFilterArgs {
+	CameraMake(InOrNotIn<String>),
+	CameraModel(InOrNotIn<String>),
+	Lens(InOrNotIn<String>),
+	FocalLength(Range<f64>),
+	Iso(Range<i32>),
+	Megapixels(Range<f64>),
+	Orientation(InOrNotIn<Orientation>),
+	HasLocation(bool),
+}
+
+impl ExifDataFilterArgs {
+	pub fn into_params(self) -> Vec<object::WhereParam> {
+		use exif_data::*;
+
+		let param = match self {
+			Self::CameraMake(v) => v.into_param(device_make::in_vec, device_make::not_in_vec),
+			Self::CameraModel(v) => v.into_param(device_model::in_vec, device_model::not_in_vec),
+			Self::Lens(v) => v.into_param(lens_model::in_vec, lens_model::not_in_vec),
+			Self::FocalLength(v) => Some(match v {
+				Range::From(v) => focal_length::gte(v),
+				Range::To(v) => focal_length::lte(v),
+			}),
+			Self::Iso(v) => Some(match v {
+				Range::From(v) => iso::gte(v),
+				Range::To(v) => iso::lte(v),
+			}),
+			Self::Megapixels(v) => Some(match v {
+				Range::From(v) => megapixels::gte(v),
+				Range::To(v) => megapixels::lte(v),
+			}),
+			Self::Orientation(v) => v.into_param(
+				|v| orientation::in_vec(orientation_names(v)),
+				|v| orientation::not_in_vec(orientation_names(v)),
+			),
+			// Media without EXIF data has no location either, so it must be matched from the object
+			Self::HasLocation(true) => {
+				return vec![object::exif_data::is(vec![latitude::not(None)])];
+			}
+			Self::HasLocation(false) => {
+				return vec![not![object::exif_data::is(vec![latitude::not(None)])]];
+			}
+		};
+
+		param
+			.map(|param| vec![object::exif_data::is(vec![param])])
+			.unwrap_or_default()
+	}
+}
+
+fn orientation_names(orientations: Vec<Orientation>) -> Vec<String> {
+	orientations
+		.into_iter()
+		.map(|orientation| orientation.as_str().to_string())
+		.collect()
+}
@@ -0,0 +1,114 @@
+use sd_prisma::prisma::{
+	self, ffmpeg_data, ffmpeg_media_codec, ffmpeg_media_program, ffmpeg_media_stream,
+};
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use super::utils::*;
+
+#[derive(Serialize, Deserialize, Type, Debug, Clone)]
+#[serde(rename_all = "camelCase", tag = "field", content = "value")]
+pub enum FfmpegDataOrder {
+	Duration(SortOrder),
+	BitRate(SortOrder),
+	Width(SortOrder),
+	Height(SortOrder),
+	FrameRate(SortOrder),
+	AudioChannels(SortOrder),
+	SampleRate(SortOrder),
+}
+
+impl FfmpegDataOrder {
+	pub fn get_sort_order(&self) -> prisma::SortOrder {
+		(*match self {
+			Self::Duration(v) => v,
+			Self::BitRate(v) => v,
+			Self::Width(v) => v,
+			Self::Height(v) => v,
+			Self::FrameRate(v) => v,
+			Self::AudioChannels(v) => v,
+			Self::SampleRate(v) => v,
+		})
+		.into()
+	}
+
+	pub fn into_param(self) -> ffmpeg_data::OrderByWithRelationParam {
+		let dir = self.get_sort_order();
+		use ffmpeg_data::*;
+		match self {
+			Self::Duration(_) => duration_seconds::order(dir),
+			Self::BitRate(_) => bits_per_second::order(dir),
+			Self::Width(_) => width::order(dir),
+			Self::Height(_) => height::order(dir),
+			Self::FrameRate(_) => frame_rate::order(dir),
+			Self::AudioChannels(_) => audio_channels::order(dir),
+			Self::SampleRate(_) => sample_rate::order(dir),
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, Type, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub enum FfmpegDataFilterArgs {
+	/// In seconds
+	Duration(Range<f64>),
+	/// Matches the codec of any stream, like `h264`, `prores` or `aac`
+	Codec(InOrNotIn<String>),
+	/// In kilobits per second
+	BitRate(Range<i32>),
+	Width(Range<i32>),
+	Height(Range<i32>),
+	FrameRate(Range<f64>),
+	AudioChannels(Range<i32>),
+	SampleRate(Range<i32>),
+	HasSubtitles(bool),
+}
+
+impl FfmpegDataFilterArgs {
+	pub fn into_param(self) -> Option<ffmpeg_data::WhereParam> {
+		use ffmpeg_data::*;
+
+		match self {
+			Self::Duration(v) => Some(match v {
+				Range::From(v) => duration_seconds::gte(v),
+				Range::To(v) => duration_seconds::lte(v),
+			}),
+			Self::Codec(v) => v.into_param(
+				|v| programs::some(vec![streams_with_codecs(v)]),
+				|v| programs::none(vec![streams_with_codecs(v)]),
+			),
+			Self::BitRate(v) => Some(match v {
+				Range::From(v) => bits_per_second::gte(i64::from(v) * 1000),
+				Range::To(v) => bits_per_second::lte(i64::from(v) * 1000),
+			}),
+			Self::Width(v) => Some(match v {
+				Range::From(v) => width::gte(v),
+				Range::To(v) => width::lte(v),
+			}),
+			Self::Height(v) => Some(match v {
+				Range::From(v) => height::gte(v),
+				Range::To(v) => height::lte(v),
+			}),
+			Self::FrameRate(v) => Some(match v {
+				Range::From(v) => frame_rate::gte(v),
+				Range::To(v) => frame_rate::lte(v),
+			}),
+			Self::AudioChannels(v) => Some(match v {
+				Range::From(v) => audio_channels::gte(v),
+				Range::To(v) => audio_channels::lte(v),
+			}),
+			Self::SampleRate(v) => Some(match v {
+				Range::From(v) => sample_rate::gte(v),
+				Range::To(v) => sample_rate::lte(v),
+			}),
+			Self::HasSubtitles(v) => Some(has_subtitles::equals(Some(v))),
+		}
+	}
+}
+
+fn streams_with_codecs(names: Vec<String>) -> ffmpeg_media_program::WhereParam {
+	ffmpeg_media_program::streams::some(vec![ffmpeg_media_stream::codec::is(vec![
+		ffmpeg_media_codec::name::in_vec(names),
+	])])
+}
@@ -1,6 +1,10 @@
 use crate::location::LocationError;
 
-use sd_core_file_path_helper::{check_file_path_exists, IsolatedFilePathData};
+use sd_core_file_path_helper::{
+	check_file_path_exists,
+	posix::{posix_id_to_db, xattr_name_db_pattern},
+	IsolatedFilePathData,
+};
 
 use sd_prisma::prisma::{self, file_path};
 
@@ -70,6 +74,15 @@ pub enum FilePathFilterArgs {
 	ModifiedAt(Range<DateTime<Utc>>),
 	IndexedAt(Range<DateTime<Utc>>),
 	Hidden(bool),
+	/// POSIX permission bits, without the file type ones, like `0o644`
+	Permissions(InOrNotIn<u32>),
+	Uid(InOrNotIn<u32>),
+	Gid(InOrNotIn<u32>),
+	OwnerName(InOrNotIn<String>),
+	GroupName(InOrNotIn<String>),
+	/// File paths having the extended attribute with this name, only the ones selected in the
+	/// location settings are stored
+	HasXattr(String),
 }
 
 impl FilePathFilterArgs {
@@ -152,10 +165,45 @@ impl FilePathFilterArgs {
 			Self::Hidden(v) => {
 				vec![hidden::equals(Some(v))]
 			}
+			Self::Permissions(v) => {
+				posix_ids_param(v, permissions::in_vec, permissions::not_in_vec)
+					.map(|v| vec![v])
+					.unwrap_or_default()
+			}
+			Self::Uid(v) => posix_ids_param(v, uid::in_vec, uid::not_in_vec)
+				.map(|v| vec![v])
+				.unwrap_or_default(),
+			Self::Gid(v) => posix_ids_param(v, gid::in_vec, gid::not_in_vec)
+				.map(|v| vec![v])
+				.unwrap_or_default(),
+			Self::OwnerName(v) => v
+				.into_param(owner_name::in_vec, owner_name::not_in_vec)
+				.map(|v| vec![v])
+				.unwrap_or_default(),
+			Self::GroupName(v) => v
+				.into_param(group_name::in_vec, group_name::not_in_vec)
+				.map(|v| vec![v])
+				.unwrap_or_default(),
+			Self::HasXattr(name) => {
+				vec![xattrs::contains(xattr_name_db_pattern(&name))]
+			}
 		})
 	}
 }
 
+/// POSIX ids and modes are unsigned, but we store them with the same bits in signed columns
+fn posix_ids_param(
+	ids: InOrNotIn<u32>,
+	in_fn: fn(Vec<i32>) -> file_path::WhereParam,
+	not_in_fn: fn(Vec<i32>) -> file_path::WhereParam,
+) -> Option<file_path::WhereParam> {
+	match ids {
+		InOrNotIn::In(ids) => InOrNotIn::In(ids.into_iter().map(posix_id_to_db).collect()),
+		InOrNotIn::NotIn(ids) => InOrNotIn::NotIn(ids.into_iter().map(posix_id_to_db).collect()),
+	}
+	.into_param(in_fn, not_in_fn)
+}
+
 #[derive(Deserialize, Type, Debug)]
 #[serde(rename_all = "camelCase")]
 pub enum FilePathObjectCursor {
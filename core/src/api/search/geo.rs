@@ -0,0 +1,364 @@
+use sd_media_metadata::exif::distance_km;
+use sd_prisma::prisma::{exif_data, object, PrismaClient, SortOrder};
+
+use std::{collections::HashMap, f64::consts::PI};
+
+use prisma_client_rust::{
+	operator::{and, or},
+	or,
+};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+/// Web Mercator maps stop at this latitude, so the world fits in a square
+const MAX_MERCATOR_LATITUDE: f64 = 85.051_128_78;
+
+/// Past this zoom level tiles are only a few meters wide, so there's nothing left to cluster
+pub const MAX_ZOOM: u8 = 20;
+
+/// Length of a degree of latitude, in kilometers
+const DEGREE_KM: f64 = 111.2;
+
+/// Radius filters look for media within this many slices of the circle, so the database only
+/// leaves out the few points in their corners for us to check
+const RADIUS_BANDS: u32 = 16;
+
+/// How many points within the radius bands are checked at a time
+const RADIUS_BATCH_SIZE: i64 = 1000;
+
+/// An area of the map, crossing the antimeridian when `west` is greater than `east`
+#[derive(Serialize, Deserialize, Type, Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+	pub north: f64,
+	pub south: f64,
+	pub east: f64,
+	pub west: f64,
+}
+
+impl BoundingBox {
+	/// The smallest bounding box holding every point within `radius_km` of the center
+	fn around((latitude, longitude): (f64, f64), radius_km: f64) -> Self {
+		let delta_lat = radius_km / DEGREE_KM;
+		let (north, south) = (latitude + delta_lat, latitude - delta_lat);
+
+		// Circles reaching a pole, or wider than the world, hold every longitude
+		let delta_lon = if north >= 90.0 || south <= -90.0 {
+			180.0
+		} else {
+			(radius_km / (DEGREE_KM * latitude.to_radians().cos())).min(180.0)
+		};
+
+		if delta_lon >= 180.0 {
+			return Self {
+				north: north.min(90.0),
+				south: south.max(-90.0),
+				east: 180.0,
+				west: -180.0,
+			};
+		}
+
+		Self {
+			north: north.min(90.0),
+			south: south.max(-90.0),
+			east: wrap_longitude(longitude + delta_lon),
+			west: wrap_longitude(longitude - delta_lon),
+		}
+	}
+
+	/// Horizontal slices of the bounding box around a circle, each only as wide as the circle is
+	/// within it
+	fn bands_around((latitude, longitude): (f64, f64), radius_km: f64) -> Vec<Self> {
+		let bounds = Self::around((latitude, longitude), radius_km);
+		if bounds.west <= -180.0 && bounds.east >= 180.0 {
+			return vec![bounds];
+		}
+
+		let height = (bounds.north - bounds.south) / f64::from(RADIUS_BANDS);
+
+		(0..RADIUS_BANDS)
+			.map(|band| {
+				let south = bounds.south + height * f64::from(band);
+				let north = south + height;
+
+				// The circle is the widest at the latitude of the band closest to its center, and
+				// degrees of longitude are the shortest at the one farthest from the equator
+				let offset_km = (latitude.clamp(south, north) - latitude) * DEGREE_KM;
+				let half_width_km = (radius_km.powi(2) - offset_km.powi(2)).max(0.0).sqrt();
+				let delta_lon =
+					half_width_km / (DEGREE_KM * north.abs().max(south.abs()).to_radians().cos());
+
+				if delta_lon >= 180.0 {
+					Self {
+						north,
+						south,
+						east: 180.0,
+						west: -180.0,
+					}
+				} else {
+					Self {
+						north,
+						south,
+						east: wrap_longitude(longitude + delta_lon),
+						west: wrap_longitude(longitude - delta_lon),
+					}
+				}
+			})
+			.collect()
+	}
+
+	pub fn into_params(self) -> Vec<exif_data::WhereParam> {
+		use exif_data::*;
+
+		vec![
+			latitude::lte(self.north),
+			latitude::gte(self.south),
+			if self.west <= self.east {
+				prisma_client_rust::and![longitude::gte(self.west), longitude::lte(self.east)]
+			} else {
+				or![longitude::gte(self.west), longitude::lte(self.east)]
+			},
+		]
+	}
+}
+
+#[derive(Serialize, Deserialize, Type, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub enum GeoFilter {
+	/// Media taken within an area of the map
+	BoundingBox(BoundingBox),
+	/// Media taken within `radius_km` kilometers of a point
+	Radius {
+		latitude: f64,
+		longitude: f64,
+		radius_km: f64,
+	},
+}
+
+impl GeoFilter {
+	pub async fn into_params(self, db: &PrismaClient) -> Result<object::WhereParam, rspc::Error> {
+		match self {
+			Self::BoundingBox(bounds) => Ok(object::exif_data::is(bounds.into_params())),
+			Self::Radius {
+				latitude,
+				longitude,
+				radius_km,
+			} => {
+				let in_bands = || {
+					or(BoundingBox::bands_around((latitude, longitude), radius_km)
+						.into_iter()
+						.map(|band| and(band.into_params()))
+						.collect())
+				};
+
+				// The database can only narrow it down to the bands, so we page through them to
+				// leave out the points in their corners
+				let mut outside = vec![];
+				let mut cursor = exif_data::id::Type::MIN;
+
+				loop {
+					let points = db
+						.exif_data()
+						.find_many(vec![exif_data::id::gt(cursor), in_bands()])
+						.order_by(exif_data::id::order(SortOrder::Asc))
+						.take(RADIUS_BATCH_SIZE)
+						.select(exif_data::select!({ id latitude longitude object_id }))
+						.exec()
+						.await?;
+
+					let Some(last) = points.last() else {
+						break;
+					};
+					cursor = last.id;
+
+					outside.extend(points.into_iter().filter_map(|data| {
+						data.latitude
+							.zip(data.longitude)
+							.filter(|&point| distance_km((latitude, longitude), point) > radius_km)
+							.map(|_| data.object_id)
+					}));
+				}
+
+				let mut params = vec![object::exif_data::is(vec![in_bands()])];
+				if !outside.is_empty() {
+					params.push(object::id::not_in_vec(outside));
+				}
+
+				Ok(and(params))
+			}
+		}
+	}
+}
+
+/// Media taken within a map tile, to show a single marker instead of every photo in it
+#[derive(Serialize, Type, Debug, PartialEq)]
+pub struct GeoCluster {
+	/// Web Mercator tile coordinates at the requested zoom
+	x: u32,
+	y: u32,
+	count: u32,
+	/// Centroid of the media in the cluster, where its marker goes
+	latitude: f64,
+	longitude: f64,
+	/// An object from the cluster, to use its thumbnail on the marker
+	object_id: object::id::Type,
+}
+
+/// Clusters the media matching `object_params` taken within `bounds` by map tile at `zoom`
+pub async fn geo_clusters(
+	db: &PrismaClient,
+	zoom: u8,
+	bounds: Option<BoundingBox>,
+	object_params: Vec<object::WhereParam>,
+) -> Result<Vec<GeoCluster>, rspc::Error> {
+	let mut params = vec![
+		exif_data::latitude::not(None),
+		exif_data::longitude::not(None),
+	];
+
+	if let Some(bounds) = bounds {
+		params.extend(bounds.into_params());
+	}
+
+	if !object_params.is_empty() {
+		params.push(exif_data::object::is(object_params));
+	}
+
+	let points = db
+		.exif_data()
+		.find_many(params)
+		.select(exif_data::select!({ latitude longitude object_id }))
+		.exec()
+		.await?
+		.into_iter()
+		.filter_map(|data| Some((data.latitude?, data.longitude?, data.object_id)));
+
+	Ok(cluster(points, zoom.min(MAX_ZOOM)))
+}
+
+fn cluster(
+	points: impl IntoIterator<Item = (f64, f64, object::id::Type)>,
+	zoom: u8,
+) -> Vec<GeoCluster> {
+	let mut clusters = HashMap::<_, GeoCluster>::new();
+
+	for (latitude, longitude, object_id) in points {
+		let (x, y) = tile((latitude, longitude), zoom);
+
+		let cluster = clusters.entry((x, y)).or_insert(GeoCluster {
+			x,
+			y,
+			count: 0,
+			latitude: 0.0,
+			longitude: 0.0,
+			object_id,
+		});
+
+		cluster.count += 1;
+		cluster.latitude += latitude;
+		cluster.longitude += longitude;
+		cluster.object_id = cluster.object_id.min(object_id);
+	}
+
+	let mut clusters = clusters
+		.into_values()
+		.map(|mut cluster| {
+			cluster.latitude /= f64::from(cluster.count);
+			cluster.longitude /= f64::from(cluster.count);
+			cluster
+		})
+		.collect::<Vec<_>>();
+
+	clusters.sort_unstable_by_key(|cluster| (cluster.y, cluster.x));
+
+	clusters
+}
+
+/// The Web Mercator tile holding the coordinates at `zoom`, as used by map views
+fn tile((latitude, longitude): (f64, f64), zoom: u8) -> (u32, u32) {
+	let tiles = f64::from(1_u32 << zoom);
+	let last_tile = (1_u32 << zoom) - 1;
+
+	let latitude = latitude
+		.clamp(-MAX_MERCATOR_LATITUDE, MAX_MERCATOR_LATITUDE)
+		.to_radians();
+
+	let x = (wrap_longitude(longitude) + 180.0) / 360.0 * tiles;
+	let y = (1.0 - latitude.tan().asinh() / PI) / 2.0 * tiles;
+
+	((x as u32).min(last_tile), (y as u32).min(last_tile))
+}
+
+fn wrap_longitude(longitude: f64) -> f64 {
+	if (-180.0..=180.0).contains(&longitude) {
+		longitude
+	} else {
+		(longitude + 180.0).rem_euclid(360.0) - 180.0
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn tiles_at_zoom() {
+		assert_eq!(tile((38.72, -9.14), 0), (0, 0));
+		assert_eq!(tile((38.72, -9.14), 10), (486, 392));
+		assert_eq!(tile((90.0, 180.0), 2), (3, 0));
+		assert_eq!(tile((-90.0, -180.0), 2), (0, 3));
+	}
+
+	#[test]
+	fn radius_bounds_wrap() {
+		let bounds = BoundingBox::around((0.0, 179.5), 111.2);
+		assert!(bounds.west > bounds.east);
+		assert!((bounds.east - -179.5).abs() < 1e-9);
+
+		let bounds = BoundingBox::around((89.9, 10.0), 50.0);
+		assert_eq!(
+			(bounds.north, bounds.west, bounds.east),
+			(90.0, -180.0, 180.0)
+		);
+	}
+
+	#[test]
+	fn radius_bands_hold_the_circle() {
+		let center = (38.72, -9.14);
+		let bands = BoundingBox::bands_around(center, 50.0);
+		assert_eq!(bands.len(), RADIUS_BANDS as usize);
+
+		let in_bands = |(latitude, longitude): (f64, f64)| {
+			bands.iter().any(|band| {
+				(band.south..=band.north).contains(&latitude)
+					&& (band.west..=band.east).contains(&longitude)
+			})
+		};
+
+		for step in 0..64 {
+			let angle = f64::from(step) / 64.0 * 2.0 * PI;
+			let point = (
+				center.0 + 0.44 * angle.sin(),
+				center.1 + 0.44 / center.0.to_radians().cos() * angle.cos(),
+			);
+
+			assert!(distance_km(center, point) < 50.0);
+			assert!(in_bands(point));
+		}
+
+		// Unlike the single box around the circle, bands leave its corners out
+		assert!(!in_bands((center.0 + 0.44, center.1 + 0.55)));
+	}
+
+	#[test]
+	fn points_cluster_by_tile() {
+		let clusters = cluster(
+			[(38.72, -9.14, 3), (38.70, -9.10, 1), (-33.86, 151.2, 2)],
+			4,
+		);
+
+		assert_eq!(clusters.len(), 2);
+		assert_eq!((clusters[0].count, clusters[0].object_id), (2, 1));
+		assert!((clusters[0].latitude - 38.71).abs() < 1e-9);
+		assert_eq!(clusters[1].count, 1);
+	}
+}
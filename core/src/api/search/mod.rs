@@ -3,6 +3,7 @@ use crate::{
 	library::Library,
 	location::{non_indexed, LocationError},
 	util::{unsafe_streamed_query, BatchedStream},
+	Node,
 };
 
 use prisma_client_rust::Operator;
@@ -20,9 +21,12 @@ use serde::{Deserialize, Serialize};
 use specta::Type;
 
 pub mod exif_data;
+pub mod ffmpeg_data;
 pub mod file_path;
+mod geo;
 pub mod object;
 pub mod saved;
+mod similar;
 mod utils;
 
 pub use self::{file_path::*, object::*, utils::*};
@@ -53,7 +57,7 @@ impl SearchFilterArgs {
 	) -> Result<(), rspc::Error> {
 		match self {
 			Self::FilePath(v) => file_path.extend(v.into_params(db).await?),
-			Self::Object(v) => object.extend(v.into_params()),
+			Self::Object(v) => object.extend(v.into_params(db).await?),
 		};
 		Ok(())
 	}
@@ -327,36 +331,7 @@ pub fn mount() -> AlphaRouter<Ctx> {
 						(objects, cursor)
 					};
 
-					let mut items = Vec::with_capacity(objects.len());
-
-					for object in objects {
-						let cas_id = object
-							.file_paths
-							.iter()
-							.map(|fp| fp.cas_id.as_ref())
-							.find_map(|c| c)
-							.map(CasId::from)
-							.map(|cas_id| cas_id.to_owned());
-
-						let has_created_thumbnail = if let Some(cas_id) = &cas_id {
-							library.thumbnail_exists(&node, cas_id).await.map_err(|e| {
-								rspc::Error::with_cause(
-									ErrorCode::InternalServerError,
-									"Failed to check that thumbnail exists".to_string(),
-									e,
-								)
-							})?
-						} else {
-							false
-						};
-
-						items.push(ExplorerItem::Object {
-							thumbnail: cas_id
-								.map(|cas_id| ThumbKey::new_indexed(cas_id, library.id)),
-							item: object,
-							has_created_thumbnail,
-						});
-					}
+					let items = objects_to_explorer_items(&node, &library, objects).await?;
 
 					Ok(SearchData { items, cursor })
 				},
@@ -390,9 +365,137 @@ pub fn mount() -> AlphaRouter<Ctx> {
 						.await? as u32)
 				})
 		})
+		.procedure("similar", {
+			#[derive(Deserialize, Type, Debug)]
+			#[serde(rename_all = "camelCase")]
+			#[specta(inline)]
+			struct Args {
+				object_id: prisma::object::id::Type,
+				#[specta(optional)]
+				max_distance: Option<u32>,
+				take: u8,
+			}
+
+			R.with2(library()).query(
+				|(node, library),
+				 Args {
+				     object_id,
+				     max_distance,
+				     take,
+				 }| async move {
+					similar::similar_objects(
+						&node,
+						&library,
+						object_id,
+						max_distance.unwrap_or(similar::DEFAULT_MAX_DISTANCE),
+						take.min(MAX_TAKE),
+					)
+					.await
+				},
+			)
+		})
+		.procedure("similarGroups", {
+			#[derive(Deserialize, Type, Debug)]
+			#[serde(rename_all = "camelCase")]
+			#[specta(inline)]
+			struct Args {
+				#[specta(optional)]
+				max_distance: Option<u32>,
+				take: u8,
+				#[specta(optional)]
+				cursor: Option<u32>,
+			}
+
+			R.with2(library()).query(
+				|(node, library),
+				 Args {
+				     max_distance,
+				     take,
+				     cursor,
+				 }| async move {
+					similar::similar_groups(
+						&node,
+						&library,
+						max_distance.unwrap_or(similar::DEFAULT_MAX_DISTANCE),
+						take.min(MAX_TAKE),
+						cursor.unwrap_or_default(),
+					)
+					.await
+				},
+			)
+		})
+		.procedure("geoClusters", {
+			#[derive(Deserialize, Type, Debug)]
+			#[serde(rename_all = "camelCase")]
+			#[specta(inline)]
+			struct Args {
+				zoom: u8,
+				#[specta(optional)]
+				bounds: Option<geo::BoundingBox>,
+				#[serde(default)]
+				filters: Vec<SearchFilterArgs>,
+			}
+
+			R.with2(library()).query(
+				|(_, library),
+				 Args {
+				     zoom,
+				     bounds,
+				     filters,
+				 }| async move {
+					let Library { db, .. } = library.as_ref();
+
+					let (fp, mut obj) = merge_filters(filters, db).await?;
+
+					if !fp.is_empty() {
+						obj.push(prisma::object::file_paths::some(fp));
+					}
+
+					geo::geo_clusters(db, zoom, bounds, obj).await
+				},
+			)
+		})
 		.merge("saved.", saved::mount())
 }
 
+async fn objects_to_explorer_items(
+	node: &Node,
+	library: &Library,
+	objects: Vec<object_with_file_paths::Data>,
+) -> Result<Vec<ExplorerItem>, rspc::Error> {
+	let mut items = Vec::with_capacity(objects.len());
+
+	for object in objects {
+		let cas_id = object
+			.file_paths
+			.iter()
+			.map(|fp| fp.cas_id.as_ref())
+			.find_map(|c| c)
+			.map(CasId::from)
+			.map(|cas_id| cas_id.to_owned());
+
+		let has_created_thumbnail = if let Some(cas_id) = &cas_id {
+			library.thumbnail_exists(node, cas_id).await.map_err(|e| {
+				rspc::Error::with_cause(
+					ErrorCode::InternalServerError,
+					"Failed to check that thumbnail exists".to_string(),
+					e,
+				)
+			})?
+		} else {
+			false
+		};
+
+		items.push(ExplorerItem::Object {
+			thumbnail: cas_id.map(|cas_id| ThumbKey::new_indexed(cas_id, library.id)),
+			item: object,
+			has_created_thumbnail,
+		});
+	}
+
+	Ok(items)
+}
+
 async fn merge_filters(
 	filters: Vec<SearchFilterArgs>,
 	db: &PrismaClient,
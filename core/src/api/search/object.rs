@@ -1,6 +1,6 @@
 // use crate::library::Category;
 
-use sd_prisma::prisma::{self, label_on_object, object, tag_on_object};
+use sd_prisma::prisma::{self, label_on_object, object, tag_on_object, PrismaClient};
 
 use chrono::{DateTime, FixedOffset};
 use prisma_client_rust::{not, or, OrderByQuery, PaginatedQuery, WhereQuery};
@@ -9,6 +9,8 @@ use specta::Type;
 
 use super::{
 	exif_data::*,
+	ffmpeg_data::*,
+	geo::GeoFilter,
 	utils::{self, *},
 };
 
@@ -62,6 +64,7 @@ pub enum ObjectOrder {
 	DateAccessed(SortOrder),
 	Kind(SortOrder),
 	MediaData(Box<ExifDataOrder>),
+	FfmpegData(Box<FfmpegDataOrder>),
 }
 
 impl ObjectOrder {
@@ -70,6 +73,7 @@ impl ObjectOrder {
 			Self::DateAccessed(v) => v,
 			Self::Kind(v) => v,
 			Self::MediaData(v) => return v.get_sort_order(),
+			Self::FfmpegData(v) => return v.get_sort_order(),
 		})
 		.into()
 	}
@@ -82,6 +86,7 @@ impl ObjectOrder {
 			Self::DateAccessed(_) => date_accessed::order(dir),
 			Self::Kind(_) => kind::order(dir),
 			Self::MediaData(v) => exif_data::order(vec![v.into_param()]),
+			Self::FfmpegData(v) => ffmpeg_data::order(vec![v.into_param()]),
 		}
 	}
 }
@@ -115,13 +120,19 @@ pub enum ObjectFilterArgs {
 	Tags(InOrNotIn<i32>),
 	Labels(InOrNotIn<i32>),
 	DateAccessed(Range<chrono::DateTime<FixedOffset>>),
+	Geo(GeoFilter),
+	MediaData(ExifDataFilterArgs),
+	FfmpegData(FfmpegDataFilterArgs),
 }
 
 impl ObjectFilterArgs {
-	pub fn into_params(self) -> Vec<object::WhereParam> {
+	pub async fn into_params(
+		self,
+		db: &PrismaClient,
+	) -> Result<Vec<object::WhereParam>, rspc::Error> {
 		use object::*;
 
-		match self {
+		Ok(match self {
 			Self::Favorite(v) => vec![favorite::equals(Some(v))],
 			Self::Hidden(v) => v.to_param().map(|v| vec![v]).unwrap_or_default(),
 			Self::Tags(v) => v
@@ -151,7 +162,13 @@ impl ObjectFilterArgs {
 					},
 				]
 			}
-		}
+			Self::Geo(v) => vec![v.into_params(db).await?],
+			Self::MediaData(v) => v.into_params(),
+			Self::FfmpegData(v) => v
+				.into_param()
+				.map(|v| vec![ffmpeg_data::is(vec![v])])
+				.unwrap_or_default(),
+		})
 	}
 }
 
@@ -0,0 +1,229 @@
+use crate::{api::locations::ExplorerItem, library::Library, Node};
+
+use sd_core_prisma_helpers::object_with_file_paths;
+use sd_images::{PerceptualHash, PerceptualHashIndex};
+use sd_prisma::prisma::{object, perceptual_hash, PrismaClient};
+
+use std::collections::HashMap;
+
+use rspc::ErrorCode;
+use serde::Serialize;
+use specta::Type;
+
+use super::objects_to_explorer_items;
+
+/// Hashes within this distance are usually of the same image, even after being resized,
+/// re-encoded or slightly edited
+pub const DEFAULT_MAX_DISTANCE: u32 = 10;
+
+#[derive(Serialize, Type, Debug)]
+pub struct SimilarObject {
+	/// Hamming distance between the perceptual hashes, 0 for visually identical images
+	distance: u32,
+	item: ExplorerItem,
+}
+
+#[derive(Serialize, Type, Debug)]
+pub struct SimilarGroups {
+	groups: Vec<Vec<ExplorerItem>>,
+	cursor: Option<u32>,
+}
+
+/// Objects with a perceptual hash within `max_distance` of the hash of `object_id`, closest first
+pub async fn similar_objects(
+	node: &Node,
+	library: &Library,
+	object_id: object::id::Type,
+	max_distance: u32,
+	take: u8,
+) -> Result<Vec<SimilarObject>, rspc::Error> {
+	let db = &library.db;
+
+	let hash = db
+		.perceptual_hash()
+		.find_unique(perceptual_hash::object_id::equals(object_id))
+		.exec()
+		.await?
+		.map(|data| PerceptualHash::from(data.hash))
+		.ok_or_else(|| {
+			rspc::Error::new(
+				ErrorCode::NotFound,
+				"object doesn't have a perceptual hash".to_string(),
+			)
+		})?;
+
+	// A single query doesn't pay off building an index, so we just compare all hashes
+	let mut distances = load_hashes(db)
+		.await?
+		.into_iter()
+		.filter(|&(_, id)| id != object_id)
+		.filter_map(|(other_hash, id)| {
+			let distance = hash.distance(other_hash);
+			(distance <= max_distance).then_some((id, distance))
+		})
+		.collect::<Vec<_>>();
+
+	distances.sort_unstable_by_key(|&(id, distance)| (distance, id));
+	distances.truncate(usize::from(take));
+
+	let mut objects = fetch_objects(db, distances.iter().map(|&(id, _)| id).collect()).await?;
+
+	let (objects, distances) = distances
+		.into_iter()
+		.filter_map(|(id, distance)| objects.remove(&id).map(|object| (object, distance)))
+		.unzip::<_, _, Vec<_>, Vec<_>>();
+
+	Ok(objects_to_explorer_items(node, library, objects)
+		.await?
+		.into_iter()
+		.zip(distances)
+		.map(|(item, distance)| SimilarObject { distance, item })
+		.collect())
+}
+
+/// Groups of near-duplicate objects, to clean them up, largest groups first
+pub async fn similar_groups(
+	node: &Node,
+	library: &Library,
+	max_distance: u32,
+	take: u8,
+	cursor: u32,
+) -> Result<SimilarGroups, rspc::Error> {
+	let db = &library.db;
+
+	let groups = group_similar(&load_hashes(db).await?, max_distance);
+
+	let skip = cursor as usize;
+	let next_cursor = (skip + usize::from(take) < groups.len()).then(|| cursor + u32::from(take));
+
+	let groups = groups
+		.into_iter()
+		.skip(skip)
+		.take(usize::from(take))
+		.collect::<Vec<_>>();
+
+	let mut objects = fetch_objects(db, groups.iter().flatten().copied().collect()).await?;
+
+	let mut items_groups = Vec::with_capacity(groups.len());
+	for group in groups {
+		let objects = group
+			.into_iter()
+			.filter_map(|id| objects.remove(&id))
+			.collect::<Vec<_>>();
+
+		// Objects can be removed while we were grouping them
+		if objects.len() > 1 {
+			items_groups.push(objects_to_explorer_items(node, library, objects).await?);
+		}
+	}
+
+	Ok(SimilarGroups {
+		groups: items_groups,
+		cursor: next_cursor,
+	})
+}
+
+async fn load_hashes(
+	db: &PrismaClient,
+) -> Result<Vec<(PerceptualHash, object::id::Type)>, rspc::Error> {
+	Ok(db
+		.perceptual_hash()
+		.find_many(vec![])
+		.select(perceptual_hash::select!({ hash object_id }))
+		.exec()
+		.await?
+		.into_iter()
+		.map(|data| (PerceptualHash::from(data.hash), data.object_id))
+		.collect())
+}
+
+async fn fetch_objects(
+	db: &PrismaClient,
+	ids: Vec<object::id::Type>,
+) -> Result<HashMap<object::id::Type, object_with_file_paths::Data>, rspc::Error> {
+	Ok(db
+		.object()
+		.find_many(vec![object::id::in_vec(ids)])
+		.include(object_with_file_paths::include())
+		.exec()
+		.await?
+		.into_iter()
+		.map(|object| (object.id, object))
+		.collect())
+}
+
+/// Groups objects whose hashes are within `max_distance` of any other hash in the group, so a
+/// series of slightly different images ends up in a single group.
+///
+/// Groups are sorted by size and then by their lowest object id, so they can be paginated.
+fn group_similar(
+	hashes: &[(PerceptualHash, object::id::Type)],
+	max_distance: u32,
+) -> Vec<Vec<object::id::Type>> {
+	fn find_root(parents: &mut [usize], mut i: usize) -> usize {
+		while parents[i] != i {
+			parents[i] = parents[parents[i]];
+			i = parents[i];
+		}
+		i
+	}
+
+	let index = hashes
+		.iter()
+		.enumerate()
+		.map(|(i, &(hash, _))| (hash, i))
+		.collect::<PerceptualHashIndex<_>>();
+
+	let mut parents = (0..hashes.len()).collect::<Vec<_>>();
+
+	for (i, &(hash, _)) in hashes.iter().enumerate() {
+		for (&j, _) in index.find_within(hash, max_distance) {
+			let (root_i, root_j) = (find_root(&mut parents, i), find_root(&mut parents, j));
+			if root_i != root_j {
+				parents[root_i.max(root_j)] = root_i.min(root_j);
+			}
+		}
+	}
+
+	let mut groups = HashMap::<_, Vec<_>>::new();
+	for (i, &(_, object_id)) in hashes.iter().enumerate() {
+		groups
+			.entry(find_root(&mut parents, i))
+			.or_default()
+			.push(object_id);
+	}
+
+	let mut groups = groups
+		.into_values()
+		.filter(|group| group.len() > 1)
+		.map(|mut group| {
+			group.sort_unstable();
+			group
+		})
+		.collect::<Vec<_>>();
+
+	groups.sort_unstable_by(|a, b| b.len().cmp(&a.len()).then_with(|| a[0].cmp(&b[0])));
+
+	groups
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn near_duplicates_are_grouped() {
+		let hashes = [
+			(PerceptualHash::from(0b0000), 1),
+			(PerceptualHash::from(0b1111_0000), 2),
+			(PerceptualHash::from(0b0001), 3),
+			(PerceptualHash::from(-1), 4),
+			(PerceptualHash::from(0b0011), 5),
+			(PerceptualHash::from(0b1111_0001), 6),
+		];
+
+		// Objects 1 and 5 are too far apart, but object 3 links them
+		assert_eq!(group_similar(&hashes, 1), vec![vec![1, 3, 5], vec![2, 6]]);
+		assert_eq!(group_similar(&hashes, 0), Vec::<Vec<_>>::new());
+	}
+}
@@ -2,8 +2,11 @@ use crate::{api::CoreEvent, invalidate_query, library::Library, old_job::JobProg
 
 use sd_core_heavy_lifting::{
 	job_system::report::{Report, Status},
+	media_processor::VideoPreviewsOptions,
 	OuterContext, ProgressUpdate, UpdateEvent,
 };
+use sd_media_metadata::exif::ReverseGeocoder;
+use sd_task_system::Throttle;
 
 use std::{
 	ops::{Deref, DerefMut},
@@ -77,6 +80,28 @@ impl OuterContext for NodeContext {
 	fn get_data_directory(&self) -> &std::path::Path {
 		&self.node.data_dir
 	}
+
+	fn indexer_throttles(&self, location_pub_id: &[u8]) -> Vec<Throttle> {
+		self.node.indexer_throttles.for_location(location_pub_id)
+	}
+
+	fn video_previews_options(&self) -> VideoPreviewsOptions {
+		self.node
+			.config
+			.preferences_watcher()
+			.borrow()
+			.thumbnailer
+			.video_previews
+	}
+
+	fn reverse_geocoder(&self) -> Option<Arc<ReverseGeocoder>> {
+		self.node.reverse_geocoder.get()
+	}
+
+	#[cfg(feature = "ai")]
+	fn image_labeler(&self) -> Option<Arc<sd_ai::image_labeler::ImageLabeler>> {
+		self.node.image_labeler.get()
+	}
 }
 
 #[derive(Clone)]
@@ -115,6 +140,23 @@ impl<OuterCtx: OuterContext + NodeContextExt> OuterContext for JobContext<OuterC
 	fn get_data_directory(&self) -> &std::path::Path {
 		self.outer_ctx.get_data_directory()
 	}
+
+	fn indexer_throttles(&self, location_pub_id: &[u8]) -> Vec<Throttle> {
+		self.outer_ctx.indexer_throttles(location_pub_id)
+	}
+
+	fn video_previews_options(&self) -> VideoPreviewsOptions {
+		self.outer_ctx.video_previews_options()
+	}
+
+	fn reverse_geocoder(&self) -> Option<Arc<ReverseGeocoder>> {
+		self.outer_ctx.reverse_geocoder()
+	}
+
+	#[cfg(feature = "ai")]
+	fn image_labeler(&self) -> Option<Arc<sd_ai::image_labeler::ImageLabeler>> {
+		self.outer_ctx.image_labeler()
+	}
 }
 
 impl<OuterCtx: OuterContext + NodeContextExt> sd_core_heavy_lifting::JobContext<OuterCtx>
@@ -1,19 +1,28 @@
 use crate::{
 	api::{utils::InvalidateOperationEvent, CoreEvent},
 	library::Library,
-	p2p::operations::{self, request_file},
+	p2p::{
+		operations::{self, remote_thumbnail, request_file},
+		P2PManager,
+	},
 	util::InfallibleResponse,
 	Node,
 };
 
 use sd_core_file_path_helper::IsolatedFilePathData;
-use sd_core_heavy_lifting::media_processor::WEBP_EXTENSION;
-use sd_core_prisma_helpers::file_path_to_handle_custom_uri;
+use sd_core_heavy_lifting::media_processor::{
+	generate_document_page, generate_thumbnail_variant, get_thumbnails_directory,
+	thumbnailer::NonCriticalThumbnailerError, ThumbFormat, ThumbSize, ThumbnailKind,
+	VideoPreviewKind, EPHEMERAL_DIR, WEBP_EXTENSION,
+};
+use sd_core_prisma_helpers::{file_path_to_handle_custom_uri, CasId};
 
-use sd_file_ext::text::is_text;
-use sd_p2p::{RemoteIdentity, P2P};
+use sd_file_ext::{extensions::DocumentExtension, text::is_text};
+use sd_images::PDF_MAX_PAGE_RENDER_WIDTH;
+use sd_media_metadata::ffmpeg::waveform::Waveform;
+use sd_p2p::RemoteIdentity;
 use sd_p2p_block::Range;
-use sd_prisma::prisma::{file_path, location};
+use sd_prisma::prisma::{ffmpeg_data, file_path, location, object};
 use sd_utils::db::maybe_missing;
 use tokio_util::sync::PollSender;
 
@@ -52,6 +61,8 @@ use self::{serve_file::serve_file, utils::*};
 
 mod mpsc_to_async_write;
 mod serve_file;
+#[cfg(feature = "ffmpeg")]
+mod transcode;
 mod utils;
 
 use mpsc_to_async_write::MpscToAsyncWrite;
@@ -68,6 +79,13 @@ struct CacheValue {
 
 const MAX_TEXT_READ_LENGTH: usize = 10 * 1024; // 10KB
 
+/// Default and maximum sizes of rendered waveforms, in pixels
+const WAVEFORM_SIZE: (u32, u32) = (512, 128);
+const MAX_WAVEFORM_SIZE: (u32, u32) = (4096, 1024);
+
+/// Width of document pages when none is requested, in pixels
+const DOCUMENT_PAGE_WIDTH: u32 = 1024;
+
 #[derive(Debug, Clone)]
 pub enum ServeFrom {
 	/// Serve from the local filesystem
@@ -88,19 +106,22 @@ pub struct LocalState {
 	// The main advantage of this LRU Cache is for video files. Video files are fetch in multiple chunks and the cache prevents a DB lookup on every chunk reducing the request time from 15-25ms to 1-10ms.
 	// TODO: We should listen to events when deleting or moving a location and evict the cache accordingly.
 	file_metadata_cache: Arc<Cache<CacheKey, CacheValue>>,
+
+	#[cfg(feature = "ffmpeg")]
+	transcode_sessions: transcode::TranscodeSessions,
 }
 
 type ExtractedPath = extract::Path<(String, String, String)>;
 
 async fn request_to_remote_node(
-	p2p: Arc<P2P>,
+	p2p: &P2PManager,
 	identity: RemoteIdentity,
 	mut request: Request<Body>,
 ) -> Response<Body> {
 	let request_upgrade_header = request.headers().get(header::UPGRADE).cloned();
 	let maybe_client_upgrade = request.extensions_mut().remove::<OnUpgrade>();
 
-	let mut response = match operations::remote_rspc(p2p.clone(), identity, request).await {
+	let mut response = match operations::remote_rspc(p2p, identity, request).await {
 		Ok(v) => v,
 		Err(e) => {
 			warn!(%identity, ?e, "Error doing remote rspc query with;");
@@ -230,34 +251,50 @@ pub fn base_router() -> Router<LocalState> {
 				|State(state): State<LocalState>,
 				 extract::Path(path): extract::Path<String>,
 				 request: Request<Body>| async move {
-					let thumbnail_path = state.node.config.data_directory().join("thumbnails");
-					let path = thumbnail_path.join(path);
-
-					// Prevent directory traversal attacks (Eg. requesting `../../../etc/passwd`)
-					// For now we only support `webp` thumbnails.
-					(path.starts_with(&thumbnail_path)
-						&& path.extension() == Some(WEBP_EXTENSION.as_ref()))
-					.then_some(())
-					.ok_or_else(|| not_found(()))?;
-
-					let file = File::open(&path).await.map_err(|e| {
-						InfallibleResponse::builder()
-							.status(if e.kind() == io::ErrorKind::NotFound {
-								StatusCode::NOT_FOUND
-							} else {
-								StatusCode::INTERNAL_SERVER_ERROR
-							})
-							.body(Body::from(""))
-					})?;
-					let metadata = file.metadata().await;
-					serve_file(
-						file,
-						metadata,
-						request.into_parts().0,
-						InfallibleResponse::builder()
-							.header("Content-Type", HeaderValue::from_static("image/webp")),
-					)
-					.await
+					serve_thumbnail(&state, &path, request).await
+				},
+			),
+		)
+		.route(
+			"/thumbstrip/*path",
+			get(
+				|State(state): State<LocalState>,
+				 extract::Path(path): extract::Path<String>,
+				 request: Request<Body>| async move {
+					serve_video_preview(&state, VideoPreviewKind::Thumbstrip, &path, request).await
+				},
+			),
+		)
+		.route(
+			"/preview-clip/*path",
+			get(
+				|State(state): State<LocalState>,
+				 extract::Path(path): extract::Path<String>,
+				 request: Request<Body>| async move {
+					serve_video_preview(&state, VideoPreviewKind::PreviewClip, &path, request).await
+				},
+			),
+		)
+		.route(
+			"/document-page/:lib_id/:cas_id/:page",
+			get(
+				|State(state): State<LocalState>,
+				 extract::Path((library_id, cas_id, page)): extract::Path<(Uuid, String, u16)>,
+				 request: Request<Body>| async move {
+					serve_document_page(&state, library_id, cas_id, page, request).await
+				},
+			),
+		)
+		.route(
+			"/waveform/:lib_id/:object_id",
+			get(
+				|State(state): State<LocalState>,
+				 extract::Path((library_id, object_id)): extract::Path<(
+					Uuid,
+					object::id::Type,
+				)>,
+				 request: Request<Body>| async move {
+					serve_waveform(&state, library_id, object_id, request).await
 				},
 			),
 		)
@@ -385,6 +422,18 @@ pub fn base_router() -> Router<LocalState> {
 				},
 			),
 		)
+		.merge(transcode_router())
+}
+
+#[cfg(feature = "ffmpeg")]
+fn transcode_router() -> Router<LocalState> {
+	transcode::router()
+}
+
+// Transcoding videos is done with FFmpeg, so without it we just don't have the routes
+#[cfg(not(feature = "ffmpeg"))]
+fn transcode_router() -> Router<LocalState> {
+	Router::new()
 }
 
 pub fn with_state(node: Arc<Node>) -> LocalState {
@@ -414,6 +463,8 @@ pub fn with_state(node: Arc<Node>) -> LocalState {
 	});
 
 	LocalState {
+		#[cfg(feature = "ffmpeg")]
+		transcode_sessions: transcode::TranscodeSessions::new(node.config.data_directory()),
 		node,
 		file_metadata_cache,
 	}
@@ -432,16 +483,38 @@ pub fn router(node: Arc<Node>) -> Router<()> {
 						Ok(identity) => identity,
 						Err(e) => {
 							warn!(%identity, ?e, "Error parsing identity;");
-							return (StatusCode::BAD_REQUEST, HeaderMap::new(), vec![])
-								.into_response();
+							return Err(
+								(StatusCode::BAD_REQUEST, HeaderMap::new(), vec![]).into_response()
+							);
 						}
 					};
 
+					// Thumbnails are cached locally so browsing the same directory again doesn't go over the network
+					if let Some((library_id, cas_id)) = parse_remote_thumbnail_path(&rest) {
+						let path = remote_thumbnail(&state.node, identity, library_id, &cas_id)
+							.await
+							.map_err(|e| {
+								warn!(%identity, %library_id, ?e, "Error fetching remote thumbnail;");
+								StatusCode::BAD_GATEWAY.into_response()
+							})?;
+
+						let file = File::open(&path).await.map_err(internal_server_error)?;
+						let metadata = file.metadata().await;
+						return serve_file(
+							file,
+							metadata,
+							request.into_parts().0,
+							InfallibleResponse::builder()
+								.header("Content-Type", HeaderValue::from_static("image/webp")),
+						)
+						.await;
+					}
+
 					*request.uri_mut() = format!("/{rest}")
 						.parse()
 						.expect("url was validated by Axum");
 
-					request_to_remote_node(state.node.p2p.p2p.clone(), identity, request).await
+					Ok(request_to_remote_node(&state.node.p2p, identity, request).await)
 				},
 			),
 		)
@@ -450,6 +523,340 @@ pub fn router(node: Arc<Node>) -> Router<()> {
 		.with_state(with_state(node))
 }
 
+/// Serves a thumbnail requested with its `<library_id | ephemeral>/<shard_hex>/<cas_id>.webp` path.
+///
+/// Other sizes than the default one can be requested with a `size` query parameter, and the format
+/// is negotiated from the `Accept` header. These are generated on their first request.
+async fn serve_thumbnail(
+	state: &LocalState,
+	path: &str,
+	request: Request<Body>,
+) -> Result<Response<Body>, Response<Body>> {
+	let thumbnails_directory = get_thumbnails_directory(state.node.config.data_directory());
+	let default_path = thumbnails_directory.join(path);
+
+	// Prevent directory traversal attacks (Eg. requesting `../../../etc/passwd`)
+	// Thumbnails are always requested as `webp`, even if served in another format.
+	(default_path.starts_with(&thumbnails_directory)
+		&& default_path.extension() == Some(WEBP_EXTENSION.as_ref()))
+	.then_some(())
+	.ok_or_else(|| not_found(()))?;
+
+	let size = query_param(&request, "size")
+		.map_or(Some(ThumbSize::default()), ThumbSize::from_name)
+		.ok_or_else(|| bad_request(()))?;
+
+	let format = ThumbFormat::negotiate(
+		request
+			.headers()
+			.get(header::ACCEPT)
+			.and_then(|accept| accept.to_str().ok()),
+	);
+
+	let path = if size == ThumbSize::default() && format == ThumbFormat::default() {
+		default_path
+	} else {
+		let (kind, cas_id) = parse_thumbnail_path(path).ok_or_else(|| not_found(()))?;
+
+		// Variants are derived from the default thumbnail, so it must exist
+		fs::metadata(&default_path).await.map_err(|e| {
+			if e.kind() == io::ErrorKind::NotFound {
+				not_found(())
+			} else {
+				internal_server_error(e)
+			}
+		})?;
+
+		let original = if size.px() > ThumbSize::default().px() {
+			find_thumbnail_original(state, kind, &cas_id).await
+		} else {
+			None
+		};
+
+		generate_thumbnail_variant(
+			state.node.config.data_directory(),
+			kind,
+			cas_id,
+			size,
+			format,
+			original,
+		)
+		.await
+		.map_err(|e| {
+			error!(?e, "Failed to generate thumbnail variant;");
+			internal_server_error(())
+		})?
+	};
+
+	let file = File::open(&path).await.map_err(|e| {
+		InfallibleResponse::builder()
+			.status(if e.kind() == io::ErrorKind::NotFound {
+				StatusCode::NOT_FOUND
+			} else {
+				StatusCode::INTERNAL_SERVER_ERROR
+			})
+			.body(Body::from(""))
+	})?;
+	let metadata = file.metadata().await;
+	serve_file(
+		file,
+		metadata,
+		request.into_parts().0,
+		InfallibleResponse::builder()
+			.header("Content-Type", HeaderValue::from_static(format.mime_type()))
+			.header("Vary", HeaderValue::from_static("Accept")),
+	)
+	.await
+}
+
+/// Finds a local file with this `cas_id` to render larger thumbnails from, along with its
+/// extension. Ephemeral files aren't in the database, so these get the default size.
+async fn find_thumbnail_original(
+	state: &LocalState,
+	kind: ThumbnailKind,
+	cas_id: &CasId<'_>,
+) -> Option<(PathBuf, String)> {
+	let ThumbnailKind::Indexed(library_id) = kind else {
+		return None;
+	};
+
+	let library = state.node.libraries.get_library(&library_id).await?;
+
+	let file_paths = library
+		.db
+		.file_path()
+		.find_many(vec![
+			file_path::cas_id::equals(cas_id.into()),
+			file_path::is_dir::equals(Some(false)),
+		])
+		.select(file_path_to_handle_custom_uri::select())
+		.exec()
+		.await
+		.map_err(|e| error!(?e, "Failed to fetch file paths for thumbnail;"))
+		.ok()?;
+
+	for file_path in file_paths {
+		let Some(location) = &file_path.location else {
+			continue;
+		};
+		let (Some(location_path), Some(extension)) = (&location.path, &file_path.extension) else {
+			continue;
+		};
+		let Ok(iso_file_path) = IsolatedFilePathData::try_from((location.id, &file_path)) else {
+			continue;
+		};
+
+		// Files from locations on other nodes aren't available here
+		let path = Path::new(location_path).join(iso_file_path);
+		if fs::try_exists(&path).await.unwrap_or(false) {
+			return Some((path, extension.clone()));
+		}
+	}
+
+	None
+}
+
+/// Serves the previews of an indexed video, which the frontend requests with the same
+/// `<library_id>/<shard_hex>/<cas_id>.webp` path used for its thumbnail.
+async fn serve_video_preview(
+	state: &LocalState,
+	kind: VideoPreviewKind,
+	path: &str,
+	request: Request<Body>,
+) -> Result<Response<Body>, Response<Body>> {
+	let (library_id, cas_id) = parse_thumb_key_path(path).ok_or_else(|| not_found(()))?;
+
+	let path = kind.compute_path(
+		get_thumbnails_directory(state.node.config.data_directory()),
+		library_id,
+		&cas_id,
+	);
+
+	let file = File::open(&path).await.map_err(|e| {
+		InfallibleResponse::builder()
+			.status(if e.kind() == io::ErrorKind::NotFound {
+				StatusCode::NOT_FOUND
+			} else {
+				StatusCode::INTERNAL_SERVER_ERROR
+			})
+			.body(Body::from(""))
+	})?;
+	let metadata = file.metadata().await;
+	serve_file(
+		file,
+		metadata,
+		request.into_parts().0,
+		InfallibleResponse::builder()
+			.header("Content-Type", HeaderValue::from_static("image/webp")),
+	)
+	.await
+}
+
+/// Serves the waveform of an audio object as a PNG image, rendered at the size requested with the
+/// `width` and `height` query parameters.
+async fn serve_waveform(
+	state: &LocalState,
+	library_id: Uuid,
+	object_id: object::id::Type,
+	request: Request<Body>,
+) -> Result<Response<Body>, Response<Body>> {
+	let library = state
+		.node
+		.libraries
+		.get_library(&library_id)
+		.await
+		.ok_or_else(|| not_found(()))?;
+
+	let width = size_param(&request, "width", WAVEFORM_SIZE.0, MAX_WAVEFORM_SIZE.0)?;
+	let height = size_param(&request, "height", WAVEFORM_SIZE.1, MAX_WAVEFORM_SIZE.1)?;
+
+	let waveform = library
+		.db
+		.ffmpeg_data()
+		.find_unique(ffmpeg_data::object_id::equals(object_id))
+		.select(ffmpeg_data::select!({ waveform }))
+		.exec()
+		.await
+		.map_err(internal_server_error)?
+		.and_then(|data| data.waveform)
+		// Empty when the waveform couldn't be extracted
+		.filter(|bytes| !bytes.is_empty())
+		.map(|bytes| Waveform::from_bytes(&bytes))
+		.ok_or_else(|| not_found(()))?;
+
+	let png = waveform.to_png(width, height).map_err(|e| {
+		error!(?e, "Failed to render waveform;");
+		internal_server_error(())
+	})?;
+
+	Ok(InfallibleResponse::builder()
+		.header("Content-Type", HeaderValue::from_static("image/png"))
+		.status(StatusCode::OK)
+		.body(Body::from(png)))
+}
+
+/// Serves a page of an indexed PDF document, at the width requested with the `width` query
+/// parameter, rendering it on its first request.
+async fn serve_document_page(
+	state: &LocalState,
+	library_id: Uuid,
+	cas_id: String,
+	page: u16,
+	request: Request<Body>,
+) -> Result<Response<Body>, Response<Body>> {
+	// `cas_id`s are hexadecimal hashes, and we need at least 3 characters for the shard directory
+	(cas_id.len() >= 3 && cas_id.chars().all(|c| c.is_ascii_alphanumeric()))
+		.then_some(())
+		.ok_or_else(|| not_found(()))?;
+	let cas_id = CasId::from(cas_id);
+
+	let width = size_param(
+		&request,
+		"width",
+		DOCUMENT_PAGE_WIDTH,
+		PDF_MAX_PAGE_RENDER_WIDTH,
+	)?;
+
+	let (document_path, _) =
+		find_thumbnail_original(state, ThumbnailKind::Indexed(library_id), &cas_id)
+			.await
+			.filter(|(_, extension)| {
+				DocumentExtension::from_str(extension)
+					.is_ok_and(|ext| ext == DocumentExtension::Pdf)
+			})
+			.ok_or_else(|| not_found(()))?;
+
+	let path = generate_document_page(
+		get_thumbnails_directory(state.node.config.data_directory()),
+		library_id,
+		&cas_id,
+		document_path,
+		page,
+		width,
+	)
+	.await
+	.map_err(|e| match e {
+		NonCriticalThumbnailerError::DocumentPageNotFound(..) => not_found(e),
+		e => {
+			error!(?e, "Failed to render document page;");
+			internal_server_error(())
+		}
+	})?;
+
+	let file = File::open(&path).await.map_err(|e| {
+		InfallibleResponse::builder()
+			.status(if e.kind() == io::ErrorKind::NotFound {
+				StatusCode::NOT_FOUND
+			} else {
+				StatusCode::INTERNAL_SERVER_ERROR
+			})
+			.body(Body::from(""))
+	})?;
+	let metadata = file.metadata().await;
+	serve_file(
+		file,
+		metadata,
+		request.into_parts().0,
+		InfallibleResponse::builder()
+			.header("Content-Type", HeaderValue::from_static("image/webp")),
+	)
+	.await
+}
+
+fn query_param<'request>(request: &'request Request<Body>, name: &str) -> Option<&'request str> {
+	request.uri().query().and_then(|query| {
+		query
+			.split('&')
+			.find_map(|param| param.strip_prefix(name)?.strip_prefix('='))
+	})
+}
+
+/// A size in pixels from the `name` query parameter, which must be between 1 and `max`
+fn size_param(
+	request: &Request<Body>,
+	name: &str,
+	default: u32,
+	max: u32,
+) -> Result<u32, Response<Body>> {
+	query_param(request, name).map_or(Ok(default), |value| {
+		value
+			.parse::<u32>()
+			.ok()
+			.filter(|value| (1..=max).contains(value))
+			.ok_or_else(|| bad_request(()))
+	})
+}
+
+/// Match `uri/thumbnail/<library_id>/<shard_hex>/<cas_id>.webp` which is how the frontend requests the thumbnails of a remote library.
+fn parse_remote_thumbnail_path(path: &str) -> Option<(Uuid, CasId<'static>)> {
+	parse_thumb_key_path(path.strip_prefix("uri/thumbnail/")?)
+}
+
+/// Match `<library_id>/<shard_hex>/<cas_id>.webp`, the path of the thumbnail of an indexed file.
+fn parse_thumb_key_path(path: &str) -> Option<(Uuid, CasId<'static>)> {
+	match parse_thumbnail_path(path)? {
+		(ThumbnailKind::Indexed(library_id), cas_id) => Some((library_id, cas_id)),
+		(ThumbnailKind::Ephemeral, _) => None,
+	}
+}
+
+/// Match `<library_id | ephemeral>/<shard_hex>/<cas_id>.webp`, the path of any thumbnail.
+fn parse_thumbnail_path(path: &str) -> Option<(ThumbnailKind, CasId<'static>)> {
+	let mut parts = path.split('/');
+	let kind = match parts.next()? {
+		EPHEMERAL_DIR => ThumbnailKind::Ephemeral,
+		library_id => ThumbnailKind::Indexed(Uuid::from_str(library_id).ok()?),
+	};
+	let _shard_hex = parts.next()?;
+	let cas_id = parts.next()?.strip_suffix(&format!(".{WEBP_EXTENSION}"))?;
+
+	// `cas_id`s are hexadecimal hashes, and we need at least 3 characters for the shard directory
+	(parts.next().is_none()
+		&& cas_id.len() >= 3
+		&& cas_id.chars().all(|c| c.is_ascii_alphanumeric()))
+	.then(|| (kind, CasId::from(cas_id.to_string())))
+}
+
 // TODO: This should possibly be determined from magic bytes when the file is indexed and stored it in the DB on the file path
 async fn infer_the_mime_type(
 	ext: &str,
@@ -0,0 +1,462 @@
+//! On the fly transcoding for videos that browsers can't play, like HEVC, `ProRes` or MKV files.
+//!
+//! Videos are served as HLS playlists, whose segments are only transcoded when a player asks for
+//! them, so seeking anywhere in the video only costs a single segment. Segments are cached on disk
+//! for as long as someone keeps watching, and idle sessions are cleaned up in the background.
+
+use crate::util::InfallibleResponse;
+
+use sd_ffmpeg::TranscodeQuality;
+use sd_utils::error::FileIOError;
+
+use std::{
+	collections::HashMap,
+	fmt::Write,
+	path::{Path, PathBuf},
+	sync::Arc,
+	time::{Duration, Instant, SystemTime},
+};
+
+use axum::{
+	body::Body,
+	extract::{self, State},
+	http::{HeaderValue, Request, Response, StatusCode},
+	routing::get,
+	Router,
+};
+use tokio::{
+	fs::{self, File},
+	io,
+	sync::Mutex,
+	time::{interval, MissedTickBehavior},
+};
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use super::{
+	get_or_init_lru_entry, serve_file::serve_file, utils::*, CacheValue, LocalState, ServeFrom,
+};
+
+/// Directory, inside the node data directory, where transcoded segments are cached
+const TRANSCODES_DIR_NAME: &str = "transcodes";
+
+const SEGMENT_DURATION: Duration = Duration::from_secs(6);
+
+/// Sessions which nobody requested anything from in this long have their segments deleted
+const IDLE_SESSION_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60);
+
+const PLAYLIST_FILE_NAME: &str = "index.m3u8";
+const SEGMENT_EXTENSION: &str = "ts";
+
+#[derive(Debug, thiserror::Error)]
+pub enum TranscodeError {
+	#[error("video duration is unavailable")]
+	NoDuration,
+	#[error("segment <index={0}> is past the end of the video")]
+	SegmentOutOfRange(u32),
+
+	#[error(transparent)]
+	FFmpeg(#[from] sd_ffmpeg::Error),
+	#[error(transparent)]
+	FileIO(#[from] FileIOError),
+}
+
+/// A file path being watched at some quality
+type SessionKey = (Uuid, Uuid, TranscodeQuality);
+
+struct Session {
+	dir: PathBuf,
+	duration: Duration,
+	/// Size and modification date of the original file, so we know when the cache is stale
+	fingerprint: (u64, Option<SystemTime>),
+	last_access: Instant,
+	/// Avoids transcoding the same segment twice when it is requested again while in progress
+	segment_locks: HashMap<u32, Arc<Mutex<()>>>,
+}
+
+#[derive(Clone)]
+pub struct TranscodeSessions {
+	cache_dir: PathBuf,
+	sessions: Arc<Mutex<HashMap<SessionKey, Session>>>,
+}
+
+impl TranscodeSessions {
+	pub fn new(data_directory: impl AsRef<Path>) -> Self {
+		let this = Self {
+			cache_dir: data_directory.as_ref().join(TRANSCODES_DIR_NAME),
+			sessions: Arc::default(),
+		};
+
+		tokio::spawn({
+			let this = this.clone();
+			async move {
+				let mut interval = interval(CLEANUP_INTERVAL);
+				interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+				loop {
+					interval.tick().await;
+					this.remove_idle_sessions().await;
+				}
+			}
+		});
+
+		this
+	}
+
+	async fn playlist(
+		&self,
+		key: SessionKey,
+		source_path: &Path,
+	) -> Result<String, TranscodeError> {
+		let (_, duration, _) = self.touch_session(key, source_path, None).await?;
+
+		Ok(hls_playlist(duration))
+	}
+
+	/// Gets the path of a transcoded segment, transcoding it if needed, and starts transcoding the
+	/// next one in background, as the player will ask for it in a few seconds
+	async fn segment(
+		&self,
+		key: SessionKey,
+		source_path: &Path,
+		index: u32,
+	) -> Result<PathBuf, TranscodeError> {
+		let segment_path = self.ensure_segment(key, source_path, index).await?;
+
+		tokio::spawn({
+			let this = self.clone();
+			let source_path = source_path.to_path_buf();
+			async move {
+				match this.ensure_segment(key, &source_path, index + 1).await {
+					Ok(_) | Err(TranscodeError::SegmentOutOfRange(_)) => {}
+					Err(e) => warn!(
+						source_path = %source_path.display(),
+						next_index = index + 1,
+						?e,
+						"Failed to transcode the next segment ahead of time;",
+					),
+				}
+			}
+		});
+
+		Ok(segment_path)
+	}
+
+	async fn ensure_segment(
+		&self,
+		key: SessionKey,
+		source_path: &Path,
+		index: u32,
+	) -> Result<PathBuf, TranscodeError> {
+		let (dir, duration, lock) = self.touch_session(key, source_path, Some(index)).await?;
+		let lock = lock.ok_or(TranscodeError::SegmentOutOfRange(index))?;
+
+		let segment_path = dir.join(format!("{index}.{SEGMENT_EXTENSION}"));
+
+		let _guard = lock.lock().await;
+
+		if fs::try_exists(&segment_path).await.unwrap_or(false) {
+			return Ok(segment_path);
+		}
+
+		let start = SEGMENT_DURATION * index;
+		sd_ffmpeg::to_transcoded_segment(
+			source_path,
+			&segment_path,
+			start,
+			SEGMENT_DURATION.min(duration.saturating_sub(start)),
+			key.2,
+		)
+		.await?;
+
+		Ok(segment_path)
+	}
+
+	/// Updates the last access of a session, creating it if it doesn't exist or if the original
+	/// file changed, returning its cache directory, the video duration and the lock of a segment
+	async fn touch_session(
+		&self,
+		key: SessionKey,
+		source_path: &Path,
+		segment_index: Option<u32>,
+	) -> Result<(PathBuf, Duration, Option<Arc<Mutex<()>>>), TranscodeError> {
+		let metadata = fs::metadata(source_path)
+			.await
+			.map_err(|e| FileIOError::from((source_path, e)))?;
+		let fingerprint = (metadata.len(), metadata.modified().ok());
+
+		if let Some(touched) = self
+			.sessions
+			.lock()
+			.await
+			.get_mut(&key)
+			.filter(|session| session.fingerprint == fingerprint)
+			.map(|session| session.touch(segment_index))
+		{
+			return Ok(touched);
+		}
+
+		// Probing without holding the lock, as it means reading the file
+		let duration = sd_ffmpeg::probe(source_path)
+			.await?
+			.duration
+			.and_then(|micros| u64::try_from(micros).ok())
+			.map(Duration::from_micros)
+			.ok_or(TranscodeError::NoDuration)?;
+
+		let mut sessions = self.sessions.lock().await;
+
+		// Someone else may have created the session while we were probing
+		if let Some(session) = sessions
+			.get_mut(&key)
+			.filter(|session| session.fingerprint == fingerprint)
+		{
+			return Ok(session.touch(segment_index));
+		}
+
+		let (library_id, file_path_pub_id, quality) = key;
+		let dir = self
+			.cache_dir
+			.join(format!("{library_id}-{file_path_pub_id}-{quality}"));
+
+		// Segments left by a previous run, or from before the file was modified
+		remove_dir(&dir).await?;
+
+		sessions.remove(&key);
+
+		Ok(sessions
+			.entry(key)
+			.or_insert(Session {
+				dir,
+				duration,
+				fingerprint,
+				last_access: Instant::now(),
+				segment_locks: HashMap::new(),
+			})
+			.touch(segment_index))
+	}
+
+	async fn remove_idle_sessions(&self) {
+		let (idle_dirs, active_dirs) = {
+			let mut sessions = self.sessions.lock().await;
+
+			let mut idle_dirs = vec![];
+			sessions.retain(|_, session| {
+				let is_active = session.last_access.elapsed() < IDLE_SESSION_TIMEOUT;
+				if !is_active {
+					idle_dirs.push(session.dir.clone());
+				}
+				is_active
+			});
+
+			(
+				idle_dirs,
+				sessions
+					.values()
+					.map(|session| session.dir.clone())
+					.collect::<Vec<_>>(),
+			)
+		};
+
+		for dir in idle_dirs {
+			if let Err(e) = remove_dir(&dir).await {
+				error!(?e, "Failed to remove idle transcoding session;");
+			}
+		}
+
+		// Segments left by a previous run, when the node was closed with sessions still active
+		let mut read_dir = match fs::read_dir(&self.cache_dir).await {
+			Ok(read_dir) => read_dir,
+			Err(e) if e.kind() == io::ErrorKind::NotFound => return,
+			Err(e) => {
+				error!(?e, "Failed to read transcodes directory;");
+				return;
+			}
+		};
+
+		while let Ok(Some(entry)) = read_dir.next_entry().await {
+			let dir = entry.path();
+			let is_stale = entry
+				.metadata()
+				.await
+				.and_then(|metadata| metadata.modified())
+				.ok()
+				.and_then(|modified| modified.elapsed().ok())
+				.map_or(true, |elapsed| elapsed > IDLE_SESSION_TIMEOUT);
+
+			if is_stale && !active_dirs.contains(&dir) {
+				if let Err(e) = remove_dir(&dir).await {
+					error!(?e, "Failed to remove stale transcoding session;");
+				}
+			}
+		}
+	}
+}
+
+impl Session {
+	fn touch(&mut self, segment_index: Option<u32>) -> (PathBuf, Duration, Option<Arc<Mutex<()>>>) {
+		self.last_access = Instant::now();
+
+		let lock = segment_index
+			.filter(|index| *index < segments_count(self.duration))
+			.map(|index| Arc::clone(self.segment_locks.entry(index).or_default()));
+
+		(self.dir.clone(), self.duration, lock)
+	}
+}
+
+async fn remove_dir(dir: &Path) -> Result<(), FileIOError> {
+	match fs::remove_dir_all(dir).await {
+		Ok(()) => Ok(()),
+		Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+		Err(e) => Err(FileIOError::from((dir, e))),
+	}
+}
+
+fn segments_count(duration: Duration) -> u32 {
+	u32::try_from(duration.as_millis().div_ceil(SEGMENT_DURATION.as_millis())).unwrap_or(u32::MAX)
+}
+
+/// Video on demand playlist, with segments addressed relatively to the playlist itself
+fn hls_playlist(duration: Duration) -> String {
+	let mut playlist = format!(
+		"#EXTM3U\n\
+		#EXT-X-VERSION:3\n\
+		#EXT-X-PLAYLIST-TYPE:VOD\n\
+		#EXT-X-TARGETDURATION:{}\n\
+		#EXT-X-MEDIA-SEQUENCE:0\n",
+		SEGMENT_DURATION.as_secs()
+	);
+
+	for index in 0..segments_count(duration) {
+		let segment_duration =
+			SEGMENT_DURATION.min(duration.saturating_sub(SEGMENT_DURATION * index));
+
+		// Writing to a `String` never fails
+		let _ = write!(
+			playlist,
+			"#EXTINF:{:.3},\n{index}.{SEGMENT_EXTENSION}\n",
+			segment_duration.as_secs_f64()
+		);
+	}
+
+	playlist.push_str("#EXT-X-ENDLIST\n");
+
+	playlist
+}
+
+/// Routes to watch a video transcoded at one of the [`TranscodeQuality`] presets, players must
+/// load `/transcode/<library_id>/<location_id>/<file_path_id>/<quality>/index.m3u8`
+pub(super) fn router() -> Router<LocalState> {
+	Router::new().route(
+		"/transcode/:lib_id/:loc_id/:path_id/:quality/:file",
+		get(
+			|State(state): State<LocalState>,
+			 extract::Path((lib_id, loc_id, path_id, quality, file)): extract::Path<(
+				String,
+				String,
+				String,
+				String,
+				String,
+			)>,
+			 request: Request<Body>| async move {
+				let quality = quality.parse::<TranscodeQuality>().map_err(bad_request)?;
+
+				let (
+					CacheValue {
+						name: source_path,
+						file_path_pub_id,
+						serve_from,
+						..
+					},
+					library,
+				) = get_or_init_lru_entry(&state, extract::Path((lib_id, loc_id, path_id))).await?;
+
+				// Only the node that has the file is able to transcode it
+				let ServeFrom::Local = serve_from else {
+					return Err(not_implemented(()));
+				};
+
+				let key = (library.id, file_path_pub_id, quality);
+
+				if file == PLAYLIST_FILE_NAME {
+					let playlist = state
+						.transcode_sessions
+						.playlist(key, &source_path)
+						.await
+						.map_err(internal_server_error)?;
+
+					return Ok(InfallibleResponse::builder()
+						.header(
+							"Content-Type",
+							HeaderValue::from_static("application/vnd.apple.mpegurl"),
+						)
+						.status(StatusCode::OK)
+						.body(Body::from(playlist)));
+				}
+
+				let index = file
+					.strip_suffix(&format!(".{SEGMENT_EXTENSION}"))
+					.and_then(|index| index.parse::<u32>().ok())
+					.ok_or_else(|| not_found(()))?;
+
+				let segment_path = state
+					.transcode_sessions
+					.segment(key, &source_path, index)
+					.await
+					.map_err(|e| match e {
+						TranscodeError::SegmentOutOfRange(_) => not_found(e),
+						e => internal_server_error(e),
+					})?;
+
+				serve_segment(&segment_path, request).await
+			},
+		),
+	)
+}
+
+async fn serve_segment(
+	segment_path: &Path,
+	request: Request<Body>,
+) -> Result<Response<Body>, Response<Body>> {
+	let file = File::open(segment_path)
+		.await
+		.map_err(internal_server_error)?;
+	let metadata = file.metadata().await;
+
+	serve_file(
+		file,
+		metadata,
+		request.into_parts().0,
+		InfallibleResponse::builder()
+			.header("Content-Type", HeaderValue::from_static("video/mp2t")),
+	)
+	.await
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn playlist_covers_the_whole_video() {
+		let playlist = hls_playlist(Duration::from_millis(14_500));
+
+		assert!(playlist.starts_with("#EXTM3U\n"));
+		assert!(playlist.contains("#EXTINF:6.000,\n0.ts\n#EXTINF:6.000,\n1.ts\n"));
+		assert!(playlist.contains("#EXTINF:2.500,\n2.ts\n"));
+		assert!(!playlist.contains("3.ts"));
+		assert!(playlist.ends_with("#EXT-X-ENDLIST\n"));
+	}
+
+	#[test]
+	fn partial_segments_are_counted() {
+		assert_eq!(segments_count(Duration::ZERO), 0);
+		assert_eq!(segments_count(SEGMENT_DURATION), 1);
+		assert_eq!(
+			segments_count(SEGMENT_DURATION + Duration::from_millis(1)),
+			2
+		);
+	}
+}
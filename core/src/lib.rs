@@ -6,12 +6,12 @@ use crate::{
 	location::LocationManagerError,
 };
 
-use sd_core_heavy_lifting::{media_processor::ThumbnailKind, JobSystem};
+use sd_core_heavy_lifting::{
+	media_processor::{perceptual_hash, ThumbnailKind},
+	JobSystem,
+};
 use sd_core_prisma_helpers::CasId;
 
-#[cfg(feature = "ai")]
-use sd_ai::old_image_labeler::{DownloadModelError, OldImageLabeler, YoloV8};
-
 use sd_task_system::TaskSystem;
 use sd_utils::error::FileIOError;
 use volume::save_storage_statistics;
@@ -26,7 +26,7 @@ use chrono::{DateTime, Utc};
 use futures_concurrency::future::Join;
 use reqwest::{RequestBuilder, Response};
 use thiserror::Error;
-use tokio::{fs, io, sync::broadcast};
+use tokio::{fs, io, spawn, sync::broadcast};
 use tracing::{error, info, warn};
 use tracing_appender::{
 	non_blocking::{NonBlocking, WorkerGuard},
@@ -78,8 +78,10 @@ pub struct Node {
 	pub http: reqwest::Client,
 	pub task_system: TaskSystem<sd_core_heavy_lifting::Error>,
 	pub job_system: JobSystem<NodeContext, JobContext<NodeContext>>,
+	pub indexer_throttles: Arc<node::IndexerThrottles>,
+	pub reverse_geocoder: node::ReverseGeocoderDataset,
 	#[cfg(feature = "ai")]
-	pub old_image_labeller: Option<OldImageLabeler>,
+	pub image_labeler: node::ImageLabelerModel,
 }
 
 impl fmt::Debug for Node {
@@ -117,16 +119,14 @@ impl Node {
 		}
 
 		#[cfg(feature = "ai")]
-		let image_labeler_version = {
-			sd_ai::init()?;
-			config.get().await.image_labeler_version
-		};
+		sd_ai::init()?;
 
 		let (locations, locations_actor) = location::Locations::new();
 		let (old_jobs, jobs_actor) = old_job::OldJobs::new();
 		let libraries = library::Libraries::new(data_dir.join("libraries")).await?;
 
 		let task_system = TaskSystem::new();
+		let indexer_throttles = node::IndexerThrottles::new(config.preferences_watcher());
 
 		let (p2p, start_p2p) = p2p::P2PManager::new(config.clone(), libraries.clone())
 			.await
@@ -135,6 +135,7 @@ impl Node {
 			data_dir: data_dir.to_path_buf(),
 			job_system: JobSystem::new(task_system.get_dispatcher(), data_dir),
 			task_system,
+			indexer_throttles,
 			old_jobs,
 			locations,
 			notifications: notifications::Notifications::new(),
@@ -147,19 +148,9 @@ impl Node {
 			)),
 			http: reqwest::Client::new(),
 			env,
+			reverse_geocoder: node::ReverseGeocoderDataset::default(),
 			#[cfg(feature = "ai")]
-			old_image_labeller: OldImageLabeler::new(
-				YoloV8::model(image_labeler_version)?,
-				data_dir,
-			)
-			.await
-			.map_err(|e| {
-				error!(
-					?e,
-					"Failed to initialize image labeller. AI features will be disabled;"
-				);
-			})
-			.ok(),
+			image_labeler: node::ImageLabelerModel::default(),
 		});
 
 		// Restore backend feature flags
@@ -180,6 +171,63 @@ impl Node {
 		node.libraries.init(&node).await?;
 		jobs_actor.start(node.clone());
 
+		// Errors are already logged, places just aren't named without a dataset
+		if let Ok(reverse_geocoder) = node
+			.reverse_geocoder
+			.load(
+				node.config
+					.get()
+					.await
+					.reverse_geocoder_dataset_path
+					.as_deref(),
+			)
+			.await
+		{
+			let node = Arc::clone(&node);
+			spawn(async move {
+				node::resolve_missing_places(&reverse_geocoder, &node.libraries).await;
+			});
+		}
+
+		// Thumbnails generated before perceptual hashes were computed don't have one yet
+		{
+			let node = Arc::clone(&node);
+			spawn(async move {
+				for library in node.libraries.get_all().await {
+					match perceptual_hash::hash_missing(
+						&node.config.data_directory(),
+						library.id,
+						&library.db,
+					)
+					.await
+					{
+						Ok(0) => {}
+						Ok(hashed) => {
+							info!(library_id = %library.id, %hashed, "Hashed existing thumbnails;");
+						}
+						Err(e) => {
+							error!(library_id = %library.id, ?e, "Failed to hash existing thumbnails;");
+						}
+					}
+				}
+			});
+		}
+
+		// The labeler must be loaded before resuming jobs, otherwise their labeling tasks are dropped
+		#[cfg(feature = "ai")]
+		{
+			let config = node.config.get().await;
+			// Errors are already logged, labeling just stays disabled
+			let _ = node
+				.image_labeler
+				.load(
+					config.image_labeler_version.as_deref(),
+					config.image_labeler_model_path.as_deref(),
+					data_dir,
+				)
+				.await;
+		}
+
 		node.job_system
 			.init(
 				&node
@@ -320,10 +368,6 @@ impl Node {
 			.join()
 			.await;
 
-		#[cfg(feature = "ai")]
-		if let Some(image_labeller) = &self.old_image_labeller {
-			image_labeller.shutdown().await;
-		}
 		info!("Spacedrive Core shutdown successful!");
 	}
 
@@ -443,7 +487,4 @@ pub enum NodeError {
 	#[cfg(feature = "ai")]
 	#[error("ai error: {0}")]
 	AI(#[from] sd_ai::Error),
-	#[cfg(feature = "ai")]
-	#[error("Failed to download model: {0}")]
-	DownloadModel(#[from] DownloadModelError),
 }
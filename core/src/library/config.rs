@@ -3,8 +3,13 @@ use crate::{
 	util::version_manager::{Kind, ManagedVersion, VersionManager, VersionManagerError},
 };
 
+use sd_core_heavy_lifting::media_processor::ffmpeg_media_data;
+use sd_core_prisma_helpers::object_with_media_data;
+
 use sd_p2p::{Identity, RemoteIdentity};
-use sd_prisma::prisma::{file_path, indexer_rule, instance, location, node, PrismaClient};
+use sd_prisma::prisma::{
+	ffmpeg_data, file_path, indexer_rule, instance, location, node, object, PrismaClient,
+};
 use sd_utils::{db::maybe_missing, error::FileIOError};
 
 use std::{
@@ -72,10 +77,11 @@ pub enum LibraryConfigVersion {
 	V9 = 9,
 	V10 = 10,
 	V11 = 11,
+	V12 = 12,
 }
 
 impl ManagedVersion<LibraryConfigVersion> for LibraryConfig {
-	const LATEST_VERSION: LibraryConfigVersion = LibraryConfigVersion::V11;
+	const LATEST_VERSION: LibraryConfigVersion = LibraryConfigVersion::V12;
 
 	const KIND: Kind = Kind::Json("version");
 
@@ -464,6 +470,39 @@ impl LibraryConfig {
 							.await?;
 					}
 
+					// Copies the technical metadata of videos and audio to their new search columns
+					(LibraryConfigVersion::V11, LibraryConfigVersion::V12) => loop {
+						let objects = db
+							.object()
+							.find_many(vec![object::ffmpeg_data::is(vec![
+								ffmpeg_data::has_subtitles::equals(None),
+							])])
+							.take(100)
+							.include(object_with_media_data::include())
+							.exec()
+							.await?;
+
+						if objects.is_empty() {
+							break;
+						}
+
+						db._batch(
+							objects
+								.into_iter()
+								.filter_map(|object| object.ffmpeg_data)
+								.map(|data| {
+									db.ffmpeg_data().update(
+										ffmpeg_data::id::equals(data.id),
+										ffmpeg_media_data::search_params(
+											&ffmpeg_media_data::from_prisma_data(data),
+										),
+									)
+								})
+								.collect::<Vec<_>>(),
+						)
+						.await?;
+					},
+
 					_ => {
 						error!(current_version = ?current, "Library config version is not handled;");
 
@@ -1,6 +1,7 @@
 use crate::{api::utils::get_size, invalidate_query, library::Library, volume::get_volumes, Node};
 
-use sd_prisma::prisma::{statistics, storage_statistics};
+use sd_core_file_path_helper::{InodeKey, UniqueInodesSize};
+use sd_prisma::prisma::{file_path, statistics, storage_statistics, SortOrder};
 use sd_utils::db::size_in_bytes_from_db;
 
 use chrono::Utc;
@@ -8,6 +9,9 @@ use tracing::{error, info};
 
 use super::LibraryManagerError;
 
+/// Number of file paths fetched at a time while summing the library size
+const TOTAL_BYTES_BATCH_SIZE: i64 = 10_000;
+
 pub async fn update_library_statistics(
 	node: &Node,
 	library: &Library,
@@ -52,24 +56,7 @@ pub async fn update_library_statistics(
 	.await
 	.unwrap_or(0);
 
-	let total_library_bytes = library
-		.db
-		.location()
-		.find_many(vec![])
-		.exec()
-		.await
-		.unwrap_or_else(|e| {
-			error!(?e, "Failed to get locations;");
-			vec![]
-		})
-		.into_iter()
-		.map(|location| {
-			location
-				.size_in_bytes
-				.map(|size| size_in_bytes_from_db(&size))
-				.unwrap_or(0)
-		})
-		.sum::<u64>();
+	let total_library_bytes = total_library_bytes(library).await;
 
 	let thumbnail_folder_size = get_size(node.config.data_directory().join("thumbnails"))
 		.await
@@ -106,3 +93,52 @@ pub async fn update_library_statistics(
 
 	Ok(stats)
 }
+
+/// Sum of the sizes of every indexed file, counting each inode only once even when its hard links
+/// live in many directories or locations
+async fn total_library_bytes(library: &Library) -> u64 {
+	let mut total = UniqueInodesSize::default();
+	let mut cursor = 0;
+
+	loop {
+		let file_paths = match library
+			.db
+			.file_path()
+			.find_many(vec![
+				file_path::id::gt(cursor),
+				file_path::is_dir::equals(Some(false)),
+			])
+			.order_by(file_path::id::order(SortOrder::Asc))
+			.take(TOTAL_BYTES_BATCH_SIZE)
+			.select(file_path::select!({ id inode device nlink size_in_bytes_bytes }))
+			.exec()
+			.await
+		{
+			Ok(file_paths) => file_paths,
+			Err(e) => {
+				error!(?e, "Failed to get file paths to sum library size;");
+				break;
+			}
+		};
+
+		let Some(last) = file_paths.last() else {
+			break;
+		};
+		cursor = last.id;
+
+		for file_path in file_paths {
+			if let Some(size_in_bytes_bytes) = file_path.size_in_bytes_bytes {
+				total.add(
+					InodeKey::from_db(
+						file_path.device.as_deref(),
+						file_path.inode.as_deref(),
+						file_path.nlink,
+					),
+					size_in_bytes_from_db(&size_in_bytes_bytes),
+				);
+			}
+		}
+	}
+
+	total.total()
+}
@@ -30,6 +30,9 @@ use uuid::Uuid;
 mod runner;
 mod watcher;
 
+#[cfg(target_os = "linux")]
+pub(super) use watcher::remove_watcher_state;
+
 #[derive(Clone, Copy, Debug)]
 enum ManagementMessageAction {
 	Add,
@@ -5,28 +5,43 @@
 //! other. If we have dangling Rename From events, we have to remove them after some time.
 //! Aside from that, when a directory is moved to our watched location from the outside, we receive
 //! a Create Dir event, this one is actually ok at least.
+//! We also persist the paths with events waiting to be handled, so on the next startup we can
+//! handle them and run an incremental scan to catch the changes made while Spacedrive was closed.
+
+use crate::{
+	invalidate_query,
+	library::Library,
+	location::{manager::LocationManagerError, reconcile_location},
+	Node,
+};
 
-use crate::{invalidate_query, library::Library, location::manager::LocationManagerError, Node};
+use sd_core_heavy_lifting::indexer::DirectorySnapshots;
+use sd_core_prisma_helpers::location_with_indexer_rules;
 
 use sd_prisma::prisma::location;
-use sd_utils::error::FileIOError;
+use sd_utils::{error::FileIOError, from_bytes_to_uuid};
 
 use std::{
-	collections::{BTreeMap, HashMap},
+	collections::{BTreeMap, HashMap, HashSet},
 	path::{Path, PathBuf},
 	sync::Arc,
 };
 
+use chrono::Utc;
 use notify::{
 	event::{CreateKind, DataChange, ModifyKind, RenameMode},
 	Event, EventKind,
 };
 use tokio::{fs, time::Instant};
-use tracing::{error, instrument, trace};
+use tracing::{debug, error, instrument, trace};
 
 use super::{
-	utils::{create_dir, recalculate_directories_size, remove, rename, update_file},
-	HUNDRED_MILLIS, ONE_SECOND,
+	state::WatcherState,
+	utils::{
+		create_dir, recalculate_directories_size, remove, rename, update_file,
+		update_files_modified_since,
+	},
+	HUNDRED_MILLIS, ONE_SECOND, THIRTY_SECONDS,
 };
 
 #[derive(Debug)]
@@ -42,6 +57,13 @@ pub(super) struct EventHandler {
 	to_recalculate_size: HashMap<PathBuf, Instant>,
 
 	path_and_instant_buffer: Vec<(PathBuf, Instant)>,
+
+	// Persisted state, only known after we reconcile the changes made while the location
+	// wasn't being watched
+	state_path: Option<PathBuf>,
+	last_reconciliation_attempt: Option<Instant>,
+	last_state_save: Instant,
+	saved_pending_paths: HashSet<PathBuf>,
 }
 
 impl super::EventHandler for EventHandler {
@@ -57,6 +79,10 @@ impl super::EventHandler for EventHandler {
 			reincident_to_update_files: HashMap::new(),
 			to_recalculate_size: HashMap::new(),
 			path_and_instant_buffer: Vec::new(),
+			state_path: None,
+			last_reconciliation_attempt: None,
+			last_state_save: Instant::now(),
+			saved_pending_paths: HashSet::new(),
 		}
 	}
 
@@ -168,6 +194,10 @@ impl super::EventHandler for EventHandler {
 
 	async fn tick(&mut self) {
 		if self.last_events_eviction_check.elapsed() > HUNDRED_MILLIS {
+			if self.state_path.is_none() {
+				self.try_reconcile().await;
+			}
+
 			if let Err(e) = self.handle_to_update_eviction().await {
 				error!(
 					?e,
@@ -195,12 +225,141 @@ impl super::EventHandler for EventHandler {
 				}
 			}
 
+			self.save_state().await;
+
 			self.last_events_eviction_check = Instant::now();
 		}
 	}
 }
 
 impl EventHandler {
+	async fn try_reconcile(&mut self) {
+		if self
+			.last_reconciliation_attempt
+			.is_some_and(|instant| instant.elapsed() < THIRTY_SECONDS)
+		{
+			return;
+		}
+
+		self.last_reconciliation_attempt = Some(Instant::now());
+
+		match self.reconcile().await {
+			Ok(maybe_state_path) => self.state_path = maybe_state_path,
+			Err(e) => error!(
+				?e,
+				"Failed to reconcile changes made while the location wasn't watched;"
+			),
+		}
+	}
+
+	/// Returns the path to persist this watcher state, or `None` if the location is offline and we
+	/// have to try again later
+	async fn reconcile(&self) -> Result<Option<PathBuf>, LocationManagerError> {
+		let location = self
+			.library
+			.db
+			.location()
+			.find_unique(location::id::equals(self.location_id))
+			.include(location_with_indexer_rules::include())
+			.exec()
+			.await?
+			.ok_or(LocationManagerError::LocationNotFound(self.location_id))?;
+
+		if !self
+			.node
+			.locations
+			.is_online(&from_bytes_to_uuid(&location.pub_id))
+			.await
+		{
+			return Ok(None);
+		}
+
+		let state_path = WatcherState::path(&self.node.data_dir, self.library.id, &location.pub_id);
+
+		let Some(WatcherState {
+			last_seen_at,
+			pending_paths,
+		}) = WatcherState::load(&state_path).await
+		else {
+			trace!("Location was never watched before, nothing to reconcile");
+			return Ok(Some(state_path));
+		};
+
+		debug!(
+			%last_seen_at,
+			pending_paths_count = pending_paths.len(),
+			"Reconciling changes made while the location wasn't watched;",
+		);
+
+		for path in &pending_paths {
+			let res = match fs::metadata(&path).await {
+				// Directories are handled by the incremental scan below
+				Ok(metadata) if metadata.is_dir() => Ok(()),
+				Ok(_) => update_file(self.location_id, path, &self.node, &self.library).await,
+				Err(_) => remove(self.location_id, path, &self.library).await,
+			};
+
+			if let Err(e) = res {
+				error!(?e, path = %path.display(), "Failed to reconcile pending path;");
+			}
+		}
+
+		update_files_modified_since(
+			self.location_id,
+			last_seen_at,
+			&pending_paths,
+			&self.node,
+			&self.library,
+		)
+		.await?;
+
+		invalidate_query!(self.library, "search.paths");
+
+		// Without snapshots, an incremental scan would walk through the whole location
+		if DirectorySnapshots::exist(&self.node.data_dir, self.library.id, &location.pub_id).await {
+			reconcile_location(&self.node, &self.library, location).await?;
+		}
+
+		Ok(Some(state_path))
+	}
+
+	async fn save_state(&mut self) {
+		let Some(state_path) = &self.state_path else {
+			return;
+		};
+
+		if self.last_state_save.elapsed() < ONE_SECOND {
+			return;
+		}
+
+		let pending_paths = self
+			.files_to_update
+			.keys()
+			.chain(self.reincident_to_update_files.keys())
+			.chain(self.rename_from.keys())
+			.cloned()
+			.collect::<HashSet<_>>();
+
+		// We still save from time to time without new pending paths, to update `last_seen_at`
+		if pending_paths == self.saved_pending_paths
+			&& self.last_state_save.elapsed() < THIRTY_SECONDS
+		{
+			return;
+		}
+
+		let state = WatcherState {
+			last_seen_at: Utc::now(),
+			pending_paths,
+		};
+
+		if let Err(e) = state.save(state_path).await {
+			error!(?e, "Failed to save watcher state;");
+		}
+
+		self.saved_pending_paths = state.pending_paths;
+		self.last_state_save = Instant::now();
+	}
+
 	async fn handle_to_update_eviction(&mut self) -> Result<(), LocationManagerError> {
 		self.path_and_instant_buffer.clear();
 
@@ -45,8 +45,13 @@ mod ios;
 #[cfg(target_os = "android")]
 mod android;
 
+#[cfg(target_os = "linux")]
+mod state;
 mod utils;
 
+#[cfg(target_os = "linux")]
+pub use state::remove_watcher_state;
+
 use utils::reject_event;
 
 #[cfg(target_os = "linux")]
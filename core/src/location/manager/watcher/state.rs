@@ -0,0 +1,103 @@
+//! Last known state of a location watcher, persisted in the node data directory. When the location
+//! watcher starts, it finds the changes made while the location wasn't being watched: events that
+//! were received but not yet applied to the database, and changed directories, that are found by
+//! an incremental scan of the location.
+
+use sd_utils::{error::FileIOError, from_bytes_to_uuid};
+
+use std::{
+	collections::HashSet,
+	io::ErrorKind,
+	path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+use tracing::warn;
+use uuid::Uuid;
+
+const WATCHER_STATE_DIR_NAME: &str = "watcher_state";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct WatcherState {
+	/// Last time the watcher was known to be running
+	pub(super) last_seen_at: DateTime<Utc>,
+	/// Paths with events waiting to be applied to the database
+	pub(super) pending_paths: HashSet<PathBuf>,
+}
+
+impl WatcherState {
+	pub(super) fn path(
+		data_directory: impl AsRef<Path>,
+		library_id: Uuid,
+		location_pub_id: &[u8],
+	) -> PathBuf {
+		data_directory
+			.as_ref()
+			.join(WATCHER_STATE_DIR_NAME)
+			.join(library_id.to_string())
+			.join(from_bytes_to_uuid(location_pub_id).to_string())
+	}
+
+	/// Returns `None` if the location was never watched before
+	pub(super) async fn load(path: impl AsRef<Path> + Send) -> Option<Self> {
+		let path = path.as_ref();
+
+		match fs::read(path).await {
+			Ok(bytes) => rmp_serde::from_slice(&bytes)
+				.map_err(|e| warn!(?e, path = %path.display(), "Failed to decode watcher state;"))
+				.ok(),
+			Err(e) if e.kind() == ErrorKind::NotFound => None,
+			Err(e) => {
+				warn!(?e, path = %path.display(), "Failed to read watcher state;");
+				None
+			}
+		}
+	}
+
+	pub(super) async fn save(&self, path: impl AsRef<Path> + Send) -> Result<(), FileIOError> {
+		let path = path.as_ref();
+
+		if let Some(parent) = path.parent() {
+			fs::create_dir_all(parent).await.map_err(|e| {
+				FileIOError::from((parent, e, "Failed to create watcher state directory"))
+			})?;
+		}
+
+		let bytes = rmp_serde::to_vec_named(self).map_err(|e| {
+			FileIOError::from((
+				path,
+				std::io::Error::new(ErrorKind::InvalidData, e),
+				"Failed to encode watcher state",
+			))
+		})?;
+
+		// Writing to a temporary file first, so a crash while saving doesn't leave a broken file
+		let tmp_path = path.with_extension("tmp");
+		fs::write(&tmp_path, bytes)
+			.await
+			.map_err(|e| FileIOError::from((&tmp_path, e, "Failed to write watcher state")))?;
+		fs::rename(&tmp_path, path)
+			.await
+			.map_err(|e| FileIOError::from((path, e, "Failed to replace watcher state")))
+	}
+}
+
+pub async fn remove_watcher_state(
+	data_directory: impl AsRef<Path> + Send,
+	library_id: Uuid,
+	location_pub_id: &[u8],
+) -> Result<(), FileIOError> {
+	let path = WatcherState::path(data_directory, library_id, location_pub_id);
+
+	match fs::remove_file(&path).await {
+		Ok(()) => Ok(()),
+		Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+		Err(e) => Err(FileIOError::from((
+			&path,
+			e,
+			"Failed to remove watcher state",
+		))),
+	}
+}
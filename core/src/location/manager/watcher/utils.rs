@@ -3,8 +3,9 @@ use crate::{
 	library::Library,
 	location::{
 		create_file_path, delete_directory, find_location,
-		indexer::reverse_update_directories_sizes, location_with_indexer_rules,
-		manager::LocationManagerError, scan_location_sub_path, update_location_size,
+		indexer::reverse_update_directories_sizes, light_scan_location,
+		location_with_indexer_rules, manager::LocationManagerError, scan_location_sub_path,
+		update_location_size,
 	},
 	object::validation::hash::file_checksum,
 	Node,
@@ -12,29 +13,36 @@ use crate::{
 
 use sd_core_file_path_helper::{
 	check_file_path_exists, filter_existing_file_path_params,
+	hard_link::{device_and_nlink, nlink_to_db},
 	isolated_file_path_data::extract_normalized_materialized_path_str,
-	loose_find_existing_file_path_params, path_is_hidden, FilePathError, FilePathMetadata,
-	IsolatedFilePathData, MetadataExt,
+	loose_find_existing_file_path_params, path_is_hidden,
+	posix::{
+		indexed_xattrs_from_db, posix_metadata_to_db, read_xattrs, xattrs_to_db, DBPosixMetadata,
+	},
+	FilePathError, FilePathMetadata, IsolatedFilePathData, MetadataExt, PosixMetadata,
 };
 use sd_core_heavy_lifting::{
 	file_identifier::FileMetadata,
+	indexer::{resolve_symlink, SymlinkPolicy, SymlinkResolution},
 	media_processor::{
 		exif_media_data, ffmpeg_media_data, generate_single_thumbnail, get_thumbnails_directory,
 		ThumbnailKind,
 	},
 };
 use sd_core_indexer_rules::{
-	seed::{GitIgnoreRules, GITIGNORE},
+	seed::{GitIgnoreRules, IgnoreFileRules, GITIGNORE, IGNORE_FILES},
 	IndexerRuler, RulerDecision,
 };
-use sd_core_prisma_helpers::{file_path_with_object, object_ids, CasId, ObjectPubId};
+use sd_core_prisma_helpers::{
+	file_path_to_isolate_with_id, file_path_with_object, object_ids, CasId, ObjectPubId,
+};
 
 use sd_file_ext::{
 	extensions::{AudioExtension, ImageExtension, VideoExtension},
 	kind::ObjectKind,
 };
 use sd_prisma::{
-	prisma::{file_path, location, object},
+	prisma::{file_path, location, object, SortOrder},
 	prisma_sync,
 };
 use sd_sync::OperationFactory;
@@ -57,6 +65,7 @@ use std::{
 	path::{Path, PathBuf},
 	str::FromStr,
 	sync::Arc,
+	time::SystemTime,
 };
 
 use chrono::{DateTime, FixedOffset, Local, Utc};
@@ -131,6 +140,16 @@ pub(super) async fn reject_event(
 								independent_ruler.extend(rules.map(Into::into));
 							}
 						}
+
+						if independent_ruler.has_system(&IGNORE_FILES) {
+							if let Some(rules) =
+								IgnoreFileRules::get_rules(location_path, path_to_check_gitignore)
+									.await
+							{
+								trace!("Found ignore files rules to follow");
+								independent_ruler.extend(rules.map(Into::into));
+							}
+						}
 					}
 
 					independent_ruler.evaluate_path(path, &metadata).await
@@ -157,6 +176,67 @@ pub(super) async fn reject_event(
 	false
 }
 
+/// Apply the location's [`SymlinkPolicy`] to a path received from the watcher.
+///
+/// Returns `true` when `path` is a symlink that must not be handled as a regular file or directory,
+/// either because it's ignored or because it's indexed as an alias by a light scan of its parent.
+async fn handle_symlink(
+	location_id: location::id::Type,
+	path: &Path,
+	node: &Arc<Node>,
+	library: &Arc<Library>,
+) -> Result<bool, LocationManagerError> {
+	match fs::symlink_metadata(path).await {
+		Ok(metadata) if metadata.is_symlink() => {}
+		Ok(_) => return Ok(false),
+		Err(e) if e.kind() == ErrorKind::NotFound => return Ok(false),
+		Err(e) => return Err(FileIOError::from((path, e)).into()),
+	}
+
+	let location = find_location(library, location_id)
+		.include(location_with_indexer_rules::include())
+		.exec()
+		.await?
+		.ok_or(LocationManagerError::LocationNotFound(location_id))?;
+
+	let location_path = maybe_missing(&location.path, "location.path")?;
+
+	match resolve_symlink(
+		SymlinkPolicy::from(location.symlink_policy),
+		path,
+		location_path,
+		&[],
+	)
+	.await?
+	{
+		SymlinkResolution::Ignore => Ok(true),
+
+		SymlinkResolution::Follow { .. } => Ok(false),
+
+		SymlinkResolution::Alias { .. } => {
+			let sub_path = IsolatedFilePathData::new(location_id, location_path, path, false)?
+				.to_parts()
+				.materialized_path
+				.to_string();
+
+			trace!(%sub_path, "Indexing symlink as an alias;");
+
+			spawn({
+				let node = Arc::clone(node);
+				let library = Arc::clone(library);
+
+				async move {
+					if let Err(e) = light_scan_location(node, library, location, sub_path).await {
+						error!(?e, "Failed to index symlink as an alias;");
+					}
+				}
+			});
+
+			Ok(true)
+		}
+	}
+}
+
 #[instrument(skip_all, fields(path = %path.as_ref().display()), err)]
 pub(super) async fn create_dir(
 	location_id: location::id::Type,
@@ -165,14 +245,18 @@ pub(super) async fn create_dir(
 	node: &Arc<Node>,
 	library: &Arc<Library>,
 ) -> Result<(), LocationManagerError> {
+	let path = path.as_ref();
+
+	if handle_symlink(location_id, path, node, library).await? {
+		return Ok(());
+	}
+
 	let location = find_location(library, location_id)
 		.include(location_with_indexer_rules::include())
 		.exec()
 		.await?
 		.ok_or(LocationManagerError::LocationNotFound(location_id))?;
 
-	let path = path.as_ref();
-
 	let location_path = maybe_missing(&location.path, "location.path")?;
 
 	trace!(new_directory = %path.display(), "Creating directory;");
@@ -196,7 +280,10 @@ pub(super) async fn create_dir(
 		library,
 		iso_file_path.to_parts(),
 		None,
-		FilePathMetadata::from_path(path, metadata)?,
+		FilePathMetadata::from_path(path, metadata)?.with_xattrs(
+			path,
+			&indexed_xattrs_from_db(location.indexed_xattrs.as_deref()),
+		),
 	)
 	.await?;
 
@@ -233,6 +320,10 @@ pub(super) async fn create_file(
 	node: &Arc<Node>,
 	library: &Arc<Library>,
 ) -> Result<(), LocationManagerError> {
+	if handle_symlink(location_id, path.as_ref(), node, library).await? {
+		return Ok(());
+	}
+
 	inner_create_file(
 		location_id,
 		extract_location_path(location_id, library).await?,
@@ -266,20 +357,27 @@ async fn inner_create_file(
 	let iso_file_path_parts = iso_file_path.to_parts();
 	let extension = iso_file_path_parts.extension.to_string();
 
-	let metadata = FilePathMetadata::from_path(path, metadata)?;
+	let metadata = FilePathMetadata::from_path(path, metadata)?.with_xattrs(
+		path,
+		&extract_location_indexed_xattrs(location_id, library).await?,
+	);
 
 	// First we check if already exist a file with this same inode number
-	// if it does, we just update it
-	if let Some(file_path) = db
-		.file_path()
-		.find_unique(file_path::location_id_inode(
-			location_id,
-			inode_to_db(metadata.inode),
-		))
-		.include(file_path_with_object::include())
-		.exec()
-		.await?
-	{
+	// if it does, we just update it, unless the file has hard links as then it's a new link to it
+	let maybe_file_path_with_same_inode = if metadata.hard_link_key().is_none() {
+		db.file_path()
+			.find_first(vec![
+				file_path::location_id::equals(Some(location_id)),
+				file_path::inode::equals(Some(inode_to_db(metadata.inode))),
+			])
+			.include(file_path_with_object::include())
+			.exec()
+			.await?
+	} else {
+		None
+	};
+
+	if let Some(file_path) = maybe_file_path_with_same_inode {
 		trace!(%iso_file_path, "File already exists with that inode;");
 
 		return inner_update_file(location_path, &file_path, path, node, library, None).await;
@@ -326,9 +424,22 @@ async fn inner_create_file(
 		fs_metadata,
 	} = FileMetadata::new(&location_path, &iso_file_path).await?;
 
+	let (device, inode, nlink) = (metadata.device, metadata.inode, metadata.nlink);
+
 	let created_file =
 		create_file_path(library, iso_file_path_parts, cas_id.clone(), metadata).await?;
 
+	// A new link to a file changes the link count of every other link to it
+	if nlink > 1 {
+		update_hard_links_nlink(
+			inode_to_db(device),
+			inode_to_db(inode),
+			nlink_to_db(nlink),
+			library,
+		)
+		.await?;
+	}
+
 	let existing_object = db
 		.object()
 		.find_first(vec![object::file_paths::some(vec![
@@ -436,9 +547,10 @@ async fn inner_create_file(
 			ObjectKind::Image => {
 				if let Ok(image_extension) = ImageExtension::from_str(&extension) {
 					if exif_media_data::can_extract(image_extension) {
-						if let Ok(Some(exif_data)) = exif_media_data::extract(path)
-							.await
-							.map_err(|e| error!(?e, "Failed to extract image media data;"))
+						if let Ok(Some(exif_data)) =
+							exif_media_data::extract(path, node.reverse_geocoder.get().as_deref())
+								.await
+								.map_err(|e| error!(?e, "Failed to extract image media data;"))
 						{
 							exif_media_data::save(
 								[(exif_data, object_id, object_pub_id.into())],
@@ -454,7 +566,7 @@ async fn inner_create_file(
 			ObjectKind::Audio => {
 				if let Ok(audio_extension) = AudioExtension::from_str(&extension) {
 					if ffmpeg_media_data::can_extract_for_audio(audio_extension) {
-						if let Ok(ffmpeg_data) = ffmpeg_media_data::extract(path)
+						if let Ok(ffmpeg_data) = ffmpeg_media_data::extract(path, true)
 							.await
 							.map_err(|e| error!(?e, "Failed to extract audio media data;"))
 						{
@@ -467,7 +579,7 @@ async fn inner_create_file(
 			ObjectKind::Video => {
 				if let Ok(video_extension) = VideoExtension::from_str(&extension) {
 					if ffmpeg_media_data::can_extract_for_video(video_extension) {
-						if let Ok(ffmpeg_data) = ffmpeg_media_data::extract(path)
+						if let Ok(ffmpeg_data) = ffmpeg_media_data::extract(path, false)
 							.await
 							.map_err(|e| error!(?e, "Failed to extract video media data;"))
 						{
@@ -489,6 +601,69 @@ async fn inner_create_file(
 	Ok(())
 }
 
+/// Number of file paths checked at a time for changes made while the location wasn't watched
+const MODIFIED_SINCE_BATCH_SIZE: i64 = 1000;
+
+/// Updates every file modified after `since`, as editing a file in place doesn't change its parent
+/// directory, so incremental scans skipping unchanged directories wouldn't see it
+#[instrument(skip(already_updated, node, library), err)]
+pub(super) async fn update_files_modified_since(
+	location_id: location::id::Type,
+	since: DateTime<Utc>,
+	already_updated: &HashSet<PathBuf>,
+	node: &Arc<Node>,
+	library: &Arc<Library>,
+) -> Result<(), LocationManagerError> {
+	let location_path = extract_location_path(location_id, library).await?;
+	let since = SystemTime::from(since);
+	let mut cursor = 0;
+
+	loop {
+		let file_paths = library
+			.db
+			.file_path()
+			.find_many(vec![
+				file_path::id::gt(cursor),
+				file_path::location_id::equals(Some(location_id)),
+				file_path::is_dir::equals(Some(false)),
+			])
+			.order_by(file_path::id::order(SortOrder::Asc))
+			.take(MODIFIED_SINCE_BATCH_SIZE)
+			.select(file_path_to_isolate_with_id::select())
+			.exec()
+			.await?;
+
+		let Some(last) = file_paths.last() else {
+			break;
+		};
+		cursor = last.id;
+
+		for file_path in &file_paths {
+			let full_path = location_path.join(IsolatedFilePathData::try_from(file_path)?);
+
+			if already_updated.contains(&full_path) {
+				continue;
+			}
+
+			// Removed files are left to the incremental scan, as their parent directory changed
+			let Ok(modified_at) = fs::metadata(&full_path)
+				.await
+				.and_then(|metadata| metadata.modified())
+			else {
+				continue;
+			};
+
+			if modified_at > since {
+				if let Err(e) = update_file(location_id, &full_path, node, library).await {
+					error!(?e, path = %full_path.display(), "Failed to update file modified while not watched;");
+				}
+			}
+		}
+	}
+
+	Ok(())
+}
+
 #[instrument(skip_all, fields(path = %path.as_ref().display()), err)]
 pub(super) async fn update_file(
 	location_id: location::id::Type,
@@ -498,6 +673,10 @@ pub(super) async fn update_file(
 ) -> Result<(), LocationManagerError> {
 	let full_path = path.as_ref();
 
+	if handle_symlink(location_id, full_path, node, library).await? {
+		return Ok(());
+	}
+
 	let metadata = match fs::metadata(full_path).await {
 		Ok(metadata) => metadata,
 		Err(e) if e.kind() == io::ErrorKind::NotFound => {
@@ -582,6 +761,18 @@ async fn inner_update_file(
 	};
 
 	let is_hidden = path_is_hidden(full_path, &fs_metadata);
+	let mut posix_metadata_params = posix_metadata_update_params(
+		file_path,
+		full_path,
+		&fs_metadata,
+		&extract_location_indexed_xattrs(
+			maybe_missing(file_path.location_id, "file_path.location_id")?,
+			library,
+		)
+		.await?,
+	);
+	posix_metadata_params.extend(hard_link_update_params(file_path, &fs_metadata));
+
 	if file_path.cas_id.as_deref() != cas_id.as_ref().map(CasId::as_str) {
 		let (sync_params, db_params): (Vec<_>, Vec<_>) = {
 			use file_path::*;
@@ -651,6 +842,7 @@ async fn inner_update_file(
 			.filter_map(|(sync_param, maybe_db_param)| {
 				maybe_db_param.map(|db_param| (sync_param, db_param))
 			})
+			.chain(posix_metadata_params)
 			.unzip()
 		};
 
@@ -805,9 +997,12 @@ async fn inner_update_file(
 					ObjectKind::Image => {
 						if let Ok(image_extension) = ImageExtension::from_str(extension) {
 							if exif_media_data::can_extract(image_extension) {
-								if let Ok(Some(exif_data)) = exif_media_data::extract(full_path)
-									.await
-									.map_err(|e| error!(?e, "Failed to extract media data;"))
+								if let Ok(Some(exif_data)) = exif_media_data::extract(
+									full_path,
+									node.reverse_geocoder.get().as_deref(),
+								)
+								.await
+								.map_err(|e| error!(?e, "Failed to extract media data;"))
 								{
 									exif_media_data::save(
 										[(exif_data, object.id, object.pub_id.as_slice().into())],
@@ -823,7 +1018,7 @@ async fn inner_update_file(
 					ObjectKind::Audio => {
 						if let Ok(audio_extension) = AudioExtension::from_str(extension) {
 							if ffmpeg_media_data::can_extract_for_audio(audio_extension) {
-								if let Ok(ffmpeg_data) = ffmpeg_media_data::extract(full_path)
+								if let Ok(ffmpeg_data) = ffmpeg_media_data::extract(full_path, true)
 									.await
 									.map_err(|e| error!(?e, "Failed to extract media data;"))
 								{
@@ -836,9 +1031,10 @@ async fn inner_update_file(
 					ObjectKind::Video => {
 						if let Ok(video_extension) = VideoExtension::from_str(extension) {
 							if ffmpeg_media_data::can_extract_for_video(video_extension) {
-								if let Ok(ffmpeg_data) = ffmpeg_media_data::extract(full_path)
-									.await
-									.map_err(|e| error!(?e, "Failed to extract media data;"))
+								if let Ok(ffmpeg_data) =
+									ffmpeg_media_data::extract(full_path, false)
+										.await
+										.map_err(|e| error!(?e, "Failed to extract media data;"))
 								{
 									ffmpeg_media_data::save([(ffmpeg_data, object.id)], db).await?;
 								}
@@ -855,31 +1051,133 @@ async fn inner_update_file(
 
 		invalidate_query!(library, "search.paths");
 		invalidate_query!(library, "search.objects");
-	} else if is_hidden != file_path.hidden.unwrap_or_default() {
-		sync.write_ops(
-			db,
-			(
-				vec![sync.shared_update(
-					prisma_sync::file_path::SyncId {
-						pub_id: file_path.pub_id.clone(),
-					},
-					file_path::hidden::NAME,
-					msgpack!(is_hidden),
-				)],
-				db.file_path().update(
-					file_path::pub_id::equals(file_path.pub_id.clone()),
-					vec![file_path::hidden::set(Some(is_hidden))],
+	} else {
+		let hidden_changed = is_hidden != file_path.hidden.unwrap_or_default();
+
+		let (sync_params, db_params): (Vec<_>, Vec<_>) = hidden_changed
+			.then(|| {
+				(
+					(file_path::hidden::NAME, msgpack!(is_hidden)),
+					file_path::hidden::set(Some(is_hidden)),
+				)
+			})
+			.into_iter()
+			.chain(posix_metadata_params)
+			.unzip();
+
+		if !sync_params.is_empty() {
+			sync.write_ops(
+				db,
+				(
+					sync_params
+						.into_iter()
+						.map(|(field, value)| {
+							sync.shared_update(
+								prisma_sync::file_path::SyncId {
+									pub_id: file_path.pub_id.clone(),
+								},
+								field,
+								value,
+							)
+						})
+						.collect(),
+					db.file_path().update(
+						file_path::pub_id::equals(file_path.pub_id.clone()),
+						db_params,
+					),
 				),
-			),
-		)
-		.await?;
+			)
+			.await?;
 
-		invalidate_query!(library, "search.paths");
+			invalidate_query!(library, "search.paths");
+		}
 	}
 
 	Ok(())
 }
 
+/// Sync and database params to update the permissions, ownership and extended attributes of
+/// `file_path`, only for the ones that changed
+fn posix_metadata_update_params(
+	file_path: &file_path_with_object::Data,
+	full_path: &Path,
+	fs_metadata: &Metadata,
+	indexed_xattrs: &[String],
+) -> Vec<((&'static str, rmpv::Value), file_path::SetParam)> {
+	use file_path::*;
+
+	let DBPosixMetadata {
+		permissions: maybe_permissions,
+		uid: maybe_uid,
+		gid: maybe_gid,
+		owner_name: maybe_owner_name,
+		group_name: maybe_group_name,
+	} = posix_metadata_to_db(PosixMetadata::from_metadata(fs_metadata));
+	let maybe_xattrs = xattrs_to_db(&read_xattrs(full_path, indexed_xattrs));
+
+	[
+		(maybe_permissions != file_path.permissions).then(|| {
+			(
+				(permissions::NAME, msgpack!(maybe_permissions)),
+				permissions::set(maybe_permissions),
+			)
+		}),
+		(maybe_uid != file_path.uid)
+			.then(|| ((uid::NAME, msgpack!(maybe_uid)), uid::set(maybe_uid))),
+		(maybe_gid != file_path.gid)
+			.then(|| ((gid::NAME, msgpack!(maybe_gid)), gid::set(maybe_gid))),
+		(maybe_owner_name != file_path.owner_name).then(|| {
+			(
+				(owner_name::NAME, msgpack!(maybe_owner_name)),
+				owner_name::set(maybe_owner_name),
+			)
+		}),
+		(maybe_group_name != file_path.group_name).then(|| {
+			(
+				(group_name::NAME, msgpack!(maybe_group_name)),
+				group_name::set(maybe_group_name),
+			)
+		}),
+		(maybe_xattrs != file_path.xattrs).then(|| {
+			(
+				(xattrs::NAME, msgpack!(maybe_xattrs)),
+				xattrs::set(maybe_xattrs),
+			)
+		}),
+	]
+	.into_iter()
+	.flatten()
+	.collect()
+}
+
+fn hard_link_update_params(
+	file_path: &file_path_with_object::Data,
+	fs_metadata: &Metadata,
+) -> Vec<((&'static str, rmpv::Value), file_path::SetParam)> {
+	use file_path::*;
+
+	let (fs_device, fs_nlink) = device_and_nlink(fs_metadata);
+	let (db_device, db_nlink) = (inode_to_db(fs_device), nlink_to_db(fs_nlink));
+
+	[
+		(Some(&db_device) != file_path.device.as_ref()).then(|| {
+			(
+				(device::NAME, msgpack!(db_device)),
+				device::set(Some(db_device)),
+			)
+		}),
+		(Some(db_nlink) != file_path.nlink).then(|| {
+			(
+				(nlink::NAME, msgpack!(db_nlink)),
+				nlink::set(Some(db_nlink)),
+			)
+		}),
+	]
+	.into_iter()
+	.flatten()
+	.collect()
+}
+
 #[instrument(
 	skip_all,
 	fields(new_path = %new_path.as_ref().display(), old_path = %old_path.as_ref().display()),
@@ -1119,6 +1417,16 @@ async fn remove_by_file_path(
 						.exec()
 						.await?;
 				}
+
+				// The remaining links to this file lost one link
+				if let (Some(device), Some(inode), Some(nlink)) =
+					(&file_path.device, &file_path.inode, file_path.nlink)
+				{
+					if nlink > 1 {
+						update_hard_links_nlink(device.clone(), inode.clone(), nlink - 1, library)
+							.await?;
+					}
+				}
 			}
 		}
 		Err(e) => return Err(FileIOError::from((path, e)).into()),
@@ -1130,6 +1438,49 @@ async fn remove_by_file_path(
 	Ok(())
 }
 
+/// Sets the number of hard links on every file path linking to the same inode, as creating or
+/// removing one of them changes it for all the others
+async fn update_hard_links_nlink(
+	device: Vec<u8>,
+	inode: Vec<u8>,
+	nlink: i32,
+	Library { db, sync, .. }: &Library,
+) -> Result<(), LocationManagerError> {
+	let (sync_params, db_params) = db
+		.file_path()
+		.find_many(vec![
+			file_path::device::equals(Some(device)),
+			file_path::inode::equals(Some(inode)),
+			file_path::nlink::not(Some(nlink)),
+		])
+		.select(file_path::select!({ pub_id }))
+		.exec()
+		.await?
+		.into_iter()
+		.map(|file_path| {
+			(
+				sync.shared_update(
+					prisma_sync::file_path::SyncId {
+						pub_id: file_path.pub_id.clone(),
+					},
+					file_path::nlink::NAME,
+					msgpack!(nlink),
+				),
+				db.file_path().update(
+					file_path::pub_id::equals(file_path.pub_id),
+					vec![file_path::nlink::set(Some(nlink))],
+				),
+			)
+		})
+		.unzip::<_, _, Vec<_>, Vec<_>>();
+
+	if !sync_params.is_empty() {
+		sync.write_ops(db, (sync_params, db_params)).await?;
+	}
+
+	Ok(())
+}
+
 #[instrument(skip_all, fields(path = %path.as_ref().display()), err)]
 pub(super) async fn extract_inode_from_path(
 	location_id: location::id::Type,
@@ -1181,6 +1532,21 @@ pub(super) async fn extract_location_path(
 			|location| Ok(maybe_missing(location.path, "location.path")?.into()),
 		)
 }
+
+async fn extract_location_indexed_xattrs(
+	location_id: location::id::Type,
+	library: &Library,
+) -> Result<Vec<String>, LocationManagerError> {
+	find_location(library, location_id)
+		.select(location::select!({ indexed_xattrs }))
+		.exec()
+		.await?
+		.map_or(
+			Err(LocationManagerError::LocationNotFound(location_id)),
+			|location| Ok(indexed_xattrs_from_db(location.indexed_xattrs.as_deref())),
+		)
+}
+
 #[instrument(skip_all, err)]
 pub(super) async fn recalculate_directories_size(
 	candidates: &mut HashMap<PathBuf, Instant>,
@@ -1,11 +1,14 @@
 use crate::{context::NodeContext, invalidate_query, library::Library, Node};
 
 use sd_core_file_path_helper::{
-	filter_existing_file_path_params, IsolatedFilePathData, IsolatedFilePathDataParts,
+	counted_more_than_once_by_subdirectories, fetch_hard_links, filter_existing_file_path_params,
+	hard_link::nlink_to_db,
+	posix::{indexed_xattrs_to_db, posix_metadata_to_db, xattrs_to_db, DBPosixMetadata},
+	InodeKey, IsolatedFilePathData, IsolatedFilePathDataParts, UniqueInodesSize,
 };
 use sd_core_heavy_lifting::{
 	file_identifier::{self, FileIdentifier},
-	indexer::{self, job::Indexer},
+	indexer::{self, job::Indexer, DirectorySnapshots, SymlinkPolicy},
 	job_system::report::ReportInputMetadata,
 	media_processor::{self, job::MediaProcessor},
 	JobEnqueuer, JobId,
@@ -288,6 +291,9 @@ pub struct LocationUpdateArgs {
 	generate_preview_media: Option<bool>,
 	sync_preview_media: Option<bool>,
 	hidden: Option<bool>,
+	symlink_policy: Option<SymlinkPolicy>,
+	/// Extended attributes to store for each file path, an empty list stops storing them
+	indexed_xattrs: Option<Vec<String>>,
 	indexer_rules_ids: Vec<i32>,
 	path: Option<String>,
 }
@@ -331,6 +337,20 @@ impl LocationUpdateArgs {
 					location::hidden::set(Some(v)),
 				)
 			}),
+			self.symlink_policy.map(|v| {
+				let v = v as i32;
+				(
+					(location::symlink_policy::NAME, msgpack!(v)),
+					location::symlink_policy::set(Some(v)),
+				)
+			}),
+			self.indexed_xattrs.map(|names| {
+				let v = indexed_xattrs_to_db(&names);
+				(
+					(location::indexed_xattrs::NAME, msgpack!(v)),
+					location::indexed_xattrs::set(v),
+				)
+			}),
 			self.path.clone().map(|v| {
 				(
 					(location::path::NAME, msgpack!(v)),
@@ -581,6 +601,49 @@ pub async fn scan_location_sub_path(
 		.map(Some)
 }
 
+/// Scans a location skipping the directories that didn't change since the last scan, used to catch
+/// the changes made while the location wasn't being watched
+#[instrument(
+	skip(node, library, location),
+	fields(library_id = %library.id, location_id = %location.id),
+	err,
+)]
+pub async fn reconcile_location(
+	node: &Arc<Node>,
+	library: &Arc<Library>,
+	location: location_with_indexer_rules::Data,
+) -> Result<Option<JobId>, sd_core_heavy_lifting::Error> {
+	// TODO(N): This isn't gonna work with removable media and this will likely permanently break if the DB is restored from a backup.
+	if location.instance_id != Some(library.config().await.instance_id) {
+		warn!("Tried to reconcile a location on a different instance");
+		return Ok(None);
+	}
+
+	let location_id = location.id;
+	let ctx = NodeContext {
+		node: Arc::clone(node),
+		library: Arc::clone(library),
+	};
+
+	let location_base_data = location::Data::from(&location);
+
+	debug!("Reconciling location");
+
+	node.job_system
+		.dispatch(
+			JobEnqueuer::new(Indexer::new_incremental(location, None)?)
+				.with_action("reconcile_location")
+				.with_metadata(ReportInputMetadata::Location(location_base_data.clone()))
+				.enqueue_next(FileIdentifier::new(location_base_data.clone(), None)?)
+				.enqueue_next(MediaProcessor::new(location_base_data, None, false)?),
+			location_id,
+			ctx,
+		)
+		.await
+		.map_err(Into::into)
+		.map(Some)
+}
+
 #[instrument(
 	skip_all,
 	fields(
@@ -888,6 +951,17 @@ pub async fn delete_location(
 		.await?;
 	debug!(elapsed_time = ?start.elapsed(), "Deleted indexer rules in location;");
 
+	if let Err(e) = DirectorySnapshots::remove(&node.data_dir, library.id, &location.pub_id).await {
+		warn!(?e, "Failed to remove location directory snapshots;");
+	}
+
+	#[cfg(target_os = "linux")]
+	if let Err(e) =
+		manager::remove_watcher_state(&node.data_dir, library.id, &location.pub_id).await
+	{
+		warn!(?e, "Failed to remove location watcher state;");
+	}
+
 	let start = Instant::now();
 
 	sync.write_op(
@@ -1014,25 +1088,37 @@ pub async fn update_location_size(
 			file_path::location_id::equals(Some(location_id)),
 			file_path::materialized_path::equals(Some("/".to_string())),
 		])
-		.select(file_path::select!({ size_in_bytes_bytes }))
+		.select(file_path::select!({ size_in_bytes_bytes inode device nlink }))
 		.exec()
 		.await?
 		.into_iter()
 		.filter_map(|file_path| {
 			file_path.size_in_bytes_bytes.map(|size_in_bytes_bytes| {
-				u64::from_be_bytes([
-					size_in_bytes_bytes[0],
-					size_in_bytes_bytes[1],
-					size_in_bytes_bytes[2],
-					size_in_bytes_bytes[3],
-					size_in_bytes_bytes[4],
-					size_in_bytes_bytes[5],
-					size_in_bytes_bytes[6],
-					size_in_bytes_bytes[7],
-				])
+				(
+					InodeKey::from_db(
+						file_path.device.as_deref(),
+						file_path.inode.as_deref(),
+						file_path.nlink,
+					),
+					u64::from_be_bytes([
+						size_in_bytes_bytes[0],
+						size_in_bytes_bytes[1],
+						size_in_bytes_bytes[2],
+						size_in_bytes_bytes[3],
+						size_in_bytes_bytes[4],
+						size_in_bytes_bytes[5],
+						size_in_bytes_bytes[6],
+						size_in_bytes_bytes[7],
+					]),
+				)
 			})
 		})
-		.sum::<u64>();
+		.collect::<UniqueInodesSize>()
+		.total()
+		.saturating_sub(counted_more_than_once_by_subdirectories(
+			&fetch_hard_links(location_id, db).await?,
+			"/",
+		));
 
 	db.location()
 		.update(
@@ -1092,6 +1178,15 @@ pub async fn create_file_path(
 
 	let indexed_at = Utc::now();
 
+	let DBPosixMetadata {
+		permissions: maybe_permissions,
+		uid: maybe_uid,
+		gid: maybe_gid,
+		owner_name: maybe_owner_name,
+		group_name: maybe_group_name,
+	} = posix_metadata_to_db(metadata.posix);
+	let maybe_xattrs = xattrs_to_db(&metadata.xattrs);
+
 	let location = db
 		.location()
 		.find_unique(location::id::equals(location_id))
@@ -1139,6 +1234,14 @@ pub async fn create_file_path(
 				(inode::NAME, msgpack!(metadata.inode.to_le_bytes())),
 				inode::set(Some(inode_to_db(metadata.inode))),
 			),
+			(
+				(device::NAME, msgpack!(inode_to_db(metadata.device))),
+				device::set(Some(inode_to_db(metadata.device))),
+			),
+			(
+				(nlink::NAME, msgpack!(nlink_to_db(metadata.nlink))),
+				nlink::set(Some(nlink_to_db(metadata.nlink))),
+			),
 			((is_dir::NAME, msgpack!(is_dir)), is_dir::set(Some(is_dir))),
 			(
 				(date_created::NAME, msgpack!(metadata.created_at)),
@@ -1156,6 +1259,24 @@ pub async fn create_file_path(
 				(hidden::NAME, msgpack!(metadata.hidden)),
 				hidden::set(Some(metadata.hidden)),
 			),
+			(
+				(permissions::NAME, msgpack!(maybe_permissions)),
+				permissions::set(maybe_permissions),
+			),
+			((uid::NAME, msgpack!(maybe_uid)), uid::set(maybe_uid)),
+			((gid::NAME, msgpack!(maybe_gid)), gid::set(maybe_gid)),
+			(
+				(owner_name::NAME, msgpack!(maybe_owner_name)),
+				owner_name::set(maybe_owner_name),
+			),
+			(
+				(group_name::NAME, msgpack!(maybe_group_name)),
+				group_name::set(maybe_group_name),
+			),
+			(
+				(xattrs::NAME, msgpack!(maybe_xattrs)),
+				xattrs::set(maybe_xattrs),
+			),
 		]
 		.into_iter()
 		.unzip()
@@ -9,7 +9,7 @@ use sd_core_heavy_lifting::{
 	},
 };
 use sd_core_indexer_rules::{
-	seed::{NO_HIDDEN, NO_SYSTEM_FILES},
+	seed::{IgnoreFileRules, NO_HIDDEN, NO_SYSTEM_FILES},
 	IndexerRule, IndexerRuler, RulerDecision,
 };
 
@@ -122,11 +122,18 @@ pub async fn walk(
 	// We wanna process and let the caller use the stream.
 	let task = spawn(async move {
 		let path = &path;
-		let indexer_ruler = IndexerRuler::new(chain_optional_iter(
+		let mut indexer_ruler = IndexerRuler::new(chain_optional_iter(
 			[IndexerRule::from(NO_SYSTEM_FILES.deref())],
 			[(!with_hidden_files).then(|| IndexerRule::from(NO_HIDDEN.deref()))],
 		));
 
+		// Without a location root, ignore files are collected all the way up like ripgrep does
+		if let Some(root) = path.ancestors().last() {
+			if let Some(rules) = IgnoreFileRules::get_rules(root, path).await {
+				indexer_ruler.extend(rules.map(Into::into));
+			}
+		}
+
 		let mut thumbnails_to_generate = vec![];
 		// Generating thumbnails for PDFs is kinda slow, so we're leaving them for last in the batch
 		let mut document_thumbnails_to_generate = vec![];
@@ -188,13 +195,21 @@ pub async fn walk(
 					{
 						matches!(
 							kind,
-							ObjectKind::Image | ObjectKind::Video | ObjectKind::Document
+							ObjectKind::Image
+								| ObjectKind::Video | ObjectKind::Document
+								| ObjectKind::Text | ObjectKind::Code
+								| ObjectKind::Config
 						)
 					}
 
 					#[cfg(not(feature = "ffmpeg"))]
 					{
-						matches!(kind, ObjectKind::Image | ObjectKind::Document)
+						matches!(
+							kind,
+							ObjectKind::Image
+								| ObjectKind::Document | ObjectKind::Text
+								| ObjectKind::Code | ObjectKind::Config
+						)
 					}
 				};
 
@@ -207,7 +222,13 @@ pub async fn walk(
 									NonIndexedLocationError::from((path, e)).into(),
 								)))
 							}) {
-						if kind == ObjectKind::Document {
+						// Documents and text files take longer to render, so they go last
+						if matches!(
+							kind,
+							ObjectKind::Document
+								| ObjectKind::Text | ObjectKind::Code
+								| ObjectKind::Config
+						) {
 							document_thumbnails_to_generate.push(GenerateThumbnailArgs::new(
 								extension.clone(),
 								cas_id.clone(),
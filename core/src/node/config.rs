@@ -1,18 +1,20 @@
 use crate::{
 	api::{notifications::Notification, BackendFeature},
-	/*object::media::old_thumbnail::preferences::ThumbnailerPreferences,*/
 	util::version_manager::{Kind, ManagedVersion, VersionManager, VersionManagerError},
 };
 
-use sd_p2p::Identity;
+use sd_core_heavy_lifting::media_processor::VideoPreviewsOptions;
+
+use sd_p2p::{Identity, RemoteIdentity};
 use sd_utils::error::FileIOError;
 
 use std::{
-	collections::HashSet,
+	collections::{HashMap, HashSet},
 	path::{Path, PathBuf},
 	sync::Arc,
 };
 
+use chrono::{DateTime, Utc};
 use int_enum::IntEnum;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Map, Value};
@@ -94,6 +96,90 @@ pub struct NodeConfigP2P {
 	/// which is why we use `String` not `SocketAddr`
 	#[serde(default)]
 	pub manual_peers: HashSet<String>,
+	/// Peers which have been explicitly paired with this node using a pairing code.
+	#[serde(default, skip_serializing_if = "HashMap::is_empty")]
+	pub trusted_peers: HashMap<RemoteIdentity, TrustedPeer>,
+	/// When enabled, Spacedrop, library and sync requests are only accepted from `trusted_peers`.
+	#[serde(default, skip_serializing_if = "skip_if_false")]
+	pub trusted_peers_only: bool,
+	/// The bandwidth limits for P2P transfers when no `bandwidth_schedule` window applies.
+	#[serde(default, skip_serializing_if = "BandwidthLimit::is_unlimited")]
+	pub bandwidth_limit: BandwidthLimit,
+	/// Time of day windows with their own bandwidth limits. The first matching window wins.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub bandwidth_schedule: Vec<BandwidthWindow>,
+}
+
+impl NodeConfigP2P {
+	pub fn is_trusted(&self, identity: &RemoteIdentity) -> bool {
+		self.trusted_peers.contains_key(identity)
+	}
+
+	/// The addresses the QUIC transport should manually dial.
+	/// This is the user provided `manual_peers` plus the last known addresses of every trusted peer.
+	pub fn manual_peer_addrs(&self) -> HashSet<String> {
+		self.manual_peers
+			.iter()
+			.chain(
+				self.trusted_peers
+					.values()
+					.flat_map(|peer| peer.addrs.iter()),
+			)
+			.cloned()
+			.collect()
+	}
+
+	/// The bandwidth limit which applies at `minute` minutes past local midnight.
+	pub fn active_bandwidth_limit(&self, minute: u16) -> &BandwidthLimit {
+		self.bandwidth_schedule
+			.iter()
+			.find(|window| window.contains(minute))
+			.map_or(&self.bandwidth_limit, |window| &window.limit)
+	}
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct TrustedPeer {
+	/// The display name of the peer. Defaults to the node name it provided while pairing but can be renamed by the user.
+	pub name: String,
+	/// The addresses the peer was reachable on when it was paired. These are dialled the same as `manual_peers`.
+	#[serde(default)]
+	pub addrs: HashSet<String>,
+	pub paired_at: DateTime<Utc>,
+}
+
+/// Rate limits for P2P transfers in bytes per second. `None` means unlimited.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub struct BandwidthLimit {
+	#[serde(default)]
+	pub upload: Option<u32>,
+	#[serde(default)]
+	pub download: Option<u32>,
+}
+
+impl BandwidthLimit {
+	pub fn is_unlimited(&self) -> bool {
+		self.upload.is_none() && self.download.is_none()
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub struct BandwidthWindow {
+	/// Minutes past local midnight at which the window starts
+	pub start: u16,
+	/// Minutes past local midnight at which the window ends. If this is before `start` the window wraps past midnight.
+	pub end: u16,
+	pub limit: BandwidthLimit,
+}
+
+impl BandwidthWindow {
+	pub fn contains(&self, minute: u16) -> bool {
+		if self.start <= self.end {
+			(self.start..self.end).contains(&minute)
+		} else {
+			minute >= self.start || minute < self.end
+		}
+	}
 }
 
 impl Default for NodeConfigP2P {
@@ -106,6 +192,10 @@ impl Default for NodeConfigP2P {
 			disable_relay: true,
 			enable_remote_access: false,
 			manual_peers: Default::default(),
+			trusted_peers: Default::default(),
+			trusted_peers_only: false,
+			bandwidth_limit: Default::default(),
+			bandwidth_schedule: Vec::new(),
 		}
 	}
 }
@@ -139,6 +229,13 @@ pub struct NodeConfig {
 	pub preferences: NodePreferences,
 	// Model version for the image labeler
 	pub image_labeler_version: Option<String>,
+	/// Model file supplied by the user, used by the image labeler instead of the bundled one
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub image_labeler_model_path: Option<PathBuf>,
+	/// GeoNames dataset supplied by the user, used by the reverse geocoder instead of the one
+	/// installed next to the app
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub reverse_geocoder_dataset_path: Option<PathBuf>,
 
 	version: NodeConfigVersion,
 }
@@ -169,8 +266,62 @@ mod identity_serde {
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq, Type)]
 pub struct NodePreferences {
-	// pub thumbnailer: ThumbnailerPreferences,
+	#[serde(default)]
+	pub thumbnailer: ThumbnailerPreferences,
 	// TODO(fogodev): introduce preferences to choose how many worker the task system should have
+	#[serde(default)]
+	pub indexer: IndexerPreferences,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq, Type)]
+pub struct ThumbnailerPreferences {
+	/// Extra previews generated for videos, they are disabled by default as they take much longer
+	/// to generate than thumbnails.
+	#[serde(default)]
+	pub video_previews: VideoPreviewsOptions,
+}
+
+/// Limits for indexer jobs, so walking locations on slow disks doesn't starve everything else.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq, Type)]
+pub struct IndexerPreferences {
+	/// Limits shared by every location indexed by this node.
+	#[serde(default)]
+	pub node: IndexerLimits,
+	/// Limits for specific locations by their `pub_id`, enforced on top of the node ones.
+	#[serde(default, skip_serializing_if = "HashMap::is_empty")]
+	pub locations: HashMap<Uuid, IndexerLimits>,
+}
+
+impl IndexerPreferences {
+	/// If any of the limits, for the node or a location, uses `run_mode`
+	pub fn uses_run_mode(&self, run_mode: IndexerRunMode) -> bool {
+		std::iter::once(&self.node)
+			.chain(self.locations.values())
+			.any(|limits| limits.run_mode == run_mode)
+	}
+}
+
+/// `None` means unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub struct IndexerLimits {
+	/// How many directories can be walked at the same time
+	#[serde(default)]
+	pub max_concurrent_walkers: Option<u32>,
+	/// How many directory entries can be walked per second
+	#[serde(default)]
+	pub max_entries_per_second: Option<u32>,
+	#[serde(default)]
+	pub run_mode: IndexerRunMode,
+}
+
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Serialize, Deserialize, Type)]
+pub enum IndexerRunMode {
+	#[default]
+	Always,
+	/// Indexing is paused while the machine runs on battery
+	PauseOnBattery,
+	/// Indexing is paused while the machine is busy with other work
+	OnlyWhenIdle,
 }
 
 #[derive(
@@ -205,7 +356,7 @@ impl ManagedVersion<NodeConfigVersion> for NodeConfig {
 		name.truncate(250);
 
 		#[cfg(feature = "ai")]
-		let image_labeler_version = Some(sd_ai::old_image_labeler::DEFAULT_MODEL_VERSION.to_string());
+		let image_labeler_version = Some(sd_ai::image_labeler::DEFAULT_MODEL_VERSION.to_string());
 		#[cfg(not(feature = "ai"))]
 		let image_labeler_version = None;
 
@@ -221,6 +372,8 @@ impl ManagedVersion<NodeConfigVersion> for NodeConfig {
 			sd_api_origin: None,
 			preferences: NodePreferences::default(),
 			image_labeler_version,
+			image_labeler_model_path: None,
+			reverse_geocoder_dataset_path: None,
 		})
 	}
 }
@@ -359,12 +512,13 @@ impl Manager {
 		#[cfg(feature = "ai")]
 		if config.image_labeler_version.is_none() {
 			config.image_labeler_version =
-				Some(sd_ai::old_image_labeler::DEFAULT_MODEL_VERSION.to_string());
+				Some(sd_ai::image_labeler::DEFAULT_MODEL_VERSION.to_string());
 		}
 
 		#[cfg(not(feature = "ai"))]
 		{
 			config.image_labeler_version = None;
+			config.image_labeler_model_path = None;
 		}
 
 		let (preferences_watcher_tx, _preferences_watcher_rx) =
@@ -411,6 +565,11 @@ impl Manager {
 			.map(|()| config.clone())
 	}
 
+	/// preferences_watcher returns a receiver which is notified every time the preferences of the node change
+	pub(crate) fn preferences_watcher(&self) -> watch::Receiver<NodePreferences> {
+		self.preferences_watcher_tx.subscribe()
+	}
+
 	/// update_preferences allows the user to update the preferences of the node
 	pub(crate) async fn update_preferences(
 		&self,
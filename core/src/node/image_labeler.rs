@@ -0,0 +1,67 @@
+use sd_ai::image_labeler::{ImageLabeler, ImageLabelerError, YoloV8};
+
+use std::{
+	path::Path,
+	sync::{Arc, PoisonError, RwLock},
+};
+
+use tracing::{error, info};
+
+/// The image labeler model currently loaded by this node, shared with the media processor jobs.
+///
+/// Jobs grab the labeler when they dispatch their tasks, so a model change only applies to the
+/// jobs dispatched after it finishes loading.
+#[derive(Debug, Default)]
+pub struct ImageLabelerModel(RwLock<Option<Arc<ImageLabeler>>>);
+
+impl ImageLabelerModel {
+	/// The loaded labeler, if any model loaded successfully
+	pub fn get(&self) -> Option<Arc<ImageLabeler>> {
+		self.0
+			.read()
+			.unwrap_or_else(PoisonError::into_inner)
+			.clone()
+	}
+
+	/// Loads the requested model version, or the default one, replacing the current labeler.
+	///
+	/// If loading fails, labeling is disabled until a model loads successfully.
+	pub async fn load(
+		&self,
+		version: Option<&str>,
+		custom_model_path: Option<&Path>,
+		data_dir: &Path,
+	) -> Result<Arc<ImageLabeler>, ImageLabelerError> {
+		let res = async {
+			ImageLabeler::new(
+				YoloV8::model(version).map_err(ImageLabelerError::from)?,
+				custom_model_path,
+				data_dir.join("models"),
+			)
+			.await
+			.map(Arc::new)
+		}
+		.await;
+
+		let mut labeler = self.0.write().unwrap_or_else(PoisonError::into_inner);
+
+		match res {
+			Ok(new_labeler) => {
+				info!(
+					version = new_labeler.version(),
+					"Image labeler model loaded;"
+				);
+				*labeler = Some(Arc::clone(&new_labeler));
+				Ok(new_labeler)
+			}
+			Err(e) => {
+				error!(
+					?e,
+					"Failed to load image labeler model, labeling is disabled;"
+				);
+				*labeler = None;
+				Err(e)
+			}
+		}
+	}
+}
@@ -0,0 +1,169 @@
+use super::{
+	config::{IndexerLimits, IndexerPreferences, IndexerRunMode, NodePreferences},
+	power::{self, IdleDetector},
+};
+
+use sd_task_system::{Throttle, ThrottleLimits};
+
+use std::{
+	collections::HashMap,
+	num::{NonZeroU32, NonZeroUsize},
+	sync::{Arc, Mutex, PoisonError},
+	time::Duration,
+};
+
+use futures_concurrency::future::Race;
+use tokio::{
+	spawn,
+	sync::watch,
+	time::{interval, MissedTickBehavior},
+};
+use tracing::{debug, trace};
+use uuid::Uuid;
+
+/// How often we check if the machine is on battery or idle, only done if some limits need it
+const POWER_STATE_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone, Copy)]
+struct PowerState {
+	on_battery: bool,
+	idle: bool,
+}
+
+/// Throttles enforcing the [`IndexerPreferences`], shared by every indexer job of this node
+#[derive(Debug)]
+pub struct IndexerThrottles {
+	node: Throttle,
+	locations: Mutex<HashMap<Uuid, Throttle>>,
+}
+
+impl IndexerThrottles {
+	pub(crate) fn new(preferences_rx: watch::Receiver<NodePreferences>) -> Arc<Self> {
+		let this = Arc::new(Self {
+			node: Throttle::new(ThrottleLimits::default()),
+			locations: Mutex::default(),
+		});
+
+		spawn(Arc::clone(&this).keep_limits_updated(preferences_rx));
+
+		this
+	}
+
+	/// Throttles for indexing the location with this `pub_id`, its own one first if it has
+	/// specific limits, and then the node one
+	pub fn for_location(&self, location_pub_id: &[u8]) -> Vec<Throttle> {
+		let maybe_location_throttle = Uuid::from_slice(location_pub_id).ok().and_then(|pub_id| {
+			self.locations
+				.lock()
+				.unwrap_or_else(PoisonError::into_inner)
+				.get(&pub_id)
+				.cloned()
+		});
+
+		maybe_location_throttle
+			.into_iter()
+			.chain([self.node.clone()])
+			.collect()
+	}
+
+	fn apply(&self, preferences: &IndexerPreferences, power_state: PowerState) {
+		self.node
+			.set_limits(throttle_limits(&preferences.node, power_state));
+
+		let mut locations = self
+			.locations
+			.lock()
+			.unwrap_or_else(PoisonError::into_inner);
+
+		locations.retain(|pub_id, throttle| {
+			let keep = preferences.locations.contains_key(pub_id);
+			if !keep {
+				// Jobs already running with this throttle won't be limited by it anymore
+				throttle.set_limits(ThrottleLimits::default());
+			}
+			keep
+		});
+
+		for (pub_id, limits) in &preferences.locations {
+			let limits = throttle_limits(limits, power_state);
+
+			locations
+				.entry(*pub_id)
+				.and_modify(|throttle| throttle.set_limits(limits))
+				.or_insert_with(|| Throttle::new(limits));
+		}
+	}
+
+	async fn keep_limits_updated(
+		self: Arc<Self>,
+		mut preferences_rx: watch::Receiver<NodePreferences>,
+	) {
+		enum StreamMessage {
+			Tick,
+			PreferencesChanged,
+			ConfigDropped,
+		}
+
+		let mut idle_detector = IdleDetector::new();
+		let mut check_interval = interval(POWER_STATE_CHECK_INTERVAL);
+		check_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+		loop {
+			let preferences = preferences_rx.borrow_and_update().indexer.clone();
+
+			let power_state = PowerState {
+				on_battery: preferences.uses_run_mode(IndexerRunMode::PauseOnBattery)
+					&& power::is_on_battery().await,
+				idle: !preferences.uses_run_mode(IndexerRunMode::OnlyWhenIdle)
+					|| idle_detector.is_idle(),
+			};
+
+			trace!(?power_state, "Updating indexer throttles limits;");
+
+			self.apply(&preferences, power_state);
+
+			let tick = async {
+				check_interval.tick().await;
+				StreamMessage::Tick
+			};
+
+			let preferences_changed = async {
+				preferences_rx
+					.changed()
+					.await
+					.map_or(StreamMessage::ConfigDropped, |()| {
+						StreamMessage::PreferencesChanged
+					})
+			};
+
+			match (tick, preferences_changed).race().await {
+				StreamMessage::Tick | StreamMessage::PreferencesChanged => {}
+				StreamMessage::ConfigDropped => {
+					debug!("Node config was dropped, stopping indexer throttles updates");
+					break;
+				}
+			}
+		}
+	}
+}
+
+fn throttle_limits(
+	IndexerLimits {
+		max_concurrent_walkers,
+		max_entries_per_second,
+		run_mode,
+	}: &IndexerLimits,
+	power_state: PowerState,
+) -> ThrottleLimits {
+	ThrottleLimits {
+		max_concurrent_tasks: max_concurrent_walkers
+			.and_then(|max| usize::try_from(max).ok())
+			.and_then(NonZeroUsize::new),
+		max_items_per_second: max_entries_per_second.and_then(NonZeroU32::new),
+		paused: match run_mode {
+			IndexerRunMode::Always => false,
+			IndexerRunMode::PauseOnBattery => power_state.on_battery,
+			IndexerRunMode::OnlyWhenIdle => !power_state.idle,
+		},
+	}
+}
@@ -1,6 +1,15 @@
 pub mod config;
 mod hardware;
+#[cfg(feature = "ai")]
+mod image_labeler;
+mod indexer_throttles;
 mod platform;
+mod power;
+mod reverse_geocoder;
 
 pub use hardware::*;
+#[cfg(feature = "ai")]
+pub use image_labeler::ImageLabelerModel;
+pub use indexer_throttles::IndexerThrottles;
 pub use platform::*;
+pub use reverse_geocoder::{resolve_missing_places, ReverseGeocoderDataset};
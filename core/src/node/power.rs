@@ -0,0 +1,95 @@
+use sysinfo::{CpuExt, Pid, ProcessExt, ProcessRefreshKind, System, SystemExt};
+
+/// Below this CPU usage, in percent and not counting our own process, the machine is considered idle
+const IDLE_CPU_USAGE_THRESHOLD: f32 = 20.0;
+
+/// If the machine has a battery and no external power source plugged in
+pub async fn is_on_battery() -> bool {
+	#[cfg(target_os = "linux")]
+	{
+		use tokio::fs;
+
+		let Ok(mut entries) = fs::read_dir("/sys/class/power_supply").await else {
+			return false;
+		};
+
+		let (mut has_battery, mut has_external_power) = (false, false);
+
+		while let Ok(Some(entry)) = entries.next_entry().await {
+			let path = entry.path();
+			let read = |name| fs::read_to_string(path.join(name));
+
+			match read("type").await.as_deref().map(str::trim) {
+				// Batteries of peripherals, like mice and keyboards, have the "Device" scope
+				Ok("Battery") => {
+					has_battery |= read("scope")
+						.await
+						.map_or(true, |scope| scope.trim() != "Device");
+				}
+				Ok("Mains" | "USB") => {
+					has_external_power |= read("online")
+						.await
+						.is_ok_and(|online| online.trim() == "1");
+				}
+				_ => {}
+			}
+		}
+
+		has_battery && !has_external_power
+	}
+
+	#[cfg(target_os = "macos")]
+	{
+		use tokio::process::Command;
+
+		Command::new("pmset")
+			.args(["-g", "batt"])
+			.output()
+			.await
+			.is_ok_and(|output| {
+				output.status.success()
+					&& String::from_utf8_lossy(&output.stdout).contains("'Battery Power'")
+			})
+	}
+
+	#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+	{
+		// TODO: Check the power source on Windows and mobile platforms
+		false
+	}
+}
+
+/// Tells if the machine is idle from the CPU usage between consecutive checks
+pub struct IdleDetector {
+	system: System,
+	pid: Option<Pid>,
+}
+
+impl IdleDetector {
+	pub fn new() -> Self {
+		Self {
+			system: System::new(),
+			pid: sysinfo::get_current_pid().ok(),
+		}
+	}
+
+	/// The first check always reports idle, as there is nothing to compare with yet
+	pub fn is_idle(&mut self) -> bool {
+		self.system.refresh_cpu();
+
+		// Our own usage doesn't count, otherwise indexing would keep pausing itself
+		let own_usage = self
+			.pid
+			.and_then(|pid| {
+				self.system
+					.refresh_process_specifics(pid, ProcessRefreshKind::new());
+				self.system.process(pid).map(ProcessExt::cpu_usage)
+			})
+			.unwrap_or_default();
+
+		let cpus_count = self.system.cpus().len().max(1) as f32;
+
+		self.system.global_cpu_info().cpu_usage() - own_usage / cpus_count
+			< IDLE_CPU_USAGE_THRESHOLD
+	}
+}
@@ -0,0 +1,82 @@
+use crate::{invalidate_query, library::Libraries};
+
+use sd_core_heavy_lifting::media_processor::exif_media_data;
+
+use sd_media_metadata::{exif::ReverseGeocoder, Error};
+
+use std::{
+	path::Path,
+	sync::{Arc, PoisonError, RwLock},
+};
+
+use tracing::{error, info, warn};
+
+/// The reverse geocoder dataset currently loaded by this node, shared with the media processor.
+///
+/// Without a dataset, media is still processed but the places where photos were taken aren't named.
+#[derive(Debug, Default)]
+pub struct ReverseGeocoderDataset(RwLock<Option<Arc<ReverseGeocoder>>>);
+
+impl ReverseGeocoderDataset {
+	/// The loaded geocoder, if any dataset loaded successfully
+	pub fn get(&self) -> Option<Arc<ReverseGeocoder>> {
+		self.0
+			.read()
+			.unwrap_or_else(PoisonError::into_inner)
+			.clone()
+	}
+
+	/// Loads the dataset supplied by the user, or the one installed next to the app, replacing the
+	/// current geocoder
+	pub async fn load(
+		&self,
+		custom_dataset_path: Option<&Path>,
+	) -> Result<Arc<ReverseGeocoder>, Error> {
+		let res = if let Some(path) = custom_dataset_path {
+			ReverseGeocoder::load(path).await
+		} else {
+			ReverseGeocoder::load(ReverseGeocoder::default_dataset_path()).await
+		}
+		.map(Arc::new);
+
+		let mut geocoder = self.0.write().unwrap_or_else(PoisonError::into_inner);
+
+		match res {
+			Ok(new_geocoder) => {
+				info!(
+					places = new_geocoder.len(),
+					"Reverse geocoder dataset loaded;"
+				);
+				*geocoder = Some(Arc::clone(&new_geocoder));
+				Ok(new_geocoder)
+			}
+			Err(e) => {
+				if custom_dataset_path.is_some() {
+					error!(?e, "Failed to load reverse geocoder dataset;");
+				} else {
+					// No dataset ships with the app, so it's only worth a warning
+					warn!(?e, "Failed to load installed reverse geocoder dataset;");
+				}
+				*geocoder = None;
+				Err(e)
+			}
+		}
+	}
+}
+
+/// Names the places of media on every library that has a location but no place yet, like the
+/// media processed before a dataset was loaded
+pub async fn resolve_missing_places(reverse_geocoder: &ReverseGeocoder, libraries: &Libraries) {
+	for library in libraries.get_all().await {
+		match exif_media_data::resolve_missing_places(reverse_geocoder, &library.db, &library.sync)
+			.await
+		{
+			Ok(0) => {}
+			Ok(resolved) => {
+				info!(library_id = %library.id, %resolved, "Named places of media;");
+				invalidate_query!(library, "files.getMediaData");
+			}
+			Err(e) => error!(library_id = %library.id, ?e, "Failed to name places of media;"),
+		}
+	}
+}
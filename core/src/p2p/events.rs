@@ -58,6 +58,8 @@ pub enum P2PEvent {
 	},
 	SpacedropProgress {
 		id: Uuid,
+		// The peer on the other end of the transfer, so progress can be shown per peer when Spacedropping to many
+		identity: RemoteIdentity,
 		percent: u8,
 	},
 	SpacedropTimedOut {
@@ -66,6 +68,11 @@ pub enum P2PEvent {
 	SpacedropRejected {
 		id: Uuid,
 	},
+	// A peer was added to the trusted peers by pairing with it
+	PeerPaired {
+		identity: RemoteIdentity,
+		name: String,
+	},
 }
 
 /// A P2P hook which listens for events and sends them over a channel which can be connected to the frontend.
@@ -1,6 +1,6 @@
 use crate::{
 	node::{
-		config::{self, P2PDiscoveryState},
+		config::{self, NodeConfigP2P, P2PDiscoveryState},
 		get_hardware_model_name, HardwareModel,
 	},
 	p2p::{
@@ -11,6 +11,7 @@ use crate::{
 };
 
 use axum::routing::IntoMakeService;
+use chrono::{Local, Timelike};
 
 use sd_p2p::{
 	flume::{bounded, Receiver},
@@ -30,8 +31,12 @@ use std::{
 use tower_service::Service;
 use tracing::error;
 
-use tokio::sync::{oneshot, Notify};
-use tracing::info;
+use tokio::{
+	io::AsyncWriteExt,
+	sync::{oneshot, Notify},
+	time::Instant,
+};
+use tracing::{info, warn};
 use uuid::Uuid;
 
 use super::{P2PEvents, PeerMetadata};
@@ -57,13 +62,16 @@ pub struct Listeners {
 pub struct P2PManager {
 	pub(crate) p2p: Arc<P2P>,
 	mdns: Mutex<Option<Mdns>>,
-	quic_transport: QuicTransport,
+	pub(super) quic_transport: QuicTransport,
 	pub quic: Arc<QuicHandle>,
 	// The `libp2p::PeerId`. This is for debugging only, use `RemoteIdentity` instead.
 	lp2p_peer_id: Libp2pPeerId,
 	pub(crate) events: P2PEvents,
 	pub(super) spacedrop_pairing_reqs: Arc<Mutex<HashMap<Uuid, oneshot::Sender<Option<String>>>>>,
 	pub(super) spacedrop_cancellations: Arc<Mutex<HashMap<Uuid, Arc<AtomicBool>>>>,
+	// Secrets of the pairing codes we have handed out and when they were created
+	pub(super) pairing_sessions: Mutex<HashMap<Uuid, Instant>>,
+	pub(super) rspc_pool: operations::rspc::RemoteRspcPool,
 	pub(crate) node_config: Arc<config::Manager>,
 	pub listeners: Mutex<Listeners>,
 	relay_config: Mutex<Vec<RelayServerEntry>>,
@@ -94,6 +102,8 @@ impl P2PManager {
 			quic_transport: quic,
 			spacedrop_pairing_reqs: Default::default(),
 			spacedrop_cancellations: Default::default(),
+			pairing_sessions: Default::default(),
+			rspc_pool: Default::default(),
 			node_config,
 			listeners: Default::default(),
 			relay_config: Default::default(),
@@ -111,6 +121,17 @@ impl P2PManager {
 		Ok((this.clone(), |node: Arc<Node>, router| {
 			tokio::spawn(start(this.clone(), node.clone(), rx, router));
 
+			// Move between the windows of the bandwidth schedule as the time of day changes
+			tokio::spawn({
+				let this = this.clone();
+				async move {
+					loop {
+						tokio::time::sleep(Duration::from_secs(60)).await;
+						this.apply_bandwidth_limit(&this.node_config.get().await.p2p);
+					}
+				}
+			});
+
 			// TODO: Cleanup this thread on p2p shutdown.
 			tokio::spawn(async move {
 				let client = reqwest::Client::new();
@@ -189,6 +210,25 @@ impl P2PManager {
 		}))
 	}
 
+	/// Apply the bandwidth limit for the current time of day to all streams.
+	fn apply_bandwidth_limit(&self, config: &NodeConfigP2P) {
+		let now = Local::now();
+		let limit = config.active_bandwidth_limit((now.hour() * 60 + now.minute()) as u16);
+		// A limit of 0 means unlimited, which is how the rate limiters report it back
+		let (upload, download) = (
+			limit.upload.filter(|rate| *rate != 0),
+			limit.download.filter(|rate| *rate != 0),
+		);
+
+		let bandwidth = self.p2p.bandwidth();
+		// Only update on change so we don't reset the rate limiters every time the schedule is checked
+		if bandwidth.upload.rate() != upload || bandwidth.download.rate() != download {
+			info!(?upload, ?download, "Setting P2P bandwidth limit;");
+			bandwidth.upload.set_rate(upload);
+			bandwidth.download.set_rate(download);
+		}
+	}
+
 	pub fn peer_metadata(&self) -> HashMap<String, String> {
 		self.p2p.metadata().clone()
 	}
@@ -261,7 +301,9 @@ impl P2PManager {
 		};
 
 		self.quic_transport
-			.set_manual_peer_addrs(config.p2p.manual_peers);
+			.set_manual_peer_addrs(config.p2p.manual_peer_addrs());
+
+		self.apply_bandwidth_limit(&config.p2p);
 
 		let should_revert = match (config.p2p.disabled, config.p2p.discovery) {
 			(true, _) | (_, P2PDiscoveryState::Disabled) => {
@@ -378,6 +420,35 @@ async fn start(
 				return;
 			};
 
+			if let Some(peer) = this.p2p.peers().get(&stream.remote_identity()).cloned() {
+				header.track(&peer, &mut stream);
+			}
+			header.limit(&this.p2p, &mut stream);
+
+			// These requests expose the users files or library data so they can be restricted to
+			// trusted peers
+			if matches!(
+				header,
+				Header::Spacedrop(_)
+					| Header::RspcRemote
+					| Header::LibraryFile { .. }
+					| Header::Sync
+			) {
+				let config = node.config.get().await;
+				if config.p2p.trusted_peers_only
+					&& !config.p2p.is_trusted(&stream.remote_identity())
+				{
+					warn!(remote = %stream.remote_identity(), "Rejecting request from untrusted peer;");
+
+					if let Header::Spacedrop(_) = header {
+						// Let the sender know the Spacedrop was rejected instead of leaving it hanging
+						stream.write_all(&[0]).await.ok();
+					}
+
+					return;
+				}
+			}
+
 			match header {
 				Header::Ping => operations::ping::receiver(stream).await,
 				Header::Spacedrop(req) => {
@@ -450,6 +521,14 @@ async fn start(
 						"Failed to handling library file request;",
 					);
 				}
+				Header::Pair { secret, name } => {
+					let Err(()) = operations::pairing::receiver(&this, secret, name, stream).await
+					else {
+						return;
+					};
+
+					error!("Failed to handle pairing request");
+				}
 			};
 		});
 	}
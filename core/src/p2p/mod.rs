@@ -7,11 +7,13 @@ mod manager;
 mod metadata;
 pub mod operations;
 mod protocol;
+mod stats;
 pub mod sync;
 
 pub use events::*;
 pub use manager::*;
 pub use metadata::*;
 pub use protocol::*;
+pub use stats::*;
 
 pub(super) const SPACEDRIVE_APP_ID: &str = "sd";
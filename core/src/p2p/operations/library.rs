@@ -31,15 +31,13 @@ pub async fn request_file(
 	let peer = p2p.peers().get(&identity).ok_or("Peer offline")?.clone();
 	let mut stream = peer.new_stream().await?;
 
-	stream
-		.write_all(
-			&Header::LibraryFile {
-				file_path_id,
-				range: range.clone(),
-			}
-			.to_bytes(),
-		)
-		.await?;
+	let header = Header::LibraryFile {
+		file_path_id,
+		range: range.clone(),
+	};
+	header.track(&peer, &mut stream);
+	header.limit(&p2p, &mut stream);
+	stream.write_all(&header.to_bytes()).await?;
 
 	let mut stream = sd_p2p_tunnel::Tunnel::initiator(stream, library_identity).await?;
 
@@ -1,8 +1,13 @@
 pub mod library;
+pub mod pairing;
 pub mod ping;
 pub mod rspc;
 pub mod spacedrop;
+pub mod thumbnail;
 
 pub use library::request_file;
+pub use pairing::pair;
+pub use ping::ping;
 pub use rspc::remote_rspc;
-pub use spacedrop::spacedrop;
+pub use spacedrop::{spacedrop, spacedrop_many};
+pub use thumbnail::{prefetch_thumbnails, remote_thumbnail};
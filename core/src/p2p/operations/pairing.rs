@@ -0,0 +1,401 @@
+use std::{
+	fmt,
+	str::FromStr,
+	sync::{Arc, PoisonError},
+	time::Duration,
+};
+
+use crate::{
+	node::config::{NodeConfigError, TrustedPeer},
+	p2p::{Header, P2PEvent, P2PManager},
+};
+
+use base64::{engine::general_purpose, Engine};
+use chrono::Utc;
+use sd_p2p::{IdentityErr, Peer, RemoteIdentity, UnicastStream, REMOTE_IDENTITY_LEN};
+use sd_p2p_proto::{decode, encode};
+use thiserror::Error;
+use tokio::{
+	io::{AsyncReadExt, AsyncWriteExt},
+	time::{sleep, Instant},
+};
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+/// The amount of time a pairing code can be used for after it was generated
+pub(crate) const PAIRING_CODE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+/// The amount of time to wait for the peer from a pairing code to become reachable
+const PAIRING_CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+/// Bump this if the binary format of `PairingCode` changes
+const PAIRING_CODE_VERSION: u8 = 1;
+
+/// The payload one node shows so another node can pair with it.
+///
+/// It's encoded as URL-safe base64 so it can be copy-pasted between devices or rendered as a QR code by the frontend.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PairingCode {
+	pub identity: RemoteIdentity,
+	/// A one-time secret which proves the code was shown to whoever is trying to pair.
+	pub secret: Uuid,
+	pub name: String,
+	pub addrs: Vec<String>,
+}
+
+#[derive(Debug, Error)]
+pub enum PairingCodeError {
+	#[error("invalid pairing code encoding: {0}")]
+	Encoding(#[from] base64::DecodeError),
+	#[error("pairing code is truncated")]
+	Truncated,
+	#[error("unsupported pairing code version '{0}'")]
+	UnsupportedVersion(u8),
+	#[error("invalid identity in pairing code: {0}")]
+	Identity(#[from] IdentityErr),
+	#[error("invalid secret in pairing code: {0}")]
+	Secret(#[from] uuid::Error),
+	#[error("invalid string in pairing code: {0}")]
+	String(#[from] std::string::FromUtf8Error),
+}
+
+impl fmt::Display for PairingCode {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let mut buf = vec![PAIRING_CODE_VERSION];
+		buf.extend_from_slice(&self.identity.get_bytes());
+		encode::uuid(&mut buf, &self.secret);
+		encode::string(&mut buf, &self.name);
+
+		let addrs = &self.addrs[..self.addrs.len().min(u8::MAX as usize)];
+		buf.push(addrs.len() as u8);
+		for addr in addrs {
+			encode::string(&mut buf, addr);
+		}
+
+		f.write_str(&general_purpose::URL_SAFE_NO_PAD.encode(buf))
+	}
+}
+
+impl FromStr for PairingCode {
+	type Err = PairingCodeError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let bytes = general_purpose::URL_SAFE_NO_PAD.decode(s.trim())?;
+		let mut bytes = bytes.as_slice();
+
+		let version = take(&mut bytes, 1)?[0];
+		if version != PAIRING_CODE_VERSION {
+			return Err(PairingCodeError::UnsupportedVersion(version));
+		}
+
+		let identity = RemoteIdentity::from_bytes(take(&mut bytes, REMOTE_IDENTITY_LEN)?)?;
+		let secret = Uuid::from_slice(take(&mut bytes, 16)?)?;
+		let name = take_string(&mut bytes)?;
+		let addrs = (0..take(&mut bytes, 1)?[0])
+			.map(|_| take_string(&mut bytes))
+			.collect::<Result<Vec<_>, _>>()?;
+
+		Ok(Self {
+			identity,
+			secret,
+			name,
+			addrs,
+		})
+	}
+}
+
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Result<&'a [u8], PairingCodeError> {
+	if bytes.len() < len {
+		return Err(PairingCodeError::Truncated);
+	}
+
+	let (head, tail) = bytes.split_at(len);
+	*bytes = tail;
+	Ok(head)
+}
+
+fn take_string(bytes: &mut &[u8]) -> Result<String, PairingCodeError> {
+	let len = take(bytes, 2).map(|len| u16::from_le_bytes([len[0], len[1]]))?;
+	String::from_utf8(take(bytes, len as usize)?.to_vec()).map_err(Into::into)
+}
+
+#[derive(Debug, Error)]
+pub enum PairingError {
+	#[error("a node can't be paired with itself")]
+	SelfPairing,
+	#[error("peer from pairing code could not be reached")]
+	PeerNotFound,
+	#[error("error creating stream: {0}")]
+	FailedNewStream(#[from] sd_p2p::NewStreamError),
+	#[error("io error during pairing: {0}")]
+	Io(#[from] std::io::Error),
+	#[error("error decoding pairing response: {0}")]
+	Decode(#[from] decode::Error),
+	#[error("pairing code was rejected, it may have expired or already been used")]
+	Rejected,
+	#[error("error saving trusted peer: {0}")]
+	NodeConfig(#[from] NodeConfigError),
+}
+
+impl P2PManager {
+	/// Generate a new one-time pairing code for this node.
+	pub async fn generate_pairing_code(&self) -> PairingCode {
+		let secret = Uuid::new_v4();
+		{
+			let mut sessions = self
+				.pairing_sessions
+				.lock()
+				.unwrap_or_else(PoisonError::into_inner);
+			sessions.retain(|_, created_at| created_at.elapsed() < PAIRING_CODE_TIMEOUT);
+			sessions.insert(secret, Instant::now());
+		}
+
+		PairingCode {
+			identity: self.p2p.remote_identity(),
+			secret,
+			name: self.node_config.get().await.name,
+			addrs: self
+				.p2p
+				.listeners()
+				.into_iter()
+				.flat_map(|listener| listener.addrs)
+				.map(|addr| addr.to_string())
+				.collect(),
+		}
+	}
+
+	pub async fn rename_trusted_peer(
+		&self,
+		identity: RemoteIdentity,
+		name: String,
+	) -> Result<bool, NodeConfigError> {
+		let mut found = false;
+		self.node_config
+			.write(|config| {
+				if let Some(peer) = config.p2p.trusted_peers.get_mut(&identity) {
+					peer.name = name;
+					found = true;
+				}
+			})
+			.await?;
+
+		Ok(found)
+	}
+
+	pub async fn revoke_trusted_peer(
+		&self,
+		identity: RemoteIdentity,
+	) -> Result<bool, NodeConfigError> {
+		let mut found = false;
+		let config = self
+			.node_config
+			.write(|config| found = config.p2p.trusted_peers.remove(&identity).is_some())
+			.await?;
+
+		self.quic_transport
+			.set_manual_peer_addrs(config.p2p.manual_peer_addrs());
+
+		Ok(found)
+	}
+}
+
+/// Pair with the node which generated `code`.
+/// On success both nodes will have stored each other as a trusted peer.
+pub async fn pair(p2p: Arc<P2PManager>, code: PairingCode) -> Result<TrustedPeer, PairingError> {
+	if code.identity == p2p.p2p.remote_identity() {
+		return Err(PairingError::SelfPairing);
+	}
+
+	// Dial the addresses from the code alongside the usual manual peers until we know if pairing succeeded
+	let config = p2p.node_config.get().await;
+	p2p.quic_transport.set_manual_peer_addrs(
+		config
+			.p2p
+			.manual_peer_addrs()
+			.into_iter()
+			.chain(code.addrs.iter().cloned())
+			.collect(),
+	);
+
+	let result = pair_with(&p2p, &code, config.name).await;
+
+	// On success this will include the addresses of the newly trusted peer
+	p2p.quic_transport
+		.set_manual_peer_addrs(p2p.node_config.get().await.p2p.manual_peer_addrs());
+
+	result
+}
+
+async fn pair_with(
+	p2p: &Arc<P2PManager>,
+	code: &PairingCode,
+	name: String,
+) -> Result<TrustedPeer, PairingError> {
+	let identity = code.identity;
+	debug!(peer = %identity, "Waiting for peer to become reachable for pairing;");
+	let peer = wait_for_peer(p2p, identity).await?;
+
+	let mut stream = peer.new_stream().await?;
+	let header = Header::Pair {
+		secret: code.secret,
+		name,
+	};
+	header.track(&peer, &mut stream);
+	header.limit(&p2p.p2p, &mut stream);
+	stream.write_all(&header.to_bytes()).await?;
+
+	if stream.read_u8().await? != 1 {
+		debug!(peer = %identity, "Pairing was rejected by;");
+		return Err(PairingError::Rejected);
+	}
+
+	let remote_name = decode::string(&mut stream).await?;
+	let trusted_peer = TrustedPeer {
+		name: if remote_name.is_empty() {
+			code.name.clone()
+		} else {
+			remote_name
+		},
+		addrs: code.addrs.iter().cloned().collect(),
+		paired_at: Utc::now(),
+	};
+
+	p2p.node_config
+		.write(|config| {
+			config
+				.p2p
+				.trusted_peers
+				.insert(identity, trusted_peer.clone());
+		})
+		.await?;
+
+	info!(peer = %identity, name = %trusted_peer.name, "Paired with;");
+	p2p.events
+		.send(P2PEvent::PeerPaired {
+			identity,
+			name: trusted_peer.name.clone(),
+		})
+		.ok();
+
+	Ok(trusted_peer)
+}
+
+async fn wait_for_peer(
+	p2p: &P2PManager,
+	identity: RemoteIdentity,
+) -> Result<Arc<Peer>, PairingError> {
+	let start = Instant::now();
+	loop {
+		if let Some(peer) = p2p
+			.p2p
+			.peers()
+			.get(&identity)
+			.filter(|peer| peer.is_connected() || peer.can_connect())
+			.cloned()
+		{
+			return Ok(peer);
+		}
+
+		if start.elapsed() > PAIRING_CONNECT_TIMEOUT {
+			return Err(PairingError::PeerNotFound);
+		}
+
+		sleep(Duration::from_millis(250)).await;
+	}
+}
+
+pub(crate) async fn receiver(
+	this: &Arc<P2PManager>,
+	secret: Uuid,
+	name: String,
+	mut stream: UnicastStream,
+) -> Result<(), ()> {
+	let identity = stream.remote_identity();
+
+	// Codes are single use so we remove it regardless of whether it has expired
+	let is_valid = this
+		.pairing_sessions
+		.lock()
+		.unwrap_or_else(PoisonError::into_inner)
+		.remove(&secret)
+		.is_some_and(|created_at| created_at.elapsed() < PAIRING_CODE_TIMEOUT);
+
+	if !is_valid {
+		warn!(peer = %identity, "Rejecting pairing request with an unknown or expired code;");
+
+		stream.write_all(&[0]).await.map_err(|e| {
+			error!(peer = %identity, ?e, "Error sending pairing rejection;");
+		})?;
+		stream.flush().await.map_err(|e| {
+			error!(peer = %identity, ?e, "Error flushing pairing rejection;");
+		})?;
+
+		return Ok(());
+	}
+
+	let addrs = this
+		.p2p
+		.peers()
+		.get(&identity)
+		.map(|peer| peer.addrs().iter().map(ToString::to_string).collect())
+		.unwrap_or_default();
+
+	let config = this
+		.node_config
+		.write(|config| {
+			config.p2p.trusted_peers.insert(
+				identity,
+				TrustedPeer {
+					name: name.clone(),
+					addrs,
+					paired_at: Utc::now(),
+				},
+			);
+		})
+		.await
+		.map_err(|e| {
+			error!(peer = %identity, ?e, "Failed to save trusted peer;");
+		})?;
+
+	this.quic_transport
+		.set_manual_peer_addrs(config.p2p.manual_peer_addrs());
+
+	let mut buf = vec![1];
+	encode::string(&mut buf, &config.name);
+	stream.write_all(&buf).await.map_err(|e| {
+		error!(peer = %identity, ?e, "Error sending pairing acceptance;");
+	})?;
+	stream.flush().await.map_err(|e| {
+		error!(peer = %identity, ?e, "Error flushing pairing acceptance;");
+	})?;
+
+	info!(peer = %identity, %name, "Paired with;");
+	this.events
+		.send(P2PEvent::PeerPaired { identity, name })
+		.ok();
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use sd_p2p::Identity;
+
+	#[test]
+	fn test_pairing_code() {
+		let original = PairingCode {
+			identity: Identity::new().to_remote_identity(),
+			secret: Uuid::new_v4(),
+			name: "Oscar's MacBook".into(),
+			addrs: vec!["192.168.1.5:7373".into(), "[fe80::1]:7373".into()],
+		};
+
+		let result = original.to_string().parse::<PairingCode>().unwrap();
+		assert_eq!(original, result);
+
+		assert!(matches!(
+			"AQ".parse::<PairingCode>(),
+			Err(PairingCodeError::Truncated)
+		));
+	}
+}
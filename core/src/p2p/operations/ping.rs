@@ -1,21 +1,26 @@
-use std::{error::Error, sync::Arc};
+use std::{error::Error, sync::Arc, time::Duration};
 
 use sd_p2p::{RemoteIdentity, UnicastStream, P2P};
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::{
+	io::{AsyncReadExt, AsyncWriteExt},
+	time::Instant,
+};
 use tracing::debug;
 
 use crate::p2p::Header;
 
-/// Send a ping to all peers we are connected to
-#[allow(unused)]
-pub async fn ping(p2p: Arc<P2P>, identity: RemoteIdentity) -> Result<(), Box<dyn Error>> {
+/// Send a ping to a peer, returning and recording the round trip time
+pub async fn ping(p2p: Arc<P2P>, identity: RemoteIdentity) -> Result<Duration, Box<dyn Error>> {
 	let peer = p2p
 		.peers()
 		.get(&identity)
 		.ok_or("Peer not found, has it been discovered?")?
 		.clone();
 	let mut stream = peer.new_stream().await?;
+	Header::Ping.track(&peer, &mut stream);
+	Header::Ping.limit(&p2p, &mut stream);
 
+	let start = Instant::now();
 	stream.write_all(&Header::Ping.to_bytes()).await?;
 
 	let mut result = [0; 4];
@@ -24,7 +29,10 @@ pub async fn ping(p2p: Arc<P2P>, identity: RemoteIdentity) -> Result<(), Box<dyn
 		return Err("Failed to receive pong".into());
 	}
 
-	Ok(())
+	let rtt = start.elapsed();
+	peer.set_rtt(rtt);
+
+	Ok(rtt)
 }
 
 pub(crate) async fn receiver(mut stream: UnicastStream) {
@@ -1,38 +1,126 @@
-use std::{error::Error, sync::Arc};
+use std::{
+	collections::HashMap,
+	error::Error,
+	future::Future,
+	sync::{Mutex, PoisonError},
+};
 
-use axum::{extract::Request, http, Router};
-use hyper::{body::Incoming, client::conn::http1::handshake, server::conn::http1, Response};
+use axum::{body::Body, extract::Request, http, Router};
+use hyper::{
+	body::Incoming,
+	client::conn::http1::{handshake, SendRequest},
+	server::conn::http1,
+	Response,
+};
 use hyper_util::rt::TokioIo;
 use sd_p2p::{RemoteIdentity, UnicastStream, P2P};
 use tokio::io::AsyncWriteExt;
 use tower_service::Service;
-use tracing::debug;
+use tracing::{debug, warn};
 
-use crate::{p2p::Header, Node};
+use crate::{
+	p2p::{Header, P2PManager},
+	Node,
+};
+
+/// The maximum amount of idle connections kept open to a single peer
+const MAX_POOLED_CONNECTIONS_PER_PEER: usize = 8;
+
+/// HTTP connections to remote nodes which are kept alive to be reused between requests.
+///
+/// Each connection is it's own stream so they are multiplexed over the single QUIC connection with the peer.
+#[derive(Debug, Default)]
+pub struct RemoteRspcPool {
+	connections: Mutex<HashMap<RemoteIdentity, Vec<SendRequest<Body>>>>,
+}
+
+impl RemoteRspcPool {
+	/// Send `request` on an idle pooled connection, if one is available.
+	/// If every connection is busy the request is given back.
+	fn try_send(
+		&self,
+		identity: RemoteIdentity,
+		request: http::Request<Body>,
+	) -> Result<impl Future<Output = hyper::Result<Response<Incoming>>>, http::Request<Body>> {
+		let mut connections = self
+			.connections
+			.lock()
+			.unwrap_or_else(PoisonError::into_inner);
+		let Some(senders) = connections.get_mut(&identity) else {
+			return Err(request);
+		};
+
+		senders.retain(|sender| !sender.is_closed());
+		match senders.iter_mut().find(|sender| sender.is_ready()) {
+			Some(sender) => Ok(sender.send_request(request)),
+			None => Err(request),
+		}
+	}
+
+	/// Keep `sender` around for future requests if there is space in the pool.
+	fn insert(
+		&self,
+		identity: RemoteIdentity,
+		sender: SendRequest<Body>,
+	) -> Option<SendRequest<Body>> {
+		let mut connections = self
+			.connections
+			.lock()
+			.unwrap_or_else(PoisonError::into_inner);
+		let senders = connections.entry(identity).or_default();
+
+		senders.retain(|sender| !sender.is_closed());
+		if senders.len() >= MAX_POOLED_CONNECTIONS_PER_PEER {
+			return Some(sender);
+		}
+
+		senders.push(sender);
+		None
+	}
+}
 
 /// Transfer an rspc query to a remote node.
+///
+/// Connections are pooled so sequential requests (Eg. loading thumbnails while browsing) don't each pay for a new stream.
 pub async fn remote_rspc(
-	p2p: Arc<P2P>,
+	p2p: &P2PManager,
 	identity: RemoteIdentity,
-	request: http::Request<axum::body::Body>,
+	request: http::Request<Body>,
 ) -> Result<Response<Incoming>, Box<dyn Error>> {
+	let request = match p2p.rspc_pool.try_send(identity, request) {
+		Ok(response) => return response.await.map_err(Into::into),
+		Err(request) => request,
+	};
+
+	let mut sender = connect(&p2p.p2p, identity).await?;
+	let response = sender.send_request(request);
+
+	// If the pool is full this connection will be closed once the response has been consumed
+	drop(p2p.rspc_pool.insert(identity, sender));
+
+	response.await.map_err(Into::into)
+}
+
+async fn connect(p2p: &P2P, identity: RemoteIdentity) -> Result<SendRequest<Body>, Box<dyn Error>> {
 	let peer = p2p
 		.peers()
 		.get(&identity)
 		.ok_or("Peer not found, has it been discovered?")?
 		.clone();
 	let mut stream = peer.new_stream().await?;
+	Header::RspcRemote.track(&peer, &mut stream);
+	Header::RspcRemote.limit(p2p, &mut stream);
 
 	stream.write_all(&Header::RspcRemote.to_bytes()).await?;
 
-	let (mut sender, conn) = handshake(TokioIo::new(stream)).await?;
+	let (sender, conn) = handshake(TokioIo::new(stream)).await?;
 	tokio::task::spawn(async move {
-		if let Err(e) = conn.await {
-			println!("Connection error: {:?}", e);
+		if let Err(e) = conn.with_upgrades().await {
+			warn!(%identity, ?e, "Remote rspc connection error;");
 		}
 	});
 
-	sender.send_request(request).await.map_err(Into::into)
+	Ok(sender)
 }
 
 pub(crate) async fn receiver(
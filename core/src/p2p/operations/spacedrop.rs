@@ -14,7 +14,7 @@ use sd_p2p::{RemoteIdentity, UnicastStream};
 use sd_p2p_block::{BlockSize, Range, SpaceblockRequest, SpaceblockRequests, Transfer};
 use thiserror::Error;
 use tokio::{
-	fs::{create_dir_all, File},
+	fs::{self, create_dir_all, File},
 	io::{AsyncReadExt, AsyncWriteExt, BufReader, BufWriter},
 	sync::oneshot,
 	time::{sleep, Instant},
@@ -29,6 +29,10 @@ pub(crate) const SPACEDROP_TIMEOUT: Duration = Duration::from_secs(60);
 pub enum SpacedropError {
 	#[error("paths argument is an empty vector")]
 	EmptyPath,
+	#[error("identities argument is an empty vector")]
+	EmptyIdentities,
+	#[error("can't Spacedrop more than {} files at once", u16::MAX)]
+	TooManyFiles,
 	#[error("error connecting to peer")]
 	FailedPeerConnection,
 	#[error("error creating stream: {0}")]
@@ -37,40 +41,112 @@ pub enum SpacedropError {
 	FailedFileOpen(#[from] std::io::Error),
 }
 
+/// A file which is going to be sent and the request describing it to the remote peer.
+type SpacedropFile = (PathBuf, SpaceblockRequest);
+
 pub async fn spacedrop(
 	p2p: Arc<P2PManager>,
 	identity: RemoteIdentity,
 	paths: Vec<PathBuf>,
 ) -> Result<Uuid, SpacedropError> {
+	let files = collect_files(paths).await?;
+
+	start(p2p, identity, Arc::new(files)).await
+}
+
+/// Spacedrop the same files to multiple peers at once.
+///
+/// Each peer gets it's own Spacedrop id so the transfers can be accepted, tracked and cancelled independently.
+pub async fn spacedrop_many(
+	p2p: Arc<P2PManager>,
+	identities: Vec<RemoteIdentity>,
+	paths: Vec<PathBuf>,
+) -> Result<Vec<(RemoteIdentity, Result<Uuid, SpacedropError>)>, SpacedropError> {
+	if identities.is_empty() {
+		return Err(SpacedropError::EmptyIdentities);
+	}
+
+	let files = Arc::new(collect_files(paths).await?);
+
+	Ok(join_all(identities.into_iter().map(|identity| {
+		let p2p = p2p.clone();
+		let files = files.clone();
+		async move { (identity, start(p2p, identity, files).await) }
+	}))
+	.await)
+}
+
+/// Resolve `paths` into the list of files to send.
+///
+/// Directories are walked recursively and every file within them is named by it's path relative to the parent of the directory,
+/// so the receiver can reconstruct the tree. Symlinks within directories are skipped.
+async fn collect_files(paths: Vec<PathBuf>) -> Result<Vec<SpacedropFile>, SpacedropError> {
 	if paths.is_empty() {
 		return Err(SpacedropError::EmptyPath);
 	}
 
-	let (files, requests): (Vec<_>, Vec<_>) = join_all(paths.into_iter().map(|path| async move {
-		let file = File::open(&path).await?;
-		let metadata = file.metadata().await?;
+	let mut files = Vec::new();
+	for path in paths {
 		let name = path
 			.file_name()
 			.map(|v| v.to_string_lossy())
 			.unwrap_or(Cow::Borrowed(""))
 			.to_string();
 
-		Ok((
-			(path, file),
-			SpaceblockRequest {
-				name,
-				size: metadata.len(),
-				range: Range::Full,
-			},
-		))
-	}))
-	.await
-	.into_iter()
-	.collect::<Result<Vec<_>, std::io::Error>>()
-	.map_err(SpacedropError::FailedFileOpen)?
-	.into_iter()
-	.unzip();
+		let metadata = fs::metadata(&path).await?;
+		if !metadata.is_dir() {
+			files.push((
+				path,
+				SpaceblockRequest {
+					name,
+					size: metadata.len(),
+					range: Range::Full,
+				},
+			));
+			continue;
+		}
+
+		let mut to_walk = vec![(path, name)];
+		while let Some((dir, dir_name)) = to_walk.pop() {
+			let mut read_dir = fs::read_dir(&dir).await?;
+			while let Some(entry) = read_dir.next_entry().await? {
+				let entry_name = format!("{dir_name}/{}", entry.file_name().to_string_lossy());
+				let file_type = entry.file_type().await?;
+
+				if file_type.is_dir() {
+					to_walk.push((entry.path(), entry_name));
+				} else if file_type.is_file() {
+					files.push((
+						entry.path(),
+						SpaceblockRequest {
+							name: entry_name,
+							size: entry.metadata().await?.len(),
+							range: Range::Full,
+						},
+					));
+				}
+			}
+		}
+	}
+
+	if files.len() > u16::MAX as usize {
+		return Err(SpacedropError::TooManyFiles);
+	}
+
+	// Make sure every file can be read before a peer is told about them, as a transfer can't skip a file once it started
+	for (path, _) in &files {
+		File::open(path).await?;
+	}
+
+	Ok(files)
+}
 
+async fn start(
+	p2p: Arc<P2PManager>,
+	identity: RemoteIdentity,
+	files: Arc<Vec<SpacedropFile>>,
+) -> Result<Uuid, SpacedropError> {
+	let requests = files.iter().map(|(_, req)| req.clone()).collect::<Vec<_>>();
 	let total_length: u64 = requests.iter().map(|req| req.size).sum();
 
 	let id = Uuid::new_v4();
@@ -97,6 +173,8 @@ pub async fn spacedrop(
 			block_size: BlockSize::from_file_size(total_length),
 			requests,
 		});
+		header.track(&peer, &mut stream);
+		header.limit(&p2p.p2p, &mut stream);
 		if let Err(e) = stream.write_all(&header.to_bytes()).await {
 			debug!(spacedrop_id = %id, ?e, "Failed to send header");
 			return;
@@ -140,13 +218,17 @@ pub async fn spacedrop(
 			&requests,
 			|percent| {
 				p2p.events
-					.send(P2PEvent::SpacedropProgress { id, percent })
+					.send(P2PEvent::SpacedropProgress {
+						id,
+						identity,
+						percent,
+					})
 					.ok();
 			},
 			&cancelled,
 		);
 
-		for (file_id, (path, file)) in files.into_iter().enumerate() {
+		for (file_id, (path, _)) in files.iter().enumerate() {
 			debug!(
 				spacedrop_id = %id,
 				%file_id,
@@ -154,7 +236,22 @@ pub async fn spacedrop(
 				"Transmitting;",
 			);
 
-			let file = BufReader::new(file);
+			let file = match File::open(path).await {
+				Ok(file) => BufReader::new(file),
+				Err(e) => {
+					debug!(
+						spacedrop_id = %id,
+						%file_id,
+						?e,
+						"Failed to open file;");
+					// The file was removed since we checked it, so we close the stream to let the receiver know
+					// the transfer won't continue instead of having it wait for the file forever
+					stream.shutdown().await.ok();
+					// TODO: Error to frontend
+					return;
+				}
+			};
+
 			if let Err(e) = transfer.send(&mut stream, file).await {
 				debug!(
 					spacedrop_id = %id,
@@ -234,6 +331,26 @@ pub(crate) async fn receiver(
 		block_size = ?req.block_size,
 		"Receiving spacedrop files;",
 	);
+
+	// Validate the paths up front as we don't wanna accept a transfer we can't save
+	let Some(relative_paths) = req
+		.requests
+		.iter()
+		.map(|req| req.relative_path())
+		.collect::<Option<Vec<_>>>()
+	else {
+		warn!(spacedrop_id = %id, "Rejecting Spacedrop with an invalid file path;");
+
+		stream.write_all(&[0]).await.map_err(|e| {
+			error!(spacedrop_id = %id, ?e, "Error sending rejection;");
+		})?;
+		stream.flush().await.map_err(|e| {
+			error!(spacedrop_id = %id, ?e, "Error flushing rejection;");
+		})?;
+
+		return Ok(());
+	};
+
 	this.spacedrop_pairing_reqs
 		.lock()
 		.unwrap_or_else(PoisonError::into_inner)
@@ -297,19 +414,21 @@ pub(crate) async fn receiver(
 						// TODO: make sure the other peer times out or we retry???
 					})?;
 
-					let names = req.requests.iter().map(|req| req.name.clone()).collect::<Vec<_>>();
+					let identity = stream.remote_identity();
 					let mut transfer = Transfer::new(&req, |percent| {
-						this.events.send(P2PEvent::SpacedropProgress { id, percent }).ok();
+						this.events.send(P2PEvent::SpacedropProgress { id, identity, percent }).ok();
 					}, &cancelled);
 
 					let file_path = PathBuf::from(file_path);
-					let names_len = names.len();
-					for file_name in names {
-						 // When transferring more than 1 file we wanna join the incoming file name to the directory provided by the user
-						 let mut path = file_path.clone();
-						 if names_len != 1 {
-							// We know the `file_path` will be a directory so we can just push the file name to it
-							path.push(&file_name);
+					// When transferring more than 1 file or a directory the user provided a directory to save into
+					let is_directory = req.requests.len() != 1
+						|| req.requests.iter().any(|req| req.name.contains('/'));
+					for (request, relative_path) in req.requests.iter().zip(&relative_paths) {
+						let file_name = &request.name;
+						let mut path = file_path.clone();
+						if is_directory {
+							// We join the relative path of the incoming file, which will recreate the senders directory structure
+							path.push(relative_path);
 						}
 
 						debug!(
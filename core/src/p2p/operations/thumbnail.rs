@@ -0,0 +1,118 @@
+use std::{error::Error, path::PathBuf, sync::Arc};
+
+use axum::body::{to_bytes, Body};
+use base64::prelude::*;
+use futures::{stream, StreamExt};
+use hyper::{Request, StatusCode};
+use image::ImageFormat;
+use sd_core_heavy_lifting::media_processor::{
+	get_shard_hex, get_thumbnails_directory, ThumbnailKind, WEBP_EXTENSION,
+};
+use sd_core_prisma_helpers::CasId;
+use sd_p2p::RemoteIdentity;
+use tokio::{fs, task::spawn_blocking};
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use crate::Node;
+
+use super::remote_rspc;
+
+/// Thumbnails are small so we buffer them entirely before saving them to the cache
+const MAX_THUMBNAIL_SIZE: usize = 10 * 1024 * 1024;
+/// The amount of thumbnails fetched from a peer at the same time when prefetching
+const PREFETCH_CONCURRENCY: usize = 4;
+/// Directory within the thumbnails directory where the thumbnails of remote libraries are cached, per peer
+const REMOTE_THUMBNAILS_DIR: &str = "remote";
+
+/// Get the path to the thumbnail for `cas_id` of a remote library, fetching it from the remote node if it isn't cached yet.
+///
+/// Thumbnails are content addressed so one we generated ourselves is used when we have it. Otherwise the thumbnail is cached
+/// apart from ours, per peer, so a peer can never shadow the thumbnails of our own libraries or the ones of another peer.
+pub async fn remote_thumbnail(
+	node: &Node,
+	identity: RemoteIdentity,
+	library_id: Uuid,
+	cas_id: &CasId<'_>,
+) -> Result<PathBuf, Box<dyn Error>> {
+	// `cas_id`s are hexadecimal hashes, and we need at least 3 characters for the shard directory
+	if cas_id.as_str().len() < 3 || !cas_id.as_str().chars().all(|c| c.is_ascii_alphanumeric()) {
+		return Err(format!("invalid cas_id '{}'", cas_id.as_str()).into());
+	}
+
+	let local_path =
+		ThumbnailKind::Indexed(library_id).compute_path(node.config.data_directory(), cas_id);
+	if fs::try_exists(&local_path).await? {
+		return Ok(local_path);
+	}
+
+	let mut path = get_thumbnails_directory(node.config.data_directory());
+	path.push(REMOTE_THUMBNAILS_DIR);
+	path.push(BASE64_URL_SAFE_NO_PAD.encode(identity.get_bytes()));
+	path.push(library_id.to_string());
+	path.push(get_shard_hex(cas_id));
+	path.push(format!("{}.{WEBP_EXTENSION}", cas_id.as_str()));
+	if fs::try_exists(&path).await? {
+		return Ok(path);
+	}
+
+	let response = remote_rspc(
+		&node.p2p,
+		identity,
+		Request::get(format!(
+			"/uri/thumbnail/{library_id}/{}/{}.{WEBP_EXTENSION}",
+			get_shard_hex(cas_id),
+			cas_id.as_str()
+		))
+		.body(Body::empty())?,
+	)
+	.await?;
+
+	if response.status() != StatusCode::OK {
+		return Err(format!("remote node responded with status '{}'", response.status()).into());
+	}
+
+	let bytes = to_bytes(Body::new(response.into_body()), MAX_THUMBNAIL_SIZE).await?;
+
+	// We don't trust the peer to send us a thumbnail, so we make sure it's one before caching it
+	let bytes = spawn_blocking(move || {
+		image::load_from_memory_with_format(&bytes, ImageFormat::WebP).map(|_| bytes)
+	})
+	.await??;
+
+	if let Some(parent) = path.parent() {
+		fs::create_dir_all(parent).await?;
+	}
+
+	// Write to a temporary file first so a concurrent request never sees a partial thumbnail
+	let tmp_path = path.with_extension(format!("{}.tmp", Uuid::new_v4()));
+	fs::write(&tmp_path, &bytes).await?;
+	if let Err(e) = fs::rename(&tmp_path, &path).await {
+		fs::remove_file(&tmp_path).await.ok();
+		return Err(e.into());
+	}
+
+	debug!(%identity, %library_id, cas_id = cas_id.as_str(), "Cached remote thumbnail;");
+
+	Ok(path)
+}
+
+/// Fetch the thumbnails for a directory listing of a remote library ahead of them being displayed.
+pub async fn prefetch_thumbnails(
+	node: Arc<Node>,
+	identity: RemoteIdentity,
+	library_id: Uuid,
+	cas_ids: Vec<String>,
+) {
+	stream::iter(cas_ids)
+		.for_each_concurrent(PREFETCH_CONCURRENCY, |cas_id| {
+			let node = &node;
+			async move {
+				let cas_id = CasId::from(cas_id);
+				if let Err(e) = remote_thumbnail(node, identity, library_id, &cas_id).await {
+					warn!(%identity, cas_id = cas_id.as_str(), %e, "Failed to prefetch remote thumbnail;");
+				}
+			}
+		})
+		.await;
+}
@@ -1,3 +1,4 @@
+use sd_p2p::{Peer, Priority, UnicastStream, P2P};
 use sd_p2p_block::{Range, SpaceblockRequests, SpaceblockRequestsError};
 use sd_p2p_proto::{decode, encode};
 use thiserror::Error;
@@ -21,6 +22,11 @@ pub enum Header {
 		file_path_id: Uuid,
 		range: Range,
 	},
+	// Pair with a node using the secret from it's pairing code
+	Pair {
+		secret: Uuid,
+		name: String,
+	},
 }
 
 #[derive(Debug, Error)]
@@ -37,6 +43,8 @@ pub enum HeaderError {
 	LibraryFileIoError(std::io::Error),
 	#[error("invalid range discriminator for library file req '{0}'")]
 	LibraryDiscriminatorInvalid(u8),
+	#[error("error with pairing request decode '{0}'")]
+	PairDecodeError(decode::Error),
 }
 
 impl Header {
@@ -50,6 +58,9 @@ impl Header {
 			0 => Ok(Self::Spacedrop(
 				SpaceblockRequests::from_stream(stream).await?,
 			)),
+			8 => Ok(Self::Spacedrop(
+				SpaceblockRequests::from_stream_extended(stream).await?,
+			)),
 			1 => Ok(Self::Ping),
 			3 => Ok(Self::Sync),
 			5 => Ok(Self::RspcRemote),
@@ -77,12 +88,57 @@ impl Header {
 					d => return Err(HeaderError::LibraryDiscriminatorInvalid(d)),
 				},
 			}),
+			7 => Ok(Self::Pair {
+				secret: decode::uuid(stream)
+					.await
+					.map_err(HeaderError::PairDecodeError)?,
+				name: decode::string(stream)
+					.await
+					.map_err(HeaderError::PairDecodeError)?,
+			}),
 			d => Err(HeaderError::DiscriminatorInvalid(d)),
 		}
 	}
 
+	/// A short name for the protocol, used to group the statistics of a peer's streams.
+	pub fn name(&self) -> &'static str {
+		match self {
+			Self::Ping => "ping",
+			Self::Spacedrop(_) => "spacedrop",
+			Self::Sync => "sync",
+			Self::RspcRemote => "rspc",
+			Self::LibraryFile { .. } => "libraryFile",
+			Self::Pair { .. } => "pair",
+		}
+	}
+
+	/// Account the traffic of `stream` to this protocol in `peer`'s statistics.
+	pub fn track(&self, peer: &Peer, stream: &mut UnicastStream) {
+		stream.track(peer.counters(self.name()));
+	}
+
+	/// Interactive protocols bypass the bandwidth limits so they stay responsive during large transfers.
+	pub fn priority(&self) -> Priority {
+		match self {
+			Self::Ping | Self::RspcRemote | Self::Pair { .. } => Priority::Interactive,
+			Self::Spacedrop(_) | Self::Sync | Self::LibraryFile { .. } => Priority::Bulk,
+		}
+	}
+
+	/// Apply the node's bandwidth limits to `stream` with this protocol's priority.
+	pub fn limit(&self, p2p: &P2P, stream: &mut UnicastStream) {
+		stream.limit(p2p.bandwidth().clone(), self.priority());
+	}
+
 	pub fn to_bytes(&self) -> Vec<u8> {
 		match self {
+			// Older peers only understand up to 255 files, so we only use the extended encoding
+			// when the Spacedrop wouldn't fit otherwise
+			Self::Spacedrop(transfer_request) if transfer_request.needs_extended() => {
+				let mut bytes = vec![8];
+				bytes.extend_from_slice(&transfer_request.to_bytes_extended());
+				bytes
+			}
 			Self::Spacedrop(transfer_request) => {
 				let mut bytes = vec![0];
 				bytes.extend_from_slice(&transfer_request.to_bytes());
@@ -100,6 +156,12 @@ impl Header {
 				buf.extend_from_slice(&range.to_bytes());
 				buf
 			}
+			Self::Pair { secret, name } => {
+				let mut buf = vec![7];
+				encode::uuid(&mut buf, secret);
+				encode::string(&mut buf, name);
+				buf
+			}
 		}
 	}
 }
@@ -128,4 +190,16 @@ mod tests {
 		// 		Ok(Header::Sync(uuid))
 		// 	);
 	}
+
+	#[tokio::test]
+	async fn test_pair_header() {
+		let original = super::Header::Pair {
+			secret: uuid::Uuid::new_v4(),
+			name: "Oscar's MacBook".into(),
+		};
+
+		let mut cursor = std::io::Cursor::new(original.to_bytes());
+		let result = super::Header::from_stream(&mut cursor).await.unwrap();
+		assert_eq!(original, result);
+	}
 }
@@ -0,0 +1,90 @@
+use std::collections::BTreeMap;
+
+use sd_p2p::{Peer, RemoteIdentity, StreamCounters};
+use sd_utils::{u64_to_frontend, U64Front};
+use serde::Serialize;
+use specta::Type;
+
+use super::{ConnectionMethod, P2PManager};
+
+/// Traffic for a single protocol with a peer.
+#[derive(Debug, Clone, Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ProtocolStats {
+	pub active_streams: u32,
+	pub bytes_sent: U64Front,
+	pub bytes_received: U64Front,
+}
+
+impl From<&StreamCounters> for ProtocolStats {
+	fn from(counters: &StreamCounters) -> Self {
+		Self {
+			active_streams: counters.active_streams() as u32,
+			bytes_sent: u64_to_frontend(counters.bytes_sent()),
+			bytes_received: u64_to_frontend(counters.bytes_received()),
+		}
+	}
+}
+
+/// A snapshot of the connection diagnostics for a peer.
+#[derive(Debug, Clone, Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct PeerStats {
+	pub identity: RemoteIdentity,
+	pub connection: ConnectionMethod,
+	/// The round trip time of the last successful ping
+	pub rtt_ms: Option<u32>,
+	pub active_streams: u32,
+	pub bytes_sent: U64Front,
+	pub bytes_received: U64Front,
+	/// Traffic broken down by protocol. Eg. `spacedrop`, `sync`, `rspc`
+	pub protocols: BTreeMap<String, ProtocolStats>,
+	/// The last error which occurred opening a stream to the peer
+	pub last_error: Option<String>,
+}
+
+impl P2PManager {
+	pub fn connection_method(&self, peer: &Peer) -> ConnectionMethod {
+		if !peer.is_connected() {
+			ConnectionMethod::Disconnected
+		} else if self.quic.is_relayed(peer.identity()) {
+			ConnectionMethod::Relay
+		} else {
+			ConnectionMethod::Local
+		}
+	}
+
+	pub fn peer_stats(&self) -> Vec<PeerStats> {
+		self.p2p
+			.peers()
+			.values()
+			.map(|peer| {
+				let counters = peer.all_counters();
+				let (active_streams, bytes_sent, bytes_received) = counters.values().fold(
+					(0, 0, 0),
+					|(active_streams, bytes_sent, bytes_received), c| {
+						(
+							active_streams + c.active_streams(),
+							bytes_sent + c.bytes_sent(),
+							bytes_received + c.bytes_received(),
+						)
+					},
+				);
+
+				PeerStats {
+					identity: peer.identity(),
+					connection: self.connection_method(peer),
+					rtt_ms: peer.rtt().map(|rtt| rtt.as_millis() as u32),
+					active_streams: active_streams as u32,
+					bytes_sent: u64_to_frontend(bytes_sent),
+					bytes_received: u64_to_frontend(bytes_received),
+					protocols: counters
+						.iter()
+						.map(|(name, c)| (name.to_string(), c.as_ref().into()))
+						.collect(),
+					last_error: peer.last_error(),
+				}
+			})
+			.collect()
+	}
+}
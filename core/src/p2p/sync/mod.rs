@@ -96,6 +96,7 @@ mod originator {
 			};
 
 			let sync = sync.clone();
+			let p2p = p2p.p2p.clone();
 
 			let library = library.clone();
 			tokio::spawn(async move {
@@ -106,6 +107,8 @@ mod originator {
 				);
 
 				let mut stream = peer.new_stream().await.unwrap();
+				Header::Sync.track(&peer, &mut stream);
+				Header::Sync.limit(&p2p, &mut stream);
 
 				stream.write_all(&Header::Sync.to_bytes()).await.unwrap();
 
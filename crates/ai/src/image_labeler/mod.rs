@@ -0,0 +1,109 @@
+use sd_utils::error::FileIOError;
+
+use std::{collections::HashMap, fmt, path::Path};
+
+use image::DynamicImage;
+use ort::Session;
+use thiserror::Error;
+use tracing::{info, trace};
+
+mod model;
+
+pub use model::{DownloadModelError, Model, ModelSource, YoloV8, DEFAULT_MODEL_VERSION};
+
+/// Labels found on an image, along with how confident the model is about each of them, from 0 to 1
+pub type Labels = HashMap<String, f32>;
+
+/// An image labeling model loaded and ready to run on the CPU
+pub struct ImageLabeler {
+	model: Box<dyn Model>,
+	session: Session,
+	version: String,
+}
+
+impl ImageLabeler {
+	/// Loads the model from `custom_model_path` when the user supplied one, otherwise from the
+	/// models bundled with the app or previously downloaded to `data_dir`, only downloading it
+	/// when it isn't available locally
+	pub async fn new(
+		model: Box<dyn Model>,
+		custom_model_path: Option<&Path>,
+		data_dir: impl AsRef<Path>,
+	) -> Result<Self, ImageLabelerError> {
+		let (model_path, version) = if let Some(path) = custom_model_path {
+			// Another model file is another model, even if it is meant to replace this version
+			(
+				path.to_path_buf(),
+				format!(
+					"{} ({})",
+					model.version(),
+					path.file_name().unwrap_or_default().to_string_lossy()
+				),
+			)
+		} else {
+			(
+				model::download_model(model.origin(), data_dir.as_ref().join(model.name())).await?,
+				model.version().to_string(),
+			)
+		};
+
+		model::check_model_file(&model_path).await?;
+
+		info!(
+			name = model.name(),
+			%version,
+			model_path = %model_path.display(),
+			"Loading image labeler model;",
+		);
+
+		let session = model::load_model(&model_path)?;
+
+		trace!(?session, "Loaded image labeler model;");
+
+		Ok(Self {
+			model,
+			session,
+			version,
+		})
+	}
+
+	#[must_use]
+	pub fn name(&self) -> &'static str {
+		self.model.name()
+	}
+
+	/// Version of the loaded model, labels generated by other versions must be generated again
+	#[must_use]
+	pub fn version(&self) -> &str {
+		&self.version
+	}
+
+	/// Labels an already decoded image. This is CPU heavy and blocking, so it must be called from
+	/// a blocking thread
+	pub fn labels(&self, image: &DynamicImage) -> Result<Labels, ImageLabelerError> {
+		let inputs = self.model.prepare_input(image)?;
+		let outputs = self.session.run(inputs)?;
+		self.model.process_output(outputs)
+	}
+}
+
+impl fmt::Debug for ImageLabeler {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("ImageLabeler")
+			.field("name", &self.model.name())
+			.field("version", &self.version)
+			.finish_non_exhaustive()
+	}
+}
+
+#[derive(Debug, Error)]
+pub enum ImageLabelerError {
+	#[error("model executor failed: {0}")]
+	ModelExecutorFailed(#[from] ort::Error),
+	#[error("model file not found: {}", .0.display())]
+	ModelFileNotFound(Box<Path>),
+	#[error(transparent)]
+	DownloadModel(#[from] DownloadModelError),
+	#[error(transparent)]
+	FileIO(#[from] FileIOError),
+}
@@ -1,28 +1,26 @@
 use sd_utils::error::FileIOError;
 
-use std::{
-	collections::HashSet,
-	path::{Path, PathBuf},
-};
+use std::path::{Path, PathBuf};
 
 use futures::prelude::stream::StreamExt;
-use image::ImageFormat;
-use ort::{Session, SessionBuilder, SessionInputs, SessionOutputs};
+use image::DynamicImage;
+use ort::{CPUExecutionProvider, Session, SessionBuilder, SessionInputs, SessionOutputs};
 use thiserror::Error;
 use tokio::{
 	fs,
 	io::{self, AsyncWriteExt},
 };
-use tracing::{error, info, trace};
+use tracing::info;
 use url::Url;
 
-use super::ImageLabelerError;
+use super::{ImageLabelerError, Labels};
 
 mod yolov8;
 
 pub use yolov8::YoloV8;
 pub use yolov8::DEFAULT_MODEL_VERSION;
 
+/// Where a model version comes from when the user didn't supply a model file
 pub enum ModelSource {
 	Url(Url),
 	Path(PathBuf),
@@ -41,115 +39,10 @@ pub trait Model: Send + Sync + 'static {
 
 	fn prepare_input<'image>(
 		&self,
-		image_path: &Path,
-		image: &'image [u8],
-		format: ImageFormat,
+		image: &'image DynamicImage,
 	) -> Result<SessionInputs<'image>, ImageLabelerError>;
 
-	fn process_output(
-		&self,
-		output: SessionOutputs<'_>,
-	) -> Result<HashSet<String>, ImageLabelerError>;
-}
-
-pub(super) struct ModelAndSession {
-	maybe_model: Option<Box<dyn Model>>,
-	maybe_session: Option<Session>,
-	model_data_dir: PathBuf,
-}
-
-impl ModelAndSession {
-	pub async fn new(
-		model: Box<dyn Model>,
-		data_dir: impl AsRef<Path>,
-	) -> Result<Self, DownloadModelError> {
-		let data_dir = data_dir.as_ref().join(model.name());
-		let model_path = download_model(model.origin(), &data_dir).await?;
-
-		info!(
-			"Loading mode: {} from {}",
-			model.name(),
-			model_path.display()
-		);
-
-		let maybe_session = check_model_file(&model_path)
-			.await
-			.map_err(|e| error!("Failed to check model file before passing to Ort: {e:#?}"))
-			.ok()
-			.and_then(|()| {
-				load_model(&model_path)
-					.map(|session| {
-						info!("Loaded model: {}", model.name());
-						trace!("{session:#?}");
-						session
-					})
-					.map_err(|e| error!("Failed to load model: {e:#?}"))
-					.ok()
-			});
-
-		Ok(Self {
-			maybe_model: maybe_session.is_some().then_some(model),
-			maybe_session,
-			model_data_dir: data_dir,
-		})
-	}
-
-	pub fn can_process(&self) -> bool {
-		self.maybe_session.is_some() && self.maybe_model.is_some()
-	}
-
-	pub async fn update_model(
-		&mut self,
-		new_model: Box<dyn Model>,
-	) -> Result<(), ImageLabelerError> {
-		info!("Attempting to change image labeler models...");
-
-		let model_path = download_model(new_model.origin(), &self.model_data_dir).await?;
-
-		info!(
-			"Change mode: {} to {}",
-			new_model.name(),
-			model_path.display()
-		);
-
-		check_model_file(&model_path).await.and_then(|()| {
-			load_model(&model_path)
-				.map(|session| {
-					info!(
-						"Changing models: {} -> {}",
-						self.maybe_model
-							.as_ref()
-							.map(|old_model| old_model.name())
-							.unwrap_or("None"),
-						new_model.name()
-					);
-
-					self.maybe_model = Some(new_model);
-					self.maybe_session = Some(session);
-				})
-				.inspect_err(|e| {
-					error!("Failed to load new model: {e:#?}");
-					self.maybe_model = None;
-					self.maybe_session = None;
-				})
-		})
-	}
-
-	pub fn process_single_image(
-		&self,
-		image_path: &Path,
-		image: Vec<u8>,
-		format: ImageFormat,
-	) -> Result<HashSet<String>, ImageLabelerError> {
-		if let (Some(session), Some(model)) = (&self.maybe_session, self.maybe_model.as_deref()) {
-			let inputs = model.prepare_input(image_path, &image, format)?;
-			let outputs = session.run(inputs)?;
-			model.process_output(outputs)
-		} else {
-			error!("Tried to process image without a loaded model");
-			Err(ImageLabelerError::NoModelAvailable)
-		}
-	}
+	fn process_output(&self, output: SessionOutputs<'_>) -> Result<Labels, ImageLabelerError>;
 }
 
 #[derive(Error, Debug)]
@@ -167,15 +60,18 @@ pub enum DownloadModelError {
 	FileIO(#[from] FileIOError),
 }
 
-fn load_model(model_path: impl AsRef<Path>) -> Result<Session, ImageLabelerError> {
+pub(super) fn load_model(model_path: impl AsRef<Path>) -> Result<Session, ImageLabelerError> {
+	// Labeling always runs on the CPU, so it behaves the same on every platform and doesn't
+	// depend on GPU drivers or fight with the UI over the GPU
 	SessionBuilder::new()?
+		.with_execution_providers([CPUExecutionProvider::default().build()])?
 		.with_parallel_execution(true)?
 		.with_memory_pattern(true)?
 		.with_model_from_file(model_path)
 		.map_err(Into::into)
 }
 
-async fn download_model(
+pub(super) async fn download_model(
 	model_origin: &ModelSource,
 	data_dir: impl AsRef<Path>,
 ) -> Result<PathBuf, DownloadModelError> {
@@ -238,17 +134,15 @@ async fn download_model(
 	}
 }
 
-async fn check_model_file(model_path: impl AsRef<Path>) -> Result<(), ImageLabelerError> {
+pub(super) async fn check_model_file(
+	model_path: impl AsRef<Path>,
+) -> Result<(), ImageLabelerError> {
 	let model_path = model_path.as_ref();
 
 	match fs::metadata(model_path).await {
 		Ok(_) => Ok(()),
 		Err(e) if e.kind() == io::ErrorKind::NotFound => {
-			error!(
-				"Model file not found: '{}'. Image labeler will be disabled!",
-				model_path.display()
-			);
-			Ok(())
+			Err(ImageLabelerError::ModelFileNotFound(model_path.into()))
 		}
 		Err(e) => Err(ImageLabelerError::FileIO(FileIOError::from((
 			model_path,
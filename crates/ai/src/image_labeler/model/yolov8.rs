@@ -1,19 +1,14 @@
 use crate::utils::get_path_relative_to_exe;
 
-use std::{
-	collections::{HashMap, HashSet},
-	fmt::Display,
-	path::Path,
-	sync::LazyLock,
-};
+use std::{collections::HashMap, fmt::Display, path::Path, sync::LazyLock};
 
 use half::f16;
-use image::{imageops::FilterType, load_from_memory_with_format, GenericImageView, ImageFormat};
+use image::{imageops::FilterType, DynamicImage, GenericImageView};
 use ndarray::{s, Array, Axis};
 use ort::{inputs, SessionInputs, SessionOutputs};
 use url::Url;
 
-use super::{DownloadModelError, ImageLabelerError, Model, ModelSource};
+use super::{DownloadModelError, ImageLabelerError, Labels, Model, ModelSource};
 
 pub struct YoloV8 {
 	model_origin: &'static ModelSource,
@@ -32,6 +27,9 @@ const MODEL_LOCATION: &str = if cfg!(target_os = "macos") {
 
 pub static DEFAULT_MODEL_VERSION: &str = "Yolo Small";
 
+/// Detections below this confidence are too often wrong to be worth a label
+const MIN_CONFIDENCE: f32 = 0.6;
+
 static MODEL_VERSIONS: LazyLock<HashMap<&'static str, ModelSource>> = LazyLock::new(|| {
 	HashMap::from([
 		("Yolo Nano", ModelSource::Url(Url::parse("https://github.com/spacedriveapp/native-deps/releases/download/yolo-2023-12-05/yolov8n.onnx").expect("Must be a valid URL"))),
@@ -91,14 +89,9 @@ impl Model for YoloV8 {
 
 	fn prepare_input<'image>(
 		&self,
-		path: &Path,
-		image: &'image [u8],
-		format: ImageFormat,
+		image: &'image DynamicImage,
 	) -> Result<SessionInputs<'image>, ImageLabelerError> {
-		let original_img = load_from_memory_with_format(image, format)
-			.map_err(|e| ImageLabelerError::ImageLoadFailed(e, path.into()))?;
-
-		let img = original_img.resize_exact(640, 640, FilterType::CatmullRom);
+		let img = image.resize_exact(640, 640, FilterType::CatmullRom);
 		let mut input = Array::<f16, _>::zeros((1, 3, 640, 640));
 		for pixel in img.pixels() {
 			let x = pixel.0 as _;
@@ -114,10 +107,7 @@ impl Model for YoloV8 {
 			.map_err(Into::into)
 	}
 
-	fn process_output(
-		&self,
-		output: SessionOutputs<'_>,
-	) -> Result<HashSet<String>, ImageLabelerError> {
+	fn process_output(&self, output: SessionOutputs<'_>) -> Result<Labels, ImageLabelerError> {
 		#[rustfmt::skip]
 		const YOLOV8_CLASS_LABELS: [&str; 80] = [
 			"person", "bicycle", "car", "motorcycle", "airplane", "bus", "train", "truck",
@@ -155,14 +145,19 @@ impl Model for YoloV8 {
 					.reduce(|accum, row| if row.1 > accum.1 { row } else { accum })
 					.expect("not empty output")
 			})
-			.filter(|(_, probability)| probability.to_f32() > 0.6)
-			.map(|(class_id, _)| YOLOV8_CLASS_LABELS[class_id])
-			.fold(HashSet::default(), |mut set, label| {
-				if !set.contains(label) {
-					set.insert(label.to_string());
+			.filter_map(|(class_id, probability)| {
+				let confidence = probability.to_f32();
+				(confidence > MIN_CONFIDENCE).then_some((YOLOV8_CLASS_LABELS[class_id], confidence))
+			})
+			// The same label can be detected many times on an image, we keep the most confident one
+			.fold(Labels::new(), |mut labels, (label, confidence)| {
+				if let Some(existing_confidence) = labels.get_mut(label) {
+					*existing_confidence = existing_confidence.max(confidence);
+				} else {
+					labels.insert(label.to_string(), confidence);
 				}
 
-				set
+				labels
 			}))
 	}
 }
@@ -3,7 +3,7 @@ use thiserror::Error;
 use ort::EnvironmentBuilder;
 use tracing::{debug, error};
 
-pub mod old_image_labeler;
+pub mod image_labeler;
 mod utils;
 
 // This path must be relative to the running binary
@@ -27,49 +27,9 @@ pub fn init() -> Result<(), Error> {
 		std::env::set_var("ORT_DYLIB_PATH", path);
 	}
 
-	// Initialize AI stuff
+	// Initialize AI stuff, each session picks its own execution providers
 	EnvironmentBuilder::default()
 		.with_name("spacedrive")
-		.with_execution_providers({
-			#[cfg(any(target_os = "macos", target_os = "ios"))]
-			{
-				use ort::{CoreMLExecutionProvider, XNNPACKExecutionProvider};
-
-				[
-					CoreMLExecutionProvider::default().build(),
-					XNNPACKExecutionProvider::default().build(),
-				]
-			}
-
-			#[cfg(target_os = "windows")]
-			{
-				use ort::DirectMLExecutionProvider;
-
-				[DirectMLExecutionProvider::default().build()]
-			}
-
-			#[cfg(target_os = "linux")]
-			{
-				use ort::XNNPACKExecutionProvider;
-
-				[XNNPACKExecutionProvider::default().build()]
-			}
-
-			// #[cfg(target_os = "android")]
-			// {
-			// 	use ort::{
-			// 		ACLExecutionProvider, ArmNNExecutionProvider, NNAPIExecutionProvider,
-			// 		QNNExecutionProvider, XNNPACKExecutionProvider,
-			// 	};
-			// 	[
-			// 		QNNExecutionProvider::default().build(),
-			// 		NNAPIExecutionProvider::default().build(),
-			// 		XNNPACKExecutionProvider::default().build(),
-			// 		ACLExecutionProvider::default().build(),
-			// 		ArmNNExecutionProvider::default().build(),
-			// 	]
-			// }
-		})
 		.commit()?;
 
 	debug!("Initialized AI environment");
@@ -82,5 +42,5 @@ pub enum Error {
 	#[error("failed to initialize AI environment: {0}")]
 	Init(#[from] ort::Error),
 	#[error(transparent)]
-	ImageLabeler(#[from] old_image_labeler::ImageLabelerError),
+	ImageLabeler(#[from] image_labeler::ImageLabelerError),
 }
@@ -16,11 +16,12 @@ use ffmpeg_sys_next::{
 	av_get_media_type_string, av_get_pix_fmt_name, av_get_sample_fmt_name, av_pix_fmt_desc_get,
 	av_reduce, avcodec_alloc_context3, avcodec_flush_buffers, avcodec_free_context,
 	avcodec_get_name, avcodec_open2, avcodec_parameters_to_context, avcodec_profile_name,
-	avcodec_receive_frame, avcodec_send_packet, AVBPrint, AVChromaLocation, AVCodec,
-	AVCodecContext, AVCodecParameters, AVColorPrimaries, AVColorRange, AVColorSpace,
-	AVColorTransferCharacteristic, AVFieldOrder, AVFrame, AVMediaType, AVPacket, AVPixelFormat,
-	AVRational, AVSampleFormat, AVERROR, AVERROR_EOF, AV_FOURCC_MAX_STRING_SIZE,
-	FF_CODEC_PROPERTY_CLOSED_CAPTIONS, FF_CODEC_PROPERTY_FILM_GRAIN, FF_CODEC_PROPERTY_LOSSLESS,
+	avcodec_receive_frame, avcodec_receive_packet, avcodec_send_frame, avcodec_send_packet,
+	AVBPrint, AVChromaLocation, AVCodec, AVCodecContext, AVCodecParameters, AVColorPrimaries,
+	AVColorRange, AVColorSpace, AVColorTransferCharacteristic, AVFieldOrder, AVFrame, AVMediaType,
+	AVPacket, AVPixelFormat, AVRational, AVSampleFormat, AVERROR, AVERROR_EOF,
+	AV_FOURCC_MAX_STRING_SIZE, FF_CODEC_PROPERTY_CLOSED_CAPTIONS, FF_CODEC_PROPERTY_FILM_GRAIN,
+	FF_CODEC_PROPERTY_LOSSLESS,
 };
 use libc::EAGAIN;
 
@@ -36,6 +37,17 @@ impl FFmpegCodecContext {
 		Ok(Self(ptr))
 	}
 
+	/// Allocates a context with the private options of `codec` already set to their defaults,
+	/// which encoders need to be configured before being opened
+	pub(crate) fn with_codec(codec: &AVCodec) -> Result<Self, Error> {
+		let ptr = unsafe { avcodec_alloc_context3(codec) };
+		if ptr.is_null() {
+			Err(FFmpegError::VideoCodecAllocation)?;
+		}
+
+		Ok(Self(ptr))
+	}
+
 	pub(crate) fn as_ref(&self) -> &AVCodecContext {
 		unsafe { self.0.as_ref() }.expect("initialized on struct creation")
 	}
@@ -87,6 +99,24 @@ impl FFmpegCodecContext {
 		}
 	}
 
+	pub(crate) fn send_frame(&mut self, frame: *const AVFrame) -> Result<bool, FFmpegError> {
+		match unsafe { avcodec_send_frame(self.as_mut(), frame) } {
+			AVERROR_EOF => Ok(false),
+			ret if ret == AVERROR(EAGAIN) => Err(FFmpegError::Again),
+			ret if ret < 0 => Err(FFmpegError::from(ret)),
+			_ => Ok(true),
+		}
+	}
+
+	pub(crate) fn receive_packet(&mut self, packet: *mut AVPacket) -> Result<bool, FFmpegError> {
+		match unsafe { avcodec_receive_packet(self.as_mut(), packet) } {
+			AVERROR_EOF => Ok(false),
+			ret if ret == AVERROR(EAGAIN) => Err(FFmpegError::Again),
+			ret if ret < 0 => Err(FFmpegError::from(ret)),
+			_ => Ok(true),
+		}
+	}
+
 	fn kind(&self) -> (Option<String>, Option<String>) {
 		let kind = unsafe { av_get_media_type_string(self.as_ref().codec_type).as_ref() }
 			.map(|media_type| unsafe { CStr::from_ptr(media_type) });
@@ -286,6 +316,7 @@ impl FFmpegCodecContext {
 			sample_format,
 			bit_per_sample,
 			channel_layout,
+			channels: (ctx.ch_layout.nb_channels > 0).then_some(ctx.ch_layout.nb_channels),
 		})
 	}
 
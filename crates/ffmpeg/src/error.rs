@@ -28,6 +28,22 @@ pub enum Error {
 	InvalidQuality(f32),
 	#[error("Received an invalid seek percentage: {0}")]
 	InvalidSeekPercentage(f32),
+	#[error(
+		"Received an invalid frames count, expected range [1, {}], received: {0}",
+		crate::MAX_THUMBSTRIP_FRAMES
+	)]
+	InvalidFramesCount(u32),
+	#[error(
+		"Received an invalid frame rate, expected range [1, {}], received: {0}",
+		crate::MAX_PREVIEW_CLIP_FRAME_RATE
+	)]
+	InvalidFrameRate(u32),
+	#[error("Thumbstrip is too large to be encoded: {0}x{1} pixels")]
+	ThumbstripTooLarge(u32, u32),
+	#[error("Received an invalid transcode quality: {0}")]
+	InvalidTranscodeQuality(String),
+	#[error("Failed to encode animated webp: {0}")]
+	AnimationEncoding(String),
 	#[error("Error while casting an integer to another integer type")]
 	IntCastError(#[from] TryFromIntError),
 	#[error("Duration for video stream is unavailable")]
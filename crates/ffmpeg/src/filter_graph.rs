@@ -8,9 +8,10 @@ use crate::{
 	utils::check_error, Error,
 };
 use ffmpeg_sys_next::{
-	avfilter_get_by_name, avfilter_graph_alloc, avfilter_graph_config,
-	avfilter_graph_create_filter, avfilter_graph_free, avfilter_link, AVFilterContext,
-	AVFilterGraph, AVRational,
+	av_strdup, avfilter_get_by_name, avfilter_graph_alloc, avfilter_graph_config,
+	avfilter_graph_create_filter, avfilter_graph_free, avfilter_graph_parse_ptr,
+	avfilter_inout_alloc, avfilter_inout_free, avfilter_link, AVFilterContext, AVFilterGraph,
+	AVFilterInOut, AVRational,
 };
 
 pub struct FFmpegFilterGraph(*mut AVFilterGraph);
@@ -157,6 +158,61 @@ impl<'a> FFmpegFilterGraph {
 		Ok((filter_graph, filter_source_ctx, filter_sink_ctx))
 	}
 
+	/// Builds a graph with a chain of filters described like `scale=w=-2:h=720,format=yuv420p`,
+	/// fed by a `source` filter (`buffer` or `abuffer`) and draining into a `sink` filter
+	pub(crate) fn from_description(
+		source: &CStr,
+		source_args: &CStr,
+		sink: &CStr,
+		description: &CStr,
+	) -> Result<(Self, &'a mut AVFilterContext, &'a mut AVFilterContext), Error> {
+		let mut filter_graph = Self::new()?;
+
+		let mut filter_source = ptr::null_mut();
+		filter_graph.setup_filter(
+			&mut filter_source,
+			source,
+			c"in",
+			Some(source_args),
+			"Failed to create filter source",
+		)?;
+
+		let mut filter_sink = ptr::null_mut();
+		filter_graph.setup_filter(
+			&mut filter_sink,
+			sink,
+			c"out",
+			None,
+			"Failed to create filter sink",
+		)?;
+
+		// The description is parsed from the point of view of the chain: its unlinked input is
+		// our source output, and its unlinked output is our sink input
+		let mut outputs = FFmpegFilterInOut::new(c"in", filter_source)?;
+		let mut inputs = FFmpegFilterInOut::new(c"out", filter_sink)?;
+
+		check_error(
+			unsafe {
+				avfilter_graph_parse_ptr(
+					filter_graph.as_mut(),
+					description.as_ptr(),
+					&mut inputs.0,
+					&mut outputs.0,
+					ptr::null_mut(),
+				)
+			},
+			"Failed to parse filter graph description",
+		)?;
+
+		filter_graph.config()?;
+
+		Ok((
+			filter_graph,
+			unsafe { filter_source.as_mut() }.ok_or(FFmpegError::NullError)?,
+			unsafe { filter_sink.as_mut() }.ok_or(FFmpegError::NullError)?,
+		))
+	}
+
 	pub(crate) fn as_mut(&mut self) -> &mut AVFilterGraph {
 		unsafe { self.0.as_mut() }.expect("initialized on struct creation")
 	}
@@ -203,6 +259,36 @@ impl Drop for FFmpegFilterGraph {
 	}
 }
 
+/// Named endpoint of a filter chain, used to link a parsed filter description to our own filters
+struct FFmpegFilterInOut(*mut AVFilterInOut);
+
+impl FFmpegFilterInOut {
+	fn new(name: &CStr, filter_ctx: *mut AVFilterContext) -> Result<Self, FFmpegError> {
+		let ptr = unsafe { avfilter_inout_alloc() };
+		if ptr.is_null() {
+			return Err(FFmpegError::FilterGraphAllocation);
+		}
+
+		let inout = Self(ptr);
+		let inner = unsafe { &mut *inout.0 };
+		inner.name = unsafe { av_strdup(name.as_ptr()) };
+		inner.filter_ctx = filter_ctx;
+		inner.pad_idx = 0;
+		inner.next = ptr::null_mut();
+
+		Ok(inout)
+	}
+}
+
+impl Drop for FFmpegFilterInOut {
+	fn drop(&mut self) {
+		if !self.0.is_null() {
+			unsafe { avfilter_inout_free(&mut self.0) };
+			self.0 = ptr::null_mut();
+		}
+	}
+}
+
 fn thumb_scale_filter_args(
 	size: Option<ThumbnailSize>,
 	codec_ctx: &FFmpegCodecContext,
@@ -7,10 +7,13 @@ use crate::{
 };
 
 use ffmpeg_sys_next::{
-	av_cmp_q, av_display_rotation_get, av_read_frame, av_reduce, av_stream_get_side_data,
-	avformat_close_input, avformat_find_stream_info, avformat_open_input, AVChapter, AVCodecID,
-	AVDictionary, AVFormatContext, AVMediaType, AVPacket, AVPacketSideDataType, AVRational,
-	AVStream, AV_DISPOSITION_ATTACHED_PIC, AV_DISPOSITION_CAPTIONS, AV_DISPOSITION_CLEAN_EFFECTS,
+	av_cmp_q, av_display_rotation_get, av_interleaved_write_frame, av_read_frame, av_reduce,
+	av_seek_frame, av_stream_get_side_data, av_write_trailer, avcodec_parameters_from_context,
+	avformat_alloc_output_context2, avformat_close_input, avformat_find_stream_info,
+	avformat_free_context, avformat_new_stream, avformat_open_input, avformat_write_header,
+	avio_closep, avio_open, AVChapter, AVCodecID, AVDictionary, AVFormatContext, AVMediaType,
+	AVPacket, AVPacketSideDataType, AVRational, AVStream, AVFMT_GLOBALHEADER, AVIO_FLAG_WRITE,
+	AV_DISPOSITION_ATTACHED_PIC, AV_DISPOSITION_CAPTIONS, AV_DISPOSITION_CLEAN_EFFECTS,
 	AV_DISPOSITION_COMMENT, AV_DISPOSITION_DEFAULT, AV_DISPOSITION_DEPENDENT,
 	AV_DISPOSITION_DESCRIPTIONS, AV_DISPOSITION_DUB, AV_DISPOSITION_FORCED,
 	AV_DISPOSITION_HEARING_IMPAIRED, AV_DISPOSITION_KARAOKE, AV_DISPOSITION_LYRICS,
@@ -19,7 +22,11 @@ use ffmpeg_sys_next::{
 	AV_NOPTS_VALUE,
 };
 
-use std::{collections::HashSet, ffi::CStr, ptr};
+use std::{
+	collections::HashSet,
+	ffi::{c_int, CStr},
+	ptr,
+};
 
 fn extract_name_and_convert_metadata(
 	metadata: *mut AVDictionary,
@@ -119,6 +126,16 @@ impl FFmpegFormatContext {
 		Ok(self)
 	}
 
+	/// Seeks to `timestamp`, in `AV_TIME_BASE` units, considering all streams
+	pub(crate) fn seek(&mut self, timestamp: i64, flags: c_int) -> Result<&mut Self, Error> {
+		check_error(
+			unsafe { av_seek_frame(self.as_mut(), -1, timestamp, flags) },
+			"Seeking video failed",
+		)?;
+
+		Ok(self)
+	}
+
 	pub(crate) fn find_stream_info(&mut self) -> Result<&mut Self, Error> {
 		check_error(
 			unsafe { avformat_find_stream_info(self.as_mut(), ptr::null_mut()) },
@@ -205,7 +222,7 @@ impl FFmpegFormatContext {
 			.unwrap_or(vec![])
 	}
 
-	fn start_time(&self) -> Option<i64> {
+	pub(crate) fn start_time(&self) -> Option<i64> {
 		let start_time = self.as_ref().start_time;
 		if start_time == AV_NOPTS_VALUE {
 			return None;
@@ -300,6 +317,117 @@ impl Drop for FFmpegFormatContext {
 	}
 }
 
+/// Format context of a media file being written, like the segments of a transcoded video
+#[derive(Debug)]
+pub struct FFmpegOutputFormatContext(*mut AVFormatContext);
+
+impl FFmpegOutputFormatContext {
+	pub(crate) fn create_file(filename: &CStr, format_name: &CStr) -> Result<Self, Error> {
+		let mut ptr = ptr::null_mut();
+
+		check_error(
+			unsafe {
+				avformat_alloc_output_context2(
+					&mut ptr,
+					ptr::null(),
+					format_name.as_ptr(),
+					filename.as_ptr(),
+				)
+			},
+			"Failed to allocate an output format context",
+		)?;
+
+		// Wrapping right away, so the context is freed if we fail to open the file
+		let mut ctx = Self(ptr);
+
+		check_error(
+			unsafe { avio_open(&mut ctx.as_mut().pb, filename.as_ptr(), AVIO_FLAG_WRITE) },
+			"Failed to open the output file",
+		)?;
+
+		Ok(ctx)
+	}
+
+	pub(crate) fn as_ref(&self) -> &AVFormatContext {
+		unsafe { self.0.as_ref() }.expect("initialized on struct creation")
+	}
+
+	pub(crate) fn as_mut(&mut self) -> &mut AVFormatContext {
+		unsafe { self.0.as_mut() }.expect("initialized on struct creation")
+	}
+
+	/// Whether the encoders must put their extra data in the container header instead of in
+	/// every keyframe
+	pub(crate) fn needs_global_header(&self) -> bool {
+		unsafe { self.as_ref().oformat.as_ref() }
+			.is_some_and(|oformat| oformat.flags & AVFMT_GLOBALHEADER != 0)
+	}
+
+	/// Adds a stream with the parameters of an already opened encoder, returning its index
+	pub(crate) fn new_stream(&mut self, encoder: &FFmpegCodecContext) -> Result<c_int, Error> {
+		let stream = unsafe { avformat_new_stream(self.as_mut(), ptr::null()).as_mut() }
+			.ok_or(FFmpegError::NullError)?;
+
+		check_error(
+			unsafe { avcodec_parameters_from_context(stream.codecpar, encoder.as_ref()) },
+			"Failed to copy the encoder parameters to the output stream",
+		)?;
+		stream.time_base = encoder.as_ref().time_base;
+
+		Ok(stream.index)
+	}
+
+	/// The time base of a stream, which the muxer is free to change when writing the header
+	pub(crate) fn stream_time_base(&self, index: c_int) -> Option<AVRational> {
+		let streams = self.as_ref().streams;
+		if streams.is_null() || index < 0 || index.unsigned_abs() >= self.as_ref().nb_streams {
+			return None;
+		}
+
+		unsafe { (*(streams.offset(isize::try_from(index).ok()?))).as_ref() }
+			.map(|stream| stream.time_base)
+	}
+
+	pub(crate) fn write_header(&mut self) -> Result<&mut Self, Error> {
+		check_error(
+			unsafe { avformat_write_header(self.as_mut(), ptr::null_mut()) },
+			"Failed to write the output file header",
+		)?;
+
+		Ok(self)
+	}
+
+	pub(crate) fn write_packet(&mut self, packet: *mut AVPacket) -> Result<&mut Self, Error> {
+		check_error(
+			unsafe { av_interleaved_write_frame(self.as_mut(), packet) },
+			"Failed to write a packet to the output file",
+		)?;
+
+		Ok(self)
+	}
+
+	pub(crate) fn write_trailer(&mut self) -> Result<&mut Self, Error> {
+		check_error(
+			unsafe { av_write_trailer(self.as_mut()) },
+			"Failed to write the output file trailer",
+		)?;
+
+		Ok(self)
+	}
+}
+
+impl Drop for FFmpegOutputFormatContext {
+	fn drop(&mut self) {
+		if !self.0.is_null() {
+			unsafe {
+				avio_closep(&mut (*self.0).pb);
+				avformat_free_context(self.0);
+			};
+			self.0 = ptr::null_mut();
+		}
+	}
+}
+
 impl From<&FFmpegFormatContext> for FFmpegMediaData {
 	fn from(ctx: &FFmpegFormatContext) -> Self {
 		Self {
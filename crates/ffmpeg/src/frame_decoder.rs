@@ -13,7 +13,7 @@ use ffmpeg_sys_next::{
 	av_buffersink_get_frame, av_buffersrc_write_frame, av_frame_alloc,
 	av_guess_sample_aspect_ratio, av_packet_alloc, av_packet_free, av_packet_unref, av_seek_frame,
 	avcodec_find_decoder, AVPacket, AVRational, AVStream, AVERROR, AVPROBE_SCORE_MAX,
-	AV_FRAME_FLAG_INTERLACED, AV_FRAME_FLAG_KEY, AV_TIME_BASE, EAGAIN,
+	AV_FRAME_FLAG_INTERLACED, AV_FRAME_FLAG_KEY, AV_NOPTS_VALUE, AV_TIME_BASE, EAGAIN,
 };
 
 #[derive(Debug, Clone, Copy)]
@@ -219,6 +219,25 @@ impl FrameDecoder {
 		})
 	}
 
+	/// Presentation time of the last decoded frame, in seconds since the start of the stream
+	pub(crate) fn frame_timestamp_secs(&self) -> Option<f64> {
+		let timestamp = self.frame.as_ref().best_effort_timestamp;
+		if timestamp == AV_NOPTS_VALUE {
+			return None;
+		}
+
+		let time_base = self.format_ctx.stream(self.preferred_stream_id)?.time_base;
+		if time_base.den == 0 {
+			return None;
+		}
+
+		#[allow(clippy::cast_precision_loss)]
+		{
+			// SAFETY: the timestamp would need to be humongous for this cast to f64 to cause problems
+			Some(timestamp as f64 * f64::from(time_base.num) / f64::from(time_base.den))
+		}
+	}
+
 	fn reset_packet(&mut self) {
 		if self.packet.is_null() {
 			self.packet = unsafe { av_packet_alloc() };
@@ -29,9 +29,10 @@
 
 use crate::{format_ctx::FFmpegFormatContext, frame_decoder::FrameDecoder, utils::from_path};
 
-use std::path::Path;
+use std::{path::Path, time::Duration};
 
 use ffmpeg_sys_next::{av_log_set_level, AV_LOG_FATAL};
+use image::DynamicImage;
 
 mod codec_ctx;
 mod dict;
@@ -40,15 +41,23 @@ mod filter_graph;
 mod format_ctx;
 mod frame_decoder;
 pub mod model;
+mod preview_clip;
 mod thumbnailer;
+mod thumbstrip;
+mod transcoder;
 mod utils;
 mod video_frame;
+mod waveform;
 
 pub use error::Error;
 pub use frame_decoder::ThumbnailSize;
 pub use model::FFmpegMediaData;
+pub use preview_clip::{PreviewClipBuilder, MAX_PREVIEW_CLIP_FRAME_RATE};
 pub use thumbnailer::ThumbnailerBuilder;
+pub use thumbstrip::{ThumbstripBuilder, MAX_THUMBSTRIP_FRAMES};
 use tokio::task::spawn_blocking;
+pub use transcoder::{TranscodeQuality, TranscoderBuilder};
+pub use waveform::Waveform;
 
 /// Helper function to generate retrieve media data from from a video/audio file
 pub async fn probe(filename: impl AsRef<Path> + Send) -> Result<FFmpegMediaData, Error> {
@@ -79,13 +88,14 @@ pub async fn probe(filename: impl AsRef<Path> + Send) -> Result<FFmpegMediaData,
 	.await?
 }
 
-/// Helper function to generate a thumbnail file from a video file with reasonable defaults
+/// Helper function to generate a thumbnail file from a video file with reasonable defaults,
+/// returning the decoded frame it was generated from
 pub async fn to_thumbnail(
 	video_file_path: impl AsRef<Path> + Send,
 	output_thumbnail_path: impl AsRef<Path> + Send,
 	size: ThumbnailSize,
 	quality: f32,
-) -> Result<(), Error> {
+) -> Result<DynamicImage, Error> {
 	// Reduce the amount of logs generated by FFmpeg
 	unsafe { av_log_set_level(AV_LOG_FATAL) };
 
@@ -97,6 +107,77 @@ pub async fn to_thumbnail(
 		.await
 }
 
+/// Helper function to generate a thumbstrip file, with `frames_count` evenly spaced frames side by
+/// side, from a video file with reasonable defaults
+pub async fn to_thumbstrip(
+	video_file_path: impl AsRef<Path> + Send,
+	output_thumbstrip_path: impl AsRef<Path> + Send,
+	frames_count: u32,
+	size: ThumbnailSize,
+	quality: f32,
+) -> Result<(), Error> {
+	// Reduce the amount of logs generated by FFmpeg
+	unsafe { av_log_set_level(AV_LOG_FATAL) };
+
+	ThumbstripBuilder::new()
+		.frames_count(frames_count)?
+		.size(size)
+		.quality(quality)?
+		.build()
+		.process(video_file_path, output_thumbstrip_path)
+		.await
+}
+
+/// Helper function to generate a short animated preview clip file from a video file with
+/// reasonable defaults
+pub async fn to_preview_clip(
+	video_file_path: impl AsRef<Path> + Send,
+	output_clip_path: impl AsRef<Path> + Send,
+	size: ThumbnailSize,
+	quality: f32,
+) -> Result<(), Error> {
+	// Reduce the amount of logs generated by FFmpeg
+	unsafe { av_log_set_level(AV_LOG_FATAL) };
+
+	PreviewClipBuilder::new()
+		.size(size)
+		.quality(quality)?
+		.build()
+		.process(video_file_path, output_clip_path)
+		.await
+}
+
+/// Helper function to transcode `duration` of a video file, starting at `start`, to a MPEG-TS
+/// segment that any browser can play through HLS
+pub async fn to_transcoded_segment(
+	video_file_path: impl AsRef<Path> + Send,
+	output_segment_path: impl AsRef<Path> + Send,
+	start: Duration,
+	duration: Duration,
+	quality: TranscodeQuality,
+) -> Result<(), Error> {
+	// Reduce the amount of logs generated by FFmpeg
+	unsafe { av_log_set_level(AV_LOG_FATAL) };
+
+	TranscoderBuilder::new()
+		.quality(quality)
+		.build()
+		.transcode_segment(video_file_path, output_segment_path, start, duration)
+		.await
+}
+
+/// Helper function to decode the first audio stream of a file into a waveform with at most
+/// `buckets` values
+pub async fn to_waveform(
+	audio_file_path: impl AsRef<Path> + Send,
+	buckets: usize,
+) -> Result<Waveform, Error> {
+	// Reduce the amount of logs generated by FFmpeg
+	unsafe { av_log_set_level(AV_LOG_FATAL) };
+
+	Waveform::from_path(audio_file_path, buckets).await
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -115,6 +115,7 @@ pub struct FFmpegAudioProps {
 	pub sample_format: Option<String>,
 	pub bit_per_sample: Option<i32>,
 	pub channel_layout: Option<String>,
+	pub channels: Option<i32>,
 }
 
 #[derive(Debug)]
@@ -0,0 +1,263 @@
+use crate::{
+	frame_decoder::ThumbnailSize,
+	utils::{frame_to_image, write_to_file},
+	Error, FrameDecoder,
+};
+
+use std::{ops::Deref, path::Path, time::Duration};
+
+use image::{imageops, RgbImage};
+use tokio::task::spawn_blocking;
+use tracing::error;
+use webp::{AnimEncoder, AnimFrame, WebPConfig};
+
+/// Maximum frame rate for preview clips, they are meant to be small
+pub const MAX_PREVIEW_CLIP_FRAME_RATE: u32 = 30;
+
+/// `PreviewClip` struct holds data from a `PreviewClipBuilder`, exposing methods
+/// to generate short and small animated previews from video files.
+#[derive(Debug, Clone)]
+pub struct PreviewClip {
+	builder: PreviewClipBuilder,
+}
+
+impl PreviewClip {
+	/// Processes a video input file and write to file system a preview clip with animated webp
+	/// format, without audio
+	pub(crate) async fn process(
+		&self,
+		video_file_path: impl AsRef<Path> + Send,
+		output_clip_path: impl AsRef<Path> + Send,
+	) -> Result<(), Error> {
+		let webp = self.process_to_webp_bytes(video_file_path).await?;
+
+		write_to_file(output_clip_path.as_ref(), &webp).await
+	}
+
+	/// Processes a video input file and returns a animated webp encoded clip as bytes
+	async fn process_to_webp_bytes(
+		&self,
+		video_file_path: impl AsRef<Path> + Send,
+	) -> Result<Vec<u8>, Error> {
+		let PreviewClipBuilder {
+			size,
+			seek_percentage,
+			duration,
+			frame_rate,
+			quality,
+		} = self.builder;
+
+		spawn_blocking({
+			let video_file_path = video_file_path.as_ref().to_path_buf();
+			move || -> Result<Vec<u8>, Error> {
+				// Embedded cover arts aren't part of the video
+				let mut decoder = FrameDecoder::new(&video_file_path, true, false)?;
+
+				// We actually have to decode a frame to get some metadata before we can start decoding for real
+				decoder.decode_video_frame()?;
+
+				if let Some(video_duration) = decoder.get_duration_secs() {
+					if let Err(err) = decoder.seek(
+						#[allow(clippy::cast_possible_truncation)]
+						{
+							// This conversion is ok because we don't worry much about precision here
+							(video_duration * f64::from(seek_percentage)).round() as i64
+						},
+					) {
+						error!(
+							"Failed to seek {}: {err:#?}",
+							video_file_path.to_string_lossy()
+						);
+						// Seeking failed, the clip starts at the beginning of the video
+						// Re-instantiating decoder to avoid possible segfault
+						// https://github.com/dirkvdb/ffmpegthumbnailer/commit/da292ccb51a526ebc833f851a388ca308d747289
+						decoder = FrameDecoder::new(&video_file_path, false, false)?;
+						decoder.decode_video_frame()?;
+					}
+				}
+
+				let frames =
+					collect_frames(&mut decoder, &video_file_path, size, duration, frame_rate)?;
+
+				encode_animation(&frames, frame_rate, quality)
+			}
+		})
+		.await?
+	}
+}
+
+/// Decodes the video from its current position, keeping `frame_rate` frames per second until we
+/// have a clip as long as `duration` or the video ends
+fn collect_frames(
+	decoder: &mut FrameDecoder,
+	video_file_path: &Path,
+	size: ThumbnailSize,
+	duration: Duration,
+	frame_rate: u32,
+) -> Result<Vec<RgbImage>, Error> {
+	let frame_interval = 1.0 / f64::from(frame_rate);
+	let clip_duration = duration.as_secs_f64();
+
+	let mut frames = Vec::new();
+	let mut start = None;
+	let mut next_frame_at = 0.0;
+
+	loop {
+		// Without timestamps, we just keep every decoded frame
+		let elapsed = decoder
+			.frame_timestamp_secs()
+			.map_or(next_frame_at, |timestamp| {
+				timestamp - *start.get_or_insert(timestamp)
+			});
+
+		if elapsed >= clip_duration {
+			break;
+		}
+
+		if elapsed >= next_frame_at {
+			frames.push(
+				frame_to_image(
+					decoder.get_scaled_video_frame(Some(size), true)?,
+					video_file_path,
+				)?
+				.to_rgb8(),
+			);
+			next_frame_at += frame_interval;
+		}
+
+		// Failing to decode more frames here means that the video ended
+		if decoder.decode_video_frame().is_err() {
+			break;
+		}
+	}
+
+	if frames.is_empty() {
+		return Err(Error::FrameDecodeError);
+	}
+
+	Ok(frames)
+}
+
+fn encode_animation(frames: &[RgbImage], frame_rate: u32, quality: f32) -> Result<Vec<u8>, Error> {
+	let Some((width, height)) = frames.first().map(RgbImage::dimensions) else {
+		return Err(Error::FrameDecodeError);
+	};
+
+	// The animation needs every frame with the same size
+	let frames = frames
+		.iter()
+		.map(|frame| {
+			if frame.dimensions() == (width, height) {
+				frame.clone()
+			} else {
+				imageops::resize(frame, width, height, imageops::FilterType::Triangle)
+			}
+		})
+		.collect::<Vec<_>>();
+
+	let mut config =
+		WebPConfig::new().map_err(|()| Error::AnimationEncoding("invalid config".to_string()))?;
+	config.lossless = 0;
+	config.quality = quality;
+
+	let frame_duration_ms = 1000 / i32::try_from(frame_rate)?;
+
+	let mut encoder = AnimEncoder::new(width, height, &config);
+	for (i, frame) in frames.iter().enumerate() {
+		encoder.add_frame(AnimFrame::from_rgb(
+			frame.as_raw(),
+			width,
+			height,
+			i32::try_from(i)? * frame_duration_ms,
+		));
+	}
+
+	// Type WebPMemory is !Send, which makes the Future in this function !Send,
+	// this make us `deref` to have a `&[u8]` and then `to_owned` to make a Vec<u8>
+	// which implies on a unwanted clone...
+	encoder
+		.try_encode()
+		.map(|webp| webp.deref().to_vec())
+		.map_err(|e| Error::AnimationEncoding(format!("{e:?}")))
+}
+
+/// `PreviewClipBuilder` struct holds data to build a `PreviewClip` struct, exposing many methods
+/// to configure how a preview clip must be generated.
+#[derive(Debug, Clone, Copy)]
+#[must_use]
+pub struct PreviewClipBuilder {
+	size: ThumbnailSize,
+	seek_percentage: f32,
+	duration: Duration,
+	frame_rate: u32,
+	quality: f32,
+}
+
+impl Default for PreviewClipBuilder {
+	fn default() -> Self {
+		Self {
+			size: ThumbnailSize::Scale(320),
+			seek_percentage: 0.1,
+			duration: Duration::from_secs(3),
+			frame_rate: 8,
+			quality: 40.0,
+		}
+	}
+}
+
+impl PreviewClipBuilder {
+	/// Creates a new `PreviewClipBuilder` with default values:
+	/// - `size`: 320 pixels
+	/// - `seek_percentage`: 10%
+	/// - `duration`: 3 seconds
+	/// - `frame_rate`: 8 frames per second
+	/// - `quality`: 40
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// To set the size of the clip, always respecting the aspect ratio of the video
+	pub const fn size(mut self, size: ThumbnailSize) -> Self {
+		self.size = size;
+		self
+	}
+
+	/// Seek percentage must be a value between 0.0 and 1.0
+	pub fn seek_percentage(mut self, seek_percentage: f32) -> Result<Self, Error> {
+		if !(0.0..=1.0).contains(&seek_percentage) {
+			return Err(Error::InvalidSeekPercentage(seek_percentage));
+		}
+		self.seek_percentage = seek_percentage;
+		Ok(self)
+	}
+
+	/// Maximum duration of the clip, it can be shorter if the video ends before
+	pub const fn duration(mut self, duration: Duration) -> Self {
+		self.duration = duration;
+		self
+	}
+
+	/// Frame rate must be a value between 1 and [`MAX_PREVIEW_CLIP_FRAME_RATE`]
+	pub const fn frame_rate(mut self, frame_rate: u32) -> Result<Self, Error> {
+		if frame_rate == 0 || frame_rate > MAX_PREVIEW_CLIP_FRAME_RATE {
+			return Err(Error::InvalidFrameRate(frame_rate));
+		}
+		self.frame_rate = frame_rate;
+		Ok(self)
+	}
+
+	/// Quality must be a value between 0.0 and 100.0
+	pub fn quality(mut self, quality: f32) -> Result<Self, Error> {
+		if !(0.0..=100.0).contains(&quality) {
+			return Err(Error::InvalidQuality(quality));
+		}
+		self.quality = quality;
+		Ok(self)
+	}
+
+	/// Builds a `PreviewClip` struct
+	#[must_use]
+	pub const fn build(self) -> PreviewClip {
+		PreviewClip { builder: self }
+	}
+}
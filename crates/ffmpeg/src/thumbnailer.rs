@@ -1,10 +1,13 @@
-use crate::{frame_decoder::ThumbnailSize, Error, FrameDecoder};
+use crate::{
+	frame_decoder::ThumbnailSize,
+	utils::{frame_to_image, write_to_file},
+	Error, FrameDecoder,
+};
 
-use std::{io, ops::Deref, path::Path};
+use std::{ops::Deref, path::Path};
 
-use image::{imageops, DynamicImage, RgbImage};
-use sd_utils::error::FileIOError;
-use tokio::{fs, io::AsyncWriteExt, task::spawn_blocking};
+use image::DynamicImage;
+use tokio::task::spawn_blocking;
 use tracing::error;
 use webp::Encoder;
 
@@ -16,46 +19,26 @@ pub struct Thumbnailer {
 }
 
 impl Thumbnailer {
-	/// Processes an video input file and write to file system a thumbnail with webp format
+	/// Processes an video input file and write to file system a thumbnail with webp format,
+	/// returning the frame it was generated from
 	pub(crate) async fn process(
 		&self,
 		video_file_path: impl AsRef<Path> + Send,
 		output_thumbnail_path: impl AsRef<Path> + Send,
-	) -> Result<(), Error> {
-		let output_thumbnail_path = output_thumbnail_path.as_ref();
-		let path = output_thumbnail_path.parent().ok_or_else(|| {
-			FileIOError::from((
-				output_thumbnail_path,
-				io::Error::new(
-					io::ErrorKind::InvalidInput,
-					"Cannot determine parent directory",
-				),
-			))
-		})?;
-
-		fs::create_dir_all(path)
-			.await
-			.map_err(|e| FileIOError::from((path, e)))?;
-
-		let webp = self.process_to_webp_bytes(video_file_path).await?;
-		let mut file = fs::File::create(output_thumbnail_path)
-			.await
-			.map_err(|e: io::Error| FileIOError::from((output_thumbnail_path, e)))?;
-
-		file.write_all(&webp)
-			.await
-			.map_err(|e| FileIOError::from((output_thumbnail_path, e)))?;
-
-		file.sync_all()
-			.await
-			.map_err(|e| FileIOError::from((output_thumbnail_path, e)).into())
+	) -> Result<DynamicImage, Error> {
+		let (webp, image) = self.process_to_webp_bytes(video_file_path).await?;
+
+		write_to_file(output_thumbnail_path.as_ref(), &webp).await?;
+
+		Ok(image)
 	}
 
-	/// Processes an video input file and returns a webp encoded thumbnail as bytes
+	/// Processes an video input file and returns a webp encoded thumbnail as bytes, along with
+	/// the frame it was encoded from
 	async fn process_to_webp_bytes(
 		&self,
 		video_file_path: impl AsRef<Path> + Send,
-	) -> Result<Vec<u8>, Error> {
+	) -> Result<(Vec<u8>, DynamicImage), Error> {
 		let prefer_embedded_metadata = self.builder.prefer_embedded_metadata;
 		let seek_percentage = self.builder.seek_percentage;
 		let size = self.builder.size;
@@ -64,7 +47,7 @@ impl Thumbnailer {
 
 		spawn_blocking({
 			let video_file_path = video_file_path.as_ref().to_path_buf();
-			move || -> Result<Vec<u8>, Error> {
+			move || -> Result<(Vec<u8>, DynamicImage), Error> {
 				let mut decoder = FrameDecoder::new(
 					&video_file_path,
 					// TODO: allow_seek should be false for remote files
@@ -106,30 +89,18 @@ impl Thumbnailer {
 				let video_frame =
 					decoder.get_scaled_video_frame(Some(size), maintain_aspect_ratio)?;
 
-				let mut image = DynamicImage::ImageRgb8(
-					RgbImage::from_raw(video_frame.width, video_frame.height, video_frame.data)
-						.ok_or(Error::CorruptVideo(video_file_path.into_boxed_path()))?,
-				);
-
-				let image = if video_frame.rotation < -135.0 {
-					imageops::rotate180_in_place(&mut image);
-					image
-				} else if video_frame.rotation > 45.0 && video_frame.rotation < 135.0 {
-					image.rotate270()
-				} else if video_frame.rotation < -45.0 && video_frame.rotation > -135.0 {
-					image.rotate90()
-				} else {
-					image
-				};
+				let image = frame_to_image(video_frame, &video_file_path)?;
 
 				// Type WebPMemory is !Send, which makes the Future in this function !Send,
 				// this make us `deref` to have a `&[u8]` and then `to_owned` to make a Vec<u8>
 				// which implies on a unwanted clone...
-				Ok(Encoder::from_image(&image)
+				let webp = Encoder::from_image(&image)
 					.expect("Should not fail as the underlining DynamicImage is an RgbImage")
 					.encode(quality)
 					.deref()
-					.to_vec())
+					.to_vec();
+
+				Ok((webp, image))
 			}
 		})
 		.await?
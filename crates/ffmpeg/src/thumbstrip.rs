@@ -0,0 +1,224 @@
+use crate::{
+	frame_decoder::ThumbnailSize,
+	utils::{frame_to_image, write_to_file},
+	Error, FrameDecoder,
+};
+
+use std::{ops::Deref, path::Path};
+
+use image::{imageops, DynamicImage, GenericImageView, RgbImage};
+use tokio::task::spawn_blocking;
+use webp::Encoder;
+
+/// Maximum amount of frames in a single thumbstrip
+pub const MAX_THUMBSTRIP_FRAMES: u32 = 60;
+
+/// `WebP` images can't be larger than this in any dimension
+const WEBP_MAX_DIMENSION: u32 = 16_383;
+
+/// `Thumbstrip` struct holds data from a `ThumbstripBuilder`, exposing methods
+/// to generate a sprite sheet of evenly spaced frames from video files.
+#[derive(Debug, Clone)]
+pub struct Thumbstrip {
+	builder: ThumbstripBuilder,
+}
+
+impl Thumbstrip {
+	/// Processes a video input file and write to file system a thumbstrip with webp format,
+	/// having all frames side by side in a single row
+	pub(crate) async fn process(
+		&self,
+		video_file_path: impl AsRef<Path> + Send,
+		output_thumbstrip_path: impl AsRef<Path> + Send,
+	) -> Result<(), Error> {
+		let webp = self.process_to_webp_bytes(video_file_path).await?;
+
+		write_to_file(output_thumbstrip_path.as_ref(), &webp).await
+	}
+
+	/// Processes a video input file and returns a webp encoded thumbstrip as bytes
+	async fn process_to_webp_bytes(
+		&self,
+		video_file_path: impl AsRef<Path> + Send,
+	) -> Result<Vec<u8>, Error> {
+		let ThumbstripBuilder {
+			frames_count,
+			size,
+			quality,
+		} = self.builder;
+
+		spawn_blocking({
+			let video_file_path = video_file_path.as_ref().to_path_buf();
+			move || -> Result<Vec<u8>, Error> {
+				// Embedded cover arts are useless for scrubbing through the video
+				let mut decoder = FrameDecoder::new(&video_file_path, true, false)?;
+
+				// We actually have to decode a frame to get some metadata before we can start decoding for real
+				decoder.decode_video_frame()?;
+
+				let duration = decoder.get_duration_secs().ok_or(Error::NoVideoDuration)?;
+
+				let mut frames = Vec::with_capacity(frames_count as usize);
+				for i in 0..frames_count {
+					// Taking the frame at the middle of each interval, so we skip the usually black
+					// first and last frames of the video
+					let seconds = duration * (f64::from(i) + 0.5) / f64::from(frames_count);
+
+					decoder.seek(
+						#[allow(clippy::cast_possible_truncation)]
+						{
+							// This conversion is ok because we don't worry much about precision here
+							seconds.floor() as i64
+						},
+					)?;
+
+					frames.push(frame_to_image(
+						decoder.get_scaled_video_frame(Some(size), true)?,
+						&video_file_path,
+					)?);
+				}
+
+				let strip = join_frames(&frames)?;
+
+				// Type WebPMemory is !Send, which makes the Future in this function !Send,
+				// this make us `deref` to have a `&[u8]` and then `to_owned` to make a Vec<u8>
+				// which implies on a unwanted clone...
+				Ok(Encoder::from_image(&strip)
+					.expect("Should not fail as the underlining DynamicImage is an RgbImage")
+					.encode(quality)
+					.deref()
+					.to_vec())
+			}
+		})
+		.await?
+	}
+}
+
+/// Places all frames side by side, resizing them to the size of the first one, as the frontend
+/// needs all of them to have the same size to show a specific frame
+fn join_frames(frames: &[DynamicImage]) -> Result<DynamicImage, Error> {
+	let Some((frame_width, frame_height)) = frames.first().map(GenericImageView::dimensions) else {
+		return Err(Error::FrameDecodeError);
+	};
+
+	#[allow(clippy::cast_possible_truncation)]
+	let strip_width = frame_width.saturating_mul(frames.len() as u32);
+	if strip_width > WEBP_MAX_DIMENSION || frame_height > WEBP_MAX_DIMENSION {
+		return Err(Error::ThumbstripTooLarge(strip_width, frame_height));
+	}
+
+	let mut strip = RgbImage::new(strip_width, frame_height);
+
+	for (x, frame) in (0..).step_by(frame_width as usize).zip(frames) {
+		let frame = if frame.dimensions() == (frame_width, frame_height) {
+			frame.to_rgb8()
+		} else {
+			imageops::resize(
+				&frame.to_rgb8(),
+				frame_width,
+				frame_height,
+				imageops::FilterType::Triangle,
+			)
+		};
+
+		imageops::replace(&mut strip, &frame, x, 0);
+	}
+
+	Ok(DynamicImage::ImageRgb8(strip))
+}
+
+/// `ThumbstripBuilder` struct holds data to build a `Thumbstrip` struct, exposing many methods
+/// to configure how a thumbstrip must be generated.
+#[derive(Debug, Clone, Copy)]
+#[must_use]
+pub struct ThumbstripBuilder {
+	frames_count: u32,
+	size: ThumbnailSize,
+	quality: f32,
+}
+
+impl Default for ThumbstripBuilder {
+	fn default() -> Self {
+		Self {
+			frames_count: 10,
+			size: ThumbnailSize::Scale(256),
+			quality: 60.0,
+		}
+	}
+}
+
+impl ThumbstripBuilder {
+	/// Creates a new `ThumbstripBuilder` with default values:
+	/// - `frames_count`: 10
+	/// - `size`: 256 pixels
+	/// - `quality`: 60
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Frames count must be a value between 1 and [`MAX_THUMBSTRIP_FRAMES`]
+	pub const fn frames_count(mut self, frames_count: u32) -> Result<Self, Error> {
+		if frames_count == 0 || frames_count > MAX_THUMBSTRIP_FRAMES {
+			return Err(Error::InvalidFramesCount(frames_count));
+		}
+		self.frames_count = frames_count;
+		Ok(self)
+	}
+
+	/// To set the size of each frame, always respecting the aspect ratio of the video
+	pub const fn size(mut self, size: ThumbnailSize) -> Self {
+		self.size = size;
+		self
+	}
+
+	/// Quality must be a value between 0.0 and 100.0
+	pub fn quality(mut self, quality: f32) -> Result<Self, Error> {
+		if !(0.0..=100.0).contains(&quality) {
+			return Err(Error::InvalidQuality(quality));
+		}
+		self.quality = quality;
+		Ok(self)
+	}
+
+	/// Builds a `Thumbstrip` struct
+	#[must_use]
+	pub const fn build(self) -> Thumbstrip {
+		Thumbstrip { builder: self }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use image::Rgb;
+
+	#[test]
+	fn frames_are_placed_side_by_side() {
+		let frames = [
+			DynamicImage::ImageRgb8(RgbImage::from_pixel(4, 2, Rgb([255, 0, 0]))),
+			DynamicImage::ImageRgb8(RgbImage::from_pixel(4, 2, Rgb([0, 255, 0]))),
+			// Frames with other sizes are resized to the size of the first one
+			DynamicImage::ImageRgb8(RgbImage::from_pixel(8, 4, Rgb([0, 0, 255]))),
+		];
+
+		let strip = join_frames(&frames).unwrap().to_rgb8();
+
+		assert_eq!(strip.dimensions(), (12, 2));
+		assert_eq!(strip.get_pixel(0, 0), &Rgb([255, 0, 0]));
+		assert_eq!(strip.get_pixel(4, 1), &Rgb([0, 255, 0]));
+		assert_eq!(strip.get_pixel(11, 1), &Rgb([0, 0, 255]));
+	}
+
+	#[test]
+	fn too_large_thumbstrips_are_rejected() {
+		let frames =
+			vec![DynamicImage::ImageRgb8(RgbImage::new(1024, 576)); MAX_THUMBSTRIP_FRAMES as usize];
+
+		assert!(matches!(
+			join_frames(&frames),
+			Err(Error::ThumbstripTooLarge(_, 576))
+		));
+		assert!(join_frames(&[]).is_err());
+	}
+}
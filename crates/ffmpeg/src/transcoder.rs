@@ -0,0 +1,814 @@
+use crate::{
+	codec_ctx::FFmpegCodecContext,
+	error::{Error, FFmpegError},
+	filter_graph::FFmpegFilterGraph,
+	format_ctx::{FFmpegFormatContext, FFmpegOutputFormatContext},
+	utils::{check_error, from_path},
+	video_frame::FFmpegFrame,
+};
+
+use sd_utils::error::FileIOError;
+
+use std::{
+	ffi::{c_int, CStr, CString},
+	fmt,
+	path::Path,
+	ptr,
+	str::FromStr,
+	time::Duration,
+};
+
+use ffmpeg_sys_next::{
+	av_buffersink_get_frame, av_buffersink_get_frame_rate, av_buffersink_get_h,
+	av_buffersink_get_sample_aspect_ratio, av_buffersink_get_time_base, av_buffersink_get_w,
+	av_buffersrc_add_frame_flags, av_channel_layout_default, av_channel_layout_describe,
+	av_frame_unref, av_get_sample_fmt_name, av_opt_set, av_packet_alloc, av_packet_free,
+	av_packet_rescale_ts, av_packet_unref, av_rescale_q, avcodec_find_decoder,
+	avcodec_find_encoder, avcodec_find_encoder_by_name, AVCodecID, AVFilterContext, AVFrame,
+	AVMediaType, AVPacket, AVPictureType, AVPixelFormat, AVRational, AVSampleFormat, AVStream,
+	AVERROR, AVERROR_EOF, AVSEEK_FLAG_BACKWARD, AV_CODEC_FLAG_GLOBAL_HEADER, AV_NOPTS_VALUE,
+	AV_TIME_BASE, EAGAIN,
+};
+use tokio::{fs, task::spawn_blocking};
+use tracing::debug;
+
+/// H.264 encoders that we try in order of preference, as any browser can play H.264 and which
+/// encoders are available depends on how `FFmpeg` was built and on the hardware
+const H264_ENCODERS: [&CStr; 5] = [
+	c"libx264",
+	c"h264_videotoolbox",
+	c"h264_mf",
+	c"h264_nvenc",
+	c"libopenh264",
+];
+
+/// Audio is always converted to stereo AAC at this sample rate
+const AUDIO_SAMPLE_RATE: c_int = 48_000;
+const AUDIO_CHANNELS: c_int = 2;
+
+const AV_TIME_BASE_Q: AVRational = AVRational {
+	num: 1,
+	den: AV_TIME_BASE,
+};
+
+/// Quality presets for transcoded videos, the resolution limit applies to the smaller side of the
+/// video, so portrait videos get the same treatment as landscape ones
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TranscodeQuality {
+	Low,
+	#[default]
+	Medium,
+	High,
+}
+
+impl TranscodeQuality {
+	pub const ALL: [Self; 3] = [Self::Low, Self::Medium, Self::High];
+
+	#[must_use]
+	pub const fn max_resolution(self) -> u32 {
+		match self {
+			Self::Low => 480,
+			Self::Medium => 720,
+			Self::High => 1080,
+		}
+	}
+
+	#[must_use]
+	pub const fn video_bit_rate(self) -> i64 {
+		match self {
+			Self::Low => 1_200_000,
+			Self::Medium => 3_000_000,
+			Self::High => 6_000_000,
+		}
+	}
+
+	#[must_use]
+	pub const fn audio_bit_rate(self) -> i64 {
+		match self {
+			Self::Low => 96_000,
+			Self::Medium => 128_000,
+			Self::High => 192_000,
+		}
+	}
+
+	#[must_use]
+	pub const fn as_str(self) -> &'static str {
+		match self {
+			Self::Low => "low",
+			Self::Medium => "medium",
+			Self::High => "high",
+		}
+	}
+}
+
+impl fmt::Display for TranscodeQuality {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.as_str())
+	}
+}
+
+impl FromStr for TranscodeQuality {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Self::ALL
+			.into_iter()
+			.find(|quality| quality.as_str() == s)
+			.ok_or_else(|| Error::InvalidTranscodeQuality(s.to_string()))
+	}
+}
+
+/// `Transcoder` struct holds data from a `TranscoderBuilder`, exposing methods to convert pieces
+/// of video files to formats that any browser is able to play.
+#[derive(Debug, Clone)]
+pub struct Transcoder {
+	builder: TranscoderBuilder,
+}
+
+impl Transcoder {
+	/// Transcodes `duration` of a video input file, starting at `start`, to a MPEG-TS file with
+	/// H.264 video and AAC audio, fit to be served as a HLS segment.
+	///
+	/// Timestamps are kept relative to the beginning of the video, so segments of the same video
+	/// can be generated independently and in any order, and still be played one after the other.
+	pub(crate) async fn transcode_segment(
+		&self,
+		video_file_path: impl AsRef<Path> + Send,
+		output_segment_path: impl AsRef<Path> + Send,
+		start: Duration,
+		duration: Duration,
+	) -> Result<(), Error> {
+		let output_segment_path = output_segment_path.as_ref();
+		let TranscoderBuilder { quality } = self.builder;
+
+		if let Some(parent) = output_segment_path.parent() {
+			fs::create_dir_all(parent)
+				.await
+				.map_err(|e| FileIOError::from((parent, e)))?;
+		}
+
+		// Writing to a temporary file first, so nobody ever sees a segment that is half done
+		let partial_segment_path = output_segment_path.with_extension("part");
+
+		spawn_blocking({
+			let video_file_path = video_file_path.as_ref().to_path_buf();
+			let partial_segment_path = partial_segment_path.clone();
+			move || {
+				SegmentTranscoder::new(
+					&video_file_path,
+					&partial_segment_path,
+					quality,
+					SegmentWindow {
+						start: start.as_secs_f64(),
+						end: (start + duration).as_secs_f64(),
+					},
+				)?
+				.run()
+			}
+		})
+		.await??;
+
+		fs::rename(&partial_segment_path, output_segment_path)
+			.await
+			.map_err(|e| FileIOError::from((output_segment_path, e)).into())
+	}
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SegmentWindow {
+	start: f64,
+	end: f64,
+}
+
+pub(crate) struct FFmpegPacket(pub(crate) *mut AVPacket);
+
+impl FFmpegPacket {
+	pub(crate) fn new() -> Result<Self, FFmpegError> {
+		let ptr = unsafe { av_packet_alloc() };
+		if ptr.is_null() {
+			return Err(FFmpegError::NullError);
+		}
+		Ok(Self(ptr))
+	}
+
+	pub(crate) fn as_mut(&mut self) -> &mut AVPacket {
+		unsafe { self.0.as_mut() }.expect("initialized on struct creation")
+	}
+
+	pub(crate) fn unref(&mut self) {
+		unsafe { av_packet_unref(self.0) };
+	}
+}
+
+impl Drop for FFmpegPacket {
+	fn drop(&mut self) {
+		if !self.0.is_null() {
+			unsafe { av_packet_free(&mut self.0) };
+			self.0 = ptr::null_mut();
+		}
+	}
+}
+
+struct SegmentTranscoder {
+	input: FFmpegFormatContext,
+	output: FFmpegOutputFormatContext,
+	video: StreamTranscoder,
+	audio: Option<StreamTranscoder>,
+	packet: FFmpegPacket,
+	window: SegmentWindow,
+}
+
+impl SegmentTranscoder {
+	fn new(
+		video_file_path: &Path,
+		output_segment_path: &Path,
+		quality: TranscodeQuality,
+		window: SegmentWindow,
+	) -> Result<Self, Error> {
+		let mut input = FFmpegFormatContext::open_file(from_path(video_file_path)?.as_c_str())?;
+		input.find_stream_info()?;
+
+		let mut output = FFmpegOutputFormatContext::create_file(
+			from_path(output_segment_path)?.as_c_str(),
+			c"mpegts",
+		)?;
+
+		let (embedded, video_stream) = input.find_preferred_video_stream(false)?;
+		if embedded {
+			// Only a cover art, there is no actual video to be watched
+			return Err(FFmpegError::StreamNotFound.into());
+		}
+
+		// All streams are shifted by the same offset to keep them in sync
+		let start_time = input.start_time().unwrap_or(0);
+
+		let video =
+			StreamTranscoder::video(&input, video_stream, &mut output, quality, start_time)?;
+
+		// Videos without sound, or with a sound we can't decode, are still worth watching
+		let audio = (0..input.as_ref().nb_streams)
+			.filter_map(|index| input.stream(index))
+			.find(|stream| {
+				unsafe { stream.codecpar.as_ref() }
+					.is_some_and(|params| params.codec_type == AVMediaType::AVMEDIA_TYPE_AUDIO)
+			})
+			.and_then(|stream| {
+				StreamTranscoder::audio(stream, &mut output, quality, start_time)
+					.map_err(|e| debug!(?e, "Skipping audio stream of video;"))
+					.ok()
+			});
+
+		if window.start > 0.0 {
+			#[allow(clippy::cast_possible_truncation)]
+			// We only need microsecond precision, and the video would have to be a few hundred
+			// thousand years long to overflow
+			let timestamp = start_time + (window.start * f64::from(AV_TIME_BASE)) as i64;
+
+			// Seeking to the keyframe before the segment start, frames before it are dropped later
+			input.seek(timestamp, AVSEEK_FLAG_BACKWARD)?;
+		}
+
+		Ok(Self {
+			input,
+			output,
+			video,
+			audio,
+			packet: FFmpegPacket::new()?,
+			window,
+		})
+	}
+
+	fn run(mut self) -> Result<(), Error> {
+		self.output.write_header()?;
+
+		while !(self.video.finished && self.audio.as_ref().map_or(true, |audio| audio.finished)) {
+			self.packet.unref();
+
+			if self.input.read_frame(self.packet.0).is_err() {
+				// The video ended, so we drain whatever is left in the decoders
+				self.video
+					.decode(ptr::null_mut(), self.window, &mut self.output)?;
+				if let Some(audio) = &mut self.audio {
+					audio.decode(ptr::null_mut(), self.window, &mut self.output)?;
+				}
+				break;
+			}
+
+			let stream_index = self.packet.as_mut().stream_index;
+			if stream_index == self.video.input_index {
+				self.video
+					.decode(self.packet.0, self.window, &mut self.output)?;
+			} else if let Some(audio) = self
+				.audio
+				.as_mut()
+				.filter(|audio| audio.input_index == stream_index)
+			{
+				audio.decode(self.packet.0, self.window, &mut self.output)?;
+			}
+		}
+
+		self.video.finish(&mut self.output)?;
+		if let Some(audio) = &mut self.audio {
+			audio.finish(&mut self.output)?;
+		}
+
+		if self.video.frames_count == 0 {
+			// The requested segment is past the end of the video
+			return Err(Error::FrameDecodeError);
+		}
+
+		self.output.write_trailer()?;
+
+		Ok(())
+	}
+}
+
+/// Decodes, filters and encodes one stream of the input file into one stream of the output file
+struct StreamTranscoder {
+	input_index: c_int,
+	input_time_base: AVRational,
+	/// Start time of the input file, in the time base of this stream
+	start_offset: i64,
+	decoder: FFmpegCodecContext,
+	// Keeping the graph alive, the source and sink filter contexts are owned by it
+	_filter_graph: FFmpegFilterGraph,
+	filter_source: *mut AVFilterContext,
+	filter_sink: *mut AVFilterContext,
+	encoder: FFmpegCodecContext,
+	output_index: c_int,
+	frame: FFmpegFrame,
+	filtered_frame: FFmpegFrame,
+	encoded_packet: FFmpegPacket,
+	frames_count: u64,
+	finished: bool,
+}
+
+impl StreamTranscoder {
+	fn video(
+		input: &FFmpegFormatContext,
+		stream: &AVStream,
+		output: &mut FFmpegOutputFormatContext,
+		quality: TranscodeQuality,
+		start_time: i64,
+	) -> Result<Self, Error> {
+		let decoder = open_decoder(stream)?;
+		let ctx = decoder.as_ref();
+
+		let source_args = format!(
+			"video_size={}x{}:pix_fmt={}:time_base={}/{}:pixel_aspect={}/{}",
+			ctx.width,
+			ctx.height,
+			// AVPixelFormat is an i32 enum, so it's safe to cast it to i32
+			ctx.pix_fmt as i32,
+			stream.time_base.num,
+			stream.time_base.den,
+			ctx.sample_aspect_ratio.num,
+			i32::max(ctx.sample_aspect_ratio.den, 1)
+		);
+
+		let (filter_graph, filter_source, filter_sink) = FFmpegFilterGraph::from_description(
+			c"buffer",
+			CString::new(source_args)?.as_c_str(),
+			c"buffersink",
+			CString::new(video_filters(
+				input.get_stream_rotation_angle(u32::try_from(stream.index)?),
+				quality,
+			))?
+			.as_c_str(),
+		)?;
+
+		let encoder = open_video_encoder(filter_sink, output, quality)?;
+
+		Self::new(
+			stream,
+			start_time,
+			decoder,
+			(filter_graph, filter_source, filter_sink),
+			encoder,
+			output,
+		)
+	}
+
+	fn audio(
+		stream: &AVStream,
+		output: &mut FFmpegOutputFormatContext,
+		quality: TranscodeQuality,
+		start_time: i64,
+	) -> Result<Self, Error> {
+		let decoder = open_decoder(stream)?;
+		let source_args = abuffer_args(stream, &decoder)?;
+
+		let encoder = open_audio_encoder(output, quality)?;
+
+		// The AAC encoder needs frames with a fixed amount of samples
+		let filters = format!(
+			"aresample={AUDIO_SAMPLE_RATE},\
+			aformat=sample_fmts=fltp:channel_layouts=stereo,\
+			asetnsamples=n={}:p=0,\
+			asettb=1/{AUDIO_SAMPLE_RATE}",
+			encoder.as_ref().frame_size
+		);
+
+		let (filter_graph, filter_source, filter_sink) = FFmpegFilterGraph::from_description(
+			c"abuffer",
+			CString::new(source_args)?.as_c_str(),
+			c"abuffersink",
+			CString::new(filters)?.as_c_str(),
+		)?;
+
+		Self::new(
+			stream,
+			start_time,
+			decoder,
+			(filter_graph, filter_source, filter_sink),
+			encoder,
+			output,
+		)
+	}
+
+	fn new(
+		stream: &AVStream,
+		start_time: i64,
+		decoder: FFmpegCodecContext,
+		(filter_graph, filter_source, filter_sink): (
+			FFmpegFilterGraph,
+			&mut AVFilterContext,
+			&mut AVFilterContext,
+		),
+		encoder: FFmpegCodecContext,
+		output: &mut FFmpegOutputFormatContext,
+	) -> Result<Self, Error> {
+		Ok(Self {
+			input_index: stream.index,
+			input_time_base: stream.time_base,
+			start_offset: unsafe { av_rescale_q(start_time, AV_TIME_BASE_Q, stream.time_base) },
+			decoder,
+			_filter_graph: filter_graph,
+			filter_source: ptr::from_mut(filter_source),
+			filter_sink: ptr::from_mut(filter_sink),
+			output_index: output.new_stream(&encoder)?,
+			encoder,
+			frame: FFmpegFrame::new()?,
+			filtered_frame: FFmpegFrame::new()?,
+			encoded_packet: FFmpegPacket::new()?,
+			frames_count: 0,
+			finished: false,
+		})
+	}
+
+	/// Sends a packet to the decoder, a null packet means that the input ended and the decoder
+	/// must be drained
+	fn decode(
+		&mut self,
+		packet: *mut AVPacket,
+		window: SegmentWindow,
+		output: &mut FFmpegOutputFormatContext,
+	) -> Result<(), Error> {
+		match self.decoder.send_packet(packet) {
+			Ok(_) | Err(FFmpegError::Again) => {}
+			// A corrupt packet only costs us a few frames
+			Err(FFmpegError::InvalidData) => return Ok(()),
+			Err(e) => {
+				return Err(Error::FFmpegWithReason(
+					e,
+					"Failed to send packet to decoder".to_string(),
+				))
+			}
+		}
+
+		loop {
+			match self.decoder.receive_frame(self.frame.as_mut()) {
+				Ok(true) => self.filter(window, output)?,
+				Ok(false) | Err(FFmpegError::Again) => return Ok(()),
+				Err(e) => {
+					return Err(Error::FFmpegWithReason(
+						e,
+						"Failed to receive frame from decoder".to_string(),
+					))
+				}
+			}
+		}
+	}
+
+	/// Feeds the decoded frame to the filter graph if it belongs to the segment
+	fn filter(
+		&mut self,
+		window: SegmentWindow,
+		output: &mut FFmpegOutputFormatContext,
+	) -> Result<(), Error> {
+		let frame = self.frame.as_mut();
+		let timestamp = frame.best_effort_timestamp;
+
+		if self.finished || timestamp == AV_NOPTS_VALUE {
+			unsafe { av_frame_unref(frame) };
+			return Ok(());
+		}
+
+		let pts = timestamp - self.start_offset;
+
+		#[allow(clippy::cast_precision_loss)]
+		// We only need millisecond precision to know where the frame is
+		let seconds =
+			pts as f64 * f64::from(self.input_time_base.num) / f64::from(self.input_time_base.den);
+
+		// Decoders output frames in presentation order, so the first frame past the end of the
+		// segment means that we are done with this stream
+		if seconds >= window.end {
+			self.finished = true;
+		}
+
+		if seconds < window.start || self.finished {
+			unsafe { av_frame_unref(frame) };
+			return Ok(());
+		}
+
+		frame.pts = pts;
+		check_error(
+			unsafe { av_buffersrc_add_frame_flags(self.filter_source, frame, 0) },
+			"Failed to feed the filter graph",
+		)?;
+		self.frames_count += 1;
+
+		self.drain_filter(output)
+	}
+
+	fn drain_filter(&mut self, output: &mut FFmpegOutputFormatContext) -> Result<(), Error> {
+		loop {
+			match unsafe { av_buffersink_get_frame(self.filter_sink, self.filtered_frame.as_mut()) }
+			{
+				ret if ret == AVERROR(EAGAIN) || ret == AVERROR_EOF => return Ok(()),
+				ret => check_error(ret, "Failed to get frame from filter graph")?,
+			}
+
+			// Picture types come from the decoded video, we must let the encoder choose its own
+			let filtered_frame = self.filtered_frame.as_mut();
+			filtered_frame.pict_type = AVPictureType::AV_PICTURE_TYPE_NONE;
+			let filtered_frame = ptr::from_ref(filtered_frame);
+
+			let res = self.encode(filtered_frame, output);
+			unsafe { av_frame_unref(self.filtered_frame.as_mut()) };
+			res?;
+		}
+	}
+
+	/// Sends a frame to the encoder, writing every packet it outputs, a null frame means that
+	/// the encoder must be drained
+	fn encode(
+		&mut self,
+		frame: *const AVFrame,
+		output: &mut FFmpegOutputFormatContext,
+	) -> Result<(), Error> {
+		match self.encoder.send_frame(frame) {
+			Ok(_) | Err(FFmpegError::Again) => {}
+			Err(e) => {
+				return Err(Error::FFmpegWithReason(
+					e,
+					"Failed to send frame to encoder".to_string(),
+				))
+			}
+		}
+
+		let output_time_base = output
+			.stream_time_base(self.output_index)
+			.ok_or(FFmpegError::StreamNotFound)?;
+
+		loop {
+			match self.encoder.receive_packet(self.encoded_packet.0) {
+				Ok(true) => {
+					let packet = self.encoded_packet.as_mut();
+					packet.stream_index = self.output_index;
+					unsafe {
+						av_packet_rescale_ts(
+							packet,
+							self.encoder.as_ref().time_base,
+							output_time_base,
+						);
+					}
+
+					// The muxer takes ownership of the packet data, leaving it blank
+					output.write_packet(packet)?;
+				}
+				Ok(false) | Err(FFmpegError::Again) => return Ok(()),
+				Err(e) => {
+					return Err(Error::FFmpegWithReason(
+						e,
+						"Failed to receive packet from encoder".to_string(),
+					))
+				}
+			}
+		}
+	}
+
+	/// Drains the filter graph and the encoder after the last frame of the segment
+	fn finish(&mut self, output: &mut FFmpegOutputFormatContext) -> Result<(), Error> {
+		check_error(
+			unsafe { av_buffersrc_add_frame_flags(self.filter_source, ptr::null_mut(), 0) },
+			"Failed to flush the filter graph",
+		)?;
+		self.drain_filter(output)?;
+
+		self.encode(ptr::null(), output)
+	}
+}
+
+pub(crate) fn open_decoder(stream: &AVStream) -> Result<FFmpegCodecContext, Error> {
+	let codec_params = unsafe { stream.codecpar.as_ref() }.ok_or(FFmpegError::NullError)?;
+	let codec = unsafe { avcodec_find_decoder(codec_params.codec_id).as_ref() }
+		.ok_or(FFmpegError::DecoderNotFound)?;
+
+	let mut decoder = FFmpegCodecContext::new()?;
+	decoder.parameters_to_context(codec_params)?;
+	decoder.as_mut().pkt_timebase = stream.time_base;
+	decoder.open2(codec)?;
+
+	Ok(decoder)
+}
+
+/// Arguments for an `abuffer` filter fed with the frames of this audio decoder
+pub(crate) fn abuffer_args(
+	stream: &AVStream,
+	decoder: &FFmpegCodecContext,
+) -> Result<String, Error> {
+	let ctx = decoder.as_ref();
+
+	let mut channel_layout = [0; 64];
+	check_error(
+		unsafe {
+			av_channel_layout_describe(
+				&ctx.ch_layout,
+				channel_layout.as_mut_ptr(),
+				channel_layout.len(),
+			)
+		},
+		"Failed to describe the audio channel layout",
+	)?;
+
+	Ok(format!(
+		"time_base={}/{}:sample_rate={}:sample_fmt={}:channel_layout={}",
+		stream.time_base.num,
+		stream.time_base.den,
+		ctx.sample_rate,
+		unsafe { av_get_sample_fmt_name(ctx.sample_fmt).as_ref() }
+			.map(|name| unsafe { CStr::from_ptr(name) }.to_string_lossy())
+			.ok_or(FFmpegError::NullError)?,
+		unsafe { CStr::from_ptr(channel_layout.as_ptr()) }.to_string_lossy(),
+	))
+}
+
+/// Deinterlaces, rotates and scales the video down to the quality resolution, without ever
+/// scaling it up, in the only pixel format that browsers play
+fn video_filters(rotation: f64, quality: TranscodeQuality) -> String {
+	// Players ignore the rotation of the original video, so we have to apply it ourselves
+	let rotate = if rotation.abs() > 135.0 {
+		"hflip,vflip,"
+	} else if rotation > 45.0 {
+		"transpose=cclock,"
+	} else if rotation < -45.0 {
+		"transpose=clock,"
+	} else {
+		""
+	};
+
+	let resolution = quality.max_resolution();
+
+	format!(
+		"yadif=deint=1,{rotate}\
+		scale=w='if(gt(iw,ih),-2,min(iw,{resolution}))':h='if(gt(iw,ih),min(ih,{resolution}),-2)',\
+		format=yuv420p"
+	)
+}
+
+/// Opens the first H.264 encoder that works, configured for the output of the filter graph
+fn open_video_encoder(
+	filter_sink: &AVFilterContext,
+	output: &FFmpegOutputFormatContext,
+	quality: TranscodeQuality,
+) -> Result<FFmpegCodecContext, Error> {
+	let codecs = H264_ENCODERS
+		.iter()
+		.filter_map(|name| unsafe { avcodec_find_encoder_by_name(name.as_ptr()).as_ref() })
+		.chain(unsafe { avcodec_find_encoder(AVCodecID::AV_CODEC_ID_H264).as_ref() });
+
+	for codec in codecs {
+		let mut encoder = FFmpegCodecContext::with_codec(codec)?;
+
+		let ctx = encoder.as_mut();
+		unsafe {
+			ctx.width = av_buffersink_get_w(filter_sink);
+			ctx.height = av_buffersink_get_h(filter_sink);
+			ctx.sample_aspect_ratio = av_buffersink_get_sample_aspect_ratio(filter_sink);
+			ctx.time_base = av_buffersink_get_time_base(filter_sink);
+			ctx.framerate = av_buffersink_get_frame_rate(filter_sink);
+		}
+		ctx.pix_fmt = AVPixelFormat::AV_PIX_FMT_YUV420P;
+		ctx.bit_rate = quality.video_bit_rate();
+		ctx.rc_max_rate = quality.video_bit_rate() * 3 / 2;
+		ctx.rc_buffer_size = c_int::try_from(quality.video_bit_rate() * 2)?;
+		// No frame reordering, so every segment starts right at its first keyframe
+		ctx.max_b_frames = 0;
+		if output.needs_global_header() {
+			ctx.flags |= c_int::try_from(AV_CODEC_FLAG_GLOBAL_HEADER)?;
+		}
+
+		if !ctx.priv_data.is_null() {
+			// Encoders that don't know this option just ignore it
+			unsafe { av_opt_set(ctx.priv_data, c"preset".as_ptr(), c"veryfast".as_ptr(), 0) };
+		}
+
+		match encoder.open2(codec) {
+			Ok(_) => return Ok(encoder),
+			// Hardware encoders are available in the build even when the hardware isn't there
+			Err(e) => debug!(
+				?e,
+				encoder = %unsafe { CStr::from_ptr(codec.name) }.to_string_lossy(),
+				"Failed to open H.264 encoder, trying the next one;",
+			),
+		}
+	}
+
+	Err(FFmpegError::EncoderNotFound.into())
+}
+
+fn open_audio_encoder(
+	output: &FFmpegOutputFormatContext,
+	quality: TranscodeQuality,
+) -> Result<FFmpegCodecContext, Error> {
+	let codec = unsafe { avcodec_find_encoder(AVCodecID::AV_CODEC_ID_AAC).as_ref() }
+		.ok_or(FFmpegError::EncoderNotFound)?;
+
+	let mut encoder = FFmpegCodecContext::with_codec(codec)?;
+
+	let ctx = encoder.as_mut();
+	ctx.sample_rate = AUDIO_SAMPLE_RATE;
+	unsafe { av_channel_layout_default(&mut ctx.ch_layout, AUDIO_CHANNELS) };
+	ctx.sample_fmt = AVSampleFormat::AV_SAMPLE_FMT_FLTP;
+	ctx.bit_rate = quality.audio_bit_rate();
+	ctx.time_base = AVRational {
+		num: 1,
+		den: AUDIO_SAMPLE_RATE,
+	};
+	if output.needs_global_header() {
+		ctx.flags |= c_int::try_from(AV_CODEC_FLAG_GLOBAL_HEADER)?;
+	}
+
+	encoder.open2(codec)?;
+
+	Ok(encoder)
+}
+
+/// `TranscoderBuilder` struct holds data to build a `Transcoder` struct, exposing many methods
+/// to configure how a video must be transcoded.
+#[derive(Debug, Clone, Copy, Default)]
+#[must_use]
+pub struct TranscoderBuilder {
+	quality: TranscodeQuality,
+}
+
+impl TranscoderBuilder {
+	/// Creates a new `TranscoderBuilder` with default values:
+	/// - `quality`: [`TranscodeQuality::Medium`]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// To set the resolution and bit rates of the transcoded video
+	pub const fn quality(mut self, quality: TranscodeQuality) -> Self {
+		self.quality = quality;
+		self
+	}
+
+	/// Builds a `Transcoder` struct
+	#[must_use]
+	pub const fn build(self) -> Transcoder {
+		Transcoder { builder: self }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn quality_round_trips_through_strings() {
+		for quality in TranscodeQuality::ALL {
+			assert_eq!(
+				quality.to_string().parse::<TranscodeQuality>().unwrap(),
+				quality
+			);
+		}
+
+		assert!(matches!(
+			"ultra".parse::<TranscodeQuality>(),
+			Err(Error::InvalidTranscodeQuality(_))
+		));
+	}
+
+	#[test]
+	fn rotated_videos_are_turned_upright() {
+		assert!(video_filters(0.0, TranscodeQuality::Low).starts_with("yadif=deint=1,scale="));
+		assert!(video_filters(90.0, TranscodeQuality::Low).contains("transpose=cclock,scale="));
+		assert!(video_filters(-90.0, TranscodeQuality::Low).contains("transpose=clock,scale="));
+		assert!(video_filters(180.0, TranscodeQuality::Low).contains("hflip,vflip,scale="));
+		assert!(video_filters(-180.0, TranscodeQuality::High).contains("min(iw,1080)"));
+	}
+}
@@ -1,6 +1,14 @@
-use crate::error::{Error, FFmpegError};
-use std::ffi::CString;
-use std::path::Path;
+use crate::{
+	error::{Error, FFmpegError},
+	frame_decoder::VideoFrame,
+};
+
+use sd_utils::error::FileIOError;
+
+use std::{ffi::CString, io, path::Path};
+
+use image::{imageops, DynamicImage, RgbImage};
+use tokio::{fs, io::AsyncWriteExt};
 
 pub fn from_path(path: impl AsRef<Path>) -> Result<CString, Error> {
 	let path = path.as_ref();
@@ -30,3 +38,54 @@ pub fn check_error(return_code: i32, error_message: &str) -> Result<(), Error> {
 		Ok(())
 	}
 }
+
+/// Converts a decoded video frame to an image, applying the rotation from the video stream
+pub(crate) fn frame_to_image(
+	video_frame: VideoFrame,
+	video_file_path: &Path,
+) -> Result<DynamicImage, Error> {
+	let mut image = DynamicImage::ImageRgb8(
+		RgbImage::from_raw(video_frame.width, video_frame.height, video_frame.data)
+			.ok_or_else(|| Error::CorruptVideo(video_file_path.into()))?,
+	);
+
+	Ok(if video_frame.rotation < -135.0 {
+		imageops::rotate180_in_place(&mut image);
+		image
+	} else if video_frame.rotation > 45.0 && video_frame.rotation < 135.0 {
+		image.rotate270()
+	} else if video_frame.rotation < -45.0 && video_frame.rotation > -135.0 {
+		image.rotate90()
+	} else {
+		image
+	})
+}
+
+/// Writes the generated bytes to the output path, creating its parent directory if needed
+pub(crate) async fn write_to_file(output_path: &Path, bytes: &[u8]) -> Result<(), Error> {
+	let path = output_path.parent().ok_or_else(|| {
+		FileIOError::from((
+			output_path,
+			io::Error::new(
+				io::ErrorKind::InvalidInput,
+				"Cannot determine parent directory",
+			),
+		))
+	})?;
+
+	fs::create_dir_all(path)
+		.await
+		.map_err(|e| FileIOError::from((path, e)))?;
+
+	let mut file = fs::File::create(output_path)
+		.await
+		.map_err(|e: io::Error| FileIOError::from((output_path, e)))?;
+
+	file.write_all(bytes)
+		.await
+		.map_err(|e| FileIOError::from((output_path, e)))?;
+
+	file.sync_all()
+		.await
+		.map_err(|e| FileIOError::from((output_path, e)).into())
+}
@@ -0,0 +1,283 @@
+use crate::{
+	codec_ctx::FFmpegCodecContext,
+	error::{Error, FFmpegError},
+	filter_graph::FFmpegFilterGraph,
+	format_ctx::FFmpegFormatContext,
+	transcoder::{abuffer_args, open_decoder, FFmpegPacket},
+	utils::{check_error, from_path},
+	video_frame::FFmpegFrame,
+};
+
+use std::{
+	ffi::{c_int, CString},
+	path::Path,
+	ptr, slice,
+};
+
+use ffmpeg_sys_next::{
+	av_buffersink_get_frame, av_buffersrc_add_frame_flags, av_frame_unref, AVFilterContext,
+	AVFrame, AVMediaType, AVPacket, AVERROR, AVERROR_EOF, EAGAIN,
+};
+use tokio::task::spawn_blocking;
+
+/// Samples are first summarized in windows of this size, which are merged into the requested
+/// amount of buckets once we know how long the audio is
+const WINDOW_SAMPLES: usize = 1024;
+
+/// Downsampled amplitude of an audio stream, mixed down to mono, with values in `[0.0, 1.0]`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Waveform {
+	/// Highest absolute sample value of each bucket
+	pub peaks: Vec<f32>,
+	/// Root mean square of the samples of each bucket, closer to how loud it sounds
+	pub rms: Vec<f32>,
+}
+
+impl Waveform {
+	/// Decodes the first audio stream of a file into a waveform with `buckets` values
+	pub(crate) async fn from_path(
+		audio_file_path: impl AsRef<Path> + Send,
+		buckets: usize,
+	) -> Result<Self, Error> {
+		spawn_blocking({
+			let audio_file_path = audio_file_path.as_ref().to_path_buf();
+			move || WaveformDecoder::new(&audio_file_path)?.run(buckets)
+		})
+		.await?
+	}
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Window {
+	peak: f32,
+	sum_of_squares: f64,
+	samples: usize,
+}
+
+/// Summarizes samples as they are decoded, so we never hold the whole audio in memory
+#[derive(Debug, Default)]
+struct WaveformAccumulator {
+	windows: Vec<Window>,
+}
+
+impl WaveformAccumulator {
+	fn push(&mut self, samples: &[f32]) {
+		for &sample in samples {
+			let window = match self.windows.last_mut() {
+				Some(window) if window.samples < WINDOW_SAMPLES => window,
+				_ => {
+					self.windows.push(Window::default());
+					self.windows.last_mut().expect("just pushed")
+				}
+			};
+
+			window.peak = window.peak.max(sample.abs());
+			window.sum_of_squares =
+				f64::from(sample).mul_add(f64::from(sample), window.sum_of_squares);
+			window.samples += 1;
+		}
+	}
+
+	/// Merges the windows into `buckets` values, short audio gets less buckets than asked for
+	fn finish(self, buckets: usize) -> Waveform {
+		let len = self.windows.len();
+		let buckets = buckets.min(len);
+
+		let (peaks, rms) = (0..buckets)
+			.map(|i| {
+				let start = i * len / buckets;
+				let end = ((i + 1) * len / buckets).max(start + 1);
+
+				let (peak, sum_of_squares, samples) = self.windows[start..end].iter().fold(
+					(0.0_f32, 0.0, 0),
+					|(peak, sum_of_squares, samples), window| {
+						(
+							peak.max(window.peak),
+							sum_of_squares + window.sum_of_squares,
+							samples + window.samples,
+						)
+					},
+				);
+
+				#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+				// SAFETY: The RMS of samples in [-1.0, 1.0] is in [0.0, 1.0], so it fits a f32, and
+				// buckets have way less samples than needed to lose precision
+				let rms = (sum_of_squares / samples as f64).sqrt() as f32;
+
+				// Float samples may go a bit over full scale
+				(peak.min(1.0), rms.min(1.0))
+			})
+			.unzip();
+
+		Waveform { peaks, rms }
+	}
+}
+
+struct WaveformDecoder {
+	input: FFmpegFormatContext,
+	input_index: c_int,
+	decoder: FFmpegCodecContext,
+	// Keeping the graph alive, the source and sink filter contexts are owned by it
+	_filter_graph: FFmpegFilterGraph,
+	filter_source: *mut AVFilterContext,
+	filter_sink: *mut AVFilterContext,
+	packet: FFmpegPacket,
+	frame: FFmpegFrame,
+	filtered_frame: FFmpegFrame,
+	accumulator: WaveformAccumulator,
+}
+
+impl WaveformDecoder {
+	fn new(audio_file_path: &Path) -> Result<Self, Error> {
+		let mut input = FFmpegFormatContext::open_file(from_path(audio_file_path)?.as_c_str())?;
+		input.find_stream_info()?;
+
+		let stream = (0..input.as_ref().nb_streams)
+			.filter_map(|index| input.stream(index))
+			.find(|stream| {
+				unsafe { stream.codecpar.as_ref() }
+					.is_some_and(|params| params.codec_type == AVMediaType::AVMEDIA_TYPE_AUDIO)
+			})
+			.ok_or(FFmpegError::StreamNotFound)?;
+
+		let decoder = open_decoder(stream)?;
+		let input_index = stream.index;
+
+		// Mixing every channel down to mono float samples, which are already in [-1.0, 1.0]
+		let (filter_graph, filter_source, filter_sink) = FFmpegFilterGraph::from_description(
+			c"abuffer",
+			CString::new(abuffer_args(stream, &decoder)?)?.as_c_str(),
+			c"abuffersink",
+			c"aformat=sample_fmts=flt:channel_layouts=mono",
+		)?;
+
+		Ok(Self {
+			input,
+			input_index,
+			decoder,
+			_filter_graph: filter_graph,
+			filter_source: ptr::from_mut(filter_source),
+			filter_sink: ptr::from_mut(filter_sink),
+			packet: FFmpegPacket::new()?,
+			frame: FFmpegFrame::new()?,
+			filtered_frame: FFmpegFrame::new()?,
+			accumulator: WaveformAccumulator::default(),
+		})
+	}
+
+	fn run(mut self, buckets: usize) -> Result<Waveform, Error> {
+		loop {
+			self.packet.unref();
+
+			if self.input.read_frame(self.packet.0).is_err() {
+				// The audio ended, so we drain whatever is left in the decoder and filter graph
+				self.decode(ptr::null_mut())?;
+				self.filter(ptr::null_mut())?;
+				break;
+			}
+
+			if self.packet.as_mut().stream_index == self.input_index {
+				self.decode(self.packet.0)?;
+			}
+		}
+
+		if self.accumulator.windows.is_empty() {
+			return Err(Error::FrameDecodeError);
+		}
+
+		Ok(self.accumulator.finish(buckets))
+	}
+
+	/// Sends a packet to the decoder, a null packet means that the input ended and the decoder
+	/// must be drained
+	fn decode(&mut self, packet: *mut AVPacket) -> Result<(), Error> {
+		match self.decoder.send_packet(packet) {
+			Ok(_) | Err(FFmpegError::Again) => {}
+			// A corrupt packet only costs us a few samples
+			Err(FFmpegError::InvalidData) => return Ok(()),
+			Err(e) => {
+				return Err(Error::FFmpegWithReason(
+					e,
+					"Failed to send packet to decoder".to_string(),
+				))
+			}
+		}
+
+		loop {
+			match self.decoder.receive_frame(self.frame.as_mut()) {
+				Ok(true) => {
+					let frame = ptr::from_mut(self.frame.as_mut());
+					let res = self.filter(frame);
+					unsafe { av_frame_unref(self.frame.as_mut()) };
+					res?;
+				}
+				Ok(false) | Err(FFmpegError::Again) => return Ok(()),
+				Err(e) => {
+					return Err(Error::FFmpegWithReason(
+						e,
+						"Failed to receive frame from decoder".to_string(),
+					))
+				}
+			}
+		}
+	}
+
+	/// Feeds a decoded frame to the filter graph and accumulates every frame it outputs, a null
+	/// frame means that the filter graph must be drained
+	fn filter(&mut self, frame: *mut AVFrame) -> Result<(), Error> {
+		check_error(
+			unsafe { av_buffersrc_add_frame_flags(self.filter_source, frame, 0) },
+			"Failed to feed the filter graph",
+		)?;
+
+		loop {
+			match unsafe { av_buffersink_get_frame(self.filter_sink, self.filtered_frame.as_mut()) }
+			{
+				ret if ret == AVERROR(EAGAIN) || ret == AVERROR_EOF => return Ok(()),
+				ret => check_error(ret, "Failed to get frame from filter graph")?,
+			}
+
+			let filtered_frame = self.filtered_frame.as_mut();
+			if let Ok(samples) = usize::try_from(filtered_frame.nb_samples) {
+				if !filtered_frame.data[0].is_null() {
+					// SAFETY: The filter graph outputs packed mono floats, so the first data plane
+					// holds exactly `nb_samples` of them
+					#[allow(clippy::cast_ptr_alignment)]
+					self.accumulator.push(unsafe {
+						slice::from_raw_parts(filtered_frame.data[0].cast::<f32>(), samples)
+					});
+				}
+			}
+
+			unsafe { av_frame_unref(self.filtered_frame.as_mut()) };
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn samples_are_merged_into_buckets() {
+		let mut accumulator = WaveformAccumulator::default();
+		accumulator.push(&vec![0.5; WINDOW_SAMPLES * 2]);
+		accumulator.push(&vec![-0.8; WINDOW_SAMPLES]);
+		accumulator.push(&vec![0.0; WINDOW_SAMPLES]);
+
+		let Waveform { peaks, rms } = accumulator.finish(2);
+		assert_eq!(peaks, [0.5, 0.8]);
+		assert!((rms[0] - 0.5).abs() < 1e-6);
+		assert!((rms[1] - 0.8 / 2.0_f32.sqrt()).abs() < 1e-6);
+	}
+
+	#[test]
+	fn short_audio_gets_less_buckets() {
+		let mut accumulator = WaveformAccumulator::default();
+		accumulator.push(&[0.25, -1.25, 0.25]);
+
+		let waveform = accumulator.finish(256);
+		assert_eq!(waveform.peaks, [1.0]);
+		assert_eq!(waveform.rms.len(), 1);
+	}
+}
@@ -179,7 +179,7 @@ extension_category_enum! {
 
 // text file extensions
 extension_category_enum! {
-	TextExtension _ALL_TEXT_EXTENSIONS {
+	TextExtension ALL_TEXT_EXTENSIONS {
 		Txt,
 		Rtf,
 		Md,
@@ -188,7 +188,7 @@ extension_category_enum! {
 }
 // config file extensions
 extension_category_enum! {
-	ConfigExtension _ALL_CONFIG_EXTENSIONS {
+	ConfigExtension ALL_CONFIG_EXTENSIONS {
 		Ini,
 		Json,
 		Yaml,
@@ -248,7 +248,7 @@ extension_category_enum! {
 
 // code extensions
 extension_category_enum! {
-	CodeExtension _ALL_CODE_EXTENSIONS {
+	CodeExtension ALL_CODE_EXTENSIONS {
 		// AppleScript
 		Scpt,
 		Scptd,
@@ -39,6 +39,20 @@ pub const SVG_TARGET_PX: f32 = 262_144f32;
 pub const PDF_PORTRAIT_RENDER_WIDTH: pdfium_render::prelude::Pixels = 794;
 pub const PDF_LANDSCAPE_RENDER_WIDTH: pdfium_render::prelude::Pixels = 1123;
 
+/// The widest a single PDF page is rendered at for paged previews, enough for a full screen
+/// preview on high density displays.
+pub const PDF_MAX_PAGE_RENDER_WIDTH: u32 = 4096;
+
+/// Text and code previews are rendered as a page with the same size of PDF ones, fitting
+/// [`TEXT_PAGE_LINES`] lines of [`TEXT_PAGE_COLUMNS`] characters of a monospace font.
+pub const TEXT_PAGE_WIDTH: u32 = 794;
+pub const TEXT_PAGE_HEIGHT: u32 = 1123;
+pub const TEXT_PAGE_PADDING: u32 = 40;
+pub const TEXT_PAGE_FONT_SIZE: u32 = 14;
+pub const TEXT_PAGE_LINES: usize = 49;
+pub const TEXT_PAGE_COLUMNS: usize = 85;
+pub const TEXT_TAB_WIDTH: usize = 4;
+
 #[cfg_attr(feature = "specta", derive(specta::Type))]
 #[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
 #[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
@@ -4,6 +4,7 @@ use crate::{
 	generic::GenericHandler,
 	pdf::PdfHandler,
 	svg::SvgHandler,
+	text::TextHandler,
 	ImageHandler,
 };
 use image::DynamicImage;
@@ -20,6 +21,12 @@ pub fn format_image(path: impl AsRef<Path>) -> Result<DynamicImage> {
 	match_to_handler(path.extension())?.handle_image(path)
 }
 
+/// Renders the first page of a text or code file, these are told apart from other files by their
+/// kind instead of their extension, so they aren't matched by [`format_image`]
+pub fn format_text(path: impl AsRef<Path>) -> Result<DynamicImage> {
+	TextHandler {}.handle_image(path.as_ref())
+}
+
 pub fn convert_image(path: impl AsRef<Path>, desired_ext: &OsStr) -> Result<DynamicImage> {
 	let path = path.as_ref();
 	match_to_handler(path.extension())?.convert_image(match_to_handler(Some(desired_ext))?, path)
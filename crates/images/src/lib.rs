@@ -36,15 +36,19 @@ mod handler;
 #[cfg(feature = "heif")]
 mod heif;
 mod pdf;
+mod perceptual_hash;
 mod svg;
+mod text;
 
 use consts::MAXIMUM_FILE_SIZE;
 
 // Re-exports
-pub use consts::{all_compatible_extensions, ConvertibleExtension};
+pub use consts::{all_compatible_extensions, ConvertibleExtension, PDF_MAX_PAGE_RENDER_WIDTH};
 pub use error::{Error, Result};
-pub use handler::{convert_image, format_image};
+pub use handler::{convert_image, format_image, format_text};
 pub use image::DynamicImage;
+pub use pdf::{pdf_page_count, render_pdf_page};
+pub use perceptual_hash::{PerceptualHash, PerceptualHashIndex};
 
 pub trait ImageHandler {
 	#[inline]
@@ -5,11 +5,13 @@ use std::{
 };
 
 use crate::{
-	consts::{PDF_LANDSCAPE_RENDER_WIDTH, PDF_PORTRAIT_RENDER_WIDTH},
-	ImageHandler, Result,
+	consts::{PDF_LANDSCAPE_RENDER_WIDTH, PDF_MAX_PAGE_RENDER_WIDTH, PDF_PORTRAIT_RENDER_WIDTH},
+	Error, ImageHandler, Result,
 };
 use image::DynamicImage;
-use pdfium_render::prelude::{PdfColor, PdfPageRenderRotation, PdfRenderConfig, Pdfium};
+use pdfium_render::prelude::{
+	PdfColor, PdfPageIndex, PdfPageRenderRotation, PdfRenderConfig, Pdfium, Pixels,
+};
 use tracing::error;
 
 // This path must be relative to the running binary
@@ -73,14 +75,51 @@ static LANDSCAPE_CONFIG: LazyLock<PdfRenderConfig> = LazyLock::new(|| {
 	thumbnail_config(PdfRenderConfig::new().set_target_width(PDF_LANDSCAPE_RENDER_WIDTH))
 });
 
+fn bind_pdfium() -> Result<Pdfium> {
+	Ok(Pdfium::new(
+		Pdfium::bind_to_library(PDFIUM_LIB.as_str()).or_else(|err| {
+			error!("{err:#?}");
+			Pdfium::bind_to_system_library()
+		})?,
+	))
+}
+
+/// How many pages a PDF document has
+pub fn pdf_page_count(path: impl AsRef<Path>) -> Result<PdfPageIndex> {
+	let pdfium = bind_pdfium()?;
+	let pdf = pdfium.load_pdf_from_file(path.as_ref(), None)?;
+
+	Ok(pdf.pages().len())
+}
+
+/// Renders the page at the zero based `page` index of a PDF document, scaled to `width` pixels
+/// while keeping its aspect ratio. Widths are capped to [`PDF_MAX_PAGE_RENDER_WIDTH`].
+pub fn render_pdf_page(
+	path: impl AsRef<Path>,
+	page: PdfPageIndex,
+	width: u32,
+) -> Result<DynamicImage> {
+	let pdfium = bind_pdfium()?;
+	let pdf = pdfium.load_pdf_from_file(path.as_ref(), None)?;
+
+	let pages = pdf.pages();
+	if page >= pages.len() {
+		return Err(Error::PageNotFound(page));
+	}
+
+	let config = thumbnail_config(
+		PdfRenderConfig::new()
+			.set_target_width(Pixels::try_from(width.clamp(1, PDF_MAX_PAGE_RENDER_WIDTH))?),
+	);
+
+	Ok(pages.get(page)?.render_with_config(&config)?.as_image())
+}
+
 pub struct PdfHandler {}
 
 impl ImageHandler for PdfHandler {
 	fn handle_image(&self, path: &Path) -> Result<DynamicImage> {
-		let pdfium = Pdfium::new(Pdfium::bind_to_library(PDFIUM_LIB.as_str()).or_else(|err| {
-			error!("{err:#?}");
-			Pdfium::bind_to_system_library()
-		})?);
+		let pdfium = bind_pdfium()?;
 
 		let pdf = pdfium.load_pdf_from_file(path, None)?;
 		let first_page = pdf.pages().first()?;
@@ -0,0 +1,218 @@
+use image::{imageops::FilterType, DynamicImage};
+
+/// Side of the grayscale grid we compare, one column larger as each bit comes from a pair of
+/// horizontally adjacent pixels
+const HASH_WIDTH: u32 = 9;
+const HASH_HEIGHT: u32 = 8;
+
+/// A perceptual hash (dHash) of an image, which is the same or very close for resized, re-encoded
+/// or slightly edited versions of the same image, unlike a content hash.
+///
+/// Similarity between images is the [`PerceptualHash::distance`] between their hashes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PerceptualHash(u64);
+
+impl PerceptualHash {
+	/// Any image works here, but an already downscaled one, like a thumbnail, is way cheaper
+	#[must_use]
+	pub fn from_image(image: &DynamicImage) -> Self {
+		let grid = image
+			.resize_exact(HASH_WIDTH, HASH_HEIGHT, FilterType::Triangle)
+			.into_luma8();
+
+		let mut hash = 0;
+		for y in 0..HASH_HEIGHT {
+			for x in 0..HASH_WIDTH - 1 {
+				hash <<= 1;
+				if grid.get_pixel(x, y).0[0] < grid.get_pixel(x + 1, y).0[0] {
+					hash |= 1;
+				}
+			}
+		}
+
+		Self(hash)
+	}
+
+	/// Hamming distance between both hashes, from 0 for the same image to 64
+	#[must_use]
+	pub const fn distance(self, other: Self) -> u32 {
+		(self.0 ^ other.0).count_ones()
+	}
+}
+
+/// Hashes are stored in the database as `i64`, as `SQLite` doesn't have unsigned integers
+impl From<PerceptualHash> for i64 {
+	fn from(PerceptualHash(hash): PerceptualHash) -> Self {
+		Self::from_ne_bytes(hash.to_ne_bytes())
+	}
+}
+
+impl From<i64> for PerceptualHash {
+	fn from(hash: i64) -> Self {
+		Self(u64::from_ne_bytes(hash.to_ne_bytes()))
+	}
+}
+
+/// An index of perceptual hashes, to find the ones close to another hash without comparing all
+/// of them. It's a BK-tree, as the Hamming distance is a metric.
+#[derive(Debug)]
+pub struct PerceptualHashIndex<T> {
+	nodes: Vec<IndexNode<T>>,
+}
+
+#[derive(Debug)]
+struct IndexNode<T> {
+	hash: PerceptualHash,
+	value: T,
+	/// Indexes of children nodes, along with their distance to this node
+	children: Vec<(u32, usize)>,
+}
+
+impl<T> Default for PerceptualHashIndex<T> {
+	fn default() -> Self {
+		Self { nodes: Vec::new() }
+	}
+}
+
+impl<T> FromIterator<(PerceptualHash, T)> for PerceptualHashIndex<T> {
+	fn from_iter<I: IntoIterator<Item = (PerceptualHash, T)>>(iter: I) -> Self {
+		let mut index = Self::default();
+		for (hash, value) in iter {
+			index.insert(hash, value);
+		}
+
+		index
+	}
+}
+
+impl<T> PerceptualHashIndex<T> {
+	#[must_use]
+	pub const fn len(&self) -> usize {
+		self.nodes.len()
+	}
+
+	#[must_use]
+	pub const fn is_empty(&self) -> bool {
+		self.nodes.is_empty()
+	}
+
+	pub fn insert(&mut self, hash: PerceptualHash, value: T) {
+		let new_node = self.nodes.len();
+
+		if new_node > 0 {
+			let mut current = 0;
+			loop {
+				let distance = self.nodes[current].hash.distance(hash);
+
+				if let Some(&(_, child)) = self.nodes[current]
+					.children
+					.iter()
+					.find(|(child_distance, _)| *child_distance == distance)
+				{
+					current = child;
+				} else {
+					self.nodes[current].children.push((distance, new_node));
+					break;
+				}
+			}
+		}
+
+		self.nodes.push(IndexNode {
+			hash,
+			value,
+			children: Vec::new(),
+		});
+	}
+
+	/// Finds all values with hashes within `max_distance` of `hash`, along with their distances,
+	/// in no particular order
+	#[must_use]
+	pub fn find_within(&self, hash: PerceptualHash, max_distance: u32) -> Vec<(&T, u32)> {
+		let mut found = Vec::new();
+
+		if self.nodes.is_empty() {
+			return found;
+		}
+
+		let mut pending = vec![0];
+		while let Some(current) = pending.pop() {
+			let node = &self.nodes[current];
+			let distance = node.hash.distance(hash);
+
+			if distance <= max_distance {
+				found.push((&node.value, distance));
+			}
+
+			// By the triangle inequality, only these subtrees can have close enough hashes
+			pending.extend(
+				node.children
+					.iter()
+					.filter(|(child_distance, _)| child_distance.abs_diff(distance) <= max_distance)
+					.map(|&(_, child)| child),
+			);
+		}
+
+		found
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use image::{Rgb, RgbImage};
+
+	fn gradient(width: u32, height: u32) -> DynamicImage {
+		DynamicImage::ImageRgb8(RgbImage::from_fn(width, height, |x, _| {
+			#[allow(clippy::cast_possible_truncation)]
+			let value = (x * 255 / width) as u8;
+			Rgb([value, value, value])
+		}))
+	}
+
+	#[test]
+	fn resized_images_have_close_hashes() {
+		let original = PerceptualHash::from_image(&gradient(640, 480));
+		let resized = PerceptualHash::from_image(&gradient(160, 120));
+
+		assert!(original.distance(resized) <= 4);
+	}
+
+	#[test]
+	fn different_images_have_distant_hashes() {
+		let image = gradient(640, 480);
+
+		let hash = PerceptualHash::from_image(&image);
+
+		assert_eq!(hash.distance(hash), 0);
+		assert!(hash.distance(PerceptualHash::from_image(&image.fliph())) > 32);
+	}
+
+	#[test]
+	fn database_round_trip() {
+		let hash = PerceptualHash(u64::MAX - 1);
+
+		assert_eq!(PerceptualHash::from(i64::from(hash)), hash);
+	}
+
+	#[test]
+	fn index_finds_only_close_hashes() {
+		let hashes = [0b0000, 0b0001, 0b0011, 0b1111, u64::MAX, 0b0001];
+		let index = hashes
+			.iter()
+			.enumerate()
+			.map(|(i, hash)| (PerceptualHash(*hash), i))
+			.collect::<PerceptualHashIndex<_>>();
+
+		assert_eq!(index.len(), hashes.len());
+
+		let mut found = index.find_within(PerceptualHash(0), 2);
+		found.sort_unstable();
+
+		assert_eq!(found, vec![(&0, 0), (&1, 1), (&2, 2), (&5, 1)]);
+		assert!(PerceptualHashIndex::<()>::default()
+			.find_within(PerceptualHash(0), 64)
+			.is_empty());
+	}
+}
@@ -0,0 +1,455 @@
+use std::{fmt::Write, fs::File, io::Read, path::Path};
+
+use crate::{
+	consts::{
+		TEXT_PAGE_COLUMNS, TEXT_PAGE_FONT_SIZE, TEXT_PAGE_HEIGHT, TEXT_PAGE_LINES,
+		TEXT_PAGE_PADDING, TEXT_PAGE_WIDTH, TEXT_TAB_WIDTH,
+	},
+	svg::usvg_options,
+	Error, ImageHandler, Result,
+};
+use image::DynamicImage;
+use resvg::{tiny_skia, usvg};
+
+/// Fonts are tried in order, as which ones are available depends on the system
+const MONOSPACE_FONTS: &str =
+	"Menlo, Consolas, 'DejaVu Sans Mono', 'Liberation Mono', 'Noto Sans Mono', 'Courier New', monospace";
+
+/// Keywords of the most common programming languages, highlighted in any code file
+const KEYWORDS: &[&str] = &[
+	"abstract",
+	"as",
+	"async",
+	"await",
+	"break",
+	"case",
+	"catch",
+	"class",
+	"const",
+	"continue",
+	"def",
+	"default",
+	"defer",
+	"del",
+	"do",
+	"elif",
+	"else",
+	"end",
+	"enum",
+	"except",
+	"export",
+	"extends",
+	"extern",
+	"false",
+	"final",
+	"finally",
+	"fn",
+	"for",
+	"from",
+	"func",
+	"function",
+	"go",
+	"if",
+	"impl",
+	"implements",
+	"import",
+	"in",
+	"interface",
+	"is",
+	"lambda",
+	"let",
+	"loop",
+	"match",
+	"mod",
+	"module",
+	"mut",
+	"namespace",
+	"new",
+	"nil",
+	"none",
+	"None",
+	"not",
+	"null",
+	"or",
+	"and",
+	"package",
+	"private",
+	"protected",
+	"pub",
+	"public",
+	"raise",
+	"return",
+	"self",
+	"static",
+	"struct",
+	"super",
+	"switch",
+	"then",
+	"this",
+	"throw",
+	"throws",
+	"trait",
+	"true",
+	"try",
+	"type",
+	"typeof",
+	"union",
+	"unsafe",
+	"use",
+	"using",
+	"val",
+	"var",
+	"void",
+	"where",
+	"while",
+	"with",
+	"yield",
+	"True",
+	"False",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+	Plain,
+	Comment,
+	String,
+	Number,
+	Keyword,
+}
+
+impl TokenKind {
+	/// Colors of a light theme, as the page is drawn over a white background
+	const fn color(self) -> &'static str {
+		match self {
+			Self::Plain => "#24292f",
+			Self::Comment => "#6e7781",
+			Self::String => "#0a3069",
+			Self::Number => "#0550ae",
+			Self::Keyword => "#cf222e",
+		}
+	}
+}
+
+/// How comments look like in a language, anything else is highlighted the same for all languages
+#[derive(Debug, Clone, Copy)]
+struct Syntax {
+	highlight: bool,
+	/// Languages where single quotes are also used for lifetimes or type variables, like `'a`,
+	/// only get double quoted strings highlighted
+	single_quoted_strings: bool,
+	line_comments: &'static [&'static str],
+	block_comment: Option<(&'static str, &'static str)>,
+}
+
+impl Syntax {
+	const PLAIN: Self = Self {
+		highlight: false,
+		single_quoted_strings: false,
+		line_comments: &[],
+		block_comment: None,
+	};
+
+	fn from_extension(extension: &str) -> Self {
+		let extension = extension.to_ascii_lowercase();
+
+		let (line_comments, block_comment): (&[&str], _) = match extension.as_str() {
+			"txt" | "md" | "markdown" | "rtf" | "" => return Self::PLAIN,
+			"py" | "rb" | "sh" | "bash" | "zsh" | "fish" | "pl" | "r" | "cr" | "nim" | "nims"
+			| "make" | "dockerfile" | "yaml" | "yml" | "toml" | "cfg" | "compose" | "ps1"
+			| "psd1" | "psm1" => (&["#"], None),
+			"ini" => (&[";", "#"], None),
+			"json" | "csv" => (&[], None),
+			"sql" => (&["--"], Some(("/*", "*/"))),
+			"lua" => (&["--"], Some(("--[[", "]]"))),
+			"hs" => (&["--"], Some(("{-", "-}"))),
+			"applescript" => (&["--", "#"], Some(("(*", "*)"))),
+			"ml" | "mli" | "mll" | "mly" => (&[], Some(("(*", "*)"))),
+			"html" | "xml" | "mathml" | "rss" | "vue" | "astro" | "mdx" => {
+				(&[], Some(("<!--", "-->")))
+			}
+			"css" => (&[], Some(("/*", "*/"))),
+			"php" | "php1" | "php2" | "php3" | "php4" | "php5" | "php6" | "phps" | "phpt"
+			| "phtml" => (&["//", "#"], Some(("/*", "*/"))),
+			// Most languages follow C
+			_ => (&["//"], Some(("/*", "*/"))),
+		};
+
+		Self {
+			highlight: true,
+			single_quoted_strings: !matches!(
+				extension.as_str(),
+				"rs" | "hs" | "ml" | "mli" | "mll" | "mly"
+			),
+			line_comments,
+			block_comment,
+		}
+	}
+}
+
+pub struct TextHandler {}
+
+impl ImageHandler for TextHandler {
+	/// Renders the first page of a text or code file, highlighted according to its extension
+	fn handle_image(&self, path: &Path) -> Result<DynamicImage> {
+		let syntax = Syntax::from_extension(
+			path.extension()
+				.and_then(|extension| extension.to_str())
+				.unwrap_or_default(),
+		);
+
+		let svg = page_svg(&first_page(path)?, syntax);
+
+		let rtree = usvg::Tree::from_str(&svg, &usvg_options())?;
+
+		let Some(mut pixmap) = tiny_skia::Pixmap::new(TEXT_PAGE_WIDTH, TEXT_PAGE_HEIGHT) else {
+			return Err(Error::Pixbuf);
+		};
+
+		resvg::render(
+			&rtree,
+			tiny_skia::Transform::default(),
+			&mut pixmap.as_mut(),
+		);
+
+		image::RgbaImage::from_raw(pixmap.width(), pixmap.height(), pixmap.data().into())
+			.map_or_else(
+				|| Err(Error::RgbImageConversion),
+				|x| Ok(DynamicImage::ImageRgba8(x)),
+			)
+	}
+}
+
+/// Reads just enough of the file to fill a page, as its lines are cut to fit anyway
+fn first_page(path: &Path) -> Result<String> {
+	let mut bytes = Vec::new();
+
+	File::open(path)
+		.and_then(|file| {
+			// Up to 4 bytes per character in UTF-8
+			file.take(u64::try_from(TEXT_PAGE_LINES * TEXT_PAGE_COLUMNS * 4).unwrap_or(u64::MAX))
+				.read_to_end(&mut bytes)
+		})
+		.map_err(|e| Error::Io(e, path.to_path_buf().into_boxed_path()))?;
+
+	// Binary files have no business being rendered as text
+	if bytes.contains(&0) {
+		return Err(Error::Unsupported);
+	}
+
+	Ok(String::from_utf8_lossy(&bytes)
+		.lines()
+		.take(TEXT_PAGE_LINES)
+		.map(|line| {
+			line.replace('\t', &" ".repeat(TEXT_TAB_WIDTH))
+				.chars()
+				.take(TEXT_PAGE_COLUMNS)
+				.collect::<String>()
+		})
+		.collect::<Vec<_>>()
+		.join("\n"))
+}
+
+fn page_svg(text: &str, syntax: Syntax) -> String {
+	let line_height = TEXT_PAGE_FONT_SIZE * 3 / 2;
+
+	let mut svg = format!(
+		"<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{TEXT_PAGE_WIDTH}\" \
+		height=\"{TEXT_PAGE_HEIGHT}\"><rect width=\"100%\" height=\"100%\" fill=\"#ffffff\"/>\
+		<g font-family=\"{MONOSPACE_FONTS}\" font-size=\"{TEXT_PAGE_FONT_SIZE}\" \
+		xml:space=\"preserve\">"
+	);
+
+	let mut y = TEXT_PAGE_PADDING + TEXT_PAGE_FONT_SIZE;
+	svg.push_str(&line_start(y));
+
+	for (kind, token) in tokenize(text, syntax) {
+		for (i, part) in token.split('\n').enumerate() {
+			if i > 0 {
+				y += line_height;
+				svg.push_str("</text>");
+				svg.push_str(&line_start(y));
+			}
+
+			if !part.is_empty() {
+				let _ = write!(
+					svg,
+					"<tspan fill=\"{}\">{}</tspan>",
+					kind.color(),
+					escape_xml(part)
+				);
+			}
+		}
+	}
+
+	svg.push_str("</text></g></svg>");
+
+	svg
+}
+
+fn line_start(y: u32) -> String {
+	format!("<text x=\"{TEXT_PAGE_PADDING}\" y=\"{y}\">")
+}
+
+fn escape_xml(text: &str) -> String {
+	text.replace('&', "&amp;")
+		.replace('<', "&lt;")
+		.replace('>', "&gt;")
+}
+
+/// Splits the text in highlighted tokens, merging consecutive plain ones
+fn tokenize(text: &str, syntax: Syntax) -> Vec<(TokenKind, &str)> {
+	let mut tokens = Vec::<(TokenKind, &str)>::new();
+	let mut start = 0;
+
+	while start < text.len() {
+		let (kind, len) = next_token(&text[start..], syntax);
+
+		match tokens.last_mut() {
+			Some((TokenKind::Plain, plain)) if kind == TokenKind::Plain => {
+				let plain_start = start - plain.len();
+				*plain = &text[plain_start..start + len];
+			}
+			_ => tokens.push((kind, &text[start..start + len])),
+		}
+
+		start += len;
+	}
+
+	tokens
+}
+
+/// Kind and length in bytes of the token at the start of the text
+fn next_token(text: &str, syntax: Syntax) -> (TokenKind, usize) {
+	if !syntax.highlight {
+		return (TokenKind::Plain, text.len());
+	}
+
+	if let Some((start, end)) = syntax.block_comment {
+		if let Some(body) = text.strip_prefix(start) {
+			let len = body
+				.find(end)
+				.map_or(text.len(), |i| start.len() + i + end.len());
+			return (TokenKind::Comment, len);
+		}
+	}
+
+	if syntax
+		.line_comments
+		.iter()
+		.any(|prefix| text.starts_with(prefix))
+	{
+		return (TokenKind::Comment, text.find('\n').unwrap_or(text.len()));
+	}
+
+	let Some(first) = text.chars().next() else {
+		return (TokenKind::Plain, 0);
+	};
+
+	if first == '"' || first == '`' || (first == '\'' && syntax.single_quoted_strings) {
+		if let Some(len) = string_len(text, first) {
+			return (TokenKind::String, len);
+		}
+	} else if first.is_ascii_digit() {
+		return (TokenKind::Number, number_len(text));
+	} else if is_word_char(first) {
+		let len = word_len(text);
+		let kind = if KEYWORDS.contains(&&text[..len]) {
+			TokenKind::Keyword
+		} else {
+			TokenKind::Plain
+		};
+		return (kind, len);
+	}
+
+	(TokenKind::Plain, first.len_utf8())
+}
+
+/// Length of a string literal starting with `quote`, only template literals span many lines, and
+/// unterminated quotes (like Rust lifetimes) aren't strings at all
+fn string_len(text: &str, quote: char) -> Option<usize> {
+	let mut chars = text.char_indices().skip(1);
+
+	while let Some((i, c)) = chars.next() {
+		match c {
+			'\\' => {
+				chars.next();
+			}
+			'\n' if quote != '`' => return None,
+			c if c == quote => return Some(i + c.len_utf8()),
+			_ => {}
+		}
+	}
+
+	None
+}
+
+fn word_len(text: &str) -> usize {
+	text.find(|c| !is_word_char(c)).unwrap_or(text.len())
+}
+
+/// Numbers may have a fractional part, a base prefix or a type suffix, like `0x1f` or `1.5f32`
+fn number_len(text: &str) -> usize {
+	text.find(|c| !(is_word_char(c) || c == '.'))
+		.unwrap_or(text.len())
+}
+
+fn is_word_char(c: char) -> bool {
+	c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn code_is_tokenized() {
+		let tokens = tokenize(
+			"let x = \"a // b\"; // done\n/* multi\nline */ 42",
+			Syntax::from_extension("rs"),
+		);
+
+		assert_eq!(
+			tokens,
+			[
+				(TokenKind::Keyword, "let"),
+				(TokenKind::Plain, " x = "),
+				(TokenKind::String, "\"a // b\""),
+				(TokenKind::Plain, "; "),
+				(TokenKind::Comment, "// done"),
+				(TokenKind::Plain, "\n"),
+				(TokenKind::Comment, "/* multi\nline */"),
+				(TokenKind::Plain, " "),
+				(TokenKind::Number, "42"),
+			]
+		);
+	}
+
+	#[test]
+	fn lifetimes_are_not_strings() {
+		let tokens = tokenize("fn f<'a>(x: &'a str)", Syntax::from_extension("rs"));
+		assert!(tokens.iter().all(|(kind, _)| *kind != TokenKind::String));
+	}
+
+	#[test]
+	fn single_quoted_strings() {
+		assert_eq!(
+			tokenize("'a b'", Syntax::from_extension("py")),
+			[(TokenKind::String, "'a b'")]
+		);
+	}
+
+	#[test]
+	fn plain_text_is_not_highlighted() {
+		let text = "if you read this, # it's \"plain\"";
+		assert_eq!(
+			tokenize(text, Syntax::from_extension("txt")),
+			[(TokenKind::Plain, text)]
+		);
+	}
+
+	#[test]
+	fn markup_is_escaped() {
+		let svg = page_svg("<b>&</b>", Syntax::from_extension("html"));
+		assert!(svg.contains("&lt;b&gt;&amp;&lt;/b&gt;"));
+	}
+}
@@ -16,6 +16,10 @@ pub enum Error {
 	Conversion,
 	#[error("there was an error while parsing the location of an image")]
 	MediaLocationParse,
+	#[error("the reverse geocoder dataset has no places")]
+	GeocoderDataset,
+	#[error("failed to encode image: {0}")]
+	Image(#[from] image::ImageError),
 
 	#[error("serde error {0}")]
 	Serde(#[from] serde_json::Error),
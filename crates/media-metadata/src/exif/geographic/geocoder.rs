@@ -0,0 +1,359 @@
+use crate::{Error, Result};
+
+use sd_utils::error::FileIOError;
+
+use std::{
+	collections::HashMap,
+	env::current_exe,
+	io,
+	path::{Path, PathBuf},
+};
+
+use tokio::{fs, task::spawn_blocking};
+
+// No dataset ships with the app, packagers and users can install one here, relative to the running
+// binary
+#[cfg(windows)]
+const DEFAULT_DATASET_LOCATION: &str = "./geonames";
+#[cfg(unix)]
+const DEFAULT_DATASET_LOCATION: &str = if cfg!(target_os = "macos") {
+	"../Frameworks/Spacedrive.framework/Resources/GeoNames"
+} else {
+	"../share/spacedrive/geonames"
+};
+
+/// Cities dump of the dataset, in the `cities*.txt` format of GeoNames
+const CITIES_FILE: &str = "cities.txt";
+/// Optional region names, in the `admin1CodesASCII.txt` format of GeoNames
+const REGIONS_FILE: &str = "admin1CodesASCII.txt";
+/// Optional country names, in the `countryInfo.txt` format of GeoNames
+const COUNTRIES_FILE: &str = "countryInfo.txt";
+
+/// Mean radius of the Earth, in kilometers
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+/// Length of a degree of latitude, in kilometers
+const DEGREE_KM: f64 = 111.2;
+
+/// A photo taken farther than this from any known place was probably taken at sea or in the
+/// wilderness, so naming the closest place would be misleading
+const MAX_DISTANCE_KM: f64 = 50.0;
+
+/// Name of the place some coordinates belong to
+#[derive(
+	Default, Clone, PartialEq, Eq, Debug, serde::Serialize, serde::Deserialize, specta::Type,
+)]
+pub struct PlaceName {
+	pub city: String,
+	pub region: Option<String>,
+	/// The country name when the dataset has it, otherwise its ISO 3166 code
+	pub country: String,
+}
+
+#[derive(Debug)]
+struct Place {
+	name: String,
+	latitude: f64,
+	longitude: f64,
+	country_code: String,
+	region_code: String,
+}
+
+/// Offline reverse geocoder, finding the closest city to some coordinates in a GeoNames dataset.
+///
+/// Places are grouped in cells of one degree, so a lookup only measures the distance to the
+/// places in the cells around the coordinates.
+#[derive(Debug)]
+pub struct ReverseGeocoder {
+	places: Vec<Place>,
+	cells: HashMap<(i16, i16), Vec<usize>>,
+	regions: HashMap<String, String>,
+	countries: HashMap<String, String>,
+}
+
+impl ReverseGeocoder {
+	/// Where a dataset installed next to the app is looked for, when the user didn't supply one
+	#[must_use]
+	pub fn default_dataset_path() -> PathBuf {
+		current_exe()
+			.ok()
+			.and_then(|exe_path| {
+				exe_path
+					.parent()
+					.map(|parent| parent.join(DEFAULT_DATASET_LOCATION))
+			})
+			.unwrap_or_else(|| PathBuf::from(DEFAULT_DATASET_LOCATION))
+	}
+
+	/// Loads a dataset from a directory with a `cities.txt` file, or from the cities file itself.
+	///
+	/// Region and country names are read from the `admin1CodesASCII.txt` and `countryInfo.txt`
+	/// files next to the cities file, when they exist.
+	pub async fn load(path: impl AsRef<Path> + Send) -> Result<Self> {
+		let path = path.as_ref();
+
+		let cities_path = if fs::metadata(path)
+			.await
+			.map_err(|e| FileIOError::from((path, e)))?
+			.is_dir()
+		{
+			path.join(CITIES_FILE)
+		} else {
+			path.to_path_buf()
+		};
+		let dir = cities_path.parent().unwrap_or(path);
+
+		let cities = read_to_string(&cities_path).await?.ok_or_else(|| {
+			FileIOError::from((
+				&cities_path,
+				io::Error::from(io::ErrorKind::NotFound),
+				"missing cities file of the reverse geocoder dataset",
+			))
+		})?;
+		let regions = read_to_string(dir.join(REGIONS_FILE)).await?;
+		let countries = read_to_string(dir.join(COUNTRIES_FILE)).await?;
+
+		spawn_blocking(move || {
+			Self::from_geonames(&cities, regions.as_deref(), countries.as_deref())
+		})
+		.await?
+	}
+
+	/// Builds the geocoder from the contents of the GeoNames files
+	pub fn from_geonames(
+		cities: &str,
+		regions: Option<&str>,
+		countries: Option<&str>,
+	) -> Result<Self> {
+		let places = cities
+			.lines()
+			.filter_map(|line| {
+				let columns = line.split('\t').collect::<Vec<_>>();
+				if columns.len() < 11 {
+					return None;
+				}
+
+				Some(Place {
+					name: columns[1].to_string(),
+					latitude: columns[4].parse().ok()?,
+					longitude: columns[5].parse().ok()?,
+					country_code: columns[8].to_string(),
+					region_code: columns[10].to_string(),
+				})
+			})
+			.collect::<Vec<_>>();
+
+		if places.is_empty() {
+			return Err(Error::GeocoderDataset);
+		}
+
+		let mut cells = HashMap::<_, Vec<_>>::new();
+		for (idx, place) in places.iter().enumerate() {
+			cells
+				.entry(cell(place.latitude, place.longitude))
+				.or_default()
+				.push(idx);
+		}
+
+		Ok(Self {
+			places,
+			cells,
+			// Lines like "US.CA\tCalifornia\tCalifornia\t5332921"
+			regions: regions
+				.map(|regions| parse_names(regions, 0, 1))
+				.unwrap_or_default(),
+			// Lines like "PT\tPRT\t620\tPO\tPortugal\tLisbon\t...", after the commented out header
+			countries: countries
+				.map(|countries| parse_names(countries, 0, 4))
+				.unwrap_or_default(),
+		})
+	}
+
+	/// How many places the dataset has
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.places.len()
+	}
+
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.places.is_empty()
+	}
+
+	/// The closest place to the `(latitude, longitude)` coordinates, if any is close enough
+	#[must_use]
+	pub fn lookup(&self, coordinates: (f64, f64)) -> Option<PlaceName> {
+		let (lat_cell, lon_cell) = cell(coordinates.0, coordinates.1);
+
+		let lat_cells_span = cells_span(MAX_DISTANCE_KM / DEGREE_KM);
+
+		let (place, _) = (lat_cell - lat_cells_span..=lat_cell + lat_cells_span)
+			.flat_map(|row| {
+				// Cells get narrower closer to the poles, so we must look at more of them
+				let narrowest_lat = f64::from(if row < 0 { row } else { row + 1 })
+					.abs()
+					.min(89.0);
+				let lon_cells_span =
+					cells_span(MAX_DISTANCE_KM / (DEGREE_KM * narrowest_lat.to_radians().cos()))
+						.min(180);
+
+				(lon_cell - lon_cells_span..=lon_cell + lon_cells_span)
+					.map(move |column| (row, wrap_longitude_cell(column)))
+			})
+			.filter_map(|key| self.cells.get(&key))
+			.flatten()
+			.map(|&idx| {
+				let place = &self.places[idx];
+				(
+					place,
+					distance_km(coordinates, (place.latitude, place.longitude)),
+				)
+			})
+			.filter(|&(_, distance)| distance <= MAX_DISTANCE_KM)
+			.min_by(|(_, a), (_, b)| a.total_cmp(b))?;
+
+		Some(PlaceName {
+			city: place.name.clone(),
+			region: self
+				.regions
+				.get(&format!("{}.{}", place.country_code, place.region_code))
+				.cloned(),
+			country: self
+				.countries
+				.get(&place.country_code)
+				.cloned()
+				.unwrap_or_else(|| place.country_code.clone()),
+		})
+	}
+}
+
+/// Great-circle distance between two `(latitude, longitude)` coordinates, in kilometers
+#[must_use]
+pub fn distance_km((lat_a, lon_a): (f64, f64), (lat_b, lon_b): (f64, f64)) -> f64 {
+	let (lat_a, lat_b) = (lat_a.to_radians(), lat_b.to_radians());
+	let (half_delta_lat, half_delta_lon) =
+		((lat_b - lat_a) / 2.0, (lon_b - lon_a).to_radians() / 2.0);
+
+	let a = (lat_a.cos() * lat_b.cos())
+		.mul_add(half_delta_lon.sin().powi(2), half_delta_lat.sin().powi(2));
+
+	2.0 * EARTH_RADIUS_KM * a.sqrt().min(1.0).asin()
+}
+
+async fn read_to_string(path: impl AsRef<Path> + Send) -> Result<Option<String>> {
+	let path = path.as_ref();
+
+	match fs::read_to_string(path).await {
+		Ok(contents) => Ok(Some(contents)),
+		Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+		Err(e) => Err(FileIOError::from((path, e)).into()),
+	}
+}
+
+fn parse_names(contents: &str, code_column: usize, name_column: usize) -> HashMap<String, String> {
+	contents
+		.lines()
+		.filter(|line| !line.starts_with('#'))
+		.filter_map(|line| {
+			let columns = line.split('\t').collect::<Vec<_>>();
+			Some((
+				(*columns.get(code_column)?).to_string(),
+				(*columns.get(name_column)?).to_string(),
+			))
+		})
+		.collect()
+}
+
+fn cell(latitude: f64, longitude: f64) -> (i16, i16) {
+	#[allow(clippy::cast_possible_truncation)]
+	{
+		// SAFETY: coordinates are within -180 and 180 degrees, so their floor fits in an i16
+		(
+			latitude.clamp(-90.0, 90.0).floor() as i16,
+			wrap_longitude_cell(longitude.clamp(-180.0, 180.0).floor() as i16),
+		)
+	}
+}
+
+fn cells_span(degrees: f64) -> i16 {
+	#[allow(clippy::cast_possible_truncation)]
+	{
+		// SAFETY: callers never need more than 180 cells, so it fits in an i16
+		degrees.ceil().min(180.0) as i16
+	}
+}
+
+/// Cells past the anti-meridian wrap around to the other side of the world
+const fn wrap_longitude_cell(column: i16) -> i16 {
+	(column + 180).rem_euclid(360) - 180
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const CITIES: &str = "\
+2267057\tLisbon\tLisbon\t\t38.71667\t-9.13333\tP\tPPLC\tPT\t\t14\t\t\t\t517802\t\t45\tEurope/Lisbon\t2022-01-01
+2735943\tPorto\tPorto\t\t41.14961\t-8.61099\tP\tPPLA\tPT\t\t17\t\t\t\t249633\t\t93\tEurope/Lisbon\t2022-01-01
+4032243\tNuku'alofa\tNuku'alofa\t\t-21.13938\t-175.2018\tP\tPPLC\tTO\t\t02\t\t\t\t22400\t\t5\tPacific/Tongatapu\t2022-01-01";
+
+	const REGIONS: &str = "PT.14\tLisbon\tLisbon\t2267056\nPT.17\tPorto\tPorto\t2735941";
+
+	const COUNTRIES: &str = "#ISO\tISO3\tISO-Numeric\tfips\tCountry\nPT\tPRT\t620\tPO\tPortugal";
+
+	fn geocoder() -> ReverseGeocoder {
+		ReverseGeocoder::from_geonames(CITIES, Some(REGIONS), Some(COUNTRIES)).unwrap()
+	}
+
+	#[test]
+	fn closest_place() {
+		assert_eq!(
+			geocoder().lookup((38.7, -9.1)),
+			Some(PlaceName {
+				city: "Lisbon".to_string(),
+				region: Some("Lisbon".to_string()),
+				country: "Portugal".to_string(),
+			})
+		);
+		assert_eq!(
+			geocoder().lookup((41.0, -8.6)).map(|place| place.city),
+			Some("Porto".to_string())
+		);
+	}
+
+	#[test]
+	fn missing_names_fall_back_to_codes() {
+		assert_eq!(
+			geocoder().lookup((-21.1, -175.2)),
+			Some(PlaceName {
+				city: "Nuku'alofa".to_string(),
+				region: None,
+				country: "TO".to_string(),
+			})
+		);
+	}
+
+	#[test]
+	fn too_far_from_any_place() {
+		assert_eq!(geocoder().lookup((38.7, -12.0)), None);
+	}
+
+	#[test]
+	fn across_the_anti_meridian() {
+		let geocoder = ReverseGeocoder::from_geonames(
+			"1\tSomewhere\tSomewhere\t\t0.0\t179.9\tP\tPPL\tFJ\t\t01\t\t\t\t0\t\t0\tPacific/Fiji\t2022-01-01",
+			None,
+			None,
+		)
+		.unwrap();
+
+		assert!(geocoder.lookup((0.0, -179.9)).is_some());
+	}
+
+	#[test]
+	fn distances() {
+		let lisbon_to_porto = distance_km((38.71667, -9.13333), (41.14961, -8.61099));
+		assert!((lisbon_to_porto - 274.0).abs() < 2.0, "{lisbon_to_porto}");
+		assert!(distance_km((0.0, 179.9), (0.0, -179.9)) < 23.0);
+	}
+}
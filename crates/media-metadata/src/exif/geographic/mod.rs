@@ -1,5 +1,7 @@
+mod geocoder;
 mod location;
 mod pluscodes;
 
+pub use geocoder::{distance_km, PlaceName, ReverseGeocoder};
 pub use location::MediaLocation;
 pub use pluscodes::PlusCode;
@@ -18,7 +18,7 @@ pub use composite::Composite;
 pub use consts::DMS_DIVISION;
 pub use datetime::MediaDate;
 pub use flash::{Flash, FlashMode, FlashValue};
-pub use geographic::{MediaLocation, PlusCode};
+pub use geographic::{distance_km, MediaLocation, PlaceName, PlusCode, ReverseGeocoder};
 pub use orientation::Orientation;
 pub use profile::ColorProfile;
 pub use reader::ExifReader;
@@ -31,6 +31,8 @@ pub struct ExifMetadata {
 	pub resolution: Resolution,
 	pub date_taken: Option<MediaDate>,
 	pub location: Option<MediaLocation>,
+	/// Where [`Self::location`] is, resolved by a [`ReverseGeocoder`] as EXIF doesn't have it
+	pub place: Option<PlaceName>,
 	pub camera_data: CameraData,
 	pub artist: Option<String>,
 	pub description: Option<String>,
@@ -74,12 +76,21 @@ impl ExifMetadata {
 		res.map(Some)
 	}
 
+	/// Names the place where the media was taken, if it has a location
+	pub fn resolve_place(&mut self, geocoder: &ReverseGeocoder) {
+		self.place = self
+			.location
+			.as_ref()
+			.and_then(|location| geocoder.lookup(location.coordinates()));
+	}
+
 	#[allow(clippy::field_reassign_with_default)]
 	fn from_reader(reader: &ExifReader) -> Self {
 		Self {
 			resolution: Resolution::from_reader(reader),
 			date_taken: MediaDate::from_reader(reader),
 			location: MediaLocation::from_exif_reader(reader).ok(),
+			place: None,
 			camera_data: CameraData {
 				device_make: reader.get_tag(Tag::Make),
 				device_model: reader.get_tag(Tag::Model),
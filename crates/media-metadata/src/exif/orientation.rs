@@ -19,6 +19,21 @@ pub enum Orientation {
 }
 
 impl Orientation {
+	/// The name of the variant, as it's serialized
+	#[must_use]
+	pub const fn as_str(&self) -> &'static str {
+		match self {
+			Self::Normal => "Normal",
+			Self::CW90 => "CW90",
+			Self::CW180 => "CW180",
+			Self::CW270 => "CW270",
+			Self::MirroredVertical => "MirroredVertical",
+			Self::MirroredHorizontal => "MirroredHorizontal",
+			Self::MirroredHorizontalAnd90CW => "MirroredHorizontalAnd90CW",
+			Self::MirroredHorizontalAnd270CW => "MirroredHorizontalAnd270CW",
+		}
+	}
+
 	/// This is used for quickly sourcing [`Orientation`] data from a path, to be later used by one of the modification functions.
 	#[allow(clippy::future_not_send)]
 	pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
@@ -9,4 +9,61 @@ pub struct AudioProps {
 	pub sample_format: Option<String>,
 	pub bit_per_sample: Option<i32>,
 	pub channel_layout: Option<String>,
+	pub channels: Option<i32>,
+}
+
+impl AudioProps {
+	/// Number of audio channels, guessed from the channel layout if it wasn't extracted,
+	/// as older extractions only stored the layout
+	#[must_use]
+	pub fn channel_count(&self) -> Option<i32> {
+		self.channels
+			.or_else(|| self.channel_layout.as_deref().and_then(channels_in_layout))
+	}
+}
+
+/// Number of channels in a layout as described by `FFmpeg`, like "stereo", "5.1(side)",
+/// "FL+FR+LFE" or "3 channels"
+fn channels_in_layout(layout: &str) -> Option<i32> {
+	let layout = layout.split('(').next()?.trim();
+
+	match layout {
+		"mono" => Some(1),
+		"stereo" | "downmix" | "binaural" => Some(2),
+		"quad" => Some(4),
+		"hexagonal" => Some(6),
+		"octagonal" | "cube" => Some(8),
+		"hexadecagonal" => Some(16),
+		_ => {
+			if let Some(count) = layout.strip_suffix(" channels") {
+				count.parse().ok()
+			} else if layout.contains('+') {
+				layout.split('+').count().try_into().ok()
+			} else {
+				// Named after their channels, like "2.1", "7.1" or "5.1.4"
+				layout
+					.split('.')
+					.map(str::parse::<i32>)
+					.sum::<Result<_, _>>()
+					.ok()
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn channels_from_layout_names() {
+		assert_eq!(channels_in_layout("mono"), Some(1));
+		assert_eq!(channels_in_layout("stereo"), Some(2));
+		assert_eq!(channels_in_layout("5.1(side)"), Some(6));
+		assert_eq!(channels_in_layout("7.1.4"), Some(12));
+		assert_eq!(channels_in_layout("22.2"), Some(24));
+		assert_eq!(channels_in_layout("3 channels"), Some(3));
+		assert_eq!(channels_in_layout("FL+FR+LFE"), Some(3));
+		assert_eq!(channels_in_layout("unknown"), None);
+	}
 }
@@ -13,10 +13,12 @@ pub mod program;
 pub mod stream;
 pub mod subtitle_props;
 pub mod video_props;
+pub mod waveform;
 
 use chapter::Chapter;
 use metadata::Metadata;
 use program::Program;
+use waveform::Waveform;
 
 #[derive(Debug, Serialize, Deserialize, Type)]
 pub struct FFmpegMetadata {
@@ -27,6 +29,8 @@ pub struct FFmpegMetadata {
 	pub chapters: Vec<Chapter>,
 	pub programs: Vec<Program>,
 	pub metadata: Metadata,
+	/// Only extracted for audio files, as video files have thumbnails
+	pub waveform: Option<Waveform>,
 }
 
 impl FFmpegMetadata {
@@ -76,6 +80,7 @@ mod extract_data {
 				chapters: chapters.into_iter().map(Into::into).collect(),
 				programs: programs.into_iter().map(Into::into).collect(),
 				metadata: metadata.into(),
+				waveform: None,
 			}
 		}
 	}
@@ -202,6 +207,7 @@ mod extract_data {
 				sample_format,
 				bit_per_sample,
 				channel_layout,
+				channels,
 			}: FFmpegAudioProps,
 		) -> Self {
 			Self {
@@ -211,6 +217,7 @@ mod extract_data {
 				sample_format,
 				bit_per_sample,
 				channel_layout,
+				channels,
 			}
 		}
 	}
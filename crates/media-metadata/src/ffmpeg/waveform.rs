@@ -0,0 +1,141 @@
+use crate::Result;
+
+use std::{io::Cursor, path::Path};
+
+use image::{ImageOutputFormat, Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+/// How many values a waveform has, enough to draw a detailed preview without bloating the database
+pub const WAVEFORM_BUCKETS: usize = 256;
+
+/// Downsampled amplitude of an audio file, mixed down to mono, with values scaled to `[0, 255]`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub struct Waveform {
+	/// Highest absolute sample value of each bucket
+	pub peaks: Vec<u8>,
+	/// Root mean square of the samples of each bucket, closer to how loud it sounds
+	pub rms: Vec<u8>,
+}
+
+impl Waveform {
+	pub async fn from_path(path: impl AsRef<Path> + Send) -> Result<Self> {
+		#[cfg(not(feature = "ffmpeg"))]
+		{
+			let _ = path;
+			Err(crate::Error::NoFFmpeg)
+		}
+
+		#[cfg(feature = "ffmpeg")]
+		{
+			sd_ffmpeg::to_waveform(path, WAVEFORM_BUCKETS)
+				.await
+				.map(Into::into)
+				.map_err(Into::into)
+		}
+	}
+
+	/// Peak and RMS values interleaved, to be stored in the database
+	#[must_use]
+	pub fn to_bytes(&self) -> Vec<u8> {
+		self.peaks
+			.iter()
+			.zip(&self.rms)
+			.flat_map(|(&peak, &rms)| [peak, rms])
+			.collect()
+	}
+
+	#[must_use]
+	pub fn from_bytes(bytes: &[u8]) -> Self {
+		let (peaks, rms) = bytes
+			.chunks_exact(2)
+			.map(|bucket| (bucket[0], bucket[1]))
+			.unzip();
+
+		Self { peaks, rms }
+	}
+
+	/// Renders the waveform as a PNG image, with bars mirrored around the middle: peaks are
+	/// translucent and RMS values opaque, white over a transparent background
+	pub fn to_png(&self, width: u32, height: u32) -> Result<Vec<u8>> {
+		let mut image = RgbaImage::new(width, height);
+		let buckets = self.peaks.len().min(self.rms.len());
+
+		if buckets > 0 && width > 0 {
+			let middle = f64::from(height) / 2.0;
+
+			for x in 0..width {
+				// SAFETY: Both values are way below what a usize can hold on any platform we support
+				#[allow(clippy::cast_possible_truncation)]
+				let bucket = (u64::from(x) * buckets as u64 / u64::from(width)) as usize;
+
+				let peak = bar_height(self.peaks[bucket], middle);
+				let rms = bar_height(self.rms[bucket], middle);
+
+				for y in 0..height {
+					let distance = (f64::from(y) + 0.5 - middle).abs();
+
+					let alpha = if distance <= rms {
+						u8::MAX
+					} else if distance <= peak {
+						u8::MAX / 2
+					} else {
+						continue;
+					};
+
+					image.put_pixel(x, y, Rgba([u8::MAX, u8::MAX, u8::MAX, alpha]));
+				}
+			}
+		}
+
+		let mut png = Cursor::new(Vec::new());
+		image.write_to(&mut png, ImageOutputFormat::Png)?;
+
+		Ok(png.into_inner())
+	}
+}
+
+/// Half height of a bar, silent buckets still get a thin line so the waveform looks continuous
+fn bar_height(value: u8, middle: f64) -> f64 {
+	(f64::from(value) / f64::from(u8::MAX) * middle).max(0.5)
+}
+
+#[cfg(feature = "ffmpeg")]
+impl From<sd_ffmpeg::Waveform> for Waveform {
+	fn from(sd_ffmpeg::Waveform { peaks, rms }: sd_ffmpeg::Waveform) -> Self {
+		fn quantize(values: Vec<f32>) -> Vec<u8> {
+			values
+				.into_iter()
+				.map(|value| {
+					// SAFETY: The value is clamped to [0.0, 255.0] and rounded
+					#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+					{
+						(value.clamp(0.0, 1.0) * f32::from(u8::MAX)).round() as u8
+					}
+				})
+				.collect()
+		}
+
+		Self {
+			peaks: quantize(peaks),
+			rms: quantize(rms),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn bytes_round_trip() {
+		let waveform = Waveform {
+			peaks: vec![255, 128, 0],
+			rms: vec![180, 64, 0],
+		};
+
+		let bytes = waveform.to_bytes();
+		assert_eq!(bytes, [255, 180, 128, 64, 0, 0]);
+		assert_eq!(Waveform::from_bytes(&bytes), waveform);
+	}
+}
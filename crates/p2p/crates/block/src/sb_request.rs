@@ -1,4 +1,4 @@
-use std::io;
+use std::{io, path::PathBuf};
 
 use thiserror::Error;
 use tokio::io::{AsyncRead, AsyncReadExt};
@@ -70,8 +70,23 @@ pub enum SpaceblockRequestsError {
 }
 
 impl SpaceblockRequests {
+	/// Decode requests with a `u8` file count, the encoding understood by every version.
 	pub async fn from_stream(
 		stream: &mut (impl AsyncRead + Unpin),
+	) -> Result<Self, SpaceblockRequestsError> {
+		Self::from_stream_inner(stream, false).await
+	}
+
+	/// Decode requests with a `u16` file count, used for Spacedrops of more than 255 files.
+	pub async fn from_stream_extended(
+		stream: &mut (impl AsyncRead + Unpin),
+	) -> Result<Self, SpaceblockRequestsError> {
+		Self::from_stream_inner(stream, true).await
+	}
+
+	async fn from_stream_inner(
+		stream: &mut (impl AsyncRead + Unpin),
+		extended: bool,
 	) -> Result<Self, SpaceblockRequestsError> {
 		let id = decode::uuid(stream)
 			.await
@@ -81,11 +96,14 @@ impl SpaceblockRequests {
 			.await
 			.map_err(SpaceblockRequestsError::BlockSize)?;
 
-		let size = stream
+		let size = if extended {
+			// Max of 65535 files in one request
+			stream.read_u16_le().await
+		} else {
 			// Max of 255 files in one request
-			.read_u8()
-			.await
-			.map_err(SpaceblockRequestsError::InvalidLen)?;
+			stream.read_u8().await.map(u16::from)
+		}
+		.map_err(SpaceblockRequestsError::InvalidLen)?;
 
 		let mut requests = Vec::new();
 		for _i in 0..size {
@@ -99,22 +117,49 @@ impl SpaceblockRequests {
 		})
 	}
 
+	/// Whether there are too many requests for the `u8` file count of [`Self::to_bytes`].
+	#[must_use]
+	pub fn needs_extended(&self) -> bool {
+		self.requests.len() > u8::MAX as usize
+	}
+
+	/// Encode requests with a `u8` file count, see [`Self::from_stream`].
 	#[must_use]
 	pub fn to_bytes(&self) -> Vec<u8> {
+		assert!(
+			!self.needs_extended(),
+			"Can't Spacedrop more than 255 files at once!"
+		);
+
+		self.to_bytes_inner(false)
+	}
+
+	/// Encode requests with a `u16` file count, see [`Self::from_stream_extended`].
+	#[must_use]
+	pub fn to_bytes_extended(&self) -> Vec<u8> {
+		assert!(
+			self.requests.len() <= u16::MAX as usize,
+			"Can't Spacedrop more than 65535 files at once!"
+		);
+
+		self.to_bytes_inner(true)
+	}
+
+	fn to_bytes_inner(&self, extended: bool) -> Vec<u8> {
 		let Self {
 			id,
 			block_size,
 			requests,
 		} = self;
-		assert!(
-			requests.len() <= 255,
-			"Can't Spacedrop more than 255 files at once!"
-		);
 
 		let mut buf = vec![];
 		encode::uuid(&mut buf, id);
 		buf.append(&mut block_size.to_bytes().to_vec());
-		buf.push(requests.len() as u8);
+		if extended {
+			buf.extend_from_slice(&(requests.len() as u16).to_le_bytes());
+		} else {
+			buf.push(requests.len() as u8);
+		}
 		for request in requests {
 			buf.extend_from_slice(&request.to_bytes());
 		}
@@ -125,6 +170,8 @@ impl SpaceblockRequests {
 /// TODO
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SpaceblockRequest {
+	/// The path of the file relative to the root of the transfer, using `/` as the separator.
+	/// For a single file this is just it's name.
 	pub name: String,
 	pub size: u64,
 	// TODO: Include file permissions
@@ -164,6 +211,23 @@ impl SpaceblockRequest {
 		})
 	}
 
+	/// Convert `name` into a relative path which is safe to join onto the directory we are saving into.
+	///
+	/// Returns `None` if the name is empty or tries to escape it's parent directory.
+	#[must_use]
+	pub fn relative_path(&self) -> Option<PathBuf> {
+		self.name
+			.split('/')
+			.map(|component| {
+				(!component.is_empty()
+					&& component != "."
+					&& component != ".."
+					&& !component.contains(['\\', ':']))
+				.then_some(component)
+			})
+			.collect()
+	}
+
 	#[must_use]
 	pub fn to_bytes(&self) -> Vec<u8> {
 		let mut buf = Vec::new();
@@ -265,4 +329,71 @@ mod tests {
 			.unwrap();
 		assert_eq!(req, req2);
 	}
+
+	#[tokio::test]
+	async fn test_spaceblock_requests_more_than_u8() {
+		let req = SpaceblockRequests {
+			id: Uuid::new_v4(),
+			block_size: BlockSize::from_file_size(42069),
+			requests: (0..300)
+				.map(|i| SpaceblockRequest {
+					name: format!("Demo/{i}"),
+					size: 42069,
+					range: Range::Full,
+				})
+				.collect(),
+		};
+
+		assert!(req.needs_extended());
+
+		let bytes = req.to_bytes_extended();
+		let req2 = SpaceblockRequests::from_stream_extended(&mut Cursor::new(bytes))
+			.await
+			.unwrap();
+		assert_eq!(req, req2);
+	}
+
+	#[tokio::test]
+	async fn test_spaceblock_requests_extended_few() {
+		let req = SpaceblockRequests {
+			id: Uuid::new_v4(),
+			block_size: BlockSize::from_file_size(42069),
+			requests: vec![SpaceblockRequest {
+				name: "Demo".to_string(),
+				size: 42069,
+				range: Range::Full,
+			}],
+		};
+		assert!(!req.needs_extended());
+
+		// Only the file count differs between the two encodings
+		let bytes = req.to_bytes_extended();
+		assert_eq!(bytes.len(), req.to_bytes().len() + 1);
+
+		let req2 = SpaceblockRequests::from_stream_extended(&mut Cursor::new(bytes))
+			.await
+			.unwrap();
+		assert_eq!(req, req2);
+	}
+
+	#[test]
+	fn test_spaceblock_request_relative_path() {
+		let req = |name: &str| SpaceblockRequest {
+			name: name.to_string(),
+			size: 0,
+			range: Range::Full,
+		};
+
+		assert_eq!(req("Demo").relative_path(), Some(PathBuf::from("Demo")));
+		assert_eq!(
+			req("Photos/2024/Demo.png").relative_path(),
+			Some(["Photos", "2024", "Demo.png"].iter().collect())
+		);
+
+		assert_eq!(req("").relative_path(), None);
+		assert_eq!(req("/etc/passwd").relative_path(), None);
+		assert_eq!(req("Photos/../../Demo").relative_path(), None);
+		assert_eq!(req("Photos//Demo").relative_path(), None);
+		assert_eq!(req("C:\\Windows").relative_path(), None);
+	}
 }
@@ -0,0 +1,173 @@
+use std::{
+	future::Future,
+	pin::Pin,
+	sync::{Arc, Mutex, PoisonError},
+	task::{Context, Poll},
+	time::Duration,
+};
+
+use tokio::time::{sleep, Instant, Sleep};
+
+/// How a stream is treated by the bandwidth limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Priority {
+	/// Latency sensitive traffic. This is never delayed but it still uses up the available bandwidth so bulk transfers back off while it's active.
+	Interactive,
+	/// Large transfers which are delayed to stay within the bandwidth limits.
+	Bulk,
+}
+
+/// The upload and download limits shared by all streams of a `P2P` instance.
+#[derive(Debug, Clone, Default)]
+pub struct Bandwidth {
+	pub upload: Arc<RateLimiter>,
+	pub download: Arc<RateLimiter>,
+}
+
+/// A token bucket which allows up to one second worth of burst.
+#[derive(Debug)]
+pub struct RateLimiter(Mutex<RateLimiterState>);
+
+#[derive(Debug)]
+struct RateLimiterState {
+	/// Bytes per second or `None` if unlimited
+	rate: Option<u32>,
+	/// The amount of bytes which can be transferred without waiting. This goes negative after a large transfer.
+	balance: i64,
+	updated: Instant,
+}
+
+impl Default for RateLimiter {
+	fn default() -> Self {
+		Self(Mutex::new(RateLimiterState {
+			rate: None,
+			balance: 0,
+			updated: Instant::now(),
+		}))
+	}
+}
+
+impl RateLimiter {
+	/// Set the limit in bytes per second. `None` or `Some(0)` removes the limit.
+	pub fn set_rate(&self, rate: Option<u32>) {
+		let mut state = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+		state.rate = rate.filter(|rate| *rate != 0);
+		state.balance = 0;
+		state.updated = Instant::now();
+	}
+
+	pub fn rate(&self) -> Option<u32> {
+		self.0.lock().unwrap_or_else(PoisonError::into_inner).rate
+	}
+
+	/// Account `bytes` which were transferred.
+	pub(crate) fn consume(&self, bytes: usize) {
+		let mut state = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+		if state.rate.is_some() {
+			state.refill();
+			state.balance -= bytes as i64;
+		}
+	}
+
+	/// How long a bulk transfer must wait before it can continue, or `None` if it can continue now.
+	pub(crate) fn delay(&self) -> Option<Duration> {
+		let mut state = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+		let rate = state.rate?;
+		state.refill();
+
+		(state.balance <= 0).then(|| {
+			// Wait until at least one byte is available
+			Duration::from_secs_f64((1 - state.balance) as f64 / rate as f64)
+		})
+	}
+}
+
+impl RateLimiterState {
+	fn refill(&mut self) {
+		let Some(rate) = self.rate else {
+			return;
+		};
+
+		let now = Instant::now();
+		let earned = ((now - self.updated).as_secs_f64() * rate as f64) as i64;
+		if self.balance + earned >= rate as i64 {
+			self.balance = rate as i64;
+			self.updated = now;
+		} else {
+			self.balance += earned;
+			// We only move forward by the time that earned whole bytes so fractions aren't lost
+			self.updated += Duration::from_secs_f64(earned as f64 / rate as f64);
+		}
+	}
+}
+
+/// The bandwidth limits applied to a single `UnicastStream`.
+#[derive(Debug)]
+pub(crate) struct StreamLimits {
+	bandwidth: Bandwidth,
+	priority: Priority,
+	read_delay: Option<Pin<Box<Sleep>>>,
+	write_delay: Option<Pin<Box<Sleep>>>,
+}
+
+impl StreamLimits {
+	pub(crate) fn new(bandwidth: Bandwidth, priority: Priority) -> Self {
+		Self {
+			bandwidth,
+			priority,
+			read_delay: None,
+			write_delay: None,
+		}
+	}
+
+	pub(crate) fn poll_read_ready(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+		poll_ready(
+			&self.bandwidth.download,
+			self.priority,
+			&mut self.read_delay,
+			cx,
+		)
+	}
+
+	pub(crate) fn poll_write_ready(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+		poll_ready(
+			&self.bandwidth.upload,
+			self.priority,
+			&mut self.write_delay,
+			cx,
+		)
+	}
+
+	pub(crate) fn read(&self, bytes: usize) {
+		self.bandwidth.download.consume(bytes);
+	}
+
+	pub(crate) fn wrote(&self, bytes: usize) {
+		self.bandwidth.upload.consume(bytes);
+	}
+}
+
+fn poll_ready(
+	limiter: &RateLimiter,
+	priority: Priority,
+	delay: &mut Option<Pin<Box<Sleep>>>,
+	cx: &mut Context<'_>,
+) -> Poll<()> {
+	if priority == Priority::Interactive {
+		return Poll::Ready(());
+	}
+
+	loop {
+		if let Some(sleep) = delay {
+			if sleep.as_mut().poll(cx).is_pending() {
+				return Poll::Pending;
+			}
+			*delay = None;
+		}
+
+		match limiter.delay() {
+			Some(duration) => *delay = Some(Box::pin(sleep(duration))),
+			None => return Poll::Ready(()),
+		}
+	}
+}
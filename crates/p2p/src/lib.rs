@@ -1,6 +1,7 @@
 //! Rust Peer to Peer Networking Library
 #![warn(clippy::all, clippy::unwrap_used, clippy::panic)]
 
+mod bandwidth;
 pub(crate) mod hook;
 pub mod hooks;
 mod identity;
@@ -9,12 +10,13 @@ mod peer;
 mod smart_guards;
 mod stream;
 
+pub use bandwidth::{Bandwidth, Priority, RateLimiter};
 pub use hook::{HookEvent, HookId, ListenerId, ShutdownGuard};
-pub use identity::{Identity, IdentityErr, RemoteIdentity};
+pub use identity::{Identity, IdentityErr, RemoteIdentity, REMOTE_IDENTITY_LEN};
 pub use p2p::{Listener, P2P};
 pub use peer::{ConnectionRequest, Peer, PeerConnectionCandidate};
 pub use smart_guards::SmartWriteGuard;
-pub use stream::UnicastStream;
+pub use stream::{StreamCounters, UnicastStream};
 
 pub use flume;
 
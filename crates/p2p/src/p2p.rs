@@ -15,7 +15,7 @@ use tracing::info;
 use crate::{
 	hook::{HandlerFn, Hook, HookEvent, ListenerData, ListenerId, ShutdownGuard},
 	smart_guards::SmartWriteGuard,
-	HookId, Identity, Peer, PeerConnectionCandidate, RemoteIdentity, UnicastStream,
+	Bandwidth, HookId, Identity, Peer, PeerConnectionCandidate, RemoteIdentity, UnicastStream,
 };
 
 /// Manager for the entire P2P system.
@@ -37,6 +37,8 @@ pub struct P2P {
 	pub(crate) peers: RwLock<HashMap<RemoteIdentity, Arc<Peer>>>,
 	/// Hooks can be registered to react to state changes in the P2P system.
 	pub(crate) hooks: RwLock<StableVec<Hook>>,
+	/// Bandwidth limits which can be applied to streams using `UnicastStream::limit`.
+	bandwidth: Bandwidth,
 }
 
 impl P2P {
@@ -63,6 +65,7 @@ impl P2P {
 			peers: Default::default(),
 			handler_tx,
 			hooks: Default::default(),
+			bandwidth: Default::default(),
 		})
 	}
 
@@ -71,6 +74,11 @@ impl P2P {
 		self.app_name
 	}
 
+	/// The upload and download limits shared by every stream which has been limited with them.
+	pub fn bandwidth(&self) -> &Bandwidth {
+		&self.bandwidth
+	}
+
 	/// The identifier of this node that can *MUST* be kept secret.
 	/// This is a private key in crypto terms.
 	pub fn identity(&self) -> &Identity {
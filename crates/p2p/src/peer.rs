@@ -2,13 +2,14 @@ use std::{
 	collections::{BTreeSet, HashMap, HashSet},
 	net::SocketAddr,
 	sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard, Weak},
+	time::Duration,
 };
 
 use crate::NewStreamError;
 use tokio::sync::{mpsc, oneshot};
 use tracing::warn;
 
-use crate::{HookEvent, HookId, ListenerId, RemoteIdentity, UnicastStream, P2P};
+use crate::{HookEvent, HookId, ListenerId, RemoteIdentity, StreamCounters, UnicastStream, P2P};
 
 #[derive(Debug)]
 pub struct Peer {
@@ -41,6 +42,12 @@ pub(crate) struct State {
 	pub(crate) connection_methods: HashMap<ListenerId, mpsc::Sender<ConnectionRequest>>,
 	/// Methods that have discovered this peer.
 	pub(crate) discovered: HashMap<HookId, BTreeSet<PeerConnectionCandidate>>,
+	/// The last error which occurred establishing a stream with the peer.
+	pub(crate) last_error: Option<String>,
+	/// The last measured round trip time to the peer.
+	pub(crate) rtt: Option<Duration>,
+	/// Counters for streams with the peer, grouped by a label chosen by the application.
+	pub(crate) counters: HashMap<&'static str, Arc<StreamCounters>>,
 }
 
 /// A request to connect to a client.
@@ -194,8 +201,66 @@ impl Peer {
 			.collect()
 	}
 
+	/// The last error which occurred trying to establish a stream with the peer.
+	/// This is kept around after a successful connection, so it can be shown for debugging flaky connections.
+	pub fn last_error(&self) -> Option<String> {
+		self.state
+			.read()
+			.unwrap_or_else(PoisonError::into_inner)
+			.last_error
+			.clone()
+	}
+
+	/// The last measured round trip time to the peer. This is set by the application as it's responsible for pinging.
+	pub fn rtt(&self) -> Option<Duration> {
+		self.state
+			.read()
+			.unwrap_or_else(PoisonError::into_inner)
+			.rtt
+	}
+
+	pub fn set_rtt(&self, rtt: Duration) {
+		self.state
+			.write()
+			.unwrap_or_else(PoisonError::into_inner)
+			.rtt = Some(rtt);
+	}
+
+	/// Get the counters for `label`, creating them if they don't exist yet.
+	/// Use `UnicastStream::track` to account a stream with this peer to them.
+	pub fn counters(&self, label: &'static str) -> Arc<StreamCounters> {
+		self.state
+			.write()
+			.unwrap_or_else(PoisonError::into_inner)
+			.counters
+			.entry(label)
+			.or_default()
+			.clone()
+	}
+
+	pub fn all_counters(&self) -> HashMap<&'static str, Arc<StreamCounters>> {
+		self.state
+			.read()
+			.unwrap_or_else(PoisonError::into_inner)
+			.counters
+			.clone()
+	}
+
 	/// Construct a new Quic stream to the peer.
 	pub async fn new_stream(&self) -> Result<UnicastStream, NewStreamError> {
+		let result = self.new_stream_inner().await;
+
+		if let Err(err) = &result {
+			self.state
+				.write()
+				.unwrap_or_else(PoisonError::into_inner)
+				.last_error = Some(err.to_string());
+		}
+
+		result
+	}
+
+	async fn new_stream_inner(&self) -> Result<UnicastStream, NewStreamError> {
 		let (addrs, connect_tx) = {
 			let state = self.state.read().unwrap_or_else(PoisonError::into_inner);
 
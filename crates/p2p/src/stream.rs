@@ -1,21 +1,70 @@
 use std::{
 	fmt, io,
 	pin::Pin,
-	task::{Context, Poll},
+	sync::{
+		atomic::{AtomicU64, AtomicUsize, Ordering},
+		Arc,
+	},
+	task::{ready, Context, Poll},
 };
 
 use sync_wrapper::SyncWrapper;
 use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt, ReadBuf};
 
-use crate::RemoteIdentity;
+use crate::{
+	bandwidth::{Bandwidth, Priority, StreamLimits},
+	RemoteIdentity,
+};
 
 trait IoStream: AsyncRead + AsyncWrite {}
 impl<S: AsyncRead + AsyncWrite> IoStream for S {}
 
+/// Counters which can be shared between many streams to keep track of how they are being used.
+#[derive(Debug, Default)]
+pub struct StreamCounters {
+	bytes_sent: AtomicU64,
+	bytes_received: AtomicU64,
+	active_streams: AtomicUsize,
+}
+
+impl StreamCounters {
+	pub fn bytes_sent(&self) -> u64 {
+		self.bytes_sent.load(Ordering::Relaxed)
+	}
+
+	pub fn bytes_received(&self) -> u64 {
+		self.bytes_received.load(Ordering::Relaxed)
+	}
+
+	/// The amount of streams which are currently tracked by these counters and haven't been dropped.
+	pub fn active_streams(&self) -> usize {
+		self.active_streams.load(Ordering::Relaxed)
+	}
+}
+
+/// Marks a stream as active on it's `StreamCounters` until it is dropped.
+#[derive(Debug)]
+struct ActiveStream(Arc<StreamCounters>);
+
+impl ActiveStream {
+	fn new(counters: Arc<StreamCounters>) -> Self {
+		counters.active_streams.fetch_add(1, Ordering::Relaxed);
+		Self(counters)
+	}
+}
+
+impl Drop for ActiveStream {
+	fn drop(&mut self) {
+		self.0.active_streams.fetch_sub(1, Ordering::Relaxed);
+	}
+}
+
 /// A unicast stream is a direct stream to a specific peer.
 pub struct UnicastStream {
 	io: SyncWrapper<Pin<Box<dyn IoStream + Send>>>,
 	remote: RemoteIdentity,
+	counters: Option<ActiveStream>,
+	limits: Option<StreamLimits>,
 }
 
 impl fmt::Debug for UnicastStream {
@@ -31,6 +80,8 @@ impl UnicastStream {
 		Self {
 			io: SyncWrapper::new(Box::pin(io)),
 			remote,
+			counters: None,
+			limits: None,
 		}
 	}
 
@@ -39,6 +90,18 @@ impl UnicastStream {
 		self.remote
 	}
 
+	/// Account all future reads and writes on this stream to `counters`.
+	/// This replaces any counters that were previously set.
+	pub fn track(&mut self, counters: Arc<StreamCounters>) {
+		self.counters = Some(ActiveStream::new(counters));
+	}
+
+	/// Apply `bandwidth` to all future reads and writes on this stream.
+	/// This replaces any limits that were previously set.
+	pub fn limit(&mut self, bandwidth: Bandwidth, priority: Priority) {
+		self.limits = Some(StreamLimits::new(bandwidth, priority));
+	}
+
 	pub async fn close(self) -> Result<(), io::Error> {
 		self.io.into_inner().shutdown().await
 	}
@@ -50,9 +113,25 @@ impl AsyncRead for UnicastStream {
 		cx: &mut Context<'_>,
 		buf: &mut ReadBuf<'_>,
 	) -> Poll<io::Result<()>> {
-		Pin::new(&mut self.get_mut().io)
-			.get_pin_mut()
-			.poll_read(cx, buf)
+		let this = self.get_mut();
+		if let Some(limits) = &mut this.limits {
+			ready!(limits.poll_read_ready(cx));
+		}
+
+		let filled = buf.filled().len();
+		let result = Pin::new(&mut this.io).get_pin_mut().poll_read(cx, buf);
+		let read = buf.filled().len() - filled;
+
+		if let Some(ActiveStream(counters)) = &this.counters {
+			counters
+				.bytes_received
+				.fetch_add(read as u64, Ordering::Relaxed);
+		}
+		if let Some(limits) = &this.limits {
+			limits.read(read);
+		}
+
+		result
 	}
 }
 
@@ -62,9 +141,25 @@ impl AsyncWrite for UnicastStream {
 		cx: &mut Context<'_>,
 		buf: &[u8],
 	) -> Poll<io::Result<usize>> {
-		Pin::new(&mut self.get_mut().io)
-			.get_pin_mut()
-			.poll_write(cx, buf)
+		let this = self.get_mut();
+		if let Some(limits) = &mut this.limits {
+			ready!(limits.poll_write_ready(cx));
+		}
+
+		let result = Pin::new(&mut this.io).get_pin_mut().poll_write(cx, buf);
+
+		if let Poll::Ready(Ok(written)) = &result {
+			if let Some(ActiveStream(counters)) = &this.counters {
+				counters
+					.bytes_sent
+					.fetch_add(*written as u64, Ordering::Relaxed);
+			}
+			if let Some(limits) = &this.limits {
+				limits.wrote(*written);
+			}
+		}
+
+		result
 	}
 
 	fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
@@ -6,11 +6,14 @@
 //!
 //! Just bring your own unified error type and dispatch some tasks, the system will handle enqueueing,
 //! parallel execution, and error handling for you. Aside from some niceties like:
-//! - Round robin scheduling between workers following the available CPU cores on the user machine;
+//! - Round robin scheduling between workers following the available CPU cores on the user machine,
+//!   and the amount of workers can be changed at runtime;
 //! - Work stealing between workers for better load balancing;
 //! - Gracefully pause and cancel tasks;
 //! - Forced abortion of tasks;
-//! - Prioritizing tasks that will suspend running tasks without priority;
+//! - Priority classes, where interactive tasks suspend running tasks and the others share the workers fairly;
+//! - Throttling how many tasks are dispatched at the same time and how fast they process their items;
+//! - Snapshots of every worker queues, running tasks and steal counts, to diagnose stalled tasks;
 //! - When the system is shutdown, it will return all pending and running tasks to theirs dispatchers, so the user can store them on disk or any other storage to be re-dispatched later;
 //!
 //!
@@ -89,16 +92,23 @@
 
 mod error;
 mod message;
+mod stats;
 mod system;
 mod task;
+mod throttle;
 mod worker;
 
 pub use error::{DispatcherShutdownError, RunError, SystemError as TaskSystemError};
+pub use stats::{
+	QueuedTasksStats, RunningTaskStats, SuspendedTaskStats, SystemStats as TaskSystemStats,
+	WorkerStats,
+};
 pub use system::{
 	BaseDispatcher as BaseTaskDispatcher, Dispatcher as TaskDispatcher, System as TaskSystem,
 };
 pub use task::{
 	AnyTaskOutput, CancelTaskOnDrop, ExecStatus, Interrupter, InterrupterFuture, InterruptionKind,
 	IntoAnyTaskOutput, IntoTask, SerializableTask, Task, TaskHandle, TaskId, TaskOutput,
-	TaskRemoteController, TaskStatus,
+	TaskPriority, TaskRemoteController, TaskStatus,
 };
+pub use throttle::{Throttle, ThrottleLimits, ThrottlePermit};
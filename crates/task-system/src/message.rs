@@ -5,6 +5,7 @@ use tokio::sync::oneshot;
 
 use super::{
 	error::{RunError, SystemError},
+	stats::WorkerStats,
 	task::{InternalTaskExecStatus, TaskId, TaskWorkState, TaskWorktable},
 	worker::WorkerId,
 };
@@ -38,6 +39,7 @@ pub enum SystemMessage {
 
 pub enum WorkerMessage<E: RunError> {
 	NewTask(TaskWorkState<E>),
+	AdoptTask(TaskWorkState<E>),
 	ResumeTask {
 		task_id: TaskId,
 		ack: oneshot::Sender<Result<(), SystemError>>,
@@ -55,6 +57,8 @@ pub enum WorkerMessage<E: RunError> {
 		ack: oneshot::Sender<Result<(), SystemError>>,
 	},
 	ShutdownRequest(oneshot::Sender<()>),
+	RetireRequest(oneshot::Sender<()>),
+	StatsRequest(oneshot::Sender<WorkerStats>),
 	StealRequest {
 		stealer_id: WorkerId,
 		ack: oneshot::Sender<bool>,
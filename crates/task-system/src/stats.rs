@@ -0,0 +1,55 @@
+use std::time::Duration;
+
+use super::{
+	task::{TaskId, TaskPriority},
+	worker::WorkerId,
+};
+
+/// A snapshot of the task system, see [`System::stats`](crate::TaskSystem::stats).
+#[derive(Debug, Clone)]
+pub struct SystemStats {
+	/// Every worker, including the ones being retired after a resize.
+	pub workers: Vec<WorkerStats>,
+}
+
+#[derive(Debug, Clone)]
+pub struct WorkerStats {
+	pub worker_id: WorkerId,
+	pub is_idle: bool,
+	/// The worker is handing its tasks over to other workers, to be removed from the system.
+	pub is_retiring: bool,
+	pub queued_tasks: QueuedTasksStats,
+	pub paused_tasks_count: usize,
+	pub running_task: Option<RunningTaskStats>,
+	/// A task suspended to let an interactive task run, it runs again before any other queued task
+	/// that isn't interactive.
+	pub suspended_task: Option<SuspendedTaskStats>,
+	/// How many tasks this worker stole from other workers.
+	pub stolen_tasks_count: u64,
+	/// How many tasks other workers stole from this one.
+	pub tasks_stolen_by_others_count: u64,
+}
+
+/// How many tasks are waiting to run on a worker, by [`TaskPriority`] class.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct QueuedTasksStats {
+	pub interactive: usize,
+	pub normal: usize,
+	pub background: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RunningTaskStats {
+	pub task_id: TaskId,
+	pub priority: TaskPriority,
+	/// Time since the worker started running this task, not counting previous runs before it was
+	/// paused or suspended.
+	pub elapsed: Duration,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SuspendedTaskStats {
+	pub task_id: TaskId,
+	pub priority: TaskPriority,
+	pub suspended_for: Duration,
+}
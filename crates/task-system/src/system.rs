@@ -1,5 +1,6 @@
 use std::{
 	cell::RefCell,
+	cmp::Ordering as CmpOrdering,
 	collections::HashSet,
 	fmt,
 	future::Future,
@@ -7,21 +8,26 @@ use std::{
 	pin::pin,
 	sync::{
 		atomic::{AtomicBool, Ordering},
-		Arc,
+		Arc, PoisonError, RwLock,
 	},
 };
 
 use async_channel as chan;
 use futures::StreamExt;
 use futures_concurrency::future::Join;
-use tokio::{spawn, sync::oneshot, task::JoinHandle};
+use tokio::{
+	spawn,
+	sync::{oneshot, Mutex},
+	task::JoinHandle,
+};
 use tracing::{error, info, instrument, trace, warn, Instrument};
 
 use super::{
 	error::{DispatcherShutdownError, RunError, SystemError},
 	message::SystemMessage,
+	stats::SystemStats,
 	task::{IntoTask, Task, TaskHandle, TaskId, TaskWorktable},
-	worker::{AtomicWorkerId, WorkStealer, Worker, WorkerBuilder},
+	worker::{AtomicWorkerId, WorkStealer, Worker, WorkerBuilder, WorkerId},
 };
 
 /// The task system is the main entry point for the library, it is responsible for creating and managing the workers
@@ -30,7 +36,9 @@ use super::{
 /// It also provides a way to shutdown the system returning all pending and running tasks.
 /// It uses internal mutability so it can be shared without hassles using [`Arc`].
 pub struct System<E: RunError> {
-	workers: Arc<Vec<Worker<E>>>,
+	workers: Arc<Workers<E>>,
+	work_stealer: WorkStealer<E>,
+	resize_lock: Mutex<()>,
 	msgs_tx: chan::Sender<SystemMessage>,
 	dispatcher: BaseDispatcher<E>,
 	handle: RefCell<Option<JoinHandle<()>>>,
@@ -38,20 +46,27 @@ pub struct System<E: RunError> {
 }
 
 impl<E: RunError> System<E> {
-	/// Created a new task system with a number of workers equal to the available parallelism in the user's machine.
+	/// Created a new task system with a number of workers equal to half of the available parallelism in the
+	/// user's machine, it can be changed later with [`System::resize`].
+	#[must_use]
 	pub fn new() -> Self {
-		// TODO: Using only the half of available cores, make this configurable on runtime in the future
-		let workers_count = usize::max(
-			std::thread::available_parallelism().map_or_else(
-				|e| {
-					error!(?e, "Failed to get available parallelism in the job system");
-					1
-				},
-				NonZeroUsize::get,
-			) / 2,
-			1,
+		let workers_count = std::thread::available_parallelism().map_or_else(
+			|e| {
+				error!(?e, "Failed to get available parallelism in the job system");
+				NonZeroUsize::MIN
+			},
+			|available_parallelism| {
+				NonZeroUsize::new(available_parallelism.get() / 2).unwrap_or(NonZeroUsize::MIN)
+			},
 		);
 
+		Self::with_workers(workers_count)
+	}
+
+	/// Created a new task system with the given number of workers.
+	pub fn with_workers(workers_count: NonZeroUsize) -> Self {
+		let workers_count = workers_count.get();
+
 		let (msgs_tx, msgs_rx) = chan::bounded(8);
 		let system_comm = SystemComm(msgs_tx.clone());
 
@@ -59,30 +74,21 @@ impl<E: RunError> System<E> {
 			.map(WorkerBuilder::new)
 			.unzip::<_, _, Vec<_>, Vec<_>>();
 
-		let task_stealer = WorkStealer::new(worker_comms);
-
-		let idle_workers = Arc::new((0..workers_count).map(|_| AtomicBool::new(true)).collect());
+		let work_stealer = WorkStealer::new(worker_comms);
 
-		let workers = Arc::new(
+		let workers = Arc::new(Workers::new(
 			workers_builders
 				.into_iter()
-				.map(|builder| builder.build(system_comm.clone(), task_stealer.clone()))
-				.collect::<Vec<_>>(),
-		);
+				.map(|builder| builder.build(system_comm.clone(), work_stealer.clone()))
+				.collect(),
+		));
 
 		let handle = spawn({
 			let workers = Arc::clone(&workers);
-			let idle_workers = Arc::clone(&idle_workers);
 
 			async move {
 				trace!("Task System message processing task starting...");
-				while let Err(e) = spawn(Self::run(
-					Arc::clone(&workers),
-					Arc::clone(&idle_workers),
-					msgs_rx.clone(),
-				))
-				.await
-				{
+				while let Err(e) = spawn(Self::run(Arc::clone(&workers), msgs_rx.clone())).await {
 					if e.is_panic() {
 						error!(?e, "Task system panicked");
 					} else {
@@ -102,10 +108,11 @@ impl<E: RunError> System<E> {
 
 		Self {
 			workers: Arc::clone(&workers),
+			work_stealer,
+			resize_lock: Mutex::new(()),
 			msgs_tx,
 			dispatcher: BaseDispatcher {
 				workers,
-				idle_workers,
 				last_worker_id: Arc::new(AtomicWorkerId::new(0)),
 				has_shutdown: Arc::clone(&has_shutdown),
 			},
@@ -114,9 +121,64 @@ impl<E: RunError> System<E> {
 		}
 	}
 
+	/// Changes the number of workers in the system.
+	///
+	/// New workers start stealing tasks from the busy ones right away. Removed workers hand their pending
+	/// tasks over to the remaining ones and suspend their running task, which will be resumed on another
+	/// worker, so no task is lost. This method only returns after the removed workers are gone.
+	pub async fn resize(&self, workers_count: NonZeroUsize) {
+		let _resize_guard = self.resize_lock.lock().await;
+
+		if self.has_shutdown.load(Ordering::Acquire) {
+			warn!("Trying to resize the tasks system that was already shutdown");
+			return;
+		}
+
+		let workers_count = workers_count.get();
+		let current_workers_count = self.workers.active_count();
+
+		match workers_count.cmp(&current_workers_count) {
+			CmpOrdering::Equal => return,
+
+			CmpOrdering::Greater => {
+				let new_workers = (current_workers_count..workers_count)
+					.map(|worker_id| {
+						let (builder, worker_comm) = WorkerBuilder::new(worker_id);
+						self.work_stealer.add_worker(worker_comm);
+						builder.build(SystemComm(self.msgs_tx.clone()), self.work_stealer.clone())
+					})
+					.collect();
+
+				self.workers.grow(new_workers);
+			}
+
+			CmpOrdering::Less => {
+				// Stop dispatching tasks to the workers being retired, and also stealing from them
+				let retiring_workers = self.workers.shrink(workers_count);
+				self.work_stealer.remove_workers(workers_count);
+
+				retiring_workers
+					.iter()
+					.map(|worker| async move { worker.retire().await })
+					.collect::<Vec<_>>()
+					.join()
+					.await;
+
+				self.workers.remove_retired();
+			}
+		}
+
+		info!(%current_workers_count, %workers_count, "Task system resized");
+	}
+
 	/// Returns the number of workers in the system.
 	pub fn workers_count(&self) -> usize {
-		self.workers.len()
+		self.workers.active_count()
+	}
+
+	/// Returns a snapshot of every worker, with their queues, running tasks and steal counts.
+	pub async fn stats(&self) -> SystemStats {
+		self.workers.stats().await
 	}
 
 	/// Dispatches a task to the system, the task will be assigned to a worker and executed as soon as possible.
@@ -145,21 +207,17 @@ impl<E: RunError> System<E> {
 		self.dispatcher.clone()
 	}
 
-	async fn run(
-		workers: Arc<Vec<Worker<E>>>,
-		idle_workers: Arc<Vec<AtomicBool>>,
-		msgs_rx: chan::Receiver<SystemMessage>,
-	) {
+	async fn run(workers: Arc<Workers<E>>, msgs_rx: chan::Receiver<SystemMessage>) {
 		let mut msg_stream = pin!(msgs_rx);
 
 		while let Some(msg) = msg_stream.next().await {
 			match msg {
 				SystemMessage::IdleReport(worker_id) => {
-					idle_workers[worker_id].store(true, Ordering::Relaxed);
+					workers.get(worker_id).set_idle(true);
 				}
 
 				SystemMessage::WorkingReport(worker_id) => {
-					idle_workers[worker_id].store(false, Ordering::Relaxed);
+					workers.get(worker_id).set_idle(false);
 				}
 
 				SystemMessage::ResumeTask {
@@ -215,6 +273,10 @@ impl<E: RunError> System<E> {
 	/// oneshot channel with shutdown response.
 	pub async fn shutdown(&self) {
 		self.has_shutdown.store(true, Ordering::Release);
+
+		// Waiting for any resize in progress to finish
+		let _resize_guard = self.resize_lock.lock().await;
+
 		if let Some(handle) = self
 			.handle
 			.try_borrow_mut()
@@ -222,6 +284,7 @@ impl<E: RunError> System<E> {
 			.and_then(|mut maybe_handle| maybe_handle.take())
 		{
 			self.workers
+				.all()
 				.iter()
 				.map(|worker| async move { worker.shutdown().await })
 				.collect::<Vec<_>>()
@@ -253,7 +316,7 @@ impl<E: RunError> System<E> {
 
 #[instrument(skip(workers, ack))]
 fn dispatch_resume_request<E: RunError>(
-	workers: &Arc<Vec<Worker<E>>>,
+	workers: &Arc<Workers<E>>,
 	task_id: TaskId,
 	task_work_table: Arc<TaskWorktable>,
 	ack: oneshot::Sender<Result<(), SystemError>>,
@@ -265,7 +328,8 @@ fn dispatch_resume_request<E: RunError>(
 			async move {
 				let (tx, rx) = oneshot::channel();
 				let first_attempt_worker_id = task_work_table.worker_id();
-				workers[first_attempt_worker_id]
+				workers
+					.get(first_attempt_worker_id)
 					.resume_task(task_id, tx)
 					.await;
 				let res = rx
@@ -277,7 +341,8 @@ fn dispatch_resume_request<E: RunError>(
 						%first_attempt_worker_id,
 						"Failed the first try to resume a not running task, trying again",
 					);
-					workers[task_work_table.worker_id()]
+					workers
+						.get(task_work_table.worker_id())
 						.resume_task(task_id, ack)
 						.await;
 				} else {
@@ -293,19 +358,20 @@ fn dispatch_resume_request<E: RunError>(
 
 #[instrument(skip(workers, ack, task_work_table))]
 fn dispatch_pause_not_running_task_request<E: RunError>(
-	workers: &Arc<Vec<Worker<E>>>,
+	workers: &Arc<Workers<E>>,
 	task_id: TaskId,
 	task_work_table: Arc<TaskWorktable>,
 	ack: oneshot::Sender<Result<(), SystemError>>,
 ) {
 	spawn(
 		{
-			let workers: Arc<Vec<Worker<E>>> = Arc::clone(workers);
+			let workers = Arc::clone(workers);
 
 			async move {
 				let (tx, rx) = oneshot::channel();
 				let first_attempt_worker_id = task_work_table.worker_id();
-				workers[first_attempt_worker_id]
+				workers
+					.get(first_attempt_worker_id)
 					.pause_not_running_task(task_id, tx)
 					.await;
 				let res = rx
@@ -317,7 +383,8 @@ fn dispatch_pause_not_running_task_request<E: RunError>(
 						%first_attempt_worker_id,
 						"Failed the first try to pause a not running task, trying again",
 					);
-					workers[task_work_table.worker_id()]
+					workers
+						.get(task_work_table.worker_id())
 						.pause_not_running_task(task_id, ack)
 						.await;
 				} else {
@@ -332,7 +399,7 @@ fn dispatch_pause_not_running_task_request<E: RunError>(
 
 #[instrument(skip(workers, ack))]
 fn dispatch_cancel_not_running_task_request<E: RunError>(
-	workers: &Arc<Vec<Worker<E>>>,
+	workers: &Arc<Workers<E>>,
 	task_id: TaskId,
 	task_work_table: Arc<TaskWorktable>,
 	ack: oneshot::Sender<Result<(), SystemError>>,
@@ -344,7 +411,8 @@ fn dispatch_cancel_not_running_task_request<E: RunError>(
 			async move {
 				let (tx, rx) = oneshot::channel();
 				let first_attempt_worker_id = task_work_table.worker_id();
-				workers[first_attempt_worker_id]
+				workers
+					.get(first_attempt_worker_id)
 					.cancel_not_running_task(task_id, tx)
 					.await;
 				let res = rx
@@ -362,7 +430,8 @@ fn dispatch_cancel_not_running_task_request<E: RunError>(
 						%first_attempt_worker_id,
 						"Failed the first try to cancel a not running task, trying again",
 					);
-					workers[task_work_table.worker_id()]
+					workers
+						.get(task_work_table.worker_id())
 						.cancel_not_running_task(task_id, ack)
 						.await;
 				} else {
@@ -379,7 +448,7 @@ fn dispatch_cancel_not_running_task_request<E: RunError>(
 
 #[instrument(skip(workers, ack))]
 fn dispatch_force_abortion_task_request<E: RunError>(
-	workers: &Arc<Vec<Worker<E>>>,
+	workers: &Arc<Workers<E>>,
 	task_id: TaskId,
 	task_work_table: Arc<TaskWorktable>,
 	ack: oneshot::Sender<Result<(), SystemError>>,
@@ -391,7 +460,8 @@ fn dispatch_force_abortion_task_request<E: RunError>(
 			async move {
 				let (tx, rx) = oneshot::channel();
 				let first_attempt_worker_id = task_work_table.worker_id();
-				workers[first_attempt_worker_id]
+				workers
+					.get(first_attempt_worker_id)
 					.force_task_abortion(task_id, tx)
 					.await;
 				let res = rx.await.expect(
@@ -403,7 +473,8 @@ fn dispatch_force_abortion_task_request<E: RunError>(
 						%first_attempt_worker_id,
 						"Failed the first try to force abortion of a not running task, trying again",
 					);
-					workers[task_work_table.worker_id()]
+					workers
+						.get(task_work_table.worker_id())
 						.force_task_abortion(task_id, ack)
 						.await;
 				} else {
@@ -556,8 +627,7 @@ impl SystemComm {
 /// It uses [`Arc`] internally so it can be cheaply cloned and put inside tasks so tasks can dispatch other tasks.
 #[derive(Debug)]
 pub struct BaseDispatcher<E: RunError> {
-	workers: Arc<Vec<Worker<E>>>,
-	idle_workers: Arc<Vec<AtomicBool>>,
+	workers: Arc<Workers<E>>,
 	last_worker_id: Arc<AtomicWorkerId>,
 	has_shutdown: Arc<AtomicBool>,
 }
@@ -610,7 +680,6 @@ impl<E: RunError> Clone for BaseDispatcher<E> {
 	fn clone(&self) -> Self {
 		Self {
 			workers: Arc::clone(&self.workers),
-			idle_workers: Arc::clone(&self.idle_workers),
 			last_worker_id: Arc::clone(&self.last_worker_id),
 			has_shutdown: Arc::clone(&self.has_shutdown),
 		}
@@ -620,29 +689,29 @@ impl<E: RunError> Clone for BaseDispatcher<E> {
 impl<E: RunError> Dispatcher<E> for BaseDispatcher<E> {
 	type DispatchError = DispatcherShutdownError<E>;
 
-	#[allow(clippy::missing_panics_doc)]
 	async fn dispatch_boxed(
 		&self,
-		task: Box<dyn Task<E>>,
+		mut task: Box<dyn Task<E>>,
 	) -> Result<TaskHandle<E>, Self::DispatchError> {
-		if self.has_shutdown.load(Ordering::Acquire) {
-			return Err(DispatcherShutdownError(vec![task]));
-		}
-
-		let worker_id = self
-				.last_worker_id
-				.fetch_update(Ordering::Release, Ordering::Acquire, |last_worker_id| {
-					Some((last_worker_id + 1) % self.workers.len())
-				})
-				.expect("we hardcoded the update function to always return Some(next_worker_id) through dispatcher");
+		loop {
+			if self.has_shutdown.load(Ordering::Acquire) {
+				return Err(DispatcherShutdownError(vec![task]));
+			}
 
-		trace!(%worker_id, task_id = %task.id(), "Dispatching task to worker");
+			let worker = self.workers.next_active(&self.last_worker_id);
 
-		let handle = self.workers[worker_id].add_task(task).await;
+			trace!(worker_id = worker.id, task_id = %task.id(), "Dispatching task to worker");
 
-		self.idle_workers[worker_id].store(false, Ordering::Relaxed);
+			match worker.add_task(task).await {
+				Ok(handle) => {
+					worker.set_idle(false);
 
-		Ok(handle)
+					return Ok(handle);
+				}
+				// The system was resized while we were dispatching, so we try again on another worker
+				Err(returned_task) => task = returned_task,
+			}
+		}
 	}
 
 	async fn dispatch_many_boxed(
@@ -653,20 +722,33 @@ impl<E: RunError> Dispatcher<E> for BaseDispatcher<E> {
 			return Err(DispatcherShutdownError(into_tasks.into_iter().collect()));
 		}
 
-		let (handles, workers_ids_set) = into_tasks
+		let workers = self.workers.active();
+
+		let (results, workers_ids_set) = into_tasks
 			.into_iter()
-			.zip((0..self.workers.len()).cycle())
-			.map(|(task, worker_id)| async move {
-				(self.workers[worker_id].add_task(task).await, worker_id)
-			})
+			.zip(workers.iter().cycle())
+			.map(|(task, worker)| async move { (worker.add_task(task).await, worker.id) })
 			.collect::<Vec<_>>()
 			.join()
 			.await
 			.into_iter()
 			.unzip::<_, _, Vec<_>, HashSet<_>>();
 
-		for worker_id in workers_ids_set {
-			self.idle_workers[worker_id].store(false, Ordering::Relaxed);
+		for worker in workers
+			.iter()
+			.filter(|worker| workers_ids_set.contains(&worker.id))
+		{
+			worker.set_idle(false);
+		}
+
+		let mut handles = Vec::with_capacity(results.len());
+
+		for result in results {
+			handles.push(match result {
+				Ok(handle) => handle,
+				// The system was resized while we were dispatching, so we try again on another worker
+				Err(returned_task) => self.dispatch_boxed(returned_task).await?,
+			});
 		}
 
 		Ok(handles)
@@ -677,6 +759,120 @@ impl<E: RunError> BaseDispatcher<E> {
 	/// Returns the number of workers in the system.
 	#[must_use]
 	pub fn workers_count(&self) -> usize {
-		self.workers.len()
+		self.workers.active_count()
+	}
+
+	/// Returns a snapshot of every worker, with their queues, running tasks and steal counts.
+	pub async fn stats(&self) -> SystemStats {
+		self.workers.stats().await
+	}
+}
+
+/// Workers shared between the system and its dispatchers.
+///
+/// Only the first `active_count` workers receive new tasks, the remaining ones are being retired by
+/// [`System::resize`] and will be removed as soon as they hand all their tasks over.
+#[derive(Debug)]
+struct Workers<E: RunError> {
+	inner: RwLock<WorkersInner<E>>,
+}
+
+#[derive(Debug)]
+struct WorkersInner<E: RunError> {
+	all: Vec<Arc<Worker<E>>>,
+	active_count: usize,
+}
+
+impl<E: RunError> Workers<E> {
+	fn new(workers: Vec<Worker<E>>) -> Self {
+		Self {
+			inner: RwLock::new(WorkersInner {
+				active_count: workers.len(),
+				all: workers.into_iter().map(Arc::new).collect(),
+			}),
+		}
+	}
+
+	fn active_count(&self) -> usize {
+		self.inner
+			.read()
+			.unwrap_or_else(PoisonError::into_inner)
+			.active_count
+	}
+
+	fn all(&self) -> Vec<Arc<Worker<E>>> {
+		self.inner
+			.read()
+			.unwrap_or_else(PoisonError::into_inner)
+			.all
+			.clone()
+	}
+
+	fn active(&self) -> Vec<Arc<Worker<E>>> {
+		let inner = self.inner.read().unwrap_or_else(PoisonError::into_inner);
+
+		inner.all[..inner.active_count].to_vec()
+	}
+
+	/// Worker with this id, or the first one if it was already retired. As the retired worker handed all
+	/// its tasks over, the first worker will answer that the task wasn't found and the system will
+	/// try again on the task's current worker.
+	fn get(&self, worker_id: WorkerId) -> Arc<Worker<E>> {
+		let inner = self.inner.read().unwrap_or_else(PoisonError::into_inner);
+
+		Arc::clone(inner.all.get(worker_id).unwrap_or(&inner.all[0]))
+	}
+
+	/// Next active worker to receive a task, in a round robin fashion
+	fn next_active(&self, last_worker_id: &AtomicWorkerId) -> Arc<Worker<E>> {
+		let inner = self.inner.read().unwrap_or_else(PoisonError::into_inner);
+
+		let worker_id = last_worker_id.fetch_add(1, Ordering::Relaxed) % inner.active_count;
+
+		Arc::clone(&inner.all[worker_id])
+	}
+
+	async fn stats(&self) -> SystemStats {
+		SystemStats {
+			workers: self
+				.all()
+				.iter()
+				.map(|worker| worker.stats())
+				.collect::<Vec<_>>()
+				.join()
+				.await
+				.into_iter()
+				.flatten()
+				.collect(),
+		}
+	}
+
+	fn grow(&self, new_workers: Vec<Worker<E>>) {
+		let mut inner = self.inner.write().unwrap_or_else(PoisonError::into_inner);
+
+		assert_eq!(
+			inner.all.len(),
+			inner.active_count,
+			"can't grow while workers are being retired"
+		);
+
+		inner.all.extend(new_workers.into_iter().map(Arc::new));
+		inner.active_count = inner.all.len();
+	}
+
+	/// Stops dispatching tasks to workers with ids from `active_count` onwards, returning them
+	fn shrink(&self, active_count: usize) -> Vec<Arc<Worker<E>>> {
+		let mut inner = self.inner.write().unwrap_or_else(PoisonError::into_inner);
+
+		inner.active_count = active_count;
+
+		inner.all[active_count..].to_vec()
+	}
+
+	fn remove_retired(&self) {
+		let mut inner = self.inner.write().unwrap_or_else(PoisonError::into_inner);
+
+		let active_count = inner.active_count;
+		inner.all.truncate(active_count);
 	}
 }
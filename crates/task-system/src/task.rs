@@ -14,7 +14,10 @@ use async_channel as chan;
 use async_trait::async_trait;
 use downcast_rs::{impl_downcast, Downcast};
 use futures::StreamExt;
-use tokio::{spawn, sync::oneshot};
+use tokio::{
+	spawn,
+	sync::{oneshot, Notify},
+};
 use tracing::{error, instrument, trace, warn, Instrument};
 use uuid::Uuid;
 
@@ -128,6 +131,36 @@ impl<T: Task<E> + 'static, E: RunError> IntoTask<E> for T {
 	}
 }
 
+/// Priority classes of tasks, ordered from the least to the most important one.
+///
+/// Each worker keeps a queue per class and picks the next task to run with a weighted round robin
+/// between them, where more important classes get more turns.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TaskPriority {
+	/// Work that can be done whenever there is nothing else to do, like generating thumbnails for a
+	/// whole location.
+	Background,
+	/// The default class of tasks.
+	#[default]
+	Normal,
+	/// Work that the user is waiting for, these tasks suspend running tasks of the other classes.
+	Interactive,
+}
+
+impl TaskPriority {
+	/// All classes, from the most to the least important one.
+	pub const ALL: [Self; 3] = [Self::Interactive, Self::Normal, Self::Background];
+
+	/// How many tasks of this class are dispatched on a worker for each round robin turn.
+	pub(crate) const fn weight(self) -> u32 {
+		match self {
+			Self::Interactive => 8,
+			Self::Normal => 4,
+			Self::Background => 1,
+		}
+	}
+}
+
 /// The main trait that represents a task that can be dispatched to the task system.
 ///
 /// All traits in the task system must return the same generic error type, so we can have a unified
@@ -148,6 +181,19 @@ pub trait Task<E: RunError>: Downcast + Send + Sync + 'static {
 		false
 	}
 
+	/// The priority class of this task, by default [`TaskPriority::Interactive`] if the task
+	/// [`with_priority`](Task::with_priority), or [`TaskPriority::Normal`] otherwise.
+	///
+	/// Only [`TaskPriority::Interactive`] tasks suspend running tasks, the other classes just share the workers
+	/// in a fair way, so [`TaskPriority::Background`] tasks still make progress under heavy load.
+	fn priority(&self) -> TaskPriority {
+		if self.with_priority() {
+			TaskPriority::Interactive
+		} else {
+			TaskPriority::Normal
+		}
+	}
+
 	/// Here we define if we want the task system to shutdown our task if it takes too long to finish. By default the
 	/// task system will wait indefinitely for the task to finish, but if the user wants to have a timeout, they can
 	/// return a [`Duration`] here and the task system will cancel the task if it takes longer than the specified time.
@@ -548,6 +594,17 @@ impl TaskRemoteController {
 			| self.worktable.has_canceled()
 			| self.worktable.has_failed()
 	}
+
+	/// Wait until the task is finalized, which happens after it was completed, canceled, aborted,
+	/// failed or returned to its dispatcher due to a shutdown
+	pub async fn wait_finalized(&self) {
+		let mut notified = pin!(self.worktable.finalized_notify.notified());
+		notified.as_mut().enable();
+
+		if !self.worktable.is_finalized() {
+			notified.await;
+		}
+	}
 }
 
 /// A handle returned when a task is dispatched to the task system, it can be used to pause, cancel, resume, or wait
@@ -655,11 +712,12 @@ pub struct TaskWorktable {
 	has_failed: AtomicBool,
 	interrupt_tx: chan::Sender<InterruptionRequest>,
 	finalized: AtomicBool,
+	finalized_notify: Notify,
 	current_worker_id: AtomicWorkerId,
 }
 
 impl TaskWorktable {
-	pub const fn new(worker_id: WorkerId, interrupt_tx: chan::Sender<InterruptionRequest>) -> Self {
+	pub fn new(worker_id: WorkerId, interrupt_tx: chan::Sender<InterruptionRequest>) -> Self {
 		Self {
 			started: AtomicBool::new(false),
 			is_running: AtomicBool::new(false),
@@ -670,6 +728,7 @@ impl TaskWorktable {
 			has_shutdown: AtomicBool::new(false),
 			has_failed: AtomicBool::new(false),
 			finalized: AtomicBool::new(false),
+			finalized_notify: Notify::new(),
 			interrupt_tx,
 			current_worker_id: AtomicWorkerId::new(worker_id),
 		}
@@ -722,6 +781,7 @@ impl TaskWorktable {
 
 	pub fn set_finalized(&self) {
 		self.finalized.store(true, Ordering::Release);
+		self.finalized_notify.notify_waiters();
 	}
 
 	pub fn pause(self: &Arc<Self>, outer_tx: oneshot::Sender<()>) {
@@ -858,21 +918,10 @@ impl TaskWorktable {
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PendingTaskKind {
-	Normal,
-	Priority,
+	Queued(TaskPriority),
 	Suspended,
 }
 
-impl PendingTaskKind {
-	const fn with_priority(has_priority: bool) -> Self {
-		if has_priority {
-			Self::Priority
-		} else {
-			Self::Normal
-		}
-	}
-}
-
 pub struct TaskWorkState<E: RunError> {
 	pub(crate) task: Box<dyn Task<E>>,
 	pub(crate) worktable: Arc<TaskWorktable>,
@@ -888,7 +937,7 @@ impl<E: RunError> TaskWorkState<E> {
 
 	#[inline]
 	pub fn kind(&self) -> PendingTaskKind {
-		PendingTaskKind::with_priority(self.task.with_priority())
+		PendingTaskKind::Queued(self.task.priority())
 	}
 }
 
@@ -918,6 +967,11 @@ impl<E: RunError> PanicOnSenderDrop<E> {
 			.expect("tried to send a task output twice to the same task handle")
 			.send(res)
 	}
+
+	/// Drops the sender without panicking, only meant for tasks that never reached a worker
+	pub fn discard(mut self) {
+		self.maybe_done_tx = None;
+	}
 }
 
 impl<E: RunError> Drop for PanicOnSenderDrop<E> {
@@ -0,0 +1,204 @@
+use std::{
+	num::{NonZeroU32, NonZeroUsize},
+	sync::Arc,
+	time::{Duration, Instant},
+};
+
+use futures::FutureExt;
+use futures_concurrency::future::Race;
+use tokio::{sync::watch, time::sleep};
+use tracing::trace;
+
+/// Limits enforced by a [`Throttle`], every limit is disabled by default
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ThrottleLimits {
+	/// How many tasks holding a [`ThrottlePermit`] can run at the same time
+	pub max_concurrent_tasks: Option<NonZeroUsize>,
+	/// How many items, reported with [`Throttle::consume`], can be processed per second
+	pub max_items_per_second: Option<NonZeroU32>,
+	/// No permit is given while paused, but the ones already acquired are kept
+	pub paused: bool,
+}
+
+/// Gates how fast tasks are dispatched to the task system.
+///
+/// Each task must acquire a [`ThrottlePermit`] before being dispatched, and keep it until it is
+/// finalized, see [`TaskRemoteController::wait_finalized`](crate::TaskRemoteController::wait_finalized).
+/// Clones share the same limits and permits, so a single throttle can gate many dispatchers.
+#[derive(Debug, Clone)]
+pub struct Throttle {
+	state: Arc<watch::Sender<State>>,
+}
+
+#[derive(Debug)]
+struct State {
+	limits: ThrottleLimits,
+	running: usize,
+	/// Items that can still be processed, negative after going over the rate limit
+	allowance: f64,
+	last_refill: Instant,
+}
+
+enum Availability {
+	Available,
+	WaitChange,
+	WaitFor(Duration),
+}
+
+impl State {
+	fn refill(&mut self, now: Instant) {
+		if let Some(rate) = self.limits.max_items_per_second {
+			let rate = f64::from(rate.get());
+
+			self.allowance = now
+				.duration_since(self.last_refill)
+				.as_secs_f64()
+				.mul_add(rate, self.allowance)
+				.min(rate);
+		}
+
+		self.last_refill = now;
+	}
+
+	fn availability(&mut self, now: Instant) -> Availability {
+		if self.limits.paused {
+			return Availability::WaitChange;
+		}
+
+		if self
+			.limits
+			.max_concurrent_tasks
+			.is_some_and(|max| self.running >= max.get())
+		{
+			return Availability::WaitChange;
+		}
+
+		self.refill(now);
+
+		match self.limits.max_items_per_second {
+			Some(rate) if self.allowance < 0.0 => Availability::WaitFor(Duration::from_secs_f64(
+				-self.allowance / f64::from(rate.get()),
+			)),
+			_ => Availability::Available,
+		}
+	}
+}
+
+impl Throttle {
+	#[must_use]
+	pub fn new(limits: ThrottleLimits) -> Self {
+		let (state_tx, _state_rx) = watch::channel(State {
+			limits,
+			running: 0,
+			allowance: limits
+				.max_items_per_second
+				.map_or(0.0, |rate| f64::from(rate.get())),
+			last_refill: Instant::now(),
+		});
+
+		Self {
+			state: Arc::new(state_tx),
+		}
+	}
+
+	/// Current limits of this throttle
+	#[must_use]
+	pub fn limits(&self) -> ThrottleLimits {
+		self.state.borrow().limits
+	}
+
+	/// How many permits are currently held
+	#[must_use]
+	pub fn running(&self) -> usize {
+		self.state.borrow().running
+	}
+
+	/// Replace the limits of this throttle, waking up anyone waiting for a permit if they changed
+	pub fn set_limits(&self, limits: ThrottleLimits) {
+		self.state.send_if_modified(|state| {
+			if state.limits == limits {
+				return false;
+			}
+
+			trace!(?limits, "Changing throttle limits;");
+
+			state.refill(Instant::now());
+			state.limits = limits;
+			state.allowance = limits
+				.max_items_per_second
+				.map_or(0.0, |rate| state.allowance.min(f64::from(rate.get())));
+
+			true
+		});
+	}
+
+	/// Report that `items` were processed, so the next permits will be delayed if we went over
+	/// the items per second limit
+	pub fn consume(&self, items: usize) {
+		self.state.send_if_modified(|state| {
+			if state.limits.max_items_per_second.is_some() {
+				state.refill(Instant::now());
+
+				#[allow(clippy::cast_precision_loss)]
+				// SAFETY: losing precision on absurdly large amounts of items is fine for rate limiting
+				{
+					state.allowance -= items as f64;
+				}
+			}
+
+			// Consuming only makes permits harder to acquire, so no need to wake anyone up
+			false
+		});
+	}
+
+	/// Wait until the limits allow one more task to run
+	#[allow(clippy::missing_panics_doc)]
+	pub async fn acquire(&self) -> ThrottlePermit {
+		let mut state_rx = self.state.subscribe();
+
+		loop {
+			// Marking the current state as seen, so we don't miss any change after checking it
+			state_rx.borrow_and_update();
+
+			let mut availability = Availability::WaitChange;
+
+			self.state.send_if_modified(|state| {
+				availability = state.availability(Instant::now());
+
+				if matches!(availability, Availability::Available) {
+					state.running += 1;
+					true
+				} else {
+					false
+				}
+			});
+
+			let changed = state_rx
+				.changed()
+				.map(|res| res.expect("throttle state sender is kept alive by ourselves"));
+
+			match availability {
+				Availability::Available => {
+					return ThrottlePermit {
+						state: Arc::clone(&self.state),
+					}
+				}
+				Availability::WaitChange => changed.await,
+				Availability::WaitFor(duration) => (changed, sleep(duration)).race().await,
+			}
+		}
+	}
+}
+
+/// Allows a task to run, releasing its slot on the [`Throttle`] when dropped
+#[derive(Debug)]
+#[must_use = "the permit is released as soon as it is dropped"]
+pub struct ThrottlePermit {
+	state: Arc<watch::Sender<State>>,
+}
+
+impl Drop for ThrottlePermit {
+	fn drop(&mut self) {
+		self.state.send_modify(|state| state.running -= 1);
+	}
+}
@@ -1,6 +1,9 @@
 use std::{
 	cell::RefCell,
-	sync::{atomic::AtomicUsize, Arc},
+	sync::{
+		atomic::{AtomicBool, AtomicUsize, Ordering},
+		Arc, PoisonError, RwLock,
+	},
 	time::Duration,
 };
 
@@ -11,6 +14,7 @@ use tracing::{error, info, instrument, trace, warn, Instrument};
 use super::{
 	error::{RunError, SystemError},
 	message::{StoleTaskMessage, TaskRunnerOutput, WorkerMessage},
+	stats::WorkerStats,
 	system::SystemComm,
 	task::{
 		Interrupter, PanicOnSenderDrop, Task, TaskHandle, TaskId, TaskRemoteController,
@@ -91,6 +95,7 @@ impl<E: RunError> WorkerBuilder<E> {
 			id,
 			system_comm,
 			msgs_tx,
+			is_idle: AtomicBool::new(true),
 			handle: RefCell::new(Some(handle)),
 		}
 	}
@@ -101,11 +106,20 @@ pub struct Worker<E: RunError> {
 	pub id: usize,
 	system_comm: SystemComm,
 	msgs_tx: chan::Sender<WorkerMessage<E>>,
+	is_idle: AtomicBool,
 	handle: RefCell<Option<JoinHandle<()>>>,
 }
 
 impl<E: RunError> Worker<E> {
-	pub async fn add_task(&self, new_task: Box<dyn Task<E>>) -> TaskHandle<E> {
+	pub fn set_idle(&self, is_idle: bool) {
+		self.is_idle.store(is_idle, Ordering::Relaxed);
+	}
+
+	/// Gives the task back if this worker was already retired
+	pub async fn add_task(
+		&self,
+		new_task: Box<dyn Task<E>>,
+	) -> Result<TaskHandle<E>, Box<dyn Task<E>>> {
 		let (done_tx, done_rx) = oneshot::channel();
 
 		let (interrupt_tx, interrupt_rx) = chan::bounded(1);
@@ -114,7 +128,8 @@ impl<E: RunError> Worker<E> {
 
 		let task_id = new_task.id();
 
-		self.msgs_tx
+		if let Err(chan::SendError(msg)) = self
+			.msgs_tx
 			.send(WorkerMessage::NewTask(TaskWorkState {
 				task: new_task,
 				worktable: Arc::clone(&worktable),
@@ -122,16 +137,25 @@ impl<E: RunError> Worker<E> {
 				done_tx: PanicOnSenderDrop::new(task_id, done_tx),
 			}))
 			.await
-			.expect("Worker channel closed trying to add task");
+		{
+			let WorkerMessage::NewTask(TaskWorkState { task, done_tx, .. }) = msg else {
+				unreachable!("we just sent a new task message");
+			};
 
-		TaskHandle {
+			trace!(%task_id, "Worker was retired before receiving the task");
+			done_tx.discard();
+
+			return Err(task);
+		}
+
+		Ok(TaskHandle {
 			done_rx,
 			controller: TaskRemoteController {
 				worktable,
 				system_comm: self.system_comm.clone(),
 				task_id,
 			},
-		}
+		})
 	}
 
 	pub async fn resume_task(
@@ -139,10 +163,8 @@ impl<E: RunError> Worker<E> {
 		task_id: TaskId,
 		ack: oneshot::Sender<Result<(), SystemError>>,
 	) {
-		self.msgs_tx
-			.send(WorkerMessage::ResumeTask { task_id, ack })
-			.await
-			.expect("Worker channel closed trying to resume task");
+		self.send_task_request(WorkerMessage::ResumeTask { task_id, ack })
+			.await;
 	}
 
 	pub async fn pause_not_running_task(
@@ -150,10 +172,8 @@ impl<E: RunError> Worker<E> {
 		task_id: TaskId,
 		ack: oneshot::Sender<Result<(), SystemError>>,
 	) {
-		self.msgs_tx
-			.send(WorkerMessage::PauseNotRunningTask { task_id, ack })
-			.await
-			.expect("Worker channel closed trying to pause a not running task");
+		self.send_task_request(WorkerMessage::PauseNotRunningTask { task_id, ack })
+			.await;
 	}
 
 	pub async fn cancel_not_running_task(
@@ -161,10 +181,8 @@ impl<E: RunError> Worker<E> {
 		task_id: TaskId,
 		ack: oneshot::Sender<Result<(), SystemError>>,
 	) {
-		self.msgs_tx
-			.send(WorkerMessage::CancelNotRunningTask { task_id, ack })
-			.await
-			.expect("Worker channel closed trying to cancel a not running task");
+		self.send_task_request(WorkerMessage::CancelNotRunningTask { task_id, ack })
+			.await;
 	}
 
 	pub async fn force_task_abortion(
@@ -172,10 +190,64 @@ impl<E: RunError> Worker<E> {
 		task_id: TaskId,
 		ack: oneshot::Sender<Result<(), SystemError>>,
 	) {
+		self.send_task_request(WorkerMessage::ForceAbortion { task_id, ack })
+			.await;
+	}
+
+	async fn send_task_request(&self, msg: WorkerMessage<E>) {
+		if let Err(chan::SendError(
+			WorkerMessage::ResumeTask { task_id, ack }
+			| WorkerMessage::PauseNotRunningTask { task_id, ack }
+			| WorkerMessage::CancelNotRunningTask { task_id, ack }
+			| WorkerMessage::ForceAbortion { task_id, ack },
+		)) = self.msgs_tx.send(msg).await
+		{
+			// This worker was retired and handed its tasks over to other workers, so we answer that
+			// the task wasn't found and the system will look for it again on its current worker
+			if ack.send(Err(SystemError::TaskNotFound(task_id))).is_err() {
+				warn!(%task_id, "Task request channel closed before sending not found response");
+			}
+		}
+	}
+
+	/// Returns `None` if this worker was already retired
+	pub async fn stats(&self) -> Option<WorkerStats> {
+		let (tx, rx) = oneshot::channel();
+
 		self.msgs_tx
-			.send(WorkerMessage::ForceAbortion { task_id, ack })
+			.send(WorkerMessage::StatsRequest(tx))
 			.await
-			.expect("Worker channel closed trying to force task abortion");
+			.ok()?;
+
+		rx.await.ok()
+	}
+
+	/// Removes this worker from the system, handing all its tasks over to the other workers
+	#[instrument(skip(self), fields(worker_id = self.id))]
+	pub async fn retire(&self) {
+		if let Some(handle) = self
+			.handle
+			.try_borrow_mut()
+			.ok()
+			.and_then(|mut maybe_handle| maybe_handle.take())
+		{
+			let (tx, rx) = oneshot::channel();
+
+			self.msgs_tx
+				.send(WorkerMessage::RetireRequest(tx))
+				.await
+				.expect("Worker channel closed trying to retire");
+
+			rx.await.expect("Worker channel closed trying to retire");
+
+			if let Err(e) = handle.await {
+				if e.is_panic() {
+					error!("Worker {} critically failed: {e:#?}", self.id);
+				}
+			}
+		} else {
+			warn!("Trying to retire a worker that was already shutdown");
+		}
 	}
 
 	#[instrument(skip(self), fields(worker_id = self.id))]
@@ -210,12 +282,20 @@ impl<E: RunError> Worker<E> {
 /// receiving `&self` which is called once, and we also use `try_borrow_mut` so we never panic
 unsafe impl<E: RunError> Sync for Worker<E> {}
 
-#[derive(Clone)]
 pub struct WorkerComm<E: RunError> {
 	worker_id: WorkerId,
 	msgs_tx: chan::Sender<WorkerMessage<E>>,
 }
 
+impl<E: RunError> Clone for WorkerComm<E> {
+	fn clone(&self) -> Self {
+		Self {
+			worker_id: self.worker_id,
+			msgs_tx: self.msgs_tx.clone(),
+		}
+	}
+}
+
 impl<E: RunError> WorkerComm<E> {
 	pub async fn steal_task(
 		&self,
@@ -224,28 +304,54 @@ impl<E: RunError> WorkerComm<E> {
 	) -> bool {
 		let (tx, rx) = oneshot::channel();
 
-		self.msgs_tx
+		if self
+			.msgs_tx
 			.send(WorkerMessage::StealRequest {
 				stealer_id,
 				ack: tx,
 				stolen_task_tx,
 			})
 			.await
-			.expect("Worker channel closed trying to steal task");
+			.is_err()
+		{
+			trace!(
+				worker_id = self.worker_id,
+				"Worker was retired before the steal request"
+			);
+			return false;
+		}
 
 		rx.await
 			.expect("Worker channel closed trying to steal task")
 	}
+
+	pub async fn adopt_task(
+		&self,
+		task_work_state: TaskWorkState<E>,
+	) -> Result<(), TaskWorkState<E>> {
+		self.msgs_tx
+			.send(WorkerMessage::AdoptTask(task_work_state))
+			.await
+			.map_err(|chan::SendError(msg)| {
+				let WorkerMessage::AdoptTask(task_work_state) = msg else {
+					unreachable!("we just sent an adopt task message");
+				};
+
+				task_work_state
+			})
+	}
 }
 
 pub struct WorkStealer<E: RunError> {
-	worker_comms: Arc<Vec<WorkerComm<E>>>,
+	worker_comms: Arc<RwLock<Vec<WorkerComm<E>>>>,
+	last_adopter_id: Arc<AtomicWorkerId>,
 }
 
 impl<E: RunError> Clone for WorkStealer<E> {
 	fn clone(&self) -> Self {
 		Self {
 			worker_comms: Arc::clone(&self.worker_comms),
+			last_adopter_id: Arc::clone(&self.last_adopter_id),
 		}
 	}
 }
@@ -253,8 +359,68 @@ impl<E: RunError> Clone for WorkStealer<E> {
 impl<E: RunError> WorkStealer<E> {
 	pub fn new(worker_comms: Vec<WorkerComm<E>>) -> Self {
 		Self {
-			worker_comms: Arc::new(worker_comms),
+			worker_comms: Arc::new(RwLock::new(worker_comms)),
+			last_adopter_id: Arc::new(AtomicWorkerId::new(0)),
+		}
+	}
+
+	fn worker_comms(&self) -> Vec<WorkerComm<E>> {
+		self.worker_comms
+			.read()
+			.unwrap_or_else(PoisonError::into_inner)
+			.clone()
+	}
+
+	pub fn add_worker(&self, worker_comm: WorkerComm<E>) {
+		self.worker_comms
+			.write()
+			.unwrap_or_else(PoisonError::into_inner)
+			.push(worker_comm);
+	}
+
+	/// Stops stealing from and handing tasks over to workers with ids from `workers_count` onwards
+	pub fn remove_workers(&self, workers_count: usize) {
+		self.worker_comms
+			.write()
+			.unwrap_or_else(PoisonError::into_inner)
+			.truncate(workers_count);
+	}
+
+	/// Gives a task from a retiring worker to one of the remaining workers, in a round robin fashion
+	#[instrument(skip_all, fields(task_id = %task_work_state.id()))]
+	pub async fn hand_over(
+		&self,
+		mut task_work_state: TaskWorkState<E>,
+	) -> Result<(), TaskWorkState<E>> {
+		let worker_comms = self.worker_comms();
+		let total_workers = worker_comms.len();
+
+		let first_worker_id =
+			self.last_adopter_id.fetch_add(1, Ordering::Relaxed) % total_workers.max(1);
+
+		for worker_comm in worker_comms
+			.iter()
+			.cycle()
+			.skip(first_worker_id)
+			.take(total_workers)
+		{
+			task_work_state
+				.worktable
+				.change_worker(worker_comm.worker_id);
+
+			match worker_comm.adopt_task(task_work_state).await {
+				Ok(()) => {
+					trace!(
+						adopter_worker_id = worker_comm.worker_id,
+						"Handed task over"
+					);
+					return Ok(());
+				}
+				Err(returned_task_work_state) => task_work_state = returned_task_work_state,
+			}
 		}
+
+		Err(task_work_state)
 	}
 
 	#[instrument(skip(self, stolen_task_tx))]
@@ -263,10 +429,10 @@ impl<E: RunError> WorkStealer<E> {
 		stealer_id: WorkerId,
 		stolen_task_tx: &chan::Sender<Option<StoleTaskMessage<E>>>,
 	) {
-		let total_workers = self.worker_comms.len();
+		let worker_comms = self.worker_comms();
+		let total_workers = worker_comms.len();
 
-		for worker_comm in self
-			.worker_comms
+		for worker_comm in worker_comms
 			.iter()
 			// Cycling over the workers
 			.cycle()
@@ -34,6 +34,10 @@ pub(super) async fn run<E: RunError>(
 	let (mut runner, stole_task_rx, task_output_rx) =
 		Runner::new(worker_id, work_stealer, system_comm);
 
+	// Kept to close the channel when retiring, so no new messages can be sent to us
+	let msgs_rx_closer = msgs_rx.clone();
+	let mut retire_ack = None;
+
 	let mut idle_checker_interval = interval_at(Instant::now(), ONE_SECOND);
 	idle_checker_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
 
@@ -52,10 +56,17 @@ pub(super) async fn run<E: RunError>(
 				let task_id = task_work_state.id();
 				runner.abort_steal_task();
 				trace!(%task_id, "New task received");
-				runner.new_task(task_id, task_work_state.kind(), task_work_state);
+				runner
+					.new_task(task_id, task_work_state.kind(), task_work_state)
+					.await;
 				trace!(%task_id, "New task added");
 			}
 
+			StreamMessage::Commands(WorkerMessage::AdoptTask(task_work_state)) => {
+				runner.abort_steal_task();
+				runner.adopt_task(task_work_state).await;
+			}
+
 			StreamMessage::Commands(WorkerMessage::ResumeTask { task_id, ack }) => {
 				trace!(%task_id, "Resume task request received");
 				if ack.send(runner.resume_task(task_id)).is_err() {
@@ -91,6 +102,17 @@ pub(super) async fn run<E: RunError>(
 				return runner.shutdown(tx).await;
 			}
 
+			// The stats requester may have given up waiting, which is fine
+			StreamMessage::Commands(WorkerMessage::StatsRequest(tx)) => {
+				drop(tx.send(runner.stats()));
+			}
+
+			StreamMessage::Commands(WorkerMessage::RetireRequest(tx)) => {
+				msgs_rx_closer.close();
+				runner.retire().await;
+				retire_ack = Some(tx);
+			}
+
 			StreamMessage::Commands(WorkerMessage::StealRequest {
 				stealer_id,
 				ack,
@@ -123,5 +145,12 @@ pub(super) async fn run<E: RunError>(
 			// Idle checking to steal some work
 			StreamMessage::IdleCheck => runner.idle_check(),
 		}
+
+		// We can only leave after processing every message sent before we closed our channel
+		if runner.has_retired() && msgs_rx_closer.is_empty() {
+			if let Some(tx) = retire_ack.take() {
+				return runner.finish_retirement(tx).await;
+			}
+		}
 	}
 }
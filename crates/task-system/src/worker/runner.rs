@@ -26,10 +26,11 @@ use super::{
 	super::{
 		error::{RunError, SystemError},
 		message::{StoleTaskMessage, TaskOutputMessage},
+		stats::{QueuedTasksStats, RunningTaskStats, SuspendedTaskStats, WorkerStats},
 		system::SystemComm,
 		task::{
 			ExecStatus, InternalTaskExecStatus, Interrupter, PanicOnSenderDrop, PendingTaskKind,
-			Task, TaskId, TaskOutput, TaskStatus, TaskWorkState, TaskWorktable,
+			Task, TaskId, TaskOutput, TaskPriority, TaskStatus, TaskWorkState, TaskWorktable,
 		},
 	},
 	TaskRunnerOutput, WorkStealer, WorkerId, ONE_SECOND,
@@ -40,6 +41,7 @@ const ONE_MINUTE: Duration = Duration::from_secs(60);
 
 const TASK_QUEUE_INITIAL_SIZE: usize = 64;
 const PRIORITY_TASK_QUEUE_INITIAL_SIZE: usize = 32;
+const BACKGROUND_TASK_QUEUE_INITIAL_SIZE: usize = 32;
 const ABORT_AND_SUSPEND_MAP_INITIAL_SIZE: usize = 8;
 
 pub(super) enum TaskAddStatus {
@@ -55,6 +57,8 @@ struct AbortAndSuspendSignalers {
 struct RunningTask {
 	id: TaskId,
 	kind: PendingTaskKind,
+	priority: TaskPriority,
+	started_at: Instant,
 	handle: JoinHandle<Result<(), Box<dyn Any + Send>>>,
 }
 
@@ -69,16 +73,120 @@ impl WaitingSuspendedTask {
 	}
 }
 
+/// Pending tasks of each [`TaskPriority`] class, dispatched with a weighted round robin between them
+struct TaskQueues<E: RunError> {
+	interactive: VecDeque<TaskWorkState<E>>,
+	normal: VecDeque<TaskWorkState<E>>,
+	background: VecDeque<TaskWorkState<E>>,
+	/// Remaining turns of each class on the current round, in the same order of [`TaskPriority::ALL`]
+	turns: [u32; 3],
+}
+
+impl<E: RunError> TaskQueues<E> {
+	fn new() -> Self {
+		Self {
+			interactive: VecDeque::with_capacity(PRIORITY_TASK_QUEUE_INITIAL_SIZE),
+			normal: VecDeque::with_capacity(TASK_QUEUE_INITIAL_SIZE),
+			background: VecDeque::with_capacity(BACKGROUND_TASK_QUEUE_INITIAL_SIZE),
+			turns: TaskPriority::ALL.map(TaskPriority::weight),
+		}
+	}
+
+	fn queue_mut(&mut self, priority: TaskPriority) -> &mut VecDeque<TaskWorkState<E>> {
+		match priority {
+			TaskPriority::Interactive => &mut self.interactive,
+			TaskPriority::Normal => &mut self.normal,
+			TaskPriority::Background => &mut self.background,
+		}
+	}
+
+	fn len(&self) -> usize {
+		self.interactive.len() + self.normal.len() + self.background.len()
+	}
+
+	fn push(&mut self, priority: TaskPriority, task_work_state: TaskWorkState<E>) {
+		if priority == TaskPriority::Interactive {
+			// The latest interactive task is the one the user is most likely waiting for
+			self.interactive.push_front(task_work_state);
+		} else {
+			self.queue_mut(priority).push_back(task_work_state);
+		}
+	}
+
+	fn remove(&mut self, task_id: TaskId) -> Option<TaskWorkState<E>> {
+		TaskPriority::ALL.into_iter().find_map(|priority| {
+			let queue = self.queue_mut(priority);
+
+			queue
+				.iter()
+				.position(|task_work_state| task_work_state.id() == task_id)
+				.and_then(|index| queue.remove(index))
+		})
+	}
+
+	/// Chooses the class of the next task to run, `extra` is a class with a pending task outside
+	/// the queues, like a suspended task
+	fn next_priority(&mut self, extra: Option<TaskPriority>) -> Option<TaskPriority> {
+		let has_tasks = TaskPriority::ALL.map(|priority| {
+			extra == Some(priority)
+				|| !match priority {
+					TaskPriority::Interactive => &self.interactive,
+					TaskPriority::Normal => &self.normal,
+					TaskPriority::Background => &self.background,
+				}
+				.is_empty()
+		});
+
+		if !has_tasks.contains(&true) {
+			return None;
+		}
+
+		// A new round starts when every class with pending tasks used up its turns
+		if !has_tasks
+			.iter()
+			.zip(self.turns)
+			.any(|(has_tasks, turns)| *has_tasks && turns > 0)
+		{
+			self.turns = TaskPriority::ALL.map(TaskPriority::weight);
+		}
+
+		TaskPriority::ALL
+			.into_iter()
+			.zip(has_tasks)
+			.zip(&mut self.turns)
+			.find_map(|((priority, has_tasks), turns)| {
+				(has_tasks && *turns > 0).then(|| {
+					*turns -= 1;
+					priority
+				})
+			})
+	}
+
+	fn shrink(&mut self) {
+		for (queue, initial_size) in [
+			(&mut self.interactive, PRIORITY_TASK_QUEUE_INITIAL_SIZE),
+			(&mut self.normal, TASK_QUEUE_INITIAL_SIZE),
+			(&mut self.background, BACKGROUND_TASK_QUEUE_INITIAL_SIZE),
+		] {
+			if queue.capacity() > initial_size {
+				assert_eq!(queue.len(), 0);
+				queue.shrink_to(initial_size);
+			}
+		}
+	}
+}
+
 pub(super) struct Runner<E: RunError> {
 	worker_id: WorkerId,
 	system_comm: SystemComm,
 	work_stealer: WorkStealer<E>,
 	task_kinds: HashMap<TaskId, PendingTaskKind>,
-	tasks: VecDeque<TaskWorkState<E>>,
+	queues: TaskQueues<E>,
 	paused_tasks: HashMap<TaskId, TaskWorkState<E>>,
 	suspended_task: Option<TaskWorkState<E>>,
-	priority_tasks: VecDeque<TaskWorkState<E>>,
+	suspended_at: Instant,
 	is_idle: bool,
+	is_retiring: bool,
 	waiting_suspension: WaitingSuspendedTask,
 	abort_and_suspend_map: HashMap<TaskId, AbortAndSuspendSignalers>,
 	stole_task_tx: chan::Sender<Option<StoleTaskMessage<E>>>,
@@ -89,6 +197,8 @@ pub(super) struct Runner<E: RunError> {
 	current_steal_task_handle: Option<JoinHandle<()>>,
 	last_steal_attempt_at: Instant,
 	steal_attempts_count: u32,
+	stolen_tasks_count: u64,
+	tasks_stolen_by_others_count: u64,
 }
 
 type RunnerCreate<E> = (
@@ -112,11 +222,12 @@ impl<E: RunError> Runner<E> {
 				system_comm,
 				work_stealer,
 				task_kinds: HashMap::with_capacity(TASK_QUEUE_INITIAL_SIZE),
-				tasks: VecDeque::with_capacity(TASK_QUEUE_INITIAL_SIZE),
+				queues: TaskQueues::new(),
 				paused_tasks: HashMap::new(),
 				suspended_task: None,
-				priority_tasks: VecDeque::with_capacity(PRIORITY_TASK_QUEUE_INITIAL_SIZE),
+				suspended_at: Instant::now(),
 				is_idle: true,
+				is_retiring: false,
 				waiting_suspension: WaitingSuspendedTask::None,
 				abort_and_suspend_map: HashMap::with_capacity(ABORT_AND_SUSPEND_MAP_INITIAL_SIZE),
 				stole_task_tx: stolen_task_tx,
@@ -127,6 +238,8 @@ impl<E: RunError> Runner<E> {
 				current_steal_task_handle: None,
 				last_steal_attempt_at: Instant::now(),
 				steal_attempts_count: 0,
+				stolen_tasks_count: 0,
+				tasks_stolen_by_others_count: 0,
 			},
 			stolen_task_rx,
 			task_output_rx,
@@ -135,16 +248,13 @@ impl<E: RunError> Runner<E> {
 
 	#[instrument(skip(self))]
 	pub(super) fn total_tasks(&self) -> usize {
-		let priority_tasks_count = self.priority_tasks.len();
 		let current_task_count = usize::from(self.current_task_handle.is_some());
 		let suspended_task_count = usize::from(self.suspended_task.is_some());
-		let tasks_count = self.tasks.len();
+		let tasks_count = self.queues.len();
 
-		trace!(%priority_tasks_count, %current_task_count, %suspended_task_count, %tasks_count,
-			"Tasks count"
-		);
+		trace!(%current_task_count, %suspended_task_count, %tasks_count, "Tasks count");
 
-		priority_tasks_count + current_task_count + suspended_task_count + tasks_count
+		current_task_count + suspended_task_count + tasks_count
 	}
 
 	#[instrument(skip(self, task_work_state))]
@@ -182,8 +292,43 @@ impl<E: RunError> Runner<E> {
 		handle
 	}
 
+	pub(super) fn stats(&self) -> WorkerStats {
+		WorkerStats {
+			worker_id: self.worker_id,
+			is_idle: self.is_idle,
+			is_retiring: self.is_retiring,
+			queued_tasks: QueuedTasksStats {
+				interactive: self.queues.interactive.len(),
+				normal: self.queues.normal.len(),
+				background: self.queues.background.len(),
+			},
+			paused_tasks_count: self.paused_tasks.len(),
+			running_task: self.current_task_handle.as_ref().map(
+				|RunningTask {
+				     id,
+				     priority,
+				     started_at,
+				     ..
+				 }| RunningTaskStats {
+					task_id: *id,
+					priority: *priority,
+					elapsed: started_at.elapsed(),
+				},
+			),
+			suspended_task: self.suspended_task.as_ref().map(|task_work_state| {
+				SuspendedTaskStats {
+					task_id: task_work_state.id(),
+					priority: task_work_state.task.priority(),
+					suspended_for: self.suspended_at.elapsed(),
+				}
+			}),
+			stolen_tasks_count: self.stolen_tasks_count,
+			tasks_stolen_by_others_count: self.tasks_stolen_by_others_count,
+		}
+	}
+
 	#[instrument(skip(self, task_work_state))]
-	pub(super) fn new_task(
+	pub(super) async fn new_task(
 		&mut self,
 		task_id: TaskId,
 		task_kind: PendingTaskKind,
@@ -191,6 +336,11 @@ impl<E: RunError> Runner<E> {
 	) {
 		trace!("Received new task");
 
+		if self.is_retiring {
+			trace!("Worker is retiring, will hand the new task over");
+			return self.hand_over(task_work_state).await;
+		}
+
 		self.task_kinds.insert(task_id, task_kind);
 
 		match self.inner_add_task(task_id, task_kind, task_work_state) {
@@ -272,30 +422,8 @@ impl<E: RunError> Runner<E> {
 
 	#[instrument(skip(self))]
 	fn pause_task_from_queues(&mut self, task_id: TaskId) -> bool {
-		if let Some(index) = self
-			.priority_tasks
-			.iter()
-			.position(|task_work_state| task_work_state.id() == task_id)
-		{
-			self.paused_tasks.insert(
-				task_id,
-				self.priority_tasks
-					.remove(index)
-					.expect("we just checked it"),
-			);
-
-			return true;
-		}
-
-		if let Some(index) = self
-			.tasks
-			.iter()
-			.position(|task_work_state| task_work_state.id() == task_id)
-		{
-			self.paused_tasks.insert(
-				task_id,
-				self.tasks.remove(index).expect("we just checked it"),
-			);
+		if let Some(task_work_state) = self.queues.remove(task_id) {
+			self.paused_tasks.insert(task_id, task_work_state);
 
 			return true;
 		}
@@ -342,26 +470,8 @@ impl<E: RunError> Runner<E> {
 	#[instrument(skip(self))]
 	#[inline]
 	fn cancel_task_from_queues(&mut self, task_id: &TaskId) -> bool {
-		if let Some(index) = self
-			.priority_tasks
-			.iter()
-			.position(|task_work_state| task_work_state.id() == *task_id)
-		{
-			send_cancel_task_response(
-				self.priority_tasks
-					.remove(index)
-					.expect("we just checked it"),
-			);
-
-			return true;
-		}
-
-		if let Some(index) = self
-			.tasks
-			.iter()
-			.position(|task_work_state| task_work_state.id() == *task_id)
-		{
-			send_cancel_task_response(self.tasks.remove(index).expect("we just checked it"));
+		if let Some(task_work_state) = self.queues.remove(*task_id) {
+			send_cancel_task_response(task_work_state);
 
 			return true;
 		}
@@ -384,11 +494,14 @@ impl<E: RunError> Runner<E> {
 		task_work_state: TaskWorkState<E>,
 	) {
 		trace!("Idle worker will process the new task");
+		let priority = task_work_state.task.priority();
 		let handle = self.spawn_task_runner(task_id, task_work_state);
 
 		self.current_task_handle = Some(RunningTask {
 			id: task_id,
 			kind: task_kind,
+			priority,
+			started_at: Instant::now(),
 			handle,
 		});
 
@@ -406,49 +519,51 @@ impl<E: RunError> Runner<E> {
 		old_task_id: TaskId,
 		old_kind: PendingTaskKind,
 	) -> TaskAddStatus {
-		match (new_kind, old_kind) {
-			(PendingTaskKind::Priority, PendingTaskKind::Priority) => {
-				trace!("Old and new tasks have priority, will put new task on priority queue");
-				self.priority_tasks.push_front(task_work_state);
-				TaskAddStatus::Enqueued
-			}
-			(PendingTaskKind::Priority, PendingTaskKind::Normal) => {
-				if self.waiting_suspension.is_waiting() {
-					trace!(
-						"Worker is already waiting for a task to be suspended, will enqueue new task"
-					);
-					self.priority_tasks.push_front(task_work_state);
-				} else {
-					trace!("Old task will be suspended");
-					// We put the query at the top of the priority queue, so it will be
-					// dispatched by the run function as soon as the current task is suspended
-					self.priority_tasks.push_front(task_work_state);
-
-					if self
-						.abort_and_suspend_map
-						.remove(&old_task_id)
-						.expect("we always store the abort and suspend signalers")
-						.suspend_tx
-						.send(())
-						.is_err()
-					{
-						warn!(%old_task_id,
-							"Suspend channel closed before receiving suspend signal. \
-							This probably happened because the task finished before we could suspend it."
-						);
-					}
+		let new_priority = match new_kind {
+			PendingTaskKind::Queued(priority) => priority,
+			// A suspended task that was paused afterwards goes back to its own class queue
+			PendingTaskKind::Suspended => task_work_state.task.priority(),
+		};
 
-					self.waiting_suspension = WaitingSuspendedTask::Task(old_task_id);
+		if new_priority == TaskPriority::Interactive
+			&& matches!(
+				old_kind,
+				PendingTaskKind::Queued(TaskPriority::Normal | TaskPriority::Background)
+			) {
+			// We put the query at the top of the interactive queue, so it will be
+			// dispatched by the run function as soon as the current task is suspended
+			self.queues.push(new_priority, task_work_state);
+
+			if self.waiting_suspension.is_waiting() {
+				trace!(
+					"Worker is already waiting for a task to be suspended, new task was enqueued"
+				);
+			} else {
+				trace!("Old task will be suspended");
+
+				if self
+					.abort_and_suspend_map
+					.remove(&old_task_id)
+					.expect("we always store the abort and suspend signalers")
+					.suspend_tx
+					.send(())
+					.is_err()
+				{
+					warn!(%old_task_id,
+						"Suspend channel closed before receiving suspend signal. \
+						This probably happened because the task finished before we could suspend it."
+					);
 				}
 
-				TaskAddStatus::Running
+				self.waiting_suspension = WaitingSuspendedTask::Task(old_task_id);
 			}
-			(_, _) => {
-				trace!("New task doesn't have priority and will be enqueued");
-				self.tasks.push_back(task_work_state);
 
-				TaskAddStatus::Enqueued
-			}
+			TaskAddStatus::Running
+		} else {
+			trace!(?new_priority, "New task will be enqueued");
+			self.queues.push(new_priority, task_work_state);
+
+			TaskAddStatus::Enqueued
 		}
 	}
 
@@ -532,28 +647,8 @@ impl<E: RunError> Runner<E> {
 				}
 			}
 
-			if let Some(index) = self
-				.priority_tasks
-				.iter()
-				.position(|task_work_state| task_work_state.id() == *task_id)
-			{
-				send_forced_abortion_task_response(
-					self.priority_tasks
-						.remove(index)
-						.expect("we just checked it"),
-				);
-
-				return Ok(());
-			}
-
-			if let Some(index) = self
-				.tasks
-				.iter()
-				.position(|task_work_state| task_work_state.id() == *task_id)
-			{
-				send_forced_abortion_task_response(
-					self.tasks.remove(index).expect("we just checked it"),
-				);
+			if let Some(task_work_state) = self.queues.remove(*task_id) {
+				send_forced_abortion_task_response(task_work_state);
 
 				return Ok(());
 			}
@@ -573,10 +668,15 @@ impl<E: RunError> Runner<E> {
 		self.abort_steal_task();
 
 		let Self {
-			tasks,
+			queues:
+				TaskQueues {
+					interactive: interactive_tasks,
+					normal: normal_tasks,
+					background: background_tasks,
+					..
+				},
 			suspended_task,
 			paused_tasks,
-			priority_tasks,
 			is_idle,
 			abort_and_suspend_map,
 			stole_task_tx: stolen_task_tx,
@@ -594,12 +694,12 @@ impl<E: RunError> Runner<E> {
 				"can't shutdown with a running task if we're idle"
 			);
 			assert!(
-				tasks.is_empty(),
+				normal_tasks.is_empty() && background_tasks.is_empty(),
 				"can't shutdown with pending tasks if we're idle"
 			);
 			assert!(
-				priority_tasks.is_empty(),
-				"can't shutdown with priority tasks if we're idle"
+				interactive_tasks.is_empty(),
+				"can't shutdown with interactive tasks if we're idle"
 			);
 			assert!(
 				suspended_task.is_none(),
@@ -649,11 +749,12 @@ impl<E: RunError> Runner<E> {
 				.await;
 			}
 
-			priority_tasks
+			interactive_tasks
 				.into_iter()
 				.chain(suspended_task.into_iter())
 				.chain(paused_tasks.into_values())
-				.chain(tasks.into_iter())
+				.chain(normal_tasks.into_iter())
+				.chain(background_tasks.into_iter())
 				.for_each(send_shutdown_task_response);
 		}
 
@@ -722,18 +823,25 @@ impl<E: RunError> Runner<E> {
 	}
 
 	pub(super) fn get_next_task(&mut self) -> Option<(PendingTaskKind, TaskWorkState<E>)> {
-		if let Some(task) = self.priority_tasks.pop_front() {
-			return Some((PendingTaskKind::Priority, task));
-		}
-
-		if let Some(task) = self.suspended_task.take() {
-			task.worktable.set_unpause();
-			return Some((PendingTaskKind::Suspended, task));
+		let priority = self.queues.next_priority(
+			self.suspended_task
+				.as_ref()
+				.map(|task_work_state| task_work_state.task.priority()),
+		)?;
+
+		// The suspended task must run before any other non interactive task, as we
+		// only have room for a single suspended task
+		if priority != TaskPriority::Interactive {
+			if let Some(task) = self.suspended_task.take() {
+				task.worktable.set_unpause();
+				return Some((PendingTaskKind::Suspended, task));
+			}
 		}
 
-		self.tasks
+		self.queues
+			.queue_mut(priority)
 			.pop_front()
-			.map(|task| (PendingTaskKind::Normal, task))
+			.map(|task| (PendingTaskKind::Queued(priority), task))
 	}
 
 	#[instrument(skip_all)]
@@ -778,8 +886,9 @@ impl<E: RunError> Runner<E> {
 				warn!("Steal request channel closed before sending task");
 				task_work_state.worktable.change_worker(self.worker_id);
 				match kind {
-					PendingTaskKind::Normal => self.tasks.push_front(task_work_state),
-					PendingTaskKind::Priority => self.priority_tasks.push_front(task_work_state),
+					PendingTaskKind::Queued(priority) => {
+						self.queues.queue_mut(priority).push_front(task_work_state);
+					}
 					PendingTaskKind::Suspended => {
 						assert!(
 							self.suspended_task.is_none(),
@@ -794,6 +903,8 @@ impl<E: RunError> Runner<E> {
 				return false;
 			}
 
+			self.tasks_stolen_by_others_count += 1;
+
 			return true; // Successfully stole the task
 		}
 
@@ -827,16 +938,24 @@ impl<E: RunError> Runner<E> {
 			}
 		}
 
+		if self.is_retiring {
+			trace!("Worker is retiring, will hand the remaining tasks over");
+			return self.hand_over_pending_tasks().await;
+		}
+
 		if let Some((next_task_kind, task_work_state)) = self.get_next_task() {
 			let next_task_id = task_work_state.id();
 
 			trace!(%next_task_id, ?next_task_kind, "Dispatching next task");
 
+			let priority = task_work_state.task.priority();
 			let handle = self.spawn_task_runner(next_task_id, task_work_state);
 
 			self.current_task_handle = Some(RunningTask {
 				id: next_task_id,
 				kind: next_task_kind,
+				priority,
+				started_at: Instant::now(),
 				handle,
 			});
 		} else {
@@ -889,6 +1008,7 @@ impl<E: RunError> Runner<E> {
 					"tried to suspend a task when we already have a suspended task"
 				);
 				self.suspended_task = Some(task_work_state);
+				self.suspended_at = Instant::now();
 				trace!("Task suspended");
 
 				self.clean_suspended_task(task_id);
@@ -900,7 +1020,7 @@ impl<E: RunError> Runner<E> {
 
 	#[instrument(skip(self))]
 	pub(super) fn idle_check(&mut self) {
-		if self.is_idle {
+		if self.is_idle && !self.is_retiring {
 			if self.current_steal_task_handle.is_none() {
 				self.steal_attempt();
 			}
@@ -926,10 +1046,7 @@ impl<E: RunError> Runner<E> {
 
 	fn idle_memory_cleanup(&mut self) {
 		// As we're idle, let's check if we need to do some memory cleanup
-		if self.tasks.capacity() > TASK_QUEUE_INITIAL_SIZE {
-			assert_eq!(self.tasks.len(), 0);
-			self.tasks.shrink_to(TASK_QUEUE_INITIAL_SIZE);
-		}
+		self.queues.shrink();
 
 		if self.task_kinds.capacity() > TASK_QUEUE_INITIAL_SIZE {
 			assert_eq!(
@@ -940,12 +1057,6 @@ impl<E: RunError> Runner<E> {
 			self.task_kinds.shrink_to(TASK_QUEUE_INITIAL_SIZE);
 		}
 
-		if self.priority_tasks.capacity() > PRIORITY_TASK_QUEUE_INITIAL_SIZE {
-			assert_eq!(self.priority_tasks.len(), 0);
-			self.priority_tasks
-				.shrink_to(PRIORITY_TASK_QUEUE_INITIAL_SIZE);
-		}
-
 		if self.paused_tasks.capacity() != self.paused_tasks.len() {
 			self.paused_tasks.shrink_to_fit();
 		}
@@ -990,12 +1101,108 @@ impl<E: RunError> Runner<E> {
 			trace!(%stolen_task_id, "Stolen task");
 
 			self.steal_attempts_count = 0;
-			self.new_task(stolen_task_id, task_work_state.kind(), task_work_state);
+			self.stolen_tasks_count += 1;
+			self.new_task(stolen_task_id, task_work_state.kind(), task_work_state)
+				.await;
 		} else {
 			self.steal_attempts_count += 1;
 		}
 	}
 
+	#[instrument(skip(self, task_work_state), fields(task_id = %task_work_state.id()))]
+	pub(super) async fn adopt_task(&mut self, task_work_state: TaskWorkState<E>) {
+		let task_id = task_work_state.id();
+
+		if task_work_state.worktable.is_paused() {
+			trace!("Adopted a paused task");
+			self.task_kinds.insert(task_id, task_work_state.kind());
+			self.paused_tasks.insert(task_id, task_work_state);
+		} else {
+			trace!("Adopted a task");
+			if self.is_idle {
+				self.system_comm.working_report(self.worker_id);
+			}
+
+			self.new_task(task_id, task_work_state.kind(), task_work_state)
+				.await;
+		}
+	}
+
+	/// Begins the retirement of this worker, handing all pending tasks over to the other workers
+	/// and suspending the running one, so it can be handed over as well when we get its output
+	#[instrument(skip(self))]
+	pub(super) async fn retire(&mut self) {
+		trace!("Worker beginning retirement process");
+
+		self.is_retiring = true;
+		self.abort_steal_task();
+
+		self.hand_over_pending_tasks().await;
+
+		if let Some(RunningTask { id: task_id, .. }) = &self.current_task_handle {
+			if !self.waiting_suspension.is_waiting() {
+				if let Some(AbortAndSuspendSignalers { suspend_tx, .. }) =
+					self.abort_and_suspend_map.remove(task_id)
+				{
+					if suspend_tx.send(()).is_err() {
+						trace!(%task_id, "Running task finished before we could suspend it");
+					} else {
+						self.waiting_suspension = WaitingSuspendedTask::Task(*task_id);
+					}
+				}
+			}
+		}
+	}
+
+	/// A retiring worker is done when it doesn't have any task left
+	pub(super) const fn has_retired(&self) -> bool {
+		self.is_retiring && self.current_task_handle.is_none()
+	}
+
+	#[instrument(skip(self, tx))]
+	pub(super) async fn finish_retirement(mut self, tx: oneshot::Sender<()>) {
+		// Workers stealing from us must not send us any more tasks, and the ones already sent
+		// will be handed over
+		self.stole_task_tx.close();
+
+		while let Ok(maybe_stolen_task) = self.suspend_on_shutdown_stole_task_rx.try_recv() {
+			if let Some(StoleTaskMessage(task_work_state)) = maybe_stolen_task {
+				self.hand_over(task_work_state).await;
+			}
+		}
+
+		self.hand_over_pending_tasks().await;
+
+		trace!("Worker retirement process completed");
+
+		if tx.send(()).is_err() {
+			warn!("Retire request channel closed before sending ack");
+		}
+	}
+
+	async fn hand_over_pending_tasks(&mut self) {
+		while let Some((_, task_work_state)) = self.get_next_task() {
+			self.task_kinds.remove(&task_work_state.id());
+			self.hand_over(task_work_state).await;
+		}
+
+		for (task_id, task_work_state) in std::mem::take(&mut self.paused_tasks) {
+			self.task_kinds.remove(&task_id);
+			self.hand_over(task_work_state).await;
+		}
+	}
+
+	async fn hand_over(&self, task_work_state: TaskWorkState<E>) {
+		if let Err(task_work_state) = self.work_stealer.hand_over(task_work_state).await {
+			warn!(
+				task_id = %task_work_state.id(),
+				"No worker left to hand the task over, it will be shutdown"
+			);
+			task_work_state.worktable.change_worker(self.worker_id);
+			send_shutdown_task_response(task_work_state);
+		}
+	}
+
 	#[instrument(skip(self))]
 	pub(crate) fn clean_suspended_task(&mut self, task_id: &TaskId) {
 		match self.waiting_suspension {
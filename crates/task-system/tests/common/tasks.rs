@@ -5,6 +5,7 @@ use std::{
 
 use sd_task_system::{
 	ExecStatus, Interrupter, InterruptionKind, IntoAnyTaskOutput, Task, TaskId, TaskOutput,
+	TaskPriority,
 };
 
 use async_channel as chan;
@@ -339,3 +340,37 @@ impl Task<SampleError> for WaitSignalTask {
 		}
 	}
 }
+
+#[derive(Debug)]
+pub struct PriorityTask {
+	id: TaskId,
+	priority: TaskPriority,
+	ran_tx: chan::Sender<TaskPriority>,
+}
+
+impl PriorityTask {
+	pub fn new(priority: TaskPriority, ran_tx: chan::Sender<TaskPriority>) -> Self {
+		Self {
+			id: TaskId::new_v4(),
+			priority,
+			ran_tx,
+		}
+	}
+}
+
+#[async_trait]
+impl Task<SampleError> for PriorityTask {
+	fn id(&self) -> TaskId {
+		self.id
+	}
+
+	fn priority(&self) -> TaskPriority {
+		self.priority
+	}
+
+	async fn run(&mut self, _interrupter: &Interrupter) -> Result<ExecStatus, SampleError> {
+		self.ran_tx.send(self.priority).await.unwrap();
+
+		Ok(ExecStatus::Done(TaskOutput::Empty))
+	}
+}
@@ -1,6 +1,12 @@
-use sd_task_system::{TaskHandle, TaskOutput, TaskStatus, TaskSystem};
+use sd_task_system::{
+	TaskHandle, TaskOutput, TaskPriority, TaskStatus, TaskSystem, Throttle, ThrottleLimits,
+};
 
-use std::{collections::VecDeque, time::Duration};
+use std::{
+	collections::VecDeque,
+	num::{NonZeroU32, NonZeroUsize},
+	time::{Duration, Instant},
+};
 
 use futures_concurrency::future::Join;
 use rand::Rng;
@@ -14,7 +20,8 @@ mod common;
 use common::{
 	actors::SampleActor,
 	tasks::{
-		BogusTask, BrokenTask, NeverTask, PauseOnceTask, ReadyTask, SampleError, WaitSignalTask,
+		BogusTask, BrokenTask, NeverTask, PauseOnceTask, PriorityTask, ReadyTask, SampleError,
+		TimeTask, WaitSignalTask,
 	},
 };
 
@@ -303,3 +310,265 @@ async fn steal_test() {
 
 	system.shutdown().await;
 }
+
+#[tokio::test]
+#[traced_test]
+async fn resize_test() {
+	let system = TaskSystem::with_workers(NonZeroUsize::new(4).unwrap());
+
+	let (wait_tasks, signals_txs) = (0..4)
+		.map(|_| WaitSignalTask::new())
+		.unzip::<_, _, Vec<_>, Vec<_>>();
+
+	// All workers are busy and have some pending tasks
+	let wait_handles = system.dispatch_many(wait_tasks).await.unwrap();
+	let ready_handles = system
+		.dispatch_many((0..20).map(|_| ReadyTask::default()))
+		.await
+		.unwrap();
+
+	// Retired workers must hand all of their tasks over to the remaining one
+	system.resize(NonZeroUsize::MIN).await;
+	assert_eq!(system.workers_count(), 1);
+
+	for signal_tx in signals_txs {
+		signal_tx.send(()).await.unwrap();
+	}
+
+	wait_handles
+		.join()
+		.await
+		.into_iter()
+		.chain(ready_handles.join().await)
+		.for_each(|res| assert!(matches!(res, Ok(TaskStatus::Done(_)))));
+
+	system.resize(NonZeroUsize::new(3).unwrap()).await;
+	assert_eq!(system.workers_count(), 3);
+
+	system
+		.dispatch_many((0..30).map(|_| ReadyTask::default()))
+		.await
+		.unwrap()
+		.join()
+		.await
+		.into_iter()
+		.for_each(|res| assert!(matches!(res, Ok(TaskStatus::Done(_)))));
+
+	system.shutdown().await;
+}
+
+#[tokio::test]
+#[traced_test]
+async fn priority_classes_test() {
+	let system = TaskSystem::with_workers(NonZeroUsize::MIN);
+
+	let (ran_tx, ran_rx) = async_channel::unbounded();
+
+	// Keeping the only worker busy while we enqueue tasks of every class
+	let (wait_task, signal_tx) = WaitSignalTask::new();
+	let wait_handle = system.dispatch(wait_task).await.unwrap();
+
+	let handles = system
+		.dispatch_many(
+			(0..8)
+				.map(|_| PriorityTask::new(TaskPriority::Background, ran_tx.clone()))
+				.chain((0..8).map(|_| PriorityTask::new(TaskPriority::Normal, ran_tx.clone()))),
+		)
+		.await
+		.unwrap();
+
+	// An interactive task suspends the running one, so it must finish before we release it
+	let interactive_handle = system
+		.dispatch(PriorityTask::new(TaskPriority::Interactive, ran_tx))
+		.await
+		.unwrap();
+
+	assert!(matches!(
+		tokio::time::timeout(Duration::from_secs(1), interactive_handle)
+			.await
+			.expect("interactive task must not wait for the running one"),
+		Ok(TaskStatus::Done(_))
+	));
+
+	signal_tx.send(()).await.unwrap();
+
+	assert!(matches!(wait_handle.await, Ok(TaskStatus::Done(_))));
+
+	handles.join().await.into_iter().for_each(|res| {
+		assert!(matches!(res, Ok(TaskStatus::Done(_))));
+	});
+
+	let ran = (0..17)
+		.map(|_| ran_rx.try_recv().unwrap())
+		.collect::<Vec<_>>();
+
+	assert_eq!(ran[0], TaskPriority::Interactive);
+
+	// Background tasks must not wait for every normal task to finish
+	let first_background = ran
+		.iter()
+		.position(|priority| *priority == TaskPriority::Background)
+		.unwrap();
+	let last_normal = ran
+		.iter()
+		.rposition(|priority| *priority == TaskPriority::Normal)
+		.unwrap();
+
+	assert!(first_background < last_normal);
+
+	system.shutdown().await;
+}
+
+#[tokio::test]
+#[traced_test]
+async fn stats_test() {
+	let system = TaskSystem::with_workers(NonZeroUsize::MIN);
+
+	let (ran_tx, _ran_rx) = async_channel::unbounded();
+
+	let (wait_task, signal_tx) = WaitSignalTask::new();
+	let wait_handle = system.dispatch(wait_task).await.unwrap();
+	let wait_task_id = wait_handle.task_id();
+
+	let mut handles = system
+		.dispatch_many(
+			(0..3)
+				.map(|_| PriorityTask::new(TaskPriority::Background, ran_tx.clone()))
+				.chain((0..2).map(|_| PriorityTask::new(TaskPriority::Normal, ran_tx.clone()))),
+		)
+		.await
+		.unwrap();
+
+	let stats = system.stats().await;
+	assert_eq!(stats.workers.len(), 1);
+
+	let worker_stats = &stats.workers[0];
+	assert!(!worker_stats.is_idle);
+	assert_eq!(worker_stats.queued_tasks.background, 3);
+	assert_eq!(worker_stats.queued_tasks.normal, 2);
+	assert_eq!(worker_stats.queued_tasks.interactive, 0);
+	assert_eq!(
+		worker_stats
+			.running_task
+			.map(|running_task| running_task.task_id),
+		Some(wait_task_id)
+	);
+	assert!(worker_stats.suspended_task.is_none());
+
+	// An interactive task suspends the running one
+	let interactive_handle = system
+		.dispatch(TimeTask::new(Duration::from_millis(200), true))
+		.await
+		.unwrap();
+	let interactive_task_id = interactive_handle.task_id();
+
+	tokio::time::sleep(Duration::from_millis(50)).await;
+
+	let stats = system.stats().await;
+
+	let running_task = stats.workers[0].running_task.unwrap();
+	assert_eq!(running_task.task_id, interactive_task_id);
+	assert_eq!(running_task.priority, TaskPriority::Interactive);
+
+	let suspended_task = stats.workers[0].suspended_task.unwrap();
+	assert_eq!(suspended_task.task_id, wait_task_id);
+	assert_eq!(suspended_task.priority, TaskPriority::Normal);
+
+	signal_tx.send(()).await.unwrap();
+
+	handles.push(wait_handle);
+	handles.push(interactive_handle);
+	handles.join().await.into_iter().for_each(|res| {
+		assert!(matches!(res, Ok(TaskStatus::Done(_))));
+	});
+
+	system.shutdown().await;
+}
+
+#[tokio::test]
+#[traced_test]
+async fn throttle_concurrency_test() {
+	let system = TaskSystem::new();
+
+	let throttle = Throttle::new(ThrottleLimits {
+		max_concurrent_tasks: NonZeroUsize::new(2),
+		..Default::default()
+	});
+
+	let mut handles = Vec::with_capacity(8);
+
+	for _ in 0..8 {
+		let permit = throttle.acquire().await;
+
+		assert!(throttle.running() <= 2);
+
+		let handle = system
+			.dispatch(TimeTask::new(Duration::from_millis(50), false))
+			.await
+			.unwrap();
+
+		let controller = handle.remote_controller();
+		tokio::spawn(async move {
+			controller.wait_finalized().await;
+			drop(permit);
+		});
+
+		handles.push(handle);
+	}
+
+	handles.join().await.into_iter().for_each(|res| {
+		assert!(matches!(res, Ok(TaskStatus::Done(_))));
+	});
+
+	system.shutdown().await;
+}
+
+#[tokio::test]
+#[traced_test]
+async fn throttle_pause_test() {
+	let throttle = Throttle::new(ThrottleLimits {
+		paused: true,
+		..Default::default()
+	});
+
+	assert!(
+		tokio::time::timeout(Duration::from_millis(100), throttle.acquire())
+			.await
+			.is_err()
+	);
+
+	let waiting = tokio::spawn({
+		let throttle = throttle.clone();
+		async move {
+			let _permit = throttle.acquire().await;
+		}
+	});
+
+	throttle.set_limits(ThrottleLimits::default());
+
+	tokio::time::timeout(Duration::from_secs(1), waiting)
+		.await
+		.expect("unpausing must release the waiting acquire")
+		.unwrap();
+
+	assert_eq!(throttle.running(), 0);
+}
+
+#[tokio::test]
+#[traced_test]
+async fn throttle_rate_test() {
+	let throttle = Throttle::new(ThrottleLimits {
+		max_items_per_second: NonZeroU32::new(10),
+		..Default::default()
+	});
+
+	// Within the first second allowance
+	drop(throttle.acquire().await);
+	throttle.consume(15);
+
+	// Now we went 5 items over the limit, so we must wait half a second
+	let start = Instant::now();
+	drop(throttle.acquire().await);
+
+	assert!(start.elapsed() >= Duration::from_millis(400));
+}
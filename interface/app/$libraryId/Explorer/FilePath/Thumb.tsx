@@ -14,7 +14,13 @@ import {
 	useRef,
 	useState
 } from 'react';
-import { getItemFilePath, ObjectKindKey, useLibraryContext, type ExplorerItem } from '@sd/client';
+import {
+	getItemFilePath,
+	ObjectKindKey,
+	useLibraryContext,
+	type ExplorerItem,
+	type ThumbSize
+} from '@sd/client';
 import { pdfViewerEnabled } from '~/util/pdfViewer';
 import { usePlatform } from '~/util/Platform';
 
@@ -242,6 +248,8 @@ export interface FileThumbProps {
 	data: ExplorerItem;
 	loadOriginal?: boolean;
 	size?: number;
+	/** Size of the thumbnail to request, only the default one is generated ahead of time */
+	thumbSize?: ThumbSize;
 	cover?: boolean;
 	frame?: boolean;
 	onLoad?: (type: ThumbType) => void;
@@ -272,7 +280,7 @@ export const FileThumb = memo(
 	forwardRef<HTMLImageElement, FileThumbProps>((props, ref) => {
 		const frame = useFrame();
 		const platform = usePlatform();
-		const itemData = useExplorerItemData(props.data);
+		const itemData = useExplorerItemData(props.data, props.thumbSize);
 		const filePath = getItemFilePath(props.data);
 		const { library } = useLibraryContext();
 		const [loadState, setLoadState] = useState<LoadState>({
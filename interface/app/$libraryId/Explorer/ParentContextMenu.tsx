@@ -33,7 +33,7 @@ export default (props: PropsWithChildren) => {
 	const { parent } = useExplorerContext();
 
 	const generateThumbsForLocation = useLibraryMutation('jobs.generateThumbsForLocation');
-	// const generateLabelsForLocation = useLibraryMutation('jobs.generateLabelsForLocation');
+	const generateLabelsForLocation = useLibraryMutation('jobs.generateLabelsForLocation');
 	const objectValidator = useLibraryMutation('jobs.objectValidator');
 	const rescanLocation = useLibraryMutation('locations.subPathRescan');
 	const createFolder = useLibraryMutation(['files.createFolder'], {
@@ -253,7 +253,7 @@ export default (props: PropsWithChildren) => {
 							icon={Image}
 						/>
 
-						{/* <CM.Item
+						<CM.Item
 							onClick={async () => {
 								try {
 									await generateLabelsForLocation.mutateAsync({
@@ -270,7 +270,7 @@ export default (props: PropsWithChildren) => {
 							}}
 							label={t('regen_labels')}
 							icon={Hash}
-						/> */}
+						/>
 
 						<CM.Item
 							onClick={async () => {
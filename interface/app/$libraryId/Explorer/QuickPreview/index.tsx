@@ -624,6 +624,7 @@ export const QuickPreview = () => {
 										});
 									}}
 									loadOriginal
+									thumbSize="Large"
 									frameClassName="!border-0"
 									mediaControls
 									className={clsx(
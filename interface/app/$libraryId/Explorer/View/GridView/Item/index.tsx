@@ -8,7 +8,8 @@ import {
 	useExplorerLayoutStore,
 	useLibraryQuery,
 	useSelector,
-	type ExplorerItem
+	type ExplorerItem,
+	type ThumbSize
 } from '@sd/client';
 import { useLocale } from '~/hooks';
 
@@ -27,6 +28,7 @@ export interface GridViewItemProps {
 	data: ExplorerItem;
 	selected: boolean;
 	cut: boolean;
+	thumbSize: ThumbSize;
 }
 
 export const GridViewItem = memo((props: GridViewItemProps) => {
@@ -82,6 +84,7 @@ const ItemFileThumb = memo((props: GridViewItemProps) => {
 	return (
 		<FileThumb
 			data={props.data}
+			thumbSize={props.thumbSize}
 			frame={!isLabel}
 			cover={isLabel}
 			blackBars
@@ -1,9 +1,9 @@
 import { Grid, useGrid } from '@virtual-grid/react';
-import { useCallback } from 'react';
+import { useCallback, useMemo } from 'react';
 import { useExplorerLayoutStore } from '@sd/client';
 
 import { useExplorerContext } from '../../Context';
-import { getItemData, getItemId, uniqueId } from '../../util';
+import { getItemData, getItemId, thumbSizeForItem, uniqueId } from '../../util';
 import { useExplorerViewContext } from '../Context';
 import { DragSelect } from '../Grid/DragSelect';
 import { GridItem } from '../Grid/Item';
@@ -22,6 +22,11 @@ export const GridView = () => {
 		(layoutStore.showTags ? 60 : 44) + (explorerSettings.showBytesInGridView ? 20 : 0);
 	const itemHeight = explorerSettings.gridItemSize + itemDetailsHeight;
 
+	const thumbSize = useMemo(
+		() => thumbSizeForItem(explorerSettings.gridItemSize),
+		[explorerSettings.gridItemSize]
+	);
+
 	const BOTTOM_PADDING = layoutStore.showTags ? 16 : 12;
 	const grid = useGrid({
 		scrollRef: explorer.scrollRef,
@@ -72,7 +77,12 @@ export const GridView = () => {
 							style={{ width: grid.itemWidth }}
 						>
 							{({ selected, cut }) => (
-								<GridViewItem data={item} selected={selected} cut={cut} />
+								<GridViewItem
+									data={item}
+									selected={selected}
+									cut={cut}
+									thumbSize={thumbSize}
+								/>
 							)}
 						</GridItem>
 					);
@@ -93,6 +93,8 @@ export const RenamableItemText = ({
 							generate_preview_media: null,
 							sync_preview_media: null,
 							hidden: null,
+							symlink_policy: null,
+							indexed_xattrs: null,
 							indexer_rules_ids: []
 						});
 
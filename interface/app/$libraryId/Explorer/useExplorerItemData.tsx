@@ -5,7 +5,8 @@ import {
 	getExplorerItemData,
 	humanizeSize,
 	ThumbKey,
-	type ExplorerItem
+	type ExplorerItem,
+	type ThumbSize
 } from '@sd/client';
 import { usePlatform } from '~/util/Platform';
 
@@ -19,9 +20,10 @@ import { explorerStore, flattenThumbnailKey } from './store';
  * 	  Be careful with the performance of the code, make sure to always memoize any objects or functions to avoid unnecessary re-renders.
  *
  * @param explorerItem - The explorer item to get data from
+ * @param thumbSize - Size of the thumbnails to request, the default one when omitted
  * @returns The extracted data from the explorer item
  */
-export function useExplorerItemData(explorerItem: ExplorerItem) {
+export function useExplorerItemData(explorerItem: ExplorerItem, thumbSize?: ThumbSize) {
 	const platform = usePlatform();
 	const cachedSize = useRef<ReturnType<typeof humanizeSize> | null>(null);
 	const [newThumbnails, setNewThumbnails] = useState<Map<string, string | null>>(new Map());
@@ -48,7 +50,9 @@ export function useExplorerItemData(explorerItem: ExplorerItem) {
 		const updateThumbnails = () =>
 			setNewThumbnails((oldThumbs) => {
 				const thumbs = thumbnailKeys.reduce<Map<string, string | null>>((acc, thumbKey) => {
-					const url = platform.getThumbnailUrlByThumbKey(thumbKey);
+					const url = platform.getThumbnailUrlByThumbKey(
+						thumbSize ? { ...thumbKey, size: thumbSize } : thumbKey
+					);
 					const thumbId = flattenThumbnailKey(thumbKey);
 					acc.set(url, explorerStore.newThumbnails.has(thumbId) ? thumbId : null);
 					return acc;
@@ -66,7 +70,7 @@ export function useExplorerItemData(explorerItem: ExplorerItem) {
 		updateThumbnails();
 
 		return subscribe(explorerStore, updateThumbnails);
-	}, [thumbnails, platform]);
+	}, [thumbnails, thumbSize, platform]);
 
 	return useMemo(() => {
 		const explorerItemData = getExplorerItemData(explorerItem);
@@ -1,5 +1,5 @@
 import dayjs from 'dayjs';
-import { type ExplorerItem } from '@sd/client';
+import { type ExplorerItem, type ThumbSize } from '@sd/client';
 import i18n from '~/app/I18n';
 import { ExplorerParamsSchema } from '~/app/route-schemas';
 import { useZodSearchParams } from '~/hooks';
@@ -27,6 +27,15 @@ export const uniqueId = (item: ExplorerItem | { pub_id: number[] }) => {
 	}
 };
 
+// Same as `ThumbSize::Small` in the core
+const SMALL_THUMB_PX = 256;
+
+/**
+ * The smallest thumbnail size that still looks sharp on grid items of `itemSize` pixels
+ */
+export const thumbSizeForItem = (itemSize: number): ThumbSize =>
+	itemSize * window.devicePixelRatio <= SMALL_THUMB_PX ? 'Small' : 'Medium';
+
 export function getItemId(index: number, items: ExplorerItem[]) {
 	const item = items[index];
 	return item ? uniqueId(item) : undefined;
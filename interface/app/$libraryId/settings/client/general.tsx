@@ -87,6 +87,7 @@ export const Component = () => {
 
 			if (value.background_processing_percentage != null) {
 				await updateThumbnailerPreferences.mutateAsync({
+					...node.data?.preferences.thumbnailer
 					// background_processing_percentage: value.background_processing_percentage
 				});
 			}
@@ -88,6 +88,8 @@ const EditLocationForm = () => {
 			path: data.path,
 			name: data.name,
 			hidden: data.hidden,
+			symlink_policy: null,
+			indexed_xattrs: null,
 			indexer_rules_ids: data.indexerRulesIds,
 			sync_preview_media: data.syncPreviewMedia,
 			generate_preview_media: data.generatePreviewMedia
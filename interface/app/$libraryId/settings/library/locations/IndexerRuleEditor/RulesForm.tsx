@@ -22,7 +22,15 @@ const ruleKinds: UnionToTuple<RuleKind> = [
 	'RejectFilesByGlob',
 	'AcceptIfChildrenDirectoriesArePresent',
 	'RejectIfChildrenDirectoriesArePresent',
-	'IgnoredByGit'
+	'IgnoredByGit',
+	'RejectFilesSmallerThan',
+	'RejectFilesLargerThan',
+	'RejectFilesModifiedBefore',
+	'RejectFilesModifiedAfter',
+	'RejectFilesByXattr',
+	'AcceptFilesByKind',
+	'RejectFilesByKind',
+	'IgnoredByIgnoreFiles'
 ];
 const ruleKindEnum = z.enum(ruleKinds);
 
@@ -242,7 +242,11 @@ function RemoteLayout() {
 							thumbKey.base_directory_str
 						)}/${encodeURIComponent(thumbKey.shard_hex)}/${encodeURIComponent(
 							thumbKey.cas_id
-						)}.webp`
+						)}.webp${
+							thumbKey.size && thumbKey.size !== 'Medium'
+								? `?size=${thumbKey.size.toLowerCase()}`
+								: ''
+						}`
 					),
 				getFileUrl: (libraryId, locationLocalId, filePathId) =>
 					platform.constructRemoteRspcPath(
@@ -13,6 +13,8 @@ export type Procedures = {
         { key: "ephemeralFiles.getMediaData", input: string, result: MediaData | null } | 
         { key: "files.get", input: LibraryArgs<number>, result: ObjectWithFilePaths2 | null } | 
         { key: "files.getConvertibleImageExtensions", input: never, result: string[] } | 
+        { key: "files.getDocumentPageCount", input: LibraryArgs<number>, result: number } | 
+        { key: "files.getHardLinks", input: LibraryArgs<number>, result: FilePathForFrontend[] } | 
         { key: "files.getMediaData", input: LibraryArgs<number>, result: MediaData } | 
         { key: "files.getPath", input: LibraryArgs<number>, result: string | null } | 
         { key: "invalidation.test-invalidate", input: never, result: number } | 
@@ -32,6 +34,7 @@ export type Procedures = {
         { key: "locations.indexer_rules.get", input: LibraryArgs<number>, result: IndexerRule } | 
         { key: "locations.indexer_rules.list", input: LibraryArgs<null>, result: IndexerRule[] } | 
         { key: "locations.indexer_rules.listForLocation", input: LibraryArgs<number>, result: IndexerRule[] } | 
+        { key: "locations.indexer_rules.preview", input: LibraryArgs<IndexerRulesPreviewArgs>, result: IndexerRulesPreview } | 
         { key: "locations.list", input: LibraryArgs<null>, result: Location[] } | 
         { key: "locations.systemLocations", input: never, result: SystemLocations } | 
         { key: "models.image_detection.list", input: never, result: string[] } | 
@@ -42,13 +45,17 @@ export type Procedures = {
         { key: "notifications.get", input: never, result: Notification[] } | 
         { key: "p2p.listeners", input: never, result: Listeners } | 
         { key: "p2p.state", input: never, result: JsonValue } | 
+        { key: "p2p.trustedPeers.list", input: never, result: { [key in RemoteIdentity]: TrustedPeer } } | 
         { key: "preferences.get", input: LibraryArgs<null>, result: LibraryPreferences } | 
+        { key: "search.geoClusters", input: LibraryArgs<{ zoom: number; bounds?: BoundingBox | null; filters?: SearchFilterArgs[] }>, result: GeoCluster[] } | 
         { key: "search.objects", input: LibraryArgs<ObjectSearchArgs>, result: SearchData<ExplorerItem> } | 
         { key: "search.objectsCount", input: LibraryArgs<{ filters?: SearchFilterArgs[] }>, result: number } | 
         { key: "search.paths", input: LibraryArgs<FilePathSearchArgs>, result: SearchData<ExplorerItem> } | 
         { key: "search.pathsCount", input: LibraryArgs<{ filters?: SearchFilterArgs[] }>, result: number } | 
         { key: "search.saved.get", input: LibraryArgs<number>, result: SavedSearch | null } | 
         { key: "search.saved.list", input: LibraryArgs<null>, result: SavedSearch[] } | 
+        { key: "search.similar", input: LibraryArgs<{ objectId: number; maxDistance?: number | null; take: number }>, result: SimilarObject[] } | 
+        { key: "search.similarGroups", input: LibraryArgs<{ maxDistance?: number | null; take: number; cursor?: number | null }>, result: SimilarGroups } | 
         { key: "sync.enabled", input: LibraryArgs<null>, result: boolean } | 
         { key: "sync.messages", input: LibraryArgs<null>, result: CRDTOperation[] } | 
         { key: "tags.get", input: LibraryArgs<number>, result: Tag | null } | 
@@ -92,6 +99,7 @@ export type Procedures = {
         { key: "jobs.cancel", input: LibraryArgs<string>, result: null } | 
         { key: "jobs.clear", input: LibraryArgs<string>, result: null } | 
         { key: "jobs.clearAll", input: LibraryArgs<null>, result: null } | 
+        { key: "jobs.generateLabelsForLocation", input: LibraryArgs<GenerateLabelsForLocationArgs>, result: string } | 
         { key: "jobs.generateThumbsForLocation", input: LibraryArgs<GenerateThumbsForLocationArgs>, result: string } | 
         { key: "jobs.identifyUniqueFiles", input: LibraryArgs<IdentifyUniqueFilesArgs>, result: string } | 
         { key: "jobs.objectValidator", input: LibraryArgs<ObjectValidatorArgs>, result: null } | 
@@ -114,11 +122,18 @@ export type Procedures = {
         { key: "locations.subPathRescan", input: LibraryArgs<RescanArgs>, result: string | null } | 
         { key: "locations.update", input: LibraryArgs<LocationUpdateArgs>, result: null } | 
         { key: "nodes.edit", input: ChangeNodeNameArgs, result: null } | 
-        { key: "nodes.updateThumbnailerPreferences", input: UpdateThumbnailerPreferences, result: null } | 
+        { key: "nodes.updateIndexerPreferences", input: IndexerPreferences, result: null } | 
+        { key: "nodes.updateThumbnailerPreferences", input: ThumbnailerPreferences, result: null } | 
         { key: "p2p.acceptSpacedrop", input: [string, string | null], result: null } | 
         { key: "p2p.cancelSpacedrop", input: string, result: null } | 
         { key: "p2p.debugConnect", input: RemoteIdentity, result: string } | 
+        { key: "p2p.generatePairingCode", input: never, result: GeneratedPairingCode } | 
+        { key: "p2p.pair", input: string, result: TrustedPeer } | 
+        { key: "p2p.prefetchRemoteThumbnails", input: PrefetchRemoteThumbnailsArgs, result: null } | 
         { key: "p2p.spacedrop", input: SpacedropArgs, result: string } | 
+        { key: "p2p.spacedropMany", input: SpacedropManyArgs, result: SpacedropStarted[] } | 
+        { key: "p2p.trustedPeers.rename", input: RenameTrustedPeerArgs, result: null } | 
+        { key: "p2p.trustedPeers.revoke", input: RemoteIdentity, result: null } | 
         { key: "preferences.update", input: LibraryArgs<LibraryPreferences>, result: null } | 
         { key: "search.saved.create", input: LibraryArgs<{ name: string; target?: SearchTarget; search?: string | null; filters?: string | null; description?: string | null; icon?: string | null }>, result: null } | 
         { key: "search.saved.delete", input: LibraryArgs<number>, result: null } | 
@@ -135,12 +150,14 @@ export type Procedures = {
         { key: "jobs.newFilePathIdentified", input: LibraryArgs<null>, result: number[] } | 
         { key: "jobs.newThumbnail", input: LibraryArgs<null>, result: ThumbKey } | 
         { key: "jobs.progress", input: LibraryArgs<null>, result: JobProgressEvent } | 
+        { key: "jobs.taskSystemStats", input: never, result: TaskSystemStats } | 
         { key: "library.actors", input: LibraryArgs<null>, result: { [key in string]: boolean } } | 
         { key: "library.updatedKindStatistic", input: LibraryArgs<null>, result: KindStatistic } | 
         { key: "locations.online", input: never, result: number[][] } | 
         { key: "locations.quickRescan", input: LibraryArgs<LightScanArgs>, result: null } | 
         { key: "notifications.listen", input: never, result: Notification } | 
         { key: "p2p.events", input: never, result: P2PEvent } | 
+        { key: "p2p.peerStats", input: never, result: PeerStats[] } | 
         { key: "search.ephemeralPaths", input: LibraryArgs<EphemeralPathSearchArgs>, result: { entries: ExplorerItem[]; errors: Error[] } } | 
         { key: "sync.active", input: LibraryArgs<null>, result: SyncStatus } | 
         { key: "sync.newMessage", input: LibraryArgs<null>, result: null }
@@ -148,7 +165,7 @@ export type Procedures = {
 
 export type Args = { search?: string | null; filters?: string | null; name?: string | null; icon?: string | null; description?: string | null }
 
-export type AudioProps = { delay: number; padding: number; sample_rate: number | null; sample_format: string | null; bit_per_sample: number | null; channel_layout: string | null }
+export type AudioProps = { delay: number; padding: number; sample_rate: number | null; sample_format: string | null; bit_per_sample: number | null; channel_layout: string | null; channels: number | null }
 
 /**
  * All of the feature flags provided by the core itself. The frontend has it's own set of feature flags!
@@ -159,6 +176,26 @@ export type BackendFeature = "cloudSync"
 
 export type Backup = ({ id: string; timestamp: string; library_id: string; library_name: string }) & { path: string }
 
+/**
+ * Rate limits for P2P transfers in bytes per second. `None` means unlimited.
+ */
+export type BandwidthLimit = { upload?: number | null; download?: number | null }
+
+export type BandwidthWindow = { 
+/**
+ * Minutes past local midnight at which the window starts
+ */
+start: number; 
+/**
+ * Minutes past local midnight at which the window ends. If this is before `start` the window wraps past midnight.
+ */
+end: number; limit: BandwidthLimit }
+
+/**
+ * An area of the map, crossing the antimeridian when `west` is greater than `east`
+ */
+export type BoundingBox = { north: number; south: number; east: number; west: number }
+
 export type BuildInfo = { version: string; commit: string }
 
 export type CRDTOperation = { instance: string; timestamp: number; model: number; record_id: JsonValue; data: CRDTOperationData }
@@ -169,7 +206,11 @@ export type CameraData = { device_make: string | null; device_model: string | nu
 
 export type CasId = string
 
-export type ChangeNodeNameArgs = { name: string | null; p2p_port: Port | null; p2p_disabled: boolean | null; p2p_ipv6_disabled: boolean | null; p2p_relay_disabled: boolean | null; p2p_discovery: P2PDiscoveryState | null; p2p_remote_access: boolean | null; p2p_manual_peers: string[] | null }
+export type ChangeNodeNameArgs = { name: string | null; p2p_port: Port | null; p2p_disabled: boolean | null; p2p_ipv6_disabled: boolean | null; p2p_relay_disabled: boolean | null; p2p_discovery: P2PDiscoveryState | null; p2p_remote_access: boolean | null; p2p_manual_peers: string[] | null; p2p_trusted_peers_only: boolean | null; p2p_bandwidth_limit: BandwidthLimit | null; p2p_bandwidth_schedule: BandwidthWindow[] | null; 
+/**
+ * An empty path goes back to the bundled dataset
+ */
+reverse_geocoder_dataset_path: string | null }
 
 export type Chapter = { id: number; start: [number, number]; end: [number, number]; time_base_den: number; time_base_num: number; metadata: Metadata }
 
@@ -260,9 +301,15 @@ export type Error = { code: ErrorCode; message: string }
  */
 export type ErrorCode = "BadRequest" | "Unauthorized" | "Forbidden" | "NotFound" | "Timeout" | "Conflict" | "PreconditionFailed" | "PayloadTooLarge" | "MethodNotSupported" | "ClientClosedRequest" | "InternalServerError"
 
-export type ExifDataOrder = { field: "epochTime"; value: SortOrder }
+export type ExifDataFilterArgs = { cameraMake: InOrNotIn<string> } | { cameraModel: InOrNotIn<string> } | { lens: InOrNotIn<string> } | { focalLength: Range<number> } | { iso: Range<number> } | { megapixels: Range<number> } | { orientation: InOrNotIn<Orientation> } | { hasLocation: boolean }
 
-export type ExifMetadata = { resolution: Resolution; date_taken: MediaDate | null; location: MediaLocation | null; camera_data: CameraData; artist: string | null; description: string | null; copyright: string | null; exif_version: string | null }
+export type ExifDataOrder = { field: "epochTime"; value: SortOrder } | { field: "cameraMake"; value: SortOrder } | { field: "cameraModel"; value: SortOrder } | { field: "lens"; value: SortOrder } | { field: "focalLength"; value: SortOrder } | { field: "iso"; value: SortOrder } | { field: "megapixels"; value: SortOrder }
+
+export type ExifMetadata = { resolution: Resolution; date_taken: MediaDate | null; location: MediaLocation | null; 
+/**
+ * Where [`Self::location`] is, resolved by a [`ReverseGeocoder`] as EXIF doesn't have it
+ */
+place: PlaceName | null; camera_data: CameraData; artist: string | null; description: string | null; copyright: string | null; exif_version: string | null }
 
 export type ExplorerItem = { type: "Path"; thumbnail: ThumbKey | null; has_created_thumbnail: boolean; item: FilePathForFrontend } | { type: "Object"; thumbnail: ThumbKey | null; has_created_thumbnail: boolean; item: ObjectWithFilePaths } | { type: "NonIndexedPath"; thumbnail: ThumbKey | null; has_created_thumbnail: boolean; item: NonIndexedPathItem } | { type: "Location"; item: Location } | { type: "SpacedropPeer"; item: PeerMetadata } | { type: "Label"; thumbnails: ThumbKey[]; item: LabelWithObjects }
 
@@ -270,11 +317,31 @@ export type ExplorerLayout = "grid" | "list" | "media"
 
 export type ExplorerSettings<TOrder> = { layoutMode: ExplorerLayout | null; gridItemSize: number | null; gridGap: number | null; mediaColumns: number | null; mediaAspectSquare: boolean | null; mediaViewWithDescendants: boolean | null; openOnDoubleClick: DoubleClickAction | null; showBytesInGridView: boolean | null; colVisibility: { [key in string]: boolean } | null; colSizes: { [key in string]: number } | null; listViewIconSize: string | null; listViewTextSize: string | null; order?: TOrder | null; showHiddenFiles?: boolean }
 
-export type FFmpegMetadata = { formats: string[]; duration: [number, number] | null; start_time: [number, number] | null; bit_rate: [number, number]; chapters: Chapter[]; programs: Program[]; metadata: Metadata }
+export type FFmpegMetadata = { formats: string[]; duration: [number, number] | null; start_time: [number, number] | null; bit_rate: [number, number]; chapters: Chapter[]; programs: Program[]; metadata: Metadata; 
+/**
+ * Only extracted for audio files, as video files have thumbnails
+ */
+waveform: Waveform | null }
 
 export type Feedback = { message: string; emoji: number }
 
-export type FfmpegMediaAudioProps = { id: number; delay: number; padding: number; sample_rate: number | null; sample_format: string | null; bit_per_sample: number | null; channel_layout: string | null; codec_id: number }
+export type FfmpegDataFilterArgs = 
+/**
+ * In seconds
+ */
+{ duration: Range<number> } | 
+/**
+ * Matches the codec of any stream, like `h264`, `prores` or `aac`
+ */
+{ codec: InOrNotIn<string> } | 
+/**
+ * In kilobits per second
+ */
+{ bitRate: Range<number> } | { width: Range<number> } | { height: Range<number> } | { frameRate: Range<number> } | { audioChannels: Range<number> } | { sampleRate: Range<number> } | { hasSubtitles: boolean }
+
+export type FfmpegDataOrder = { field: "duration"; value: SortOrder } | { field: "bitRate"; value: SortOrder } | { field: "width"; value: SortOrder } | { field: "height"; value: SortOrder } | { field: "frameRate"; value: SortOrder } | { field: "audioChannels"; value: SortOrder } | { field: "sampleRate"; value: SortOrder }
+
+export type FfmpegMediaAudioProps = { id: number; delay: number; padding: number; sample_rate: number | null; sample_format: string | null; bit_per_sample: number | null; channel_layout: string | null; channels: number | null; codec_id: number }
 
 export type FfmpegMediaChapter = { chapter_id: number; start: number[]; end: number[]; time_base_den: number; time_base_num: number; title: string | null; metadata: number[] | null; ffmpeg_data_id: number }
 
@@ -282,15 +349,24 @@ export type FfmpegMediaVideoProps = { id: number; pixel_format: string | null; c
 
 export type FileCreateContextTypes = "empty" | "text"
 
-export type FilePath = { id: number; pub_id: number[]; is_dir: boolean | null; cas_id: string | null; integrity_checksum: string | null; location_id: number | null; materialized_path: string | null; name: string | null; extension: string | null; hidden: boolean | null; size_in_bytes: string | null; size_in_bytes_bytes: number[] | null; inode: number[] | null; object_id: number | null; key_id: number | null; date_created: string | null; date_modified: string | null; date_indexed: string | null }
+export type FilePath = { id: number; pub_id: number[]; is_dir: boolean | null; cas_id: string | null; integrity_checksum: string | null; location_id: number | null; materialized_path: string | null; name: string | null; extension: string | null; hidden: boolean | null; size_in_bytes: string | null; size_in_bytes_bytes: number[] | null; inode: number[] | null; device: number[] | null; nlink: number | null; symlink_target: string | null; object_id: number | null; key_id: number | null; permissions: number | null; uid: number | null; gid: number | null; owner_name: string | null; group_name: string | null; xattrs: string | null; date_created: string | null; date_modified: string | null; date_indexed: string | null }
 
 export type FilePathCursor = { isDir: boolean; variant: FilePathCursorVariant }
 
 export type FilePathCursorVariant = "none" | { name: CursorOrderItem<string> } | { sizeInBytes: SortOrder } | { dateCreated: CursorOrderItem<string> } | { dateModified: CursorOrderItem<string> } | { dateIndexed: CursorOrderItem<string> } | { object: FilePathObjectCursor }
 
-export type FilePathFilterArgs = { locations: InOrNotIn<number> } | { path: { location_id: number; path: string; include_descendants: boolean } } | { name: TextMatch } | { extension: InOrNotIn<string> } | { createdAt: Range<string> } | { modifiedAt: Range<string> } | { indexedAt: Range<string> } | { hidden: boolean }
+export type FilePathFilterArgs = { locations: InOrNotIn<number> } | { path: { location_id: number; path: string; include_descendants: boolean } } | { name: TextMatch } | { extension: InOrNotIn<string> } | { createdAt: Range<string> } | { modifiedAt: Range<string> } | { indexedAt: Range<string> } | { hidden: boolean } | 
+/**
+ * POSIX permission bits, without the file type ones, like `0o644`
+ */
+{ permissions: InOrNotIn<number> } | { uid: InOrNotIn<number> } | { gid: InOrNotIn<number> } | { ownerName: InOrNotIn<string> } | { groupName: InOrNotIn<string> } | 
+/**
+ * File paths having the extended attribute with this name, only the ones selected in the
+ * location settings are stored
+ */
+{ hasXattr: string }
 
-export type FilePathForFrontend = { id: number; pub_id: number[]; is_dir: boolean | null; cas_id: string | null; integrity_checksum: string | null; location_id: number | null; materialized_path: string | null; name: string | null; extension: string | null; hidden: boolean | null; size_in_bytes: string | null; size_in_bytes_bytes: number[] | null; inode: number[] | null; object_id: number | null; object: { id: number; pub_id: number[]; kind: number | null; key_id: number | null; hidden: boolean | null; favorite: boolean | null; important: boolean | null; note: string | null; date_created: string | null; date_accessed: string | null; tags: ({ object_id: number; tag_id: number; tag: Tag; date_created: string | null })[]; exif_data: { resolution: number[] | null; media_date: number[] | null; media_location: number[] | null; camera_data: number[] | null; artist: string | null; description: string | null; copyright: string | null; exif_version: string | null } | null } | null; key_id: number | null; date_created: string | null; date_modified: string | null; date_indexed: string | null }
+export type FilePathForFrontend = { id: number; pub_id: number[]; is_dir: boolean | null; cas_id: string | null; integrity_checksum: string | null; location_id: number | null; materialized_path: string | null; name: string | null; extension: string | null; hidden: boolean | null; size_in_bytes: string | null; size_in_bytes_bytes: number[] | null; inode: number[] | null; device: number[] | null; nlink: number | null; symlink_target: string | null; object_id: number | null; object: { id: number; pub_id: number[]; kind: number | null; key_id: number | null; hidden: boolean | null; favorite: boolean | null; important: boolean | null; note: string | null; date_created: string | null; date_accessed: string | null; tags: ({ object_id: number; tag_id: number; tag: Tag; date_created: string | null })[]; exif_data: { resolution: number[] | null; media_date: number[] | null; media_location: number[] | null; camera_data: number[] | null; artist: string | null; description: string | null; copyright: string | null; exif_version: string | null } | null } | null; key_id: number | null; permissions: number | null; uid: number | null; gid: number | null; owner_name: string | null; group_name: string | null; xattrs: string | null; date_created: string | null; date_modified: string | null; date_indexed: string | null }
 
 export type FilePathObjectCursor = { dateAccessed: CursorOrderItem<string> } | { kind: CursorOrderItem<number> }
 
@@ -347,8 +423,39 @@ export type FromPattern = { pattern: string; replace_all: boolean }
 
 export type FullRescanArgs = { location_id: number; reidentify_objects: boolean }
 
+export type GenerateLabelsForLocationArgs = { id: number; path: string; regenerate?: boolean }
+
 export type GenerateThumbsForLocationArgs = { id: number; path: string; regenerate?: boolean }
 
+export type GeneratedPairingCode = { code: string; expires_in_secs: number }
+
+/**
+ * Media taken within a map tile, to show a single marker instead of every photo in it
+ */
+export type GeoCluster = { 
+/**
+ * Web Mercator tile coordinates at the requested zoom
+ */
+x: number; y: number; count: number; 
+/**
+ * Centroid of the media in the cluster, where its marker goes
+ */
+latitude: number; longitude: number; 
+/**
+ * An object from the cluster, to use its thumbnail on the marker
+ */
+object_id: number }
+
+export type GeoFilter = 
+/**
+ * Media taken within an area of the map
+ */
+{ boundingBox: BoundingBox } | 
+/**
+ * Media taken within `radius_km` kilometers of a point
+ */
+{ radius: { latitude: number; longitude: number; radius_km: number } }
+
 export type GetAll = { backups: Backup[]; directory: string }
 
 export type HardwareModel = "Other" | "MacStudio" | "MacBookAir" | "MacBookPro" | "MacBook" | "MacMini" | "MacPro" | "IMac" | "IMacPro" | "IPad" | "IPhone" | "Simulator" | "Android"
@@ -357,6 +464,32 @@ export type IdentifyUniqueFilesArgs = { id: number; path: string }
 
 export type InOrNotIn<T> = { in: T[] } | { notIn: T[] }
 
+/**
+ * `None` means unlimited.
+ */
+export type IndexerLimits = { 
+/**
+ * How many directories can be walked at the same time
+ */
+max_concurrent_walkers?: number | null; 
+/**
+ * How many directory entries can be walked per second
+ */
+max_entries_per_second?: number | null; run_mode?: IndexerRunMode }
+
+/**
+ * Limits for indexer jobs, so walking locations on slow disks doesn't starve everything else.
+ */
+export type IndexerPreferences = { 
+/**
+ * Limits shared by every location indexed by this node.
+ */
+node?: IndexerLimits; 
+/**
+ * Limits for specific locations by their `pub_id`, enforced on top of the node ones.
+ */
+locations?: { [key in string]: IndexerLimits } }
+
 export type IndexerRule = { id: number; pub_id: number[]; name: string | null; default: boolean | null; rules_per_kind: number[] | null; date_created: string | null; date_modified: string | null }
 
 /**
@@ -368,9 +501,47 @@ export type IndexerRule = { id: number; pub_id: number[]; name: string | null; d
  * 
  * In case of `RuleKind::AcceptIfChildrenDirectoriesArePresent` or `RuleKind::RejectIfChildrenDirectoriesArePresent` the
  * `parameters` field must be a vector of strings containing the names of the directories.
+ * 
+ * In case of `RuleKind::RejectFilesSmallerThan` or `RuleKind::RejectFilesLargerThan` the
+ * `parameters` field must contain a single integer, the size limit in bytes.
+ * 
+ * In case of `RuleKind::RejectFilesModifiedBefore` or `RuleKind::RejectFilesModifiedAfter` the
+ * `parameters` field must contain a single RFC 3339 date, like `2024-01-01T00:00:00Z`.
+ * 
+ * In case of `RuleKind::RejectFilesByXattr` the `parameters` field must be a vector of strings
+ * containing extended attribute names, like `com.dropbox.ignored` or `user.spacedrive.ignore`.
+ * 
+ * In case of `RuleKind::AcceptFilesByKind` or `RuleKind::RejectFilesByKind` the `parameters`
+ * field must be a vector of `ObjectKind` names, like `Image` or `Video`.
  */
 export type IndexerRuleCreateArgs = { name: string; dry_run: boolean; rules: ([RuleKind, string[]])[] }
 
+export type IndexerRulesPreview = { accepted: PreviewedEntry[]; rejected: PreviewedEntry[]; 
+/**
+ * The walk stopped early because it reached the maximum number of entries
+ */
+truncated: boolean; errors: NonCriticalError[] }
+
+export type IndexerRulesPreviewArgs = { path: string; 
+/**
+ * Already saved indexer rules to apply along with the candidate ones
+ */
+indexer_rules_ids: number[]; 
+/**
+ * Candidate rules, in the same format of `IndexerRuleCreateArgs`
+ */
+rules: ([RuleKind, string[]])[]; symlink_policy: SymlinkPolicy | null; max_depth: number | null; max_entries: number | null }
+
+export type IndexerRunMode = "Always" | 
+/**
+ * Indexing is paused while the machine runs on battery
+ */
+"PauseOnBattery" | 
+/**
+ * Indexing is paused while the machine is busy with other work
+ */
+"OnlyWhenIdle"
+
 export type InvalidateOperationEvent = { type: "single"; data: SingleInvalidateOperationEvent } | { type: "all" }
 
 export type JobGroup = { id: string; running_job_id: string | null; action: string | null; status: Status; created_at: string; jobs: Report[] }
@@ -416,7 +587,7 @@ instance_id: number;
  */
 cloud_id?: string | null; generate_sync_operations?: boolean; version: LibraryConfigVersion }
 
-export type LibraryConfigVersion = "V0" | "V1" | "V2" | "V3" | "V4" | "V5" | "V6" | "V7" | "V8" | "V9" | "V10" | "V11"
+export type LibraryConfigVersion = "V0" | "V1" | "V2" | "V3" | "V4" | "V5" | "V6" | "V7" | "V8" | "V9" | "V10" | "V11" | "V12"
 
 export type LibraryConfigWrapped = { uuid: string; instance_id: string; instance_public_key: RemoteIdentity; config: LibraryConfig }
 
@@ -430,7 +601,7 @@ export type ListenerState = { type: "Listening" } | { type: "Error"; error: stri
 
 export type Listeners = { ipv4: ListenerState; ipv6: ListenerState; relay: ListenerState }
 
-export type Location = { id: number; pub_id: number[]; name: string | null; path: string | null; total_capacity: number | null; available_capacity: number | null; size_in_bytes: number[] | null; is_archived: boolean | null; generate_preview_media: boolean | null; sync_preview_media: boolean | null; hidden: boolean | null; date_created: string | null; scan_state: number; instance_id: number | null }
+export type Location = { id: number; pub_id: number[]; name: string | null; path: string | null; total_capacity: number | null; available_capacity: number | null; size_in_bytes: number[] | null; is_archived: boolean | null; generate_preview_media: boolean | null; sync_preview_media: boolean | null; hidden: boolean | null; date_created: string | null; scan_state: number; symlink_policy: number | null; indexed_xattrs: string | null; instance_id: number | null }
 
 /**
  * `LocationCreateArgs` is the argument received from the client using `rspc` to create a new location.
@@ -449,7 +620,11 @@ export type LocationSettings = { explorer: ExplorerSettings<FilePathOrder> }
  * It is important to note that only the indexer rule ids in this vector will be used from now on.
  * Old rules that aren't in this vector will be purged.
  */
-export type LocationUpdateArgs = { id: number; name: string | null; generate_preview_media: boolean | null; sync_preview_media: boolean | null; hidden: boolean | null; indexer_rules_ids: number[]; path: string | null }
+export type LocationUpdateArgs = { id: number; name: string | null; generate_preview_media: boolean | null; sync_preview_media: boolean | null; hidden: boolean | null; symlink_policy: SymlinkPolicy | null; 
+/**
+ * Extended attributes to store for each file path, an empty list stops storing them
+ */
+indexed_xattrs: string[] | null; indexer_rules_ids: number[]; path: string | null }
 
 export type LocationWithIndexerRule = { id: number; pub_id: number[]; name: string | null; path: string | null; total_capacity: number | null; available_capacity: number | null; size_in_bytes: number[] | null; is_archived: boolean | null; generate_preview_media: boolean | null; sync_preview_media: boolean | null; hidden: boolean | null; date_created: string | null; instance_id: number | null; indexer_rules: IndexerRule[] }
 
@@ -478,9 +653,25 @@ export type NodeConfigP2P = { discovery?: P2PDiscoveryState; port: Port; disable
  * - `[::1]` or `[::1]:3000`
  * which is why we use `String` not `SocketAddr`
  */
-manual_peers?: string[] }
+manual_peers?: string[]; 
+/**
+ * Peers which have been explicitly paired with this node using a pairing code.
+ */
+trusted_peers?: { [key in RemoteIdentity]: TrustedPeer }; 
+/**
+ * When enabled, Spacedrop, library and sync requests are only accepted from `trusted_peers`.
+ */
+trusted_peers_only?: boolean; 
+/**
+ * The bandwidth limits for P2P transfers when no `bandwidth_schedule` window applies.
+ */
+bandwidth_limit?: BandwidthLimit; 
+/**
+ * Time of day windows with their own bandwidth limits. The first matching window wins.
+ */
+bandwidth_schedule?: BandwidthWindow[] }
 
-export type NodePreferences = Record<string, never>
+export type NodePreferences = { thumbnailer?: ThumbnailerPreferences; indexer?: IndexerPreferences }
 
 export type NodeState = ({ 
 /**
@@ -490,19 +681,19 @@ id: string;
 /**
  * name is the display name of the current node. This is set by the user and is shown in the UI. // TODO: Length validation so it can fit in DNS record
  */
-name: string; identity: RemoteIdentity; p2p: NodeConfigP2P; features: BackendFeature[]; preferences: NodePreferences; image_labeler_version: string | null }) & { data_path: string; device_model: string | null; is_in_docker: boolean }
+name: string; identity: RemoteIdentity; p2p: NodeConfigP2P; features: BackendFeature[]; preferences: NodePreferences; image_labeler_version: string | null; image_labeler_model_path: string | null; reverse_geocoder_dataset_path: string | null }) & { data_path: string; device_model: string | null; is_in_docker: boolean }
 
 export type NonCriticalError = { indexer: NonCriticalIndexerError } | { file_identifier: NonCriticalFileIdentifierError } | { media_processor: NonCriticalMediaProcessorError }
 
 export type NonCriticalFileIdentifierError = { failed_to_extract_file_metadata: string } | { failed_to_extract_isolated_file_path_data: { file_path_pub_id: string; error: string } } | { file_path_without_is_dir_field: number }
 
-export type NonCriticalIndexerError = { failed_directory_entry: string } | { metadata: string } | { indexer_rule: string } | { file_path_metadata: string } | { fetch_already_existing_file_path_ids: string } | { fetch_file_paths_to_remove: string } | { iso_file_path: string } | { dispatch_keep_walking: string } | { missing_file_path_data: string }
+export type NonCriticalIndexerError = { failed_directory_entry: string } | { metadata: string } | { indexer_rule: string } | { file_path_metadata: string } | { fetch_already_existing_file_path_ids: string } | { fetch_file_paths_to_remove: string } | { iso_file_path: string } | { dispatch_keep_walking: string } | { missing_file_path_data: string } | { directory_snapshots: string }
 
 export type NonCriticalMediaDataExtractorError = { FailedToExtractImageMediaData: [string, string] } | { FilePathMissingObjectId: number } | { FailedToConstructIsolatedFilePathData: [number, string] }
 
 export type NonCriticalMediaProcessorError = { media_data_extractor: NonCriticalMediaDataExtractorError } | { thumbnailer: NonCriticalThumbnailerError }
 
-export type NonCriticalThumbnailerError = { MissingCasId: number } | { FailedToExtractIsolatedFilePathData: [number, string] } | { VideoThumbnailGenerationFailed: [string, string] } | { FormatImage: [string, string] } | { WebPEncoding: [string, string] } | { PanicWhileGeneratingThumbnail: [string, string] } | { CreateShardDirectory: string } | { SaveThumbnail: [string, string] } | { TaskTimeout: string }
+export type NonCriticalThumbnailerError = { MissingCasId: number } | { FailedToExtractIsolatedFilePathData: [number, string] } | { VideoThumbnailGenerationFailed: [string, string] } | { VideoPreviewGenerationFailed: [string, VideoPreviewKind, string] } | { FormatImage: [string, string] } | { DocumentPageNotFound: [string, number] } | { WebPEncoding: [string, string] } | { ImageEncoding: [string, string] } | { PanicWhileGeneratingThumbnail: [string, string] } | { CreateShardDirectory: string } | { SaveThumbnail: [string, string] } | { TaskTimeout: string } | { SavePerceptualHashes: string }
 
 export type NonIndexedPathItem = { path: string; name: string; extension: string; kind: number; is_dir: boolean; date_created: string; date_modified: string; size_in_bytes_bytes: number[]; hidden: boolean }
 
@@ -525,19 +716,19 @@ export type Object = { id: number; pub_id: number[]; kind: number | null; key_id
 
 export type ObjectCursor = "none" | { dateAccessed: CursorOrderItem<string> } | { kind: CursorOrderItem<number> }
 
-export type ObjectFilterArgs = { favorite: boolean } | { hidden: ObjectHiddenFilter } | { kind: InOrNotIn<number> } | { tags: InOrNotIn<number> } | { labels: InOrNotIn<number> } | { dateAccessed: Range<string> }
+export type ObjectFilterArgs = { favorite: boolean } | { hidden: ObjectHiddenFilter } | { kind: InOrNotIn<number> } | { tags: InOrNotIn<number> } | { labels: InOrNotIn<number> } | { dateAccessed: Range<string> } | { geo: GeoFilter } | { mediaData: ExifDataFilterArgs } | { ffmpegData: FfmpegDataFilterArgs }
 
 export type ObjectHiddenFilter = "exclude" | "include"
 
-export type ObjectOrder = { field: "dateAccessed"; value: SortOrder } | { field: "kind"; value: SortOrder } | { field: "mediaData"; value: ExifDataOrder }
+export type ObjectOrder = { field: "dateAccessed"; value: SortOrder } | { field: "kind"; value: SortOrder } | { field: "mediaData"; value: ExifDataOrder } | { field: "ffmpegData"; value: FfmpegDataOrder }
 
 export type ObjectSearchArgs = { take: number; orderAndPagination?: OrderAndPagination<number, ObjectOrder, ObjectCursor> | null; filters?: SearchFilterArgs[] }
 
 export type ObjectValidatorArgs = { id: number; path: string }
 
-export type ObjectWithFilePaths = { id: number; pub_id: number[]; kind: number | null; key_id: number | null; hidden: boolean | null; favorite: boolean | null; important: boolean | null; note: string | null; date_created: string | null; date_accessed: string | null; file_paths: ({ id: number; pub_id: number[]; is_dir: boolean | null; cas_id: string | null; integrity_checksum: string | null; location_id: number | null; materialized_path: string | null; name: string | null; extension: string | null; hidden: boolean | null; size_in_bytes: string | null; size_in_bytes_bytes: number[] | null; inode: number[] | null; object_id: number | null; object: { id: number; pub_id: number[]; kind: number | null; key_id: number | null; hidden: boolean | null; favorite: boolean | null; important: boolean | null; note: string | null; date_created: string | null; date_accessed: string | null; exif_data: { resolution: number[] | null; media_date: number[] | null; media_location: number[] | null; camera_data: number[] | null; artist: string | null; description: string | null; copyright: string | null; exif_version: string | null } | null; ffmpeg_data: { id: number; formats: string; bit_rate: number[]; duration: number[] | null; start_time: number[] | null; chapters: FfmpegMediaChapter[]; programs: ({ program_id: number; streams: ({ stream_id: number; name: string | null; codec: { id: number; kind: string | null; sub_kind: string | null; tag: string | null; name: string | null; profile: string | null; bit_rate: number; video_props: FfmpegMediaVideoProps | null; audio_props: FfmpegMediaAudioProps | null; stream_id: number; program_id: number; ffmpeg_data_id: number } | null; aspect_ratio_num: number; aspect_ratio_den: number; frames_per_second_num: number; frames_per_second_den: number; time_base_real_den: number; time_base_real_num: number; dispositions: string | null; title: string | null; encoder: string | null; language: string | null; duration: number[] | null; metadata: number[] | null; program_id: number; ffmpeg_data_id: number })[]; name: string | null; metadata: number[] | null; ffmpeg_data_id: number })[]; title: string | null; creation_time: string | null; date: string | null; album_artist: string | null; disc: string | null; track: string | null; album: string | null; artist: string | null; metadata: number[] | null; object_id: number } | null } | null; key_id: number | null; date_created: string | null; date_modified: string | null; date_indexed: string | null })[] }
+export type ObjectWithFilePaths = { id: number; pub_id: number[]; kind: number | null; key_id: number | null; hidden: boolean | null; favorite: boolean | null; important: boolean | null; note: string | null; date_created: string | null; date_accessed: string | null; file_paths: ({ id: number; pub_id: number[]; is_dir: boolean | null; cas_id: string | null; integrity_checksum: string | null; location_id: number | null; materialized_path: string | null; name: string | null; extension: string | null; hidden: boolean | null; size_in_bytes: string | null; size_in_bytes_bytes: number[] | null; inode: number[] | null; device: number[] | null; nlink: number | null; symlink_target: string | null; object_id: number | null; object: { id: number; pub_id: number[]; kind: number | null; key_id: number | null; hidden: boolean | null; favorite: boolean | null; important: boolean | null; note: string | null; date_created: string | null; date_accessed: string | null; exif_data: { resolution: number[] | null; media_date: number[] | null; media_location: number[] | null; camera_data: number[] | null; artist: string | null; description: string | null; copyright: string | null; exif_version: string | null } | null; ffmpeg_data: { id: number; formats: string; bit_rate: number[]; duration: number[] | null; start_time: number[] | null; chapters: FfmpegMediaChapter[]; programs: ({ program_id: number; streams: ({ stream_id: number; name: string | null; codec: { id: number; kind: string | null; sub_kind: string | null; tag: string | null; name: string | null; profile: string | null; bit_rate: number; video_props: FfmpegMediaVideoProps | null; audio_props: FfmpegMediaAudioProps | null; stream_id: number; program_id: number; ffmpeg_data_id: number } | null; aspect_ratio_num: number; aspect_ratio_den: number; frames_per_second_num: number; frames_per_second_den: number; time_base_real_den: number; time_base_real_num: number; dispositions: string | null; title: string | null; encoder: string | null; language: string | null; duration: number[] | null; metadata: number[] | null; program_id: number; ffmpeg_data_id: number })[]; name: string | null; metadata: number[] | null; ffmpeg_data_id: number })[]; title: string | null; creation_time: string | null; date: string | null; album_artist: string | null; disc: string | null; track: string | null; album: string | null; artist: string | null; metadata: number[] | null; object_id: number } | null } | null; key_id: number | null; permissions: number | null; uid: number | null; gid: number | null; owner_name: string | null; group_name: string | null; xattrs: string | null; date_created: string | null; date_modified: string | null; date_indexed: string | null })[] }
 
-export type ObjectWithFilePaths2 = { id: number; pub_id: number[]; kind: number | null; key_id: number | null; hidden: boolean | null; favorite: boolean | null; important: boolean | null; note: string | null; date_created: string | null; date_accessed: string | null; file_paths: ({ id: number; pub_id: number[]; is_dir: boolean | null; cas_id: string | null; integrity_checksum: string | null; location_id: number | null; materialized_path: string | null; name: string | null; extension: string | null; hidden: boolean | null; size_in_bytes: string | null; size_in_bytes_bytes: number[] | null; inode: number[] | null; object_id: number | null; object: { id: number; pub_id: number[]; kind: number | null; key_id: number | null; hidden: boolean | null; favorite: boolean | null; important: boolean | null; note: string | null; date_created: string | null; date_accessed: string | null; exif_data: { resolution: number[] | null; media_date: number[] | null; media_location: number[] | null; camera_data: number[] | null; artist: string | null; description: string | null; copyright: string | null; exif_version: string | null } | null; ffmpeg_data: { id: number; formats: string; bit_rate: number[]; duration: number[] | null; start_time: number[] | null; chapters: FfmpegMediaChapter[]; programs: ({ program_id: number; streams: ({ stream_id: number; name: string | null; codec: { id: number; kind: string | null; sub_kind: string | null; tag: string | null; name: string | null; profile: string | null; bit_rate: number; video_props: FfmpegMediaVideoProps | null; audio_props: FfmpegMediaAudioProps | null; stream_id: number; program_id: number; ffmpeg_data_id: number } | null; aspect_ratio_num: number; aspect_ratio_den: number; frames_per_second_num: number; frames_per_second_den: number; time_base_real_den: number; time_base_real_num: number; dispositions: string | null; title: string | null; encoder: string | null; language: string | null; duration: number[] | null; metadata: number[] | null; program_id: number; ffmpeg_data_id: number })[]; name: string | null; metadata: number[] | null; ffmpeg_data_id: number })[]; title: string | null; creation_time: string | null; date: string | null; album_artist: string | null; disc: string | null; track: string | null; album: string | null; artist: string | null; metadata: number[] | null; object_id: number } | null } | null; key_id: number | null; date_created: string | null; date_modified: string | null; date_indexed: string | null })[] }
+export type ObjectWithFilePaths2 = { id: number; pub_id: number[]; kind: number | null; key_id: number | null; hidden: boolean | null; favorite: boolean | null; important: boolean | null; note: string | null; date_created: string | null; date_accessed: string | null; file_paths: ({ id: number; pub_id: number[]; is_dir: boolean | null; cas_id: string | null; integrity_checksum: string | null; location_id: number | null; materialized_path: string | null; name: string | null; extension: string | null; hidden: boolean | null; size_in_bytes: string | null; size_in_bytes_bytes: number[] | null; inode: number[] | null; device: number[] | null; nlink: number | null; symlink_target: string | null; object_id: number | null; object: { id: number; pub_id: number[]; kind: number | null; key_id: number | null; hidden: boolean | null; favorite: boolean | null; important: boolean | null; note: string | null; date_created: string | null; date_accessed: string | null; exif_data: { resolution: number[] | null; media_date: number[] | null; media_location: number[] | null; camera_data: number[] | null; artist: string | null; description: string | null; copyright: string | null; exif_version: string | null } | null; ffmpeg_data: { id: number; formats: string; bit_rate: number[]; duration: number[] | null; start_time: number[] | null; chapters: FfmpegMediaChapter[]; programs: ({ program_id: number; streams: ({ stream_id: number; name: string | null; codec: { id: number; kind: string | null; sub_kind: string | null; tag: string | null; name: string | null; profile: string | null; bit_rate: number; video_props: FfmpegMediaVideoProps | null; audio_props: FfmpegMediaAudioProps | null; stream_id: number; program_id: number; ffmpeg_data_id: number } | null; aspect_ratio_num: number; aspect_ratio_den: number; frames_per_second_num: number; frames_per_second_den: number; time_base_real_den: number; time_base_real_num: number; dispositions: string | null; title: string | null; encoder: string | null; language: string | null; duration: number[] | null; metadata: number[] | null; program_id: number; ffmpeg_data_id: number })[]; name: string | null; metadata: number[] | null; ffmpeg_data_id: number })[]; title: string | null; creation_time: string | null; date: string | null; album_artist: string | null; disc: string | null; track: string | null; album: string | null; artist: string | null; metadata: number[] | null; object_id: number } | null } | null; key_id: number | null; permissions: number | null; uid: number | null; gid: number | null; owner_name: string | null; group_name: string | null; xattrs: string | null; date_created: string | null; date_modified: string | null; date_indexed: string | null })[] }
 
 export type OldFileCopierJobInit = { source_location_id: number; target_location_id: number; sources_file_path_ids: number[]; target_location_relative_directory_path: string }
 
@@ -559,18 +750,58 @@ export type Orientation = "Normal" | "CW90" | "CW180" | "CW270" | "MirroredVerti
 
 export type P2PDiscoveryState = "Everyone" | "ContactsOnly" | "Disabled"
 
-export type P2PEvent = { type: "PeerChange"; identity: RemoteIdentity; connection: ConnectionMethod; discovery: DiscoveryMethod; metadata: PeerMetadata; addrs: string[] } | { type: "PeerDelete"; identity: RemoteIdentity } | { type: "SpacedropRequest"; id: string; identity: RemoteIdentity; peer_name: string; files: string[] } | { type: "SpacedropProgress"; id: string; percent: number } | { type: "SpacedropTimedOut"; id: string } | { type: "SpacedropRejected"; id: string }
+export type P2PEvent = { type: "PeerChange"; identity: RemoteIdentity; connection: ConnectionMethod; discovery: DiscoveryMethod; metadata: PeerMetadata; addrs: string[] } | { type: "PeerDelete"; identity: RemoteIdentity } | { type: "SpacedropRequest"; id: string; identity: RemoteIdentity; peer_name: string; files: string[] } | { type: "SpacedropProgress"; id: string; identity: RemoteIdentity; percent: number } | { type: "SpacedropTimedOut"; id: string } | { type: "SpacedropRejected"; id: string } | { type: "PeerPaired"; identity: RemoteIdentity; name: string }
 
 export type PeerMetadata = { name: string; operating_system: OperatingSystem | null; device_model: HardwareModel | null; version: string | null }
 
+/**
+ * A snapshot of the connection diagnostics for a peer.
+ */
+export type PeerStats = { identity: RemoteIdentity; connection: ConnectionMethod; 
+/**
+ * The round trip time of the last successful ping
+ */
+rttMs: number | null; activeStreams: number; bytesSent: [number, number]; bytesReceived: [number, number]; 
+/**
+ * Traffic broken down by protocol. Eg. `spacedrop`, `sync`, `rspc`
+ */
+protocols: { [key in string]: ProtocolStats }; 
+/**
+ * The last error which occurred opening a stream to the peer
+ */
+lastError: string | null }
+
+/**
+ * Name of the place some coordinates belong to
+ */
+export type PlaceName = { city: string; region: string | null; 
+/**
+ * The country name when the dataset has it, otherwise its ISO 3166 code
+ */
+country: string }
+
 export type PlusCode = string
 
 export type Port = { type: "random" } | { type: "discrete"; value: number }
 
+export type PrefetchRemoteThumbnailsArgs = { identity: RemoteIdentity; library_id: string; cas_ids: string[] }
+
+export type PreviewedEntry = { path: string; is_dir: boolean; 
+/**
+ * Kind of the rule that decided this entry, `None` if no rule applied to it or if it was only
+ * accepted for being an ancestor of an accepted entry
+ */
+decided_by: RuleKind | null }
+
 export type Program = { id: number; name: string | null; streams: Stream[]; metadata: Metadata }
 
 export type Props = { Video: VideoProps } | { Audio: AudioProps } | { Subtitle: SubtitleProps }
 
+/**
+ * Traffic for a single protocol with a peer.
+ */
+export type ProtocolStats = { activeStreams: number; bytesSent: [number, number]; bytesReceived: [number, number] }
+
 export type Range<T> = { from: T } | { to: T }
 
 export type RemoteIdentity = string
@@ -583,6 +814,8 @@ export type RenameMany = { from_pattern: FromPattern; to_pattern: string; from_f
 
 export type RenameOne = { from_file_path_id: number; to: string }
 
+export type RenameTrustedPeerArgs = { identity: RemoteIdentity; name: string }
+
 export type Report = { id: string; name: JobName; action: string | null; metadata: ReportMetadata[]; critical_error: string | null; non_critical_errors: NonCriticalError[]; created_at: string | null; started_at: string | null; completed_at: string | null; parent_id: string | null; status: Status; task_count: number; completed_task_count: number; info: string; phase: string; message: string; estimated_completion: string }
 
 export type ReportInputMetadata = { type: "location"; data: Location } | { type: "sub_path"; data: string }
@@ -597,7 +830,9 @@ export type Resolution = { width: number; height: number }
 
 export type Response = { Start: { user_code: string; verification_url: string; verification_url_complete: string } } | "Complete" | { Error: string }
 
-export type RuleKind = "AcceptFilesByGlob" | "RejectFilesByGlob" | "AcceptIfChildrenDirectoriesArePresent" | "RejectIfChildrenDirectoriesArePresent" | "IgnoredByGit"
+export type RuleKind = "AcceptFilesByGlob" | "RejectFilesByGlob" | "AcceptIfChildrenDirectoriesArePresent" | "RejectIfChildrenDirectoriesArePresent" | "IgnoredByGit" | "RejectFilesSmallerThan" | "RejectFilesLargerThan" | "RejectFilesModifiedBefore" | "RejectFilesModifiedAfter" | "RejectFilesByXattr" | "AcceptFilesByKind" | "RejectFilesByKind" | "IgnoredByIgnoreFiles"
+
+export type RunningTaskStats = { task_id: string; priority: TaskPriority; elapsed_ms: number }
 
 export type SavedSearch = { id: number; pub_id: number[]; target: string | null; search: string | null; filters: string | null; name: string | null; icon: string | null; description: string | null; date_created: string | null; date_modified: string | null }
 
@@ -611,6 +846,14 @@ export type SetFavoriteArgs = { id: number; favorite: boolean }
 
 export type SetNoteArgs = { id: number; note: string | null }
 
+export type SimilarGroups = { groups: ExplorerItem[][]; cursor: number | null }
+
+export type SimilarObject = { 
+/**
+ * Hamming distance between the perceptual hashes, 0 for visually identical images
+ */
+distance: number; item: ExplorerItem }
+
 export type SingleInvalidateOperationEvent = { 
 /**
  * This fields are intentionally private.
@@ -621,6 +864,10 @@ export type SortOrder = "Asc" | "Desc"
 
 export type SpacedropArgs = { identity: RemoteIdentity; file_path: string[] }
 
+export type SpacedropManyArgs = { identities: RemoteIdentity[]; paths: string[] }
+
+export type SpacedropStarted = { type: "Started"; identity: RemoteIdentity; id: string } | { type: "Failed"; identity: RemoteIdentity; error: string }
+
 export type Statistics = { id: number; date_captured: string; total_object_count: number; library_db_size: string; total_local_bytes_used: string; total_local_bytes_capacity: string; total_local_bytes_free: string; total_library_bytes: string; total_library_unique_bytes: string; total_library_preview_media_bytes: string }
 
 export type StatisticsResponse = { statistics: Statistics | null }
@@ -631,6 +878,13 @@ export type Stream = { id: number; name: string | null; codec: Codec | null; asp
 
 export type SubtitleProps = { width: number; height: number }
 
+export type SuspendedTaskStats = { task_id: string; priority: TaskPriority; suspended_for_ms: number }
+
+/**
+ * How symbolic links found inside a location are handled by the indexer and the watcher
+ */
+export type SymlinkPolicy = "Ignore" | "Alias" | "Follow"
+
 export type SyncStatus = { ingest: boolean; cloud_send: boolean; cloud_receive: boolean; cloud_ingest: boolean }
 
 export type SystemLocations = { desktop: string | null; documents: string | null; downloads: string | null; pictures: string | null; music: string | null; videos: string | null }
@@ -645,16 +899,71 @@ export type TagUpdateArgs = { id: number; name: string | null; color: string | n
 
 export type Target = { Object: number } | { FilePath: number }
 
+export type TaskPriority = "Background" | "Normal" | "Interactive"
+
+export type TaskSystemStats = { workers: TaskSystemWorkerStats[] }
+
+export type TaskSystemWorkerStats = { worker_id: number; is_idle: boolean; is_retiring: boolean; queued_interactive_tasks: number; queued_normal_tasks: number; queued_background_tasks: number; paused_tasks_count: number; running_task: RunningTaskStats | null; suspended_task: SuspendedTaskStats | null; stolen_tasks_count: number; tasks_stolen_by_others_count: number }
+
 export type TextMatch = { contains: string } | { startsWith: string } | { endsWith: string } | { equals: string }
 
 /**
  * This type is used to pass the relevant data to the frontend so it can request the thumbnail.
  * Tt supports extending the shard hex to support deeper directory structures in the future
  */
-export type ThumbKey = { shard_hex: string; cas_id: CasId; base_directory_str: string }
+export type ThumbKey = { shard_hex: string; cas_id: CasId; base_directory_str: string; size: ThumbSize }
 
-export type UpdateThumbnailerPreferences = Record<string, never>
+/**
+ * Sizes we serve thumbnails in, `ThumbSize::Medium` is the one generated by the media
+ * processor while the others are only generated on their first request.
+ */
+export type ThumbSize = "Small" | "Medium" | "Large"
+
+export type ThumbnailerPreferences = { 
+/**
+ * Extra previews generated for videos, they are disabled by default as they take much longer
+ * to generate than thumbnails.
+ */
+video_previews?: VideoPreviewsOptions }
+
+export type TrustedPeer = { 
+/**
+ * The display name of the peer. Defaults to the node name it provided while pairing but can be renamed by the user.
+ */
+name: string; 
+/**
+ * The addresses the peer was reachable on when it was paired. These are dialled the same as `manual_peers`.
+ */
+addrs?: string[]; paired_at: string }
+
+export type VideoPreviewKind = "Thumbstrip" | "PreviewClip"
+
+/**
+ * Extra previews generated for indexed videos, besides their thumbnail.
+ */
+export type VideoPreviewsOptions = { 
+/**
+ * A sprite sheet with evenly spaced frames, to scrub through the video
+ */
+thumbstrip: boolean; 
+/**
+ * A short animated clip without audio, to be played on hover
+ */
+preview_clip: boolean }
 
 export type VideoProps = { pixel_format: string | null; color_range: string | null; bits_per_channel: number | null; color_space: string | null; color_primaries: string | null; color_transfer: string | null; field_order: string | null; chroma_location: string | null; width: number; height: number; aspect_ratio_num: number | null; aspect_ratio_den: number | null; properties: string[] }
 
 export type Volume = { name: string; mount_points: string[]; total_capacity: string; available_capacity: string; disk_type: DiskType; file_system: string | null; is_root_filesystem: boolean }
+
+/**
+ * Downsampled amplitude of an audio file, mixed down to mono, with values scaled to `[0, 255]`
+ */
+export type Waveform = { 
+/**
+ * Highest absolute sample value of each bucket
+ */
+peaks: number[]; 
+/**
+ * Root mean square of the samples of each bucket, closer to how loud it sounds
+ */
+rms: number[] }